@@ -5,7 +5,7 @@ use std::cell::RefCell;
 use std::ffi::{c_char, c_void, CStr, CString};
 use tract_api::{
     AsFact, DatumType, InferenceModelInterface, ModelInterface, NnefInterface, OnnxInterface,
-    RunnableInterface, StateInterface, ValueInterface,
+    RunnableInterface, StateInterface, TfliteInterface, ValueInterface,
 };
 use tract_rs::{State, Value};
 
@@ -186,6 +186,27 @@ pub unsafe extern "C" fn tract_nnef_model_for_path(
     })
 }
 
+/// Parse and load an NNEF model from an in-memory buffer as a tract TypedModel.
+///
+/// `data` points to `len` bytes of a tar or tar.gz archive. The buffer can be released once the
+/// function returns.
+#[no_mangle]
+pub unsafe extern "C" fn tract_nnef_model_for_bytes(
+    nnef: *const TractNnef,
+    data: *const u8,
+    len: usize,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(nnef, data, model);
+        *model = std::ptr::null_mut();
+        let bytes = std::slice::from_raw_parts(data, len);
+        let m = Box::new(TractModel((*nnef).0.model_for_bytes(bytes)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
 /// Dump a TypedModel as a NNEF tar file.
 ///
 /// `path` is a null-terminated utf-8 string pointer to the `.tar` file to be created.
@@ -282,6 +303,160 @@ pub unsafe extern "C" fn tract_onnx_model_for_path(
     })
 }
 
+/// Parse and load an ONNX model from an in-memory buffer as a tract InferenceModel.
+///
+/// `data` points to `len` bytes of an ONNX protobuf model. The buffer can be released once the
+/// function returns. External data, if any, is read through the resolver set with
+/// `tract_onnx_set_external_data_resolver`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_onnx_model_for_bytes(
+    onnx: *const TractOnnx,
+    data: *const u8,
+    len: usize,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(onnx, data, model);
+        *model = std::ptr::null_mut();
+        let bytes = std::slice::from_raw_parts(data, len);
+        let m = Box::new(TractInferenceModel((*onnx).0.model_for_bytes(bytes)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Callback providing the external data of ONNX models loaded from memory.
+///
+/// `location` is the null-terminated utf-8 location of the data as found in the model. `offset`
+/// and `length` give the requested range, `length` being -1 when the data extends to the end
+/// of the location. The callback must point `data` and `data_len` to the requested bytes and
+/// return `TRACT_RESULT_OK`. The bytes are copied before the callback is called again.
+pub type TractExternalDataResolver = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    location: *const c_char,
+    offset: usize,
+    length: i64,
+    data: *mut *const u8,
+    data_len: *mut usize,
+) -> TRACT_RESULT;
+
+/// Set the callback used to read external data of models loaded with
+/// `tract_onnx_model_for_bytes`. Models loaded with `tract_onnx_model_for_path` keep reading
+/// external data from the filesystem.
+///
+/// `user_data` is passed as is to the callback, and must stay valid as long as `onnx` is used to
+/// load models.
+#[no_mangle]
+pub unsafe extern "C" fn tract_onnx_set_external_data_resolver(
+    onnx: *mut TractOnnx,
+    resolver: TractExternalDataResolver,
+    user_data: *mut c_void,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(onnx);
+        let user_data = user_data as usize;
+        (*onnx).0.set_external_data_resolver(move |location, offset, length| {
+            let c_location = CString::new(location)?;
+            let mut data: *const u8 = std::ptr::null();
+            let mut data_len = 0usize;
+            let result = resolver(
+                user_data as *mut c_void,
+                c_location.as_ptr(),
+                offset,
+                length.map(|l| l as i64).unwrap_or(-1),
+                &mut data,
+                &mut data_len,
+            );
+            anyhow::ensure!(
+                result == TRACT_RESULT::TRACT_RESULT_OK && !data.is_null(),
+                "External data resolver failed for {location:?}"
+            );
+            Ok(std::slice::from_raw_parts(data, data_len).to_vec())
+        })
+    })
+}
+
+// TFLITE
+pub struct TractTflite(tract_rs::Tflite);
+
+/// Creates an instance of a TFLite framework that can be used to load and dump TFLite models.
+///
+/// The returned object should be destroyed with `tract_tflite_destroy` once the model
+/// has been loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_create(tflite: *mut *mut TractTflite) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite);
+        *tflite = Box::into_raw(Box::new(TractTflite(tract_rs::tflite()?)));
+        Ok(())
+    })
+}
+
+/// Destroy the TFLite framework. It is safe to detroy it once the model had been loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_destroy(tflite: *mut *mut TractTflite) -> TRACT_RESULT {
+    release!(tflite)
+}
+
+/// Parse and load a TFLite model as a tract TypedModel.
+///
+/// `path` is a null-terminated utf-8 string pointer. It must point to a `.tflite` model file.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_model_for_path(
+    tflite: *const TractTflite,
+    path: *const c_char,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, path, model);
+        *model = std::ptr::null_mut();
+        let path = CStr::from_ptr(path).to_str()?;
+        let m = Box::new(TractModel(
+            (*tflite).0.model_for_path(path).with_context(|| format!("opening file {path:?}"))?,
+        ));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Parse and load a TFLite model from an in-memory buffer as a tract TypedModel.
+///
+/// `data` points to `len` bytes of a TFLite flatbuffer. The buffer can be released once the
+/// function returns.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_model_for_bytes(
+    tflite: *const TractTflite,
+    data: *const u8,
+    len: usize,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, data, model);
+        *model = std::ptr::null_mut();
+        let bytes = std::slice::from_raw_parts(data, len);
+        let m = Box::new(TractModel((*tflite).0.model_for_bytes(bytes)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Dump a TypedModel as a TFLite file.
+///
+/// `path` is a null-terminated utf-8 string pointer to the `.tflite` file to be created.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_write_model_to_path(
+    tflite: *const TractTflite,
+    path: *const c_char,
+    model: *const TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, model, path);
+        let path = CStr::from_ptr(path).to_str()?;
+        (*tflite).0.write_model_to_path(path, &(*model).0)?;
+        Ok(())
+    })
+}
+
 // INFERENCE MODEL
 pub struct TractInferenceModel(tract_rs::InferenceModel);
 
@@ -752,6 +927,27 @@ pub unsafe extern "C" fn tract_model_into_runnable(
     })
 }
 
+/// Convert a TypedModel into a TypedRunnableModel evaluating independent nodes concurrently on
+/// a pool of `threads` threads.
+///
+/// This function transfers ownership of the `model` argument to the newly-created `runnable` model.
+///
+/// Runnable are reference counted. When done, it should be released with `tract_runnable_release`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_into_parallel_runnable(
+    model: *mut *mut TractModel,
+    threads: usize,
+    runnable: *mut *mut TractRunnable,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(model, runnable);
+        let m = Box::from_raw(*model).0;
+        *model = std::ptr::null_mut();
+        *runnable = Box::into_raw(Box::new(TractRunnable(m.into_parallel_runnable(threads)?))) as _;
+        Ok(())
+    })
+}
+
 /// Query the number of properties in a model.
 #[no_mangle]
 pub unsafe extern "C" fn tract_model_property_count(
@@ -1006,6 +1202,27 @@ pub unsafe extern "C" fn tract_state_output_count(
     })
 }
 
+/// Empty all key/value caches of a State, keeping their allocations.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_reset_kv_caches(state: *mut TractState) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(state);
+        (*state).0.reset_kv_caches()
+    })
+}
+
+/// Keep only the first `len` positions in all key/value caches of a State.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_truncate_kv_caches(
+    state: *mut TractState,
+    len: usize,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(state);
+        (*state).0.truncate_kv_caches(len)
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn tract_state_destroy(state: *mut *mut TractState) -> TRACT_RESULT {
     release!(state)
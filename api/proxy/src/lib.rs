@@ -1,6 +1,7 @@
-use std::ffi::{CStr, CString};
+use std::ffi::{c_char, c_void, CStr, CString};
 use std::path::Path;
 use std::ptr::{null, null_mut};
+use std::sync::{Arc, Mutex};
 
 use tract_api::*;
 use tract_proxy_sys as sys;
@@ -45,7 +46,13 @@ pub fn nnef() -> Result<Nnef> {
 pub fn onnx() -> Result<Onnx> {
     let mut onnx = null_mut();
     check!(sys::tract_onnx_create(&mut onnx))?;
-    Ok(Onnx(onnx))
+    Ok(Onnx(onnx, None))
+}
+
+pub fn tflite() -> Result<Tflite> {
+    let mut tflite = null_mut();
+    check!(sys::tract_tflite_create(&mut tflite))?;
+    Ok(Tflite(tflite))
 }
 
 pub fn version() -> &'static str {
@@ -65,6 +72,12 @@ impl NnefInterface for Nnef {
         Ok(Model(model))
     }
 
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Model> {
+        let mut model = null_mut();
+        check!(sys::tract_nnef_model_for_bytes(self.0, bytes.as_ptr(), bytes.len(), &mut model))?;
+        Ok(Model(model))
+    }
+
     fn enable_tract_core(&mut self) -> Result<()> {
         check!(sys::tract_nnef_enable_tract_core(self.0))
     }
@@ -114,7 +127,45 @@ impl NnefInterface for Nnef {
 }
 
 // ONNX
-wrapper!(Onnx, TractOnnx, tract_onnx_destroy);
+wrapper!(Onnx, TractOnnx, tract_onnx_destroy, Option<Arc<ExternalDataResolver>>);
+
+type ExternalDataResolverFn = dyn Fn(&str, usize, Option<usize>) -> Result<Vec<u8>> + Send + Sync;
+
+/// Keeps the user resolver alive as long as the Onnx object, and the last bytes it produced
+/// until tract has copied them.
+pub struct ExternalDataResolver {
+    resolver: Box<ExternalDataResolverFn>,
+    buffer: Mutex<Vec<u8>>,
+}
+
+impl std::fmt::Debug for ExternalDataResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExternalDataResolver")
+    }
+}
+
+unsafe extern "C" fn external_data_resolver(
+    user_data: *mut c_void,
+    location: *const c_char,
+    offset: usize,
+    length: i64,
+    data: *mut *const u8,
+    data_len: *mut usize,
+) -> sys::TRACT_RESULT {
+    let state = &*(user_data as *const ExternalDataResolver);
+    let location = CStr::from_ptr(location).to_string_lossy();
+    let length = if length < 0 { None } else { Some(length as usize) };
+    match (state.resolver)(&location, offset, length) {
+        Ok(bytes) => {
+            let mut buffer = state.buffer.lock().unwrap();
+            *buffer = bytes;
+            *data = buffer.as_ptr();
+            *data_len = buffer.len();
+            sys::TRACT_RESULT_TRACT_RESULT_OK
+        }
+        Err(_) => sys::TRACT_RESULT_TRACT_RESULT_KO,
+    }
+}
 
 impl OnnxInterface for Onnx {
     type InferenceModel = InferenceModel;
@@ -127,6 +178,60 @@ impl OnnxInterface for Onnx {
         check!(sys::tract_onnx_model_for_path(self.0, path.as_ptr(), &mut model))?;
         Ok(InferenceModel(model))
     }
+
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<InferenceModel> {
+        let mut model = null_mut();
+        check!(sys::tract_onnx_model_for_bytes(self.0, bytes.as_ptr(), bytes.len(), &mut model))?;
+        Ok(InferenceModel(model))
+    }
+
+    fn set_external_data_resolver<F>(&mut self, resolver: F) -> Result<()>
+    where
+        F: Fn(&str, usize, Option<usize>) -> Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        let state = Arc::new(ExternalDataResolver {
+            resolver: Box::new(resolver),
+            buffer: Mutex::default(),
+        });
+        check!(sys::tract_onnx_set_external_data_resolver(
+            self.0,
+            Some(external_data_resolver),
+            Arc::as_ptr(&state) as *mut c_void
+        ))?;
+        self.1 = Some(state);
+        Ok(())
+    }
+}
+
+// TFLITE
+wrapper!(Tflite, TractTflite, tract_tflite_destroy);
+
+impl TfliteInterface for Tflite {
+    type Model = Model;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
+        let path = path.as_ref();
+        let path = CString::new(
+            path.to_str().with_context(|| format!("Failed to re-encode {path:?} to uff-8"))?,
+        )?;
+        let mut model = null_mut();
+        check!(sys::tract_tflite_model_for_path(self.0, path.as_ptr(), &mut model))?;
+        Ok(Model(model))
+    }
+
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Model> {
+        let mut model = null_mut();
+        check!(sys::tract_tflite_model_for_bytes(self.0, bytes.as_ptr(), bytes.len(), &mut model))?;
+        Ok(Model(model))
+    }
+
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Model) -> Result<()> {
+        let path = path.as_ref();
+        let path = CString::new(
+            path.to_str().with_context(|| format!("Failed to re-encode {path:?} to uff-8"))?,
+        )?;
+        check!(sys::tract_tflite_write_model_to_path(self.0, path.as_ptr(), model.0))?;
+        Ok(())
+    }
 }
 
 // INFERENCE MODEL
@@ -320,6 +425,13 @@ impl ModelInterface for Model {
         Ok(Runnable(runnable))
     }
 
+    fn into_parallel_runnable(self, threads: usize) -> Result<Runnable> {
+        let mut model = self;
+        let mut runnable = null_mut();
+        check!(sys::tract_model_into_parallel_runnable(&mut model.0, threads, &mut runnable))?;
+        Ok(Runnable(runnable))
+    }
+
     fn concretize_symbols(
         &mut self,
         values: impl IntoIterator<Item = (impl AsRef<str>, i64)>,
@@ -476,6 +588,14 @@ impl StateInterface for State {
         check!(sys::tract_state_output_count(self.0, &mut count))?;
         Ok(count)
     }
+
+    fn reset_kv_caches(&mut self) -> Result<()> {
+        check!(sys::tract_state_reset_kv_caches(self.0))
+    }
+
+    fn truncate_kv_caches(&mut self, len: usize) -> Result<()> {
+        check!(sys::tract_state_truncate_kv_caches(self.0, len))
+    }
 }
 
 // VALUE
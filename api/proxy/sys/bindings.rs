@@ -1957,9 +1957,25 @@ pub struct TractState {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct TractTflite {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct TractValue {
     _unused: [u8; 0],
 }
+#[doc = " Callback providing the external data of ONNX models loaded from memory.\n\n `location` is the null-terminated utf-8 location of the data as found in the model. `offset`\n and `length` give the requested range, `length` being -1 when the data extends to the end\n of the location. The callback must point `data` and `data_len` to the requested bytes and\n return `TRACT_RESULT_OK`. The bytes are copied before the callback is called again."]
+pub type TractExternalDataResolver = ::std::option::Option<
+    unsafe extern "C" fn(
+        user_data: *mut ::std::os::raw::c_void,
+        location: *const ::std::os::raw::c_char,
+        offset: usize,
+        length: i64,
+        data: *mut *const u8,
+        data_len: *mut usize,
+    ) -> TRACT_RESULT,
+>;
 extern "C" {
     #[doc = " Retrieve the last error that happened in this thread. A function encountered an error if\n its return type is of type `TRACT_RESULT` and it returned `TRACT_RESULT_KO`.\n\n # Return value\n  It returns a pointer to a null-terminated UTF-8 string that will contain the error description.\n  Rust side keeps ownership of the buffer. It will be valid as long as no other tract calls is\n  performed by the thread.\n  If no error occured, null is returned."]
     pub fn tract_get_last_error() -> *const ::std::os::raw::c_char;
@@ -2003,6 +2019,15 @@ extern "C" {
         model: *mut *mut TractModel,
     ) -> TRACT_RESULT;
 }
+extern "C" {
+    #[doc = " Parse and load an NNEF model from an in-memory buffer as a tract TypedModel.\n\n `data` points to `len` bytes of a tar or tar.gz archive. The buffer can be released once the\n function returns."]
+    pub fn tract_nnef_model_for_bytes(
+        nnef: *const TractNnef,
+        data: *const u8,
+        len: usize,
+        model: *mut *mut TractModel,
+    ) -> TRACT_RESULT;
+}
 extern "C" {
     #[doc = " Dump a TypedModel as a NNEF tar file.\n\n `path` is a null-terminated utf-8 string pointer to the `.tar` file to be created.\n\n This function creates a plain, non-compressed, archive."]
     pub fn tract_nnef_write_model_to_tar(
@@ -2043,6 +2068,56 @@ extern "C" {
         model: *mut *mut TractInferenceModel,
     ) -> TRACT_RESULT;
 }
+extern "C" {
+    #[doc = " Parse and load an ONNX model from an in-memory buffer as a tract InferenceModel.\n\n `data` points to `len` bytes of an ONNX protobuf model. The buffer can be released once the\n function returns. External data, if any, is read through the resolver set with\n `tract_onnx_set_external_data_resolver`."]
+    pub fn tract_onnx_model_for_bytes(
+        onnx: *const TractOnnx,
+        data: *const u8,
+        len: usize,
+        model: *mut *mut TractInferenceModel,
+    ) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Set the callback used to read external data of models loaded with\n `tract_onnx_model_for_bytes`.\n\n `user_data` is passed as is to the callback, and must stay valid as long as `onnx` is used to\n load models."]
+    pub fn tract_onnx_set_external_data_resolver(
+        onnx: *mut TractOnnx,
+        resolver: TractExternalDataResolver,
+        user_data: *mut ::std::os::raw::c_void,
+    ) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Creates an instance of a TFLite framework that can be used to load and dump TFLite models.\n\n The returned object should be destroyed with `tract_tflite_destroy` once the model\n has been loaded."]
+    pub fn tract_tflite_create(tflite: *mut *mut TractTflite) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Destroy the TFLite framework. It is safe to detroy it once the model had been loaded."]
+    pub fn tract_tflite_destroy(tflite: *mut *mut TractTflite) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Parse and load a TFLite model as a tract TypedModel.\n\n `path` is a null-terminated utf-8 string pointer. It must point to a `.tflite` model file."]
+    pub fn tract_tflite_model_for_path(
+        tflite: *const TractTflite,
+        path: *const ::std::os::raw::c_char,
+        model: *mut *mut TractModel,
+    ) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Parse and load a TFLite model from an in-memory buffer as a tract TypedModel.\n\n `data` points to `len` bytes of a TFLite flatbuffer. The buffer can be released once the\n function returns."]
+    pub fn tract_tflite_model_for_bytes(
+        tflite: *const TractTflite,
+        data: *const u8,
+        len: usize,
+        model: *mut *mut TractModel,
+    ) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Dump a TypedModel as a TFLite file.\n\n `path` is a null-terminated utf-8 string pointer to the `.tflite` file to be created."]
+    pub fn tract_tflite_write_model_to_path(
+        tflite: *const TractTflite,
+        path: *const ::std::os::raw::c_char,
+        model: *const TractModel,
+    ) -> TRACT_RESULT;
+}
 extern "C" {
     #[doc = " Query an InferenceModel input counts."]
     pub fn tract_inference_model_input_count(
@@ -2226,6 +2301,14 @@ extern "C" {
         runnable: *mut *mut TractRunnable,
     ) -> TRACT_RESULT;
 }
+extern "C" {
+    #[doc = " Convert a TypedModel into a TypedRunnableModel evaluating independent nodes concurrently on\n a pool of `threads` threads.\n\n This function transfers ownership of the `model` argument to the newly-created `runnable` model.\n\n Runnable are reference counted. When done, it should be released with `tract_runnable_release`."]
+    pub fn tract_model_into_parallel_runnable(
+        model: *mut *mut TractModel,
+        threads: usize,
+        runnable: *mut *mut TractRunnable,
+    ) -> TRACT_RESULT;
+}
 extern "C" {
     #[doc = " Query the number of properties in a model."]
     pub fn tract_model_property_count(model: *const TractModel, count: *mut usize) -> TRACT_RESULT;
@@ -2321,6 +2404,14 @@ extern "C" {
     #[doc = " Query an State output counts."]
     pub fn tract_state_output_count(state: *const TractState, outputs: *mut usize) -> TRACT_RESULT;
 }
+extern "C" {
+    #[doc = " Empty all key/value caches of a State, keeping their allocations."]
+    pub fn tract_state_reset_kv_caches(state: *mut TractState) -> TRACT_RESULT;
+}
+extern "C" {
+    #[doc = " Keep only the first `len` positions in all key/value caches of a State."]
+    pub fn tract_state_truncate_kv_caches(state: *mut TractState, len: usize) -> TRACT_RESULT;
+}
 extern "C" {
     pub fn tract_state_destroy(state: *mut *mut TractState) -> TRACT_RESULT;
 }
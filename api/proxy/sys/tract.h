@@ -56,8 +56,25 @@ typedef struct TractRunnable TractRunnable;
 
 typedef struct TractState TractState;
 
+typedef struct TractTflite TractTflite;
+
 typedef struct TractValue TractValue;
 
+/**
+ * Callback providing the external data of ONNX models loaded from memory.
+ *
+ * `location` is the null-terminated utf-8 location of the data as found in the model. `offset`
+ * and `length` give the requested range, `length` being -1 when the data extends to the end
+ * of the location. The callback must point `data` and `data_len` to the requested bytes and
+ * return `TRACT_RESULT_OK`. The bytes are copied before the callback is called again.
+ */
+typedef enum TRACT_RESULT (*TractExternalDataResolver)(void *user_data,
+                                                       const char *location,
+                                                       uintptr_t offset,
+                                                       int64_t length,
+                                                       const uint8_t **data,
+                                                       uintptr_t *data_len);
+
 /**
  * Retrieve the last error that happened in this thread. A function encountered an error if
  * its return type is of type `TRACT_RESULT` and it returned `TRACT_RESULT_KO`.
@@ -115,6 +132,17 @@ enum TRACT_RESULT tract_nnef_model_for_path(const struct TractNnef *nnef,
                                             const char *path,
                                             struct TractModel **model);
 
+/**
+ * Parse and load an NNEF model from an in-memory buffer as a tract TypedModel.
+ *
+ * `data` points to `len` bytes of a tar or tar.gz archive. The buffer can be released once the
+ * function returns.
+ */
+enum TRACT_RESULT tract_nnef_model_for_bytes(const struct TractNnef *nnef,
+                                             const uint8_t *data,
+                                             uintptr_t len,
+                                             struct TractModel **model);
+
 /**
  * Dump a TypedModel as a NNEF tar file.
  *
@@ -168,6 +196,72 @@ enum TRACT_RESULT tract_onnx_model_for_path(const struct TractOnnx *onnx,
                                             const char *path,
                                             struct TractInferenceModel **model);
 
+/**
+ * Parse and load an ONNX model from an in-memory buffer as a tract InferenceModel.
+ *
+ * `data` points to `len` bytes of an ONNX protobuf model. The buffer can be released once the
+ * function returns. External data, if any, is read through the resolver set with
+ * `tract_onnx_set_external_data_resolver`.
+ */
+enum TRACT_RESULT tract_onnx_model_for_bytes(const struct TractOnnx *onnx,
+                                             const uint8_t *data,
+                                             uintptr_t len,
+                                             struct TractInferenceModel **model);
+
+/**
+ * Set the callback used to read external data of models loaded with
+ * `tract_onnx_model_for_bytes`. Models loaded with `tract_onnx_model_for_path` keep reading
+ * external data from the filesystem.
+ *
+ * `user_data` is passed as is to the callback, and must stay valid as long as `onnx` is used to
+ * load models.
+ */
+enum TRACT_RESULT tract_onnx_set_external_data_resolver(struct TractOnnx *onnx,
+                                                        TractExternalDataResolver resolver,
+                                                        void *user_data);
+
+/**
+ * Creates an instance of a TFLite framework that can be used to load and dump TFLite models.
+ *
+ * The returned object should be destroyed with `tract_tflite_destroy` once the model
+ * has been loaded.
+ */
+enum TRACT_RESULT tract_tflite_create(struct TractTflite **tflite);
+
+/**
+ * Destroy the TFLite framework. It is safe to detroy it once the model had been loaded.
+ */
+enum TRACT_RESULT tract_tflite_destroy(struct TractTflite **tflite);
+
+/**
+ * Parse and load a TFLite model as a tract TypedModel.
+ *
+ * `path` is a null-terminated utf-8 string pointer. It must point to a `.tflite` model file.
+ */
+enum TRACT_RESULT tract_tflite_model_for_path(const struct TractTflite *tflite,
+                                              const char *path,
+                                              struct TractModel **model);
+
+/**
+ * Parse and load a TFLite model from an in-memory buffer as a tract TypedModel.
+ *
+ * `data` points to `len` bytes of a TFLite flatbuffer. The buffer can be released once the
+ * function returns.
+ */
+enum TRACT_RESULT tract_tflite_model_for_bytes(const struct TractTflite *tflite,
+                                               const uint8_t *data,
+                                               uintptr_t len,
+                                               struct TractModel **model);
+
+/**
+ * Dump a TypedModel as a TFLite file.
+ *
+ * `path` is a null-terminated utf-8 string pointer to the `.tflite` file to be created.
+ */
+enum TRACT_RESULT tract_tflite_write_model_to_path(const struct TractTflite *tflite,
+                                                   const char *path,
+                                                   const struct TractModel *model);
+
 /**
  * Query an InferenceModel input counts.
  */
@@ -381,6 +475,18 @@ enum TRACT_RESULT tract_model_profile_json(struct TractModel *model,
 enum TRACT_RESULT tract_model_into_runnable(struct TractModel **model,
                                             struct TractRunnable **runnable);
 
+/**
+ * Convert a TypedModel into a TypedRunnableModel evaluating independent nodes concurrently on
+ * a pool of `threads` threads.
+ *
+ * This function transfers ownership of the `model` argument to the newly-created `runnable` model.
+ *
+ * Runnable are reference counted. When done, it should be released with `tract_runnable_release`.
+ */
+enum TRACT_RESULT tract_model_into_parallel_runnable(struct TractModel **model,
+                                                     uintptr_t threads,
+                                                     struct TractRunnable **runnable);
+
 /**
  * Query the number of properties in a model.
  */
@@ -502,6 +608,16 @@ enum TRACT_RESULT tract_state_input_count(const struct TractState *state, uintpt
  */
 enum TRACT_RESULT tract_state_output_count(const struct TractState *state, uintptr_t *outputs);
 
+/**
+ * Empty all key/value caches of a State, keeping their allocations.
+ */
+enum TRACT_RESULT tract_state_reset_kv_caches(struct TractState *state);
+
+/**
+ * Keep only the first `len` positions in all key/value caches of a State.
+ */
+enum TRACT_RESULT tract_state_truncate_kv_caches(struct TractState *state, uintptr_t len);
+
 enum TRACT_RESULT tract_state_destroy(struct TractState **state);
 
 /**
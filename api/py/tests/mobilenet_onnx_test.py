@@ -35,6 +35,33 @@ def test_onnx():
     confidences = result[0].to_numpy()
     assert numpy.argmax(confidences) == 652
 
+def test_onnx_for_bytes():
+    with open("./mobilenetv2-7.onnx", "rb") as f:
+        model = tract.onnx().model_for_bytes(f.read()).into_optimized().into_runnable()
+    result = model.run([grace_hopper_1x3x224x244()])
+    confidences = result[0].to_numpy()
+    assert numpy.argmax(confidences) == 652
+
+def test_nnef_for_bytes_and_read():
+    nnef = tract.nnef()
+    with open("mobilenet_v2_1.0.onnx.nnef.tgz", "rb") as f:
+        model = nnef.model_for_bytes(f.read())
+    assert str(model.input_fact(0)) == "1,3,224,224,F32"
+    with open("mobilenet_v2_1.0.onnx.nnef.tgz", "rb") as f:
+        model = nnef.model_for_read(f)
+    assert str(model.input_fact(0)) == "1,3,224,224,F32"
+
+def test_parallel_runnable():
+    model = (
+        tract.onnx()
+        .model_for_path("./mobilenetv2-7.onnx")
+        .into_optimized()
+        .into_parallel_runnable(4)
+    )
+    result = model.run([grace_hopper_1x3x224x244()])
+    confidences = result[0].to_numpy()
+    assert numpy.argmax(confidences) == 652
+
 def test_state():
     model = (
         tract.onnx()
@@ -175,6 +202,21 @@ def test_typed_model_to_nnef_and_back():
         assert str(reloaded.input_fact(0)) == "B,3,224,224,F32"
         assert str(reloaded.output_fact(0)) == "B,1000,F32"
 
+def test_typed_model_to_tflite_and_back():
+    model = tract.onnx().model_for_path("./mobilenetv2-7.onnx")
+    model.set_input_fact(0, "1,3,224,224,f32")
+    typed = model.into_typed().into_decluttered()
+    with tempfile.TemporaryDirectory() as tmpdirname:
+        path = Path(tmpdirname) / "mobilenet.tflite"
+        tflite = tract.tflite()
+        tflite.write_model_to_path(typed, path)
+        reloaded = tflite.model_for_path(path).into_optimized().into_runnable()
+        result = reloaded.run([grace_hopper_1x3x224x244()])
+        confidences = result[0].to_numpy()
+        assert numpy.argmax(confidences) == 652
+        reloaded = tflite.model_for_bytes(path.read_bytes())
+        assert str(reloaded.input_fact(0)) == "1,3,224,224,F32"
+
 def test_cost():
     model = tract.nnef().model_for_path("mobilenet_v2_1.0.onnx.nnef.tgz")
     assert str(model.input_fact(0)) == "1,3,224,224,F32"
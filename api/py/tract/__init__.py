@@ -50,6 +50,7 @@ from .inference_model import InferenceModel
 from .runnable import Runnable
 from .nnef import Nnef
 from .onnx import Onnx
+from .tflite import Tflite
 
 def version() -> str:
     """Return the version string of `tract` native library"""
@@ -63,3 +64,7 @@ def onnx() -> Onnx:
     """Return a newly-created ONNX context for loading models"""
     return Onnx()
 
+def tflite() -> Tflite:
+    """Return a newly-created TFLite context for loading and saving models"""
+    return Tflite()
+
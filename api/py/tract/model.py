@@ -192,6 +192,13 @@ class Model:
         check(lib.tract_model_into_runnable(byref(self.ptr), byref(runnable)))
         return Runnable(runnable)
 
+    def into_parallel_runnable(self, threads: int) -> Runnable:
+        """Transform the model into a Runnable evaluating independent nodes concurrently on a pool of `threads` threads"""
+        self._valid()
+        runnable = c_void_p()
+        check(lib.tract_model_into_parallel_runnable(byref(self.ptr), c_size_t(threads), byref(runnable)))
+        return Runnable(runnable)
+
     def property_keys(self) -> List[str]:
         """Query the list of properties names of the model."""
         self._valid()
@@ -1,6 +1,6 @@
 from ctypes import *
 from pathlib import Path
-from typing import Dict, List, Union
+from typing import BinaryIO, Dict, List, Union
 from .bindings import check, lib
 from .model import Model
 
@@ -48,6 +48,22 @@ class Nnef:
         check(lib.tract_nnef_model_for_path(self.ptr, path, byref(model)))
         return Model(model)
 
+    def model_for_bytes(self, data: Union[bytes, bytearray, memoryview]) -> Model:
+        """
+        Load an NNEF model from an in-memory `tar` or `tar.gz` archive
+        """
+        self._valid()
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_nnef_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return Model(model)
+
+    def model_for_read(self, reader: BinaryIO) -> Model:
+        """
+        Load an NNEF model from a binary file-like object over a `tar` or `tar.gz` archive
+        """
+        return self.model_for_bytes(reader.read())
+
     def with_tract_core(self) -> "Nnef":
         """
         Enable tract-opl extensions to NNEF to covers tract-core operator set
@@ -1,9 +1,13 @@
 from ctypes import *
 from pathlib import Path
-from typing import Dict, List, Union
+from typing import BinaryIO, Callable, Dict, List, Optional, Union
 from .bindings import check, lib
 from .inference_model import InferenceModel
 
+_EXTERNAL_DATA_RESOLVER = CFUNCTYPE(
+    c_int, c_void_p, c_char_p, c_size_t, c_int64, POINTER(c_void_p), POINTER(c_size_t)
+)
+
 class Onnx:
     """
     Represent the ONNX context in tract.
@@ -43,3 +47,56 @@ class Onnx:
         path = str(path).encode("utf-8")
         check(lib.tract_onnx_model_for_path(self.ptr, path, byref(model)))
         return InferenceModel(model)
+
+    def model_for_bytes(self, data: Union[bytes, bytearray, memoryview]) -> InferenceModel:
+        """
+        Load an ONNX model from an in-memory protobuf buffer as an InferenceModel
+
+        External data, if any, is read through the resolver set with
+        `set_external_data_resolver`.
+        """
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_onnx_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return InferenceModel(model)
+
+    def model_for_read(self, reader: BinaryIO) -> InferenceModel:
+        """
+        Load an ONNX model from a binary file-like object as an InferenceModel
+        """
+        return self.model_for_bytes(reader.read())
+
+    def set_external_data_resolver(
+        self, resolver: Callable[[str, int, Optional[int]], bytes]
+    ) -> "Onnx":
+        """
+        Set the function providing external data to models loaded with `model_for_bytes` or
+        `model_for_read`.
+        Models loaded with `model_for_path` keep reading external data from the filesystem.
+
+        `resolver` is called with the location of the data as found in the model, an offset and
+        an optional length, and must return the matching bytes.
+
+        ```python
+        weights = bundle.read("model.onnx.data")
+        onnx = tract.onnx()
+        onnx.set_external_data_resolver(
+            lambda location, offset, length: weights[offset:][:length]
+        )
+        model = onnx.model_for_bytes(bundle.read("model.onnx"))
+        ```
+        """
+        def callback(user_data, location, offset, length, data, data_len):
+            try:
+                buf = resolver(location.decode("utf-8"), offset, None if length < 0 else length)
+                # keep the bytes alive until tract has copied them
+                self._external_data = (c_ubyte * len(buf)).from_buffer_copy(buf)
+                data[0] = addressof(self._external_data)
+                data_len[0] = len(buf)
+                return 0
+            except Exception:
+                return 1
+
+        self._external_data_resolver = _EXTERNAL_DATA_RESOLVER(callback)
+        check(lib.tract_onnx_set_external_data_resolver(self.ptr, self._external_data_resolver, None))
+        return self
@@ -56,6 +56,16 @@ class State:
             result.append(Value(c_void_p(v)))
         return result
 
+    def reset_kv_caches(self):
+        """Empty all key/value caches of the model, keeping their allocations."""
+        self._valid()
+        check(lib.tract_state_reset_kv_caches(self.ptr))
+
+    def truncate_kv_caches(self, len: int):
+        """Keep only the first `len` positions in all key/value caches of the model."""
+        self._valid()
+        check(lib.tract_state_truncate_kv_caches(self.ptr, c_size_t(len)))
+
     def freeze(self) -> "FrozenState":
         self._valid()
         frozen = c_void_p()
@@ -0,0 +1,72 @@
+from ctypes import *
+from pathlib import Path
+from typing import BinaryIO, Dict, List, Union
+from .bindings import check, lib, TractError
+from .model import Model
+
+class Tflite:
+    """
+    Represent a TFLite context in tract.
+
+    It allows to load TFLite flatbuffer models as a `Model`, and to save a `Model` in the
+    TFLite format. Not every tract operator has a TFLite counterpart, so saving may fail on
+    models using operators outside of the TFLite builtin set.
+
+    ```python
+    model = (
+        tract.tflite()
+        .model_for_path("./mobilenet_v2_1.0_224.tflite")
+        .into_optimized()
+        .into_runnable()
+    )
+    ```
+    """
+
+    def __init__(self):
+        ptr = c_void_p()
+        check(lib.tract_tflite_create(byref(ptr)))
+        self.ptr = ptr
+
+    def __del__(self):
+        check(lib.tract_tflite_destroy(byref(self.ptr)))
+
+    def _valid(self):
+        if self.ptr == None:
+            raise TractError("invalid tflite context")
+
+    def model_for_path(self, path: Union[str, Path]) -> Model:
+        """
+        Load a TFLite model from the file at `path`
+        """
+        self._valid()
+        model = c_void_p()
+        path = str(path).encode("utf-8")
+        check(lib.tract_tflite_model_for_path(self.ptr, path, byref(model)))
+        return Model(model)
+
+    def model_for_bytes(self, data: Union[bytes, bytearray, memoryview]) -> Model:
+        """
+        Load a TFLite model from an in-memory flatbuffer
+        """
+        self._valid()
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_tflite_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return Model(model)
+
+    def model_for_read(self, reader: BinaryIO) -> Model:
+        """
+        Load a TFLite model from a binary file-like object
+        """
+        return self.model_for_bytes(reader.read())
+
+    def write_model_to_path(self, model: Model, path: Union[str, Path]) -> None:
+        """
+        Save `model` as a TFLite file in `path`.
+        """
+        self._valid()
+        model._valid()
+        if not isinstance(model, Model):
+            raise TractError("Expected a Model, called with " + model);
+        path = str(path).encode("utf-8")
+        check(lib.tract_tflite_write_model_to_path(self.ptr, path, model.ptr))
@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Display};
+use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -8,10 +9,13 @@ use tract_extra::WithTractExtra;
 use tract_libcli::annotations::Annotations;
 use tract_libcli::profile::BenchLimits;
 use tract_nnef::internal::parse_tdim;
+use tract_nnef::prelude::tract_linalg::multithread::Executor;
 use tract_nnef::prelude::{
-    Framework, IntoTValue, SymbolValues, TValue, TVec, Tensor, TractResult, TypedFact, TypedModel,
-    TypedRunnableModel, TypedSimplePlan, TypedSimpleState,
+    Framework, IntoTValue, PlanOptions, SymbolValues, TValue, TVec, Tensor, TractResult, TypedFact,
+    TypedModel, TypedRunnableModel, TypedSimplePlan, TypedSimpleState,
 };
+use tract_onnx::data_resolver::ModelDataResolver;
+use tract_onnx::model::ParseResult;
 use tract_onnx::prelude::InferenceModelExt;
 use tract_onnx_opl::WithOnnx;
 use tract_pulse::model::{PulsedModel, PulsedModelExt};
@@ -25,7 +29,12 @@ pub fn nnef() -> Result<Nnef> {
 }
 
 pub fn onnx() -> Result<Onnx> {
-    Ok(Onnx(tract_onnx::onnx()))
+    Ok(Onnx { onnx: tract_onnx::onnx(), external_data_resolver: None })
+}
+
+/// Creates an instance of a TFLite framework that can be used to load and dump TFLite models.
+pub fn tflite() -> Result<Tflite> {
+    Ok(Tflite(tract_tflite::tflite()))
 }
 
 /// tract version tag
@@ -41,6 +50,14 @@ impl NnefInterface for Nnef {
         self.0.model_for_path(path).map(Model)
     }
 
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<Model> {
+        self.0.model_for_read(&mut bytes).map(Model)
+    }
+
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Model> {
+        self.0.model_for_read(reader).map(Model)
+    }
+
     fn enable_tract_core(&mut self) -> Result<()> {
         self.0.enable_tract_core();
         Ok(())
@@ -84,11 +101,87 @@ impl NnefInterface for Nnef {
     }
 }
 
-pub struct Onnx(tract_onnx::Onnx);
+pub struct Onnx {
+    onnx: tract_onnx::Onnx,
+    external_data_resolver: Option<Arc<dyn ModelDataResolver + Send + Sync>>,
+}
+
 impl OnnxInterface for Onnx {
     type InferenceModel = InferenceModel;
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::InferenceModel> {
-        Ok(InferenceModel(self.0.model_for_path(path)?))
+        Ok(InferenceModel(self.onnx.model_for_path(path)?))
+    }
+
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<Self::InferenceModel> {
+        self.model_for_read(&mut bytes)
+    }
+
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::InferenceModel> {
+        let proto = self.onnx.proto_model_for_read(reader)?;
+        let ParseResult { model, unresolved_inputs, .. } =
+            if let Some(resolver) = &self.external_data_resolver {
+                // external data locations are handed to the resolver as is, relative to an empty
+                // dir. model_for_path keeps reading them from the filesystem.
+                let onnx = tract_onnx::Onnx { provider: resolver.clone(), ..self.onnx.clone() };
+                onnx.parse(&proto, Some(""))?
+            } else {
+                self.onnx.parse(&proto, None)?
+            };
+        anyhow::ensure!(
+            unresolved_inputs.is_empty(),
+            "Could not resolve inputs at top-level: {:?}",
+            unresolved_inputs
+        );
+        Ok(InferenceModel(model))
+    }
+
+    fn set_external_data_resolver<F>(&mut self, resolver: F) -> Result<()>
+    where
+        F: Fn(&str, usize, Option<usize>) -> Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.external_data_resolver = Some(Arc::new(ExternalDataResolver(resolver)));
+        Ok(())
+    }
+}
+
+struct ExternalDataResolver<F>(F);
+
+impl<F> ModelDataResolver for ExternalDataResolver<F>
+where
+    F: Fn(&str, usize, Option<usize>) -> Result<Vec<u8>>,
+{
+    fn read_bytes_from_path(
+        &self,
+        buf: &mut Vec<u8>,
+        p: &Path,
+        offset: usize,
+        length: Option<usize>,
+    ) -> TractResult<()> {
+        let location = p.to_str().with_context(|| format!("Non utf-8 location {p:?}"))?;
+        buf.extend((self.0)(location, offset, length)?);
+        Ok(())
+    }
+}
+
+pub struct Tflite(tract_tflite::Tflite);
+impl TfliteInterface for Tflite {
+    type Model = Model;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
+        self.0.model_for_path(path).map(Model)
+    }
+
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<Model> {
+        self.0.model_for_read(&mut bytes).map(Model)
+    }
+
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Model> {
+        self.0.model_for_read(reader).map(Model)
+    }
+
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Model) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.0.write(&model.0, file)?;
+        Ok(())
     }
 }
 
@@ -226,6 +319,15 @@ impl ModelInterface for Model {
         Ok(Runnable(Arc::new(self.0.into_runnable()?)))
     }
 
+    fn into_parallel_runnable(self, threads: usize) -> Result<Runnable> {
+        let options = PlanOptions {
+            parallel_nodes: true,
+            executor: Some(Executor::multithread(threads)),
+            ..PlanOptions::default()
+        };
+        Ok(Runnable(Arc::new(self.0.into_runnable_with_options(&options)?)))
+    }
+
     fn concretize_symbols(
         &mut self,
         values: impl IntoIterator<Item = (impl AsRef<str>, i64)>,
@@ -353,6 +455,14 @@ impl StateInterface for State {
         let outputs = self.0.run(inputs)?;
         Ok(outputs.into_iter().map(Value).collect())
     }
+
+    fn reset_kv_caches(&mut self) -> Result<()> {
+        self.0.reset_kv_caches()
+    }
+
+    fn truncate_kv_caches(&mut self, len: usize) -> Result<()> {
+        self.0.truncate_kv_caches(len)
+    }
 }
 
 // VALUE
@@ -1,6 +1,7 @@
 use anyhow::{ensure, Result};
 use boow::Bow;
 use std::fmt::{Debug, Display};
+use std::io::Read;
 use std::path::Path;
 
 #[macro_use]
@@ -16,6 +17,16 @@ pub trait NnefInterface: Sized {
     /// * `path` can point to a directory, a `tar` file or a `tar.gz` file.
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::Model>;
 
+    /// Load a NNEF model from an in-memory `tar` or `tar.gz` archive.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::Model>;
+
+    /// Load a NNEF model from a reader over a `tar` or `tar.gz` archive.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::Model> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+
     /// Allow the framework to use tract_core extensions instead of a stricter NNEF definition.
     fn enable_tract_core(&mut self) -> Result<()>;
 
@@ -82,6 +93,52 @@ pub trait NnefInterface: Sized {
 pub trait OnnxInterface {
     type InferenceModel: InferenceModelInterface;
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::InferenceModel>;
+
+    /// Load an ONNX model from an in-memory protobuf buffer.
+    ///
+    /// External data, if any, is read through the resolver set with
+    /// `set_external_data_resolver`.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::InferenceModel>;
+
+    /// Load an ONNX model from a reader over a protobuf stream.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::InferenceModel> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+
+    /// Set the function providing external data to models loaded from bytes or a reader.
+    /// Models loaded with `model_for_path` keep reading external data from the filesystem.
+    ///
+    /// It is called with the `location` of the external data as found in the model, an offset
+    /// and an optional length, and must return the matching bytes.
+    fn set_external_data_resolver<F>(&mut self, resolver: F) -> Result<()>
+    where
+        F: Fn(&str, usize, Option<usize>) -> Result<Vec<u8>> + Send + Sync + 'static;
+}
+
+/// an implementation of tract's TFLite framework object
+///
+/// Entry point for TFLite model manipulation: loading from file, dumping to file.
+pub trait TfliteInterface: Sized {
+    type Model: ModelInterface;
+    /// Load a TFLite flatbuffer model from the path into a tract-core model.
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::Model>;
+
+    /// Load a TFLite model from an in-memory flatbuffer.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::Model>;
+
+    /// Load a TFLite model from a reader over a flatbuffer.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::Model> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+
+    /// Dump a TypedModel as a TFLite flatbuffer file.
+    ///
+    /// `path` is the `.tflite` file name to dump to
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Self::Model) -> Result<()>;
 }
 
 pub trait InferenceModelInterface: Sized {
@@ -150,6 +207,15 @@ pub trait ModelInterface: Sized {
 
     fn into_runnable(self) -> Result<Self::Runnable>;
 
+    /// Transform the model into a Runnable evaluating independent nodes concurrently on a pool
+    /// of `threads` threads.
+    ///
+    /// Implementations without inter-op parallelism fall back to `into_runnable`.
+    fn into_parallel_runnable(self, threads: usize) -> Result<Self::Runnable> {
+        let _ = threads;
+        self.into_runnable()
+    }
+
     fn concretize_symbols(
         &mut self,
         values: impl IntoIterator<Item = (impl AsRef<str>, i64)>,
@@ -201,6 +267,12 @@ pub trait StateInterface {
         I: IntoIterator<Item = V>,
         V: TryInto<Self::Value, Error = E>,
         E: Into<anyhow::Error>;
+
+    /// Empty all key/value caches of the model, keeping their allocations.
+    fn reset_kv_caches(&mut self) -> Result<()>;
+
+    /// Keep only the first `len` positions in all key/value caches of the model.
+    fn truncate_kv_caches(&mut self, len: usize) -> Result<()>;
 }
 
 pub trait ValueInterface: Sized + Clone {
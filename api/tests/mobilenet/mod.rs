@@ -47,6 +47,27 @@ fn test_onnx() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_parallel_runnable() -> anyhow::Result<()> {
+    ensure_models()?;
+    let model = onnx()?
+        .model_for_path("mobilenetv2-7.onnx")?
+        .into_optimized()?
+        .into_parallel_runnable(4)?;
+    let hopper = grace_hopper();
+    let result = model.run([hopper])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+    Ok(())
+}
+
 #[test]
 fn test_state() -> anyhow::Result<()> {
     ensure_models()?;
@@ -87,6 +108,37 @@ fn test_nnef() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_onnx_for_bytes() -> anyhow::Result<()> {
+    ensure_models()?;
+    let bytes = std::fs::read("mobilenetv2-7.onnx")?;
+    let model = onnx()?.model_for_bytes(&bytes)?.into_optimized()?.into_runnable()?;
+    let result = model.run([grace_hopper()])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+    Ok(())
+}
+
+#[test]
+fn test_nnef_for_bytes_and_read() -> anyhow::Result<()> {
+    ensure_models()?;
+    let nnef = nnef()?;
+    let bytes = std::fs::read("mobilenet_v2_1.0.onnx.nnef.tgz")?;
+    let model = nnef.model_for_bytes(&bytes)?;
+    assert_eq!(model.input_fact(0)?.to_string(), "1,3,224,224,F32");
+    let mut file = std::fs::File::open("mobilenet_v2_1.0.onnx.nnef.tgz")?;
+    let model = nnef.model_for_read(&mut file)?;
+    assert_eq!(model.input_fact(0)?.to_string(), "1,3,224,224,F32");
+    Ok(())
+}
+
 #[test]
 fn test_inference_model() -> anyhow::Result<()> {
     ensure_models()?;
@@ -244,6 +296,35 @@ fn test_typed_model_to_nnef_and_back() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_typed_model_to_tflite_and_back() -> anyhow::Result<()> {
+    ensure_models()?;
+    let mut model = onnx()?.model_for_path("mobilenetv2-7.onnx")?;
+    model.set_input_fact(0, "1,3,224,224,f32")?;
+    let typed = model.into_typed()?.into_decluttered()?;
+    let dir = tempfile::tempdir()?;
+    let tflite = tflite()?;
+
+    let path = dir.path().join("mobilenet.tflite");
+    tflite.write_model_to_path(&path, &typed)?;
+    let reloaded = tflite.model_for_path(path)?.into_optimized()?.into_runnable()?;
+    let result = reloaded.run([grace_hopper()])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+
+    let bytes = std::fs::read(dir.path().join("mobilenet.tflite"))?;
+    let reloaded = tflite.model_for_bytes(&bytes)?;
+    assert_eq!(reloaded.input_fact(0)?.to_string(), "1,3,224,224,F32");
+    Ok(())
+}
+
 #[test]
 fn test_cost() -> anyhow::Result<()> {
     ensure_models()?;
@@ -2,6 +2,7 @@
 use std::fmt::{Debug, Display};
 #[allow(unused_imports)]
 use std::fs;
+use std::time::{Duration, Instant};
 
 use nu_ansi_term::Color::*;
 
@@ -25,6 +26,9 @@ pub fn handle(
     let resilent = sub_matches.is_present("resilient");
     if sub_matches.value_of("stage").is_some() {
         // --with is by pipeline and put in params
+        if sub_matches.is_present("accuracy") {
+            return handle_accuracy(params, &run_params);
+        }
         return handle_reference_stage(cumulative, params, &output_params, &run_params);
     } else if let Some(npz) = sub_matches.value_of("npz") {
         return handle_npz(cumulative, npz, params, &output_params, &run_params);
@@ -233,6 +237,87 @@ pub fn handle_reference_stage(
     handle_with_model(cumulative, params, output_params, reference_model, run_params)
 }
 
+/// Compare the model outputs with the reference stage ones end to end, reporting the error and
+/// the latency of both models instead of checking each node. This is meant for lossy transforms,
+/// like quantization, where intermediate values are not expected to match.
+pub fn handle_accuracy(params: &Parameters, run_params: &RunParams) -> TractResult<()> {
+    let reference_model =
+        params.reference_model.as_ref().context("No reference model. need --with ?")?;
+    let reference_model = reference_model
+        .downcast_ref::<TypedModel>()
+        .context("Only work with a typed reference model")?;
+    let model =
+        params.tract_model.downcast_ref::<TypedModel>().context("Only work with a typed model")?;
+    // the reference is optimized too, for the latency comparison to be meaningful
+    let reference = reference_model.clone().into_optimized()?.into_runnable()?;
+    let plan = SimplePlan::new(model)?;
+    let all_inputs = tract_libcli::tensor::retrieve_or_make_inputs(model, run_params)?;
+    let first = all_inputs.first().context("No inputs")?;
+    reference.run(first.clone())?;
+    plan.run(first.clone())?;
+
+    let outputs = model.output_outlets()?;
+    // per output: max abs error, sum of abs errors, number of values, max abs reference value
+    let mut errors = vec![(0f32, 0f64, 0usize, 0f32); outputs.len()];
+    let (mut reference_time, mut model_time) = (Duration::default(), Duration::default());
+    for (turn, inputs) in all_inputs.iter().enumerate() {
+        let start = Instant::now();
+        let expected = reference.run(inputs.clone())?;
+        reference_time += start.elapsed();
+        let start = Instant::now();
+        let found = plan.run(inputs.clone())?;
+        model_time += start.elapsed();
+        ensure!(expected.len() == found.len(), "At turn {turn}, output number mismatch");
+        for (ix, (expected, found)) in expected.iter().zip(found.iter()).enumerate() {
+            ensure!(
+                expected.shape() == found.shape(),
+                "At turn {turn}, output {ix} shape mismatch: {:?} vs {:?}",
+                expected.shape(),
+                found.shape()
+            );
+            let expected = expected.cast_to::<f32>()?;
+            let found = found.cast_to::<f32>()?;
+            let error = &mut errors[ix];
+            for (e, f) in expected.as_slice::<f32>()?.iter().zip(found.as_slice::<f32>()?) {
+                error.0 = error.0.max((e - f).abs());
+                error.1 += (e - f).abs() as f64;
+                error.2 += 1;
+                error.3 = error.3.max(e.abs());
+            }
+        }
+    }
+
+    let turns = all_inputs.len() as f64;
+    let reference_time = Duration::from_secs_f64(reference_time.as_secs_f64() / turns);
+    let model_time = Duration::from_secs_f64(model_time.as_secs_f64() / turns);
+    for (outlet, (max, sum, count, magnitude)) in outputs.iter().zip(errors) {
+        let name = model.outlet_label(*outlet).unwrap_or(&model.node(outlet.node).name);
+        let mean = sum / count.max(1) as f64;
+        if params.machine_friendly {
+            println!(
+                "{name}: max_abs_error: {max} mean_abs_error: {mean} max_abs_ref: {magnitude}"
+            );
+        } else {
+            println!(
+                "{name}: max abs error {max:.3e} ({:.2}% of max abs value), mean abs error {mean:.3e}",
+                max / magnitude * 100.
+            );
+        }
+    }
+    if params.machine_friendly {
+        println!("reference_real: {}", reference_time.as_secs_f64());
+        println!("real: {}", model_time.as_secs_f64());
+    } else {
+        println!(
+            "Latency: reference {}, model {} ({:.2}x speedup)",
+            tract_libcli::terminal::dur_avg(reference_time),
+            tract_libcli::terminal::dur_avg(model_time),
+            reference_time.as_secs_f64() / model_time.as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
 pub fn handle_with_model(
     cumulative: bool,
     params: &Parameters,
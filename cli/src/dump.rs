@@ -224,6 +224,23 @@ pub fn handle(
         }
     }
 
+    #[cfg(feature = "onnx")]
+    if let Some(path) = sub_matches.value_of("onnx") {
+        let threshold = sub_matches.is_present("onnx-external-data").then_some(1024);
+        let onnx = tract_onnx::onnx().with_external_data_threshold(threshold);
+        if let Some(mut typed) = model.downcast_ref::<TypedModel>().cloned() {
+            rename_outputs(&mut typed, sub_matches)?;
+            onnx.write_to_path(&typed, path).context("Writing model to ONNX")?;
+        } else {
+            bail!("Only typed model can be dumped")
+        }
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    if sub_matches.value_of("onnx").is_some() {
+        bail!("This is a tract build without support for ONNX.")
+    }
+
     #[cfg(feature = "tflite")]
     if let Some(path) = sub_matches.value_of("tflite") {
         let tflite = tract_tflite::tflite();
@@ -112,6 +112,7 @@ fn main() -> TractResult<()> {
         .arg(arg!(--"label-wires" "Propagate node labels to wires"))
 
         .arg(arg!(--"tf-initializer-output-node" [node] "Set an initializer node"))
+        .arg(arg!(--"tf-signature" [name] "Signature to load from a TensorFlow SavedModel directory"))
 
         .arg(arg!(--"override-fact" [fact] "Override a fact."))
 
@@ -131,7 +132,7 @@ fn main() -> TractResult<()> {
         .arg(arg!(--"f16-to-f32" "Convert the decluttered network from f16 to f32"))
         .arg(Arg::new("transform").short('t').long("transform").multiple_occurrences(true).takes_value(true).help("Apply a built-in transformation to the model"))
         .arg(Arg::new("set").long("set").multiple_occurrences(true).takes_value(true)
-             .long_help("Set a symbol to a concrete value after decluttering"))
+             .long_help("Set a symbol to a concrete value after decluttering (--set S=12), or declare an assumption on symbols (--set \"S>=1\", --set \"S<=4096\")"))
 
         // deprecated
         .arg(arg!(--"allow-float-casts" "Allow casting between f16, f32 and f64 around model").hide(true))
@@ -144,6 +145,7 @@ fn main() -> TractResult<()> {
         .arg(arg!(--"nnef-tract-pulse" "Allow usage of tract-pulse extension in NNEF dump and load"))
         .arg(arg!(--"nnef-tract-extra" "Allow usage of tract-extra extension in NNEF dump and load"))
         .arg(arg!(--"nnef-extended-identifier" "Allow usage of the i\"...\" syntax to escape identifier names"))
+        .arg(arg!(--mmap "Memory-map NNEF tensors and ONNX external data instead of copying them"))
 
         .arg(arg!(--"threads" [THREADS] "Setup a thread pool for computing. 0 will guess the number of physical cores"))
 
@@ -188,6 +190,13 @@ fn main() -> TractResult<()> {
                 .long("resilient")
                 .takes_value(false)
                 .help("Try nodes one per one to mitigate crashes"),
+        )
+        .arg(
+            Arg::new("accuracy")
+                .long("accuracy")
+                .takes_value(false)
+                .requires("stage")
+                .help("Only compare model outputs with the reference stage, and report the error and the latency of both"),
         );
     let compare = run_options(compare);
     let compare = assertions_options(compare);
@@ -352,6 +361,17 @@ fn dump_subcommand<'a>() -> clap::Command<'a> {
             .long("tflite")
             .help("Dump the network in TfLite format"),
             )
+        .arg(
+            Arg::new("onnx")
+            .takes_value(true)
+            .long("onnx")
+            .help("Dump the network in ONNX format"),
+            )
+        .arg(
+            Arg::new("onnx-external-data")
+            .long("onnx-external-data")
+            .help("Store ONNX weights of 1KB or more in a separate .data file"),
+            )
         .arg(
             Arg::new("compress-submodels")
             .long("compress-submodels")
@@ -664,5 +684,8 @@ fn nnef(matches: &clap::ArgMatches) -> tract_nnef::internal::Nnef {
     if matches.is_present("nnef-extended-identifier") {
         fw.allow_extended_identifier_syntax(true);
     }
+    if matches.is_present("mmap") {
+        fw.mmap_tensors(true);
+    }
     fw
 }
@@ -184,6 +184,8 @@ impl Parameters {
                 "onnx"
             } else if location.path().extension().map(|s| s == "tflite").unwrap_or(false) {
                 "tflite"
+            } else if location.is_dir() && location.path().join("saved_model.pb").exists() {
+                "tf"
             } else if location.is_dir()
                 || location.path().to_string_lossy().ends_with(".tar")
                 || location.path().to_string_lossy().ends_with(".tar.gz")
@@ -212,6 +214,8 @@ impl Parameters {
                     nnef.proto_model_for_read(&mut flate2::read::GzDecoder::new(
                         &mut *location.read()?,
                     ))?
+                } else if let (true, Location::Fs(file)) = (nnef.mmap_tensors, location) {
+                    nnef.proto_model_for_path(file)?
                 } else {
                     nnef.proto_model_for_read(&mut *location.read()?)?
                 };
@@ -293,6 +297,11 @@ impl Parameters {
                 if matches.is_present("onnx-ignore-output-types") {
                     onnx = onnx.with_ignore_output_types(true);
                 }
+                if matches.is_present("mmap") {
+                    onnx = onnx.with_data_resolver(
+                        tract_onnx::data_resolver::SharedMmapDataResolver::default(),
+                    );
+                }
                 info_usage("loaded framework (onnx)", probe);
                 let graph = onnx.proto_model_for_read(&mut *location.read()?)?;
                 info_usage("proto model loaded", probe);
@@ -321,7 +330,11 @@ impl Parameters {
             "tf" => {
                 let tf = tract_tensorflow::tensorflow();
                 info_usage("loaded framework (tf)", probe);
-                let mut graph = tf.proto_model_for_read(&mut *location.read()?)?;
+                let mut graph = if let (true, Location::Fs(dir)) = (location.is_dir(), location) {
+                    tf.read_saved_model_dir(dir, matches.value_of("tf-signature"))?
+                } else {
+                    tf.proto_model_for_read(&mut *location.read()?)?
+                };
                 info_usage("proto model loaded", probe);
                 if matches.is_present("determinize") {
                     tract_tensorflow::Tensorflow::determinize(&mut graph)?;
@@ -740,6 +753,13 @@ impl Parameters {
         if let Some(transform) = matches.values_of("transform") {
             for transform in transform {
                 stage!(transform, typed_model -> typed_model, |m:TypedModel| {
+                    if let Some(spec) = transform.strip_prefix("int8-static:") {
+                        use tract_core::quantize::*;
+                        use tract_core::transform::ModelTransform;
+                        let (mut quantization, path) = parse_static_quantization(spec).with_context(|| format!("Could not parse static quantization {}", transform))?;
+                        quantization.calibration = CalibrationData::Inputs(tensor::calibration_from_npz(&m, path)?);
+                        return quantization.transform_into(&m)
+                    }
                     let transform = tract_core::transform::get_transform(transform).with_context(|| format!("Could not find transform named {}", transform))?;
                     transform.transform_into(&m)
                 });
@@ -748,6 +768,10 @@ impl Parameters {
         if let Some(set) = matches.values_of("set") {
             let mut values = SymbolValues::default();
             for set in set {
+                if set.contains(['<', '>']) || set.contains("==") {
+                    typed_model.as_ref().unwrap().symbol_table.add_assertion(set)?;
+                    continue;
+                }
                 let (key, value) = set
                     .split_once('=')
                     .with_context(|| format!("--set must be in the X=value form, got {set}"))?;
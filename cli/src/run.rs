@@ -19,7 +19,7 @@ use tract_pulse::internal::*;
 /// Add a tensor entry into a npz file.
 fn npz_add_tensor(npz: &mut NpzWriter<File>, name: String, tensor: &Tensor) -> TractResult<()> {
     match tensor.datum_type() {
-        DatumType::F16 => npz.add_array(name, &tensor.cast_to::<f32>()?.to_array_view::<f32>()?)?,
+        DatumType::F16 | DatumType::BF16 => npz.add_array(name, &tensor.cast_to::<f32>()?.to_array_view::<f32>()?)?,
         DatumType::Bool => npz.add_array(name, &tensor.to_array_view::<bool>()?)?,
         DatumType::U8 => npz.add_array(name, &tensor.to_array_view::<u8>()?)?,
         DatumType::U16 => npz.add_array(name, &tensor.to_array_view::<u16>()?)?,
@@ -8,7 +8,7 @@ use crate::ops::cast::Cast;
 use crate::ops::einsum::EinSum;
 use crate::ops::element_wise::ElementWiseOp;
 use crate::ops::konst::Const;
-use crate::ops::scan::Scan;
+use crate::ops::scan::{Loop, Scan};
 use crate::ops::source::TypedSource;
 use crate::transform::ModelTransform;
 
@@ -131,7 +131,7 @@ impl<T1: Datum + Float, T2: Datum + Float>
             let new_op = if let Some(source) = node.op_as::<TypedSource>() {
                 Box::new(TypedSource::new(fact_float_precision_conversion::<T1, T2>(&source.fact)))
             } else if let Some(konst) = node.op_as::<Const>() {
-                Box::new(Const(tensor_float_precision_conversion::<T1, T2>(&konst.0)))
+                Box::new(Const::new(tensor_float_precision_conversion::<T1, T2>(&konst.0)))
             } else if let Some(cast) = node.op_as::<Cast>() {
                 if cast.to == T1::datum_type() {
                     Box::new(Cast { to: T2::datum_type() })
@@ -154,6 +154,10 @@ impl<T1: Datum + Float, T2: Datum + Float>
                 let body =
                     FloatPrecisionTranslator::<T1, T2>::default().translate_model(&op.body)?;
                 Box::new(Scan { body, ..op.clone() })
+            } else if let Some(op) = node.op_as::<Loop>() {
+                let body =
+                    FloatPrecisionTranslator::<T1, T2>::default().translate_model(&op.body)?;
+                Box::new(Loop { body, ..op.clone() })
             } else if let Some(op) = node.op_as::<EinSum>() {
                 Box::new(EinSum {
                     operating_dt: dt_float_precision_conversion::<T1, T2>(op.operating_dt),
@@ -312,4 +316,37 @@ mod test {
             .is_nan());
         Ok(())
     }
+
+    #[test]
+    fn test_bf16_transform() -> TractResult<()> {
+        // bf16 has the f32 exponent range, so the intermediate 1e10 does not overflow
+        let mut model = build_f32_model()?;
+        model.transform(&FloatPrecisionTranslator::<f32, bf16>::default())?;
+        let runnable_model = model.into_runnable()?;
+        assert_eq!(
+            runnable_model.run(tvec![tensor1(&[bf16::from_f32(5.0)]).into()])?[0],
+            tensor1(&[bf16::NEG_INFINITY]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bf16_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let a = model.add_source("a", f32::fact([3, 5]))?;
+        let b = model.add_const("b", Tensor::from_shape(&[5, 4], &[0.25f32; 20])?)?;
+        let op = EinSum::new("mk,kn->mn".parse()?, f32::datum_type());
+        model.wire_node("einsum", op, &[a, b])?;
+        model.auto_outputs()?;
+        model.transform(&FloatPrecisionTranslator::<f32, bf16>::default())?;
+        let a = Tensor::from_shape(&[3, 5], &(0..15).map(|x| x as f32).collect::<Vec<_>>())?;
+        let expected =
+            tensor2(&[[2.5f32; 4], [8.75; 4], [15.0; 4]]).cast_to::<bf16>()?.into_owned();
+        for model in [model.clone(), model.into_optimized()?] {
+            let input = a.cast_to::<bf16>()?.into_owned();
+            let output = model.into_runnable()?.run(tvec!(input.into()))?.remove(0);
+            output.close_enough(&expected, Approximation::Close)?;
+        }
+        Ok(())
+    }
 }
@@ -86,6 +86,7 @@ pub mod floats;
 pub mod model;
 pub mod optim;
 pub mod plan;
+pub mod quantize;
 pub mod runtime;
 pub mod transform;
 pub mod value;
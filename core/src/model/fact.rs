@@ -200,7 +200,11 @@ pub struct TypedFact {
     /// optional uniform value
     pub uniform: Option<Arc<Tensor>>,
     /// optional opaque metadata
-    pub opaque_metadata: Option<Box<dyn OpaqueMetadata>>
+    pub opaque_metadata: Option<Box<dyn OpaqueMetadata>>,
+    /// optional per-axis quantization parameters, attached by framework loaders to constant
+    /// weights. They are not propagated through ops: quantized conv and einsum get per-axis zero
+    /// points and scales as inputs.
+    pub per_axis_q: Option<Arc<PerAxisQParams>>,
 }
 
 impl TypedFact {
@@ -226,6 +230,7 @@ impl TypedFact {
             uniform: None,
             konst: None,
             opaque_metadata: None,
+            per_axis_q: None,
         }
     }
 
@@ -235,7 +240,8 @@ impl TypedFact {
             shape: ShapeFact::scalar(), 
             konst: None, 
             uniform: None, 
-            opaque_metadata: None, 
+            opaque_metadata: None,
+            per_axis_q: None,
         }
     }
 
@@ -249,6 +255,7 @@ impl TypedFact {
             konst: None, 
             uniform: None,
             opaque_metadata: None,
+            per_axis_q: None,
         }
     }
 
@@ -295,6 +302,20 @@ impl TypedFact {
                 bail!("Fact said to be uniform ({:?}) and equal to {:?} which is not.", u, k);
             }
         }
+        if let Some(q) = &self.per_axis_q {
+            if q.axis >= self.shape.rank() {
+                bail!(
+                    "Per-axis quantization on axis {} of a rank {} fact",
+                    q.axis,
+                    self.shape.rank()
+                );
+            }
+            if let Ok(dim) = self.shape[q.axis].to_usize() {
+                if dim != q.len() {
+                    bail!("Per-axis quantization: {} parameters for axis of size {dim}", q.len());
+                }
+            }
+        }
         Ok(())
     }
 
@@ -306,6 +327,11 @@ impl TypedFact {
         self.opaque_metadata = Some(opaque_metadata.into());
         self
     }
+
+    pub fn with_per_axis_q(mut self, per_axis_q: impl Into<Arc<PerAxisQParams>>) -> Self {
+        self.per_axis_q = Some(per_axis_q.into());
+        self
+    }
 }
 
 impl Fact for TypedFact {
@@ -378,6 +404,7 @@ impl From<Arc<Tensor>> for TypedFact {
             shape: ShapeFact::from_dims(t.shape().iter().map(TDim::from)),
             uniform: t.as_uniform().map(Arc::new),
             opaque_metadata: None,
+            per_axis_q: None,
             konst: Some(t),
         }
     }
@@ -398,6 +425,9 @@ impl<'a> From<&'a Arc<Tensor>> for TypedFact {
 impl fmt::Debug for TypedFact {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match (self.konst.as_ref(), self.opaque_metadata.as_ref()) {
+            (Some(ref k), _) if self.per_axis_q.is_some() => {
+                write!(fmt, "{k:?} ({:?})", self.per_axis_q.as_ref().unwrap())
+            }
             (Some(ref k), _) => write!(fmt, "{k:?}"),
             (None, None) if self.rank() > 0 => write!(fmt, "{:?},{:?}", self.shape, self.datum_type),
             (None, Some(ref meta)) if self.rank() > 0 => write!(fmt, "{:?},{:?},{:?}", self.shape, self.datum_type, meta),
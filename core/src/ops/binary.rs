@@ -81,8 +81,16 @@ pub trait BinMiniOp: fmt::Debug + dyn_clone::DynClone + Send + Sync + 'static +
     }
 
     fn generic_eval(&self, a: TValue, b: TValue, c_dt: DatumType) -> TractResult<Tensor> {
+        let c_shape = crate::broadcast::multi_broadcast(&[a.shape(), b.shape()])?;
+        let in_a = &*c_shape == a.shape() && c_dt == a.datum_type();
+        let in_b = c_dt == b.datum_type() && (a.len() == 1 || a.shape() == b.shape());
         if let Some(tensor) = self.maybe_eval_qbinary_as_float_op(&a, &b, &c_dt)? {
             Ok(tensor)
+        } else if in_a && a.is_exclusive() && !(in_b && b.is_exclusive()) {
+            // a is the sole reference to its buffer while b is shared: reuse a instead of cloning b
+            let mut a = a.into_tensor();
+            self.eval_in_a(&mut a, &b)?;
+            Ok(a)
         } else if c_dt == b.datum_type() && a.len() == 1 {
             let mut b = b.into_tensor();
             self.eval_uniform_in_place(&a, &mut b)?;
@@ -91,17 +99,14 @@ pub trait BinMiniOp: fmt::Debug + dyn_clone::DynClone + Send + Sync + 'static +
             let mut b = b.into_tensor();
             self.eval_unicast_in_place(&a, &mut b)?;
             Ok(b)
+        } else if in_a {
+            let mut a = a.into_tensor();
+            self.eval_in_a(&mut a, &b)?;
+            Ok(a)
         } else {
-            let c_shape = crate::broadcast::multi_broadcast(&[a.shape(), b.shape()])?;
-            if &*c_shape == a.shape() && c_dt == a.datum_type() {
-                let mut a = a.into_tensor();
-                self.eval_in_a(&mut a, &b)?;
-                Ok(a)
-            } else {
-                let mut c = unsafe { Tensor::uninitialized_dt(c_dt, &c_shape)? };
-                self.eval_out_of_place(&mut c, &a, &b)?;
-                Ok(c)
-            }
+            let mut c = unsafe { Tensor::uninitialized_dt(c_dt, &c_shape)? };
+            self.eval_out_of_place(&mut c, &a, &b)?;
+            Ok(c)
         }
     }
     fn eval(&self, a: TValue, b: TValue, c_dt: DatumType) -> TractResult<Tensor> {
@@ -178,6 +183,14 @@ impl EvalOp for TypedBinOp {
         let (a, b) = args_2!(inputs);
         ensure!(a.rank() == b.rank());
         let c_dt = self.output_datum_type(a.datum_type(), b.datum_type())?;
+        if a.datum_type() == DatumType::BF16 || b.datum_type() == DatumType::BF16 {
+            // bf16 is evaluated through f32
+            let a = a.cast_to::<f32>()?.into_owned().into_tvalue();
+            let b = b.cast_to::<f32>()?.into_owned().into_tvalue();
+            let f32_dt = if c_dt == DatumType::BF16 { DatumType::F32 } else { c_dt };
+            let c = self.0.eval(a, b, f32_dt)?.cast_to_dt(c_dt)?.into_owned();
+            return Ok(tvec!(c.into_tvalue()));
+        }
         Ok(tvec!(self.0.eval(a, b, c_dt)?.into_tvalue()))
     }
 }
@@ -295,6 +308,11 @@ impl EvalOp for MergeOpUnicast {
 
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let (a, b) = args_2!(inputs);
+        if a.is_exclusive() && !b.is_exclusive() {
+            let mut a = a.into_tensor();
+            self.0.eval_in_a(&mut a, &b)?;
+            return Ok(tvec!(a.into_tvalue()));
+        }
         let mut b = b.into_tensor();
         self.0.eval_unicast_in_place(&a, &mut b)?;
         Ok(tvec!(b.into_tvalue()))
@@ -704,8 +722,12 @@ macro_rules! bin_to_bool {
                     bail!("{} does not support {:?}", self.name(), a.datum_type());
             }
 
-            fn eval_in_a(&self, a: &mut Tensor, _b: &Tensor) -> TractResult<()> {
-                bail!("{} does not support {:?}", self.name(), a.datum_type());
+            fn eval_in_a(&self, a: &mut Tensor, b: &Tensor) -> TractResult<()> {
+                // inputs may not be bool, so this goes through a separate output
+                let mut c = unsafe { Tensor::uninitialized_dt(bool::datum_type(), a.shape())? };
+                self.eval_out_of_place(&mut c, a, b)?;
+                *a = c;
+                Ok(())
             }
 
             fn result_datum_type(&self, _a: DatumType, _b: DatumType) -> TractResult<DatumType> {
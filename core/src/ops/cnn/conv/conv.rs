@@ -148,6 +148,25 @@ impl Conv {
 
         let abc_scale = qmm::combine_scales(model, name, k_scale, x_scale, y_scale)?;
 
+        if !model.outlet_fact(k0)?.shape.volume().is_one() {
+            // zero point compensation is performed on the mmm output (N,G,C,HW or N,HW,G,C)
+            let co = self.output_channels();
+            ensure!(model.outlet_fact(k0)?.shape.volume() == co.to_dim());
+            let mut shape: TVec<TDim> = tvec!((co / self.group).to_dim());
+            if self.group > 1 {
+                shape.insert(0, self.group.to_dim());
+            }
+            if !self.pool_spec.data_format.c_is_last() {
+                shape.push(1.to_dim());
+            }
+            let current = model.outlet_fact(k0)?.shape.to_tvec();
+            k0 = model.wire_node(
+                format!("{name}.k0_axis_fix"),
+                AxisOp::Reshape(0, current, shape),
+                &[k0],
+            )?[0];
+        }
+
         let im2col = model.wire_node(
             format!("{name}.im2col"),
             Im2Col::new(self.pool_spec.clone(), self.group, k, &b_fact.shape, mmm.clone())?,
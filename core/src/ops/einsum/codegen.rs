@@ -227,16 +227,16 @@ fn dequant(
     let mut patch = TypedModelPatch::new("Dequantizing einsum");
 
     let mut taps = patch.taps(model, &node.inputs)?;
-    for ab in [0, 1] {
-        let scale_input = 4 + ab * 2;
-        if !patch.outlet_fact(taps[scale_input])?.shape.volume().is_one() {
-            let q_axis_in_output = op.axes.axis((InOut::In(scale_input), 0))?.outputs[0][0];
+    // per-axis zero points and scales (a0, a_scale, b0, b_scale)
+    for q_input in 3..7 {
+        if !patch.outlet_fact(taps[q_input])?.shape.volume().is_one() {
+            let q_axis_in_output = op.axes.axis((InOut::In(q_input), 0))?.outputs[0][0];
             let output_rank = node.outputs[0].fact.rank();
             for i in 1..(output_rank - q_axis_in_output) {
-                taps[scale_input] = patch.wire_node(
-                    format!("{name}.scale_input{ab}_axis_fix_{i}"),
+                taps[q_input] = patch.wire_node(
+                    format!("{name}.q_input{q_input}_axis_fix_{i}"),
                     AxisOp::Add(i),
-                    &[taps[scale_input]],
+                    &[taps[q_input]],
                 )?[0];
             }
         }
@@ -331,16 +331,28 @@ fn lir_mat_mul_unary(
         )
         .map(Some);
     }
-    let a_dt = input_facts[0].datum_type;
-    let b_dt = input_facts[1].datum_type;
-    let dt = op.operating_dt;
+    let name = &node.name;
+    let mut patch = TypedModelPatch::new("Einsum to LirMatMulUnary");
+    let mut a = patch.tap_model(model, node.inputs[0])?;
+    let mut b = patch.tap_model(model, node.inputs[1])?;
+    let mut a_dt = input_facts[0].datum_type;
+    let mut b_dt = input_facts[1].datum_type;
+    let mut dt = op.operating_dt;
+    if dt == DatumType::BF16 {
+        // bf16 operands are multiplied with f32 accumulation, and cast back after the store
+        if a_dt != DatumType::BF16 {
+            a = patch.wire_node(format!("{name}.cast_a"), cast(DatumType::BF16), &[a])?[0];
+            a_dt = DatumType::BF16;
+        }
+        if b_dt != DatumType::BF16 {
+            b = patch.wire_node(format!("{name}.cast_b"), cast(DatumType::BF16), &[b])?[0];
+            b_dt = DatumType::BF16;
+        }
+        dt = DatumType::F32;
+    }
     let mmm = tract_linalg::ops()
         .mmm(a_dt, b_dt, dt, m.to_usize().ok(), k.to_usize().ok(), n.to_usize().ok())
         .unwrap();
-    let name = &node.name;
-    let mut patch = TypedModelPatch::new("Einsum to LirMatMulUnary");
-    let a = patch.tap_model(model, node.inputs[0])?;
-    let b = patch.tap_model(model, node.inputs[1])?;
     let packing = mmm
         .packings()
         .iter()
@@ -375,8 +387,8 @@ fn lir_mat_mul_unary(
         }
     }
 
-    let c_fact = op.output_facts(&input_facts)?.remove(0);
-    let name = &node.name;
+    let mut c_fact = op.output_facts(&input_facts)?.remove(0);
+    c_fact.datum_type = dt;
     let geo = AddMatMulGeometry {
         k: k.clone(),
         mmm: mmm.clone(),
@@ -392,7 +404,10 @@ fn lir_mat_mul_unary(
         vec![ProtoFusedSpec::AddMatMul { geo, a: 0, b: 1, packing }, ProtoFusedSpec::Store(output)],
     )
     .context("Creating LirMatMulUnary")?;
-    let output = patch.wire_node(name, lir, &[pa, pb])?[0];
+    let mut output = patch.wire_node(name, lir, &[pa, pb])?[0];
+    if dt != op.operating_dt {
+        output = patch.wire_node(format!("{name}.cast_c"), cast(op.operating_dt), &[output])?[0];
+    }
     patch.shunt_outside(model, node.id.into(), output)?;
     Ok(Some(patch))
 }
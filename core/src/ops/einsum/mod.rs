@@ -207,6 +207,15 @@ impl EvalOp for EinSum {
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let output = if let Some(qp) = self.q_params {
             eval::eval_q(&self.axes, qp, inputs)
+        } else if self.operating_dt == DatumType::BF16 {
+            // bf16 products are accumulated in f32
+            let inputs = inputs
+                .iter()
+                .map(|t| Ok(t.cast_to::<f32>()?.into_owned().into_tvalue()))
+                .collect::<TractResult<TVec<_>>>()?;
+            eval::eval_t::<f32>(&self.axes, inputs)?
+                .cast_to_dt(DatumType::BF16)
+                .map(|t| t.into_owned())
         } else {
             dispatch_numbers!(eval::eval_t(self.operating_dt)(&self.axes, inputs))
         }?;
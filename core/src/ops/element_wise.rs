@@ -100,6 +100,17 @@ impl EvalOp for ElementWiseOp {
     }
 
     fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        if inputs[0].datum_type() == DatumType::BF16 {
+            // bf16 is evaluated through f32
+            let input = inputs[0].cast_to::<f32>()?.into_owned().into_tvalue();
+            let output = self.eval(tvec!(input))?.remove(0).into_tensor();
+            let output = if output.datum_type() == DatumType::F32 {
+                output.cast_to_dt(DatumType::BF16)?.into_owned()
+            } else {
+                output
+            };
+            return Ok(tvec!(output.into_tvalue()));
+        }
         if let Some(_dt) = self.0.output_type(inputs[0].datum_type()) {
             Ok(tvec!(self.0.eval_out_of_place(&inputs[0], self.1)?.into_tvalue()))
         } else {
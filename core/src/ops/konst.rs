@@ -32,11 +32,15 @@ impl TypedOp for Const {
     fn change_axes(
         &self,
         _model: &TypedModel,
-        _node: &TypedNode,
+        node: &TypedNode,
         io: InOut,
         change: &AxisOp,
     ) -> TractResult<Option<AxisChangeConsequence>> {
         anyhow::ensure!(io == InOut::Out(0));
+        // per-axis quantization parameters live on the fact, a substitute op would lose them
+        if node.outputs[0].fact.per_axis_q.is_some() {
+            return Ok(None);
+        }
         let mut new_tensor = self.0.clone().into_tensor();
         if change.change_tensor(&mut new_tensor, false).is_ok() {
             Ok(Some(AxisChangeConsequence {
@@ -69,6 +73,11 @@ impl TypedOp for Const {
         } else {
             self.clone()
         };
-        target.wire_node(&node.name, op, &[])
+        let wire = target.wire_node(&node.name, op, &[])?;
+        if let Some(q) = &node.outputs[0].fact.per_axis_q {
+            let fact = target.outlet_fact(wire[0])?.clone().with_per_axis_q(q.clone());
+            target.set_outlet_fact(wire[0], fact)?;
+        }
+        Ok(wire)
     }
 }
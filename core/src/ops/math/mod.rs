@@ -220,12 +220,22 @@ bin_to_super_type!(rem, Rem,
                                       },
                                       [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64] => |c, a, b| *c = a.clone() % b);
 
+/// Like comparisons, except symbolic dimensions stay symbolic.
+fn operating_datum_type_for_min_max(a: DatumType, b: DatumType) -> TractResult<DatumType> {
+    if a == DatumType::TDim && b == DatumType::TDim {
+        Ok(DatumType::TDim)
+    } else {
+        super::logic::operating_datum_type_for_cmp(a, b)
+    }
+}
+
 bin_to_super_type!(min, Min, linalg:Min,
-                   operating_datum_type: super::logic::operating_datum_type_for_cmp,
+                   operating_datum_type: operating_datum_type_for_min_max,
                    q: [i8, u8, i32] => |c, a, b, _, _| *c = if a < b { *a } else { *b };
                    q_op_on_f32: |a: f32, b: f32| a.min(b),
                    [f16, f32, f64] => |c,a,b| *c = a.min(*b),
-                   [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.min(b));
+                   [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.min(b),
+                   [TDim] => |c, a, b| *c = a.clone().mini(b.clone()));
 
 bin_to_super_type!(max, Max,
                    eval_override: |a:TValue, b: TValue, c_dt: DatumType| -> TractResult<Tensor> {
@@ -267,11 +277,12 @@ bin_to_super_type!(max, Max,
                     Max.generic_eval(a, b, c_dt)
                    },
                    linalg:Max,
-                   operating_datum_type: super::logic::operating_datum_type_for_cmp,
+                   operating_datum_type: operating_datum_type_for_min_max,
                    q: [i8, u8, i32] => |c, a, b, _, _| *c = if a < b { *b } else { *a };
                    q_op_on_f32: |a: f32, b: f32| -> f32 {a.max(b)},
                    [f16, f32, f64] => |c,a,b| *c = a.max(*b),
-                   [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.max(b));
+                   [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.max(b),
+                   [TDim] => |c, a, b| *c = a.clone().maxi(b.clone()));
 
 bin_to_super_type!(pow, Pow,
                    declutter: declutter_pow,
@@ -654,7 +665,8 @@ element_wise!(tanh, Tanh,
  [f32] => |_, xs| { (tract_linalg::ops().tanh_f32)().run(xs) },
  [f64] => |_, xs| { xs.iter_mut().for_each(|x| *x = x.tanh()); Ok(()) };
  q: [i8, u8, i32] => f32::tanh;
- cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))};
+ declutter: crate::ops::nn::detect_gelu_approximate
 );
 
 element_wise!(erf, Erf,
@@ -665,7 +677,8 @@ element_wise!(erf, Erf,
      xs.iter_mut().zip(f32s.into_iter()).for_each(|(x, f)| *x = f16::from_f32(f));
      Ok(())
 };
- cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))};
+ declutter: crate::ops::nn::detect_gelu
 );
 
 element_wise!(acosh, Acosh, [f16, f32, f64] => |_, xs| {
@@ -703,6 +716,20 @@ mod tests {
     use super::*;
     use ndarray::arr2;
 
+    #[test]
+    fn min_max_of_dims() -> TractResult<()> {
+        let table = SymbolTable::default();
+        let s: TDim = table.sym("S").into();
+        table.add_assertion("S >= 1")?;
+        let a = tensor1(&[s.clone(), s.clone() * 2]);
+        let b = tensor1(&[TDim::from(512), s.clone()]);
+        let c = min().eval(tvec!(a.clone().into_tvalue(), b.clone().into_tvalue()))?;
+        assert_eq!(*c[0], tensor1(&[s.clone().mini(512.into()), s.clone()]));
+        let c = max().eval(tvec!(a.into_tvalue(), b.into_tvalue()))?;
+        assert_eq!(*c[0], tensor1(&[s.clone().maxi(512.into()), s * 2]));
+        Ok(())
+    }
+
     #[test]
     fn test_mul() {
         let a = arr2(&[[1., 2.], [3., 4.]]);
@@ -760,4 +787,54 @@ mod tests {
         assert!(op.0.downcast_ref::<ShiftRight>().is_some());
         Ok(())
     }
+
+    /// Run the model, recording the buffer each op output lives in.
+    fn run_with_buffers(model: &TypedModel, input: Tensor) -> TractResult<(Tensor, Vec<usize>)> {
+        let plan = SimplePlan::new(model)?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut buffers = vec![];
+        let outputs =
+            state.run_plan_with_eval(tvec!(input.into()), |session, op_state, node, inputs| {
+                let outputs = crate::plan::eval(session, op_state, node, inputs)?;
+                if !node.op_is::<crate::ops::source::TypedSource>() {
+                    buffers.extend(
+                        outputs.iter().map(|o| unsafe { o.as_ptr_unchecked::<u8>() as usize }),
+                    );
+                }
+                TractResult::Ok(outputs)
+            })?;
+        Ok((outputs[0].clone().into_tensor(), buffers))
+    }
+
+    #[test]
+    fn binary_chain_computes_in_place() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([4usize]))?;
+        let one = model.add_const("one", tensor1(&[1f32; 4]))?;
+        let a = model.wire_node("a", add(), &[x, one])?[0];
+        let b = model.wire_node("b", mul(), &[a, x])?[0];
+        let c = model.wire_node("c", sub(), &[x, b])?[0];
+        let d = model.wire_node("d", div(), &[c, x])?[0];
+        model.set_output_outlets(&[d])?;
+        let (result, buffers) = run_with_buffers(&model, tensor1(&[1f32, 2., 3., 4.]))?;
+        assert_eq!(result, tensor1(&[-1f32, -2., -3., -4.]));
+        // only a allocates: b, c and d reuse the buffer of their sole-referenced operand
+        assert_eq!(buffers.iter().unique().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn shared_values_are_not_computed_over() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([4usize]))?;
+        let one = model.add_const("one", tensor1(&[1f32; 4]))?;
+        let a = model.wire_node("a", add(), &[x, one])?[0];
+        let b = model.wire_node("b", mul(), &[a, a])?[0];
+        let c = model.wire_node("c", add(), &[b, a])?[0];
+        model.set_output_outlets(&[c])?;
+        let (result, buffers) = run_with_buffers(&model, tensor1(&[1f32, 2., 3., 4.]))?;
+        assert_eq!(result, tensor1(&[6f32, 12., 20., 30.]));
+        assert_eq!(buffers.iter().unique().count(), 2);
+        Ok(())
+    }
 }
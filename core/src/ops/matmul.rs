@@ -1,3 +1,4 @@
+pub mod block_quant;
 pub mod lir_unary;
 pub mod mir_quant;
 pub mod pack;
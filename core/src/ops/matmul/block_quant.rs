@@ -0,0 +1,190 @@
+use crate::internal::*;
+use crate::ops::matmul::lir_unary::{
+    AddMatMulGeometry, LirMatMulUnary, MapOutputAxisToInput, ProtoFusedSpec,
+};
+use crate::ops::matmul::pack::MatMatMulPack;
+use tract_linalg::block_quant::{BlockQuantValue, PackedBlockQuant};
+use tract_linalg::frame::Packer;
+use tract_linalg::mmm::MMMInput;
+use tract_ndarray::Ix2;
+
+/// Product of activations by block-quantized weights.
+///
+/// Inputs are the weights, a constant scalar `Opaque` tensor holding a [BlockQuantValue] of
+/// shape `[m, k]`, and the activations of shape `[.., k]`. The output has shape `[.., m]`.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct BlockQuantMatMul;
+
+pub fn block_quant_value(fact: &TypedFact) -> TractResult<&BlockQuantValue> {
+    ensure!(fact.datum_type == Opaque::datum_type() && fact.shape.volume().is_one());
+    fact.konst
+        .as_ref()
+        .and_then(|k| k.to_scalar::<Opaque>().ok())
+        .and_then(|o| o.downcast_ref::<BlockQuantValue>())
+        .context("Expected constant block quantized weights")
+}
+
+impl Op for BlockQuantMatMul {
+    fn name(&self) -> Cow<str> {
+        "BlockQuantMatMul".into()
+    }
+
+    op_as_typed_op!();
+    impl_op_same_as!();
+}
+
+impl EvalOp for BlockQuantMatMul {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (weights, input) = args_2!(inputs);
+        let weights = weights
+            .to_scalar::<Opaque>()?
+            .downcast_ref::<BlockQuantValue>()
+            .context("Expected block quantized weights")?;
+        let dt = input.datum_type();
+        let mut shape: TVec<usize> = input.shape().into();
+        let rows = input.len() / weights.k;
+        let input = input.cast_to::<f32>()?.into_owned().into_shape(&[rows, weights.k])?;
+        let a = weights.dequant_f32()?;
+        let a = a.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let x = input.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let output = x.dot(&a.t()).into_tensor();
+        *shape.last_mut().unwrap() = weights.m;
+        Ok(tvec!(output.into_shape(&shape)?.cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl TypedOp for BlockQuantMatMul {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 2, "BlockQuantMatMul expects weights and input");
+        let weights = block_quant_value(inputs[0])?;
+        let input = inputs[1];
+        ensure!(input.datum_type.is_float(), "BlockQuantMatMul expects float input");
+        ensure!(input.rank() >= 1 && input.shape[input.rank() - 1] == weights.k.to_dim());
+        let mut shape = input.shape.to_tvec();
+        *shape.last_mut().unwrap() = weights.m.to_dim();
+        Ok(tvec!(input.datum_type.fact(shape)))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let weights = block_quant_value(inputs[0])?;
+        Ok(tvec!((Cost::FMA(inputs[1].datum_type), inputs[1].shape.volume() * weights.m)))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let input_facts = model.node_input_facts(node.id)?;
+        let weights = block_quant_value(input_facts[0])?;
+        let dt = input_facts[1].datum_type;
+        if dt != f32::datum_type() && dt != f16::datum_type() {
+            return Ok(None);
+        }
+        let rank = input_facts[1].rank();
+        let n = if rank > 1 { input_facts[1].shape[rank - 2].to_usize().ok() } else { Some(1) };
+        let Some(mmm) = tract_linalg::ops().mmm(dt, dt, dt, Some(weights.m), Some(weights.k), n)
+        else {
+            return Ok(None);
+        };
+        let Some((packing, (a_pack, b_pack))) =
+            mmm.packings().iter().enumerate().find_map(|(ix, (a, b))| {
+                let a = a.downcast_ref::<Packer>().filter(|p| p.dt == dt)?;
+                let b = b.downcast_ref::<Packer>().filter(|p| p.dt == dt)?;
+                Some((ix, (a.clone(), b.clone())))
+            })
+        else {
+            return Ok(None);
+        };
+
+        let name = &node.name;
+        let mut patch = TypedModelPatch::new("BlockQuantMatMul to LirMatMulUnary");
+        let packed: Box<dyn MMMInput> = Box::new(PackedBlockQuant::new(weights.clone(), &a_pack)?);
+        let pa =
+            patch.add_const(format!("{name}.packed_weights"), tensor0(Opaque::from(packed)))?;
+        let mut b = patch.tap_model(model, node.inputs[1])?;
+        if rank == 1 {
+            b = patch.wire_node(format!("{name}.add_n"), AxisOp::Add(0), &[b])?[0];
+        }
+        let c_rank = rank.max(2);
+        let pack_b = MatMatMulPack { packer: b_pack, k_axis: c_rank - 1, mn_axis: c_rank - 2 };
+        let pb = patch.wire_node(format!("{name}.pack_b"), pack_b, &[b])?[0];
+
+        let b_fact = patch.outlet_fact(b)?.clone();
+        let c_to_b_axis_mapping =
+            (0..c_rank - 2).filter(|&ax| !b_fact.shape[ax].is_one()).map(|ax| (ax, ax)).collect();
+        let mut c_shape = b_fact.shape.to_tvec();
+        c_shape[c_rank - 1] = weights.m.to_dim();
+        let c_fact = dt.fact(c_shape);
+        let geo = AddMatMulGeometry {
+            k: weights.k.to_dim(),
+            mmm: mmm.clone(),
+            c_to_a_axis_mapping: MapOutputAxisToInput(tvec!()),
+            c_to_b_axis_mapping: MapOutputAxisToInput(c_to_b_axis_mapping),
+        };
+        let output = unsafe { mmm.c_view(c_rank - 1, c_rank - 2) };
+        let lir = LirMatMulUnary::new(
+            mmm,
+            c_fact,
+            c_rank - 1,
+            c_rank - 2,
+            vec![
+                ProtoFusedSpec::AddMatMul { geo, a: 0, b: 1, packing },
+                ProtoFusedSpec::Store(output),
+            ],
+        )
+        .context("Creating LirMatMulUnary")?;
+        let mut wire = patch.wire_node(format!("{name}.lir"), lir, &[pa, pb])?[0];
+        if rank == 1 {
+            wire = patch.wire_node(format!("{name}.rm_n"), AxisOp::Rm(0), &[wire])?[0];
+        }
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_linalg::block_quant::{BlockQuant, Q4_0, Q8_0};
+
+    fn check(format: Box<dyn BlockQuant>, input_shape: &[usize]) -> TractResult<()> {
+        let (m, k) = (13, 64);
+        let w: Vec<f32> = (0..m * k).map(|i| ((i * 31) % 17) as f32 / 17.0 - 0.5).collect();
+        let bq = BlockQuantValue::quant_f32(format, &Tensor::from_shape(&[m, k], &w)?)?;
+        let mut model = TypedModel::default();
+        let weights = model.add_const("weights", tensor0(Opaque(Arc::new(bq))))?;
+        let input = model.add_source("input", f32::fact(input_shape))?;
+        let output = model.wire_node("mm", BlockQuantMatMul, &[weights, input])?;
+        model.set_output_outlets(&output)?;
+        let len = input_shape.iter().product::<usize>();
+        let x =
+            Tensor::from_shape(input_shape, &(0..len).map(|x| (x % 7) as f32).collect::<Vec<_>>())?;
+        let expected = model.clone().into_runnable()?.run(tvec!(x.clone().into_tvalue()))?;
+        let optimized = model.into_optimized()?;
+        assert!(optimized.nodes().iter().any(|n| n.op_is::<LirMatMulUnary>()));
+        let found = optimized.into_runnable()?.run(tvec!(x.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Close)
+    }
+
+    #[test]
+    fn q4_0_matrix() -> TractResult<()> {
+        check(Box::new(Q4_0), &[5, 64])
+    }
+
+    #[test]
+    fn q8_0_batched() -> TractResult<()> {
+        check(Box::new(Q8_0), &[2, 3, 64])
+    }
+
+    #[test]
+    fn q4_0_vector() -> TractResult<()> {
+        check(Box::new(Q4_0), &[64])
+    }
+}
@@ -285,6 +285,10 @@ impl EvalOp for LirMatMulUnary {
         true
     }
 
+    fn uses_planned_outputs(&self) -> bool {
+        true
+    }
+
     fn eval_with_session(
         &self,
         session: &SessionState,
@@ -304,7 +308,7 @@ impl EvalOp for LirMatMulUnary {
             if self.trivial_path {
                 let c_shape = self.c_fact.shape.as_concrete().unwrap_unchecked();
                 let geometry = self.geometry.as_concrete().unwrap_unchecked();
-                let mut c = Tensor::uninitialized_dt(self.c_fact.datum_type, c_shape)?;
+                let mut c = session.uninitialized_output(0, self.c_fact.datum_type, c_shape)?;
                 let uops: Vec<FusedSpec> =
                     self.micro_ops.iter().map(|o| o.resolve_trivial(&inputs, &mut c)).collect();
                 self.mmm.run_with_scratch_space(geometry.m, geometry.n, scratch.as_mut(), &uops)?;
@@ -312,7 +316,7 @@ impl EvalOp for LirMatMulUnary {
             } else {
                 let geometry = self.geometry.to_concrete(&session.resolved_symbols)?;
                 let c_shape = self.c_fact.shape.eval_to_usize(&session.resolved_symbols)?;
-                let c = Tensor::uninitialized_dt(self.c_fact.datum_type, &c_shape)?;
+                let c = session.uninitialized_output(0, self.c_fact.datum_type, &c_shape)?;
                 let mut uops = vec![FusedSpec::ShiftLeft(0); self.micro_ops.len()];
                 let mut looping_shape: TVec<usize> = c_shape.to_smallvec();
                 looping_shape[self.c_m_axis] = 1;
@@ -84,6 +84,10 @@ pub(crate) fn combine_scales(
     Ok(abc_scale)
 }
 
+/// Wires the zero point compensation of an integer matmul result.
+///
+/// `a0` and `b0` can be scalars or per-axis tensors. Per-axis zero points must be shaped so that
+/// they broadcast against `result` when aligned to the right.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn compensate_zero_points(
     model: &mut TypedModel,
@@ -6,9 +6,9 @@ use tract_linalg::frame::Packer;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MatMatMulPack {
-    pub(crate) packer: Packer,
-    pub(crate) k_axis: usize,
-    pub(crate) mn_axis: usize,
+    pub packer: Packer,
+    pub k_axis: usize,
+    pub mn_axis: usize,
 }
 
 impl Op for MatMatMulPack {
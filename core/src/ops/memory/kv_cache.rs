@@ -0,0 +1,334 @@
+use crate::internal::*;
+
+/// Key/value cache for autoregressive decoding.
+///
+/// Appends its input to a buffer kept in the op state along `axis`, and outputs the full
+/// content of the cache. `past` is the cache length before the append, so the output length
+/// along `axis` is `past + input length`. The buffer is preallocated for `max_len` positions
+/// when it can be evaluated, and grows geometrically otherwise.
+///
+/// The output borrows the state memory instead of being allocated for each turn. When all axes
+/// before `axis` have dimension 1, it is a view over the buffer itself. Otherwise the valid
+/// prefix is strided in the buffer, and is compacted in a staging tensor reused from one turn to
+/// the next. Outputs still alive when the cache is appended to again are left untouched: the
+/// buffer is copied instead of being overwritten.
+///
+/// Caches can be truncated or reset through [crate::plan::SimpleState].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KvCache {
+    pub id: String,
+    pub axis: usize,
+    pub past: Symbol,
+    pub max_len: Option<TDim>,
+}
+
+impl Op for KvCache {
+    fn name(&self) -> Cow<str> {
+        "KvCache".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = vec![format!("id: {:?}, axis: {}, past: {}", self.id, self.axis, self.past)];
+        if let Some(max_len) = &self.max_len {
+            info.push(format!("max len: {max_len}"));
+        }
+        Ok(info)
+    }
+
+    impl_op_same_as!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for KvCache {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(KvCacheState {
+            axis: self.axis,
+            buffer: None,
+            staging: None,
+            output: None,
+            len: 0,
+        })))
+    }
+}
+
+impl TypedOp for KvCache {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 1, "Expected one input (new keys or values) for KvCache op");
+        ensure!(self.axis < inputs[0].rank());
+        ensure!(inputs[0].datum_type.is_copy(), "KvCache only supports plain datum types");
+        let mut shape = inputs[0].shape.to_tvec();
+        let len = self.past.to_dim() + &shape[self.axis];
+        shape[self.axis] = len;
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KvCacheState {
+    axis: usize,
+    buffer: Option<Arc<Tensor>>,
+    staging: Option<Arc<Tensor>>,
+    /// Kept during the turn so that consumers can not unwrap the view and write through it.
+    output: Option<Arc<Tensor>>,
+    len: usize,
+}
+
+impl KvCacheState {
+    /// Number of positions currently in the cache.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of positions the cache can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buffer.as_ref().map(|b| b.shape()[self.axis]).unwrap_or(0)
+    }
+
+    /// Empty the cache, keeping its allocation.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    /// Keep only the first `len` positions.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    fn reserve(
+        &mut self,
+        op: &KvCache,
+        session: &SessionState,
+        input: &Tensor,
+        needed: usize,
+    ) -> TractResult<()> {
+        let max_len =
+            op.max_len.as_ref().and_then(|max| max.eval(&session.resolved_symbols).to_usize().ok());
+        if let Some(max_len) = max_len {
+            ensure!(
+                needed <= max_len,
+                "KvCache {:?} overflow: {} positions needed, max is {}",
+                op.id,
+                needed,
+                max_len
+            );
+        }
+        let capacity = if let Some(buffer) = &self.buffer {
+            ensure!(
+                buffer.datum_type() == input.datum_type(),
+                "KvCache {:?} holds {:?}, got {:?}",
+                op.id,
+                buffer.datum_type(),
+                input.datum_type()
+            );
+            ensure!(
+                buffer.rank() == input.rank()
+                    && (0..input.rank())
+                        .all(|ax| ax == op.axis || buffer.shape()[ax] == input.shape()[ax]),
+                "KvCache {:?} holds {:?}, can not append {:?} on axis {}",
+                op.id,
+                buffer.shape(),
+                input.shape(),
+                op.axis
+            );
+            buffer.shape()[op.axis]
+        } else {
+            0
+        };
+        let new_capacity = if needed <= capacity {
+            if self.buffer.as_mut().is_some_and(|b| Arc::get_mut(b).is_some()) {
+                return Ok(());
+            }
+            // previous outputs still borrow the buffer: copy it instead of overwriting them
+            capacity
+        } else {
+            max_len.unwrap_or_else(|| needed.next_power_of_two().max(2 * capacity))
+        };
+        let mut shape: TVec<usize> = input.shape().into();
+        shape[op.axis] = new_capacity;
+        let mut buffer = unsafe { Tensor::uninitialized_dt(input.datum_type(), &shape)? };
+        if let Some(previous) = self.buffer.take() {
+            if self.len > 0 {
+                buffer.assign_slice(0..self.len, &previous, 0..self.len, op.axis)?;
+            }
+        }
+        self.buffer = Some(Arc::new(buffer));
+        Ok(())
+    }
+
+    /// The valid content of the cache, as a tensor borrowing the buffer or the staging tensor.
+    fn output(&mut self) -> TractResult<TValue> {
+        let KvCacheState { axis, buffer, staging, output, len } = self;
+        let buffer = buffer.as_ref().context("KvCache buffer not allocated")?;
+        let dt = buffer.datum_type();
+        let capacity = buffer.shape()[*axis];
+        let mut shape: TVec<usize> = buffer.shape().into();
+        shape[*axis] = *len;
+        let outer = shape[..*axis].iter().product::<usize>();
+        let slab = shape[*axis + 1..].iter().product::<usize>() * dt.size_of();
+        let source = if outer == 1 {
+            buffer.clone()
+        } else {
+            if !staging
+                .as_mut()
+                .is_some_and(|s| s.shape() == buffer.shape() && Arc::get_mut(s).is_some())
+            {
+                *staging = Some(Arc::new(unsafe { Tensor::uninitialized_dt(dt, buffer.shape())? }));
+            }
+            let target = Arc::get_mut(staging.as_mut().unwrap()).unwrap();
+            unsafe {
+                let from = buffer.as_bytes().as_ptr();
+                let to = target.as_bytes_mut().as_mut_ptr();
+                for o in 0..outer {
+                    std::ptr::copy_nonoverlapping(
+                        from.add(o * capacity * slab),
+                        to.add(o * *len * slab),
+                        *len * slab,
+                    );
+                }
+            }
+            staging.clone().unwrap()
+        };
+        let data = source.as_bytes().as_ptr() as *mut u8;
+        let owner: Arc<dyn std::any::Any + Send + Sync> = source;
+        // the buffer is only written to when no output borrows it anymore
+        let view = unsafe {
+            Tensor::from_raw_parts_owned_by(dt, &shape, data, outer * *len * slab, owner)?
+        };
+        let view = Arc::new(view);
+        *output = Some(view.clone());
+        Ok(TValue::from_const(view))
+    }
+}
+
+impl OpState for KvCacheState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let op = op.downcast_ref::<KvCache>().context("Wrong op for KvCacheState")?;
+        let input = args_1!(inputs);
+        self.output = None;
+        let past = self.len;
+        let len = past + input.shape()[op.axis];
+        self.reserve(op, session, &input, len)?;
+        let buffer = self.buffer.as_mut().and_then(Arc::get_mut).unwrap();
+        buffer.assign_slice(past..len, &input, .., op.axis)?;
+        self.len = len;
+        session.resolved_symbols.set(&op.past, past as i64);
+        Ok(tvec!(self.output()?))
+    }
+}
+
+trivial_op_state_freeeze!(KvCacheState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(batch: usize, max_len: Option<TDim>) -> TractResult<(TypedModel, Symbol)> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let p = model.symbol_table.sym("P");
+        let source = model.add_source("input", f32::fact(dims!(batch, s, 3)))?;
+        let op = KvCache { id: "k".into(), axis: 1, past: p.clone(), max_len };
+        let output = model.wire_node("cache", op, &[source])?;
+        model.set_output_outlets(&output)?;
+        Ok((model, p))
+    }
+
+    fn chunk(batch: usize, len: usize, start: usize) -> TValue {
+        Tensor::from_shape(
+            &[batch, len, 3],
+            &(start..start + batch * 3 * len).map(|x| x as f32).collect::<Vec<_>>(),
+        )
+        .unwrap()
+        .into_tvalue()
+    }
+
+    #[test]
+    fn appends_across_turns() -> TractResult<()> {
+        let (model, p) = model(2, None)?;
+        let mut state = SimpleState::new(model.into_runnable()?)?;
+        let first = chunk(2, 3, 0);
+        let second = chunk(2, 1, 100);
+        let out = state.run(tvec!(first.clone()))?.remove(0);
+        assert_eq!(out.shape(), &[2, 3, 3]);
+        let out = state.run(tvec!(second.clone()))?.remove(0);
+        assert_eq!(out.shape(), &[2, 4, 3]);
+        assert_eq!(state.session_state.resolved_symbols[&p], Some(3));
+        let expected = Tensor::stack_tensors(1, &[first.into_tensor(), second.into_tensor()])?;
+        assert_eq!(*out, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn truncate_and_reset() -> TractResult<()> {
+        let (model, _) = model(2, Some(16.to_dim()))?;
+        let mut state = SimpleState::new(model.into_runnable()?)?;
+        state.run(tvec!(chunk(2, 5, 0)))?;
+        state.truncate_kv_caches(2)?;
+        let out = state.run(tvec!(chunk(2, 1, 0)))?.remove(0);
+        assert_eq!(out.shape(), &[2, 3, 3]);
+        assert_eq!(state.kv_caches().next().unwrap().capacity(), 16);
+        state.reset_kv_caches()?;
+        let out = state.run(tvec!(chunk(2, 2, 0)))?.remove(0);
+        assert_eq!(out.shape(), &[2, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn outputs_borrow_the_state() -> TractResult<()> {
+        for batch in [1, 2] {
+            let (model, _) = model(batch, Some(8.to_dim()))?;
+            let mut state = SimpleState::new(model.into_runnable()?)?;
+            let mut chunks = vec![];
+            let mut addresses = std::collections::HashSet::new();
+            for turn in 0..4 {
+                chunks.push(chunk(batch, 1, 100 * turn).into_tensor());
+                let out = state.run(tvec!(chunks.last().unwrap().clone().into_tvalue()))?.remove(0);
+                assert_eq!(*out, Tensor::stack_tensors(1, &chunks)?);
+                addresses.insert(out.as_bytes().as_ptr() as usize);
+            }
+            // every turn output lives at the same place in the state memory
+            assert_eq!(addresses.len(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn live_outputs_are_not_overwritten() -> TractResult<()> {
+        let (model, _) = model(1, Some(8.to_dim()))?;
+        let mut state = SimpleState::new(model.into_runnable()?)?;
+        let first = state.run(tvec!(chunk(1, 2, 0)))?.remove(0);
+        let snapshot = first.clone().into_tensor();
+        state.truncate_kv_caches(0)?;
+        state.run(tvec!(chunk(1, 2, 100)))?;
+        assert_eq!(first.into_tensor(), snapshot);
+        Ok(())
+    }
+
+    #[test]
+    fn overflow() -> TractResult<()> {
+        let (model, _) = model(2, Some(4.to_dim()))?;
+        let mut state = SimpleState::new(model.into_runnable()?)?;
+        state.run(tvec!(chunk(2, 3, 0)))?;
+        assert!(state.run(tvec!(chunk(2, 2, 0))).is_err());
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
 pub mod force_eval;
+pub mod kv_cache;
 pub mod load;
 pub mod store;
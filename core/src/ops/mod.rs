@@ -114,6 +114,12 @@ pub trait EvalOp {
     }
 
     fn is_stateless(&self) -> bool;
+
+    /// Whether the op allocates its outputs with `SessionState::uninitialized_output`, letting
+    /// the plan place them in its memory arena.
+    fn uses_planned_outputs(&self) -> bool {
+        false
+    }
 }
 
 /// A base operation
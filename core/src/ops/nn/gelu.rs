@@ -0,0 +1,242 @@
+use crate::internal::*;
+use crate::ops::binary::{BinMiniOp, TypedBinOp};
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::math::{Add, Mul, Pow, Square};
+
+/// √(2/π), the scale inside the tanh approximation of Gelu.
+const SQRT_2_OVER_PI: f32 = 0.797_884_6;
+/// Cubic coefficient of the tanh approximation of Gelu.
+const GELU_CUBIC: f32 = 0.044715;
+
+element_wise!(gelu, Gelu,
+ [f16] => |_, xs| { (tract_linalg::ops().gelu_f16)().run(xs) },
+ [f32] => |_, xs| { (tract_linalg::ops().gelu_f32)().run(xs) };
+ cost: |dt| {tvec!((Cost::FMA(dt), 14), (Cost::Div(dt), 1))}
+);
+
+fn sgelu_approximate(x: f32) -> f32 {
+    0.5 * x * (1.0 + (SQRT_2_OVER_PI * (x + GELU_CUBIC * x * x * x)).tanh())
+}
+
+element_wise!(gelu_approximate, GeluApproximate,
+ [f16] => |_, xs| {
+     xs.iter_mut().for_each(|x| *x = f16::from_f32(sgelu_approximate(x.to_f32())));
+     Ok(())
+ },
+ [f32] => |_, xs| {
+     xs.iter_mut().for_each(|x| *x = sgelu_approximate(*x));
+     Ok(())
+ };
+ cost: |dt| {tvec!((Cost::FMA(dt), 16), (Cost::Div(dt), 1))}
+);
+
+/// If `node` is a binary `Op` with one uniform input close to `value`, return the other input.
+pub(crate) fn binary_with_uniform<Op: BinMiniOp>(
+    model: &TypedModel,
+    node: &TypedNode,
+    value: f32,
+) -> TractResult<Option<OutletId>> {
+    let Some(bin) = node.op_as::<TypedBinOp>() else { return Ok(None) };
+    if !bin.0.is::<Op>() {
+        return Ok(None);
+    }
+    let Some(uniform) = crate::ops::binary::one_input_is_uniform(model, node)? else {
+        return Ok(None);
+    };
+    if model.outlet_fact(uniform.var)?.shape != node.outputs[0].fact.shape {
+        return Ok(None);
+    }
+    let found = uniform.uni.cast_to_scalar::<f32>()?;
+    Ok(((found - value).abs() <= 1e-4 * value.abs().max(1.0)).then_some(uniform.var))
+}
+
+fn other_input(node: &TypedNode, outlet: OutletId) -> Option<OutletId> {
+    match &*node.inputs {
+        [a, b] if *a == outlet => Some(*b),
+        [a, b] if *b == outlet => Some(*a),
+        _ => None,
+    }
+}
+
+/// Fold 0.5 · x · (1 + erf(x / √2)) into Gelu. Called on the Erf node.
+pub(crate) fn detect_gelu(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let dt = node.outputs[0].fact.datum_type;
+    if dt != f32::datum_type() && dt != f16::datum_type() {
+        return Ok(None);
+    }
+    let Some(scaled) = model.single_prec(node.id)? else { return Ok(None) };
+    let Some(x) = binary_with_uniform::<Mul>(model, scaled, std::f32::consts::FRAC_1_SQRT_2)?
+    else {
+        return Ok(None);
+    };
+    fold_gelu_tail(model, node, x, "Fold Gelu", gelu())
+}
+
+/// Fold 0.5 · x · (1 + tanh(√(2/π) · (x + 0.044715 · x³))) into GeluApproximate. Called on the
+/// Tanh node.
+pub(crate) fn detect_gelu_approximate(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let dt = node.outputs[0].fact.datum_type;
+    if dt != f32::datum_type() && dt != f16::datum_type() {
+        return Ok(None);
+    }
+    let Some(scaled) = model.single_prec(node.id)? else { return Ok(None) };
+    let Some(inner) = binary_with_uniform::<Mul>(model, scaled, SQRT_2_OVER_PI)? else {
+        return Ok(None);
+    };
+    let inner = model.node(inner.node);
+    if !inner.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Add>()) || inner.inputs.len() != 2 {
+        return Ok(None);
+    }
+    for (x, cubic) in [(inner.inputs[0], inner.inputs[1]), (inner.inputs[1], inner.inputs[0])] {
+        let Some(cube) = binary_with_uniform::<Mul>(model, model.node(cubic.node), GELU_CUBIC)?
+        else {
+            continue;
+        };
+        if is_cube_of(model, cube, x)? {
+            return fold_gelu_tail(model, node, x, "Fold GeluApproximate", gelu_approximate());
+        }
+    }
+    Ok(None)
+}
+
+/// Is `outlet` x³, either as Pow(x, 3) or as a product of x and x² (itself x · x or Square(x)).
+fn is_cube_of(model: &TypedModel, outlet: OutletId, x: OutletId) -> TractResult<bool> {
+    let node = model.node(outlet.node);
+    if binary_with_uniform::<Pow>(model, node, 3.0)? == Some(x) {
+        return Ok(true);
+    }
+    if !node.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>()) {
+        return Ok(false);
+    }
+    let Some(other) = other_input(node, x) else { return Ok(false) };
+    let square = model.node(other.node);
+    let is_square = if square.op_as::<ElementWiseOp>().is_some_and(|e| e.0.is::<Square>()) {
+        square.inputs[0] == x
+    } else {
+        square.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>())
+            && square.inputs.len() == 2
+            && square.inputs[0] == x
+            && square.inputs[1] == x
+    };
+    Ok(is_square)
+}
+
+/// Match (1 + act) · x · 0.5 after the activation `node`, in any of the usual association
+/// orders, and replace it by `op` applied to `x`.
+fn fold_gelu_tail(
+    model: &TypedModel,
+    node: &TypedNode,
+    x: OutletId,
+    name: &str,
+    op: ElementWiseOp,
+) -> TractResult<Option<TypedModelPatch>> {
+    let Some(plus_one) = super::sole_succ(model, node.id) else { return Ok(None) };
+    if binary_with_uniform::<Add>(model, plus_one, 1.0)? != Some(node.id.into()) {
+        return Ok(None);
+    }
+    let Some(m1) = super::sole_succ(model, plus_one.id) else { return Ok(None) };
+    if !m1.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>()) {
+        return Ok(None);
+    }
+    let Some(other) = other_input(m1, plus_one.id.into()) else { return Ok(None) };
+    let output = if other == x {
+        // (x * (1 + act)) * 0.5
+        let Some(m2) = super::sole_succ(model, m1.id) else { return Ok(None) };
+        if binary_with_uniform::<Mul>(model, m2, 0.5)? != Some(m1.id.into()) {
+            return Ok(None);
+        }
+        m2
+    } else if binary_with_uniform::<Mul>(model, model.node(other.node), 0.5)? == Some(x) {
+        // (x * 0.5) * (1 + act)
+        m1
+    } else if binary_with_uniform::<Mul>(model, m1, 0.5)? == Some(plus_one.id.into()) {
+        // x * ((1 + act) * 0.5)
+        let Some(m2) = super::sole_succ(model, m1.id) else { return Ok(None) };
+        if !m2.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>())
+            || other_input(m2, m1.id.into()) != Some(x)
+        {
+            return Ok(None);
+        }
+        m2
+    } else {
+        return Ok(None);
+    };
+    if output.outputs[0].fact.shape != model.outlet_fact(x)?.shape {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::new(name);
+    let wire = patch.tap_model(model, x)?;
+    let wire = patch.wire_node(&output.name, op, &[wire])?[0];
+    patch.shunt_outside(model, output.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math::{add, erf, mul, tanh};
+
+    #[test]
+    fn fold_onnx_gelu() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 7]))?;
+        let inv_sqrt2 =
+            model.add_const("inv_sqrt2", tensor2(&[[std::f32::consts::FRAC_1_SQRT_2]]))?;
+        let one = model.add_const("one", tensor2(&[[1f32]]))?;
+        let half = model.add_const("half", tensor2(&[[0.5f32]]))?;
+        let w = model.wire_node("scale", mul(), &[x, inv_sqrt2])?;
+        let w = model.wire_node("erf", erf(), &w)?;
+        let w = model.wire_node("add", add(), &[w[0], one])?;
+        let w = model.wire_node("mul", mul(), &[x, w[0]])?;
+        let w = model.wire_node("gelu", mul(), &[w[0], half])?;
+        model.set_output_outlets(&w)?;
+        let input =
+            tensor2(&[[-3f32, -2., -1., 0., 1., 2., 3.], [0.5, -0.5, 4., -4., 0.1, 8., -8.]]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let model = model.into_decluttered()?;
+        assert!(model
+            .nodes()
+            .iter()
+            .any(|n| n.op_as::<ElementWiseOp>().is_some_and(|e| e.0.is::<Gelu>())));
+        assert!(!model.nodes().iter().any(|n| n.op_is::<TypedBinOp>()));
+        let found = model.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+
+    #[test]
+    fn fold_tanh_gelu() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 7]))?;
+        let cubic = model.add_const("cubic_coef", tensor2(&[[GELU_CUBIC]]))?;
+        let scale = model.add_const("scale", tensor2(&[[SQRT_2_OVER_PI]]))?;
+        let one = model.add_const("one", tensor2(&[[1f32]]))?;
+        let half = model.add_const("half", tensor2(&[[0.5f32]]))?;
+        let w = model.wire_node("x2", mul(), &[x, x])?;
+        let w = model.wire_node("x3", mul(), &[w[0], x])?;
+        let w = model.wire_node("cubic", mul(), &[cubic, w[0]])?;
+        let w = model.wire_node("inner", add(), &[x, w[0]])?;
+        let w = model.wire_node("scaled", mul(), &[scale, w[0]])?;
+        let w = model.wire_node("tanh", tanh(), &w)?;
+        let w = model.wire_node("one_plus", add(), &[one, w[0]])?;
+        let w = model.wire_node("times_x", mul(), &[x, w[0]])?;
+        let w = model.wire_node("gelu", mul(), &[half, w[0]])?;
+        model.set_output_outlets(&w)?;
+        let input =
+            tensor2(&[[-3f32, -2., -1., 0., 1., 2., 3.], [0.5, -0.5, 4., -4., 0.1, 8., -8.]]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let model = model.into_decluttered()?;
+        assert!(model
+            .nodes()
+            .iter()
+            .any(|n| n.op_as::<ElementWiseOp>().is_some_and(|e| e.0.is::<GeluApproximate>())));
+        assert!(!model.nodes().iter().any(|n| n.op_is::<TypedBinOp>()));
+        let found = model.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+}
@@ -1,20 +1,41 @@
 mod data_formats;
+mod gelu;
 mod reduce;
+mod rms_norm;
+mod sdpa;
+mod silu;
 mod softmax;
 
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape, SymDataShape};
+pub use self::gelu::{gelu, gelu_approximate, Gelu, GeluApproximate};
+pub(crate) use self::gelu::{detect_gelu, detect_gelu_approximate};
 pub use self::reduce::{Reduce, Reducer, expand_mean_of_squares};
+pub use self::rms_norm::RmsNorm;
+pub use self::sdpa::{FlashSdpa, ScaledDotProductAttention};
+pub use self::silu::{silu, Silu};
 pub use self::softmax::{Softmax, SoftmaxExp};
 
 pub use crate::internal::*;
 
 use tract_num_traits::AsPrimitive;
 
+/// The only consumer of `id`'s single output. Unlike `Graph::single_succ`, the consumer may have
+/// other inputs, as binary ops in activation patterns do.
+pub(crate) fn sole_succ(model: &TypedModel, id: usize) -> Option<&TypedNode> {
+    let node = model.node(id);
+    if node.outputs.len() != 1 {
+        return None;
+    }
+    let [succ] = &*node.outputs[0].successors else { return None };
+    Some(model.node(succ.node))
+}
+
 element_wise!(sigmoid, Sigmoid,
  [f16] => |_, xs| { (tract_linalg::ops().sigmoid_f16)().run(xs) },
  [f32] => |_, xs| { (tract_linalg::ops().sigmoid_f32)().run(xs) };
  q: [i8, u8, i32, i32] => |x: f32| 1.0 / (1.0+(-x).exp());
- cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))};
+ declutter: silu::detect_silu
 );
 
 element_wise!(hard_swish, HardSwish,
@@ -334,6 +334,9 @@ impl TypedOp for Reduce {
         model: &TypedModel,
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if self.reducer == Reducer::MeanOfSquares {
+            return super::rms_norm::detect_rms_norm(self, model, node);
+        }
         if self.reducer == Reducer::Sum {
             let Some(prec) = model.single_prec(node.id)? else { return Ok(None) };
             let Some(prec_ew) = prec.op_as::<ElementWiseOp>() else { return Ok(None) };
@@ -0,0 +1,171 @@
+use crate::internal::*;
+use crate::ops::binary::TypedBinOp;
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::math::{Add, Mul, Rsqrt};
+use tract_ndarray::Axis;
+
+use super::{Reduce, Reducer};
+
+/// Root mean square normalization: x / sqrt(mean(x², axis) + eps).
+///
+/// f16 inputs are computed in f32.
+#[derive(Clone, Debug, Hash)]
+pub struct RmsNorm {
+    pub axis: usize,
+    pub eps: Arc<Tensor>,
+}
+
+impl Op for RmsNorm {
+    fn name(&self) -> Cow<str> {
+        "RmsNorm".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}, eps: {:?}", self.axis, self.eps)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RmsNorm {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let dt = input.datum_type();
+        let mut work = input.into_tensor().cast_to::<f32>()?.into_owned();
+        let eps = self.eps.cast_to_scalar::<f32>()?;
+        let mut view = work.to_array_view_mut::<f32>()?;
+        for mut lane in view.lanes_mut(Axis(self.axis)) {
+            let mean_of_squares = lane.iter().map(|x| x * x).sum::<f32>() / lane.len() as f32;
+            let scale = (mean_of_squares + eps).sqrt().recip();
+            if let Some(slice) = lane.as_slice_mut() {
+                (tract_linalg::ops().mul_by_scalar_f32)().run_with_params(slice, scale)?;
+            } else {
+                lane.mapv_inplace(|x| x * scale);
+            }
+        }
+        Ok(tvec!(work.cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl TypedOp for RmsNorm {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 1, "RmsNorm expects one input");
+        ensure!(inputs[0].datum_type.is_float(), "RmsNorm only supports float inputs");
+        ensure!(self.axis < inputs[0].rank());
+        Ok(tvec!(inputs[0].datum_type.fact(inputs[0].shape.clone())))
+    }
+
+    fn axes_mapping(
+        &self,
+        inputs: &[&TypedFact],
+        outputs: &[&TypedFact],
+    ) -> TractResult<AxesMapping> {
+        AxesMapping::natural(inputs, outputs)
+    }
+
+    fn change_axes(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        _io: InOut,
+        change: &AxisOp,
+    ) -> TractResult<Option<AxisChangeConsequence>> {
+        let Some(axis) = change.transform_axis(self.axis) else { return Ok(None) };
+        Ok(Some(AxisChangeConsequence::new(
+            model,
+            node,
+            Some(Box::new(RmsNorm { axis, ..self.clone() })),
+            change,
+        )))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let dt = inputs[0].datum_type;
+        let shape = &inputs[0].shape;
+        let lanes: TDim =
+            shape.iter().enumerate().filter(|(ix, _)| *ix != self.axis).map(|(_, d)| d).product();
+        Ok(tvec!((Cost::FMA(dt), shape.volume() * 2), (Cost::Div(dt), lanes)))
+    }
+
+    as_op!();
+}
+
+/// Fold x * rsqrt(mean_of_squares(x) + eps) into RmsNorm. Called on the MeanOfSquares node.
+pub(crate) fn detect_rms_norm(
+    op: &Reduce,
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    if op.reducer != Reducer::MeanOfSquares || op.axes.len() != 1 {
+        return Ok(None);
+    }
+    let x = node.inputs[0];
+    let dt = model.outlet_fact(x)?.datum_type;
+    if !dt.is_float() {
+        return Ok(None);
+    }
+    let Some(add) = super::sole_succ(model, node.id) else { return Ok(None) };
+    if !add.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Add>()) {
+        return Ok(None);
+    }
+    let Some(eps) = crate::ops::binary::one_input_is_uniform(model, add)? else {
+        return Ok(None);
+    };
+    if eps.var != node.id.into() || add.outputs[0].fact.shape != node.outputs[0].fact.shape {
+        return Ok(None);
+    }
+    let Some(rsqrt) = super::sole_succ(model, add.id) else { return Ok(None) };
+    if !rsqrt.op_as::<ElementWiseOp>().is_some_and(|ew| ew.0.is::<Rsqrt>()) {
+        return Ok(None);
+    }
+    let Some(mul) = super::sole_succ(model, rsqrt.id) else { return Ok(None) };
+    let rsqrt_outlet = OutletId::new(rsqrt.id, 0);
+    if !mul.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>())
+        || (mul.inputs[..] != [x, rsqrt_outlet] && mul.inputs[..] != [rsqrt_outlet, x])
+    {
+        return Ok(None);
+    }
+    let eps = eps.uni.cast_to_dt(dt)?.into_owned();
+    let mut patch = TypedModelPatch::new("Fold RmsNorm");
+    let wire = patch.tap_model(model, x)?;
+    let wire = patch.wire_node(
+        &mul.name,
+        RmsNorm { axis: op.axes[0], eps: eps.into_arc_tensor() },
+        &[wire],
+    )?[0];
+    patch.shunt_outside(model, mul.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math::{add, mul, rsqrt, square};
+
+    #[test]
+    fn fold_rms_norm() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([3, 8]))?;
+        let eps = model.add_const("eps", tensor2(&[[1e-5f32]]))?;
+        let inv_n = model.add_const("inv_n", tensor2(&[[0.125f32]]))?;
+        let w = model.wire_node("square", square(), &[x])?;
+        let w = model.wire_node("sum", Reduce::new(tvec!(1), Reducer::Sum), &w)?;
+        let w = model.wire_node("mean", mul(), &[w[0], inv_n])?;
+        let w = model.wire_node("add", add(), &[w[0], eps])?;
+        let w = model.wire_node("rsqrt", rsqrt(), &w)?;
+        let w = model.wire_node("norm", mul(), &[x, w[0]])?;
+        model.set_output_outlets(&w)?;
+        let input =
+            Tensor::from_shape(&[3, 8], &(0..24).map(|x| x as f32 - 10.).collect::<Vec<_>>())?;
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let model = model.into_decluttered()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<RmsNorm>()));
+        assert!(!model.nodes().iter().any(|n| n.op_is::<Reduce>()));
+        let found = model.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+}
@@ -0,0 +1,202 @@
+use super::{MaskOffsets, SdpaGeometry};
+use crate::internal::*;
+use tract_linalg::mmm::{BinOp, FusedSpec, MatMatMul, ScratchSpace};
+
+/// Rough size of a L1 data cache: K and V tiles are sized to fit in it.
+const L1_CACHE_BYTES: usize = 32 * 1024;
+
+/// Number of query rows processed together.
+const QUERY_BLOCK: usize = 64;
+
+/// Tiled, single-pass attention for f32 (FlashAttention-style).
+///
+/// Queries are processed by blocks of rows against blocks of keys. Scores for a block are
+/// computed by a MatMatMul kernel, then folded into a running max and a running sum (online
+/// softmax) so that the full score matrix is never materialized.
+#[derive(Clone, Debug)]
+pub struct FlashSdpa {
+    pub scale: Option<f32>,
+    pub causal: bool,
+    pub mmm: Box<dyn MatMatMul>,
+    pub packing: usize,
+}
+
+impl FlashSdpa {
+    pub fn new(scale: Option<f32>, causal: bool, depth: Option<usize>) -> Option<FlashSdpa> {
+        let f32 = f32::datum_type();
+        let mmm = tract_linalg::ops().mmm(f32, f32, f32, Some(QUERY_BLOCK), depth, None)?;
+        let packing = mmm.packings().iter().position(|p| {
+            p.0.can_prepare_types().contains(&f32) && p.1.can_prepare_types().contains(&f32)
+        })?;
+        Some(FlashSdpa { scale, causal, mmm, packing })
+    }
+
+    fn block_sizes(&self, d: usize, dv: usize) -> (usize, usize) {
+        let mr = self.mmm.mr();
+        let nr = self.mmm.nr();
+        let block_q = QUERY_BLOCK.div_ceil(mr) * mr;
+        let block_k = (L1_CACHE_BYTES / (4 * (d + dv).max(1))).clamp(16, 512);
+        (block_q, block_k.div_ceil(nr) * nr)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn eval_head(
+        &self,
+        geo: &SdpaGeometry,
+        scratch: &mut dyn ScratchSpace,
+        q: &[f32],
+        k: &[f32],
+        v: &[f32],
+        mask: Option<(&[f32], MaskOffsets)>,
+        output: &mut [f32],
+    ) -> TractResult<()> {
+        let SdpaGeometry { sq, sk, d, dv, .. } = *geo;
+        let (block_q, block_k) = self.block_sizes(d, dv);
+        let (a_format, b_format) = self.mmm.packings()[self.packing];
+        let c_view = self.mmm.c_view(0, 1);
+        let scale = tensor0(self.scale.unwrap_or_else(|| (d as f32).sqrt().recip()));
+        for i0 in (0..sq).step_by(block_q) {
+            let rows = block_q.min(sq - i0);
+            let q_block = Tensor::from_shape(&[rows, d], &q[i0 * d..][..rows * d])?;
+            let q_block = a_format.prepare_tensor(&q_block, 1, 0)?;
+            let mut max = vec![f32::NEG_INFINITY; rows];
+            let mut sum = vec![0f32; rows];
+            let mut acc = Tensor::zero::<f32>(&[rows, dv])?;
+            for j0 in (0..sk).step_by(block_k) {
+                if self.causal && geo.is_causally_masked(i0 + rows - 1, j0) {
+                    break;
+                }
+                let cols = block_k.min(sk - j0);
+                let k_block = Tensor::from_shape(&[cols, d], &k[j0 * d..][..cols * d])?;
+                let k_block = b_format.prepare_tensor(&k_block, 1, 0)?;
+                let mut scores = Tensor::uninitialized::<f32>(&[rows, cols])?;
+                self.mmm.run_with_scratch_space(
+                    rows,
+                    cols,
+                    scratch,
+                    &[
+                        FusedSpec::AddMatMul { a: &*q_block, b: &*k_block, packing: self.packing },
+                        FusedSpec::BinScalar(&scale, BinOp::Mul),
+                        FusedSpec::Store(c_view.wrap(&scores.view_mut())),
+                    ],
+                )?;
+                let scores_slice = scores.as_slice_mut::<f32>()?;
+                let acc_slice = acc.as_slice_mut::<f32>()?;
+                for r in 0..rows {
+                    let i = i0 + r;
+                    let row = &mut scores_slice[r * cols..][..cols];
+                    for (c, x) in row.iter_mut().enumerate() {
+                        let j = j0 + c;
+                        if self.causal && geo.is_causally_masked(i, j) {
+                            *x = f32::NEG_INFINITY;
+                        } else if let Some((mask, offsets)) = mask {
+                            *x += offsets.get(mask, i, j);
+                        }
+                    }
+                    let new_max = row.iter().copied().fold(max[r], f32::max);
+                    if new_max == f32::NEG_INFINITY {
+                        row.fill(0.0);
+                        continue;
+                    }
+                    let mut block_sum = 0f32;
+                    for x in row.iter_mut() {
+                        *x = (*x - new_max).exp();
+                        block_sum += *x;
+                    }
+                    let correction = (max[r] - new_max).exp();
+                    if correction != 1.0 {
+                        acc_slice[r * dv..][..dv].iter_mut().for_each(|a| *a *= correction);
+                    }
+                    sum[r] = sum[r] * correction + block_sum;
+                    max[r] = new_max;
+                }
+                let v_block = Tensor::from_shape(&[cols, dv], &v[j0 * dv..][..cols * dv])?;
+                let v_block = b_format.prepare_tensor(&v_block, 0, 1)?;
+                let probs = a_format.prepare_tensor(&scores, 1, 0)?;
+                let acc_store = c_view.wrap(&acc.view_mut());
+                self.mmm.run_with_scratch_space(
+                    rows,
+                    dv,
+                    scratch,
+                    &[
+                        FusedSpec::AddMatMul { a: &*probs, b: &*v_block, packing: self.packing },
+                        FusedSpec::AddUnicast(acc_store),
+                        FusedSpec::Store(acc_store),
+                    ],
+                )?;
+            }
+            let acc = acc.as_slice::<f32>()?;
+            for r in 0..rows {
+                let norm = if sum[r] > 0.0 { sum[r].recip() } else { 0.0 };
+                for (o, a) in output[(i0 + r) * dv..][..dv].iter_mut().zip(&acc[r * dv..][..dv]) {
+                    *o = a * norm;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Op for FlashSdpa {
+    fn name(&self) -> Cow<str> {
+        "FlashSdpa".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("scale: {:?}, causal: {}", self.scale, self.causal),
+            format!("kernel: {}", self.mmm.kernel_name()),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for FlashSdpa {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let geo = SdpaGeometry::new(
+            inputs[0].shape(),
+            inputs[1].shape(),
+            inputs[2].shape(),
+            inputs.get(3).map(|m| m.shape()),
+        )?;
+        let q = inputs[0].as_slice::<f32>()?;
+        let k = inputs[1].as_slice::<f32>()?;
+        let v = inputs[2].as_slice::<f32>()?;
+        let mask = inputs.get(3).map(|m| m.as_slice::<f32>()).transpose()?;
+        let mut output = Tensor::zero::<f32>(&geo.output_shape())?;
+        let output_slice = output.as_slice_mut::<f32>()?;
+        unsafe {
+            let mut scratch = self.mmm.allocate_scratch_space();
+            for head in geo.heads() {
+                self.eval_head(
+                    &geo,
+                    &mut *scratch,
+                    &q[head.q..][..geo.sq * geo.d],
+                    &k[head.k..][..geo.sk * geo.d],
+                    &v[head.v..][..geo.sk * geo.dv],
+                    mask.zip(head.mask),
+                    &mut output_slice[head.o..][..geo.sq * geo.dv],
+                )?;
+            }
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for FlashSdpa {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].datum_type == f32::datum_type());
+        super::output_facts(inputs)
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        super::cost(inputs)
+    }
+
+    as_op!();
+}
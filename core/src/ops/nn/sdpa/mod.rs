@@ -0,0 +1,680 @@
+use crate::axes::Axis;
+use crate::internal::*;
+use crate::ops::binary::TypedBinOp;
+use crate::ops::einsum::EinSum;
+use crate::ops::math::{Add, Div, Mul};
+use crate::ops::nn::{Softmax, SoftmaxExp};
+use crate::tract_data::itertools::Itertools;
+
+mod flash;
+
+pub use flash::FlashSdpa;
+
+/// Scaled dot-product attention: softmax(scale · Q·Kᵀ + mask)·V.
+///
+/// Inputs are q `[..., H, Sq, D]`, k `[..., Hkv, Sk, D]`, v `[..., Hkv, Sk, Dv]` and an optional
+/// additive mask broadcastable to `[..., H, Sq, Sk]`. The output is `[..., H, Sq, Dv]`.
+///
+/// Leading axes of k and v must match q or be 1, except the heads axis (right before Sq) where
+/// H must be a multiple of Hkv: groups of H / Hkv query heads share a key/value head (GQA,
+/// or MQA when Hkv is 1).
+///
+/// In causal mode, the mask is aligned on the bottom-right corner: query i can attend to key j
+/// if j <= i + Sk - Sq, which is what a decoder with a key-value cache expects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScaledDotProductAttention {
+    /// Defaults to 1/sqrt(D).
+    pub scale: Option<f32>,
+    pub causal: bool,
+}
+
+impl ScaledDotProductAttention {
+    pub fn effective_scale(&self, depth: usize) -> f32 {
+        self.scale.unwrap_or_else(|| (depth as f32).sqrt().recip())
+    }
+
+    fn eval_f32(
+        &self,
+        geo: &SdpaGeometry,
+        q: &[f32],
+        k: &[f32],
+        v: &[f32],
+        mask: Option<&[f32]>,
+        output: &mut [f32],
+    ) {
+        let SdpaGeometry { sq, sk, d, dv, .. } = *geo;
+        let scale = self.effective_scale(d);
+        let mut scores = vec![0f32; sk];
+        for head in geo.heads() {
+            let q = &q[head.q..][..sq * d];
+            let k = &k[head.k..][..sk * d];
+            let v = &v[head.v..][..sk * dv];
+            for i in 0..sq {
+                let q = &q[i * d..][..d];
+                for (j, score) in scores.iter_mut().enumerate() {
+                    *score = if self.causal && geo.is_causally_masked(i, j) {
+                        f32::NEG_INFINITY
+                    } else {
+                        let dot = q.iter().zip(&k[j * d..][..d]).map(|(a, b)| a * b).sum::<f32>();
+                        dot * scale + head.mask.map(|m| m.get(mask.unwrap(), i, j)).unwrap_or(0.0)
+                    };
+                }
+                let o = &mut output[head.o + i * dv..][..dv];
+                o.fill(0.0);
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                if max == f32::NEG_INFINITY {
+                    continue;
+                }
+                let mut sum = 0f32;
+                for score in &mut scores {
+                    *score = (*score - max).exp();
+                    sum += *score;
+                }
+                for (j, score) in scores.iter().enumerate() {
+                    let p = score / sum;
+                    for (o, v) in o.iter_mut().zip(&v[j * dv..][..dv]) {
+                        *o += p * v;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Op for ScaledDotProductAttention {
+    fn name(&self) -> Cow<str> {
+        "ScaledDotProductAttention".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("scale: {:?}, causal: {}", self.scale, self.causal)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for ScaledDotProductAttention {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let dt = inputs[0].datum_type();
+        let inputs = inputs.iter().map(|t| t.cast_to::<f32>()).collect::<TractResult<TVec<_>>>()?;
+        let geo = SdpaGeometry::new(
+            inputs[0].shape(),
+            inputs[1].shape(),
+            inputs[2].shape(),
+            inputs.get(3).map(|m| m.shape()),
+        )?;
+        let mut output = Tensor::zero::<f32>(&geo.output_shape())?;
+        self.eval_f32(
+            &geo,
+            inputs[0].as_slice::<f32>()?,
+            inputs[1].as_slice::<f32>()?,
+            inputs[2].as_slice::<f32>()?,
+            inputs.get(3).map(|m| m.as_slice::<f32>()).transpose()?,
+            output.as_slice_mut::<f32>()?,
+        );
+        Ok(tvec!(output.cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl TypedOp for ScaledDotProductAttention {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        output_facts(inputs)
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        cost(inputs)
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if model.outlet_fact(node.inputs[0])?.datum_type != f32::datum_type() {
+            return Ok(None);
+        }
+        let depth = model.outlet_fact(node.inputs[0])?.shape.last().unwrap().to_usize().ok();
+        let Some(flash) = FlashSdpa::new(self.scale, self.causal, depth) else {
+            return Ok(None);
+        };
+        TypedModelPatch::replace_single_op(model, node, &node.inputs, flash).map(Some)
+    }
+
+    as_op!();
+}
+
+pub(super) fn output_facts(inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+    ensure!(inputs.len() == 3 || inputs.len() == 4, "Attention expects q, k, v and optional mask");
+    let dt = inputs[0].datum_type;
+    ensure!(dt.is_float(), "Attention expects float inputs, got {:?}", dt);
+    ensure!(inputs.iter().all(|f| f.datum_type == dt), "Attention inputs must share a type");
+    let rank = inputs[0].rank();
+    ensure!(rank >= 2);
+    ensure!(inputs.iter().all(|f| f.rank() == rank), "Attention inputs must share a rank");
+    let (q, k, v) = (&inputs[0].shape, &inputs[1].shape, &inputs[2].shape);
+    ensure!(
+        q[rank - 1] == k[rank - 1],
+        "q and k depth mismatch ({} vs {})",
+        q[rank - 1],
+        k[rank - 1]
+    );
+    ensure!(
+        k[rank - 2] == v[rank - 2],
+        "k and v length mismatch ({} vs {})",
+        k[rank - 2],
+        v[rank - 2]
+    );
+    let mut shape: TVec<TDim> = q[..rank - 1].into();
+    shape.push(v[rank - 1].clone());
+    Ok(tvec!(dt.fact(shape)))
+}
+
+pub(super) fn cost(inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+    let rank = inputs[0].rank();
+    let (q, k, v) = (&inputs[0].shape, &inputs[1].shape, &inputs[2].shape);
+    let scores: TDim = q[..rank - 1].iter().product::<TDim>() * &k[rank - 2];
+    Ok(tvec!((Cost::FMA(inputs[0].datum_type), scores * (q[rank - 1].clone() + &v[rank - 1]))))
+}
+
+/// Offsets of one attention head in the (contiguous) inputs and output.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct HeadOffsets {
+    pub q: usize,
+    pub k: usize,
+    pub v: usize,
+    pub o: usize,
+    pub mask: Option<MaskOffsets>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct MaskOffsets {
+    offset: usize,
+    row_stride: usize,
+    col_stride: usize,
+}
+
+impl MaskOffsets {
+    #[inline]
+    pub fn get(&self, mask: &[f32], i: usize, j: usize) -> f32 {
+        mask[self.offset + i * self.row_stride + j * self.col_stride]
+    }
+}
+
+/// Actual dimensions of an attention problem, checked against broadcasting rules.
+#[derive(Clone, Debug)]
+pub(super) struct SdpaGeometry {
+    pub leading: TVec<usize>,
+    pub kv_leading: TVec<usize>,
+    pub mask_shape: Option<TVec<usize>>,
+    pub sq: usize,
+    pub sk: usize,
+    pub d: usize,
+    pub dv: usize,
+}
+
+impl SdpaGeometry {
+    pub fn new(
+        q: &[usize],
+        k: &[usize],
+        v: &[usize],
+        mask: Option<&[usize]>,
+    ) -> TractResult<SdpaGeometry> {
+        let rank = q.len();
+        ensure!(rank >= 2 && k.len() == rank && v.len() == rank);
+        let lead = rank - 2;
+        let (sq, d, sk, dv) = (q[lead], q[lead + 1], k[lead], v[lead + 1]);
+        ensure!(k[lead + 1] == d, "q and k depth mismatch ({} vs {})", d, k[lead + 1]);
+        ensure!(v[lead] == sk, "k and v length mismatch ({} vs {})", sk, v[lead]);
+        ensure!(k[..lead] == v[..lead], "k and v leading dims mismatch ({:?} vs {:?})", k, v);
+        for axis in 0..lead {
+            if axis + 1 == lead {
+                ensure!(
+                    k[axis] > 0 && q[axis] % k[axis] == 0,
+                    "{} query heads can not be grouped over {} key-value heads",
+                    q[axis],
+                    k[axis]
+                );
+            } else {
+                ensure!(
+                    k[axis] == q[axis] || k[axis] == 1,
+                    "Can not broadcast k {:?} to q {:?}",
+                    k,
+                    q
+                );
+            }
+        }
+        if let Some(mask) = mask {
+            ensure!(mask.len() == rank, "Attention mask rank must be {}, got {:?}", rank, mask);
+            ensure!(
+                mask.iter()
+                    .zip(q[..lead].iter().chain([sq, sk].iter()))
+                    .all(|(m, q)| m == q || *m == 1),
+                "Can not broadcast mask {:?} to [{:?}, {}, {}]",
+                mask,
+                &q[..lead],
+                sq,
+                sk
+            );
+        }
+        Ok(SdpaGeometry {
+            leading: q[..lead].into(),
+            kv_leading: k[..lead].into(),
+            mask_shape: mask.map(|m| m.into()),
+            sq,
+            sk,
+            d,
+            dv,
+        })
+    }
+
+    pub fn output_shape(&self) -> TVec<usize> {
+        let mut shape = self.leading.clone();
+        shape.push(self.sq);
+        shape.push(self.dv);
+        shape
+    }
+
+    #[inline]
+    pub fn is_causally_masked(&self, i: usize, j: usize) -> bool {
+        j + self.sq > i + self.sk
+    }
+
+    pub fn heads(&self) -> impl Iterator<Item = HeadOffsets> + '_ {
+        let lead = self.leading.len();
+        tract_ndarray::indices(&*self.leading).into_iter().map(move |coords| {
+            let mut q_ix = 0;
+            let mut kv_ix = 0;
+            let mut mask_ix = 0;
+            for axis in 0..lead {
+                let kv_coord = if axis + 1 == lead {
+                    coords[axis] / (self.leading[axis] / self.kv_leading[axis])
+                } else if self.kv_leading[axis] == 1 {
+                    0
+                } else {
+                    coords[axis]
+                };
+                q_ix = q_ix * self.leading[axis] + coords[axis];
+                kv_ix = kv_ix * self.kv_leading[axis] + kv_coord;
+                if let Some(mask) = &self.mask_shape {
+                    mask_ix = mask_ix * mask[axis] + if mask[axis] == 1 { 0 } else { coords[axis] };
+                }
+            }
+            let mask = self.mask_shape.as_ref().map(|mask| {
+                let (rows, cols) = (mask[lead], mask[lead + 1]);
+                MaskOffsets {
+                    offset: mask_ix * rows * cols,
+                    row_stride: if rows == 1 { 0 } else { cols },
+                    col_stride: (cols != 1) as usize,
+                }
+            });
+            HeadOffsets {
+                q: q_ix * self.sq * self.d,
+                k: kv_ix * self.sk * self.d,
+                v: kv_ix * self.sk * self.dv,
+                o: q_ix * self.sq * self.dv,
+                mask,
+            }
+        })
+    }
+}
+
+fn single_use(model: &TypedModel, outlet: OutletId) -> bool {
+    model.node(outlet.node).outputs[outlet.slot].successors.len() == 1
+}
+
+fn leads_to_scores(model: &TypedModel, outlet: OutletId) -> bool {
+    let prec = model.node(outlet.node);
+    prec.op_is::<EinSum>()
+        || prec.op_as::<TypedBinOp>().is_some_and(|bin| {
+            (bin.0.is::<Mul>() || bin.0.is::<Div>())
+                && prec.inputs.iter().any(|i| model.node(i.node).op_is::<EinSum>())
+        })
+}
+
+fn uniform_f32(model: &TypedModel, outlet: OutletId) -> TractResult<Option<f32>> {
+    let fact = model.outlet_fact(outlet)?;
+    if fact.shape.volume().is_one() {
+        if let Some(uniform) = &fact.uniform {
+            return Ok(Some(uniform.cast_to_scalar::<f32>()?));
+        }
+    }
+    Ok(None)
+}
+
+/// Look for EinSum(q, k) -> [Mul|Div by scalar] -> [Add mask] -> Softmax -> EinSum(_, v)
+/// around a softmax node, and replace it by a single ScaledDotProductAttention.
+pub(crate) fn fuse_attention(
+    softmax: &Softmax,
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let dt = model.outlet_fact(node.inputs[0])?.datum_type;
+    if softmax.axes.len() != 1
+        || softmax.quant_output_dt.is_some()
+        || softmax.exp != SoftmaxExp::default()
+        || !dt.is_float()
+    {
+        return Ok(None);
+    }
+    // single_succ only follows single-input successors
+    let [succ] = &*node.outputs[0].successors else { return Ok(None) };
+    let av_node = model.node(succ.node);
+    let Some(av) = av_node.op_as::<EinSum>() else { return Ok(None) };
+    if av.q_params.is_some() || av_node.inputs.len() != 2 {
+        return Ok(None);
+    }
+    let probs_slot = succ.slot;
+
+    // walk back from softmax input to the QK einsum
+    let mut wire = node.inputs[0];
+    let mut mask = None;
+    let mut scale = 1f32;
+    let prec = model.node(wire.node);
+    if let Some(bin) = prec.op_as::<TypedBinOp>() {
+        if bin.0.is::<Add>() && single_use(model, wire) {
+            let scores_slot = (0..2).find(|&slot| leads_to_scores(model, prec.inputs[slot]));
+            let Some(scores_slot) = scores_slot else { return Ok(None) };
+            mask = Some(prec.inputs[1 - scores_slot]);
+            wire = prec.inputs[scores_slot];
+        }
+    }
+    let prec = model.node(wire.node);
+    if let Some(bin) = prec.op_as::<TypedBinOp>() {
+        if !single_use(model, wire) {
+            return Ok(None);
+        }
+        if bin.0.is::<Mul>() {
+            let Some(slot) =
+                (0..2).find(|&slot| model.node(prec.inputs[slot].node).op_is::<EinSum>())
+            else {
+                return Ok(None);
+            };
+            let Some(value) = uniform_f32(model, prec.inputs[1 - slot])? else { return Ok(None) };
+            scale = value;
+            wire = prec.inputs[slot];
+        } else if bin.0.is::<Div>() {
+            let Some(value) = uniform_f32(model, prec.inputs[1])? else { return Ok(None) };
+            scale = value.recip();
+            wire = prec.inputs[0];
+        } else {
+            return Ok(None);
+        }
+    }
+    let qk_node = model.node(wire.node);
+    let Some(qk) = qk_node.op_as::<EinSum>() else { return Ok(None) };
+    if qk.q_params.is_some() || qk_node.inputs.len() != 2 || !single_use(model, wire) {
+        return Ok(None);
+    }
+    let scores_shape = &model.outlet_fact(wire)?.shape;
+    // scaling and masking must not broadcast the scores
+    if model.outlet_fact(node.inputs[0])?.shape != *scores_shape {
+        return Ok(None);
+    }
+    let facts = model.node_input_facts(qk_node.id)?;
+    let (q_fact, k_fact) = (facts[0], facts[1]);
+    let v_fact = model.outlet_fact(av_node.inputs[1 - probs_slot])?;
+    if [q_fact, k_fact, v_fact].iter().any(|f| f.datum_type != dt) {
+        return Ok(None);
+    }
+    if let Some(mask) = mask {
+        let mask = model.outlet_fact(mask)?;
+        if mask.datum_type != dt || mask.rank() != scores_shape.len() {
+            return Ok(None);
+        }
+    }
+
+    // classify QK axes. Axes of size one summed over are leftovers of squeezed dimensions (a
+    // batch of one, typically): they are dropped from the inputs.
+    let unit = |fact: &TypedFact, positions: &[usize]| {
+        positions.iter().all(|&pos| fact.shape[pos].is_one())
+    };
+    let qk_unit = |axis: &Axis| unit(q_fact, &axis.inputs[0]) && unit(k_fact, &axis.inputs[1]);
+    let mut batch = vec![];
+    let mut contracted = vec![];
+    let (mut sq, mut sk) = (None, None);
+    for axis in qk.axes.iter_all_axes() {
+        match (axis.inputs[0].len(), axis.inputs[1].len(), axis.outputs[0].len()) {
+            (1, 1, 1) => batch.push(axis),
+            (1, 0, 1) if sq.is_none() => sq = Some(axis),
+            (0, 1, 1) if sk.is_none() => sk = Some(axis),
+            (1, 1, 0) => contracted.push(axis),
+            (_, _, 0) if qk_unit(axis) => (),
+            _ => return Ok(None),
+        }
+    }
+    // the head dimension is the contracted axis, the others must be of size one
+    contracted.sort_by_key(|axis| !qk_unit(axis));
+    let Some(d) = contracted.pop() else { return Ok(None) };
+    if !contracted.into_iter().all(qk_unit) {
+        return Ok(None);
+    }
+    let (Some(sq), Some(sk)) = (sq, sk) else { return Ok(None) };
+    if softmax.axes[0] != sk.outputs[0][0] {
+        return Ok(None);
+    }
+    batch.sort_by_key(|axis| axis.outputs[0][0]);
+    // q leading dims define the output, so it must not be broadcast
+    if batch.iter().any(|axis| q_fact.shape[axis.inputs[0][0]] != scores_shape[axis.outputs[0][0]])
+    {
+        return Ok(None);
+    }
+
+    // map scores axes to PV axes
+    let (p, v) = (probs_slot, 1 - probs_slot);
+    let find_av = |in_p: Option<usize>, in_v: bool, out: bool| {
+        av.axes.iter_all_axes().find(|axis| {
+            axis.inputs[p].first().copied() == in_p
+                && axis.inputs[p].len() <= 1
+                && axis.inputs[v].len() == in_v as usize
+                && axis.outputs[0].len() == out as usize
+        })
+    };
+    // axes only in v and of size one are dropped from v, and added back to the output
+    let v_unit = |axis: &Axis| unit(v_fact, &axis.inputs[v]);
+    let mut v_only = av
+        .axes
+        .iter_all_axes()
+        .filter(|axis| axis.inputs[p].is_empty() && axis.inputs[v].len() == 1)
+        .collect_vec();
+    // the value dimension is the one kept in the output, the others must be of size one
+    v_only.sort_by_key(|axis| (axis.outputs[0].len(), !v_unit(axis)));
+    let Some(av_dv) = v_only.pop().filter(|axis| axis.outputs[0].len() == 1) else {
+        return Ok(None);
+    };
+    if !v_only.iter().all(|axis| v_unit(axis)) {
+        return Ok(None);
+    }
+    if av.axes.iter_all_axes().count() != batch.len() + 3 + v_only.len() {
+        return Ok(None);
+    }
+    let Some(av_sk) = find_av(Some(sk.outputs[0][0]), true, false) else { return Ok(None) };
+    let Some(av_sq) = find_av(Some(sq.outputs[0][0]), false, true) else { return Ok(None) };
+    let mut av_batch = vec![];
+    for axis in &batch {
+        let Some(av_axis) = find_av(Some(axis.outputs[0][0]), true, true) else {
+            return Ok(None);
+        };
+        if v_fact.shape[av_axis.inputs[v][0]] != k_fact.shape[axis.inputs[1][0]] {
+            return Ok(None);
+        }
+        av_batch.push(av_axis);
+    }
+
+    let prefix: String = batch.iter().map(|a| a.repr).collect();
+    let av_prefix: String = av_batch.iter().map(|a| a.repr).collect();
+    let order =
+        |mapping: &AxesMapping, io: InOut| -> String { mapping.axes(io).map(|a| a.repr).collect() };
+    let mut patch = TypedModelPatch::new("Fuse scaled dot-product attention");
+    let name = &av_node.name;
+    let mut inputs = tvec!();
+    for (label, outlet, from, to) in [
+        (
+            "q",
+            qk_node.inputs[0],
+            order(&qk.axes, InOut::In(0)),
+            format!("{prefix}{}{}", sq.repr, d.repr),
+        ),
+        (
+            "k",
+            qk_node.inputs[1],
+            order(&qk.axes, InOut::In(1)),
+            format!("{prefix}{}{}", sk.repr, d.repr),
+        ),
+        (
+            "v",
+            av_node.inputs[v],
+            order(&av.axes, InOut::In(v)),
+            format!("{av_prefix}{}{}", av_sk.repr, av_dv.repr),
+        ),
+    ]
+    .into_iter()
+    .chain(mask.map(|mask| {
+        ("mask", mask, order(&qk.axes, InOut::Out(0)), format!("{prefix}{}{}", sq.repr, sk.repr))
+    })) {
+        let mut wire = patch.tap_model(model, outlet)?;
+        let transform = format!("{from}->{to}").parse::<AxesMapping>()?.translate_to_axis_ops()?;
+        for (ix, op) in transform.into_iter().enumerate() {
+            wire = patch.wire_node(format!("{name}.sdpa_{label}.{ix}"), op, &[wire])?[0];
+        }
+        inputs.push(wire);
+    }
+    let op = ScaledDotProductAttention { scale: Some(scale), causal: false };
+    let mut wire = patch.wire_node(format!("{name}.sdpa"), op, &inputs)?;
+    let transform =
+        format!("{av_prefix}{}{}->{}", av_sq.repr, av_dv.repr, order(&av.axes, InOut::Out(0)))
+            .parse::<AxesMapping>()?
+            .translate_to_axis_ops()?;
+    for (ix, op) in transform.into_iter().enumerate() {
+        wire = patch.wire_node(format!("{name}.sdpa_output.{ix}"), op, &wire)?;
+    }
+    patch.shunt_outside(model, av_node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math::mul;
+
+    fn random(shape: &[usize], seed: usize) -> Tensor {
+        let len = shape.iter().product::<usize>();
+        let data = (0..len)
+            .map(|i| (((i * 7919 + seed * 104729) % 211) as f32 / 105.0) - 1.0)
+            .collect_vec();
+        Tensor::from_shape(shape, &data).unwrap()
+    }
+
+    fn naive_model(
+        q: &[usize],
+        k: &[usize],
+        v: &[usize],
+        scale: f32,
+        exp: SoftmaxExp,
+    ) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let q = model.add_source("q", f32::fact(q))?;
+        let k = model.add_source("k", f32::fact(k))?;
+        let v = model.add_source("v", f32::fact(v))?;
+        let scores = model.wire_node(
+            "qk",
+            EinSum::new("bhqd,bhkd->bhqk".parse()?, f32::datum_type()),
+            &[q, k],
+        )?;
+        let scale = model.add_const("scale", tensor0(scale).broadcast_into_rank(4)?)?;
+        let scaled = model.wire_node("scaled", mul(), &[scores[0], scale])?;
+        let probs = model.wire_node("softmax", Softmax::new(tvec!(3), None, exp), &scaled)?;
+        let output = model.wire_node(
+            "pv",
+            EinSum::new("bhqk,bhkv->bhqv".parse()?, f32::datum_type()),
+            &[probs[0], v],
+        )?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    fn sdpa_model(op: ScaledDotProductAttention, shapes: &[&[usize]]) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let inputs = shapes
+            .iter()
+            .enumerate()
+            .map(|(ix, shape)| model.add_source(format!("input.{ix}"), f32::fact(*shape)))
+            .collect::<TractResult<TVec<_>>>()?;
+        let output = model.wire_node("sdpa", op, &inputs)?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn declutter_fuses_attention() -> TractResult<()> {
+        // a batch of one is squeezed out of the QK einsum before the fusion sees it
+        for b in [1, 2] {
+            let (q, k, v) = ([b, 2, 5, 4], [b, 2, 7, 4], [b, 2, 7, 3]);
+            let model = naive_model(&q, &k, &v, 0.5, SoftmaxExp::default())?;
+            let inputs = tvec!(
+                random(&q, 0).into_tvalue(),
+                random(&k, 1).into_tvalue(),
+                random(&v, 2).into_tvalue()
+            );
+            let expected = model.clone().into_runnable()?.run(inputs.clone())?;
+            let decluttered = model.into_decluttered()?;
+            assert!(decluttered.nodes().iter().any(|n| n.op_is::<ScaledDotProductAttention>()));
+            assert!(!decluttered.nodes().iter().any(|n| n.op_is::<Softmax>()));
+            let found = decluttered.into_runnable()?.run(inputs)?;
+            found[0].close_enough(&expected[0], Approximation::Close)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn approximate_softmax_is_not_fused() -> TractResult<()> {
+        let (q, k, v) = ([1, 2, 5, 4], [1, 2, 7, 4], [1, 2, 7, 3]);
+        let model = naive_model(&q, &k, &v, 0.5, SoftmaxExp::FastCompact)?.into_decluttered()?;
+        assert!(!model.nodes().iter().any(|n| n.op_is::<ScaledDotProductAttention>()));
+        Ok(())
+    }
+
+    #[test]
+    fn grouped_query_causal() -> TractResult<()> {
+        let (q, k, v) = ([4, 3, 2], [2, 5, 2], [2, 5, 3]);
+        let op = ScaledDotProductAttention { scale: None, causal: true };
+        let inputs = tvec!(
+            random(&q, 3).into_tvalue(),
+            random(&k, 4).into_tvalue(),
+            random(&v, 5).into_tvalue()
+        );
+        let found = op.eval(inputs.clone())?.remove(0);
+        // query head 3 uses kv head 1; query row 0 sees keys 0..=2
+        let q = inputs[0].slice(0, 3, 4)?.slice(1, 0, 1)?;
+        let k = inputs[1].slice(0, 1, 2)?.slice(1, 0, 3)?;
+        let v = inputs[2].slice(0, 1, 2)?.slice(1, 0, 3)?;
+        let expected = ScaledDotProductAttention { scale: None, causal: false }
+            .eval(tvec!(q.into_tvalue(), k.into_tvalue(), v.into_tvalue()))?
+            .remove(0);
+        found.slice(0, 3, 4)?.slice(1, 0, 1)?.close_enough(&expected, Approximation::Close)
+    }
+
+    #[test]
+    fn flash_matches_reference() -> TractResult<()> {
+        for causal in [false, true] {
+            let (q, k, v, mask) = ([2, 4, 37, 8], [2, 2, 301, 8], [2, 2, 301, 5], [1, 4, 37, 301]);
+            let op = ScaledDotProductAttention { scale: None, causal };
+            let model = sdpa_model(op, &[&q, &k, &v, &mask])?;
+            let inputs = tvec!(
+                random(&q, 6).into_tvalue(),
+                random(&k, 7).into_tvalue(),
+                random(&v, 8).into_tvalue(),
+                random(&mask, 9).into_tvalue()
+            );
+            let expected = model.clone().into_runnable()?.run(inputs.clone())?;
+            let optimized = model.into_optimized()?;
+            assert!(optimized.nodes().iter().any(|n| n.op_is::<FlashSdpa>()));
+            let found = optimized.into_runnable()?.run(inputs)?;
+            found[0].close_enough(&expected[0], Approximation::Approximate)?;
+        }
+        Ok(())
+    }
+}
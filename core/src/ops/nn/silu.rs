@@ -0,0 +1,34 @@
+use crate::internal::*;
+use crate::ops::binary::TypedBinOp;
+use crate::ops::math::Mul;
+
+element_wise!(silu, Silu,
+ [f16] => |_, xs| { (tract_linalg::ops().silu_f16)().run(xs) },
+ [f32] => |_, xs| { (tract_linalg::ops().silu_f32)().run(xs) };
+ cost: |dt| {tvec!((Cost::FMA(dt), 12), (Cost::Div(dt), 1))}
+);
+
+/// Fold x · sigmoid(x) into Silu. Called on the Sigmoid node.
+pub(crate) fn detect_silu(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let dt = node.outputs[0].fact.datum_type;
+    if dt != f32::datum_type() && dt != f16::datum_type() {
+        return Ok(None);
+    }
+    let x = node.inputs[0];
+    let Some(succ) = super::sole_succ(model, node.id) else { return Ok(None) };
+    if !succ.op_as::<TypedBinOp>().is_some_and(|b| b.0.is::<Mul>()) {
+        return Ok(None);
+    }
+    let sig = OutletId::new(node.id, 0);
+    if succ.inputs[..] != [x, sig] && succ.inputs[..] != [sig, x] {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::new("Fold Silu");
+    let wire = patch.tap_model(model, x)?;
+    let wire = patch.wire_node(&succ.name, silu(), &[wire])?[0];
+    patch.shunt_outside(model, succ.id.into(), wire)?;
+    Ok(Some(patch))
+}
@@ -96,6 +96,14 @@ impl TypedOp for Softmax {
         }
     }
 
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        super::sdpa::fuse_attention(self, model, node)
+    }
+
     as_op!();
 }
 
@@ -3,9 +3,11 @@ use std::fmt;
 
 mod lir;
 mod mir;
+mod while_loop;
 
 pub use lir::{LirScan, State};
 pub use mir::Scan;
+pub use while_loop::{Loop, LoopState};
 
 #[derive(Clone, new, Hash, Eq, PartialEq, Copy, Debug)]
 pub struct ScanInfo {
@@ -0,0 +1,410 @@
+use crate::ops::submodel::TypedModelOpState;
+use crate::ops::OpStateFreeze;
+
+use super::*;
+use tract_data::internal::*;
+
+/// A generic loop with loop-carried dependencies, scan outputs and an early-exit condition.
+///
+/// Inputs are: the maximum trip count (i64 scalar), the initial condition (bool scalar), the
+/// initial values of the loop-carried dependencies, then the values the body closes over.
+///
+/// Body inputs are: the iteration number (i64 scalar), the condition (bool scalar), the
+/// loop-carried dependencies, then the closures. Body outputs are: the updated condition, the
+/// updated loop-carried dependencies, then the scan outputs.
+///
+/// The op outputs the final values of the loop-carried dependencies, then the scan outputs
+/// stacked on a new leading axis of length `iterations`.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub body: TypedModel,
+    pub carried: usize,
+    pub iterations: Symbol,
+    pub decluttered: bool,
+    pub codegen: bool,
+}
+
+impl Loop {
+    pub fn new(body: TypedModel, carried: usize, iterations: Symbol) -> TractResult<Loop> {
+        body.check_consistency()?;
+        ensure!(body.input_outlets()?.len() >= 2 + carried);
+        ensure!(body.output_outlets()?.len() >= 1 + carried);
+        Ok(Loop { body, carried, iterations, decluttered: false, codegen: false })
+    }
+
+    pub fn scan_outputs(&self) -> usize {
+        self.body.outputs.len() - 1 - self.carried
+    }
+
+    fn body_condition_is_constant_true(&self) -> TractResult<bool> {
+        let cond_out = self.body.output_outlets()?[0];
+        if cond_out == self.body.input_outlets()?[1] {
+            return Ok(true);
+        }
+        if let Some(k) = &self.body.outlet_fact(cond_out)?.konst {
+            return Ok(k.cast_to_scalar::<bool>()?);
+        }
+        Ok(false)
+    }
+
+    fn declutter_body(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.decluttered {
+            let mut new = self.clone();
+            new.body.declutter()?;
+            new.decluttered = true;
+            Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// A loop with a known trip count and a condition that never changes is a plain Scan.
+    fn declutter_as_scan(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let inputs = model.node_input_facts(node.id)?;
+        let Some(trip_count) = &inputs[0].konst else { return Ok(None) };
+        let Some(cond) = &inputs[1].konst else { return Ok(None) };
+        let trip_count = trip_count.cast_to_scalar::<i64>()?;
+        if !cond.cast_to_scalar::<bool>()?
+            || trip_count <= 0
+            || trip_count == i64::MAX
+            || !self.body_condition_is_constant_true()?
+        {
+            return Ok(None);
+        }
+
+        let mut body = TypedModel::default();
+        let mut mapping: HashMap<OutletId, OutletId> = HashMap::default();
+        let body_inputs = self.body.input_outlets()?.to_vec();
+        let counter = body.add_source("iteration_num", i64::fact([1]))?;
+        mapping.insert(
+            body_inputs[0],
+            body.wire_node("iteration_num.rm", AxisOp::Rm(0), &[counter])?[0],
+        );
+        mapping.insert(body_inputs[1], body.add_const("cond", tensor0(true))?);
+        for input in &body_inputs[2..] {
+            let name = &self.body.node(input.node).name;
+            mapping.insert(*input, body.add_source(name, self.body.outlet_fact(*input)?.clone())?);
+        }
+        for n in self.body.eval_order()? {
+            let n = self.body.node(n);
+            if Graph::is_source(&n.op) {
+                continue;
+            }
+            let n_inputs = n.inputs.iter().map(|o| mapping[o]).collect::<TVec<_>>();
+            let n_outputs = body.wire_node(&n.name, &n.op, &n_inputs)?;
+            for (slot, outlet) in n_outputs.into_iter().enumerate() {
+                mapping.insert(OutletId::new(n.id, slot), outlet);
+            }
+        }
+        let mut outputs = tvec!();
+        let mut output_mapping = vec![];
+        for (ix, output) in self.body.output_outlets()?.iter().enumerate().skip(1) {
+            if ix <= self.carried {
+                outputs.push(mapping[output]);
+                output_mapping.push(OutputMapping {
+                    state: true,
+                    last_value_slot: Some(ix - 1),
+                    scan: None,
+                    full_dim_hint: None,
+                });
+            } else {
+                let name = format!("{}.scan-output-{}", node.name, ix - 1 - self.carried);
+                outputs.push(body.wire_node(name, AxisOp::Add(0), &[mapping[output]])?[0]);
+                output_mapping.push(OutputMapping {
+                    state: false,
+                    last_value_slot: None,
+                    scan: Some((ix - 1, ScanInfo { axis: 0, chunk: 1 })),
+                    full_dim_hint: None,
+                });
+            }
+        }
+        body.set_output_outlets(&outputs)?;
+
+        let mut input_mapping = vec![InputMapping::Scan(ScanInfo { axis: 0, chunk: 1 })];
+        input_mapping.extend((0..self.carried).map(|_| InputMapping::State));
+        input_mapping.extend((2 + self.carried..body_inputs.len()).map(|_| InputMapping::Full));
+
+        let mut patch = TypedModelPatch::default();
+        let range = tract_ndarray::Array1::from_iter(0..trip_count).into_tensor();
+        let mut scan_inputs =
+            tvec!(patch.add_const(format!("{}.iteration_num", node.name), range)?);
+        for input in &node.inputs[2..] {
+            scan_inputs.push(patch.tap_model(model, *input)?);
+        }
+        let scan = Scan::new(body, input_mapping, output_mapping, 0)?;
+        let wires = patch.wire_node(&node.name, scan, &scan_inputs)?;
+        for (ix, wire) in wires.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), wire)?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "carried: {} scan outputs: {} iterations: {}",
+            self.carried,
+            self.scan_outputs(),
+            self.iterations
+        )])
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        let plan = SimplePlan::new(self.body.clone())?;
+        Ok(Some(Box::new(LoopState {
+            carried: self.carried,
+            model_state: TypedSimpleState::new(Arc::new(plan))?,
+        })))
+    }
+}
+
+impl TypedOp for Loop {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == self.body.inputs.len());
+        ensure!(inputs[0].datum_type == i64::datum_type());
+        ensure!(inputs[1].datum_type == bool::datum_type());
+        ensure!(self.body.output_fact(0)?.datum_type == bool::datum_type());
+        let mut facts = tvec!();
+        for ix in 0..self.carried {
+            let ifact = self.body.input_fact(2 + ix)?;
+            let ofact = self.body.output_fact(1 + ix)?;
+            ensure!(
+                ifact.datum_type == ofact.datum_type,
+                "inconsistent loop-carried type: body input {} is {:?} and body output {} is {:?}",
+                2 + ix,
+                ifact,
+                1 + ix,
+                ofact
+            );
+            facts.push(ofact.without_value());
+        }
+        for ix in 0..self.scan_outputs() {
+            let fact = self.body.output_fact(1 + self.carried + ix)?;
+            let mut shape: TVec<TDim> = fact.shape.to_tvec();
+            shape.insert(0, self.iterations.to_dim());
+            facts.push(fact.datum_type.fact(shape));
+        }
+        Ok(facts)
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if let Some(patch) = self.declutter_as_scan(model, node)? {
+            return Ok(Some(patch));
+        }
+        self.declutter_body(model, node)
+    }
+
+    fn concretize_dims(
+        &self,
+        _source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        values: &SymbolValues,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|o| mapping[o]).collect::<TVec<_>>();
+        let op = Self { body: self.body.concretize_dims(values)?, ..self.clone() };
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.codegen {
+            let mut new = self.clone();
+            new.body = new.body.into_optimized()?;
+            new.codegen = true;
+            Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LoopState {
+    carried: usize,
+    model_state: TypedModelOpState,
+}
+
+#[derive(Clone, Debug)]
+struct FrozenLoopState {
+    carried: usize,
+    model_state: TypedFrozenSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+}
+
+impl OpStateFreeze for LoopState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(FrozenLoopState { carried: self.carried, model_state: self.model_state.freeze() })
+    }
+}
+
+impl FrozenOpState for FrozenLoopState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(LoopState { carried: self.carried, model_state: self.model_state.unfreeze() })
+    }
+}
+
+impl OpState for LoopState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        _op: &dyn Op,
+        inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let trip_count = inputs[0].cast_to_scalar::<i64>()?;
+        let mut cond = inputs[1].cast_to_scalar::<bool>()?;
+        let mut carried: TVec<TValue> = inputs[2..2 + self.carried].into();
+        let closures = &inputs[2 + self.carried..];
+        let model = self.model_state.model();
+        let scan_outputs = model.outputs.len() - 1 - self.carried;
+        let mut scanned: Vec<Vec<Tensor>> = vec![vec![]; scan_outputs];
+
+        let mut i = 0i64;
+        while i < trip_count && cond {
+            let mut iter_inputs: TVec<TValue> =
+                tvec!(tensor0(i).into_tvalue(), tensor0(cond).into_tvalue());
+            iter_inputs.extend(carried.drain(..));
+            iter_inputs.extend(closures.iter().cloned());
+            trace!("iter_inputs #{}: {:?}", i, iter_inputs);
+            let mut iter_outputs =
+                self.model_state.run(iter_inputs).with_context(|| "Evaluating loop body")?;
+            trace!("iter_outputs #{}: {:?}", i, iter_outputs);
+            for (ix, v) in iter_outputs.drain(1 + self.carried..).enumerate() {
+                let mut v = v.into_tensor();
+                v.insert_axis(0)?;
+                scanned[ix].push(v);
+            }
+            carried.extend(iter_outputs.drain(1..));
+            cond = iter_outputs[0].cast_to_scalar::<bool>()?;
+            i += 1;
+        }
+
+        let mut outputs = carried;
+        for (ix, values) in scanned.into_iter().enumerate() {
+            let output = if values.len() > 0 {
+                Tensor::stack_tensors(0, &values)?
+            } else {
+                let fact = self.model_state.model().output_fact(1 + self.carried + ix)?;
+                let mut shape: TVec<usize> =
+                    fact.shape.eval_to_usize(&session.resolved_symbols)?.into_owned();
+                shape.insert(0, 0);
+                Tensor::zero_dt(fact.datum_type, &shape)?
+            };
+            outputs.push(output.into_tvalue());
+        }
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // body: (i, cond, acc) -> (i < 3, acc + i, acc)
+    fn counting_loop() -> TractResult<(TypedModel, Symbol)> {
+        let mut body = TypedModel::default();
+        let i = body.add_source("i", i64::scalar_fact())?;
+        let _cond = body.add_source("cond", bool::scalar_fact())?;
+        let acc = body.add_source("acc", i64::scalar_fact())?;
+        let three = body.add_const("three", tensor0(3i64))?;
+        let cond = body.wire_node("cond_out", crate::ops::logic::less(), &[i, three])?[0];
+        let sum = body.wire_node("sum", crate::ops::math::add(), &[acc, i])?[0];
+        body.set_output_outlets(&[cond, sum, acc])?;
+
+        let mut model = TypedModel::default();
+        let iterations = model.symbol_table.new_with_prefix("loop");
+        let trip_count = model.add_source("trip_count", i64::scalar_fact())?;
+        let cond = model.add_const("cond", tensor0(true))?;
+        let init = model.add_const("init", tensor0(10i64))?;
+        let outputs = model.wire_node(
+            "loop",
+            Loop::new(body, 1, iterations.clone())?,
+            &[trip_count, cond, init],
+        )?;
+        model.set_output_outlets(&outputs)?;
+        Ok((model, iterations))
+    }
+
+    #[test]
+    fn early_exit() -> TractResult<()> {
+        let (model, _) = counting_loop()?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor0(10i64).into_tvalue()))?;
+        // iterations i=0..=3, cond turns false after i=3
+        assert_eq!(*outputs[0], tensor0(16i64));
+        assert_eq!(*outputs[1], tensor1(&[10i64, 10, 11, 13]));
+        Ok(())
+    }
+
+    #[test]
+    fn trip_count() -> TractResult<()> {
+        let (model, _) = counting_loop()?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor0(2i64).into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor0(11i64));
+        assert_eq!(*outputs[1], tensor1(&[10i64, 10]));
+        Ok(())
+    }
+
+    #[test]
+    fn constant_trip_count_declutters_to_scan() -> TractResult<()> {
+        let mut body = TypedModel::default();
+        let i = body.add_source("i", i64::scalar_fact())?;
+        let cond = body.add_source("cond", bool::scalar_fact())?;
+        let acc = body.add_source("acc", i64::scalar_fact())?;
+        let sum = body.wire_node("sum", crate::ops::math::add(), &[acc, i])?[0];
+        body.set_output_outlets(&[cond, sum, sum])?;
+
+        let mut model = TypedModel::default();
+        let iterations = model.symbol_table.new_with_prefix("loop");
+        let trip_count = model.add_const("trip_count", tensor0(4i64))?;
+        let cond = model.add_const("cond", tensor0(true))?;
+        let init = model.add_source("init", i64::scalar_fact())?;
+        let outputs =
+            model.wire_node("loop", Loop::new(body, 1, iterations)?, &[trip_count, cond, init])?;
+        model.set_output_outlets(&outputs)?;
+        let model = model.into_decluttered()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<Loop>()));
+        assert!(model.nodes().iter().any(|n| n.op_is::<Scan>()));
+        let outputs = model.into_runnable()?.run(tvec!(tensor0(1i64).into_tvalue()))?;
+        assert_eq!(*outputs[0], tensor0(7i64));
+        assert_eq!(*outputs[1], tensor1(&[1i64, 2, 4, 7]));
+        Ok(())
+    }
+}
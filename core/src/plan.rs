@@ -3,15 +3,20 @@ use std::cell::RefCell;
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
-use multithread::Executor;
+use multithread::{current_tract_executor, Executor};
+use rayon::prelude::*;
 
 use crate::internal::*;
 use crate::model::{Fact, Graph, OutletId};
 use crate::ops::konst::Const;
+use crate::ops::kv_cache::{KvCache, KvCacheState};
 use crate::ops::FrozenOpState;
 
+use self::memory::{Arena, MemoryPlan, PlannedBuffer};
 use self::order::{eval_order_for_nodes, eval_order_opt_ram_for_nodes};
 
+pub mod memory;
+
 #[derive(Default, Clone, Debug)]
 pub struct PlanOptions {
     /// Use the simple ordering instead of the newer memory friendly one
@@ -19,6 +24,16 @@ pub struct PlanOptions {
 
     /// Override default global executor
     pub executor: Option<Executor>,
+
+    /// Allocate every op output separately instead of planning a memory arena
+    pub skip_memory_arena: bool,
+
+    /// Evaluate independent stateless nodes concurrently on the executor thread pool. This
+    /// applies to `SimpleState::run` and `exec`, and disables the memory arena.
+    ///
+    /// `run_plan_with_eval` and `exec_plan_with_eval` take a caller-provided (non `Send`) eval
+    /// function and always run the plan sequentially, in `order`.
+    pub parallel_nodes: bool,
 }
 
 #[derive(Default)]
@@ -27,6 +42,8 @@ pub struct SessionState {
     pub resolved_symbols: SymbolValues,
     pub tensors: HashMap<String, Tensor>,
     pub cached_mmm_scratch_space: RefCell<Option<Box<dyn tract_linalg::mmm::ScratchSpace>>>,
+    /// Arena buffers reserved for the outputs of the op being evaluated.
+    pub planned_outputs: RefCell<TVec<Option<PlannedBuffer>>>,
 }
 
 impl Clone for SessionState {
@@ -36,7 +53,29 @@ impl Clone for SessionState {
             resolved_symbols: self.resolved_symbols.clone(),
             tensors: self.tensors.clone(),
             cached_mmm_scratch_space: None.into(),
+            planned_outputs: Default::default(),
+        }
+    }
+}
+
+impl SessionState {
+    /// Allocate output `slot` of the op being evaluated, in the buffer the plan reserved for it
+    /// in its memory arena if there is one that fits.
+    ///
+    /// # Safety
+    ///
+    /// As with `Tensor::uninitialized_dt`, the tensor content must be written before it is read.
+    pub unsafe fn uninitialized_output(
+        &self,
+        slot: usize,
+        dt: DatumType,
+        shape: &[usize],
+    ) -> TractResult<Tensor> {
+        let planned = self.planned_outputs.borrow_mut().get_mut(slot).and_then(|b| b.take());
+        if let Some(tensor) = planned.map(|b| b.into_tensor(dt, shape)).transpose()?.flatten() {
+            return Ok(tensor);
         }
+        Tensor::uninitialized_dt(dt, shape)
     }
 }
 
@@ -57,6 +96,9 @@ where
     outputs: Vec<OutletId>,
     order: Vec<usize>,
     flush_lists: Vec<TVec<usize>>,
+    moved_inputs: Vec<TVec<usize>>,
+    memory: Option<MemoryPlan>,
+    waves: Option<Vec<TVec<usize>>>,
     has_unresolved_symbols: bool,
     executor: Option<Executor>,
     _casper: PhantomData<(F, O)>,
@@ -130,6 +172,31 @@ where
                 flush_lists[flush_at].push(node)
             }
         }
+        // an input can be moved to the op rather than cloned when its producer is flushed at this
+        // step and feeds no other input of the node: the op then holds the sole reference to the
+        // value and can compute in place over it
+        let moved_inputs: Vec<TVec<usize>> = order
+            .iter()
+            .enumerate()
+            .map(|(step, &node)| {
+                let inputs = &model.borrow().node(node).inputs;
+                (0..inputs.len())
+                    .filter(|&ix| {
+                        let prec = inputs[ix].node;
+                        values_needed_until_step[prec] == step
+                            && !model.borrow().node(prec).op_is::<Const>()
+                            && inputs.iter().filter(|i| i.node == prec).count() == 1
+                    })
+                    .collect()
+            })
+            .collect();
+        let waves =
+            if options.parallel_nodes { Some(waves(model.borrow(), &order, deps)) } else { None };
+        let memory = if options.skip_memory_arena || options.parallel_nodes {
+            None
+        } else {
+            Some(MemoryPlan::new(model.borrow(), &order, &values_needed_until_step, outputs)?)
+        };
         #[allow(clippy::mutable_key_type)]
         let mut symbols: std::collections::HashSet<Symbol> = Default::default();
         for node in &model.borrow().nodes {
@@ -143,6 +210,9 @@ where
             model,
             order,
             flush_lists,
+            moved_inputs,
+            memory,
+            waves,
             outputs: outputs.to_vec(),
             has_unresolved_symbols: !symbols.is_empty(),
             _casper: PhantomData,
@@ -154,6 +224,10 @@ where
         &self.order
     }
 
+    pub fn memory_plan(&self) -> Option<&MemoryPlan> {
+        self.memory.as_ref()
+    }
+
     pub fn run(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let mut state = SimpleState::new(self)?;
         state.run(inputs)
@@ -176,6 +250,9 @@ where
     pub states: Vec<Option<Box<dyn OpState>>>,
     pub session_state: SessionState,
     pub values: Vec<Option<TVec<TValue>>>,
+    /// Symbols resolved from the inputs of the last turn.
+    input_symbols: Vec<Symbol>,
+    arena: Arena,
     _phantom: PhantomData<(M, F, O)>,
 }
 
@@ -191,8 +268,15 @@ where
         let session = SessionState::default();
         let model = plan.borrow().model();
         let states: Vec<Option<Box<dyn OpState>>> = vec![None; model.nodes.len()];
-        let mut state =
-            SimpleState { plan, states, session_state: session, values, _phantom: PhantomData };
+        let mut state = SimpleState {
+            plan,
+            states,
+            session_state: session,
+            values,
+            input_symbols: vec![],
+            arena: Arena::default(),
+            _phantom: PhantomData,
+        };
         state.populate_consts();
         state.reset_op_states()?;
         Ok(state)
@@ -224,14 +308,149 @@ where
         Ok(())
     }
 
+    /// Key/value caches states, in node order.
+    pub fn kv_caches(&self) -> impl Iterator<Item = &KvCacheState> {
+        self.states.iter().flatten().filter_map(|s| s.downcast_ref::<KvCacheState>())
+    }
+
+    pub fn kv_caches_mut(&mut self) -> impl Iterator<Item = &mut KvCacheState> {
+        self.states.iter_mut().flatten().filter_map(|s| s.downcast_mut::<KvCacheState>())
+    }
+
+    /// Empty all key/value caches, keeping their allocations.
+    pub fn reset_kv_caches(&mut self) -> TractResult<()> {
+        self.kv_caches_mut().for_each(|cache| cache.reset());
+        Ok(())
+    }
+
+    /// Keep only the first `len` positions in all key/value caches.
+    pub fn truncate_kv_caches(&mut self, len: usize) -> TractResult<()> {
+        self.kv_caches_mut().for_each(|cache| cache.truncate(len));
+        Ok(())
+    }
+
     pub fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
-        self.run_plan_with_eval(inputs, self::eval)
+        if self.plan().waves.is_some() {
+            self.set_inputs(inputs)?;
+            self.exec()?;
+            let outputs = self.outputs()?;
+            self.reset_turn()?;
+            Ok(outputs)
+        } else {
+            self.run_plan_with_eval(inputs, self::eval)
+        }
     }
 
     pub fn exec(&mut self) -> TractResult<()> {
-        self.exec_plan_with_eval(self::eval)
+        if self.plan().waves.is_some() {
+            self.exec_waves()
+        } else {
+            self.exec_plan_with_eval(self::eval)
+        }
+    }
+
+    /// Evaluate the plan wave by wave, running the stateless nodes of a wave concurrently on the
+    /// executor pool. Values cross threads as shared tensors, so ops do not compute in place.
+    fn exec_waves(&mut self) -> TractResult<()> {
+        let executor = self.plan().executor.clone().unwrap_or_else(current_tract_executor);
+        let Executor::MultiThread(pool) = executor else {
+            return self.exec_plan_with_eval(self::eval);
+        };
+        let &mut SimpleState {
+            ref plan,
+            ref mut session_state,
+            ref mut states,
+            ref mut values,
+            ..
+        } = self;
+        let plan = plan.borrow();
+        let model = plan.model();
+        session_state.inputs = std::mem::take(&mut session_state.inputs)
+            .into_iter()
+            .map(|(ix, t)| (ix, t.into_arc_tensor().into_tvalue()))
+            .collect();
+        for wave in plan.waves.as_ref().unwrap() {
+            let mut concurrent: Vec<(usize, &dyn Op, TVec<Arc<Tensor>>)> = vec![];
+            for &step in wave {
+                let node = model.node(plan.order[step]);
+                let mut inputs: TVec<TValue> = tvec![];
+                for i in &node.inputs {
+                    let prec = values[i.node].as_ref().ok_or_else(|| {
+                        format_err!(
+                            "Computing {}, precursor {} not done:",
+                            node,
+                            model.node(i.node)
+                        )
+                    })?;
+                    inputs.push(prec[i.slot].clone())
+                }
+                if node.op().is_stateless() {
+                    let inputs = inputs.into_iter().map(|v| v.into_arc_tensor()).collect();
+                    concurrent.push((node.id, node.op(), inputs));
+                } else {
+                    let vs = eval(session_state, states[node.id].as_deref_mut(), node, inputs)?;
+                    values[node.id] =
+                        Some(vs.into_iter().map(|v| v.into_arc_tensor().into_tvalue()).collect());
+                }
+            }
+            // SessionState is not Sync: each worker gets its own copy of the session inputs,
+            // symbols and tensors, and keeps its scratch space across the ops it evaluates
+            let session_inputs: HashMap<usize, Arc<Tensor>> = session_state
+                .inputs
+                .iter()
+                .map(|(ix, t)| (*ix, t.clone().into_arc_tensor()))
+                .collect();
+            let symbols = &session_state.resolved_symbols;
+            let tensors = &session_state.tensors;
+            let results: Vec<(usize, TractResult<TVec<Arc<Tensor>>>)> = pool.install(|| {
+                concurrent
+                    .into_par_iter()
+                    .map_init(
+                        || SessionState {
+                            inputs: session_inputs
+                                .iter()
+                                .map(|(ix, t)| (*ix, t.clone().into_tvalue()))
+                                .collect(),
+                            resolved_symbols: symbols.clone(),
+                            tensors: tensors.clone(),
+                            ..SessionState::default()
+                        },
+                        |session, (id, op, inputs)| {
+                            let inputs = inputs.into_iter().map(|t| t.into_tvalue()).collect();
+                            let outputs = op
+                                .eval_with_session(session, inputs)
+                                .map(|vs| vs.into_iter().map(|v| v.into_arc_tensor()).collect());
+                            (id, outputs)
+                        },
+                    )
+                    .collect()
+            });
+            for (id, outputs) in results {
+                let node = model.node(id);
+                let outputs = outputs.with_context(|| format!("Evaluating {node}"))?;
+                values[id] = Some(outputs.into_iter().map(|t| t.into_tvalue()).collect());
+            }
+            for &step in wave {
+                let node = model.node(plan.order[step]);
+                if plan.has_unresolved_symbols {
+                    Self::resolve_from_outputs(
+                        &mut session_state.resolved_symbols,
+                        node,
+                        values[node.id].as_ref().unwrap(),
+                    )?;
+                }
+            }
+            for &step in wave {
+                for flush in &plan.flush_lists[step] {
+                    values[*flush] = None;
+                }
+            }
+        }
+        Ok(())
     }
 
+    /// Run the plan sequentially with a custom eval function, ignoring
+    /// `PlanOptions::parallel_nodes`.
     pub fn run_plan_with_eval<Eval, E>(
         &mut self,
         inputs: TVec<TValue>,
@@ -288,21 +507,31 @@ where
                 ref mut session_state,
                 ref mut states,
                 ref mut values,
+                ref mut arena,
                 ..
             } = self;
             let plan = plan.borrow();
             let model = plan.model();
+            if let Some(memory) = &plan.memory {
+                arena.prepare(memory, &session_state.resolved_symbols);
+            }
             for (step, n) in plan.order.iter().enumerate() {
                 let node = model.node(*n);
                 trace!("Running step {}, node {}", step, node);
                 let mut inputs: TVec<TValue> = tvec![];
-                for i in &node.inputs {
+                for (ix, i) in node.inputs.iter().enumerate() {
                     trace!("  use input {:?}", i);
                     let prec_node = model.node(i.node);
-                    let prec = values[i.node].as_ref().ok_or_else(|| {
+                    let prec = values[i.node].as_mut().ok_or_else(|| {
                         format_err!("Computing {}, precursor {} not done:", node, prec_node)
                     })?;
-                    inputs.push(prec[i.slot].clone())
+                    if plan.moved_inputs[step].contains(&ix) {
+                        trace!("  move {:?}, last use", i);
+                        inputs.push(prec.swap_remove(i.slot));
+                        values[i.node] = None;
+                    } else {
+                        inputs.push(prec[i.slot].clone())
+                    }
                 }
 
                 for flush in &plan.flush_lists[step] {
@@ -333,21 +562,22 @@ where
                     }
                 }
 
+                if let Some(planned) = plan.memory.as_ref().map(|m| &m.by_node[node.id]) {
+                    let mut buffers = session_state.planned_outputs.borrow_mut();
+                    buffers.clear();
+                    for &(slot, ix) in planned {
+                        let len = buffers.len().max(slot + 1);
+                        buffers.resize_with(len, || None);
+                        buffers[slot] = arena.buffer(ix);
+                    }
+                }
+
                 let vs = eval(session_state, states[node.id].as_deref_mut(), node, inputs)
                     .map_err(|e| e.into())?;
+                session_state.planned_outputs.borrow_mut().clear();
 
                 if plan.has_unresolved_symbols {
-                    for (o, v) in node.outputs.iter().zip(vs.iter()) {
-                        if let Ok(f) = o.fact.to_typed_fact() {
-                            for (dim_abstract, dim_concrete) in f.shape.iter().zip(v.shape()) {
-                                Self::resolve(
-                                    &mut session_state.resolved_symbols,
-                                    dim_abstract,
-                                    *dim_concrete as i64,
-                                )?;
-                            }
-                        }
-                    }
+                    Self::resolve_from_outputs(&mut session_state.resolved_symbols, node, &vs)?;
                 }
                 if cfg!(debug_assertions) {
                     let facts = model.node_output_facts(node.id)?;
@@ -388,13 +618,56 @@ where
             self.model().inputs.len(),
             inputs.len()
         );
+        self.reset_turn_symbols();
         for (ix, t) in inputs.into_iter().enumerate() {
             self.set_input(ix, t)?
         }
         Ok(())
     }
 
-    fn resolve(symbols: &mut SymbolValues, expression: &TDim, provided: i64) -> TractResult<()> {
+    /// For models with key/value caches, forget symbols resolved from the previous turn inputs,
+    /// and expose the current caches lengths, so that input shapes can change from one turn to
+    /// the next. Other models keep symbols resolved from inputs across turns.
+    fn reset_turn_symbols(&mut self) {
+        if self.kv_caches().next().is_none() {
+            self.input_symbols.clear();
+            return;
+        }
+        let SimpleState { plan, session_state, states, input_symbols, .. } = self;
+        for sym in input_symbols.drain(..) {
+            session_state.resolved_symbols[&sym] = None;
+        }
+        for node in (*plan).borrow().model().nodes() {
+            if let Some(op) = node.op_as::<KvCache>() {
+                if let Some(cache) =
+                    states[node.id].as_ref().and_then(|s| s.downcast_ref::<KvCacheState>())
+                {
+                    session_state.resolved_symbols.set(&op.past, cache.len() as i64);
+                }
+            }
+        }
+    }
+
+    fn resolve_from_outputs(
+        symbols: &mut SymbolValues,
+        node: &Node<F, O>,
+        values: &[TValue],
+    ) -> TractResult<()> {
+        for (o, v) in node.outputs.iter().zip(values.iter()) {
+            if let Ok(f) = o.fact.to_typed_fact() {
+                for (dim_abstract, dim_concrete) in f.shape.iter().zip(v.shape()) {
+                    Self::resolve(symbols, dim_abstract, *dim_concrete as i64)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(
+        symbols: &mut SymbolValues,
+        expression: &TDim,
+        provided: i64,
+    ) -> TractResult<Option<Symbol>> {
         let expected = expression.eval(symbols);
         if let Ok(x) = expected.to_i64() {
             if x != provided {
@@ -406,9 +679,10 @@ where
             if let Some(v) = solve_for(&sym, &expected, &provided.to_dim()) {
                 debug!("Determined symbol {sym}={v}");
                 symbols[&sym] = Some(v.to_i64().unwrap());
+                return Ok(Some(sym));
             }
         }
-        Ok(())
+        Ok(None)
     }
 
     pub fn set_input(&mut self, input: usize, t: TValue) -> TractResult<()> {
@@ -417,12 +691,16 @@ where
             .input_outlets()?
             .get(input)
             .with_context(|| format!("Invalid input id for model ({input})."))?;
-        let SimpleState { plan, session_state, .. } = self;
+        let SimpleState { plan, session_state, input_symbols, .. } = self;
         let plan = (*plan).borrow();
         let model = plan.model.borrow();
         if let Ok(fact) = model.outlet_fact(outlet)?.to_typed_fact() {
             for (expected, provided) in fact.shape.iter().zip(t.shape()) {
-                Self::resolve(&mut session_state.resolved_symbols, expected, *provided as i64)?;
+                input_symbols.extend(Self::resolve(
+                    &mut session_state.resolved_symbols,
+                    expected,
+                    *provided as i64,
+                )?);
             }
         }
         let fact = self.plan.borrow().model().outlet_fact(outlet)?;
@@ -594,6 +872,27 @@ where
     }
 }
 
+/// Group the plan steps in waves, each node depending only on nodes from previous waves.
+fn waves<F, O>(model: &Graph<F, O>, order: &[usize], deps: &[(usize, usize)]) -> Vec<TVec<usize>>
+where
+    F: Fact + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+{
+    let mut wave_of_node: Vec<Option<usize>> = vec![None; model.nodes().len()];
+    let mut waves: Vec<TVec<usize>> = vec![];
+    for (step, &node) in order.iter().enumerate() {
+        let precs = model.node(node).inputs.iter().map(|i| i.node);
+        let precs = precs.chain(deps.iter().filter(|d| d.0 == node).map(|d| d.1));
+        let wave = precs.filter_map(|prec| wave_of_node[prec]).map(|w| w + 1).max().unwrap_or(0);
+        wave_of_node[node] = Some(wave);
+        if waves.len() <= wave {
+            waves.resize(wave + 1, tvec!());
+        }
+        waves[wave].push(step);
+    }
+    waves
+}
+
 pub fn eval<F, O>(
     session_state: &mut SessionState,
     mut state: Option<&mut (dyn OpState + 'static)>,
@@ -646,6 +945,7 @@ where
                 resolved_symbols: self.resolved_symbols.clone(),
                 tensors: self.tensors.clone(),
                 cached_mmm_scratch_space: None.into(),
+                planned_outputs: Default::default(),
             },
             states: self.states.iter().map(|s| s.as_ref().map(|s| s.unfreeze())).collect(),
             values: self
@@ -653,6 +953,8 @@ where
                 .iter()
                 .map(|t| t.as_ref().map(|t| t.iter().map(|t| t.clone().into_tvalue()).collect()))
                 .collect(),
+            input_symbols: vec![],
+            arena: Arena::default(),
             _phantom: PhantomData,
         };
         state.populate_consts();
@@ -663,6 +965,8 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ops::math::{add, mul};
+
     fn is_send<T: Send>() {}
     fn is_sync<T: Sync>() {}
 
@@ -690,4 +994,114 @@ mod test {
     fn frozen_type_state_is_send() {
         is_send::<TypedFrozenSimpleState<TypedModel, TypedSimplePlan<TypedModel>>>();
     }
+
+    #[test]
+    fn input_symbols_stick_across_turns() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let x = model.add_source("x", f32::fact(dims!(s.clone())))?;
+        let y = model.wire_node("y", add(), &[x, x])?;
+        model.set_output_outlets(&y)?;
+        let plan = SimplePlan::new(&model)?;
+        let mut state = SimpleState::new(&plan)?;
+        state.run(tvec!(tensor1(&[0f32; 3]).into()))?;
+        assert_eq!(state.session_state.resolved_symbols[&s], Some(3));
+        assert!(state.run(tvec!(tensor1(&[0f32; 4]).into())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_nodes_match_sequential() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([8usize]))?;
+        let mut towers = tvec!();
+        for i in 0..4 {
+            let k = model.add_const(format!("k{i}"), tensor1(&[i as f32 + 1.; 8]))?;
+            let a = model.wire_node(format!("mul{i}"), mul(), &[x, k])?[0];
+            towers.push(model.wire_node(format!("add{i}"), add(), &[a, k])?[0]);
+        }
+        let mut sum = towers[0];
+        for (i, tower) in towers.iter().enumerate().skip(1) {
+            sum = model.wire_node(format!("sum{i}"), add(), &[sum, *tower])?[0];
+        }
+        model.set_output_outlets(&[sum])?;
+        let input = tensor1(&(0..8).map(|x| x as f32).collect::<Vec<_>>());
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone().into()))?;
+
+        let options = PlanOptions {
+            parallel_nodes: true,
+            executor: Some(Executor::multithread(4)),
+            ..PlanOptions::default()
+        };
+        let plan = SimplePlan::new_with_options(&model, &options)?;
+        // the four towers start in the same wave, right after the source
+        assert_eq!(plan.waves.as_ref().unwrap()[1].len(), 4);
+        let mut state = SimpleState::new(&plan)?;
+        for _ in 0..3 {
+            assert_eq!(state.run(tvec!(input.clone().into()))?, expected);
+        }
+        Ok(())
+    }
+
+    /// Adds a tensor from the session state to its input.
+    #[derive(Debug, Clone, Hash)]
+    struct AddSessionTensor(String);
+
+    impl Op for AddSessionTensor {
+        fn name(&self) -> Cow<str> {
+            "AddSessionTensor".into()
+        }
+
+        op_as_typed_op!();
+    }
+
+    impl EvalOp for AddSessionTensor {
+        fn is_stateless(&self) -> bool {
+            true
+        }
+
+        fn eval_with_session(
+            &self,
+            session: &SessionState,
+            inputs: TVec<TValue>,
+        ) -> TractResult<TVec<TValue>> {
+            let tensor = session.tensors.get(&self.0).context("Missing session tensor")?;
+            let sum = &inputs[0].to_array_view::<f32>()? + &tensor.to_array_view::<f32>()?;
+            Ok(tvec!(sum.into_tvalue()))
+        }
+    }
+
+    impl TypedOp for AddSessionTensor {
+        fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+            Ok(tvec!(inputs[0].without_value()))
+        }
+
+        as_op!();
+    }
+
+    #[test]
+    fn parallel_nodes_see_session_tensors() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([4usize]))?;
+        let towers = (0..4)
+            .map(|i| {
+                Ok(model.wire_node(format!("tower{i}"), AddSessionTensor("t".into()), &[x])?[0])
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        model.set_output_outlets(&towers)?;
+        let options = PlanOptions {
+            parallel_nodes: true,
+            executor: Some(Executor::multithread(4)),
+            ..PlanOptions::default()
+        };
+        let plan = SimplePlan::new_with_options(&model, &options)?;
+        assert_eq!(plan.waves.as_ref().unwrap()[1].len(), 4);
+        let mut state = SimpleState::new(&plan)?;
+        state.session_state.tensors.insert("t".into(), tensor1(&[1f32, 2., 3., 4.]));
+        let outputs = state.run(tvec!(tensor1(&[1f32; 4]).into()))?;
+        for output in outputs {
+            assert_eq!(*output, tensor1(&[2f32, 3., 4., 5.]));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,354 @@
+//! Static memory planning for SimplePlan.
+//!
+//! Outputs of ops opting in with `EvalOp::uses_planned_outputs` get a region in a single arena
+//! per SimpleState, chosen from their liveness in the plan order so that values never alive at
+//! the same time share memory. The arena is reused from one run to the next.
+//!
+//! Tensors built over the arena keep their region alive: a region is only handed out again
+//! once every tensor using it or a region overlapping it is gone, so a value retained beyond
+//! its planned lifetime (by an op state, or passed through to a model output) just makes the
+//! next producer fall back to a regular allocation.
+
+use std::alloc::Layout;
+use std::any::Any;
+use std::fmt;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph};
+use tract_itertools::Itertools;
+
+/// Alignment of every region in the arena.
+pub const ARENA_ALIGNMENT: usize = 128;
+
+fn align(size: usize) -> usize {
+    size.div_ceil(ARENA_ALIGNMENT) * ARENA_ALIGNMENT
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedOutlet {
+    pub outlet: OutletId,
+    /// Size in bytes, possibly depending on symbols.
+    pub size: TDim,
+    /// Steps of the plan order where the value is produced and last consumed.
+    pub live_from: usize,
+    pub live_until: usize,
+}
+
+impl PlannedOutlet {
+    fn lives_with(&self, other: &PlannedOutlet) -> bool {
+        self.live_from <= other.live_until && other.live_from <= self.live_until
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPlan {
+    pub outlets: Vec<PlannedOutlet>,
+    /// Planned outputs of each node, as (slot, index in outlets).
+    pub by_node: Vec<TVec<(usize, usize)>>,
+    /// Layout computed once and for all when no size depends on symbols.
+    pub resolved: Option<Arc<ResolvedMemoryPlan>>,
+}
+
+impl MemoryPlan {
+    pub fn new<F, O>(
+        model: &Graph<F, O>,
+        order: &[usize],
+        values_needed_until_step: &[usize],
+        outputs: &[OutletId],
+    ) -> TractResult<MemoryPlan>
+    where
+        F: Fact + Clone + 'static,
+        O: fmt::Debug + fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    {
+        let mut plan =
+            MemoryPlan { by_node: vec![tvec!(); model.nodes().len()], ..MemoryPlan::default() };
+        for (step, &node) in order.iter().enumerate() {
+            let node = model.node(node);
+            if !node.op.as_ref().uses_planned_outputs() {
+                continue;
+            }
+            // values without consumer stay around until the end of the turn
+            let live_until = if values_needed_until_step[node.id] > step {
+                values_needed_until_step[node.id]
+            } else {
+                order.len()
+            };
+            for (slot, output) in node.outputs.iter().enumerate() {
+                let outlet = OutletId::new(node.id, slot);
+                if outputs.contains(&outlet) {
+                    continue;
+                }
+                let Ok(fact) = output.fact.to_typed_fact() else { continue };
+                if !fact.datum_type.is_copy() {
+                    continue;
+                }
+                let size = fact.shape.iter().product::<TDim>() * fact.datum_type.size_of();
+                plan.by_node[node.id].push((slot, plan.outlets.len()));
+                plan.outlets.push(PlannedOutlet { outlet, size, live_from: step, live_until });
+            }
+        }
+        if plan.outlets.iter().all(|o| o.size.to_usize().is_ok()) {
+            let sizes = plan.outlets.iter().map(|o| o.size.to_usize().ok()).collect();
+            plan.resolved = Some(Arc::new(ResolvedMemoryPlan::new(&plan.outlets, sizes)));
+        }
+        Ok(plan)
+    }
+
+    /// Compute the layout for the current symbol values. Outlets with a size that can not be
+    /// evaluated yet are left out.
+    pub fn resolve(&self, symbols: &SymbolValues) -> Arc<ResolvedMemoryPlan> {
+        if let Some(resolved) = &self.resolved {
+            return resolved.clone();
+        }
+        Arc::new(ResolvedMemoryPlan::new(&self.outlets, self.sizes(symbols)))
+    }
+
+    fn sizes(&self, symbols: &SymbolValues) -> Vec<Option<usize>> {
+        self.outlets.iter().map(|o| o.size.eval(symbols).to_usize().ok()).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedMemoryPlan {
+    pub sizes: Vec<Option<usize>>,
+    pub offsets: Vec<Option<usize>>,
+    /// For each outlet, the outlets using some of its memory at another time.
+    pub conflicts: Vec<TVec<usize>>,
+    pub arena_size: usize,
+}
+
+impl ResolvedMemoryPlan {
+    /// Greedy first-fit placement, largest values first.
+    pub fn new(outlets: &[PlannedOutlet], sizes: Vec<Option<usize>>) -> ResolvedMemoryPlan {
+        let mut offsets: Vec<Option<usize>> = vec![None; outlets.len()];
+        let mut placed: Vec<usize> = vec![];
+        let candidates = (0..outlets.len())
+            .filter(|&ix| sizes[ix].is_some_and(|s| s > 0))
+            .sorted_by_key(|&ix| (std::cmp::Reverse(sizes[ix]), outlets[ix].live_from));
+        for ix in candidates {
+            let size = sizes[ix].unwrap();
+            let mut offset = 0;
+            for other in placed
+                .iter()
+                .filter(|&&other| outlets[ix].lives_with(&outlets[other]))
+                .sorted_by_key(|&&other| offsets[other])
+            {
+                let other_offset = offsets[*other].unwrap();
+                if offset + size <= other_offset {
+                    break;
+                }
+                offset = offset.max(align(other_offset + sizes[*other].unwrap()));
+            }
+            offsets[ix] = Some(offset);
+            placed.push(ix);
+        }
+        let range = |ix: usize| offsets[ix].map(|o| o..o + sizes[ix].unwrap());
+        let mut conflicts = vec![tvec!(); outlets.len()];
+        for (a, b) in placed.iter().tuple_combinations() {
+            let (ra, rb) = (range(*a).unwrap(), range(*b).unwrap());
+            if ra.start < rb.end && rb.start < ra.end {
+                conflicts[*a].push(*b);
+                conflicts[*b].push(*a);
+            }
+        }
+        let arena_size = placed.iter().map(|&ix| range(ix).unwrap().end).max().unwrap_or(0);
+        ResolvedMemoryPlan { sizes, offsets, conflicts, arena_size }
+    }
+}
+
+struct ArenaStorage {
+    ptr: *mut u8,
+    size: usize,
+    _blob: Blob,
+}
+
+unsafe impl Send for ArenaStorage {}
+unsafe impl Sync for ArenaStorage {}
+
+/// A region of the arena, shared by every tensor built over it.
+struct ArenaRegion(#[allow(dead_code)] Arc<ArenaStorage>);
+
+/// Memory arena backing the planned outputs of a SimpleState.
+#[derive(Default)]
+pub struct Arena {
+    resolved: Option<Arc<ResolvedMemoryPlan>>,
+    storage: Option<Arc<ArenaStorage>>,
+    regions: Vec<Arc<ArenaRegion>>,
+}
+
+impl Clone for Arena {
+    fn clone(&self) -> Self {
+        Arena::default()
+    }
+}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Arena({} bytes)", self.storage.as_ref().map(|s| s.size).unwrap_or(0))
+    }
+}
+
+impl Arena {
+    /// Get the arena ready for a turn with the given symbols values.
+    pub fn prepare(&mut self, plan: &MemoryPlan, symbols: &SymbolValues) {
+        if plan.outlets.is_empty() {
+            return;
+        }
+        let resolved = match (&plan.resolved, &self.resolved) {
+            (Some(resolved), _) => resolved.clone(),
+            (None, Some(current)) if current.sizes == plan.sizes(symbols) => current.clone(),
+            (None, _) => plan.resolve(symbols),
+        };
+        let same_layout = self.resolved.as_ref().is_some_and(|r| Arc::ptr_eq(r, &resolved));
+        let in_use = self.regions.iter().any(|r| Arc::strong_count(r) > 1);
+        let big_enough = self.storage.as_ref().is_some_and(|s| s.size >= resolved.arena_size);
+        // tensors surviving from a previous layout may overlap anything in the new one
+        if !big_enough || (!same_layout && in_use) {
+            let size = resolved.arena_size;
+            let mut blob = unsafe { Blob::new_for_size_and_align(size, ARENA_ALIGNMENT) };
+            let ptr = blob.as_bytes_mut().as_mut_ptr();
+            self.storage = Some(Arc::new(ArenaStorage { ptr, size, _blob: blob }));
+            self.regions.clear();
+        }
+        if self.regions.len() != plan.outlets.len() {
+            let storage = self.storage.as_ref().unwrap();
+            self.regions =
+                (0..plan.outlets.len()).map(|_| Arc::new(ArenaRegion(storage.clone()))).collect();
+        }
+        self.resolved = Some(resolved);
+    }
+
+    /// The buffer for planned outlet `ix`, if it is placed and free.
+    pub fn buffer(&self, ix: usize) -> Option<PlannedBuffer> {
+        let resolved = self.resolved.as_ref()?;
+        let offset = resolved.offsets[ix]?;
+        let storage = self.storage.as_ref()?;
+        if std::iter::once(&ix)
+            .chain(resolved.conflicts[ix].iter())
+            .any(|&other| Arc::strong_count(&self.regions[other]) > 1)
+        {
+            return None;
+        }
+        Some(PlannedBuffer {
+            ptr: unsafe { storage.ptr.add(offset) },
+            size: resolved.sizes[ix]?,
+            owner: self.regions[ix].clone(),
+        })
+    }
+}
+
+/// A region of the arena reserved for an op output during its evaluation.
+pub struct PlannedBuffer {
+    ptr: *mut u8,
+    size: usize,
+    owner: Arc<ArenaRegion>,
+}
+
+unsafe impl Send for PlannedBuffer {}
+
+impl fmt::Debug for PlannedBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PlannedBuffer({} bytes)", self.size)
+    }
+}
+
+impl PlannedBuffer {
+    /// Build an uninitialized tensor over the buffer, if it is big enough.
+    pub unsafe fn into_tensor(self, dt: DatumType, shape: &[usize]) -> TractResult<Option<Tensor>> {
+        let bytes = shape.iter().product::<usize>() * dt.size_of();
+        if !dt.is_copy() || bytes == 0 || bytes > self.size {
+            return Ok(None);
+        }
+        let owner: Arc<dyn Any + Send + Sync> = self.owner;
+        let layout = Layout::from_size_align_unchecked(bytes, ARENA_ALIGNMENT);
+        let blob = Blob::from_raw_parts_owned_by(self.ptr, layout, owner);
+        Ok(Some(Tensor::from_blob(dt, shape, blob)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::einsum::EinSum;
+
+    fn outlet(live_from: usize, live_until: usize) -> PlannedOutlet {
+        PlannedOutlet { outlet: OutletId::new(0, 0), size: 0.into(), live_from, live_until }
+    }
+
+    #[test]
+    fn reuse_memory_of_dead_values() {
+        let outlets = [outlet(0, 1), outlet(1, 2), outlet(2, 3)];
+        let plan = ResolvedMemoryPlan::new(&outlets, vec![Some(100), Some(100), Some(100)]);
+        assert_eq!(plan.offsets, vec![Some(0), Some(128), Some(0)]);
+        assert_eq!(plan.arena_size, 228);
+        assert_eq!(plan.conflicts, vec![tvec!(2), tvec!(), tvec!(0)]);
+    }
+
+    #[test]
+    fn unknown_sizes_are_not_placed() {
+        let outlets = [outlet(0, 1), outlet(0, 1)];
+        let plan = ResolvedMemoryPlan::new(&outlets, vec![None, Some(10)]);
+        assert_eq!(plan.offsets, vec![None, Some(0)]);
+    }
+
+    fn matmul_chain() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("x", f32::fact([4, 4]))?;
+        for ix in 0..4 {
+            let w = model.add_const(
+                format!("w{ix}"),
+                Tensor::from_shape(&[4, 4], &(0..16).map(|x| x as f32 / 16.).collect_vec())?,
+            )?;
+            wire = model.wire_node(
+                format!("mm{ix}"),
+                EinSum::new("mk,kn->mn".parse()?, f32::datum_type()),
+                &[wire, w],
+            )?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        model.into_optimized()
+    }
+
+    #[test]
+    fn arena_matches_heap() -> TractResult<()> {
+        let model = matmul_chain()?;
+        let input = tensor2(&[[1f32, 2., 3., 4.]; 4]);
+        let heap = SimplePlan::new_with_options(
+            model.clone(),
+            &PlanOptions { skip_memory_arena: true, ..PlanOptions::default() },
+        )?;
+        let expected = heap.run(tvec!(input.clone().into_tvalue()))?;
+        let plan = SimplePlan::new(model)?;
+        assert!(plan.memory_plan().is_some_and(|m| !m.outlets.is_empty()));
+        let mut state = SimpleState::new(&plan)?;
+        for _ in 0..3 {
+            let found = state.run(tvec!(input.clone().into_tvalue()))?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn retained_values_are_not_overwritten() -> TractResult<()> {
+        let outlets = vec![outlet(0, 1), outlet(1, 2), outlet(2, 3)];
+        let plan = MemoryPlan {
+            by_node: vec![],
+            resolved: Some(Arc::new(ResolvedMemoryPlan::new(&outlets, vec![Some(64); 3]))),
+            outlets,
+        };
+        let mut arena = Arena::default();
+        arena.prepare(&plan, &SymbolValues::default());
+        let kept = unsafe { arena.buffer(0).unwrap().into_tensor(f32::datum_type(), &[16])? };
+        assert!(kept.is_some());
+        // 2 shares the memory of 0
+        assert!(arena.buffer(2).is_none());
+        assert!(arena.buffer(1).is_some());
+        // same goes for next turns, until the tensor is dropped
+        arena.prepare(&plan, &SymbolValues::default());
+        assert!(arena.buffer(0).is_none());
+        std::mem::drop(kept);
+        assert!(arena.buffer(0).is_some());
+        assert!(arena.buffer(2).is_some());
+        Ok(())
+    }
+}
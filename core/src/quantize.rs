@@ -0,0 +1,582 @@
+//! Post-training quantization of float models.
+//!
+//! Static quantization measures activation ranges once, by running calibration inputs through
+//! the float model, then rewrites convolutions and matrix products to their int8 forms. Values
+//! are quantized right before a rewritten op and dequantized right after it, and consecutive
+//! rewritten ops exchange quantized values directly.
+//!
+//! Dynamic quantization only needs the weights: they are quantized once, while activations are
+//! quantized on each run from their actual range, as ONNX `DynamicQuantizeLinear` and
+//! `MatMulInteger` do.
+
+use crate::internal::*;
+use crate::ops::binary::wire_with_rank_broadcast;
+use crate::ops::cast::{cast, Cast};
+use crate::ops::cnn::Conv;
+use crate::ops::einsum::EinSum;
+use crate::ops::math::mul;
+use crate::ops::quant::DynamicQuantizeLinearU8;
+use crate::transform::ModelTransform;
+
+/// Number of values per tensor kept for percentile estimation.
+const PERCENTILE_SAMPLES: usize = 1 << 16;
+
+/// How activation ranges are derived from the values observed during calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationMethod {
+    /// Smallest and largest observed values.
+    MinMax,
+    /// Symmetric percentiles of the observed values (e.g. 99.99), clipping outliers.
+    Percentile(f32),
+}
+
+#[derive(Debug, Clone)]
+pub enum CalibrationData {
+    /// One set of model inputs per sample.
+    Inputs(Vec<TVec<Tensor>>),
+}
+
+/// Static int8 post-training quantization.
+#[derive(Debug, Clone)]
+pub struct StaticQuantization {
+    pub calibration: CalibrationData,
+    pub method: CalibrationMethod,
+    /// Activation storage type, I8 or U8. Weights are always symmetric I8.
+    pub activation_dt: DatumType,
+}
+
+impl StaticQuantization {
+    pub fn new(calibration: CalibrationData) -> StaticQuantization {
+        StaticQuantization {
+            calibration,
+            method: CalibrationMethod::MinMax,
+            activation_dt: DatumType::I8,
+        }
+    }
+
+    pub fn with_method(self, method: CalibrationMethod) -> StaticQuantization {
+        StaticQuantization { method, ..self }
+    }
+
+    pub fn with_activation_dt(self, activation_dt: DatumType) -> StaticQuantization {
+        StaticQuantization { activation_dt, ..self }
+    }
+
+    /// Run the calibration samples, returning the range of each of the `outlets`.
+    fn calibrate(
+        &self,
+        model: &TypedModel,
+        samples: &[TVec<Tensor>],
+        outlets: &[OutletId],
+    ) -> TractResult<HashMap<OutletId, (f32, f32)>> {
+        let plan = SimplePlan::new(model)?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut observed: HashMap<OutletId, Observation> = HashMap::default();
+        for sample in samples {
+            let inputs = sample.iter().map(|t| t.clone().into_tvalue()).collect();
+            state.run_plan_with_eval(inputs, |session, op_state, node, inputs| {
+                let outputs = crate::plan::eval(session, op_state, node, inputs)?;
+                for (slot, value) in outputs.iter().enumerate() {
+                    let outlet = OutletId::new(node.id, slot);
+                    if outlets.contains(&outlet) {
+                        observed.entry(outlet).or_default().observe(value, self.method)?;
+                    }
+                }
+                TractResult::Ok(outputs)
+            })?;
+        }
+        observed.into_iter().map(|(outlet, obs)| Ok((outlet, obs.range(self.method)))).collect()
+    }
+
+    fn quantized_dt(&self, (min, max): (f32, f32)) -> DatumType {
+        let (min, max) = (min.min(0.), max.max(0.));
+        let (qmin, qmax) =
+            if self.activation_dt == DatumType::U8 { (0., 255.) } else { (-128., 127.) };
+        let scale = if max > min { (max - min) / (qmax - qmin) } else { 1. };
+        let zero_point = (qmin - min / scale).round().clamp(qmin, qmax) as i32;
+        let qp = QParams::ZpScale { zero_point, scale };
+        if self.activation_dt == DatumType::U8 {
+            DatumType::QU8(qp)
+        } else {
+            DatumType::QI8(qp)
+        }
+    }
+
+    fn quantize_conv(
+        &self,
+        model: &mut TypedModel,
+        node: usize,
+        conv: &Conv,
+        x_dt: DatumType,
+        c_dt: DatumType,
+    ) -> TractResult<()> {
+        let node = model.node(node);
+        let name = &node.name;
+        let mut patch = TypedModelPatch::default();
+        let x = wire_quantized_input(&mut patch, model, node.inputs[0], x_dt, name)?;
+        let kernel = model.outlet_fact(node.inputs[1])?.konst.clone().unwrap();
+        let kernel = quantize_weights(&kernel)?;
+        let bias = model.outlet_fact(node.inputs[2])?.konst.clone().unwrap();
+        let (x0, x_scale) = x_dt.zp_scale();
+        let (k0, k_scale) = kernel.datum_type().zp_scale();
+        let (c0, c_scale) = c_dt.zp_scale();
+        let bias = bias
+            .cast_to::<f32>()?
+            .to_array_view::<f32>()?
+            .mapv(|b| (b / (x_scale * k_scale)).round() as i32)
+            .into_tensor();
+        let inputs = [
+            x,
+            patch.add_const(format!("{name}.kernel"), kernel)?,
+            patch.add_const(format!("{name}.bias"), bias)?,
+            patch.add_const(format!("{name}.x0"), rctensor0(x0))?,
+            patch.add_const(format!("{name}.x_scale"), rctensor0(x_scale))?,
+            patch.add_const(format!("{name}.k0"), rctensor0(k0))?,
+            patch.add_const(format!("{name}.k_scale"), rctensor0(k_scale))?,
+            patch.add_const(format!("{name}.c0"), rctensor0(c0))?,
+            patch.add_const(format!("{name}.c_scale"), rctensor0(c_scale))?,
+        ];
+        let op = Conv { q_params: Some(c_dt), ..conv.clone() };
+        let wire = patch.wire_node(format!("{name}.quantized"), op, &inputs)?;
+        let wire = patch.wire_node(name, cast(f32::datum_type()), &wire)?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        patch.apply(model)
+    }
+
+    fn quantize_einsum(
+        &self,
+        model: &mut TypedModel,
+        node: usize,
+        einsum: &EinSum,
+        input_dts: &[Option<DatumType>],
+        c_dt: DatumType,
+    ) -> TractResult<()> {
+        let node = model.node(node);
+        let name = &node.name;
+        let mut patch = TypedModelPatch::default();
+        let mut operands = tvec!();
+        for (ix, input) in node.inputs.iter().enumerate() {
+            if let Some(dt) = input_dts[ix] {
+                operands.push(wire_quantized_input(&mut patch, model, *input, dt, name)?);
+            } else {
+                let weights = model.outlet_fact(*input)?.konst.clone().unwrap();
+                let weights = quantize_weights(&weights)?;
+                operands.push(patch.add_const(format!("{name}.weights.{ix}"), weights)?);
+            }
+        }
+        let a_qp = patch.outlet_fact(operands[0])?.datum_type.zp_scale();
+        let b_qp = patch.outlet_fact(operands[1])?.datum_type.zp_scale();
+        let c_qp = c_dt.zp_scale();
+        // a plain i32 bias: a quantized one would make codegen rescale the requantized output
+        let inputs = [
+            operands[0],
+            operands[1],
+            patch.add_const(format!("{name}.bias"), tensor0(0i32))?,
+            patch.add_const(format!("{name}.a0"), rctensor0(a_qp.0))?,
+            patch.add_const(format!("{name}.a_scale"), rctensor0(a_qp.1))?,
+            patch.add_const(format!("{name}.b0"), rctensor0(b_qp.0))?,
+            patch.add_const(format!("{name}.b_scale"), rctensor0(b_qp.1))?,
+            patch.add_const(format!("{name}.c0"), rctensor0(c_qp.0))?,
+            patch.add_const(format!("{name}.c_scale"), rctensor0(c_qp.1))?,
+        ];
+        let mut axes = einsum.axes.clone();
+        for input in 0..7 {
+            axes = axes.with_extra_input(2 + input)?;
+        }
+        let op = EinSum::newq(axes, i32::datum_type(), c_dt);
+        let wire = patch.wire_node(format!("{name}.quantized"), op, &inputs)?;
+        let wire = patch.wire_node(name, cast(f32::datum_type()), &wire)?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        patch.apply(model)
+    }
+}
+
+impl ModelTransform for StaticQuantization {
+    fn name(&self) -> Cow<str> {
+        "int8-static".into()
+    }
+
+    fn transform(&self, model: &mut TypedModel) -> TractResult<()> {
+        ensure!(
+            matches!(self.activation_dt, DatumType::I8 | DatumType::U8),
+            "Activations can only be quantized to I8 or U8, got {:?}",
+            self.activation_dt
+        );
+        let CalibrationData::Inputs(samples) = &self.calibration;
+        ensure!(!samples.is_empty(), "Static quantization requires calibration samples");
+        let candidates: Vec<usize> = model
+            .eval_order()?
+            .into_iter()
+            .filter(|&node| is_quantizable(model, model.node(node)))
+            .collect();
+        let mut outlets = vec![];
+        for &node in &candidates {
+            let node = model.node(node);
+            outlets.push(OutletId::new(node.id, 0));
+            for input in &node.inputs {
+                if model.outlet_fact(*input)?.konst.is_none() {
+                    outlets.push(*input);
+                }
+            }
+        }
+        let ranges = self.calibrate(model, samples, &outlets)?;
+        // compute all the types before patching: inputs of the later nodes are rewired
+        let mut rewrites = vec![];
+        for &node in &candidates {
+            let node = model.node(node);
+            let input_dts = node
+                .inputs
+                .iter()
+                .map(|i| ranges.get(i).map(|range| self.quantized_dt(*range)))
+                .collect::<TVec<_>>();
+            let Some(output) = ranges.get(&OutletId::new(node.id, 0)) else { continue };
+            rewrites.push((node.id, input_dts, self.quantized_dt(*output)));
+        }
+        for (node, input_dts, c_dt) in rewrites {
+            if let Some(conv) = model.node(node).op_as::<Conv>().cloned() {
+                let Some(x_dt) = input_dts[0] else { continue };
+                self.quantize_conv(model, node, &conv, x_dt, c_dt)?;
+            } else if let Some(einsum) = model.node(node).op_as::<EinSum>().cloned() {
+                if input_dts.iter().all(|dt| dt.is_none()) {
+                    continue;
+                }
+                self.quantize_einsum(model, node, &einsum, &input_dts, c_dt)?;
+            }
+        }
+        model.compact()
+    }
+}
+
+/// Parse a static quantization specification, `[minmax:|percentile=<p>:][i8:|u8:]<calibration>`.
+///
+/// Returns the transform, with no calibration samples yet, and the calibration source, left to
+/// the caller to load.
+pub fn parse_static_quantization(spec: &str) -> Option<(StaticQuantization, &str)> {
+    let mut options: Vec<&str> = spec.split(':').collect();
+    let source = options.pop().filter(|p| !p.is_empty())?;
+    let mut transform = StaticQuantization::new(CalibrationData::Inputs(vec![]));
+    for option in options {
+        transform = match option {
+            "minmax" => transform.with_method(CalibrationMethod::MinMax),
+            "i8" => transform.with_activation_dt(DatumType::I8),
+            "u8" => transform.with_activation_dt(DatumType::U8),
+            p => {
+                let p = p.strip_prefix("percentile=")?.parse().ok()?;
+                transform.with_method(CalibrationMethod::Percentile(p))
+            }
+        }
+    }
+    Some((transform, source))
+}
+
+/// Dynamic int8 quantization of matrix products with constant weights.
+///
+/// Weights are quantized to symmetric I8, activations to U8 at runtime, and the i32 products are
+/// scaled back to f32.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DynamicQuantization;
+
+impl DynamicQuantization {
+    fn quantize_einsum(
+        &self,
+        model: &mut TypedModel,
+        node: usize,
+        einsum: &EinSum,
+    ) -> TractResult<()> {
+        let node = model.node(node);
+        let name = &node.name;
+        let mut patch = TypedModelPatch::default();
+        let mut operands = tvec!();
+        let mut zero_points = tvec!();
+        let mut scales = tvec!();
+        for (ix, input) in node.inputs.iter().enumerate() {
+            if let Some(weights) = &model.outlet_fact(*input)?.konst {
+                let mut weights = quantize_weights(weights)?;
+                let (_, scale) = weights.datum_type().zp_scale();
+                unsafe { weights.set_datum_type(i8::datum_type()) };
+                operands.push(patch.add_const(format!("{name}.weights.{ix}"), weights)?);
+                zero_points.push(patch.add_const(format!("{name}.zero_point.{ix}"), tensor0(0i8))?);
+                scales.push(patch.add_const(format!("{name}.scale.{ix}"), tensor0(scale))?);
+            } else {
+                let wire = patch.tap_model(model, *input)?;
+                let quant = patch.wire_node(
+                    format!("{name}.quantize.{ix}"),
+                    DynamicQuantizeLinearU8,
+                    &[wire],
+                )?;
+                operands.push(quant[0]);
+                scales.push(quant[1]);
+                zero_points.push(quant[2]);
+            }
+        }
+        // the accumulator is computed in i32 with unit scales, rescaling happens in f32
+        let inputs = [
+            operands[0],
+            operands[1],
+            patch.add_const(format!("{name}.bias"), tensor0(0i32))?,
+            zero_points[0],
+            patch.add_const(format!("{name}.a_scale"), tensor0(1f32))?,
+            zero_points[1],
+            patch.add_const(format!("{name}.b_scale"), tensor0(1f32))?,
+            patch.add_const(format!("{name}.c0"), tensor0(0i32))?,
+            patch.add_const(format!("{name}.c_scale"), tensor0(1f32))?,
+        ];
+        let mut axes = einsum.axes.clone();
+        for input in 0..7 {
+            axes = axes.with_extra_input(2 + input)?;
+        }
+        let op = EinSum::newq(axes, i32::datum_type(), i32::datum_type());
+        let wire = patch.wire_node(format!("{name}.quantized"), op, &inputs)?;
+        let wire = patch.wire_node(format!("{name}.dequantize"), cast(f32::datum_type()), &wire)?;
+        let scale = patch.wire_node(format!("{name}.scale"), mul(), &[scales[0], scales[1]])?;
+        let wire = wire_with_rank_broadcast(name, &mut patch, mul(), &[wire[0], scale[0]])?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        patch.apply(model)
+    }
+}
+
+impl ModelTransform for DynamicQuantization {
+    fn name(&self) -> Cow<str> {
+        "int8-dynamic".into()
+    }
+
+    fn transform(&self, model: &mut TypedModel) -> TractResult<()> {
+        let candidates: Vec<usize> = model
+            .eval_order()?
+            .into_iter()
+            .filter(|&node| is_dynamically_quantizable(model, model.node(node)))
+            .collect();
+        for node in candidates {
+            let einsum = model.node(node).op_as::<EinSum>().unwrap().clone();
+            self.quantize_einsum(model, node, &einsum)?;
+        }
+        model.compact()
+    }
+}
+
+/// Values seen on an outlet during calibration.
+#[derive(Debug, Default)]
+struct Observation {
+    min: f32,
+    max: f32,
+    samples: Vec<f32>,
+}
+
+impl Observation {
+    fn observe(&mut self, value: &Tensor, method: CalibrationMethod) -> TractResult<()> {
+        let value = value.cast_to::<f32>()?;
+        let values = value.as_slice::<f32>()?;
+        for &v in values {
+            self.min = self.min.min(v);
+            self.max = self.max.max(v);
+        }
+        if let CalibrationMethod::Percentile(_) = method {
+            let stride = values.len().div_ceil(PERCENTILE_SAMPLES).max(1);
+            self.samples.extend(values.iter().step_by(stride));
+        }
+        Ok(())
+    }
+
+    fn range(mut self, method: CalibrationMethod) -> (f32, f32) {
+        match method {
+            CalibrationMethod::Percentile(p) if !self.samples.is_empty() => {
+                self.samples.sort_by(|a, b| a.total_cmp(b));
+                let last = (self.samples.len() - 1) as f32;
+                let at = |q: f32| self.samples[(q.clamp(0., 1.) * last).round() as usize];
+                (at(1. - p / 100.), at(p / 100.))
+            }
+            _ => (self.min, self.max),
+        }
+    }
+}
+
+fn is_quantizable(model: &TypedModel, node: &TypedNode) -> bool {
+    let Ok(facts) = model.node_input_facts(node.id) else { return false };
+    if !facts.iter().all(|f| f.datum_type == f32::datum_type()) {
+        return false;
+    }
+    if let Some(conv) = node.op_as::<Conv>() {
+        conv.q_params.is_none() && facts[1].konst.is_some() && facts[2].konst.is_some()
+    } else if let Some(einsum) = node.op_as::<EinSum>() {
+        einsum.q_params.is_none()
+            && einsum.operating_dt == f32::datum_type()
+            && node.inputs.len() == 2
+    } else {
+        false
+    }
+}
+
+fn is_dynamically_quantizable(model: &TypedModel, node: &TypedNode) -> bool {
+    node.op_is::<EinSum>()
+        && is_quantizable(model, node)
+        && node.inputs.iter().filter(|i| model.outlet_fact(**i).unwrap().konst.is_some()).count()
+            == 1
+}
+
+/// Symmetric per-tensor I8 quantization of constant weights.
+fn quantize_weights(weights: &Tensor) -> TractResult<Tensor> {
+    let weights = weights.cast_to::<f32>()?;
+    let max = weights.as_slice::<f32>()?.iter().fold(0f32, |acc, w| acc.max(w.abs()));
+    let scale = if max > 0. { max / 127. } else { 1. };
+    Ok(weights.cast_to_dt(DatumType::QI8(QParams::ZpScale { zero_point: 0, scale }))?.into_owned())
+}
+
+/// Wire `outlet` quantized to `dt`, reusing the quantized value when it is the output of a
+/// previous rewrite.
+fn wire_quantized_input(
+    patch: &mut TypedModelPatch,
+    model: &TypedModel,
+    outlet: OutletId,
+    dt: DatumType,
+    name: &str,
+) -> TractResult<OutletId> {
+    let prec = model.node(outlet.node);
+    if prec.op_as::<Cast>().is_some_and(|c| c.to == f32::datum_type())
+        && model.outlet_fact(prec.inputs[0])?.datum_type.is_quantized()
+    {
+        let quantized = patch.tap_model(model, prec.inputs[0])?;
+        if patch.outlet_fact(quantized)?.datum_type == dt {
+            return Ok(quantized);
+        }
+        return Ok(patch.wire_node(
+            format!("{name}.requantize.{}", outlet.node),
+            cast(dt),
+            &[quantized],
+        )?[0]);
+    }
+    let float = patch.tap_model(model, outlet)?;
+    Ok(patch.wire_node(format!("{name}.quantize.{}", outlet.node), cast(dt), &[float])?[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+    use crate::ops::math::add;
+    use crate::ops::nn::DataFormat;
+    use tract_itertools::{izip, Itertools};
+
+    fn samples(shape: &[usize], count: usize) -> Vec<TVec<Tensor>> {
+        (0..count)
+            .map(|s| {
+                let len = shape.iter().product::<usize>();
+                let data = (0..len).map(|i| ((i * 7 + s * 13) % 17) as f32 / 8. - 1.).collect_vec();
+                tvec!(tensor1(&data).into_shape(shape).unwrap())
+            })
+            .collect()
+    }
+
+    fn check(
+        model: &TypedModel,
+        transform: &dyn ModelTransform,
+        samples: &[TVec<Tensor>],
+    ) -> TractResult<TypedModel> {
+        let quantized = transform.transform_into(model)?;
+        let optimized = quantized.clone().into_optimized()?;
+        for sample in samples {
+            let inputs: TVec<TValue> = sample.iter().map(|t| t.clone().into()).collect();
+            let expected = model.clone().into_runnable()?.run(inputs.clone())?.remove(0);
+            let max = expected.as_slice::<f32>()?.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+            for candidate in [&quantized, &optimized] {
+                let found = candidate.clone().into_runnable()?.run(inputs.clone())?.remove(0);
+                let error = izip!(found.as_slice::<f32>()?, expected.as_slice::<f32>()?)
+                    .fold(0f32, |acc, (f, e)| acc.max((f - e).abs()));
+                assert!(error <= max / 10., "error {error} for outputs up to {max}");
+            }
+        }
+        Ok(quantized)
+    }
+
+    fn matmul(model: &mut TypedModel, name: &str, x: OutletId, w: Tensor) -> TractResult<OutletId> {
+        let w = model.add_const(format!("{name}.w"), w)?;
+        let op = EinSum::new("mk,kn->mn".parse()?, f32::datum_type());
+        Ok(model.wire_node(name, op, &[x, w])?[0])
+    }
+
+    #[test]
+    fn matmul_chain() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 8]))?;
+        let w1 = tensor1(&(0..32).map(|i| (i % 5) as f32 / 4. - 0.5).collect_vec());
+        let y = matmul(&mut model, "mm1", x, w1.into_shape(&[8, 4])?)?;
+        let w2 = tensor1(&(0..12).map(|i| (i % 3) as f32 - 1.).collect_vec());
+        let y = matmul(&mut model, "mm2", y, w2.into_shape(&[4, 3])?)?;
+        model.set_output_outlets(&[y])?;
+        let samples = samples(&[2, 8], 4);
+        let transform = StaticQuantization::new(CalibrationData::Inputs(samples.clone()));
+        let quantized = check(&model, &transform, &samples)?;
+        let einsums = quantized.nodes().iter().filter_map(|n| n.op_as::<EinSum>()).collect_vec();
+        assert_eq!(einsums.len(), 2);
+        assert!(einsums.iter().all(|e| e.q_params.is_some()));
+        // a single quantization at the entry and a single dequantization at the exit
+        assert_eq!(quantized.nodes().iter().filter(|n| n.op_is::<Cast>()).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn conv_percentile_u8() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([1, 2, 6]))?;
+        let kernel = model.add_const(
+            "kernel",
+            tensor1(&(0..12).map(|i| (i % 4) as f32 / 2. - 0.7).collect_vec())
+                .into_shape(&[3, 2, 2])?,
+        )?;
+        let bias = model.add_const("bias", tensor1(&[0.1f32, -0.2, 0.3]))?;
+        let pool_spec = PoolSpec {
+            data_format: DataFormat::NCHW,
+            kernel_shape: tvec!(2),
+            padding: PaddingSpec::Valid,
+            dilations: None,
+            strides: None,
+            input_channels: 2,
+            output_channels: 3,
+        };
+        let conv = Conv::new(pool_spec, KernelFormat::OIHW, 1, None);
+        let y = model.wire_node("conv", conv, &[x, kernel, bias])?;
+        let y = model.wire_node("add", add(), &[y[0], y[0]])?;
+        model.set_output_outlets(&y)?;
+        let samples = samples(&[1, 2, 6], 4);
+        let transform = StaticQuantization::new(CalibrationData::Inputs(samples.clone()))
+            .with_method(CalibrationMethod::Percentile(99.9))
+            .with_activation_dt(DatumType::U8);
+        let quantized = check(&model, &transform, &samples)?;
+        let conv = quantized.node_by_name("conv.quantized")?.op_as::<Conv>().unwrap();
+        assert!(matches!(conv.q_params, Some(DatumType::QU8(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([3, 8]))?;
+        let w = tensor1(&(0..40).map(|i| (i % 7) as f32 / 3. - 1.).collect_vec());
+        let y = matmul(&mut model, "mm", x, w.into_shape(&[8, 5])?)?;
+        // no constant operand: left in f32
+        let y = model.wire_node(
+            "gram",
+            EinSum::new("mk,nk->mn".parse()?, f32::datum_type()),
+            &[y, y],
+        )?;
+        model.set_output_outlets(&y)?;
+        let quantized = check(&model, &DynamicQuantization, &samples(&[3, 8], 4))?;
+        let einsums = quantized.nodes().iter().filter_map(|n| n.op_as::<EinSum>()).collect_vec();
+        assert_eq!(einsums.iter().filter(|e| e.q_params.is_some()).count(), 1);
+        assert_eq!(einsums.iter().filter(|e| e.q_params.is_none()).count(), 1);
+        assert!(quantized.nodes().iter().any(|n| n.op_is::<DynamicQuantizeLinearU8>()));
+        Ok(())
+    }
+
+    #[test]
+    fn transform_spec() {
+        assert!(crate::transform::get_transform("int8-dynamic").is_some());
+        assert_eq!(parse_static_quantization("calib.npz").unwrap().1, "calib.npz");
+        let (transform, source) =
+            parse_static_quantization("percentile=99.99:u8:calib.npz").unwrap();
+        assert_eq!(source, "calib.npz");
+        assert_eq!(transform.method, CalibrationMethod::Percentile(99.99));
+        assert_eq!(transform.activation_dt, DatumType::U8);
+        assert!(parse_static_quantization("median:calib.npz").is_none());
+        assert!(parse_static_quantization("").is_none());
+    }
+}
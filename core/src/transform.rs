@@ -9,7 +9,13 @@ use tract_data::TractResult;
 
 use crate::floats::FloatPrecisionTranslator;
 use crate::ops::nn::{Softmax, SoftmaxExp, TypedModel};
+use crate::quantize::DynamicQuantization;
 
+/// Transforms available by name.
+///
+/// Static quantization (`int8-static`) is not one of them, as it needs calibration data: build a
+/// [`crate::quantize::StaticQuantization`] instead (tract command line does it for
+/// `--transform int8-static:<spec>`).
 pub fn get_transform(name: &str) -> Option<Box<dyn ModelTransform>> {
     match name {
         #[cfg(feature = "blas")]
@@ -20,6 +26,13 @@ pub fn get_transform(name: &str) -> Option<Box<dyn ModelTransform>> {
         name if name.starts_with("f16-to-f32") => {
             build_float_translator::<f16, f32>(name.strip_prefix("f16-to-f32"))
         }
+        name if name.starts_with("f32-to-bf16") => {
+            build_float_translator::<f32, bf16>(name.strip_prefix("f32-to-bf16"))
+        }
+        name if name.starts_with("bf16-to-f32") => {
+            build_float_translator::<bf16, f32>(name.strip_prefix("bf16-to-f32"))
+        }
+        "int8-dynamic" => Some(Box::new(DynamicQuantization)),
         "softmax-fast-compact" => Some(Box::new(SoftmaxFastCompact)),
         _ => None,
     }
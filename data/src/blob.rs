@@ -2,16 +2,22 @@ use num_traits::Zero;
 
 use crate::{TractError, TractResult};
 use std::alloc::*;
+use std::any::Any;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::ptr::null_mut;
+use std::sync::Arc;
 
-#[derive(Eq)]
 pub struct Blob {
     layout: std::alloc::Layout,
     data: *mut u8,
+    /// Set when `data` points into memory the blob did not allocate (a memory arena, a mapped
+    /// file): the owner keeps it alive, and the blob never frees it.
+    owner: Option<Arc<dyn Any + Send + Sync>>,
 }
 
+impl Eq for Blob {}
+
 impl Default for Blob {
     #[inline]
     fn default() -> Blob {
@@ -29,7 +35,7 @@ impl Clone for Blob {
 impl Drop for Blob {
     #[inline]
     fn drop(&mut self) {
-        if !self.data.is_null() {
+        if !self.data.is_null() && self.owner.is_none() {
             unsafe { dealloc(self.data, self.layout) }
         }
     }
@@ -59,7 +65,7 @@ impl Blob {
     #[inline]
     pub unsafe fn ensure_size_and_align(&mut self, size: usize, align: usize) {
         if size > self.layout.size() || align > self.layout.align() {
-            if !self.data.is_null() {
+            if !self.data.is_null() && self.owner.take().is_none() {
                 std::alloc::dealloc(self.data as _, self.layout);
             }
             self.layout = Layout::from_size_align_unchecked(size, align);
@@ -75,7 +81,29 @@ impl Blob {
             data = unsafe { alloc(layout) };
             assert!(!data.is_null());
         }
-        Blob { layout, data }
+        Blob { layout, data, owner: None }
+    }
+
+    /// Wrap memory allocated and kept alive by `owner`, without copying it.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be valid for reads and writes of `layout.size()` bytes, aligned on
+    /// `layout.align()`, for as long as `owner` lives, and not be accessed through another path
+    /// while the blob lives.
+    #[inline]
+    pub unsafe fn from_raw_parts_owned_by(
+        data: *mut u8,
+        layout: Layout,
+        owner: Arc<dyn Any + Send + Sync>,
+    ) -> Blob {
+        Blob { layout, data, owner: Some(owner) }
+    }
+
+    /// The owner of the memory, for blobs wrapping memory they did not allocate.
+    #[inline]
+    pub fn owner(&self) -> Option<&Arc<dyn Any + Send + Sync>> {
+        self.owner.as_ref()
     }
 
     #[inline]
@@ -3,7 +3,7 @@ use crate::internal::*;
 use crate::dim::TDim;
 use crate::tensor::Tensor;
 use crate::TVec;
-use half::f16;
+use half::{bf16, f16};
 #[cfg(feature = "complex")]
 use num_complex::Complex;
 use scan_fmt::scan_fmt;
@@ -96,6 +96,74 @@ impl std::fmt::Debug for QParams {
     }
 }
 
+/// Quantization parameters varying along one axis of a tensor (typically the output channel
+/// axis of a convolution kernel or matmul weights).
+#[derive(Clone, PartialEq)]
+pub struct PerAxisQParams {
+    pub axis: usize,
+    pub zero_points: Vec<i32>,
+    pub scales: Vec<f32>,
+}
+
+impl Eq for PerAxisQParams {}
+
+#[allow(clippy::derived_hash_with_manual_eq)]
+impl Hash for PerAxisQParams {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.axis.hash(state);
+        self.zero_points.hash(state);
+        self.scales.iter().for_each(|s| s.to_bits().hash(state));
+    }
+}
+
+impl PerAxisQParams {
+    pub fn new(axis: usize, zero_points: Vec<i32>, scales: Vec<f32>) -> TractResult<Self> {
+        ensure!(
+            zero_points.len() == scales.len(),
+            "Per-axis quantization: {} zero points for {} scales",
+            zero_points.len(),
+            scales.len()
+        );
+        Ok(PerAxisQParams { axis, zero_points, scales })
+    }
+
+    pub fn len(&self) -> usize {
+        self.scales.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scales.is_empty()
+    }
+
+    pub fn zp_scale(&self, ix: usize) -> (i32, f32) {
+        (self.zero_points[ix], self.scales[ix])
+    }
+
+    /// Returns the equivalent per-tensor parameters if all channels share the same values.
+    pub fn uniform(&self) -> Option<QParams> {
+        let (zp, scale) = (*self.zero_points.first()?, *self.scales.first()?);
+        if self.zero_points.iter().all(|z| *z == zp) && self.scales.iter().all(|s| *s == scale) {
+            Some(QParams::ZpScale { zero_point: zp, scale })
+        } else {
+            None
+        }
+    }
+
+    pub fn zero_points_tensor(&self) -> Tensor {
+        tensor1(&self.zero_points)
+    }
+
+    pub fn scales_tensor(&self) -> Tensor {
+        tensor1(&self.scales)
+    }
+}
+
+impl std::fmt::Debug for PerAxisQParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "axis:{} Z:{:?} S:{:?}", self.axis, self.zero_points, self.scales)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub enum DatumType {
     Bool,
@@ -108,6 +176,7 @@ pub enum DatumType {
     I32,
     I64,
     F16,
+    BF16,
     F32,
     F64,
     TDim,
@@ -152,7 +221,9 @@ impl DatumType {
                 .copied()
                 .collect();
         }
-        if self.is_float() {
+        if *self == BF16 {
+            tvec!(BF16, F32, F64)
+        } else if self.is_float() {
             [F16, F32, F64].iter().filter(|s| s.size_of() >= self.size_of()).copied().collect()
         } else if self.is_signed() {
             [I8, I16, I32, I64, TDim]
@@ -208,7 +279,7 @@ impl DatumType {
     }
 
     pub fn is_float(&self) -> bool {
-        matches!(self, DatumType::F16 | DatumType::F32 | DatumType::F64)
+        matches!(self, DatumType::F16 | DatumType::BF16 | DatumType::F32 | DatumType::F64)
     }
 
     pub fn is_number(&self) -> bool {
@@ -363,6 +434,7 @@ impl DatumType {
             DatumType::I32 => tensor0(i32::MIN),
             DatumType::I64 => tensor0(i64::MIN),
             DatumType::F16 => tensor0(f16::MIN),
+            DatumType::BF16 => tensor0(bf16::MIN),
             DatumType::F32 => tensor0(f32::MIN),
             DatumType::F64 => tensor0(f64::MIN),
             _ => panic!("No min value for datum type {self:?}"),
@@ -380,6 +452,7 @@ impl DatumType {
             DatumType::I64 => tensor0(i64::MAX),
             DatumType::QI32(_) => tensor0(i32::MAX),
             DatumType::F16 => tensor0(f16::MAX),
+            DatumType::BF16 => tensor0(bf16::MAX),
             DatumType::F32 => tensor0(f32::MAX),
             DatumType::F64 => tensor0(f64::MAX),
             _ => panic!("No max value for datum type {self:?}"),
@@ -408,6 +481,7 @@ impl std::str::FromStr for DatumType {
                 "U32" | "u32" => Ok(DatumType::U32),
                 "U64" | "u64" => Ok(DatumType::U64),
                 "F16" | "f16" => Ok(DatumType::F16),
+                "BF16" | "bf16" => Ok(DatumType::BF16),
                 "F32" | "f32" => Ok(DatumType::F32),
                 "F64" | "f64" => Ok(DatumType::F64),
                 "Bool" | "bool" => Ok(DatumType::Bool),
@@ -508,6 +582,7 @@ macro_rules! datum {
 
 datum!(bool, Bool);
 datum!(f16, F16);
+datum!(bf16, BF16);
 datum!(f32, F32);
 datum!(f64, F64);
 datum!(i8, I8);
@@ -575,4 +650,14 @@ mod tests {
             DatumType::QU8(QParams::ZpScale { zero_point: 128, scale: 0.01 })
         );
     }
+
+    #[test]
+    fn test_per_axis_qparams_uniform() {
+        let qp = PerAxisQParams::new(0, vec![0, 0], vec![0.5, 0.5]).unwrap();
+        assert_eq!(qp.uniform(), Some(QParams::ZpScale { zero_point: 0, scale: 0.5 }));
+        let qp = PerAxisQParams::new(0, vec![0, 0], vec![0.5, 0.25]).unwrap();
+        assert_eq!(qp.uniform(), None);
+        assert!(PerAxisQParams::new(0, vec![0], vec![0.5, 0.25]).is_err());
+        assert_eq!(PerAxisQParams::new(0, vec![], vec![]).unwrap().uniform(), None);
+    }
 }
@@ -11,7 +11,7 @@ mod tree;
 
 pub use self::parse::parse_tdim;
 pub use self::resolve::solve_for;
-pub use self::sym::{Symbol, SymbolTable, SymbolValues};
+pub use self::sym::{Assertion, Symbol, SymbolTable, SymbolValues};
 pub use self::tree::{TDim, UndeterminedSymbol};
 
 use crate::{TractError, TractResult};
@@ -3,8 +3,8 @@ use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, alphanumeric1, digit1, one_of};
 use nom::combinator::{all_consuming, map, map_res, recognize};
-use nom::multi::many0;
-use nom::sequence::{delimited, pair, separated_pair};
+use nom::multi::{many0, separated_list1};
+use nom::sequence::{delimited, pair, preceded, separated_pair};
 use nom::IResult;
 
 pub fn parse_tdim(symbol_table: &SymbolTable, input: &str) -> TractResult<TDim> {
@@ -14,6 +14,25 @@ pub fn parse_tdim(symbol_table: &SymbolTable, input: &str) -> TractResult<TDim>
     }
 }
 
+pub fn parse_assertion(symbol_table: &SymbolTable, input: &str) -> TractResult<Assertion> {
+    match all_consuming(|i| assertion(symbol_table, i))(input) {
+        Ok(pair) => Ok(pair.1),
+        Err(e) => bail!("Failed to parse {:?}, {:?}", input, e),
+    }
+}
+
+fn assertion<'i>(symbol_table: &SymbolTable, i: &'i str) -> IResult<&'i str, Assertion> {
+    let s = symbol_table;
+    macro_rules! cmp {
+        ($op: expr, $variant: ident) => {
+            map(separated_pair(|i| expr(s, i), stag($op), |i| expr(s, i)), |(a, b)| {
+                Assertion::$variant(a, b)
+            })
+        };
+    }
+    alt((cmp!("==", Eq), cmp!("<=", LTE), cmp!(">=", GTE), cmp!("<", LT), cmp!(">", GT)))(i)
+}
+
 fn expr<'i>(symbol_table: &SymbolTable, i: &'i str) -> IResult<&'i str, TDim> {
     add(symbol_table, i)
 }
@@ -43,12 +62,25 @@ fn div<'i>(symbol_table: &SymbolTable, input: &'i str) -> IResult<&'i str, TDim>
 fn atom<'i>(symbol_table: &SymbolTable, i: &'i str) -> IResult<&'i str, TDim> {
     alt((
         map(numeric, TDim::Val),
+        map(|i| func(symbol_table, "min", i), |terms| TDim::Min(terms).reduce()),
+        map(|i| func(symbol_table, "max", i), |terms| TDim::Max(terms).reduce()),
         map(|i| identifier(symbol_table, i), TDim::Sym),
         map(pair(recognize(stag("-")), |i| atom(symbol_table, i)), |(_, dim)| dim * -1),
         delimited(stag("("), |i| expr(symbol_table, i), stag(")")),
     ))(i)
 }
 
+fn func<'i>(
+    symbol_table: &SymbolTable,
+    name: &'static str,
+    i: &'i str,
+) -> IResult<&'i str, Vec<TDim>> {
+    preceded(
+        stag(name),
+        delimited(stag("("), separated_list1(stag(","), |i| expr(symbol_table, i)), stag(")")),
+    )(i)
+}
+
 fn identifier<'i>(symbol_table: &SymbolTable, i: &'i str) -> IResult<&'i str, Symbol> {
     map(recognize(pair(alt((alpha1, tag("_"))), many0(alt((alphanumeric1, tag("_")))))), |s| {
         symbol_table.sym(s)
@@ -110,4 +142,34 @@ mod test {
         assert_eq!(parse_tdim(&table, "1+2*3").unwrap(), 7.into());
         assert_eq!(parse_tdim(&table, "1*2+3").unwrap(), 5.into());
     }
+
+    #[test]
+    fn parse_min_max() {
+        let table = SymbolTable::default();
+        let s = TDim::Sym(table.sym("S"));
+        assert_eq!(
+            parse_tdim(&table, "min(S, 512)").unwrap(),
+            TDim::Min(vec![s.clone(), 512.into()])
+        );
+        assert_eq!(parse_tdim(&table, "max(S,1)+1").unwrap(), TDim::Max(vec![s, 1.into()]) + 1);
+        assert_eq!(parse_tdim(&table, "min(3,max(1,2))").unwrap(), 2.into());
+        assert_eq!(parse_tdim(&table, "minimum").unwrap(), TDim::Sym(table.sym("minimum")));
+    }
+
+    #[test]
+    fn parse_assertions() {
+        let table = SymbolTable::default();
+        let s = TDim::Sym(table.sym("S"));
+        assert_eq!(parse_assertion(&table, "S>=1").unwrap(), Assertion::GTE(s.clone(), 1.into()));
+        assert_eq!(
+            parse_assertion(&table, "S <= 4096").unwrap(),
+            Assertion::LTE(s.clone(), 4096.into())
+        );
+        assert_eq!(
+            parse_assertion(&table, "S<2*S").unwrap(),
+            Assertion::LT(s.clone(), s.clone() * 2)
+        );
+        assert_eq!(parse_assertion(&table, "S == 12").unwrap(), Assertion::Eq(s, 12.into()));
+        assert!(parse_assertion(&table, "S").is_err());
+    }
 }
@@ -4,23 +4,62 @@ use std::sync::{Arc, Mutex};
 use string_interner::DefaultStringInterner;
 use string_interner::Symbol as _;
 
+use super::parse::parse_assertion;
+use super::TDim;
+use crate::TractResult;
+
+#[derive(Default)]
+pub struct SymbolTableData {
+    table: DefaultStringInterner,
+    // assertions are kept with bare symbol ids: TDim symbols point back to the table and would
+    // keep it alive forever
+    assertions: Vec<Assertion<DetachedTDim>>,
+    /// Expressions known to be positive or zero, derived from the assertions.
+    known_positive: Vec<DetachedTDim>,
+}
+
 #[derive(Clone, Default)]
-pub struct SymbolTable(pub Arc<Mutex<DefaultStringInterner>>);
+pub struct SymbolTable(pub Arc<Mutex<SymbolTableData>>);
 
 impl SymbolTable {
     pub fn get(&self, name: &str) -> Option<Symbol> {
-        let table = self.0.lock().unwrap();
-        table.get(name).map(|sym| Symbol(Arc::clone(&self.0), sym))
+        let data = self.0.lock().unwrap();
+        data.table.get(name).map(|sym| Symbol(Arc::clone(&self.0), sym))
     }
 
     pub fn sym(&self, name: &str) -> Symbol {
-        let mut table = self.0.lock().unwrap();
-        let sym = table.get_or_intern(name);
+        let mut data = self.0.lock().unwrap();
+        let sym = data.table.get_or_intern(name);
         Symbol(Arc::clone(&self.0), sym)
     }
 
+    /// Declare an assumption on the symbols, like `S >= 1` or `S <= 4096`.
+    pub fn add_assertion(&self, assertion: &str) -> TractResult<()> {
+        // parse before locking: parsing interns symbols
+        let assertion = parse_assertion(self, assertion)?;
+        let known_positive = assertion.as_known_positive();
+        let mut data = self.0.lock().unwrap();
+        let assertion = assertion.map(DetachedTDim::detach);
+        if !data.assertions.contains(&assertion) {
+            data.assertions.push(assertion);
+            data.known_positive.extend(known_positive.iter().map(DetachedTDim::detach));
+        }
+        Ok(())
+    }
+
+    pub fn all_assertions(&self) -> Vec<Assertion> {
+        let data = self.0.lock().unwrap();
+        data.assertions.iter().map(|a| a.map(|d| d.attach(&self.0))).collect()
+    }
+
+    pub(super) fn known_positive(&self) -> Vec<TDim> {
+        let data = self.0.lock().unwrap();
+        data.known_positive.iter().map(|d| d.attach(&self.0)).collect()
+    }
+
     pub fn new_with_prefix(&self, prefix: &str) -> Symbol {
-        let mut table = self.0.lock().unwrap();
+        let mut data = self.0.lock().unwrap();
+        let table = &mut data.table;
         let sym = if table.get(prefix).is_none() {
             table.get_or_intern(prefix)
         } else {
@@ -39,23 +78,57 @@ impl SymbolTable {
 
 impl std::hash::Hash for SymbolTable {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let table = self.0.lock().unwrap();
-        table.len().hash(state);
-        for t in &*table {
+        let data = self.0.lock().unwrap();
+        data.table.len().hash(state);
+        for t in &data.table {
             t.hash(state);
         }
+        data.assertions.hash(state);
     }
 }
 
 impl fmt::Debug for SymbolTable {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let table = self.0.lock().unwrap();
-        write!(f, "{}", (&table).into_iter().map(|(_, s)| s).join(" "))
+        let data = self.0.lock().unwrap();
+        write!(f, "{}", (&data.table).into_iter().map(|(_, s)| s).join(" "))
     }
 }
 
 #[derive(Clone)]
-pub struct Symbol(Arc<Mutex<DefaultStringInterner>>, string_interner::DefaultSymbol);
+pub struct Symbol(Arc<Mutex<SymbolTableData>>, string_interner::DefaultSymbol);
+
+impl Symbol {
+    pub fn symbol_table(&self) -> SymbolTable {
+        SymbolTable(Arc::clone(&self.0))
+    }
+
+    /// Inclusive bounds of the symbol values deduced from the assertions of its table.
+    pub fn bounds(&self) -> (Option<i64>, Option<i64>) {
+        let (mut low, mut high) = (None::<i64>, None::<i64>);
+        for positive in self.symbol_table().known_positive() {
+            // only the affine forms a*S+c >= 0 in this symbol give bounds
+            let symbols = positive.symbols();
+            if symbols.len() != 1 || !symbols.contains(self) {
+                continue;
+            }
+            // no simplification here: it may ask for the bounds again
+            let at = |v: i64| positive.eval_to_i64(&SymbolValues::default().with(self, v)).ok();
+            let (Some(c), Some(c_a), Some(c_2a)) = (at(0), at(1), at(2)) else { continue };
+            let a = c_a - c;
+            if a == 0 || c_2a - c != 2 * a {
+                continue;
+            }
+            if a > 0 {
+                let bound = num_integer::Integer::div_ceil(&-c, &a);
+                low = Some(low.map(|l| l.max(bound)).unwrap_or(bound));
+            } else {
+                let bound = num_integer::Integer::div_floor(&c, &-a);
+                high = Some(high.map(|h| h.min(bound)).unwrap_or(bound));
+            }
+        }
+        (low, high)
+    }
+}
 
 impl PartialEq for Symbol {
     fn eq(&self, other: &Self) -> bool {
@@ -85,8 +158,8 @@ impl std::hash::Hash for Symbol {
 
 impl std::fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Ok(table) = self.0.lock() {
-            if let Some(s) = table.resolve(self.1) {
+        if let Ok(data) = self.0.lock() {
+            if let Some(s) = data.table.resolve(self.1) {
                 return write!(f, "{s}");
             }
         }
@@ -133,3 +206,99 @@ impl std::ops::IndexMut<&Symbol> for SymbolValues {
         &mut self.0[index.1.to_usize()]
     }
 }
+
+/// An assumption on symbol values, used to simplify and compare expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Assertion<D = TDim> {
+    Eq(D, D),
+    LT(D, D),
+    GT(D, D),
+    LTE(D, D),
+    GTE(D, D),
+}
+
+impl<D> Assertion<D> {
+    fn map<E>(&self, f: impl Fn(&D) -> E) -> Assertion<E> {
+        use Assertion::*;
+        match self {
+            Eq(left, right) => Eq(f(left), f(right)),
+            LT(left, right) => LT(f(left), f(right)),
+            GT(left, right) => GT(f(left), f(right)),
+            LTE(left, right) => LTE(f(left), f(right)),
+            GTE(left, right) => GTE(f(left), f(right)),
+        }
+    }
+}
+
+impl Assertion {
+    /// Expressions this assertion guarantees to be positive or zero.
+    pub fn as_known_positive(&self) -> Vec<TDim> {
+        use Assertion::*;
+        match self {
+            Eq(left, right) => vec![left.clone() - right, right.clone() - left],
+            GTE(left, right) => vec![left.clone() - right],
+            GT(left, right) => vec![left.clone() - right - 1],
+            LTE(left, right) => vec![right.clone() - left],
+            LT(left, right) => vec![right.clone() - left - 1],
+        }
+    }
+}
+
+impl Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Assertion::*;
+        match self {
+            Eq(left, right) => write!(f, "{left} == {right}"),
+            LT(left, right) => write!(f, "{left} < {right}"),
+            GT(left, right) => write!(f, "{left} > {right}"),
+            LTE(left, right) => write!(f, "{left} <= {right}"),
+            GTE(left, right) => write!(f, "{left} >= {right}"),
+        }
+    }
+}
+
+/// A TDim with bare symbol ids, not holding on its symbol table.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum DetachedTDim {
+    Val(i64),
+    Sym(string_interner::DefaultSymbol),
+    Add(Vec<DetachedTDim>),
+    Mul(Vec<DetachedTDim>),
+    MulInt(i64, Box<DetachedTDim>),
+    Div(Box<DetachedTDim>, u64),
+    Broadcast(Vec<DetachedTDim>),
+    Min(Vec<DetachedTDim>),
+    Max(Vec<DetachedTDim>),
+}
+
+impl DetachedTDim {
+    fn detach(dim: &TDim) -> DetachedTDim {
+        let all = |terms: &[TDim]| terms.iter().map(Self::detach).collect();
+        match dim {
+            TDim::Val(v) => Self::Val(*v),
+            TDim::Sym(s) => Self::Sym(s.1),
+            TDim::Add(terms) => Self::Add(all(terms)),
+            TDim::Mul(terms) => Self::Mul(all(terms)),
+            TDim::MulInt(p, a) => Self::MulInt(*p, Box::new(Self::detach(a))),
+            TDim::Div(a, q) => Self::Div(Box::new(Self::detach(a)), *q),
+            TDim::Broadcast(terms) => Self::Broadcast(all(terms)),
+            TDim::Min(terms) => Self::Min(all(terms)),
+            TDim::Max(terms) => Self::Max(all(terms)),
+        }
+    }
+
+    fn attach(&self, table: &Arc<Mutex<SymbolTableData>>) -> TDim {
+        let all = |terms: &[DetachedTDim]| terms.iter().map(|t| t.attach(table)).collect();
+        match self {
+            Self::Val(v) => TDim::Val(*v),
+            Self::Sym(s) => TDim::Sym(Symbol(Arc::clone(table), *s)),
+            Self::Add(terms) => TDim::Add(all(terms)),
+            Self::Mul(terms) => TDim::Mul(all(terms)),
+            Self::MulInt(p, a) => TDim::MulInt(*p, Box::new(a.attach(table))),
+            Self::Div(a, q) => TDim::Div(Box::new(a.attach(table)), *q),
+            Self::Broadcast(terms) => TDim::Broadcast(all(terms)),
+            Self::Min(terms) => TDim::Min(all(terms)),
+            Self::Max(terms) => TDim::Max(all(terms)),
+        }
+    }
+}
@@ -30,6 +30,8 @@ pub enum TDim {
     MulInt(i64, Box<TDim>),
     Div(Box<TDim>, u64),
     Broadcast(Vec<TDim>),
+    Min(Vec<TDim>),
+    Max(Vec<TDim>),
 }
 
 use TDim::*;
@@ -38,13 +40,15 @@ fn tdim_compare(a: &TDim, b: &TDim) -> Ordering {
     match (a, b) {
         (Sym(a), Sym(b)) => a.cmp(b),
         (Val(a), Val(b)) => a.cmp(b),
-        (Add(a), Add(b)) | (Mul(a), Mul(b)) | (Broadcast(a), Broadcast(b)) => {
-            a.len().cmp(&b.len()).then(
-                a.iter()
-                    .zip(b.iter())
-                    .fold(Ordering::Equal, |acc, (a, b)| acc.then_with(|| tdim_compare(a, b))),
-            )
-        }
+        (Add(a), Add(b))
+        | (Mul(a), Mul(b))
+        | (Broadcast(a), Broadcast(b))
+        | (Min(a), Min(b))
+        | (Max(a), Max(b)) => a.len().cmp(&b.len()).then(
+            a.iter()
+                .zip(b.iter())
+                .fold(Ordering::Equal, |acc, (a, b)| acc.then_with(|| tdim_compare(a, b))),
+        ),
         (MulInt(p, d), MulInt(q, e)) => p.cmp(q).then_with(|| tdim_compare(d, e)),
         (Div(d, p), Div(e, q)) => p.cmp(q).then_with(|| tdim_compare(d, e)),
         (Sym(_), _) => Ordering::Less,
@@ -59,6 +63,10 @@ fn tdim_compare(a: &TDim, b: &TDim) -> Ordering {
         (_, MulInt(_, _)) => Ordering::Greater,
         (Broadcast(_), _) => Ordering::Less,
         (_, Broadcast(_)) => Ordering::Greater,
+        (Min(_), _) => Ordering::Less,
+        (_, Min(_)) => Ordering::Greater,
+        (Div(_, _), _) => Ordering::Less,
+        (_, Div(_, _)) => Ordering::Greater,
     }
 }
 
@@ -72,6 +80,8 @@ impl fmt::Display for TDim {
             Broadcast(it) => write!(fmt, "{}", it.iter().map(|x| format!("({x})")).join("#")),
             MulInt(a, b) => write!(fmt, "{a}*{b}"),
             Div(a, b) => write!(fmt, "({a})/{b}"),
+            Min(it) => write!(fmt, "min({})", it.iter().join(",")),
+            Max(it) => write!(fmt, "max({})", it.iter().join(",")),
         }
     }
 }
@@ -119,6 +129,12 @@ impl TDim {
             }),
             Div(a, q) => Ok(a.eval_to_i64(values)? / *q as i64),
             MulInt(p, a) => Ok(a.eval_to_i64(values)? * *p),
+            Min(terms) => terms
+                .iter()
+                .try_fold(i64::MAX, |acc, it| it.eval_to_i64(values).map(|x| acc.min(x))),
+            Max(terms) => terms
+                .iter()
+                .try_fold(i64::MIN, |acc, it| it.eval_to_i64(values).map(|x| acc.max(x))),
         }
     }
 
@@ -133,6 +149,8 @@ impl TDim {
             }),
             Div(a, q) => a.eval(values) / *q as i64,
             MulInt(p, a) => a.eval(values) * *p,
+            Min(terms) => Min(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
+            Max(terms) => Max(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
         }
     }
 
@@ -151,6 +169,14 @@ impl TDim {
             }),
             Div(a, q) => Ok(a.substitute(from, to)? / *q as i64),
             MulInt(p, a) => Ok(a.substitute(from, to)? * *p),
+            Min(terms) => {
+                Ok(Min(terms.iter().map(|t| t.substitute(from, to)).collect::<TractResult<_>>()?)
+                    .reduce())
+            }
+            Max(terms) => {
+                Ok(Max(terms.iter().map(|t| t.substitute(from, to)).collect::<TractResult<_>>()?)
+                    .reduce())
+            }
         }
     }
 
@@ -174,13 +200,14 @@ impl TDim {
             Broadcast(terms) => 4 * terms.iter().map(TDim::cost).sum::<usize>(),
             Div(a, _) => 3 * a.cost(),
             MulInt(_, a) => 2 * a.cost(),
+            Min(terms) | Max(terms) => 5 * terms.iter().map(TDim::cost).sum::<usize>(),
         }
     }
 
     fn wiggle(&self) -> Vec<TDim> {
         use self::TDim::*;
         match self {
-            Sym(_) | Val(_) | Mul(_) | Broadcast(_) => vec![self.clone()],
+            Sym(_) | Val(_) | Mul(_) | Broadcast(_) | Min(_) | Max(_) => vec![self.clone()],
             Add(terms) => {
                 let mut forms = vec![];
                 let sub_exprs = terms.iter().map(|e| e.wiggle()).multi_cartesian_product();
@@ -419,10 +446,186 @@ impl TDim {
                     Broadcast(terms)
                 }
             }
+            Min(terms) => Self::simplify_min_max(terms, false),
+            Max(terms) => Self::simplify_min_max(terms, true),
             Val(_) | Sym(_) => self,
         }
     }
 
+    fn simplify_min_max(terms: Vec<TDim>, max: bool) -> TDim {
+        let mut terms: Vec<TDim> = terms
+            .into_iter()
+            .map(|t| t.simplify())
+            .flat_map(|t| match t {
+                Min(terms) if !max => terms,
+                Max(terms) if max => terms,
+                t => vec![t],
+            })
+            .sorted_by(tdim_compare)
+            .dedup()
+            .collect();
+        // drop the terms that can not be the result, constant or not
+        let mut ix = 0;
+        while ix < terms.len() && terms.len() > 1 {
+            let dominated = terms.iter().enumerate().any(|(other_ix, other)| {
+                other_ix != ix && {
+                    let gap =
+                        if max { other.clone() - &terms[ix] } else { terms[ix].clone() - other };
+                    gap.prove_positive_or_zero()
+                }
+            });
+            if dominated {
+                terms.remove(ix);
+            } else {
+                ix += 1;
+            }
+        }
+        if terms.len() == 1 {
+            terms.remove(0)
+        } else if max {
+            Max(terms)
+        } else {
+            Min(terms)
+        }
+    }
+
+    /// Inclusive bounds of the expression values, deduced from the symbols assertions.
+    fn bounds(&self) -> (Option<i64>, Option<i64>) {
+        fn all<I: Iterator<Item = Option<i64>>>(it: I) -> Option<Vec<i64>> {
+            it.collect()
+        }
+        match self {
+            Val(v) => (Some(*v), Some(*v)),
+            Sym(s) => s.bounds(),
+            Add(terms) => terms.iter().map(|t| t.bounds()).fold(
+                (Some(0), Some(0)),
+                |(low, high), (t_low, t_high)| {
+                    (
+                        low.zip(t_low).and_then(|(a, b)| a.checked_add(b)),
+                        high.zip(t_high).and_then(|(a, b)| a.checked_add(b)),
+                    )
+                },
+            ),
+            MulInt(p, a) => {
+                let (low, high) = a.bounds();
+                let (low, high) =
+                    (low.and_then(|l| l.checked_mul(*p)), high.and_then(|h| h.checked_mul(*p)));
+                if *p >= 0 {
+                    (low, high)
+                } else {
+                    (high, low)
+                }
+            }
+            // only tracked for positive factors
+            Mul(terms) => terms.iter().map(|t| t.bounds()).fold(
+                (Some(1), Some(1)),
+                |(low, high), (t_low, t_high)| match (low, t_low) {
+                    (Some(l), Some(t_l)) if l >= 0 && t_l >= 0 => {
+                        (l.checked_mul(t_l), high.zip(t_high).and_then(|(a, b)| a.checked_mul(b)))
+                    }
+                    _ => (None, None),
+                },
+            ),
+            Div(a, q) => {
+                let (low, high) = a.bounds();
+                (low.map(|l| l / *q as i64), high.map(|h| h / *q as i64))
+            }
+            Min(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect_vec();
+                (
+                    all(bounds.iter().map(|b| b.0)).and_then(|l| l.into_iter().min()),
+                    bounds.iter().filter_map(|b| b.1).min(),
+                )
+            }
+            Max(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect_vec();
+                (
+                    bounds.iter().filter_map(|b| b.0).max(),
+                    all(bounds.iter().map(|b| b.1)).and_then(|h| h.into_iter().max()),
+                )
+            }
+            Broadcast(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect_vec();
+                (
+                    all(bounds.iter().map(|b| b.0)).and_then(|l| l.into_iter().min()),
+                    all(bounds.iter().map(|b| b.1)).and_then(|h| h.into_iter().max()),
+                )
+            }
+        }
+    }
+
+    pub fn low_inclusive_bound(&self) -> Option<i64> {
+        self.clone().simplify().bounds().0
+    }
+
+    pub fn high_inclusive_bound(&self) -> Option<i64> {
+        self.clone().simplify().bounds().1
+    }
+
+    /// True if the expression is positive or zero for all the symbol values satisfying the
+    /// symbol table assertions. False means "unknown".
+    pub fn prove_positive_or_zero(&self) -> bool {
+        let it = self.clone().simplify();
+        if it.bounds().0.is_some_and(|low| low >= 0) {
+            return true;
+        }
+        match &it {
+            Max(terms) => return terms.iter().any(|t| t.prove_positive_or_zero()),
+            Min(terms) => return terms.iter().all(|t| t.prove_positive_or_zero()),
+            // max(a, b) >= a and -min(a, b) >= -a
+            Add(terms) => {
+                for (ix, term) in terms.iter().enumerate() {
+                    let alternatives = match term {
+                        Max(alts) => alts.clone(),
+                        MulInt(p, t) => match &**t {
+                            Max(alts) if *p > 0 => {
+                                alts.iter().map(|a| MulInt(*p, b!(a.clone()))).collect()
+                            }
+                            Min(alts) if *p < 0 => {
+                                alts.iter().map(|a| MulInt(*p, b!(a.clone()))).collect()
+                            }
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    for alternative in alternatives {
+                        let mut terms = terms.clone();
+                        terms[ix] = alternative;
+                        if Add(terms).prove_positive_or_zero() {
+                            return true;
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        // assertions involving several symbols
+        if let Some(sym) = it.symbols().into_iter().next() {
+            for positive in sym.symbol_table().known_positive() {
+                if (it.clone() - positive).bounds().0.is_some_and(|low| low >= 0) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn prove_strict_positive(&self) -> bool {
+        (self.clone() - 1).prove_positive_or_zero()
+    }
+
+    pub fn prove_negative_or_zero(&self) -> bool {
+        (-self.clone()).prove_positive_or_zero()
+    }
+
+    pub fn mini(self, other: TDim) -> TDim {
+        Min(vec![self, other]).reduce()
+    }
+
+    pub fn maxi(self, other: TDim) -> TDim {
+        Max(vec![self, other]).reduce()
+    }
+
     pub fn gcd(&self) -> u64 {
         use self::TDim::*;
         use num_integer::Integer;
@@ -442,7 +645,9 @@ impl TDim {
                     1
                 }
             }
-            Broadcast(terms) => terms.iter().map(|t| t.gcd()).reduce(|a, b| a.gcd(&b)).unwrap_or(1),
+            Broadcast(terms) | Min(terms) | Max(terms) => {
+                terms.iter().map(|t| t.gcd()).reduce(|a, b| a.gcd(&b)).unwrap_or(1)
+            }
         }
     }
 
@@ -457,6 +662,8 @@ impl TDim {
             Sym(_) => panic!(),
             Add(terms) => Add(terms.iter().map(|t| t.div(d)).collect()),
             Broadcast(terms) => Broadcast(terms.iter().map(|t| t.div(d)).collect()),
+            Min(terms) => Min(terms.iter().map(|t| t.div(d)).collect()),
+            Max(terms) => Max(terms.iter().map(|t| t.div(d)).collect()),
             Mul(_) => Div(Box::new(self.clone()), d),
             MulInt(p, a) => {
                 if *p == d as i64 {
@@ -495,7 +702,7 @@ impl TDim {
                     let (n, d) = slope_rec(a, sym);
                     (n, d * *q as i64)
                 }
-                Broadcast(terms) => slope_rec(&terms[0], sym),
+                Broadcast(terms) | Min(terms) | Max(terms) => slope_rec(&terms[0], sym),
             }
         }
         let (p, q) = slope_rec(self, sym);
@@ -507,7 +714,7 @@ impl TDim {
         match self {
             Val(_) => maplit::hashset!(),
             Sym(s) => maplit::hashset!(s.clone()),
-            Add(terms) | Mul(terms) | Broadcast(terms) => {
+            Add(terms) | Mul(terms) | Broadcast(terms) | Min(terms) | Max(terms) => {
                 terms.iter().fold(maplit::hashset!(), |mut set, v| {
                     set.extend(v.symbols());
                     set
@@ -995,4 +1202,61 @@ mod tests {
         let mul2 = (term.clone() - 1) * (term.clone() * 2 - 3);
         assert_eq!(mul1, mul2);
     }
+
+    #[test]
+    fn min_max_of_values() {
+        assert_eq!(TDim::from(3).mini(5.into()), 3.into());
+        assert_eq!(TDim::from(3).maxi(5.into()), 5.into());
+        assert_eq!(s().mini(s()), s());
+        assert_eq!(s().maxi(s() + 1), s() + 1);
+        assert_eq!(Min(vec![s(), Min(vec![2.into(), 4.into()])]).reduce(), s().mini(2.into()));
+    }
+
+    #[test]
+    fn min_max_with_assertions() {
+        let table = SymbolTable::default();
+        let t: TDim = table.sym("T").into();
+        assert_eq!(t.clone().mini(512.into()), Min(vec![t.clone(), 512.into()]));
+        table.add_assertion("T <= 256").unwrap();
+        table.add_assertion("T >= 1").unwrap();
+        assert_eq!(t.clone().mini(512.into()), t);
+        assert_eq!(t.clone().maxi(1.into()), t);
+        assert_eq!(t.clone().maxi(0.into()), t);
+        assert_eq!((t.clone() * 2).mini(t.clone()), t);
+        assert_eq!(t.clone().mini(100.into()), Min(vec![t.clone(), 100.into()]));
+        assert_eq!(
+            t.clone().mini(100.into()).eval(&SymbolValues::default().with(&table.sym("T"), 12)),
+            12.into()
+        );
+    }
+
+    #[test]
+    fn prove_with_assertions() {
+        let table = SymbolTable::default();
+        let t: TDim = table.sym("T").into();
+        let u: TDim = table.sym("U").into();
+        assert!(!t.prove_positive_or_zero());
+        table.add_assertion("T >= 1").unwrap();
+        table.add_assertion("U >= T").unwrap();
+        assert!(t.prove_strict_positive());
+        assert!((t.clone() * 3 - 3).prove_positive_or_zero());
+        assert!(!(t.clone() - 2).prove_negative_or_zero());
+        assert!((u.clone() - &t).prove_positive_or_zero());
+        assert!((t.clone().maxi(8.into()) - 8).prove_positive_or_zero());
+        assert!((t.clone() - t.clone().mini(8.into())).prove_positive_or_zero());
+        assert_eq!(t.low_inclusive_bound(), Some(1));
+        assert_eq!(t.high_inclusive_bound(), None);
+        assert_eq!((t.clone().mini(8.into()) * 2).high_inclusive_bound(), Some(16));
+    }
+
+    #[test]
+    fn assertions_do_not_leak_table() {
+        let table = SymbolTable::default();
+        let data = std::sync::Arc::downgrade(&table.0);
+        table.add_assertion("T >= 1").unwrap();
+        table.add_assertion("U >= T").unwrap();
+        assert_eq!(table.all_assertions().len(), 2);
+        drop(table);
+        assert!(data.upgrade().is_none());
+    }
 }
@@ -17,7 +17,7 @@ pub type TractResult<T> = anyhow::Result<T>;
 
 pub mod prelude {
     pub use crate::blob::Blob;
-    pub use crate::datum::{round_ties_to_even, Datum, DatumType, QParams};
+    pub use crate::datum::{round_ties_to_even, Datum, DatumType, PerAxisQParams, QParams};
     pub use crate::dim::{Symbol, SymbolTable, SymbolValues, TDim, ToDim};
     pub use crate::opaque::Opaque;
     pub use crate::tensor::litteral::*;
@@ -31,7 +31,7 @@ pub mod prelude {
         dispatch_floatlike, dispatch_hash, dispatch_numbers, dispatch_signed,
     };
     pub use crate::{TractError, TractResult};
-    pub use half::f16;
+    pub use half::{bf16, f16};
     pub use itertools as tract_itertools;
     #[cfg(feature = "complex")]
     pub use num_complex::Complex;
@@ -33,6 +33,7 @@ macro_rules! dispatch_datum {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<$crate::prelude::bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::Blob => $($path)::*::<$crate::prelude::Blob>($($args),*),
@@ -73,6 +74,7 @@ macro_rules! dispatch_datum_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             DatumType::Blob => $($path)::*::<Blob>($($args),*),
@@ -113,6 +115,7 @@ macro_rules! dispatch_copy {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<$crate::prelude::bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -150,6 +153,7 @@ macro_rules! dispatch_copy_by_size {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<i16>($($args),*),
+            DatumType::BF16 => $($path)::*::<i16>($($args),*),
             DatumType::F32  => $($path)::*::<i32>($($args),*),
             DatumType::F64  => $($path)::*::<i64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
@@ -209,6 +213,7 @@ macro_rules! dispatch_zerolike {
             DatumType::I32  => $($path)::*::<i32>($($args),*),
             DatumType::I64  => $($path)::*::<i64>($($args),*),
             DatumType::F16  => $($path)::*::<f16>($($args),*),
+            DatumType::BF16 => $($path)::*::<$crate::prelude::bf16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             DatumType::QI8(_)  => $($path)::*::<i8>($($args),*),
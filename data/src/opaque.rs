@@ -8,7 +8,11 @@ use std::sync::Arc;
 use downcast_rs::{impl_downcast, Downcast};
 use dyn_hash::DynHash;
 
-pub trait OpaquePayload: DynHash + Send + Sync + Debug + Display + Downcast { }
+pub trait OpaquePayload: DynHash + Send + Sync + Debug + Display + Downcast {
+    fn same_as(&self, _other: &dyn OpaquePayload) -> bool {
+        false
+    }
+}
 impl_downcast!(OpaquePayload);
 dyn_hash::hash_trait_object!(OpaquePayload);
 
@@ -85,6 +89,6 @@ impl Default for Opaque {
 
 impl PartialEq for Opaque {
     fn eq(&self, other: &Self) -> bool {
-        Arc::ptr_eq(&self.0, &other.0)
+        Arc::ptr_eq(&self.0, &other.0) || self.0.same_as(&*other.0)
     }
 }
@@ -5,7 +5,7 @@ use crate::dim::TDim;
 use crate::internal::*;
 use crate::opaque::Opaque;
 use crate::TVec;
-use half::f16;
+use half::{bf16, f16};
 use itertools::Itertools;
 use ndarray::prelude::*;
 #[cfg(feature = "complex")]
@@ -45,6 +45,7 @@ impl Approximation {
             (Exact, _) => (0.0, 0.0),
             (Close, DatumType::F16) => (1e-3, 1e-3),
             (Approximate, DatumType::F16) => (1e-3, 5e-3),
+            (Close | Approximate, DatumType::BF16) => (1e-2, 1e-2),
             (Approximate, qp) if qp.is_quantized() => (qp.zp_scale().1 as f64, 0.),
             (Close, _) => (1e-7, 1e-7),
             (Approximate, _) => (1e-4, 5e-4),
@@ -84,6 +85,7 @@ impl Hash for Tensor {
                 U32 => self.as_slice_unchecked::<u32>().hash(state),
                 U64 => self.as_slice_unchecked::<u64>().hash(state),
                 F16 => self.as_slice_unchecked::<i16>().hash(state),
+                BF16 => self.as_slice_unchecked::<i16>().hash(state),
                 F32 => self.as_slice_unchecked::<i32>().hash(state),
                 F64 => self.as_slice_unchecked::<i64>().hash(state),
                 TDim => self.as_slice_unchecked::<crate::dim::TDim>().hash(state),
@@ -392,6 +394,57 @@ impl Tensor {
         Ok(tensor)
     }
 
+    /// Create a tensor using `data` as its storage, without copying.
+    ///
+    /// # Safety
+    ///
+    /// The content of the blob is used as is, so the caller must treat the tensor as
+    /// uninitialized unless the blob holds valid `dt` items.
+    pub unsafe fn from_blob(dt: DatumType, shape: &[usize], data: Blob) -> TractResult<Tensor> {
+        ensure!(dt.is_copy(), "Can not build a {:?} tensor over a raw blob", dt);
+        let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
+        ensure!(
+            data.len() == bytes,
+            "Blob of {} bytes can not hold a {:?} {:?} tensor",
+            data.len(),
+            shape,
+            dt
+        );
+        let mut tensor = Tensor { strides: tvec!(), dt, shape: shape.into(), data, len: 0 };
+        tensor.update_strides_and_len();
+        Ok(tensor)
+    }
+
+    /// Create a tensor borrowing `len` bytes at `data` from memory kept alive by `owner` (a
+    /// mapped file, typically).
+    ///
+    /// The data is only copied if it can not be used in place: non-plain types, or a pointer
+    /// not aligned for `dt`.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`Blob::from_raw_parts_owned_by`], and the bytes must hold valid `dt`
+    /// items.
+    pub unsafe fn from_raw_parts_owned_by(
+        dt: DatumType,
+        shape: &[usize],
+        data: *mut u8,
+        len: usize,
+        owner: Arc<dyn std::any::Any + Send + Sync>,
+    ) -> TractResult<Tensor> {
+        let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
+        ensure!(len == bytes, "{} bytes can not hold a {:?} {:?} tensor", len, shape, dt);
+        if len == 0
+            || !dt.is_copy()
+            || dt == DatumType::Bool
+            || (data as usize) % dt.alignment() != 0
+        {
+            return Tensor::from_raw_dt(dt, shape, std::slice::from_raw_parts(data, len));
+        }
+        let layout = std::alloc::Layout::from_size_align(len, dt.alignment())?;
+        Tensor::from_blob(dt, shape, Blob::from_raw_parts_owned_by(data, layout, owner))
+    }
+
     pub unsafe fn from_slice_align<T: Datum>(content: &[T], align: usize) -> TractResult<Tensor> {
         let bytes = if content.len() == 0 {
             &[]
@@ -747,6 +800,12 @@ impl Tensor {
         self.dt
     }
 
+    /// Get the alignment (in bytes) of the tensor storage.
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        self.data.layout().align()
+    }
+
     /// Set the datum type of the tensor.
     #[inline]
     pub unsafe fn set_datum_type(&mut self, dt: DatumType) {
@@ -1092,6 +1151,19 @@ impl Tensor {
                 dispatch_datum!(Self::cast_to_string(self.dt)(self, &mut result));
                 return Ok(Cow::Owned(result));
             }
+            // bf16 only casts natively from and to f32, everything else goes through f32
+            if self.dt == DatumType::BF16 || dst_dt == DatumType::BF16 {
+                if self.dt == DatumType::BF16 && dst_dt == DatumType::F32 {
+                    self.natural_cast::<bf16, f32>(&mut result);
+                    return Ok(Cow::Owned(result));
+                } else if self.dt == DatumType::F32 && dst_dt == DatumType::BF16 {
+                    self.natural_cast::<f32, bf16>(&mut result);
+                    return Ok(Cow::Owned(result));
+                } else {
+                    let f32s = self.cast_to::<f32>()?;
+                    return Ok(Cow::Owned(f32s.cast_to_dt(dst_dt)?.into_owned()));
+                }
+            }
             macro_rules! n {
                 ($source:ty) => {
                     if <$source>::datum_type() == self.datum_type() {
@@ -1354,7 +1426,10 @@ impl Tensor {
 
     pub fn deep_clone(&self) -> Tensor {
         unsafe {
-            let mut tensor = Tensor::uninitialized_dt(self.datum_type(), self.shape()).unwrap();
+            // keep the alignment: packed operands rely on more than the datum type's
+            let mut tensor =
+                Tensor::uninitialized_aligned_dt(self.datum_type(), self.shape(), self.alignment())
+                    .unwrap();
             if self.len() > 0 {
                 if self.dt.is_copy() {
                     self.data.as_ptr().copy_to_nonoverlapping(
@@ -1796,4 +1871,41 @@ mod tests {
         let t = tensor0(TDim::from(a));
         let _ = t.clone();
     }
+
+    #[test]
+    fn clone_keeps_alignment() -> TractResult<()> {
+        let t = unsafe { Tensor::from_raw_dt_align(f32::datum_type(), &[4], &[0u8; 16], 64)? };
+        assert_eq!(t.clone().alignment(), 64);
+        Ok(())
+    }
+
+    #[test]
+    fn cast_bf16() -> TractResult<()> {
+        let t = tensor1(&[1.0f32, -2.5, 0.15625]);
+        let bf = t.cast_to::<bf16>()?;
+        assert_eq!(bf.datum_type(), DatumType::BF16);
+        assert_eq!(bf.cast_to::<f32>()?.as_ref(), &t);
+        assert_eq!(bf.cast_to::<i32>()?.as_ref(), &tensor1(&[1i32, -2, 0]));
+        assert_eq!(tensor1(&[3i64]).cast_to::<bf16>()?.as_slice::<bf16>()?, &[bf16::from_f32(3.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn borrow_from_owner() -> TractResult<()> {
+        let owner = Arc::new(std::sync::Mutex::new(tensor1(&[1f32, 2., 3., 4., 5.])));
+        let data = owner.lock().unwrap().as_bytes_mut().as_mut_ptr();
+        let borrowed = unsafe {
+            Tensor::from_raw_parts_owned_by(f32::datum_type(), &[2, 2], data, 16, owner.clone())?
+        };
+        assert!(borrowed.as_ptr::<f32>()? as *const u8 == data);
+        assert_eq!(borrowed, Tensor::from_shape(&[2, 2], &[1f32, 2., 3., 4.])?);
+        // the copying path reads the same bytes, so the borrowing tensor must be gone
+        drop(borrowed);
+        let misaligned = unsafe {
+            Tensor::from_raw_parts_owned_by(f32::datum_type(), &[3], data.add(2), 12, owner)?
+        };
+        assert!(misaligned.as_ptr::<f32>()? as *const u8 != unsafe { data.add(2) });
+        assert_eq!(misaligned.len(), 3);
+        Ok(())
+    }
 }
@@ -23,6 +23,7 @@ pub struct InferenceFact {
     pub datum_type: TypeFactoid,
     pub shape: ShapeFactoid,
     pub value: ValueFact,
+    pub opaque_metadata: Option<Box<dyn OpaqueMetadata>>,
 }
 
 impl InferenceFact {
@@ -84,6 +85,13 @@ impl InferenceFact {
     pub fn without_value(self) -> InferenceFact {
         InferenceFact { value: GenericFactoid::Any, ..self }
     }
+
+    pub fn with_opaque_metadata<O: Into<Box<dyn OpaqueMetadata>>>(
+        self,
+        opaque_metadata: O,
+    ) -> InferenceFact {
+        InferenceFact { opaque_metadata: Some(opaque_metadata.into()), ..self }
+    }
 }
 
 impl Factoid for InferenceFact {
@@ -100,6 +108,7 @@ impl Factoid for InferenceFact {
             datum_type: self.datum_type.unify(&other.datum_type)?,
             shape: self.shape.unify(&other.shape)?,
             value: self.value.unify(&other.value)?,
+            opaque_metadata: self.opaque_metadata.clone().or_else(|| other.opaque_metadata.clone()),
         };
 
         trace!("Unifying {:?} with {:?} into {:?}.", self, other, tensor);
@@ -174,7 +183,14 @@ impl<'a> TryFrom<&'a InferenceFact> for TypedFact {
             let shape = ShapeFact::from_dims(shape);
             let konst = fact.value.concretize();
             let uniform = konst.as_ref().and_then(|k| k.as_uniform()).map(Arc::new);
-            Ok(TypedFact { datum_type, shape, konst, uniform, opaque_metadata: None })
+            Ok(TypedFact {
+                datum_type,
+                shape,
+                konst,
+                uniform,
+                opaque_metadata: fact.opaque_metadata.clone(),
+                per_axis_q: None,
+            })
         } else {
             bail!("Can not make a TypedFact out of {:?}", fact)
         }
@@ -193,6 +209,7 @@ impl<'a> From<&'a TypedFact> for InferenceFact {
         if let Some(k) = &t.konst {
             fact.value = Arc::clone(k).into();
         }
+        fact.opaque_metadata = t.opaque_metadata.clone();
         fact
     }
 }
@@ -103,6 +103,7 @@ pub fn infer_forward_basic(
         datum_type,
         shape: infer_shape_broadcasting(&input_shapes)?.unwrap_or_else(|| shapefactoid![..]),
         value: valuefact!(_),
+        opaque_metadata: None,
     };
 
     Ok(Some(tvec![output]))
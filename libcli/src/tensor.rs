@@ -71,6 +71,7 @@ fn parse_dt(dt: &str) -> TractResult<DatumType> {
     Ok(match dt.to_lowercase().as_ref() {
         "bool" => DatumType::Bool,
         "f16" => DatumType::F16,
+        "bf16" => DatumType::BF16,
         "f32" => DatumType::F32,
         "f64" => DatumType::F64,
         "i8" => DatumType::I8,
@@ -233,6 +234,41 @@ pub fn for_npz(
     bail!("Can not extract tensor from {}", name);
 }
 
+/// Read calibration samples for a model from a npz file, with arrays named after the model
+/// inputs, or `turn_<n>/<input>` for several samples.
+pub fn calibration_from_npz(
+    model: &TypedModel,
+    path: impl AsRef<std::path::Path>,
+) -> TractResult<Vec<TVec<Tensor>>> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).with_context(|| format!("Opening {path:?}"))?;
+    let mut npz = ndarray_npy::NpzReader::new(file)?;
+    let names = npz.names()?;
+    let find = |name: String| {
+        [name.clone(), format!("{name}.npy")].into_iter().find(|n| names.contains(n))
+    };
+    let multiturn = names.iter().any(|n| n.starts_with("turn_0/"));
+    let mut samples = vec![];
+    for turn in 0.. {
+        let prefix = if multiturn { format!("turn_{turn}/") } else { String::new() };
+        let mut sample = tvec!();
+        for input in model.input_outlets()? {
+            let input_name = &model.node(input.node).name;
+            let Some(name) = find(format!("{prefix}{input_name}")) else {
+                ensure!(turn > 0, "No calibration array for input {input_name} in {path:?}");
+                return Ok(samples);
+            };
+            let dt = model.outlet_fact(*input)?.datum_type;
+            sample.push(for_npz(&mut npz, &name)?.cast_to_dt(dt)?.into_owned());
+        }
+        samples.push(sample);
+        if !multiturn {
+            break;
+        }
+    }
+    Ok(samples)
+}
+
 pub fn for_string(
     symbol_table: &SymbolTable,
     value: &str,
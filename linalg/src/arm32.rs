@@ -77,12 +77,20 @@ pub fn plug(ops: &mut Ops) {
                 }
             }),
         };
+        ops.mmm_impls.extend([
+            armv7neon::armv7neon_mmm_f32_32x1_cortexa7.mmm(),
+            armv7neon::armv7neon_mmm_f32_32x1_cortexa9.mmm(),
+            armv7neon::armv7neon_mmm_f32_32x1_generic.mmm(),
+            armv7neon::armv7neon_mmm_i32_8x4.mmm(),
+            armv7neon::armv7neon_mmm_i32_32x1.mmm(),
+        ]);
         ops.qmmm_i32 = Box::new(|_, _, _| armv7neon::armv7neon_mmm_i32_8x4.mmm());
         ops.qmmv_i32 = Box::new(|_, _| armv7neon::armv7neon_mmm_i32_32x1.mmm());
         ops.sigmoid_f32 = Box::new(|| armv7neon_sigmoid_f32_4n::ew());
         ops.tanh_f32 = Box::new(|| armv7neon_tanh_f32_4n::ew());
     } else {
         log::info!("armvfpv2 activated for smmm");
+        ops.mmm_impls.push(armvfpv2::armvfpv2_mmm_f32_4x4.mmm());
         ops.mmm_f32 = Box::new(|_, _, _| armvfpv2::armvfpv2_mmm_f32_4x4.mmm());
     }
 }
@@ -325,6 +325,12 @@ pub fn plug(ops: &mut Ops) {
     {
         if has_amx() {
             log::info!("AMX optimisation activated");
+            ops.mmm_impls.extend([
+                apple_amx::apple_amx_mmm_f16_64x32.mmm(),
+                apple_amx::apple_amx_mmm_f32_32x32.mmm(),
+                apple_amx::apple_amx_mmm_f16_64x1.mmm(),
+                apple_amx::apple_amx_mmm_f32_32x1.mmm(),
+            ]);
             ops.mmm_f16 = Box::new(|_, _, _| apple_amx::apple_amx_mmm_f16_64x32.mmm());
             ops.mmm_f32 = Box::new(|_, _, _| apple_amx::apple_amx_mmm_f32_32x32.mmm());
             ops.mmv_f16 = Box::new(|_, _| apple_amx::apple_amx_mmm_f16_64x1.mmm());
@@ -1,3 +1,4 @@
+pub mod block_quant;
 #[macro_use]
 pub mod element_wise;
 
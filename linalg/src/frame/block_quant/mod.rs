@@ -0,0 +1,303 @@
+use downcast_rs::{impl_downcast, Downcast};
+use dyn_clone::DynClone;
+use dyn_hash::DynHash;
+use std::alloc::Layout;
+use std::fmt::{Debug, Display};
+use std::sync::Arc;
+use tract_data::internal::*;
+
+use crate::frame::Packer;
+use crate::mmm::MMMInput;
+
+mod q4_0;
+mod q8_0;
+
+pub use q4_0::Q4_0;
+pub use q8_0::Q8_0;
+
+/// Largest block length of the supported formats, sizing the stack buffer blocks are
+/// dequantized to.
+pub const MAX_BLOCK_LEN: usize = 32;
+
+/// A block quantization scheme: values are grouped by blocks of `block_len()` consecutive
+/// items along the reduction axis, each block being stored in `block_bytes()` bytes
+/// (typically a scale and packed integer values).
+pub trait BlockQuant: Debug + Display + Send + Sync + DynClone + DynHash + Downcast {
+    fn same_as(&self, other: &dyn BlockQuant) -> bool;
+
+    fn block_len(&self) -> usize;
+
+    fn block_bytes(&self) -> usize;
+
+    fn dequant_block_f32(&self, quant: &[u8], block: &mut [f32]);
+
+    fn quant_block_f32(&self, block: &[f32], quant: &mut [u8]);
+
+    fn quant_f32(&self, input: &[f32]) -> TractResult<Blob> {
+        ensure!(
+            input.len() % self.block_len() == 0,
+            "{} quantization expects a multiple of {} values, got {}",
+            self,
+            self.block_len(),
+            input.len()
+        );
+        let mut quant =
+            Blob::from_bytes(&vec![0u8; input.len() / self.block_len() * self.block_bytes()])?;
+        for (block, quant) in
+            input.chunks(self.block_len()).zip(quant.as_bytes_mut().chunks_mut(self.block_bytes()))
+        {
+            self.quant_block_f32(block, quant);
+        }
+        Ok(quant)
+    }
+
+    fn dequant_f32(&self, input: &[u8]) -> TractResult<Tensor> {
+        ensure!(
+            input.len() % self.block_bytes() == 0,
+            "{} dequantization expects a multiple of {} bytes, got {}",
+            self,
+            self.block_bytes(),
+            input.len()
+        );
+        let mut tensor =
+            Tensor::zero::<f32>(&[input.len() / self.block_bytes() * self.block_len()])?;
+        for (quant, block) in input
+            .chunks(self.block_bytes())
+            .zip(tensor.as_slice_mut::<f32>()?.chunks_mut(self.block_len()))
+        {
+            self.dequant_block_f32(quant, block);
+        }
+        Ok(tensor)
+    }
+}
+impl_downcast!(BlockQuant);
+dyn_clone::clone_trait_object!(BlockQuant);
+dyn_hash::hash_trait_object!(BlockQuant);
+
+impl PartialEq for Box<dyn BlockQuant> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref().same_as(other.as_ref())
+    }
+}
+
+impl Eq for Box<dyn BlockQuant> {}
+
+/// Block-quantized weights of shape `[m, k]`, stored row after row.
+///
+/// They travel through models as the payload of a scalar `Opaque` tensor.
+#[derive(Clone, Debug, Hash)]
+pub struct BlockQuantValue {
+    pub format: Box<dyn BlockQuant>,
+    pub m: usize,
+    pub k: usize,
+    pub value: Arc<Blob>,
+}
+
+impl BlockQuantValue {
+    pub fn new(format: Box<dyn BlockQuant>, m: usize, k: usize, value: Blob) -> TractResult<Self> {
+        ensure!(
+            k % format.block_len() == 0,
+            "k={} is not a multiple of {} block length ({})",
+            k,
+            format,
+            format.block_len()
+        );
+        ensure!(
+            value.len() == m * k / format.block_len() * format.block_bytes(),
+            "Expected {} bytes for {}x{} {} weights, got {}",
+            m * k / format.block_len() * format.block_bytes(),
+            m,
+            k,
+            format,
+            value.len()
+        );
+        Ok(BlockQuantValue { format, m, k, value: Arc::new(value) })
+    }
+
+    /// Quantize a `[m, k]` f32 tensor.
+    pub fn quant_f32(format: Box<dyn BlockQuant>, tensor: &Tensor) -> TractResult<Self> {
+        ensure!(tensor.rank() == 2, "Block quantization expects a [m, k] matrix");
+        let (m, k) = (tensor.shape()[0], tensor.shape()[1]);
+        let tensor = tensor.cast_to::<f32>()?;
+        let value = format.quant_f32(tensor.as_slice::<f32>()?)?;
+        Self::new(format, m, k, value)
+    }
+
+    /// Dequantize to a `[m, k]` f32 tensor.
+    pub fn dequant_f32(&self) -> TractResult<Tensor> {
+        self.format.dequant_f32(&self.value)?.into_shape(&[self.m, self.k])
+    }
+
+    fn row_bytes(&self) -> usize {
+        self.k / self.format.block_len() * self.format.block_bytes()
+    }
+}
+
+impl Display for BlockQuantValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}x{}", self.format, self.m, self.k)
+    }
+}
+
+impl OpaquePayload for BlockQuantValue {}
+
+/// Block-quantized weights exposed as the A input of a MatMatMul kernel.
+///
+/// Panels are dequantized on the fly, in the scratch buffer of the kernel, to the layout
+/// `packer` would have produced (f32 or f16).
+#[derive(Clone, Debug, Hash)]
+pub struct PackedBlockQuant {
+    pub value: BlockQuantValue,
+    pub packer: Packer,
+}
+
+impl PackedBlockQuant {
+    pub fn new(value: BlockQuantValue, packer: &Packer) -> TractResult<PackedBlockQuant> {
+        ensure!(
+            packer.dt == f32::datum_type() || packer.dt == f16::datum_type(),
+            "Block quantized weights can only be unpacked to f32 or f16, not {:?}",
+            packer.dt
+        );
+        ensure!(
+            value.format.block_len() <= MAX_BLOCK_LEN,
+            "Block length {} exceeds {}",
+            value.format.block_len(),
+            MAX_BLOCK_LEN
+        );
+        Ok(PackedBlockQuant { value, packer: packer.clone() })
+    }
+
+    unsafe fn unpack_panel<T: Datum + Copy>(
+        &self,
+        i: usize,
+        panel: *mut T,
+        convert: impl Fn(f32) -> T,
+    ) {
+        let r = self.packer.r;
+        let k = self.value.k;
+        let block_len = self.value.format.block_len();
+        let block_bytes = self.value.format.block_bytes();
+        let panel = std::slice::from_raw_parts_mut(panel, self.packer.single_panel_len(k));
+        panel.fill(convert(0.0));
+        let mut block = [0f32; MAX_BLOCK_LEN];
+        let block = &mut block[..block_len];
+        for row in 0..r.min(self.value.m.saturating_sub(i * r)) {
+            let quant = &self.value.value[(i * r + row) * self.value.row_bytes()..];
+            for (b, quant) in quant.chunks(block_bytes).take(k / block_len).enumerate() {
+                self.value.format.dequant_block_f32(quant, block);
+                for (ix, x) in block.iter().enumerate() {
+                    *panel.get_unchecked_mut((b * block_len + ix) * r + row) = convert(*x);
+                }
+            }
+        }
+    }
+}
+
+impl MMMInput for PackedBlockQuant {
+    fn scratch_panel_buffer_layout(&self) -> Option<Layout> {
+        Some(self.packer.single_panel_layout(self.value.k, self.packer.dt.size_of()))
+    }
+
+    fn panel_bytes(&self, i: usize, buffer: Option<*mut u8>) -> *const u8 {
+        let buffer = buffer.expect("PackedBlockQuant needs a scratch panel buffer");
+        unsafe {
+            if self.packer.dt == f16::datum_type() {
+                self.unpack_panel(i, buffer as *mut f16, f16::from_f32)
+            } else {
+                self.unpack_panel(i, buffer as *mut f32, |x| x)
+            }
+        }
+        buffer
+    }
+
+    fn mn(&self) -> usize {
+        self.value.m
+    }
+
+    fn r(&self) -> usize {
+        self.packer.r
+    }
+
+    fn k(&self) -> usize {
+        self.value.k
+    }
+}
+
+impl Display for PackedBlockQuant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (unpacked to {:?} panels)", self.value, self.packer.dt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mmm::{FusedSpec, MatMatMul};
+
+    fn weights(m: usize, k: usize) -> Tensor {
+        let values: Vec<f32> =
+            (0..m * k).map(|i| ((i * 7919) % 113) as f32 / 113.0 - 0.5).collect();
+        Tensor::from_shape(&[m, k], &values).unwrap()
+    }
+
+    #[test]
+    fn roundtrip_error_is_small() -> TractResult<()> {
+        for format in [Box::new(Q4_0) as Box<dyn BlockQuant>, Box::new(Q8_0)] {
+            let w = weights(3, 64);
+            let bq = BlockQuantValue::quant_f32(format.clone(), &w)?;
+            let back = bq.dequant_f32()?;
+            let tolerance = if format.is::<Q4_0>() { 0.05 } else { 0.005 };
+            for (a, b) in w.as_slice::<f32>()?.iter().zip(back.as_slice::<f32>()?) {
+                assert!((a - b).abs() < tolerance, "{format}: {a} vs {b}");
+            }
+        }
+        Ok(())
+    }
+
+    fn run_mmm(mmm: &dyn MatMatMul, format: Box<dyn BlockQuant>) -> TractResult<()> {
+        let (m, k, n) = (19, 64, 5);
+        let bq = BlockQuantValue::quant_f32(format, &weights(m, k))?;
+        let reference_a = bq.dequant_f32()?;
+        let b =
+            Tensor::from_shape(&[k, n], &(0..k * n).map(|x| (x % 5) as f32).collect::<Vec<_>>())?;
+        let (a_format, b_format) = mmm.packings()[0];
+        let packer = a_format.downcast_ref::<Packer>().unwrap();
+        let pa: Box<dyn MMMInput> = Box::new(PackedBlockQuant::new(bq, packer)?);
+        let pb = b_format.prepare_tensor(&b, 0, 1)?;
+        let mut c = Tensor::zero::<f32>(&[m, n])?;
+        unsafe {
+            mmm.run(
+                m,
+                n,
+                &[
+                    FusedSpec::AddMatMul { a: &*pa, b: &*pb, packing: 0 },
+                    FusedSpec::Store(mmm.c_view(0, 1).wrap(&c.view_mut())),
+                ],
+            )?;
+        }
+        let a = reference_a.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+        let b = b.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+        let expected = a.dot(&b).into_tensor();
+        c.close_enough(&expected, Approximation::Close)
+    }
+
+    #[test]
+    fn mmm_q4_0() -> TractResult<()> {
+        run_mmm(
+            &*crate::ops()
+                .mmm(DatumType::F32, DatumType::F32, DatumType::F32, None, None, None)
+                .unwrap(),
+            Box::new(Q4_0),
+        )
+    }
+
+    #[test]
+    fn mmm_q8_0() -> TractResult<()> {
+        run_mmm(
+            &*crate::ops()
+                .mmm(DatumType::F32, DatumType::F32, DatumType::F32, None, None, None)
+                .unwrap(),
+            Box::new(Q8_0),
+        )
+    }
+}
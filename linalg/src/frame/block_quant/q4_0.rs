@@ -0,0 +1,58 @@
+use std::fmt::Display;
+use tract_data::internal::*;
+
+use super::BlockQuant;
+
+/// 32 values per block: a f16 scale followed by 16 bytes of 4-bit values, offset by 8.
+///
+/// Byte `i` stores item `i` in its low nibble and item `i + 16` in its high nibble.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Q4_0;
+
+impl Q4_0 {
+    const LEN: usize = 32;
+}
+
+impl BlockQuant for Q4_0 {
+    fn same_as(&self, other: &dyn BlockQuant) -> bool {
+        other.is::<Self>()
+    }
+
+    fn block_len(&self) -> usize {
+        Self::LEN
+    }
+
+    fn block_bytes(&self) -> usize {
+        2 + Self::LEN / 2
+    }
+
+    fn quant_block_f32(&self, block: &[f32], quant: &mut [u8]) {
+        // levels go from -8 to 7: pick the scale sign so that the smallest step covers both
+        // ends of the block, instead of clipping the side opposite to the largest magnitude
+        let (lo, hi) = block.iter().fold((0f32, 0f32), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        let positive = (-lo / 8.0).max(hi / 7.0);
+        let negative = (-lo / 7.0).max(hi / 8.0);
+        let scale = f16::from_f32(if positive <= negative { positive } else { -negative });
+        let inv = if scale.to_f32() != 0.0 { scale.to_f32().recip() } else { 0.0 };
+        quant[0..2].copy_from_slice(&scale.to_le_bytes());
+        for i in 0..Self::LEN / 2 {
+            let lo = (block[i] * inv + 8.5).clamp(0.0, 15.0) as u8;
+            let hi = (block[i + Self::LEN / 2] * inv + 8.5).clamp(0.0, 15.0) as u8;
+            quant[2 + i] = lo | (hi << 4);
+        }
+    }
+
+    fn dequant_block_f32(&self, quant: &[u8], block: &mut [f32]) {
+        let scale = f16::from_le_bytes([quant[0], quant[1]]).to_f32();
+        for i in 0..Self::LEN / 2 {
+            block[i] = ((quant[2 + i] & 0x0F) as i8 - 8) as f32 * scale;
+            block[i + Self::LEN / 2] = ((quant[2 + i] >> 4) as i8 - 8) as f32 * scale;
+        }
+    }
+}
+
+impl Display for Q4_0 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Q4_0")
+    }
+}
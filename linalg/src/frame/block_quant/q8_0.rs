@@ -0,0 +1,49 @@
+use std::fmt::Display;
+use tract_data::internal::*;
+
+use super::BlockQuant;
+
+/// 32 values per block: a f16 scale followed by 32 signed bytes.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Q8_0;
+
+impl Q8_0 {
+    const LEN: usize = 32;
+}
+
+impl BlockQuant for Q8_0 {
+    fn same_as(&self, other: &dyn BlockQuant) -> bool {
+        other.is::<Self>()
+    }
+
+    fn block_len(&self) -> usize {
+        Self::LEN
+    }
+
+    fn block_bytes(&self) -> usize {
+        2 + Self::LEN
+    }
+
+    fn quant_block_f32(&self, block: &[f32], quant: &mut [u8]) {
+        let amax = block.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+        let scale = amax / 127.0;
+        let inv = if scale != 0.0 { scale.recip() } else { 0.0 };
+        quant[0..2].copy_from_slice(&f16::from_f32(scale).to_le_bytes());
+        for (q, x) in quant[2..].iter_mut().zip(block) {
+            *q = (x * inv).round() as i8 as u8;
+        }
+    }
+
+    fn dequant_block_f32(&self, quant: &[u8], block: &mut [f32]) {
+        let scale = f16::from_le_bytes([quant[0], quant[1]]).to_f32();
+        for (x, q) in block.iter_mut().zip(&quant[2..]) {
+            *x = *q as i8 as f32 * scale;
+        }
+    }
+}
+
+impl Display for Q8_0 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Q8_0")
+    }
+}
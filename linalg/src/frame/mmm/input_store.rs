@@ -17,7 +17,9 @@ pub trait MMMInputFormat: Downcast + Debug {
 }
 impl_downcast!(MMMInputFormat);
 
-pub trait MMMInput: dyn_clone::DynClone + Debug + DynHash + Send + Sync + Display {
+pub trait MMMInput:
+    Downcast + dyn_clone::DynClone + Debug + DynHash + Send + Sync + Display
+{
     fn scratch_panel_buffer_layout(&self) -> Option<Layout>;
     fn panel_bytes(&self, i: usize, buffer: Option<*mut u8>) -> *const u8;
     fn panels_count(&self) -> usize {
@@ -27,6 +29,7 @@ pub trait MMMInput: dyn_clone::DynClone + Debug + DynHash + Send + Sync + Displa
     fn r(&self) -> usize;
     fn k(&self) -> usize;
 }
+impl_downcast!(MMMInput);
 dyn_clone::clone_trait_object!(MMMInput);
 dyn_hash::hash_trait_object!(MMMInput);
 
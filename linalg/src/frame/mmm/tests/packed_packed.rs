@@ -18,7 +18,7 @@ macro_rules! mmm_packed_packed_tests {
             use num_traits::Zero;
             use proptest::prelude::*;
             #[allow(unused_imports)]
-            use tract_data::prelude::f16;
+            use tract_data::prelude::{bf16, f16};
             #[allow(unused_imports)]
             use $crate::frame::mmm::tests::packed_packed::*;
             use $crate::frame::mmm::MatMatMulKer;
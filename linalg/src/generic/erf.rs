@@ -2,7 +2,7 @@ use crate::element_wise::ElementWiseKer;
 
 #[allow(non_upper_case_globals)]
 #[allow(clippy::excessive_precision)]
-fn serf(x: &mut f32) {
+pub(crate) fn serf(x: &mut f32) {
     const a1: f32 = 0.0705230784;
     const a2: f32 = 0.0422820123;
     const a3: f32 = 0.0092705272;
@@ -0,0 +1,96 @@
+use super::erf::serf;
+use crate::element_wise::ElementWiseKer;
+use tract_data::internal::*;
+
+pub fn sgelu(x: f32) -> f32 {
+    let mut e = x * std::f32::consts::FRAC_1_SQRT_2;
+    serf(&mut e);
+    0.5 * x * (1.0 + e)
+}
+
+#[derive(Clone, Debug)]
+pub struct SGelu4;
+
+impl ElementWiseKer<f32> for SGelu4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        16
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f32], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = sgelu(*px))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HGelu8;
+
+impl ElementWiseKer<f16> for HGelu8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        16
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = f16::from_f32(sgelu(px.to_f32())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::element_wise::test::test_element_wise;
+    use proptest::prelude::*;
+
+    // Abramowitz and Stegun 7.1.26
+    fn erf(x: f64) -> f64 {
+        let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+        let y = t
+            * (0.254829592
+                + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+        (1.0 - y * (-x * x).exp()).copysign(x)
+    }
+
+    fn reference(x: f32) -> f32 {
+        (0.5 * x as f64 * (1.0 + erf(x as f64 * std::f64::consts::FRAC_1_SQRT_2))) as f32
+    }
+
+    proptest! {
+        #[test]
+        fn gelu_f32(xs in proptest::collection::vec(-10f32..10.0, 0..100)) {
+            test_element_wise::<SGelu4, _, _>(&xs, reference)?
+        }
+
+        #[test]
+        fn gelu_f16(xs in proptest::collection::vec(-10f32..10.0, 0..100)) {
+            let xs = xs.into_iter().map(f16::from_f32).collect::<Vec<_>>();
+            test_element_wise::<HGelu8, _, _>(&xs, |x| f16::from_f32(reference(x.to_f32())))?
+        }
+    }
+}
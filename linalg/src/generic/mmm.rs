@@ -170,7 +170,10 @@ where
                 }
                 FusedKerSpec::AddMatMul { k, pa, pb, packing } => {
                     use std::mem::transmute;
-                    if TI::datum_type().is_float() {
+                    if TI::datum_type() == f32::datum_type() && packing == 1 {
+                        let ab = transmute::<&mut [[TI; NR]; MR], &mut [[f32; NR]; MR]>(&mut ab);
+                        add_mat_mul::<MR, NR, f32, bf16, bf16>(pa, pb, k, ab)
+                    } else if TI::datum_type().is_float() {
                         add_mat_mul::<MR, NR, TI, TI, TI>(pa, pb, k, &mut ab);
                     } else if TI::datum_type() == i32::datum_type() {
                         let ab = transmute::<&mut [[TI; NR]; MR], &mut [[i32; NR]; MR]>(&mut ab);
@@ -201,8 +204,24 @@ where
 
 MMMKernelWrapper!(f16, generic_f16_4x4; kernel::<f16, 4, 4>; 4, 4; 4, 4; 0, 0; no_prefetch, true);
 MMMKernelWrapper!(f16, generic_f16_4x1; kernel::<f16, 4, 1>; 4, 1; 4, 4; 0, 0; no_prefetch, true);
-MMMKernelWrapper!(f32, generic_f32_4x4; kernel::<f32, 4, 4>; 4, 4; 4, 4; 0, 0; no_prefetch, true);
-MMMKernelWrapper!(f32, generic_f32_4x1; kernel::<f32, 4, 1>; 4, 1; 4, 4; 0, 0; no_prefetch, true);
+MMMKernelWrapper!(f32, generic_f32_4x4; kernel::<f32, 4, 4>; 4, 4; 4, 4; 0, 0; no_prefetch, true,
+ packing_defs: {
+     const BF16_A: Packer = Packer::new(DatumType::BF16, 4, 4, 0);
+     const BF16_B: Packer = Packer::new(DatumType::BF16, 4, 4, 0);
+     const BF16_BF16: (&dyn MMMInputFormat, &dyn MMMInputFormat) = (&BF16_A, &BF16_B);
+ },
+ packings: BF16_BF16,
+ test: mmm_packed_packed_tests!{ true, generic_f32_4x4, bf16bf16:1, bf16, bf16, f32, f32 }
+);
+MMMKernelWrapper!(f32, generic_f32_4x1; kernel::<f32, 4, 1>; 4, 1; 4, 4; 0, 0; no_prefetch, true,
+ packing_defs: {
+     const BF16_A: Packer = Packer::new(DatumType::BF16, 4, 4, 0);
+     const BF16_B: Packer = Packer::new(DatumType::BF16, 1, 4, 0);
+     const BF16_BF16: (&dyn MMMInputFormat, &dyn MMMInputFormat) = (&BF16_A, &BF16_B);
+ },
+ packings: BF16_BF16,
+ test: mmm_packed_packed_tests!{ true, generic_f32_4x1, bf16bf16:1, bf16, bf16, f32, f32 }
+);
 MMMKernelWrapper!(f64, generic_f64_4x4; kernel::<f64, 4, 4>; 4, 4; 4, 4; 0, 0; no_prefetch, true);
 MMMKernelWrapper!(f64, generic_f64_4x1; kernel::<f64, 4, 1>; 4, 1; 4, 4; 0, 0; no_prefetch, true);
 MMMKernelWrapper!(i32, generic_i32_4x4; kernel::<i32, 4, 4>; 4, 4; 4, 4; 0, 0; no_prefetch, true,
@@ -0,0 +1,84 @@
+use super::sigmoid::ssigmoid;
+use crate::element_wise::ElementWiseKer;
+use tract_data::internal::*;
+
+#[derive(Clone, Debug)]
+pub struct SSilu4;
+
+impl ElementWiseKer<f32> for SSilu4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        16
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f32], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px *= ssigmoid(*px))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HSilu8;
+
+impl ElementWiseKer<f16> for HSilu8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        16
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        // the f16 sigmoid approximation loses too much once multiplied by x: go through f32
+        x.iter_mut().for_each(|px| {
+            let x = px.to_f32();
+            *px = f16::from_f32(x * ssigmoid(x))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::element_wise::test::test_element_wise;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn silu_f32(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            test_element_wise::<SSilu4, _, _>(&xs, |x| x / (1.0 + (-x).exp()))?
+        }
+
+        #[test]
+        fn silu_f16(xs in proptest::collection::vec(-4f32..4.0, 0..100)) {
+            let xs = xs.into_iter().map(f16::from_f32).collect::<Vec<_>>();
+            test_element_wise::<HSilu8, _, _>(&xs, |x| {
+                let x = x.to_f32();
+                f16::from_f32(x / (1.0 + (-x).exp()))
+            })?
+        }
+    }
+}
@@ -36,7 +36,7 @@ pub mod arm32;
 #[cfg(all(target_family = "wasm", target_feature = "simd128"))]
 pub mod wasm;
 
-pub use self::frame::{element_wise, lut, mmm};
+pub use self::frame::{block_quant, element_wise, lut, mmm};
 
 use tract_data::prelude::*;
 
@@ -59,6 +59,9 @@ pub struct Ops {
     mmm_f16: MMMImpl,
     mmv_f16: MMVImpl,
 
+    mmm_bf16: MMMImpl,
+    mmv_bf16: MMVImpl,
+
     qmmm_i32: MMMImpl,
     qmmv_i32: MMVImpl,
 
@@ -74,6 +77,10 @@ pub struct Ops {
     pub tanh_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
     pub tanh_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub erf_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
+    pub gelu_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
+    pub gelu_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
+    pub silu_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
+    pub silu_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub lut_u8: Box<dyn Fn(&[u8]) -> Box<dyn lut::Lut> + Send + Sync>,
 
     pub max_f16: Box<dyn Fn() -> Box<dyn reduce::Reduce<f16>> + Send + Sync>,
@@ -93,6 +100,26 @@ impl Ops {
         &self.mmm_impls
     }
 
+    /// Every matrix multiplier kernel this set of ops may hand out, including the
+    /// per-type defaults, deduplicated by kernel name.
+    pub fn all_mmm_impls(&self) -> Vec<Box<dyn MatMatMul>> {
+        let mut impls = self.mmm_impls.clone();
+        for mmm in [&self.mmm_f64, &self.mmm_f32, &self.mmm_f16, &self.mmm_bf16, &self.qmmm_i32] {
+            impls.push(mmm(None, None, None));
+        }
+        for mmv in [&self.mmv_f64, &self.mmv_f32, &self.mmv_f16, &self.mmv_bf16, &self.qmmv_i32] {
+            impls.push(mmv(None, None));
+        }
+        impls.sort_by_key(|mmm| mmm.kernel_name());
+        impls.dedup_by_key(|mmm| mmm.kernel_name());
+        impls
+    }
+
+    /// Lookup a matrix multiplier kernel by its name.
+    pub fn mmm_impl_by_name(&self, name: &str) -> Option<Box<dyn MatMatMul>> {
+        self.all_mmm_impls().into_iter().find(|mmm| mmm.kernel_name() == name)
+    }
+
     pub fn mmm(
         &self,
         a: DatumType,
@@ -113,6 +140,9 @@ impl Ops {
             (F16, F16, F16) => {
                 Some(if n == Some(1) { (self.mmv_f16)(m, k) } else { (self.mmm_f16)(m, k, n) })
             }
+            (BF16, BF16, F32) => {
+                Some(if n == Some(1) { (self.mmv_bf16)(m, k) } else { (self.mmm_bf16)(m, k, n) })
+            }
             (I8, I8, I32) => {
                 Some(if n == Some(1) { (self.qmmv_i32)(m, k) } else { (self.qmmm_i32)(m, k, n) })
             }
@@ -134,6 +164,8 @@ pub fn generic() -> Ops {
         mmv_f32: Box::new(|_, _| generic_f32_4x1.mmm()),
         mmm_f16: Box::new(|_, _, _| generic_f16_4x4.mmm()),
         mmv_f16: Box::new(|_, _| generic_f16_4x1.mmm()),
+        mmm_bf16: Box::new(|_, _, _| generic_f32_4x4.mmm()),
+        mmv_bf16: Box::new(|_, _| generic_f32_4x1.mmm()),
         qmmm_i32: Box::new(|_, _, _| generic_i32_4x4.mmm()),
         qmmv_i32: Box::new(|_, _| generic_i32_4x4.mmm()),
         leaky_relu_f16: Box::new(|| generic::HLeakyRelu8::ew()),
@@ -145,6 +177,10 @@ pub fn generic() -> Ops {
         tanh_f16: Box::new(|| generic::HTanh8::ew()),
         tanh_f32: Box::new(|| generic::STanh4::ew()),
         erf_f32: Box::new(|| generic::SErf4::ew()),
+        gelu_f16: Box::new(|| generic::HGelu8::ew()),
+        gelu_f32: Box::new(|| generic::SGelu4::ew()),
+        silu_f16: Box::new(|| generic::HSilu8::ew()),
+        silu_f32: Box::new(|| generic::SSilu4::ew()),
         lut_u8: Box::new(|table: &[u8]| Box::new(lut::LutImpl::<generic::GenericLut8>::new(table))),
         max_f16: Box::new(|| generic::reduce::max::HMax8::red()),
         max_f32: Box::new(|| generic::reduce::max::SMax4::red()),
@@ -218,6 +254,13 @@ impl LADatum for f16 {
     }
 }
 
+impl LADatum for bf16 {
+    #[cfg(test)]
+    fn strat() -> BoxedStrategy<Self> {
+        f32::strat().prop_map(|f| f.as_()).boxed()
+    }
+}
+
 impl LADatum for f32 {
     #[cfg(test)]
     fn strat() -> BoxedStrategy<Self> {
@@ -258,3 +301,30 @@ impl LADatum for i32 {
 fn setup_test_logger() {
     let _ = env_logger::Builder::from_env("TRACT_LOG").try_init();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selected_kernels_are_found_by_name() {
+        let ops = ops();
+        let dims = [None, Some(1), Some(2), Some(3), Some(4), Some(5), Some(8), Some(16), Some(64)];
+        for (a, b, c) in [
+            (DatumType::F32, DatumType::F32, DatumType::F32),
+            (DatumType::F16, DatumType::F16, DatumType::F16),
+            (DatumType::I8, DatumType::I8, DatumType::I32),
+        ] {
+            for m in dims {
+                for n in dims {
+                    let mmm = ops.mmm(a, b, c, m, Some(32), n).unwrap();
+                    assert!(
+                        ops.mmm_impl_by_name(&mmm.kernel_name()).is_some(),
+                        "{} is not registered",
+                        mmm.kernel_name()
+                    );
+                }
+            }
+        }
+    }
+}
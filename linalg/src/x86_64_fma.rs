@@ -121,6 +121,17 @@ fn plug_fma(ops: &mut Ops) {
 }
 
 fn plug_avx512f(ops: &mut Ops) {
+    ops.mmm_impls.extend([
+        mmm::avx512_mmm_f32_128x1.mmm(),
+        mmm::avx512_mmm_f32_16x1.mmm(),
+        mmm::avx512_mmm_f32_16x12.mmm(),
+        mmm::avx512_mmm_f32_16x8.mmm(),
+        mmm::avx512_mmm_f32_32x6.mmm(),
+        mmm::avx512_mmm_f32_32x5.mmm(),
+        mmm::avx512_mmm_f32_48x4.mmm(),
+        mmm::avx512_mmm_f32_64x3.mmm(),
+        mmm::avx512_mmm_f32_80x2.mmm(),
+    ]);
     ops.mmv_f32 = Box::new(|m, _k| match m {
         Some(m) if m < 31 => mmm::avx512_mmm_f32_16x1.mmm(),
         _ => mmm::avx512_mmm_f32_128x1.mmm(),
@@ -23,6 +23,7 @@ impl ProtoModel {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum QuantFormat {
     Linear { params: QParams, bits: i8, signed: bool },
+    PerAxisLinear { params: PerAxisQParams, bits: i8, signed: bool },
 }
 
 impl QuantFormat {
@@ -39,15 +40,36 @@ impl QuantFormat {
         }
     }
 
+    pub fn from_per_axis(datum_type: DatumType, params: &PerAxisQParams) -> QuantFormat {
+        QuantFormat::PerAxisLinear {
+            params: params.clone(),
+            bits: 8 * datum_type.size_of() as i8,
+            signed: datum_type.is_signed(),
+        }
+    }
+
     pub fn datum_type(&self) -> DatumType {
+        let (params, bits, signed) = match self {
+            QuantFormat::Linear { params, bits, signed } => (*params, bits, signed),
+            // the datum type carries the parameters of the first channel
+            QuantFormat::PerAxisLinear { params, bits, signed } => {
+                let (zero_point, scale) = params.zp_scale(0);
+                (QParams::ZpScale { zero_point, scale }, bits, signed)
+            }
+        };
+        match (bits, signed) {
+            (8, true) => DatumType::QI8(params),
+            (8, false) => DatumType::QU8(params),
+            (32, true) => DatumType::QI32(params),
+            (32, false) => DatumType::U32,
+            _ => todo!(),
+        }
+    }
+
+    pub fn per_axis_q(&self) -> Option<&PerAxisQParams> {
         match self {
-            QuantFormat::Linear { params, bits, signed } => match (bits, signed) {
-                (8, true) => DatumType::QI8(*params),
-                (8, false) => DatumType::QU8(*params),
-                (32, true) => DatumType::QI32(*params),
-                (32, false) => DatumType::U32,
-                _ => todo!(),
-            },
+            QuantFormat::PerAxisLinear { params, .. } => Some(params),
+            _ => None,
         }
     }
 }
@@ -35,9 +35,14 @@ impl<'a> Dumper<'a> {
         writeln!(self.w, "version {};\n", document.version)?;
         for ext in document.extension.iter().sorted() {
             write!(self.w, "extension")?;
-            for id in ext {
+            for (ix, id) in ext.iter().enumerate() {
                 write!(self.w, " ")?;
-                self.identifier(id)?;
+                if ix > 0 && ext[0].0 == "tract_assert" {
+                    // assertion tokens are expressions and operators, not identifiers
+                    write!(self.w, "{}", id.0)?;
+                } else {
+                    self.identifier(id)?;
+                }
             }
             writeln!(self.w, ";")?;
         }
@@ -54,9 +54,21 @@ fn version(i: &str) -> IResult<&str, NumericLiteral> {
     delimited(stag("version"), numeric_literal, stag(";"))(i)
 }
 
-// <extension> ::= "extension" <identifier>+ ";"
+// <extension> ::= "extension" <identifier> (<escaped-identifier> | <token>)* ";"
 fn extension(i: &str) -> IResult<&str, Vec<Identifier>> {
-    delimited(stag("extension"), many1(spaced(identifier)), stag(";"))(i)
+    delimited(
+        stag("extension"),
+        map(
+            pair(spaced(identifier), many0(spaced(alt((escaped_identifier, extension_token))))),
+            |(head, tail)| std::iter::once(head).chain(tail).collect(),
+        ),
+        stag(";"),
+    )(i)
+}
+
+// extension arguments are not always identifiers (e.g. "extension tract_assert S >= 1;")
+fn extension_token(i: &str) -> IResult<&str, Identifier> {
+    map(is_not(" \t\r\n;#"), Identifier::from)(i)
 }
 
 // FRAGMENT
@@ -478,6 +490,16 @@ mod test {
         assert_eq!(p(type_spec, "tensor<complex>[]"), Array(Box::new(Tensor(TypeName::Complex))));
     }
 
+    #[test]
+    fn test_extension() {
+        let ext = |i: &'static str| p(extension, i).into_iter().map(|id| id.0).collect::<Vec<_>>();
+        assert_eq!(ext("extension tract_symbol S;"), ["tract_symbol", "S"]);
+        assert_eq!(
+            ext("extension tract_assert min(S,4) >= 1;"),
+            ["tract_assert", "min(S,4)", ">=", "1"]
+        );
+    }
+
     #[test]
     fn test_fragment_decl_fizz() {
         let parsed = p(
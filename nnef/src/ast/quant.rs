@@ -5,6 +5,7 @@ use nom::character::complete::digit1;
 use nom::combinator::{map_res, recognize};
 use nom::sequence::{delimited, pair};
 use tract_core::internal::*;
+use tract_core::tract_data::itertools::Itertools;
 
 use nom::branch::alt;
 use nom::{bytes::complete::*, multi::*};
@@ -37,9 +38,24 @@ fn integer_numeric<T: FromStr>(i: &str) -> IResult<&str, T> {
 
 // <qparam> ::= "<identifier>": <qparam>
 fn qparam(i: &str) -> IResult<&str, QuantFormat> {
-    let (i, id) =
-        nom::branch::alt((stag("linear_quantize"), stag("zero_point_linear_quantize")))(i)?;
+    let (i, id) = nom::branch::alt((
+        stag("linear_quantize"),
+        stag("zero_point_linear_quantize"),
+        stag("per_axis_linear_quantize"),
+    ))(i)?;
     let (i, _) = stag("(")(i)?;
+    if id == "per_axis_linear_quantize" {
+        let (i, (axis, zero_points, scales, bits, signed)) = permutation((
+            arg("axis", integer_numeric),
+            arg("zero_point", list(integer_numeric)),
+            arg("scale", list(float)),
+            arg("bits", integer_numeric),
+            arg("signed", logical_literal),
+        ))(i)?;
+        let (i, _) = stag(")")(i)?;
+        let params = PerAxisQParams { axis, zero_points, scales };
+        return Ok((i, QuantFormat::PerAxisLinear { params, bits, signed }));
+    }
     let (i, params, bits, signed) = match id {
         "linear_quantize" => {
             let (i, (bits, max, min)) =
@@ -65,6 +81,14 @@ fn qparam(i: &str) -> IResult<&str, QuantFormat> {
     let (i, _) = stag(")")(i)?;
     Ok((i, QuantFormat::Linear { params, bits, signed }))
 }
+// <list>(<f>) ::= "[" <f> ("," <f>)* "]"
+fn list<'s, T, F>(f: F) -> impl Fn(&'s str) -> IResult<&'s str, Vec<T>>
+where
+    F: Fn(&'s str) -> IResult<&'s str, T> + Copy,
+{
+    move |i: &str| delimited(stag("["), separated_list0(stag(","), f), stag("]"))(i)
+}
+
 // <arg>(<id>, <f>) ::= <id> "=" <f> ","
 fn arg<'s, T, F>(name: &'static str, f: F) -> impl Fn(&'s str) -> IResult<&'s str, T>
 where
@@ -93,6 +117,13 @@ pub(crate) fn write_quant_format(
         QuantFormat::Linear {
             params: QParams::MinMax {min, max}, bits, signed: _
         } => writeln!(w, ": linear_quantize(max = {max:.9}, min = {min:.9}, bits = {bits});")?,
+        QuantFormat::PerAxisLinear { params, bits, signed } => writeln!(
+            w,
+            ": per_axis_linear_quantize(axis = {}, zero_point = [{}], scale = [{}], bits = {bits}, signed = {signed});",
+            params.axis,
+            params.zero_points.iter().join(", "),
+            params.scales.iter().map(|s| format!("{s:.9}")).join(", "),
+        )?,
     }
     Ok(())
 }
@@ -185,6 +216,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_per_axis_qparam() {
+        assert_eq!(
+            p(
+                qparam,
+                "per_axis_linear_quantize(axis = 0, zero_point = [0, -2], scale = [0.5, 0.25], bits = 8, signed = true)"
+            ),
+            QuantFormat::PerAxisLinear {
+                params: PerAxisQParams { axis: 0, zero_points: vec![0, -2], scales: vec![0.5, 0.25] },
+                bits: 8,
+                signed: true
+            }
+        );
+    }
+
     #[test]
     fn test_quant_file_1() {
         assert_eq!(
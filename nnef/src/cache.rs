@@ -0,0 +1,173 @@
+//! On-disk cache for optimized models.
+//!
+//! Optimizing a big model can take seconds. This cache stores the codegen'd model, packed
+//! weights and kernel choices included, as an NNEF archive using the `tract_lir` extension,
+//! so that later processes can reload it without running the optimizer again.
+//!
+//! Entries are keyed on the matrix multiplication kernels available on the running CPU (see
+//! [`crate::ops::lir::fingerprint`]): on a machine with a different kernel set, or if a model
+//! contains operators `tract_lir` can not serialize, the model is optimized as usual.
+use std::path::{Path, PathBuf};
+
+use crate::internal::*;
+use crate::ops::lir::fingerprint;
+
+pub const LIR_FINGERPRINT_PROPERTY: &str = "tract_lir_fingerprint";
+
+pub struct OptimizedModelCache {
+    pub framework: Nnef,
+    pub dir: PathBuf,
+}
+
+impl OptimizedModelCache {
+    pub fn new(mut framework: Nnef, dir: impl AsRef<Path>) -> OptimizedModelCache {
+        if !framework.registries.iter().any(|r| r.id.0 == "tract_core") {
+            framework.enable_tract_core();
+        }
+        if !framework.registries.iter().any(|r| r.id.0 == "tract_lir") {
+            framework.enable_tract_lir();
+        }
+        OptimizedModelCache { framework, dir: dir.as_ref().to_path_buf() }
+    }
+
+    /// Location of the cache entry for `key` on this machine.
+    pub fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{:016x}.nnef.tar", fnv1a(fingerprint().as_bytes())))
+    }
+
+    /// Load the optimized model for `key`, if it has been cached for this machine.
+    pub fn load(&self, key: &str) -> TractResult<Option<TypedModel>> {
+        let path = self.path_for(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        // an entry that can not be read back (truncated, or referring to kernels this build
+        // lacks) is a miss: the model gets optimized and the entry overwritten
+        let model = match self.framework.model_for_path(&path) {
+            Ok(model) => model,
+            Err(e) => {
+                warn!("Ignoring {path:?}, it could not be loaded: {e:?}");
+                return Ok(None);
+            }
+        };
+        let cached_fingerprint = model
+            .properties
+            .get(LIR_FINGERPRINT_PROPERTY)
+            .and_then(|t| t.to_scalar::<String>().ok().cloned());
+        if cached_fingerprint != Some(fingerprint()) {
+            debug!("Ignoring {path:?}, it was optimized for different kernels");
+            return Ok(None);
+        }
+        Ok(Some(model))
+    }
+
+    /// Store an optimized model for `key`.
+    pub fn store(&self, key: &str, model: &TypedModel) -> TractResult<()> {
+        let mut model = model.clone();
+        model.properties.insert(LIR_FINGERPRINT_PROPERTY.to_string(), rctensor0(fingerprint()));
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Creating cache directory {:?}", self.dir))?;
+        let path = self.path_for(key);
+        let mut partial = path.clone().into_os_string();
+        partial.push(".partial");
+        let file =
+            std::fs::File::create(&partial).with_context(|| format!("Creating {partial:?}"))?;
+        self.framework.write_to_tar(&model, file)?;
+        std::fs::rename(&partial, &path).with_context(|| format!("Renaming to {path:?}"))?;
+        Ok(())
+    }
+
+    /// Get the optimized model for `key` from the cache, or optimize the model produced by
+    /// `model` and try to store it for the next time.
+    pub fn load_or_optimize(
+        &self,
+        key: &str,
+        model: impl FnOnce() -> TractResult<TypedModel>,
+    ) -> TractResult<TypedModel> {
+        match self.load(key) {
+            Ok(Some(model)) => return Ok(model),
+            Ok(None) => (),
+            Err(e) => warn!("Ignoring cached optimized model for {key}: {e:?}"),
+        }
+        let optimized = model()?.into_optimized()?;
+        if let Err(e) = self.store(key, &optimized) {
+            warn!("Could not cache optimized model for {key}: {e:?}");
+        }
+        Ok(optimized)
+    }
+}
+
+/// 64-bit FNV-1a. Unlike the std hashers, it is stable across toolchains, so cache entries
+/// survive a rebuild of the same tract version.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::einsum::EinSum;
+    use tract_core::ops::math::add;
+    use tract_core::ops::matmul::lir_unary::LirMatMulUnary;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 16]))?;
+        let w = model.add_const("w", Tensor::from_shape(&[16, 8], &[0.25f32; 128])?)?;
+        let mm =
+            model.wire_node("mm", EinSum::new("mk,kn->mn".parse()?, f32::datum_type()), &[x, w])?;
+        let b = model.add_const("b", Tensor::from_shape(&[1, 8], &[1f32; 8])?)?;
+        let y = model.wire_node("bias", add(), &[mm[0], b])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn roundtrip_optimized_model() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let cache = OptimizedModelCache::new(crate::nnef(), dir.path());
+        let optimized = cache.load_or_optimize("mlp", model)?;
+        assert!(cache.path_for("mlp").exists());
+
+        let reloaded = cache.load_or_optimize("mlp", || bail!("Model should be cached"))?;
+        assert!(reloaded.nodes().iter().any(|n| n.op_is::<LirMatMulUnary>()));
+
+        let input = tvec!(Tensor::from_shape(&[2, 16], &[1f32; 32])?.into_tvalue());
+        let expected = optimized.into_runnable()?.run(input.clone())?;
+        let found = reloaded.into_runnable()?.run(input)?;
+        expected[0].close_enough(&found[0], Approximation::Exact)
+    }
+
+    #[test]
+    fn stable_entry_names() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn ignore_other_fingerprint() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let cache = OptimizedModelCache::new(crate::nnef(), dir.path());
+        let mut optimized = model()?.into_optimized()?;
+        cache.store("mlp", &optimized)?;
+        optimized
+            .properties
+            .insert(LIR_FINGERPRINT_PROPERTY.to_string(), rctensor0("other".to_string()));
+        cache.framework.write_to_tar(&optimized, std::fs::File::create(cache.path_for("mlp"))?)?;
+        assert!(cache.load("mlp")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_entry_is_a_miss() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let cache = OptimizedModelCache::new(crate::nnef(), dir.path());
+        std::fs::write(cache.path_for("mlp"), b"not a tar archive")?;
+        assert!(cache.load("mlp")?.is_none());
+        let optimized = cache.load_or_optimize("mlp", model)?;
+        assert!(optimized.nodes().iter().any(|n| n.op_is::<LirMatMulUnary>()));
+        assert!(cache.load("mlp")?.is_some());
+        Ok(())
+    }
+}
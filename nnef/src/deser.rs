@@ -44,6 +44,7 @@ impl<'mb> ModelBuilder<'mb> {
     }
 
     fn translate(&mut self) -> TractResult<()> {
+        let mut assertions = vec![];
         'ext: for ext in &self.proto_model.doc.extension {
             match &*ext[0].0 {
                 "tract_registry" => {
@@ -64,6 +65,12 @@ impl<'mb> ModelBuilder<'mb> {
                     let symbol = self.model.symbol_table.new_with_prefix(&ext[1].0);
                     self.symbols.push(symbol);
                 }
+                "tract_assert" => {
+                    if ext.len() < 2 {
+                        bail!("tract_assert expects an assertion: example: \"extension tract_assert S >= 1;\"")
+                    }
+                    assertions.push(ext[1..].iter().map(|id| &*id.0).join(" "));
+                }
                 _ => {
                     for reg in &self.framework.registries {
                         for reg_ext in &reg.extensions {
@@ -77,6 +84,13 @@ impl<'mb> ModelBuilder<'mb> {
                 }
             };
         }
+        // after the symbols declarations, whatever the extensions order
+        for assertion in assertions {
+            self.model
+                .symbol_table
+                .add_assertion(&assertion)
+                .with_context(|| format!("Parsing assertion {assertion:?}"))?;
+        }
         self.scopes.push(HashMap::new());
         self.wire_body(&self.proto_model.doc.graph_def.body).context("Wiring root graph body")?;
         let vars = self.scopes.pop().unwrap();
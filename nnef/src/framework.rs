@@ -3,6 +3,7 @@ use tract_core::tract_data::itertools::Itertools;
 
 use crate::ast::quant::write_quant_format;
 use crate::ast::{Document, Identifier, ProtoModel, QuantFormat};
+use crate::mmap::MappedFile;
 use crate::{internal::*, nnef};
 use std::io::Read;
 #[cfg(target_family = "unix")]
@@ -19,6 +20,9 @@ pub struct Nnef {
     pub registries: Vec<Registry>,
     pub resource_loaders: Vec<Box<dyn ResourceLoader + 'static>>,
     pub allow_extended_identifier_syntax: bool,
+    /// Map model files in memory when loading from a path, so that tensors borrow their data
+    /// from the mapping instead of copying it.
+    pub mmap_tensors: bool,
 }
 
 impl Default for Nnef {
@@ -33,6 +37,7 @@ impl Default for Nnef {
                 TypedModelLoader::new(false).into_boxed(),
             ],
             allow_extended_identifier_syntax: false,
+            mmap_tensors: false,
         }
     }
 }
@@ -66,10 +71,28 @@ impl Nnef {
         self
     }
 
+    pub fn enable_tract_lir(&mut self) {
+        self.registries.push(crate::ops::tract_lir());
+    }
+
+    pub fn with_tract_lir(mut self) -> Self {
+        self.registries.push(crate::ops::tract_lir());
+        self
+    }
+
     pub fn allow_extended_identifier_syntax(&mut self, allow_extended_identifier_syntax: bool) {
         self.allow_extended_identifier_syntax = allow_extended_identifier_syntax;
     }
 
+    pub fn mmap_tensors(&mut self, mmap_tensors: bool) {
+        self.mmap_tensors = mmap_tensors;
+    }
+
+    pub fn with_mmap_tensors(mut self, mmap_tensors: bool) -> Self {
+        self.mmap_tensors = mmap_tensors;
+        self
+    }
+
     pub fn translate(
         &self,
         proto_model: &ProtoModel,
@@ -235,6 +258,22 @@ impl Nnef {
         }
         Ok(())
     }
+
+    fn proto_model_for_mapped_tar(&self, mapped: &MappedFile) -> TractResult<ProtoModel> {
+        let mut resources: HashMap<String, Arc<dyn Resource>> = Default::default();
+        // walk the whole archive before loading anything: tensors borrowing the mapping forbid
+        // reading it through another path while they live
+        let mut entries = vec![];
+        for entry in tar::Archive::new(mapped.as_bytes()).entries()? {
+            let entry = entry?;
+            let start = entry.raw_file_position() as usize;
+            entries.push((entry.path()?.to_path_buf(), start..start + entry.size() as usize));
+        }
+        for (path, range) in entries {
+            read_mapped(&path, &mapped.slice(range)?, &mut resources, self)?;
+        }
+        proto_model_from_resources(resources)
+    }
 }
 
 impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
@@ -246,6 +285,13 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
     fn proto_model_for_path(&self, path: impl AsRef<Path>) -> TractResult<ProtoModel> {
         let path = path.as_ref();
         if path.is_file() {
+            if self.mmap_tensors {
+                let mapped = MappedFile::open(path)?;
+                // compressed archives can not be mapped, they are streamed as usual
+                if !mapped.as_bytes().starts_with(&[0x1f, 0x8b]) {
+                    return self.proto_model_for_mapped_tar(&mapped);
+                }
+            }
             let mut f = std::fs::File::open(path)?;
             return self.proto_model_for_read(&mut f);
         }
@@ -266,8 +312,13 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
                 .components()
                 .skip(path.components().count())
                 .collect::<std::path::PathBuf>();
-            let mut stream = std::fs::File::open(entry.path())?;
-            read_stream(&subpath, &mut stream, &mut resources, self)?;
+            if self.mmap_tensors {
+                let mapped = MappedFile::open(entry.path())?;
+                read_mapped(&subpath, &mapped, &mut resources, self)?;
+            } else {
+                let mut stream = std::fs::File::open(entry.path())?;
+                read_stream(&subpath, &mut stream, &mut resources, self)?;
+            }
         }
         proto_model_from_resources(resources)
     }
@@ -399,6 +450,26 @@ fn read_stream<R: std::io::Read>(
     reader: &mut R,
     resources: &mut HashMap<String, Arc<dyn Resource>>,
     framework: &Nnef,
+) -> TractResult<()> {
+    read_resource(path, resources, framework, |loader| loader.try_load(path, reader, framework))
+}
+
+fn read_mapped(
+    path: &Path,
+    mapped: &MappedFile,
+    resources: &mut HashMap<String, Arc<dyn Resource>>,
+    framework: &Nnef,
+) -> TractResult<()> {
+    read_resource(path, resources, framework, |loader| {
+        loader.try_load_mapped(path, mapped, framework)
+    })
+}
+
+fn read_resource(
+    path: &Path,
+    resources: &mut HashMap<String, Arc<dyn Resource>>,
+    framework: &Nnef,
+    mut try_load: impl FnMut(&dyn ResourceLoader) -> TractResult<Option<(String, Arc<dyn Resource>)>>,
 ) -> TractResult<()> {
     // ignore path with any component starting with "." (because OSX's tar is weird)
     #[cfg(target_family = "unix")]
@@ -408,7 +479,7 @@ fn read_stream<R: std::io::Read>(
     let mut last_loader_name;
     for loader in framework.resource_loaders.iter() {
         last_loader_name = Some(loader.name());
-        let loaded = loader.try_load(path, reader, framework).with_context(|| {
+        let loaded = try_load(&**loader).with_context(|| {
             anyhow!("Error while loading resource by {:?} at path {:?}", loader.name(), path)
         })?;
         if let Some((id, resource)) = loaded {
@@ -3,8 +3,10 @@
 extern crate log;
 
 pub mod ast;
+pub mod cache;
 pub mod deser;
 pub mod framework;
+pub mod mmap;
 pub mod ops;
 pub mod registry;
 pub mod resource;
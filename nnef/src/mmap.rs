@@ -0,0 +1,133 @@
+//! Memory mapped model files, letting constant tensors borrow their data from the mapping
+//! instead of copying it.
+use std::any::Any;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::internal::*;
+
+/// A region of a file mapped in memory.
+///
+/// The mapping is private and copy-on-write: pages are read from the file when first accessed,
+/// and a tensor modifying its data never writes back to the file. The file must not be
+/// truncated while the mapping (or any tensor borrowing from it) is alive.
+#[derive(Clone)]
+pub struct MappedFile {
+    mmap: Arc<dyn Any + Send + Sync>,
+    data: *mut u8,
+    len: usize,
+}
+
+// data points into the mapping kept alive by mmap
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl std::fmt::Debug for MappedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "MappedFile({:?}, {} bytes)", self.data, self.len)
+    }
+}
+
+impl MappedFile {
+    pub fn open(path: impl AsRef<Path>) -> TractResult<MappedFile> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        let mut mmap = unsafe { memmap2::MmapOptions::new().map_copy(&file) }
+            .with_context(|| format!("Mapping {path:?}"))?;
+        let (data, len) = (mmap.as_mut_ptr(), mmap.len());
+        Ok(MappedFile { mmap: Arc::new(mmap), data, len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.data, self.len) }
+        }
+    }
+
+    /// A sub-region of this one, sharing the same mapping.
+    pub fn slice(&self, range: Range<usize>) -> TractResult<MappedFile> {
+        ensure!(
+            range.start <= range.end && range.end <= self.len,
+            "Range {:?} out of a mapped region of {} bytes",
+            range,
+            self.len
+        );
+        let data = unsafe { self.data.add(range.start) };
+        Ok(MappedFile { mmap: self.mmap.clone(), data, len: range.len() })
+    }
+
+    /// Build a tensor over the whole region, borrowing it if it is suitably aligned.
+    ///
+    /// # Safety
+    ///
+    /// The region must hold valid `dt` items.
+    pub unsafe fn tensor(&self, dt: DatumType, shape: &[usize]) -> TractResult<Tensor> {
+        Tensor::from_raw_parts_owned_by(dt, shape, self.data, self.len, self.mmap.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::konst::Const;
+    use tract_core::ops::math::add;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 3]))?;
+        let w =
+            model.add_const("w", Tensor::from_shape(&[2, 3], &[0.5f32, 1., 2., 3., 4., 5.])?)?;
+        let y = model.wire_node("y", add(), &[x, w])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    fn check(loaded: &TypedModel) -> TractResult<()> {
+        let w = loaded.nodes().iter().find_map(|n| n.op_as::<Const>()).unwrap();
+        assert_eq!(*w.0, Tensor::from_shape(&[2, 3], &[0.5f32, 1., 2., 3., 4., 5.])?);
+        Ok(())
+    }
+
+    #[test]
+    fn tensor_borrows_mapping() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let path = dir.path().join("data");
+        let values = [0.5f32, 1., 2., 3., 4., 5.];
+        std::fs::write(&path, values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>())?;
+        let mapped = MappedFile::open(&path)?;
+        // only keep a raw pointer: the mapping must not be read while the tensor borrows it
+        let data = mapped.as_bytes().as_ptr();
+        let tensor = unsafe { mapped.tensor(f32::datum_type(), &[2, 3])? };
+        assert!(tensor.as_ptr::<f32>()? as *const u8 == data);
+        assert_eq!(tensor, Tensor::from_shape(&[2, 3], &values)?);
+        Ok(())
+    }
+
+    #[test]
+    fn load_mapped_tar() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let path = dir.path().join("model.nnef.tar");
+        let nnef = crate::nnef().with_mmap_tensors(true);
+        nnef.write_to_tar(&model()?, std::fs::File::create(&path)?)?;
+        check(&nnef.model_for_path(&path)?)
+    }
+
+    #[test]
+    fn load_mapped_dir() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let path = dir.path().join("model");
+        let nnef = crate::nnef().with_mmap_tensors(true);
+        nnef.write_to_dir(&model()?, &path)?;
+        check(&nnef.model_for_path(&path)?)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::internal::*;
 use tract_core::ops;
 
+mod block_quant;
 mod broadcast;
 mod cast;
 #[cfg(feature = "complex")]
@@ -11,6 +12,7 @@ mod einsum;
 mod fft;
 mod force_eval;
 mod gather;
+mod kv_cache;
 mod load;
 mod matmul;
 mod one_hot;
@@ -18,8 +20,10 @@ mod qconv;
 mod qmatmul;
 mod range;
 mod reduce;
+mod rms_norm;
 mod scan;
 mod scatter;
+mod sdpa;
 mod shape_of;
 mod softmax;
 mod source;
@@ -32,6 +36,10 @@ pub fn register(registry: &mut Registry) {
     registry.register_unit_element_wise("tract_core_round_even", &ops::math::RoundHalfToEven {});
     registry.register_unit_element_wise("tract_core_erf", &ops::math::Erf {});
     registry.register_unit_element_wise("tract_core_hard_swish", &ops::nn::HardSwish {});
+    registry.register_unit_element_wise("tract_core_gelu", &ops::nn::Gelu {});
+    registry
+        .register_unit_element_wise("tract_core_gelu_approximate", &ops::nn::GeluApproximate {});
+    registry.register_unit_element_wise("tract_core_silu", &ops::nn::Silu {});
 
     registry.register_binary("tract_core_xor", &ops::logic::Xor {});
     registry.register_binary("tract_core_bitand", &ops::logic::BitAnd {});
@@ -41,6 +49,7 @@ pub fn register(registry: &mut Registry) {
 
     registry.register_binary("tract_shl", &ops::math::ShiftLeft);
     registry.register_binary("tract_shr", &ops::math::ShiftRight);
+    block_quant::register(registry);
     broadcast::register(registry);
     cast::register(registry);
     #[cfg(feature = "complex")]
@@ -51,14 +60,17 @@ pub fn register(registry: &mut Registry) {
     fft::register(registry);
     force_eval::register(registry);
     gather::register(registry);
+    kv_cache::register(registry);
     load::register(registry);
     matmul::register(registry);
     one_hot::register(registry);
     qconv::register(registry);
     qmatmul::register(registry);
     reduce::register(registry);
+    rms_norm::register(registry);
     scan::register(registry);
     scatter::register(registry);
+    sdpa::register(registry);
     shape_of::register(registry);
     softmax::register(registry);
     source::register(registry);
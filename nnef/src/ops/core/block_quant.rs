@@ -0,0 +1,32 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::matmul::block_quant::BlockQuantMatMul;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_block_quant_matmul);
+    registry.register_primitive(
+        "tract_core_block_quant_matmul",
+        &[TypeName::Scalar.tensor().named("weights"), TypeName::Scalar.tensor().named("input")],
+        &[("output", TypeName::Scalar.tensor())],
+        de_block_quant_matmul,
+    );
+}
+
+fn ser_block_quant_matmul(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    _op: &BlockQuantMatMul,
+) -> TractResult<Option<Arc<RValue>>> {
+    let weights = ast.mapping[&node.inputs[0]].clone();
+    let input = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation("tract_core_block_quant_matmul", &[weights, input], &[])))
+}
+
+fn de_block_quant_matmul(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let weights = invocation.named_arg_as(builder, "weights")?;
+    let input = invocation.named_arg_as(builder, "input")?;
+    builder.wire(BlockQuantMatMul, &[weights, input])
+}
@@ -0,0 +1,51 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::kv_cache::KvCache;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_kv_cache);
+    registry.register_primitive(
+        "tract_core_kv_cache",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::String.named("id"),
+            TypeName::Integer.named("axis"),
+            TypeName::String.named("past"),
+            TypeName::Integer.named("max_len"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_kv_cache,
+    );
+}
+
+fn ser_kv_cache(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &KvCache,
+) -> TractResult<Option<Arc<RValue>>> {
+    let wire = ast.mapping[&node.inputs[0]].clone();
+    ast.ensure_symbol(&op.past)?;
+    let mut named = vec![
+        ("id", string(&op.id)),
+        ("axis", numeric(op.axis)),
+        ("past", string(op.past.to_string())),
+    ];
+    if let Some(max_len) = &op.max_len {
+        for sym in max_len.symbols() {
+            ast.ensure_symbol(&sym)?;
+        }
+        named.push(("max_len", tdim(max_len)));
+    }
+    Ok(Some(invocation("tract_core_kv_cache", &[wire], &named)))
+}
+
+fn de_kv_cache(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let id: String = invocation.named_arg_as(builder, "id")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let past: String = invocation.named_arg_as(builder, "past")?;
+    let past = builder.model.symbol_table.sym(&past);
+    let max_len: Option<TDim> =
+        builder.allowing_new_symbols(|builder| invocation.get_named_arg_as(builder, "max_len"))?;
+    builder.wire(KvCache { id, axis, past, max_len }, &[input])
+}
@@ -28,8 +28,8 @@ fn qconv_parameters() -> Vec<Parameter> {
         TypeName::Integer.array().named("stride"),
         TypeName::Integer.array().array().named("padding"),
         TypeName::String.spec().named("border"),
-        TypeName::Integer.spec().named("a0"),
-        TypeName::Scalar.spec().named("a_scale"),
+        TypeName::Integer.tensor().named("a0"),
+        TypeName::Scalar.tensor().named("a_scale"),
         TypeName::Integer.spec().named("b0"),
         TypeName::Scalar.spec().named("b_scale"),
         TypeName::Integer.spec().named("c0"),
@@ -42,7 +42,13 @@ fn qconv_unary_dump(
     node: &TypedNode,
     op: &Conv,
 ) -> TractResult<Option<Arc<RValue>>> {
-    if op.q_params.is_none() || node.outputs[0].fact.datum_type.is_quantized() {
+    if op.q_params.is_none() {
+        return Ok(None);
+    }
+    // per-channel kernel quantization can not be expressed by the standard conv in all cases
+    let per_channel =
+        ast.model.node_input_facts(node.id)?[5..7].iter().any(|f| !f.shape.volume().is_one());
+    if node.outputs[0].fact.datum_type.is_quantized() && !per_channel {
         return Ok(None);
     }
     let mut named_args = make_conv_named_args(node, &op.pool_spec, op.group, false, None)?;
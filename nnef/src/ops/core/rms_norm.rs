@@ -0,0 +1,39 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::RmsNorm;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_rms_norm);
+    registry.register_primitive(
+        "tract_core_rms_norm",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.named("axis"),
+            TypeName::Scalar.named("eps"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_rms_norm,
+    );
+}
+
+fn ser_rms_norm(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &RmsNorm,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_rms_norm",
+        &[input],
+        &[("axis", numeric(op.axis)), ("eps", numeric(op.eps.cast_to_scalar::<f32>()?))],
+    )))
+}
+
+fn de_rms_norm(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let eps: f32 = invocation.named_arg_as(builder, "eps")?;
+    let dt = builder.model.outlet_fact(input)?.datum_type;
+    let eps = tensor0(eps).cast_to_dt(dt)?.into_owned().into_arc_tensor();
+    builder.wire(RmsNorm { axis, eps }, &[input])
+}
@@ -0,0 +1,41 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::ScaledDotProductAttention;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_sdpa);
+    registry.register_primitive(
+        "tract_core_sdpa",
+        &[
+            TypeName::Scalar.tensor().array().named("inputs"),
+            TypeName::Scalar.named("scale"),
+            TypeName::Logical.named("causal").default(false),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_sdpa,
+    );
+}
+
+fn ser_sdpa(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ScaledDotProductAttention,
+) -> TractResult<Option<Arc<RValue>>> {
+    let inputs: Vec<_> = node.inputs.iter().map(|i| (*ast.mapping[i]).clone()).collect();
+    let mut named = vec![("causal", logical(op.causal))];
+    if let Some(scale) = op.scale {
+        named.push(("scale", numeric(scale)));
+    }
+    Ok(Some(invocation("tract_core_sdpa", &[Arc::new(RValue::Array(inputs))], &named)))
+}
+
+fn de_sdpa(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let inputs: TVec<OutletId> = invocation.named_arg_as(builder, "inputs")?;
+    ensure!(
+        inputs.len() == 3 || inputs.len() == 4,
+        "tract_core_sdpa expects q, k, v and optional mask"
+    );
+    let scale: Option<f32> = invocation.get_named_arg_as(builder, "scale")?;
+    let causal = invocation.named_arg_as(builder, "causal")?;
+    builder.wire(ScaledDotProductAttention { scale, causal }, &inputs)
+}
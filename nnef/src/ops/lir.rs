@@ -0,0 +1,427 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::binary::MergeOpUnicast;
+use tract_core::ops::change_axes::IntoShape;
+use tract_core::ops::matmul::lir_unary::{
+    AddMatMulGeometry, LirMatMulUnary, MapOutputAxisToInput, ProtoFusedSpec,
+};
+use tract_core::ops::matmul::pack::MatMatMulPack;
+use tract_core::tract_data::itertools::Itertools;
+use tract_core::tract_linalg::mmm::pack::Packer;
+use tract_core::tract_linalg::mmm::{BinOp, MatMatMul, OutputStoreSpec, RoundingPolicy};
+
+/// Identifies the set of matrix multiplication kernels available on the running CPU.
+///
+/// Codegen'd models refer to kernels by name and carry weights packed for them, so a
+/// serialized one can only be reloaded where this fingerprint is identical.
+pub fn fingerprint() -> String {
+    let kernels = tract_core::tract_linalg::ops()
+        .all_mmm_impls()
+        .iter()
+        .map(|mmm| mmm.kernel_name().to_string())
+        .join(",");
+    format!("{}:{}:{}", env!("CARGO_PKG_VERSION"), std::env::consts::ARCH, kernels)
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_lir_matmul);
+    registry.register_primitive(
+        "tract_lir_matmul",
+        &[
+            TypeName::Any.tensor().array().named("inputs"),
+            TypeName::String.named("kernel"),
+            TypeName::String.named("c_dt"),
+            TypeName::Integer.array().named("c_shape"),
+            TypeName::Integer.named("c_m_axis"),
+            TypeName::Integer.named("c_n_axis"),
+            TypeName::String.array().named("micro_ops"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_lir_matmul,
+    );
+    registry.register_dumper(ser_pack);
+    registry.register_primitive(
+        "tract_lir_pack",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::String.named("dt"),
+            TypeName::Integer.named("r"),
+            TypeName::Integer.named("alignment"),
+            TypeName::Integer.named("end_padding_record"),
+            TypeName::Integer.named("k_axis"),
+            TypeName::Integer.named("mn_axis"),
+        ],
+        &[("output", TypeName::Any.tensor())],
+        de_pack,
+    );
+    registry.register_dumper(ser_into_shape);
+    registry.register_primitive(
+        "tract_lir_into_shape",
+        &[
+            TypeName::Any.tensor().named("input"),
+            TypeName::String.named("mapping"),
+            TypeName::Integer.array().named("dims"),
+        ],
+        &[("output", TypeName::Any.tensor())],
+        de_into_shape,
+    );
+    registry.register_dumper(ser_unicast);
+    registry.register_primitive(
+        "tract_lir_unicast",
+        &[
+            TypeName::Scalar.tensor().named("a"),
+            TypeName::Scalar.tensor().named("b"),
+            TypeName::String.named("op"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_unicast,
+    );
+}
+
+fn ser_lir_matmul(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &LirMatMulUnary,
+) -> TractResult<Option<Arc<RValue>>> {
+    let inputs = node.inputs.iter().map(|i| (*ast.mapping[i]).clone()).collect_vec();
+    let micro_ops = op
+        .micro_ops
+        .iter()
+        .map(|uop| Ok(string(dump_micro_op(&*op.mmm, uop)?)))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(Some(invocation(
+        "tract_lir_matmul",
+        &[Arc::new(RValue::Array(inputs))],
+        &[
+            ("kernel", string(op.mmm.kernel_name())),
+            ("c_dt", datum_type(op.c_fact.datum_type)),
+            ("c_shape", tdims(&op.c_fact.shape)),
+            ("c_m_axis", numeric(op.c_m_axis)),
+            ("c_n_axis", numeric(op.c_n_axis)),
+            ("micro_ops", array(micro_ops)),
+        ],
+    )))
+}
+
+fn de_lir_matmul(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let inputs: TVec<OutletId> = invocation.named_arg_as(builder, "inputs")?;
+    let kernel: String = invocation.named_arg_as(builder, "kernel")?;
+    let mmm = tract_core::tract_linalg::ops()
+        .mmm_impl_by_name(&kernel)
+        .with_context(|| format!("Matrix multiplication kernel {kernel} is not available"))?;
+    let c_dt = if let Some(dt) = invocation.dt_from_quant_file.first().copied().flatten() {
+        dt
+    } else {
+        invocation.named_arg_as::<String>(builder, "c_dt")?.parse()?
+    };
+    let c_shape: TVec<TDim> =
+        builder.allowing_new_symbols(|builder| invocation.named_arg_as(builder, "c_shape"))?;
+    let c_m_axis = invocation.named_arg_as(builder, "c_m_axis")?;
+    let c_n_axis = invocation.named_arg_as(builder, "c_n_axis")?;
+    let micro_ops = invocation
+        .named_arg_as::<TVec<String>>(builder, "micro_ops")?
+        .iter()
+        .map(|uop| parse_micro_op(&builder.model.symbol_table, &*mmm, uop))
+        .collect::<TractResult<Vec<_>>>()?;
+    let op = LirMatMulUnary::new(mmm, c_dt.fact(c_shape), c_m_axis, c_n_axis, micro_ops)?;
+    builder.wire(op, &inputs)
+}
+
+fn ser_pack(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &MatMatMulPack,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_lir_pack",
+        &[input],
+        &[
+            ("dt", datum_type(op.packer.dt)),
+            ("r", numeric(op.packer.r)),
+            ("alignment", numeric(op.packer.alignment)),
+            ("end_padding_record", numeric(op.packer.end_padding_record)),
+            ("k_axis", numeric(op.k_axis)),
+            ("mn_axis", numeric(op.mn_axis)),
+        ],
+    )))
+}
+
+fn de_pack(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let dt = invocation.named_arg_as::<String>(builder, "dt")?.parse()?;
+    let packer = Packer::new(
+        dt,
+        invocation.named_arg_as(builder, "r")?,
+        invocation.named_arg_as(builder, "alignment")?,
+        invocation.named_arg_as(builder, "end_padding_record")?,
+    );
+    let k_axis = invocation.named_arg_as(builder, "k_axis")?;
+    let mn_axis = invocation.named_arg_as(builder, "mn_axis")?;
+    builder.wire(MatMatMulPack { packer, k_axis, mn_axis }, &[input])
+}
+
+fn ser_into_shape(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &IntoShape,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_lir_into_shape",
+        &[input],
+        &[("mapping", string(op.mapping.to_string())), ("dims", ints(&op.dims))],
+    )))
+}
+
+fn de_into_shape(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let mapping = invocation.named_arg_as::<String>(builder, "mapping")?.parse::<AxesMapping>()?;
+    let dims: TVec<usize> = invocation.named_arg_as(builder, "dims")?;
+    let op = IntoShape {
+        mapping,
+        len: dims.iter().product(),
+        strides: Tensor::natural_strides(&dims),
+        dims,
+    };
+    builder.wire(op, &[input])
+}
+
+fn ser_unicast(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &MergeOpUnicast,
+) -> TractResult<Option<Arc<RValue>>> {
+    let a = ast.mapping[&node.inputs[0]].clone();
+    let b = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation("tract_lir_unicast", &[a, b], &[("op", string(op.0.name()))])))
+}
+
+fn de_unicast(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let a = invocation.named_arg_as(builder, "a")?;
+    let b = invocation.named_arg_as(builder, "b")?;
+    let name: String = invocation.named_arg_as(builder, "op")?;
+    let mini_op = builder
+        .framework
+        .registries
+        .iter()
+        .flat_map(|registry| registry.binary_ops.iter())
+        .find(|(_, op)| op.name() == name)
+        .map(|(_, op)| op.clone())
+        .with_context(|| format!("No binary operator named {name}"))?;
+    builder.wire(MergeOpUnicast(mini_op), &[a, b])
+}
+
+// Micro ops are serialized as whitespace separated tokens: their kind, then their arguments.
+fn dump_micro_op(mmm: &dyn MatMatMul, uop: &ProtoFusedSpec) -> TractResult<String> {
+    use ProtoFusedSpec::*;
+    Ok(match uop {
+        AddMatMul { geo, a, b, packing } => {
+            ensure!(geo.mmm.kernel_name() == mmm.kernel_name());
+            format!(
+                "matmul {a} {b} {packing} {} {} {}",
+                geo.k,
+                dump_axis_map(&geo.c_to_a_axis_mapping),
+                dump_axis_map(&geo.c_to_b_axis_mapping)
+            )
+        }
+        BinScalar(v, op) => format!("bin_scalar {v} {op:?}"),
+        LeakyRelu(v) => format!("leaky_relu {v}"),
+        BinPerRow(v, op, map) => format!("bin_per_row {v} {op:?} {}", dump_axis_map(map)),
+        BinPerCol(v, op, map) => format!("bin_per_col {v} {op:?} {}", dump_axis_map(map)),
+        AddRowColProducts(row, col) => format!("add_row_col_products {row} {col}"),
+        AddUnicast(store, v, map) => {
+            format!("add_unicast {} {v} {}", dump_store(store), dump_axis_map(map))
+        }
+        Scaler(scaler) => format!(
+            "scaler {} {} {} {:?}",
+            scaler.scale.to_bits(),
+            scaler.mult.map(|m| m.to_string()).unwrap_or_else(|| "-".to_string()),
+            scaler.shift,
+            scaler.policy
+        ),
+        Store(store) => format!("store {}", dump_store(store)),
+    })
+}
+
+fn parse_micro_op(
+    symbols: &SymbolTable,
+    mmm: &dyn MatMatMul,
+    uop: &str,
+) -> TractResult<ProtoFusedSpec> {
+    use ProtoFusedSpec::*;
+    let tokens = uop.split_whitespace().collect_vec();
+    let arg = |ix: usize| -> TractResult<&str> {
+        tokens.get(ix).copied().with_context(|| format!("Missing argument in micro op {uop:?}"))
+    };
+    Ok(match arg(0)? {
+        "matmul" => AddMatMul {
+            a: arg(1)?.parse()?,
+            b: arg(2)?.parse()?,
+            packing: arg(3)?.parse()?,
+            geo: AddMatMulGeometry {
+                k: parse_tdim(symbols, arg(4)?)?,
+                mmm: tract_core::dyn_clone::clone_box(mmm),
+                c_to_a_axis_mapping: parse_axis_map(arg(5)?)?,
+                c_to_b_axis_mapping: parse_axis_map(arg(6)?)?,
+            },
+        },
+        "bin_scalar" => BinScalar(arg(1)?.parse()?, parse_bin_op(arg(2)?)?),
+        "leaky_relu" => LeakyRelu(arg(1)?.parse()?),
+        "bin_per_row" => {
+            BinPerRow(arg(1)?.parse()?, parse_bin_op(arg(2)?)?, parse_axis_map(arg(3)?)?)
+        }
+        "bin_per_col" => {
+            BinPerCol(arg(1)?.parse()?, parse_bin_op(arg(2)?)?, parse_axis_map(arg(3)?)?)
+        }
+        "add_row_col_products" => AddRowColProducts(arg(1)?.parse()?, arg(2)?.parse()?),
+        "add_unicast" => {
+            AddUnicast(parse_store(arg(1)?)?, arg(2)?.parse()?, parse_axis_map(arg(3)?)?)
+        }
+        "scaler" => Scaler(tract_core::tract_linalg::Scaler {
+            scale: f32::from_bits(arg(1)?.parse()?),
+            mult: if arg(2)? == "-" { None } else { Some(arg(2)?.parse()?) },
+            shift: arg(3)?.parse()?,
+            policy: parse_rounding_policy(arg(4)?)?,
+        }),
+        "store" => Store(parse_store(arg(1)?)?),
+        kind => bail!("Unknown micro op {kind:?}"),
+    })
+}
+
+fn dump_axis_map(map: &MapOutputAxisToInput) -> String {
+    if map.0.is_empty() {
+        "-".to_string()
+    } else {
+        map.0.iter().map(|(c, i)| format!("{c}:{i}")).join(",")
+    }
+}
+
+fn parse_axis_map(s: &str) -> TractResult<MapOutputAxisToInput> {
+    if s == "-" {
+        return Ok(MapOutputAxisToInput(tvec!()));
+    }
+    s.split(',')
+        .map(|pair| {
+            let (c, i) =
+                pair.split_once(':').with_context(|| format!("Invalid axis mapping {s:?}"))?;
+            Ok((c.parse()?, i.parse()?))
+        })
+        .collect::<TractResult<TVec<_>>>()
+        .map(MapOutputAxisToInput)
+}
+
+fn dump_store(store: &OutputStoreSpec) -> String {
+    match store {
+        OutputStoreSpec::View { m_axis, n_axis, mr, nr } => {
+            format!("view:{m_axis},{n_axis},{mr},{nr}")
+        }
+        OutputStoreSpec::Strides { row_byte_stride, col_byte_stride, mr, nr } => {
+            format!("strides:{row_byte_stride},{col_byte_stride},{mr},{nr}")
+        }
+    }
+}
+
+fn parse_store(s: &str) -> TractResult<OutputStoreSpec> {
+    let (kind, args) = s.split_once(':').with_context(|| format!("Invalid store {s:?}"))?;
+    let args = args.split(',').map(|a| Ok(a.parse::<isize>()?)).collect::<TractResult<Vec<_>>>()?;
+    ensure!(args.len() == 4, "Invalid store {s:?}");
+    let (mr, nr) = (args[2] as usize, args[3] as usize);
+    Ok(match kind {
+        "view" => {
+            OutputStoreSpec::View { m_axis: args[0] as usize, n_axis: args[1] as usize, mr, nr }
+        }
+        "strides" => {
+            OutputStoreSpec::Strides { row_byte_stride: args[0], col_byte_stride: args[1], mr, nr }
+        }
+        _ => bail!("Invalid store {s:?}"),
+    })
+}
+
+fn parse_bin_op(s: &str) -> TractResult<BinOp> {
+    use BinOp::*;
+    [Min, Max, Add, Mul, Sub, SubF]
+        .into_iter()
+        .find(|op| format!("{op:?}") == s)
+        .with_context(|| format!("Unknown binary operator {s:?}"))
+}
+
+fn parse_rounding_policy(s: &str) -> TractResult<RoundingPolicy> {
+    use RoundingPolicy::*;
+    [Native, Zero, Away, MinusInf, PlusInf, Even, Odd]
+        .into_iter()
+        .find(|policy| format!("{policy:?}") == s)
+        .with_context(|| format!("Unknown rounding policy {s:?}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::einsum::EinSum;
+
+    fn optimized_matmul(n: usize) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([16, n]))?;
+        let w = (0..512).map(|i| (i % 7) as f32 - 3.).collect_vec();
+        let w = model.add_const("w", Tensor::from_shape(&[32, 16], &w)?)?;
+        let op = EinSum::new("mk,kn->mn".parse()?, f32::datum_type());
+        let y = model.wire_node("mm", op, &[w, x])?;
+        model.set_output_outlets(&y)?;
+        model.into_optimized()
+    }
+
+    fn kernel(model: &TypedModel) -> Option<String> {
+        model
+            .nodes()
+            .iter()
+            .find_map(|n| n.op_as::<LirMatMulUnary>())
+            .map(|op| op.mmm.kernel_name().to_string())
+    }
+
+    #[test]
+    fn roundtrip_selected_kernels() -> TractResult<()> {
+        let nnef = crate::nnef().with_tract_core().with_tract_lir();
+        // small n values steer the selectors to their specialized kernels
+        for n in [2, 3, 4, 5, 7, 16] {
+            let optimized = optimized_matmul(n)?;
+            let buffer = nnef.write_to_tar(&optimized, vec![])?;
+            let reloaded = nnef.model_for_read(&mut &*buffer)?;
+            assert!(kernel(&optimized).is_some());
+            assert_eq!(kernel(&optimized), kernel(&reloaded));
+            let input = tvec!(Tensor::from_shape(&[16, n], &vec![1f32; 16 * n])?.into_tvalue());
+            let expected = optimized.into_runnable()?.run(input.clone())?;
+            let found = reloaded.into_runnable()?.run(input)?;
+            expected[0].close_enough(&found[0], Approximation::Exact)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_micro_ops() -> TractResult<()> {
+        let ops = tract_core::tract_linalg::ops();
+        let mmm = ops.mmm(DatumType::F32, DatumType::F32, DatumType::F32, None, None, None).unwrap();
+        let symbols = SymbolTable::default();
+        for uop in [
+            "matmul 0 1 0 16 - 1:0",
+            "bin_scalar 2 Mul",
+            "leaky_relu 1",
+            "bin_per_row 3 Add 0:1,2:0",
+            "bin_per_col 1 SubF -",
+            "add_row_col_products 2 3",
+            "add_unicast view:0,1,4,4 2 -",
+            "scaler 1065353216 - 0 Even",
+            "store strides:16,4,8,8",
+        ] {
+            assert_eq!(dump_micro_op(&*mmm, &parse_micro_op(&symbols, &*mmm, uop)?)?, uop);
+        }
+        assert!(parse_micro_op(&symbols, &*mmm, "transpose 0").is_err());
+        assert!(parse_micro_op(&symbols, &*mmm, "bin_scalar 2").is_err());
+        Ok(())
+    }
+}
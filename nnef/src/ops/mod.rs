@@ -1,6 +1,7 @@
 use crate::internal::*;
 
 pub(super) mod core;
+pub mod lir;
 pub mod nnef;
 pub(super) mod resource;
 
@@ -25,3 +26,14 @@ pub fn tract_resource() -> Registry {
     resource::register(&mut reg);
     reg
 }
+
+pub fn tract_lir() -> Registry {
+    let mut reg = Registry::new("tract_lir")
+        .with_doc("Extension `tract_lir` exposes NNEF fragments for operators produced by")
+        .with_doc("tract-core codegen. They refer to matrix multiplication kernels by name and")
+        .with_doc("carry pre-packed weights, so they only load on a CPU with the same kernels.")
+        .with_doc("")
+        .with_doc("Add `extension tract_lir` to `graph.nnef`");
+    lir::register(&mut reg);
+    reg
+}
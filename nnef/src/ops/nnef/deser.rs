@@ -74,7 +74,17 @@ pub fn variable(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) ->
             shape
         );
     }
-    builder.wire(tract_core::ops::konst::Const::new(tensor), &[])
+    let per_axis_q = builder
+        .naming_scopes
+        .last()
+        .and_then(|id| builder.proto_model.quantization.as_ref()?.get(id)?.per_axis_q())
+        .cloned();
+    let konst = builder.wire_as_outlets(tract_core::ops::konst::Const::new(tensor), &[])?[0];
+    if let Some(q) = per_axis_q {
+        let fact = builder.model.outlet_fact(konst)?.clone().with_per_axis_q(q);
+        builder.model.set_outlet_fact(konst, fact)?;
+    }
+    Ok(Value::from(tvec!(konst)))
 }
 
 // fragment reshape<?>( input: tensor<?>, shape: integer[], axis_start: integer = 0, axis_count: integer = -1 )
@@ -378,10 +388,17 @@ pub fn conv_or_deconv(
         Box::new(Deconv::new(pool_spec, KernelFormat::OIHW, adjustments, group))
     } else {
         if let Some(odt) = &output_dt {
-            for dt in &[&input_fact.datum_type, &kernel_fact.datum_type, odt] {
-                let qp = dt.qparams().unwrap_or_default();
-                inputs.push(builder.add_const(tensor0(qp.zp_scale().0))?);
-                inputs.push(builder.add_const(tensor0(qp.zp_scale().1))?);
+            let dts = [input_fact.datum_type, kernel_fact.datum_type, *odt];
+            for (ix, dt) in dts.iter().enumerate() {
+                if let Some(q) = kernel_fact.per_axis_q.as_ref().filter(|_| ix == 1) {
+                    ensure!(q.axis == 0, "Per-axis kernel quantization must be on output channels");
+                    inputs.push(builder.add_const(q.zero_points_tensor())?);
+                    inputs.push(builder.add_const(q.scales_tensor())?);
+                } else {
+                    let qp = dt.qparams().unwrap_or_default();
+                    inputs.push(builder.add_const(tensor0(qp.zp_scale().0))?);
+                    inputs.push(builder.add_const(tensor0(qp.zp_scale().1))?);
+                }
             }
         }
         Box::new(Conv::new(pool_spec, KernelFormat::OIHW, group, output_dt))
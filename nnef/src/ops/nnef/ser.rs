@@ -54,7 +54,11 @@ pub fn konst(
     node: &TypedNode,
     op: &ops::konst::Const,
 ) -> TractResult<Option<Arc<RValue>>> {
-    Ok(Some(ast.konst(&node.name, &op.0)?))
+    let value = ast.konst(&node.name, &op.0)?;
+    if let (Some(q), RValue::Identifier(id)) = (&node.outputs[0].fact.per_axis_q, &*value) {
+        ast.quantization.insert(id.clone(), QuantFormat::from_per_axis(op.0.datum_type(), q));
+    }
+    Ok(Some(value))
 }
 
 pub fn concat(
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use crate::ast::{Document, QuantFormat};
 use crate::internal::*;
+use crate::mmap::MappedFile;
 use tract_core::downcast_rs::{impl_downcast, DowncastSync};
 
 pub const GRAPH_NNEF_FILENAME: &str = "graph.nnef";
@@ -36,6 +37,17 @@ pub trait ResourceLoader: Send + Sync {
         framework: &Nnef,
     ) -> TractResult<Option<(String, Arc<dyn Resource>)>>;
 
+    /// Try to load a resource from a mapped file. Loaders able to borrow from the mapping
+    /// override this, the default reads the mapped bytes through `try_load`.
+    fn try_load_mapped(
+        &self,
+        path: &Path,
+        mapped: &MappedFile,
+        framework: &Nnef,
+    ) -> TractResult<Option<(String, Arc<dyn Resource>)>> {
+        self.try_load(path, &mut mapped.as_bytes(), framework)
+    }
+
     fn into_boxed(self) -> Box<dyn ResourceLoader>
     where
         Self: Sized + 'static,
@@ -95,6 +107,21 @@ impl ResourceLoader for DatLoader {
             Ok(None)
         }
     }
+
+    fn try_load_mapped(
+        &self,
+        path: &Path,
+        mapped: &MappedFile,
+        _framework: &Nnef,
+    ) -> TractResult<Option<(String, Arc<dyn Resource>)>> {
+        if path.extension().map(|e| e == "dat").unwrap_or(false) {
+            let tensor = crate::tensors::read_tensor_mapped(mapped)
+                .with_context(|| format!("Error while reading tensor {path:?}"))?;
+            Ok(Some((resource_path_to_id(path)?, Arc::new(tensor))))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl Resource for HashMap<String, QuantFormat> {}
@@ -203,6 +203,11 @@ impl<'a> IntoAst<'a> {
         for sym in self.symbols {
             extension.push(vec!["tract_symbol".into(), Identifier(sym.to_string())]);
         }
+        for assertion in self.model.symbol_table.all_assertions() {
+            let mut ext = vec!["tract_assert".into()];
+            ext.extend(assertion.to_string().split_whitespace().map(Identifier::from));
+            extension.push(ext);
+        }
         let properties = FragmentDef {
             decl: FragmentDecl {
                 id: Identifier("tract_core_properties".to_string()),
@@ -370,6 +375,10 @@ impl<'a> IntoAst<'a> {
                 let array =
                     Self::dump_rec_tensor(&tensor.to_array_view::<f16>()?, |f| numeric(f)).into();
                 return Ok(invocation("tract_core_cast", &[array], &[("to", string("f16"))]));
+            } else if have_tract_core && tensor.datum_type() == DatumType::BF16 {
+                let array =
+                    Self::dump_rec_tensor(&tensor.to_array_view::<bf16>()?, |f| numeric(f)).into();
+                return Ok(invocation("tract_core_cast", &[array], &[("to", string("bf16"))]));
             } else if have_tract_core && tensor.datum_type().is_integer() {
                 if let Ok(value) = tensor.cast_to::<i64>() {
                     let value =
@@ -447,6 +456,20 @@ pub fn tdim(dim: &TDim) -> RValue {
         TDim::MulInt(x, y) => RValue::Binary(numeric(x).boxed(), "*".to_string(), tdim(y).boxed()),
         TDim::Div(x, y) => RValue::Binary(tdim(x).boxed(), "/".to_string(), numeric(y).boxed()),
         TDim::Broadcast(_) => todo!(),
+        TDim::Min(terms) => terms
+            .iter()
+            .map(|t| Arc::new(tdim(t)))
+            .reduce(|x, y| invocation("min", &[x, y], &[]))
+            .unwrap()
+            .as_ref()
+            .clone(),
+        TDim::Max(terms) => terms
+            .iter()
+            .map(|t| Arc::new(tdim(t)))
+            .reduce(|x, y| invocation("max", &[x, y], &[]))
+            .unwrap()
+            .as_ref()
+            .clone(),
     }
 }
 
@@ -507,3 +530,31 @@ pub fn invocation(
     RValue::Invocation(Invocation { id: id.as_ref().into(), generic_type_name: None, arguments })
         .into()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip_assertions() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        model.symbol_table.add_assertion("S >= 1")?;
+        model.symbol_table.add_assertion("S <= 4096")?;
+        let x = model.add_source("x", f32::fact([s]))?;
+        model.set_output_outlets(&[x])?;
+        let nnef = crate::nnef().with_tract_core();
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        // extensions are dumped sorted
+        let assertions = reloaded
+            .symbol_table
+            .all_assertions()
+            .iter()
+            .map(|a| a.to_string())
+            .sorted()
+            .collect_vec();
+        assert_eq!(assertions, ["S <= 4096", "S >= 1"]);
+        Ok(())
+    }
+}
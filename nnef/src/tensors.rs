@@ -1,8 +1,41 @@
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use tract_core::internal::*;
+use tract_core::tract_linalg::block_quant::{BlockQuant, BlockQuantValue, Q4_0, Q8_0};
+use tract_core::tract_linalg::mmm::{EagerPackedInput, MMMInput};
+
+use crate::mmap::MappedFile;
 
 const TRACT_ITEM_TYPE_VENDOR: u16 = (b'T' as u16) << 8u16 | b'R' as u16;
 
+// block quantized weights are stored as their raw blocks, the shape being the logical [m, k]
+const Q4_0_ITEM_TYPE: u16 = 0x2040;
+const Q8_0_ITEM_TYPE: u16 = 0x2080;
+
+// bfloat16 has no item type in the NNEF spec, it goes under the tract vendor
+const BF16_ITEM_TYPE: u16 = 0x0100;
+
+// pre-packed matmul operands are stored as [mn, k, r, panel_bytes, alignment], followed by the
+// packed buffer serialized as a regular nested tensor
+const EAGER_PACKED_ITEM_TYPE: u16 = 0x3000;
+
+fn block_quant_item_type(format: &dyn BlockQuant) -> Option<u16> {
+    if format.is::<Q4_0>() {
+        Some(Q4_0_ITEM_TYPE)
+    } else if format.is::<Q8_0>() {
+        Some(Q8_0_ITEM_TYPE)
+    } else {
+        None
+    }
+}
+
+fn block_quant_format(item_type: u16) -> Option<Box<dyn BlockQuant>> {
+    match item_type {
+        Q4_0_ITEM_TYPE => Some(Box::new(Q4_0)),
+        Q8_0_ITEM_TYPE => Some(Box::new(Q8_0)),
+        _ => None,
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 struct Header {
@@ -19,7 +52,20 @@ struct Header {
     padding: [u32; 11],
 }
 
-pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
+pub fn read_tensor<R: std::io::Read>(reader: R) -> TractResult<Tensor> {
+    read_tensor_from(reader, None)
+}
+
+/// Read a tensor from a mapped .dat file. Plain numeric tensors borrow their data from the
+/// mapping instead of copying it.
+pub fn read_tensor_mapped(mapped: &MappedFile) -> TractResult<Tensor> {
+    read_tensor_from(mapped.as_bytes(), Some(mapped))
+}
+
+fn read_tensor_from<R: std::io::Read>(
+    mut reader: R,
+    mapped: Option<&MappedFile>,
+) -> TractResult<Tensor> {
     unsafe {
         let mut header: Header = std::mem::zeroed();
         let buffer: &mut [u8; 128] = std::mem::transmute(&mut header);
@@ -37,6 +83,35 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             header.dims[0..header.rank as usize].iter().map(|d| *d as _).collect();
         let len = shape.iter().product::<usize>();
 
+        if header.item_type_vendor == TRACT_ITEM_TYPE_VENDOR {
+            if let Some(format) = block_quant_format(header.item_type) {
+                ensure!(header.rank == 2, "Block quantized tensors must be [m, k] matrices");
+                let mut data = vec![0u8; header.data_size_bytes as usize];
+                reader.read_exact(&mut data)?;
+                let value =
+                    BlockQuantValue::new(format, shape[0], shape[1], Blob::from_bytes(&data)?)?;
+                return Ok(tensor0(Opaque(Arc::new(value))));
+            }
+            if header.item_type == EAGER_PACKED_ITEM_TYPE {
+                ensure!(header.rank == 5, "Packed tensors must be described by five dimensions");
+                let [mn, k, r, panel_bytes, alignment] = [0, 1, 2, 3, 4].map(|ix| shape[ix]);
+                let mut data = vec![0u8; header.data_size_bytes as usize];
+                reader.read_exact(&mut data)?;
+                let mut packed = read_tensor(data.as_slice())?;
+                if packed.alignment() != alignment {
+                    packed = Tensor::from_raw_dt_align(
+                        packed.datum_type(),
+                        packed.shape(),
+                        packed.as_bytes(),
+                        alignment,
+                    )?;
+                }
+                let input: Box<dyn MMMInput> =
+                    Box::new(EagerPackedInput { packed, panel_bytes, mn, k, r });
+                return Ok(tensor0(Opaque::from(input)));
+            }
+        }
+
         if header.item_type == 5 {
             let expected_bit_size = len * header.bits_per_item as usize;
             let real_bit_size = header.data_size_bytes as usize * 8;
@@ -49,6 +124,7 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
                 );
             }
         } else if header.bits_per_item != 0xFFFFFFFF
+            && header.bits_per_item != 0xFFFF
             && len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize
         {
             bail!(
@@ -101,6 +177,7 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             // 5 - 0b0101 - bool values, 1 bit or 8 bits (0 means false, non-zero means true)
             (0, 5, 1 | 8) => DatumType::Bool,
             (TRACT_ITEM_TYPE_VENDOR, 0x1000, 0xFFFF) => DatumType::String,
+            (TRACT_ITEM_TYPE_VENDOR, BF16_ITEM_TYPE, 16) => DatumType::BF16,
             #[cfg(feature = "complex")]
             (TRACT_ITEM_TYPE_VENDOR, 0, 32) => DatumType::ComplexF16,
             #[cfg(feature = "complex")]
@@ -119,6 +196,10 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
                 header.bits_per_item
             ),
         };
+        if let Some(mapped) = mapped.filter(|_| dt.is_copy() && dt != DatumType::Bool) {
+            let data = mapped.slice(128..128 + header.data_size_bytes as usize)?;
+            return data.tensor(dt, &shape);
+        }
         if dt.is_copy() {
             let mut tensor = Tensor::uninitialized_dt(dt, &shape)?;
             if dt == DatumType::Bool && header.bits_per_item == 1 {
@@ -137,8 +218,8 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             }
             Ok(tensor)
         } else if dt == DatumType::String {
-            let mut tensor = Tensor::zero_dt(dt, &shape)?;
-            for item in tensor.as_slice_mut_unchecked::<String>() {
+            let mut tensor = tract_ndarray::ArrayD::<String>::default(&*shape);
+            for item in tensor.iter_mut() {
                 let len: u32 = reader.read_u32::<LE>()?;
                 let mut bytes = Vec::with_capacity(len as usize);
                 #[allow(clippy::uninit_vec)]
@@ -146,13 +227,18 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
                 reader.read_exact(&mut bytes)?;
                 *item = String::from_utf8(bytes)?;
             }
-            Ok(tensor)
+            Ok(tensor.into_tensor())
         } else {
             todo!()
         }
     }
 }
 
+/// Header fields are 32 bits wide: refuse to silently truncate bigger sizes.
+fn header_u32(value: usize) -> TractResult<u32> {
+    u32::try_from(value).with_context(|| format!("{value} does not fit in a NNEF tensor header"))
+}
+
 pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResult<()> {
     unsafe {
         ensure!(tensor.datum_type() != TDim::datum_type());
@@ -160,18 +246,58 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
         header.magic = [0x4e, 0xef];
         header.version_maj = 1;
         header.version_min = 0;
+        if let Some(bqv) =
+            tensor.to_scalar::<Opaque>().ok().and_then(|o| o.downcast_ref::<BlockQuantValue>())
+        {
+            header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
+            header.item_type = block_quant_item_type(&*bqv.format)
+                .with_context(|| format!("Don't know how to serialize {}", bqv.format))?;
+            header.bits_per_item = 0xFFFFFFFF;
+            header.rank = 2;
+            header.dims[0] = header_u32(bqv.m)?;
+            header.dims[1] = header_u32(bqv.k)?;
+            header.data_size_bytes = header_u32(bqv.value.len())?;
+            let header_buf: &[u8; 128] = std::mem::transmute(&header);
+            w.write_all(header_buf)?;
+            w.write_all(&bqv.value)?;
+            return Ok(());
+        }
+        if let Some(epi) = tensor
+            .to_scalar::<Opaque>()
+            .ok()
+            .and_then(|o| o.downcast_ref::<Box<dyn MMMInput>>())
+            .and_then(|input| (**input).downcast_ref::<EagerPackedInput>())
+        {
+            let mut data = vec![];
+            write_tensor(&mut data, &epi.packed)?;
+            header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
+            header.item_type = EAGER_PACKED_ITEM_TYPE;
+            header.bits_per_item = 0xFFFFFFFF;
+            header.rank = 5;
+            for (ix, d) in
+                [epi.mn, epi.k, epi.r, epi.panel_bytes, epi.packed.alignment()].iter().enumerate()
+            {
+                header.dims[ix] = header_u32(*d)?;
+            }
+            header.data_size_bytes = header_u32(data.len())?;
+            let header_buf: &[u8; 128] = std::mem::transmute(&header);
+            w.write_all(header_buf)?;
+            w.write_all(&data)?;
+            return Ok(());
+        }
         if tensor.rank() > 8 {
             bail!("Only rank up to 8 are supported");
         }
         header.rank = tensor.rank() as u32;
         for d in 0..tensor.rank() {
-            header.dims[d] = tensor.shape()[d] as u32;
+            header.dims[d] = header_u32(tensor.shape()[d])?;
         }
-        header.data_size_bytes = (tensor.len() * tensor.datum_type().size_of()) as u32;
+        header.data_size_bytes = header_u32(tensor.len() * tensor.datum_type().size_of())?;
         header.bits_per_item = (tensor.datum_type().size_of() * 8) as u32;
 
         let (itv, it) = match tensor.datum_type() {
             DatumType::F16 | DatumType::F32 | DatumType::F64 => (0, 0),
+            DatumType::BF16 => (TRACT_ITEM_TYPE_VENDOR, BF16_ITEM_TYPE),
             DatumType::U8
             | DatumType::U16
             | DatumType::U32
@@ -185,6 +311,9 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
             | DatumType::QI32(_) => (0, 3),
             DatumType::String => {
                 header.bits_per_item = 0xFFFF;
+                header.data_size_bytes = header_u32(
+                    tensor.as_slice_unchecked::<String>().iter().map(|s| 4 + s.len()).sum(),
+                )?;
                 (TRACT_ITEM_TYPE_VENDOR, 0x1000)
             }
             #[cfg(feature = "complex")]
@@ -225,6 +354,78 @@ mod test {
         assert_eq!(std::mem::size_of::<Header>(), 128);
     }
 
+    #[test]
+    fn serde_block_quant() -> TractResult<()> {
+        let weights = Tensor::from_shape(&[2, 32], &(0..64).map(|x| x as f32).collect::<Vec<_>>())?;
+        let value = BlockQuantValue::quant_f32(Box::new(Q4_0), &weights)?;
+        let t = tensor0(Opaque(Arc::new(value.clone())));
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        let serde_tensor = read_tensor(buffer.as_slice())?;
+        let serde_value =
+            serde_tensor.to_scalar::<Opaque>()?.downcast_ref::<BlockQuantValue>().unwrap();
+        assert_eq!(serde_value.m, 2);
+        assert_eq!(serde_value.k, 32);
+        assert!(serde_value.format.is::<Q4_0>());
+        assert_eq!(serde_value.value.as_bytes(), value.value.as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn serde_tensor_string() -> TractResult<()> {
+        let t = tensor2(&[["hello", ""], ["wörld", "!"]].map(|row| row.map(String::from)));
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        assert_eq!(buffer.len(), 128 + 4 * 4 + 5 + 6 + 1);
+        assert_eq!(t, read_tensor(buffer.as_slice())?);
+        Ok(())
+    }
+
+    #[test]
+    fn serde_eager_packed() -> TractResult<()> {
+        use tract_core::tract_linalg::mmm::pack::Packer;
+        let weights = Tensor::from_shape(&[6, 5], &(0..30).map(|x| x as f32).collect::<Vec<_>>())?;
+        let packer = Packer::new(f32::datum_type(), 4, 64, 0);
+        let t = tensor0(Opaque::from(packer.pack_tensor(&weights, 1, 0)?));
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        let serde_tensor = read_tensor(buffer.as_slice())?;
+        let unpack = |t: &Tensor| -> TractResult<EagerPackedInput> {
+            let input = t.to_scalar::<Opaque>()?.downcast_ref::<Box<dyn MMMInput>>().unwrap();
+            Ok((**input).downcast_ref::<EagerPackedInput>().unwrap().clone())
+        };
+        let (original, serde) = (unpack(&t)?, unpack(&serde_tensor)?);
+        assert_eq!((serde.mn, serde.k, serde.r), (6, 5, 4));
+        assert_eq!(serde.panel_bytes, original.panel_bytes);
+        assert_eq!(serde.packed.alignment(), 64);
+        // compared bitwise: the padding past mn is left uninitialized, NaN in debug builds
+        assert_eq!(serde.packed.as_bytes(), original.packed.as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn read_mapped_tensor() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let path = dir.path().join("w.dat");
+        let t = tensor2(&[[1.0f32, 2.5, -3.0], [0.125, 1e3, -7.5]]);
+        write_tensor(&mut std::fs::File::create(&path)?, &t)?;
+        let mapped = MappedFile::open(&path)?;
+        let serde_tensor = read_tensor_mapped(&mapped)?;
+        assert_eq!(serde_tensor.as_bytes().as_ptr(), mapped.as_bytes()[128..].as_ptr());
+        assert_eq!(t, serde_tensor);
+        Ok(())
+    }
+
+    #[test]
+    fn serde_tensor_bf16() -> TractResult<()> {
+        let t = tensor2(&[[1.0f32, 2.5, -3.0], [0.125, 1e3, -7.5]]).cast_to::<bf16>()?.into_owned();
+        let mut buffer = Vec::<u8>::new();
+        write_tensor(&mut buffer, &t)?;
+        let serde_tensor = read_tensor(buffer.as_slice())?;
+        assert_eq!(t, serde_tensor);
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "complex")]
     fn serde_tensor_complex_f32() -> TractResult<()> {
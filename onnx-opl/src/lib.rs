@@ -8,7 +8,11 @@ pub mod lrn;
 pub mod ml;
 pub mod multinomial;
 pub mod non_max_suppression;
+pub mod optional;
 pub mod random;
+pub mod sequence;
+pub mod text;
+pub mod vision;
 
 pub trait WithOnnx {
     fn with_onnx(self) -> Self;
@@ -32,6 +36,8 @@ fn onnx_opl_registry() -> Registry {
     non_max_suppression::register(&mut registry);
     multinomial::register(&mut registry);
     random::register(&mut registry);
+    text::register(&mut registry);
+    vision::register(&mut registry);
     registry.register_element_wise(
         "tract_onnx_isinf",
         TypeId::of::<is_inf::IsInf>(),
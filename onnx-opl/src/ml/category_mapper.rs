@@ -56,7 +56,7 @@ impl EvalOp for DirectLookup {
 
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let input = args_1!(inputs);
-        let output = dispatch_hash!(Self::eval_t(self.values.datum_type())(self, &input))?;
+        let output = dispatch_datum!(Self::eval_t(self.values.datum_type())(self, &input))?;
         Ok(tvec!(output.into_tvalue()))
     }
 }
@@ -98,12 +98,25 @@ impl TypedOp for DirectLookup {
 pub struct ReverseLookup {
     keys: Arc<Tensor>,
     index: HashMap<u64, SmallVec<[i32; 1]>>,
-    fallback_value: i32,
+    /// index of unknown keys, or None to fail on them
+    fallback_value: Option<i32>,
 }
 
 #[allow(clippy::manual_hash_one)]
 impl ReverseLookup {
     pub fn new(keys: Arc<Tensor>, fallback_value: i32) -> TractResult<ReverseLookup> {
+        Self::new_with_fallback(keys, Some(fallback_value))
+    }
+
+    /// A lookup that fails on keys it does not know.
+    pub fn strict(keys: Arc<Tensor>) -> TractResult<ReverseLookup> {
+        Self::new_with_fallback(keys, None)
+    }
+
+    fn new_with_fallback(
+        keys: Arc<Tensor>,
+        fallback_value: Option<i32>,
+    ) -> TractResult<ReverseLookup> {
         unsafe fn new_t<T: Datum + Hash>(keys: &Tensor) -> HashMap<u64, SmallVec<[i32; 1]>> {
             let keys = keys.as_slice_unchecked::<T>();
             let mut hashmap = HashMap::<u64, SmallVec<[i32; 1]>>::default();
@@ -140,7 +153,10 @@ impl ReverseLookup {
             for (i, o) in
                 input.as_slice::<T>()?.iter().zip(output.as_slice_mut_unchecked::<i32>().iter_mut())
             {
-                *o = self.search_t(i).unwrap_or(self.fallback_value);
+                *o = match self.search_t(i) {
+                    Some(ix) => ix,
+                    None => self.fallback_value.with_context(|| format!("Unknown key {i:?}"))?,
+                };
             }
             Ok(output)
         }
@@ -214,6 +230,7 @@ fn parameters_reverse_lookup() -> Vec<Parameter> {
         TypeName::Scalar.tensor().named("input"),
         TypeName::Scalar.tensor().named("keys"),
         TypeName::Scalar.named("fallback"),
+        TypeName::Logical.named("strict").default(false),
     ]
 }
 
@@ -235,11 +252,11 @@ fn dump_reverse_lookup(
 ) -> TractResult<Option<Arc<RValue>>> {
     let input = ast.mapping[&node.inputs[0]].clone();
     let values = ast.konst_variable(format!("{}.keys", node.name), &op.keys)?;
-    Ok(Some(invocation(
-        "tract_onnx_ml_reverse_lookup",
-        &[input, values],
-        &[("fallback", numeric(op.fallback_value))],
-    )))
+    let mut attributes = vec![("fallback", numeric(op.fallback_value.unwrap_or(-1)))];
+    if op.fallback_value.is_none() {
+        attributes.push(("strict", logical(true)));
+    }
+    Ok(Some(invocation("tract_onnx_ml_reverse_lookup", &[input, values], &attributes)))
 }
 
 fn load_direct_lookup(
@@ -258,8 +275,30 @@ fn load_reverse_lookup(
     invocation: &ResolvedInvocation,
 ) -> TractResult<Value> {
     let input = invocation.named_arg_as(builder, "input")?;
-    let keys: isize = invocation.named_arg_as(builder, "keys")?;
-    let fallback_value = invocation.named_arg_as(builder, "fallback")?;
-    let op = ReverseLookup::new(fallback_value, keys as i32)?;
+    let keys: Arc<Tensor> = invocation.named_arg_as(builder, "keys")?;
+    let fallback_value: isize = invocation.named_arg_as(builder, "fallback")?;
+    let strict: bool = invocation.named_arg_as(builder, "strict")?;
+    let op = if strict {
+        ReverseLookup::strict(keys)?
+    } else {
+        ReverseLookup::new(keys, fallback_value as i32)?
+    };
     builder.wire(op, &[input])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_lookup_fallback() -> TractResult<()> {
+        let keys = rctensor1(&["a".to_string(), "b".to_string()]);
+        let input = tensor1(&["b".to_string(), "c".to_string()]);
+        let op = ReverseLookup::new(keys.clone(), -1)?;
+        let output = op.eval(tvec!(input.clone().into_tvalue()))?;
+        assert_eq!(*output[0], tensor1(&[1i32, -1]));
+        let op = ReverseLookup::strict(keys)?;
+        assert!(op.eval(tvec!(input.into_tvalue())).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,501 @@
+use tract_ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis, Ix1, Ix2};
+use tract_nnef::internal::*;
+use tract_nnef::ser::ints;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_ml_svm_classifier",
+        &parameters_classifier(),
+        &[("label", TypeName::Integer.tensor()), ("scores", TypeName::Scalar.tensor())],
+        load_classifier,
+    );
+    registry.register_primitive(
+        "tract_onnx_ml_svm_regressor",
+        &parameters_regressor(),
+        &[("output", TypeName::Scalar.tensor())],
+        load_regressor,
+    );
+    registry.register_dumper(dump_classifier);
+    registry.register_dumper(dump_regressor);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SvmKernelType {
+    #[default]
+    Linear,
+    Poly,
+    Rbf,
+    Sigmoid,
+}
+
+impl SvmKernelType {
+    pub fn parse(s: &str) -> TractResult<SvmKernelType> {
+        match s {
+            "LINEAR" => Ok(SvmKernelType::Linear),
+            "POLY" => Ok(SvmKernelType::Poly),
+            "RBF" => Ok(SvmKernelType::Rbf),
+            "SIGMOID" => Ok(SvmKernelType::Sigmoid),
+            _ => bail!("Invalid SVM kernel type: {}", s),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SvmKernelType::Linear => "LINEAR",
+            SvmKernelType::Poly => "POLY",
+            SvmKernelType::Rbf => "RBF",
+            SvmKernelType::Sigmoid => "SIGMOID",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SvmKernel {
+    pub kernel_type: SvmKernelType,
+    pub gamma: f32,
+    pub coef0: f32,
+    pub degree: f32,
+}
+
+impl SvmKernel {
+    pub fn eval(&self, a: ArrayView1<f32>, b: ArrayView1<f32>) -> f32 {
+        match self.kernel_type {
+            SvmKernelType::Linear => a.dot(&b),
+            SvmKernelType::Poly => (self.gamma * a.dot(&b) + self.coef0).powf(self.degree),
+            SvmKernelType::Rbf => {
+                let d2 = a.iter().zip(b.iter()).map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+                (-self.gamma * d2).exp()
+            }
+            SvmKernelType::Sigmoid => (self.gamma * a.dot(&b) + self.coef0).tanh(),
+        }
+    }
+}
+
+fn argmax(values: impl IntoIterator<Item = f32>) -> usize {
+    let mut best = (0, f32::NEG_INFINITY);
+    for (ix, v) in values.into_iter().enumerate() {
+        if v > best.1 {
+            best = (ix, v);
+        }
+    }
+    best.0
+}
+
+// pairwise coupling of one-vs-one probabilities (second method of Wu, Lin and Weng, as in libsvm)
+fn multiclass_probability(k: usize, r: &[f32]) -> Vec<f32> {
+    let max_iter = 100.max(k);
+    let eps = 0.005 / k as f32;
+    let mut p = vec![1.0 / k as f32; k];
+    let mut q = vec![0f32; k * k];
+    let mut qp = vec![0f32; k];
+    for t in 0..k {
+        for j in 0..t {
+            q[t * k + t] += r[j * k + t] * r[j * k + t];
+            q[t * k + j] = q[j * k + t];
+        }
+        for j in t + 1..k {
+            q[t * k + t] += r[j * k + t] * r[j * k + t];
+            q[t * k + j] = -r[j * k + t] * r[t * k + j];
+        }
+    }
+    for _ in 0..max_iter {
+        let mut pqp = 0.0;
+        for t in 0..k {
+            qp[t] = (0..k).map(|j| q[t * k + j] * p[j]).sum();
+            pqp += p[t] * qp[t];
+        }
+        if qp.iter().all(|qp| (qp - pqp).abs() < eps) {
+            break;
+        }
+        for t in 0..k {
+            let diff = (-qp[t] + pqp) / q[t * k + t];
+            p[t] += diff;
+            pqp = (pqp + diff * (diff * q[t * k + t] + 2.0 * qp[t])) / (1.0 + diff) / (1.0 + diff);
+            for j in 0..k {
+                qp[j] = (qp[j] + diff * q[t * k + j]) / (1.0 + diff);
+                p[j] /= 1.0 + diff;
+            }
+        }
+    }
+    p
+}
+
+/// Support vector classifier.
+///
+/// Without support vectors, `coefficients` holds one row of weights per class (linear mode).
+/// Otherwise, `support_vectors` are grouped by class according to `vectors_per_class`,
+/// `coefficients` is the [n_classes - 1, n_vectors] one-vs-one dual coefficients matrix and `rho`
+/// holds one intercept per pair of classes.
+///
+/// Outputs the index of the winning class and the scores: one decision value per pair of classes
+/// in one-vs-one mode, unless Platt scaling coefficients are provided, in which case the scores
+/// are the class probabilities.
+#[derive(Debug, Clone)]
+pub struct SvmClassifier {
+    pub kernel: SvmKernel,
+    pub n_classes: usize,
+    pub support_vectors: Arc<Tensor>,
+    pub vectors_per_class: TVec<usize>,
+    pub coefficients: Arc<Tensor>,
+    pub rho: Arc<Tensor>,
+    pub prob_a: Option<Arc<Tensor>>,
+    pub prob_b: Option<Arc<Tensor>>,
+}
+
+impl SvmClassifier {
+    fn one_vs_one(&self) -> bool {
+        !self.vectors_per_class.is_empty()
+    }
+
+    pub fn n_scores(&self) -> usize {
+        if self.one_vs_one() && self.prob_a.is_none() {
+            self.n_classes * (self.n_classes - 1) / 2
+        } else {
+            self.n_classes
+        }
+    }
+
+    fn eval_row(&self, x: ArrayView1<f32>, scores: &mut [f32]) -> TractResult<usize> {
+        let coefficients =
+            self.coefficients.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let rho = self.rho.as_slice::<f32>()?;
+        if !self.one_vs_one() {
+            for (c, score) in scores.iter_mut().enumerate() {
+                *score = self.kernel.eval(x, coefficients.row(c)) + rho[c];
+            }
+            return Ok(argmax(scores.iter().copied()));
+        }
+        let support_vectors =
+            self.support_vectors.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let kernels: Vec<f32> =
+            support_vectors.outer_iter().map(|sv| self.kernel.eval(x, sv)).collect();
+        let starts: Vec<usize> = self
+            .vectors_per_class
+            .iter()
+            .scan(0, |acc, n| {
+                *acc += n;
+                Some(*acc - n)
+            })
+            .collect();
+        let k = self.n_classes;
+        let mut decisions = Vec::with_capacity(k * (k - 1) / 2);
+        let mut votes = vec![0usize; k];
+        for i in 0..k {
+            for j in i + 1..k {
+                let class_i = starts[i]..starts[i] + self.vectors_per_class[i];
+                let class_j = starts[j]..starts[j] + self.vectors_per_class[j];
+                let d = class_i.map(|v| coefficients[(j - 1, v)] * kernels[v]).sum::<f32>()
+                    + class_j.map(|v| coefficients[(i, v)] * kernels[v]).sum::<f32>()
+                    + rho[decisions.len()];
+                votes[if d > 0.0 { i } else { j }] += 1;
+                decisions.push(d);
+            }
+        }
+        if let (Some(prob_a), Some(prob_b)) = (&self.prob_a, &self.prob_b) {
+            let prob_a = prob_a.as_slice::<f32>()?;
+            let prob_b = prob_b.as_slice::<f32>()?;
+            let mut pairs = vec![0f32; k * k];
+            let mut p = 0;
+            for i in 0..k {
+                for j in i + 1..k {
+                    let proba = 1.0 / (1.0 + (decisions[p] * prob_a[p] + prob_b[p]).exp());
+                    let proba = proba.clamp(1e-7, 1.0 - 1e-7);
+                    pairs[i * k + j] = proba;
+                    pairs[j * k + i] = 1.0 - proba;
+                    p += 1;
+                }
+            }
+            scores.copy_from_slice(&multiclass_probability(k, &pairs));
+            Ok(argmax(scores.iter().copied()))
+        } else {
+            scores.copy_from_slice(&decisions);
+            Ok(argmax(votes.iter().map(|v| *v as f32)))
+        }
+    }
+}
+
+impl Op for SvmClassifier {
+    fn name(&self) -> Cow<str> {
+        "SvmClassifier".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SvmClassifier {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input: ArrayView2<f32> = input.to_array_view::<f32>()?.into_dimensionality()?;
+        let n = input.shape()[0];
+        let mut labels = Array1::<i32>::zeros(n);
+        let mut scores = Array2::<f32>::zeros((n, self.n_scores()));
+        for (ix, (x, mut scores)) in
+            input.outer_iter().zip(scores.axis_iter_mut(Axis(0))).enumerate()
+        {
+            labels[ix] = self.eval_row(x, scores.as_slice_mut().unwrap())? as i32;
+        }
+        Ok(tvec!(labels.into_tvalue(), scores.into_tvalue()))
+    }
+}
+
+impl TypedOp for SvmClassifier {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 2, "SvmClassifier expects a [N, F] input");
+        let n = &inputs[0].shape[0];
+        Ok(tvec!(i32::fact([n.clone()]), f32::fact([n.clone(), self.n_scores().to_dim()])))
+    }
+
+    as_op!();
+}
+
+/// Support vector regressor.
+///
+/// Without support vectors, `coefficients` is a single row of weights (linear mode). One-class
+/// regressors output 1 for positive decisions, -1 otherwise.
+#[derive(Debug, Clone)]
+pub struct SvmRegressor {
+    pub kernel: SvmKernel,
+    pub support_vectors: Arc<Tensor>,
+    pub coefficients: Arc<Tensor>,
+    pub rho: f32,
+    pub one_class: bool,
+}
+
+impl SvmRegressor {
+    fn eval_row(&self, x: ArrayView1<f32>) -> TractResult<f32> {
+        let coefficients =
+            self.coefficients.to_array_view::<f32>()?.into_dimensionality::<Ix1>()?;
+        let mut decision = self.rho;
+        if self.support_vectors.len() == 0 {
+            decision += self.kernel.eval(x, coefficients)
+        } else {
+            let support_vectors =
+                self.support_vectors.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+            decision += support_vectors
+                .outer_iter()
+                .zip(coefficients.iter())
+                .map(|(sv, c)| c * self.kernel.eval(x, sv))
+                .sum::<f32>()
+        }
+        if self.one_class {
+            Ok(if decision > 0.0 { 1.0 } else { -1.0 })
+        } else {
+            Ok(decision)
+        }
+    }
+}
+
+impl Op for SvmRegressor {
+    fn name(&self) -> Cow<str> {
+        "SvmRegressor".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SvmRegressor {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input: ArrayView2<f32> = input.to_array_view::<f32>()?.into_dimensionality()?;
+        let output =
+            input.outer_iter().map(|x| self.eval_row(x)).collect::<TractResult<Vec<_>>>()?;
+        let n = output.len();
+        Ok(tvec!(tensor1(&output).into_shape(&[n, 1])?.into_tvalue()))
+    }
+}
+
+impl TypedOp for SvmRegressor {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 2, "SvmRegressor expects a [N, F] input");
+        Ok(tvec!(f32::fact([inputs[0].shape[0].clone(), 1.to_dim()])))
+    }
+
+    as_op!();
+}
+
+fn kernel_parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::String.named("kernel_type"),
+        TypeName::Scalar.named("gamma"),
+        TypeName::Scalar.named("coef0"),
+        TypeName::Scalar.named("degree"),
+    ]
+}
+
+fn parameters_classifier() -> Vec<Parameter> {
+    let mut params = vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("support_vectors"),
+        TypeName::Scalar.tensor().named("coefficients"),
+        TypeName::Scalar.tensor().named("rho"),
+        TypeName::Scalar.tensor().named("prob_a"),
+        TypeName::Scalar.tensor().named("prob_b"),
+        TypeName::Integer.named("n_classes"),
+        TypeName::Integer.array().named("vectors_per_class"),
+    ];
+    params.extend(kernel_parameters());
+    params
+}
+
+fn parameters_regressor() -> Vec<Parameter> {
+    let mut params = vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("support_vectors"),
+        TypeName::Scalar.tensor().named("coefficients"),
+        TypeName::Scalar.named("rho"),
+        TypeName::Logical.named("one_class"),
+    ];
+    params.extend(kernel_parameters());
+    params
+}
+
+fn dump_kernel(kernel: &SvmKernel) -> [(&'static str, RValue); 4] {
+    [
+        ("kernel_type", string(kernel.kernel_type.as_str())),
+        ("gamma", numeric(kernel.gamma)),
+        ("coef0", numeric(kernel.coef0)),
+        ("degree", numeric(kernel.degree)),
+    ]
+}
+
+fn load_kernel(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<SvmKernel> {
+    let kernel_type: String = invocation.named_arg_as(builder, "kernel_type")?;
+    Ok(SvmKernel {
+        kernel_type: SvmKernelType::parse(&kernel_type)?,
+        gamma: invocation.named_arg_as(builder, "gamma")?,
+        coef0: invocation.named_arg_as(builder, "coef0")?,
+        degree: invocation.named_arg_as(builder, "degree")?,
+    })
+}
+
+fn dump_classifier(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &SvmClassifier,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let name = &node.name;
+    let support_vectors =
+        ast.konst_variable(format!("{name}_support_vectors"), &op.support_vectors)?;
+    let coefficients = ast.konst_variable(format!("{name}_coefficients"), &op.coefficients)?;
+    let rho = ast.konst_variable(format!("{name}_rho"), &op.rho)?;
+    // absent Platt scaling coefficients are dumped as empty tensors
+    let empty = rctensor1::<f32>(&[]);
+    let prob_a =
+        ast.konst_variable(format!("{name}_prob_a"), op.prob_a.as_ref().unwrap_or(&empty))?;
+    let prob_b =
+        ast.konst_variable(format!("{name}_prob_b"), op.prob_b.as_ref().unwrap_or(&empty))?;
+    let mut attributes = vec![
+        ("n_classes", numeric(op.n_classes)),
+        ("vectors_per_class", ints(&op.vectors_per_class)),
+    ];
+    attributes.extend(dump_kernel(&op.kernel));
+    Ok(Some(invocation(
+        "tract_onnx_ml_svm_classifier",
+        &[input, support_vectors, coefficients, rho, prob_a, prob_b],
+        &attributes,
+    )))
+}
+
+fn load_classifier(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let support_vectors = invocation.named_arg_as(builder, "support_vectors")?;
+    let coefficients = invocation.named_arg_as(builder, "coefficients")?;
+    let rho = invocation.named_arg_as(builder, "rho")?;
+    let prob_a: Arc<Tensor> = invocation.named_arg_as(builder, "prob_a")?;
+    let prob_b: Arc<Tensor> = invocation.named_arg_as(builder, "prob_b")?;
+    let n_classes = invocation.named_arg_as(builder, "n_classes")?;
+    let vectors_per_class = invocation.named_arg_as(builder, "vectors_per_class")?;
+    let kernel = load_kernel(builder, invocation)?;
+    let op = SvmClassifier {
+        kernel,
+        n_classes,
+        support_vectors,
+        vectors_per_class,
+        coefficients,
+        rho,
+        prob_a: Some(prob_a).filter(|t| t.len() > 0),
+        prob_b: Some(prob_b).filter(|t| t.len() > 0),
+    };
+    builder.wire(op, &[input])
+}
+
+fn dump_regressor(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &SvmRegressor,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let name = &node.name;
+    let support_vectors =
+        ast.konst_variable(format!("{name}_support_vectors"), &op.support_vectors)?;
+    let coefficients = ast.konst_variable(format!("{name}_coefficients"), &op.coefficients)?;
+    let mut attributes = vec![("rho", numeric(op.rho)), ("one_class", logical(op.one_class))];
+    attributes.extend(dump_kernel(&op.kernel));
+    Ok(Some(invocation(
+        "tract_onnx_ml_svm_regressor",
+        &[input, support_vectors, coefficients],
+        &attributes,
+    )))
+}
+
+fn load_regressor(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let support_vectors = invocation.named_arg_as(builder, "support_vectors")?;
+    let coefficients = invocation.named_arg_as(builder, "coefficients")?;
+    let rho = invocation.named_arg_as(builder, "rho")?;
+    let one_class = invocation.named_arg_as(builder, "one_class")?;
+    let kernel = load_kernel(builder, invocation)?;
+    let op = SvmRegressor { kernel, support_vectors, coefficients, rho, one_class };
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_coupling_is_identity() {
+        let p = multiclass_probability(2, &[0.0, 0.8, 0.2, 0.0]);
+        assert!((p[0] - 0.8).abs() < 1e-2, "{p:?}");
+        assert!((p[1] - 0.2).abs() < 1e-2, "{p:?}");
+    }
+
+    #[test]
+    fn one_vs_one_votes() {
+        // three classes, one support vector each, linear kernel
+        let op = SvmClassifier {
+            kernel: SvmKernel::default(),
+            n_classes: 3,
+            support_vectors: rctensor2(&[[1f32, 0.], [0., 1.], [-1., -1.]]),
+            vectors_per_class: tvec!(1, 1, 1),
+            coefficients: rctensor2(&[[1f32, -1., -1.], [1., 1., -1.]]),
+            rho: rctensor1(&[0f32, 0., 0.]),
+            prob_a: None,
+            prob_b: None,
+        };
+        let input = tensor2(&[[2f32, 0.], [0., 2.], [-2., -2.]]);
+        let outputs = op.eval(tvec!(input.into_tvalue())).unwrap();
+        assert_eq!(outputs[0].as_slice::<i32>().unwrap(), &[0, 1, 2]);
+        assert_eq!(outputs[1].shape(), &[3, 3]);
+    }
+}
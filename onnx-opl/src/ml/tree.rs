@@ -52,7 +52,7 @@ impl Cmp {
 impl TryFrom<u8> for Cmp {
     type Error = TractError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if (1..=5).contains(&value) {
+        if (1..=6).contains(&value) {
             unsafe { Ok(std::mem::transmute::<u8, Cmp>(value)) }
         } else {
             bail!("Invalid value for Cmp: {}", value);
@@ -251,20 +251,35 @@ impl AggregateFn for AvgFn {
 }
 
 #[derive(Clone, Copy, Default, Debug)]
-pub struct MaxFn;
+pub struct MaxFn {
+    started: bool,
+}
 
 impl AggregateFn for MaxFn {
     fn aggregate(&mut self, score: f32, total: &mut f32) {
-        *total = total.max(score);
+        // the output starts at zero: the first contribution must overwrite it
+        *total = if self.started { total.max(score) } else { score };
+        self.started = true;
+    }
+
+    fn post_aggregate(&mut self, _total: &mut f32) {
+        self.started = false;
     }
 }
 
 #[derive(Clone, Copy, Default, Debug)]
-pub struct MinFn;
+pub struct MinFn {
+    started: bool,
+}
 
 impl AggregateFn for MinFn {
     fn aggregate(&mut self, score: f32, total: &mut f32) {
-        *total = total.min(score);
+        *total = if self.started { total.min(score) } else { score };
+        self.started = true;
+    }
+
+    fn post_aggregate(&mut self, _total: &mut f32) {
+        self.started = false;
     }
 }
 
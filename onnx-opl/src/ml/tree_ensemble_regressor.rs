@@ -0,0 +1,173 @@
+pub use super::tree::{Aggregate, Cmp, TreeEnsemble, TreeEnsembleData};
+use super::tree_ensemble_classifier::parse_aggregate;
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_ml_tree_ensemble_regressor",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct TreeEnsembleRegressor {
+    pub ensemble: TreeEnsemble,
+}
+
+impl Op for TreeEnsembleRegressor {
+    fn name(&self) -> Cow<str> {
+        "TreeEnsembleRegressor".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TreeEnsembleRegressor {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<f32>()?;
+        let input = input.to_array_view::<f32>()?;
+        let targets = self.ensemble.eval(input)?;
+        Ok(tvec!(targets.into_tvalue()))
+    }
+}
+
+impl TypedOp for TreeEnsembleRegressor {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let n = &inputs[0].shape[0];
+        Ok(tvec!(f32::fact(&[n.clone(), self.ensemble.n_classes().into()])))
+    }
+
+    as_op!();
+}
+
+fn parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("trees"),
+        TypeName::Scalar.tensor().named("nodes"),
+        TypeName::Scalar.tensor().named("leaves"),
+        TypeName::Integer.named("max_used_feature"),
+        TypeName::Integer.named("n_targets"),
+        TypeName::String.named("aggregate_fn"),
+    ]
+}
+
+fn dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &TreeEnsembleRegressor,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let trees = ast.konst_variable(format!("{}_trees", node.name), &op.ensemble.data.trees)?;
+    let nodes = ast.konst_variable(format!("{}_nodes", node.name), &op.ensemble.data.nodes)?;
+    let leaves = ast.konst_variable(format!("{}_leaves", node.name), &op.ensemble.data.leaves)?;
+    let agg = match op.ensemble.aggregate_fn {
+        Aggregate::Min => "MIN",
+        Aggregate::Max => "MAX",
+        Aggregate::Sum => "SUM",
+        Aggregate::Avg => "AVERAGE",
+    };
+    Ok(Some(invocation(
+        "tract_onnx_ml_tree_ensemble_regressor",
+        &[input, trees, nodes, leaves],
+        &[
+            ("max_used_feature", numeric(op.ensemble.max_used_feature)),
+            ("n_targets", numeric(op.ensemble.n_classes)),
+            ("aggregate_fn", string(agg)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let trees = invocation.named_arg_as(builder, "trees")?;
+    let nodes = invocation.named_arg_as(builder, "nodes")?;
+    let leaves = invocation.named_arg_as(builder, "leaves")?;
+    let max_used_feature = invocation.named_arg_as(builder, "max_used_feature")?;
+    let n_classes = invocation.named_arg_as(builder, "n_targets")?;
+    let aggregate_fn: String = invocation.named_arg_as(builder, "aggregate_fn")?;
+    let aggregate_fn = parse_aggregate(&aggregate_fn)?;
+    let data = TreeEnsembleData { trees, nodes, leaves };
+    let ensemble = TreeEnsemble { data, n_classes, max_used_feature, aggregate_fn };
+    let op = TreeEnsembleRegressor { ensemble };
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch(offset: u32, cmp: Cmp, feature: u32, value: f32) -> [u32; 5] {
+        [feature, offset + 1, offset + 2, value.to_bits(), cmp as u32]
+    }
+
+    fn leaf(start: u32, end: u32) -> [u32; 5] {
+        [start, end, 0, 0, 0]
+    }
+
+    fn weight(target: u32, weight: f32) -> [u32; 2] {
+        [target, weight.to_bits()]
+    }
+
+    // two stumps over two targets: the second one contributes to both targets on its left
+    fn regressor(aggregate_fn: Aggregate) -> TreeEnsembleRegressor {
+        let data = TreeEnsembleData {
+            trees: rctensor1(&[0u32, 3]),
+            nodes: rctensor2(&[
+                branch(0, Cmp::LessEqual, 0, 0.5),
+                leaf(0, 1),
+                leaf(1, 2),
+                branch(3, Cmp::Less, 1, 0.),
+                leaf(2, 4),
+                leaf(4, 5),
+            ]),
+            leaves: rctensor2(&[
+                weight(0, 1.),
+                weight(1, 2.),
+                weight(0, 10.),
+                weight(1, 20.),
+                weight(1, 40.),
+            ]),
+        };
+        TreeEnsembleRegressor { ensemble: TreeEnsemble::build(data, 1, 2, aggregate_fn).unwrap() }
+    }
+
+    fn eval(op: &TreeEnsembleRegressor) -> TractResult<Tensor> {
+        let input = tensor2(&[[0f32, -1.], [1., 1.]]);
+        Ok(op.eval(tvec!(input.into_tvalue()))?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn sum() -> TractResult<()> {
+        assert_eq!(eval(&regressor(Aggregate::Sum))?, tensor2(&[[11f32, 20.], [0., 42.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn average_over_contributions() -> TractResult<()> {
+        assert_eq!(eval(&regressor(Aggregate::Avg))?, tensor2(&[[5.5f32, 20.], [0., 21.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn max() -> TractResult<()> {
+        assert_eq!(eval(&regressor(Aggregate::Max))?, tensor2(&[[10f32, 20.], [0., 40.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn output_facts() -> TractResult<()> {
+        let op = regressor(Aggregate::Sum);
+        let facts = op.output_facts(&[&f32::fact([3, 2])])?;
+        assert_eq!(facts[0], f32::fact([3, 2]));
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::sequence::{SequenceFact, TensorSequence};
+use tract_nnef::internal::*;
+
+/// A map from keys to values, carried around as the payload of an opaque scalar.
+///
+/// Keys and values are two 1D tensors of the same length.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct TensorMap {
+    pub keys: Arc<Tensor>,
+    pub values: Arc<Tensor>,
+}
+
+impl OpaquePayload for TensorMap {
+    fn same_as(&self, other: &dyn OpaquePayload) -> bool {
+        other.downcast_ref::<Self>().is_some_and(|other| other == self)
+    }
+}
+
+impl fmt::Display for TensorMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Map<{:?},{:?}>[{}]",
+            self.keys.datum_type(),
+            self.values.datum_type(),
+            self.keys.len()
+        )
+    }
+}
+
+impl TensorMap {
+    pub fn from_tensor(t: &Tensor) -> TractResult<&TensorMap> {
+        t.to_scalar::<Opaque>()?
+            .downcast_ref::<TensorMap>()
+            .with_context(|| format!("Expected a map, got {t:?}"))
+    }
+
+    pub fn into_tensor(self) -> Tensor {
+        tensor0(Opaque(Arc::new(self)))
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators-ml.md#ai.onnx.ml.ZipMap
+///
+/// Turns [N, C] scores into a sequence of N maps from the class labels to the scores.
+#[derive(Clone, Debug, Hash)]
+pub struct ZipMap {
+    pub class_labels: Arc<Tensor>,
+}
+
+impl Op for ZipMap {
+    fn name(&self) -> Cow<str> {
+        "ZipMap".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for ZipMap {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        ensure!(
+            input.rank() == 2 && input.shape()[1] == self.class_labels.len(),
+            "ZipMap expects [N, {}] scores, got {:?}",
+            self.class_labels.len(),
+            input.shape()
+        );
+        let items = input
+            .to_array_view::<f32>()?
+            .outer_iter()
+            .map(|row| {
+                let values = row.to_owned().into_arc_tensor();
+                let map = TensorMap { keys: self.class_labels.clone(), values };
+                map.into_tensor().into_arc_tensor()
+            })
+            .collect();
+        Ok(tvec!(TensorSequence::new(Opaque::datum_type(), items).into_tvalue()))
+    }
+}
+
+impl TypedOp for ZipMap {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].datum_type == f32::datum_type());
+        ensure!(inputs[0].rank() == 2);
+        let element =
+            SequenceFact { datum_type: Opaque::datum_type(), shape: Some(ShapeFact::scalar()) };
+        Ok(tvec!(element.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn one_map_per_row() -> TractResult<()> {
+        let op = ZipMap { class_labels: rctensor1(&[10i64, 20]) };
+        let output = op.eval(tvec!(tensor2(&[[0.25f32, 0.75], [1., 0.]]).into_tvalue()))?;
+        let items = &TensorSequence::from_tensor(&output[0])?.items;
+        assert_eq!(items.len(), 2);
+        let map = TensorMap::from_tensor(&items[1])?;
+        assert_eq!(*map.keys, tensor1(&[10i64, 20]));
+        assert_eq!(*map.values, tensor1(&[1f32, 0.]));
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use tract_nnef::internal::*;
+
+/// An optional tensor (or sequence), carried around as the payload of an opaque scalar.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct OptionalValue(pub Option<Arc<Tensor>>);
+
+impl OpaquePayload for OptionalValue {
+    fn same_as(&self, other: &dyn OpaquePayload) -> bool {
+        other.downcast_ref::<Self>().is_some_and(|other| other == self)
+    }
+}
+
+impl fmt::Display for OptionalValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(t) => write!(f, "Some({t:?})"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl OptionalValue {
+    /// Returns None if the tensor is not an optional value.
+    pub fn from_tensor(t: &Tensor) -> Option<&OptionalValue> {
+        t.to_scalar::<Opaque>().ok()?.downcast_ref::<OptionalValue>()
+    }
+
+    pub fn into_tvalue(self) -> TValue {
+        tensor0(Opaque(Arc::new(self))).into_tvalue()
+    }
+}
+
+/// Describes the wrapped value in the opaque metadata of the optional fact.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct OptionalFact {
+    /// fact of the wrapped value, if known
+    pub inner: Option<TypedFact>,
+}
+
+impl OpaqueMetadata for OptionalFact {
+    fn same_as(&self, other: &dyn OpaqueMetadata) -> bool {
+        other.downcast_ref::<Self>().is_some_and(|other| other == self)
+    }
+}
+
+impl OptionalFact {
+    pub fn wrapping(fact: &TypedFact) -> OptionalFact {
+        // keep the opaque metadata of wrapped sequences
+        OptionalFact { inner: Some(TypedFact { konst: None, uniform: None, ..fact.clone() }) }
+    }
+
+    /// Returns None if the fact is not an optional fact.
+    pub fn from_typed_fact(fact: &TypedFact) -> Option<OptionalFact> {
+        if let Some(meta) = fact.opaque_metadata.as_ref().and_then(|m| m.downcast_ref::<Self>()) {
+            return Some(meta.clone());
+        }
+        let value = OptionalValue::from_tensor(fact.konst.as_ref()?)?;
+        Some(OptionalFact { inner: value.0.as_ref().map(TypedFact::from) })
+    }
+
+    pub fn into_typed_fact(self) -> TypedFact {
+        Opaque::scalar_fact().with_opaque_metadata(self)
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#Optional
+///
+/// Wraps its input if it has one, or makes an empty optional of the given type.
+#[derive(Clone, Debug, Hash)]
+pub struct OptionalConstruct {
+    pub empty: Option<TypedFact>,
+}
+
+impl Op for OptionalConstruct {
+    fn name(&self) -> Cow<str> {
+        "Optional".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for OptionalConstruct {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let value = inputs.into_iter().next().map(|t| t.into_arc_tensor());
+        Ok(tvec!(OptionalValue(value).into_tvalue()))
+    }
+}
+
+impl TypedOp for OptionalConstruct {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let fact = if let Some(input) = inputs.first() {
+            OptionalFact::wrapping(input)
+        } else {
+            OptionalFact { inner: self.empty.clone() }
+        };
+        Ok(tvec!(fact.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#OptionalHasElement
+///
+/// A missing input makes an empty optional, a plain tensor a full one.
+#[derive(Clone, Debug, Hash)]
+pub struct OptionalHasElement;
+
+impl Op for OptionalHasElement {
+    fn name(&self) -> Cow<str> {
+        "OptionalHasElement".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for OptionalHasElement {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let has = match inputs.first() {
+            None => false,
+            Some(input) => OptionalValue::from_tensor(input).map(|o| o.0.is_some()).unwrap_or(true),
+        };
+        Ok(tvec!(tensor0(has).into_tvalue()))
+    }
+}
+
+impl TypedOp for OptionalHasElement {
+    fn output_facts(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(bool::scalar_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#OptionalGetElement
+///
+/// Plain tensors (and sequences) go through unchanged.
+#[derive(Clone, Debug, Hash)]
+pub struct OptionalGetElement;
+
+impl Op for OptionalGetElement {
+    fn name(&self) -> Cow<str> {
+        "OptionalGetElement".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for OptionalGetElement {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        if let Some(optional) = OptionalValue::from_tensor(&input) {
+            let value = optional.0.clone().context("OptionalGetElement on an empty optional")?;
+            Ok(tvec!(value.into_tvalue()))
+        } else {
+            Ok(tvec!(input))
+        }
+    }
+}
+
+impl TypedOp for OptionalGetElement {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if let Some(optional) = OptionalFact::from_typed_fact(inputs[0]) {
+            Ok(tvec!(optional.inner.context("Type of the optional element is unknown")?))
+        } else {
+            Ok(tvec!(inputs[0].clone()))
+        }
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_and_unwrap() -> TractResult<()> {
+        let some = OptionalConstruct { empty: None }.eval(tvec!(tensor1(&[1i64]).into_tvalue()))?;
+        let has = OptionalHasElement.eval(some.clone())?;
+        assert_eq!(*has[0], tensor0(true));
+        let value = OptionalGetElement.eval(some)?;
+        assert_eq!(*value[0], tensor1(&[1i64]));
+        let none = OptionalConstruct { empty: Some(i64::fact([1])) }.eval(tvec!())?;
+        let has = OptionalHasElement.eval(none.clone())?;
+        assert_eq!(*has[0], tensor0(false));
+        assert!(OptionalGetElement.eval(none).is_err());
+        Ok(())
+    }
+}
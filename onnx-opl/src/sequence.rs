@@ -0,0 +1,640 @@
+use std::fmt;
+
+use tract_nnef::internal::*;
+
+/// A sequence of tensors, carried around as the payload of an opaque scalar.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct TensorSequence {
+    pub datum_type: DatumType,
+    pub items: TVec<Arc<Tensor>>,
+}
+
+impl OpaquePayload for TensorSequence {
+    fn same_as(&self, other: &dyn OpaquePayload) -> bool {
+        other.downcast_ref::<Self>().is_some_and(|other| other == self)
+    }
+}
+
+impl fmt::Display for TensorSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sequence<{:?}>[{}]", self.datum_type, self.items.len())
+    }
+}
+
+impl TensorSequence {
+    pub fn new(datum_type: DatumType, items: TVec<Arc<Tensor>>) -> TensorSequence {
+        TensorSequence { datum_type, items }
+    }
+
+    pub fn from_tensor(t: &Tensor) -> TractResult<&TensorSequence> {
+        t.to_scalar::<Opaque>()?
+            .downcast_ref::<TensorSequence>()
+            .with_context(|| format!("Expected a sequence, got {t:?}"))
+    }
+
+    pub fn into_tensor(self) -> Tensor {
+        tensor0(Opaque(Arc::new(self)))
+    }
+
+    pub fn into_tvalue(self) -> TValue {
+        self.into_tensor().into_tvalue()
+    }
+}
+
+/// Describes the elements of a sequence in the opaque metadata of the sequence fact.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SequenceFact {
+    pub datum_type: DatumType,
+    /// shape shared by all elements, unknown for a sequence that has never held any
+    pub shape: Option<ShapeFact>,
+}
+
+impl OpaqueMetadata for SequenceFact {
+    fn same_as(&self, other: &dyn OpaqueMetadata) -> bool {
+        other.downcast_ref::<Self>().is_some_and(|other| other == self)
+    }
+}
+
+impl SequenceFact {
+    pub fn of_element(fact: &TypedFact) -> SequenceFact {
+        SequenceFact { datum_type: fact.datum_type, shape: Some(fact.shape.clone()) }
+    }
+
+    pub fn from_typed_fact(fact: &TypedFact) -> TractResult<SequenceFact> {
+        if let Some(meta) = fact.opaque_metadata.as_ref().and_then(|m| m.downcast_ref::<Self>()) {
+            return Ok(meta.clone());
+        }
+        if let Some(konst) = &fact.konst {
+            let seq = TensorSequence::from_tensor(konst)?;
+            let shape = seq
+                .items
+                .first()
+                .filter(|first| seq.items.iter().all(|it| it.shape() == first.shape()))
+                .map(|first| first.shape().iter().collect());
+            return Ok(SequenceFact { datum_type: seq.datum_type, shape });
+        }
+        bail!("Expected a sequence, got {:?}", fact)
+    }
+
+    pub fn into_typed_fact(self) -> TypedFact {
+        Opaque::scalar_fact().with_opaque_metadata(self)
+    }
+
+    pub fn element_fact(&self) -> TractResult<TypedFact> {
+        let shape = self.shape.as_ref().context("Sequence element shape is unknown")?;
+        Ok(self.datum_type.fact(shape.clone()))
+    }
+
+    /// Accounts for a new element, turning the dimensions that do not match into symbols.
+    pub fn merge(&self, element: &TypedFact, symbol: &Symbol) -> TractResult<SequenceFact> {
+        ensure!(
+            self.datum_type == element.datum_type,
+            "Inconsistent sequence element types: {:?} and {:?}",
+            self.datum_type,
+            element.datum_type
+        );
+        let Some(shape) = &self.shape else { return Ok(SequenceFact::of_element(element)) };
+        ensure!(
+            shape.rank() == element.rank(),
+            "Inconsistent sequence element ranks: {:?} and {:?}",
+            shape,
+            element.shape
+        );
+        let shape = shape
+            .iter()
+            .zip(element.shape.iter())
+            .enumerate()
+            .map(|(axis, (a, b))| if a == b { a.clone() } else { axis_symbol(symbol, axis) })
+            .collect();
+        Ok(SequenceFact { datum_type: self.datum_type, shape: Some(shape) })
+    }
+}
+
+/// Dimension of an element axis that varies along a sequence.
+fn axis_symbol(symbol: &Symbol, axis: usize) -> TDim {
+    symbol.symbol_table().sym(&format!("{symbol}_axis{axis}")).to_dim()
+}
+
+fn resolve_position(position: &Tensor, len: usize, inclusive: bool) -> TractResult<usize> {
+    let position = position.cast_to_scalar::<i64>()?;
+    let upper = len as i64 + inclusive as i64;
+    ensure!(
+        position >= -(len as i64) && position < upper,
+        "Position {} out of bounds for a sequence of length {}",
+        position,
+        len
+    );
+    Ok(if position < 0 { position + len as i64 } else { position } as usize)
+}
+
+fn resolve_axis(axis: i64, rank: usize) -> TractResult<usize> {
+    let resolved = if axis < 0 { axis + rank as i64 } else { axis };
+    ensure!(resolved >= 0 && resolved < rank as i64, "Invalid axis {} for rank {}", axis, rank);
+    Ok(resolved as usize)
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceEmpty
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceEmpty {
+    pub datum_type: DatumType,
+}
+
+impl Op for SequenceEmpty {
+    fn name(&self) -> Cow<str> {
+        "SequenceEmpty".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceEmpty {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, _inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(tvec!(TensorSequence::new(self.datum_type, tvec!()).into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceEmpty {
+    fn output_facts(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(SequenceFact { datum_type: self.datum_type, shape: None }.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceConstruct
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceConstruct {
+    pub symbol: Symbol,
+}
+
+impl Op for SequenceConstruct {
+    fn name(&self) -> Cow<str> {
+        "SequenceConstruct".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceConstruct {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        ensure!(inputs.len() > 0, "SequenceConstruct expects at least one input");
+        let datum_type = inputs[0].datum_type();
+        let items = inputs.into_iter().map(|t| t.into_arc_tensor()).collect();
+        Ok(tvec!(TensorSequence::new(datum_type, items).into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceConstruct {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() > 0, "SequenceConstruct expects at least one input");
+        let mut fact = SequenceFact::of_element(inputs[0]);
+        for input in &inputs[1..] {
+            fact = fact.merge(input, &self.symbol)?;
+        }
+        Ok(tvec!(fact.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceInsert
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceInsert {
+    pub symbol: Symbol,
+}
+
+impl Op for SequenceInsert {
+    fn name(&self) -> Cow<str> {
+        "SequenceInsert".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceInsert {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut seq = TensorSequence::from_tensor(&inputs[0])?.clone();
+        ensure!(
+            inputs[1].datum_type() == seq.datum_type,
+            "Can not insert a {:?} tensor in a sequence of {:?}",
+            inputs[1].datum_type(),
+            seq.datum_type
+        );
+        let position = if let Some(position) = inputs.get(2) {
+            resolve_position(position, seq.items.len(), true)?
+        } else {
+            seq.items.len()
+        };
+        seq.items.insert(position, inputs[1].clone().into_arc_tensor());
+        Ok(tvec!(seq.into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceInsert {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let fact = SequenceFact::from_typed_fact(inputs[0])?.merge(inputs[1], &self.symbol)?;
+        Ok(tvec!(fact.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceAt
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceAt;
+
+impl Op for SequenceAt {
+    fn name(&self) -> Cow<str> {
+        "SequenceAt".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceAt {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let seq = TensorSequence::from_tensor(&inputs[0])?;
+        let position = resolve_position(&inputs[1], seq.items.len(), false)?;
+        Ok(tvec!(seq.items[position].clone().into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceAt {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(SequenceFact::from_typed_fact(inputs[0])?.element_fact()?))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceErase
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceErase;
+
+impl Op for SequenceErase {
+    fn name(&self) -> Cow<str> {
+        "SequenceErase".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceErase {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut seq = TensorSequence::from_tensor(&inputs[0])?.clone();
+        ensure!(seq.items.len() > 0, "Can not erase from an empty sequence");
+        let position = if let Some(position) = inputs.get(1) {
+            resolve_position(position, seq.items.len(), false)?
+        } else {
+            seq.items.len() - 1
+        };
+        seq.items.remove(position);
+        Ok(tvec!(seq.into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceErase {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(SequenceFact::from_typed_fact(inputs[0])?.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceLength
+#[derive(Clone, Debug, Hash)]
+pub struct SequenceLength;
+
+impl Op for SequenceLength {
+    fn name(&self) -> Cow<str> {
+        "SequenceLength".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceLength {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let seq = TensorSequence::from_tensor(&inputs[0])?;
+        Ok(tvec!(tensor0(seq.items.len() as i64).into_tvalue()))
+    }
+}
+
+impl TypedOp for SequenceLength {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        SequenceFact::from_typed_fact(inputs[0])?;
+        Ok(tvec!(i64::scalar_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SplitToSequence
+#[derive(Clone, Debug, Hash)]
+pub struct SplitToSequence {
+    pub axis: i64,
+    pub keepdims: bool,
+    pub symbol: Symbol,
+}
+
+impl Op for SplitToSequence {
+    fn name(&self) -> Cow<str> {
+        "SplitToSequence".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SplitToSequence {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = &inputs[0];
+        let axis = resolve_axis(self.axis, input.rank())?;
+        let dim = input.shape()[axis];
+        let lengths: TVec<usize> = match inputs.get(1) {
+            None => tvec!(1; dim),
+            Some(split) if split.rank() == 0 => {
+                let chunk = split.cast_to_scalar::<i64>()?;
+                ensure!(chunk > 0, "SplitToSequence split must be positive, got {}", chunk);
+                let chunk = chunk as usize;
+                (0..dim).step_by(chunk).map(|start| chunk.min(dim - start)).collect()
+            }
+            Some(split) => {
+                let split = split.cast_to::<i64>()?;
+                let lengths: TVec<usize> =
+                    split.as_slice::<i64>()?.iter().map(|l| *l as usize).collect();
+                ensure!(
+                    lengths.iter().sum::<usize>() == dim,
+                    "SplitToSequence lengths {:?} do not add up to {}",
+                    lengths,
+                    dim
+                );
+                lengths
+            }
+        };
+        let mut items = tvec!();
+        let mut start = 0;
+        for len in lengths {
+            let mut item = input.slice(axis, start, start + len)?;
+            if inputs.len() == 1 && !self.keepdims {
+                item.remove_axis(axis)?;
+            }
+            items.push(item.into_arc_tensor());
+            start += len;
+        }
+        Ok(tvec!(TensorSequence::new(input.datum_type(), items).into_tvalue()))
+    }
+}
+
+impl TypedOp for SplitToSequence {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let axis = resolve_axis(self.axis, inputs[0].rank())?;
+        let mut shape: TVec<TDim> = inputs[0].shape.to_tvec();
+        let chunk = if let Some(split) = inputs.get(1) {
+            let dim = shape[axis].to_usize().ok();
+            split.konst.as_ref().and_then(|split| -> Option<usize> {
+                let split = split.cast_to::<i64>().ok()?;
+                let lengths = split.as_slice::<i64>().ok()?;
+                let first = *lengths.first()? as usize;
+                if split.rank() == 0 {
+                    dim.filter(|dim| dim % first == 0).map(|_| first)
+                } else {
+                    lengths.iter().all(|l| *l as usize == first).then_some(first)
+                }
+            })
+        } else {
+            Some(1)
+        };
+        shape[axis] = chunk.map(|c| c.to_dim()).unwrap_or_else(|| axis_symbol(&self.symbol, axis));
+        if inputs.len() == 1 && !self.keepdims {
+            shape.remove(axis);
+        }
+        let fact = SequenceFact { datum_type: inputs[0].datum_type, shape: Some(shape.into()) };
+        Ok(tvec!(fact.into_typed_fact()))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#ConcatFromSequence
+#[derive(Clone, Debug, Hash)]
+pub struct ConcatFromSequence {
+    pub axis: i64,
+    pub new_axis: bool,
+    pub symbol: Symbol,
+}
+
+impl Op for ConcatFromSequence {
+    fn name(&self) -> Cow<str> {
+        "ConcatFromSequence".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for ConcatFromSequence {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let seq = TensorSequence::from_tensor(&inputs[0])?;
+        ensure!(seq.items.len() > 0, "ConcatFromSequence on an empty sequence");
+        let rank = seq.items[0].rank();
+        let output = if self.new_axis {
+            let axis = resolve_axis(self.axis, rank + 1)?;
+            let items = seq
+                .items
+                .iter()
+                .map(|it| {
+                    let mut it = (**it).clone();
+                    it.insert_axis(axis)?;
+                    Ok(it)
+                })
+                .collect::<TractResult<TVec<Tensor>>>()?;
+            Tensor::stack_tensors(axis, &items)?
+        } else {
+            Tensor::stack_tensors(resolve_axis(self.axis, rank)?, &seq.items)?
+        };
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for ConcatFromSequence {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let element = SequenceFact::from_typed_fact(inputs[0])?.element_fact()?;
+        let mut shape: TVec<TDim> = element.shape.to_tvec();
+        if self.new_axis {
+            let axis = resolve_axis(self.axis, shape.len() + 1)?;
+            shape.insert(axis, self.symbol.to_dim());
+        } else {
+            let axis = resolve_axis(self.axis, shape.len())?;
+            shape[axis] = self.symbol.to_dim();
+        }
+        Ok(tvec!(element.datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#SequenceMap
+///
+/// The body is run once per element of the sequence inputs, the other inputs being passed
+/// as is to every iteration.
+#[derive(Clone, Debug)]
+pub struct SequenceMap {
+    pub body: TypedModel,
+    pub sequence_inputs: TVec<bool>,
+}
+
+impl Op for SequenceMap {
+    fn name(&self) -> Cow<str> {
+        "SequenceMap".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for SequenceMap {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let sequences = inputs
+            .iter()
+            .zip(self.sequence_inputs.iter())
+            .map(|(input, is_seq)| is_seq.then(|| TensorSequence::from_tensor(input)).transpose())
+            .collect::<TractResult<TVec<Option<&TensorSequence>>>>()?;
+        let len = sequences[0].context("SequenceMap first input must be a sequence")?.items.len();
+        ensure!(
+            sequences.iter().flatten().all(|s| s.items.len() == len),
+            "SequenceMap sequence inputs must have the same length"
+        );
+        let plan = SimplePlan::new(&self.body)?;
+        let mut outputs: TVec<TVec<Arc<Tensor>>> = tvec!(tvec!(); self.body.outputs.len());
+        for i in 0..len {
+            let iter_inputs = inputs
+                .iter()
+                .zip(sequences.iter())
+                .map(|(input, seq)| match seq {
+                    Some(seq) => seq.items[i].clone().into_tvalue(),
+                    None => input.clone(),
+                })
+                .collect::<TVec<TValue>>();
+            for (ix, output) in plan.run(iter_inputs)?.into_iter().enumerate() {
+                outputs[ix].push(output.into_arc_tensor());
+            }
+        }
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, items)| {
+                let datum_type = self.body.output_fact(ix)?.datum_type;
+                Ok(TensorSequence::new(datum_type, items).into_tvalue())
+            })
+            .collect()
+    }
+}
+
+impl TypedOp for SequenceMap {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == self.body.inputs.len());
+        ensure!(inputs.len() == self.sequence_inputs.len());
+        (0..self.body.outputs.len())
+            .map(|ix| Ok(SequenceFact::of_element(self.body.output_fact(ix)?).into_typed_fact()))
+            .collect()
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(op: impl EvalOp, inputs: TVec<Tensor>) -> TractResult<TVec<TValue>> {
+        op.eval(inputs.into_iter().map(|t| t.into_tvalue()).collect())
+    }
+
+    #[test]
+    fn insert_at_and_erase() -> TractResult<()> {
+        let seq = run(SequenceEmpty { datum_type: f32::datum_type() }, tvec!())?;
+        let symbol = SymbolTable::default().sym("S");
+        let seq = run(
+            SequenceInsert { symbol: symbol.clone() },
+            tvec!(seq[0].clone().into_tensor(), tensor1(&[1f32, 2.])),
+        )?;
+        let seq = run(
+            SequenceInsert { symbol },
+            tvec!(seq[0].clone().into_tensor(), tensor1(&[0f32]), tensor0(0i64)),
+        )?;
+        let len = run(SequenceLength, tvec!(seq[0].clone().into_tensor()))?;
+        assert_eq!(*len[0], tensor0(2i64));
+        let last = run(SequenceAt, tvec!(seq[0].clone().into_tensor(), tensor0(-1i64)))?;
+        assert_eq!(*last[0], tensor1(&[1f32, 2.]));
+        let seq = run(SequenceErase, tvec!(seq[0].clone().into_tensor()))?;
+        let first = run(SequenceAt, tvec!(seq[0].clone().into_tensor(), tensor0(0i64)))?;
+        assert_eq!(*first[0], tensor1(&[0f32]));
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_concat() -> TractResult<()> {
+        let symbol = SymbolTable::default().sym("S");
+        let input = tensor2(&[[0f32, 1., 2.], [3., 4., 5.]]);
+        let seq = run(
+            SplitToSequence { axis: 1, keepdims: true, symbol: symbol.clone() },
+            tvec!(input.clone(), tensor0(2i64)),
+        )?;
+        let items = &TensorSequence::from_tensor(&seq[0])?.items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(*items[1], tensor2(&[[2f32], [5.]]));
+        let concat = run(
+            ConcatFromSequence { axis: 1, new_axis: false, symbol },
+            tvec!(seq[0].clone().into_tensor()),
+        )?;
+        assert_eq!(*concat[0], input);
+        Ok(())
+    }
+
+    #[test]
+    fn merged_facts_use_symbols() -> TractResult<()> {
+        let symbol = SymbolTable::default().sym("S");
+        let op = SequenceConstruct { symbol };
+        let a = f32::fact([2, 3]);
+        let b = f32::fact([2, 4]);
+        let fact = SequenceFact::from_typed_fact(&op.output_facts(&[&a, &b])?[0])?;
+        let shape = fact.shape.unwrap();
+        assert_eq!(shape[0], 2.to_dim());
+        assert!(shape[1].to_i64().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::hash::{Hash, Hasher};
+
+use regex::Regex;
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_regex_full_match",
+        &[TypeName::String.tensor().named("input"), TypeName::String.named("pattern")],
+        &[("output", TypeName::Logical.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#RegexFullMatch
+///
+/// Patterns use the syntax of the regex crate, which is close to (but not exactly) RE2.
+#[derive(Clone, Debug)]
+pub struct RegexFullMatch {
+    pub pattern: String,
+    regex: Regex,
+}
+
+impl RegexFullMatch {
+    pub fn new(pattern: impl Into<String>) -> TractResult<RegexFullMatch> {
+        let pattern = pattern.into();
+        let regex = Regex::new(&format!("^(?:{pattern})$"))
+            .with_context(|| format!("Invalid regular expression {pattern:?}"))?;
+        Ok(RegexFullMatch { pattern, regex })
+    }
+}
+
+impl Hash for RegexFullMatch {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pattern.hash(state);
+    }
+}
+
+impl Op for RegexFullMatch {
+    fn name(&self) -> Cow<str> {
+        "RegexFullMatch".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("pattern: {:?}", self.pattern)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RegexFullMatch {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let output = input.to_array_view::<String>()?.map(|s| self.regex.is_match(s));
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for RegexFullMatch {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].datum_type == String::datum_type(), "RegexFullMatch expects strings");
+        Ok(tvec!(bool::fact(inputs[0].shape.iter())))
+    }
+
+    fn axes_mapping(
+        &self,
+        inputs: &[&TypedFact],
+        outputs: &[&TypedFact],
+    ) -> TractResult<AxesMapping> {
+        AxesMapping::natural(inputs, outputs)
+    }
+
+    fn change_axes(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        _io: InOut,
+        change: &AxisOp,
+    ) -> TractResult<Option<AxisChangeConsequence>> {
+        Ok(Some(AxisChangeConsequence::new(model, node, None, change)))
+    }
+
+    as_op!();
+}
+
+fn dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &RegexFullMatch,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_regex_full_match",
+        &[input],
+        &[("pattern", string(&op.pattern))],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let pattern: String = invocation.named_arg_as(builder, "pattern")?;
+    builder.wire(RegexFullMatch::new(pattern)?, &[input])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_whole_string() -> TractResult<()> {
+        let op = RegexFullMatch::new("[a-z]+@[a-z]+\\.com|admin")?;
+        let input =
+            tensor1(&["me@mail.com", "me@mail.com.fr", "admin", "administrator"].map(String::from));
+        let output = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*output[0], tensor1(&[true, false, true, false]));
+        Ok(())
+    }
+}
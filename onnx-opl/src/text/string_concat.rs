@@ -0,0 +1,74 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::broadcast::multi_broadcast;
+use tract_nnef::tract_ndarray::Zip;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_string_concat",
+        &[TypeName::String.tensor().named("a"), TypeName::String.tensor().named("b")],
+        &[("output", TypeName::String.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#StringConcat
+///
+/// Inputs are expected to have the same rank (see wire_rank_broadcast).
+#[derive(Clone, Debug, Hash)]
+pub struct StringConcat;
+
+impl Op for StringConcat {
+    fn name(&self) -> Cow<str> {
+        "StringConcat".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for StringConcat {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (a, b) = args_2!(inputs);
+        let shape = multi_broadcast(&[a.shape(), b.shape()])?;
+        let a = a.to_array_view::<String>()?;
+        let b = b.to_array_view::<String>()?;
+        let a = a.broadcast(&*shape).context("Broadcasting first input")?;
+        let b = b.broadcast(&*shape).context("Broadcasting second input")?;
+        let output = Zip::from(&a).and(&b).map_collect(|a, b| format!("{a}{b}"));
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for StringConcat {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs.iter().all(|i| i.datum_type == String::datum_type()),
+            "StringConcat expects strings"
+        );
+        ensure!(inputs[0].rank() == inputs[1].rank(), "StringConcat expects inputs of same rank");
+        let shape = multi_broadcast(&[&inputs[0].shape.to_tvec(), &inputs[1].shape.to_tvec()])?;
+        Ok(tvec!(String::fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    _op: &StringConcat,
+) -> TractResult<Option<Arc<RValue>>> {
+    let a = ast.mapping[&node.inputs[0]].clone();
+    let b = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation("tract_onnx_string_concat", &[a, b], &[])))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let a = invocation.named_arg_as(builder, "a")?;
+    let b = invocation.named_arg_as(builder, "b")?;
+    builder.wire(StringConcat, &[a, b])
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use tract_nnef::internal::*;
+use tract_nnef::tract_num_traits::Zero;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_string_normalizer",
+        &[
+            TypeName::String.tensor().named("input"),
+            TypeName::String.tensor().named("stopwords"),
+            TypeName::String.named("case_change_action").default("NONE"),
+            TypeName::Logical.named("is_case_sensitive").default(false),
+        ],
+        &[("output", TypeName::String.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum CaseChange {
+    Lower,
+    Upper,
+    None,
+}
+
+impl CaseChange {
+    pub fn parse(s: &str) -> TractResult<CaseChange> {
+        Ok(match s {
+            "LOWER" => CaseChange::Lower,
+            "UPPER" => CaseChange::Upper,
+            "NONE" => CaseChange::None,
+            _ => bail!("Unsupported case_change_action {}", s),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseChange::Lower => "LOWER",
+            CaseChange::Upper => "UPPER",
+            CaseChange::None => "NONE",
+        }
+    }
+
+    fn apply(&self, s: &str) -> String {
+        match self {
+            CaseChange::Lower => s.to_lowercase(),
+            CaseChange::Upper => s.to_uppercase(),
+            CaseChange::None => s.to_string(),
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#StringNormalizer
+///
+/// Input is [C] or [1, C]. The number of words left after stop words removal only appears
+/// at runtime.
+#[derive(Clone, Debug, Hash)]
+pub struct StringNormalizer {
+    pub case_change: CaseChange,
+    pub case_sensitive: bool,
+    pub stopwords: Arc<Tensor>,
+    pub len: Symbol,
+}
+
+impl Op for StringNormalizer {
+    fn name(&self) -> Cow<str> {
+        "StringNormalizer".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("case_change: {:?}, case_sensitive: {}", self.case_change, self.case_sensitive),
+            format!("{} stop words", self.stopwords.len()),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for StringNormalizer {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        ensure!(
+            input.rank() == 1 || (input.rank() == 2 && input.shape()[0] == 1),
+            "StringNormalizer expects a [C] or [1, C] input, got {:?}",
+            input.shape()
+        );
+        let fold = |s: &str| if self.case_sensitive { s.to_string() } else { s.to_lowercase() };
+        let stopwords: HashSet<String> =
+            self.stopwords.as_slice::<String>()?.iter().map(|s| fold(s)).collect();
+        let mut words: Vec<String> = input
+            .as_slice::<String>()?
+            .iter()
+            .filter(|s| !stopwords.contains(&fold(s)))
+            .map(|s| self.case_change.apply(s))
+            .collect();
+        if words.is_empty() {
+            words.push(String::new());
+        }
+        let mut shape = tvec!(words.len());
+        if input.rank() == 2 {
+            shape.insert(0, 1);
+        }
+        Ok(tvec!(tract_ndarray::ArrayD::from_shape_vec(&*shape, words)?.into_tvalue()))
+    }
+}
+
+impl TypedOp for StringNormalizer {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].datum_type == String::datum_type(), "StringNormalizer expects strings");
+        ensure!(
+            inputs[0].rank() == 1 || inputs[0].rank() == 2,
+            "StringNormalizer expects a [C] or [1, C] input"
+        );
+        let len = if self.stopwords.len() == 0 && !inputs[0].shape.last().unwrap().is_zero() {
+            inputs[0].shape.last().unwrap().clone()
+        } else {
+            self.len.to_dim()
+        };
+        let mut shape = tvec!(len);
+        if inputs[0].rank() == 2 {
+            shape.insert(0, 1usize.to_dim());
+        }
+        Ok(tvec!(String::fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &StringNormalizer,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let stopwords = ast.konst_variable(format!("{}.stopwords", node.name), &op.stopwords)?;
+    Ok(Some(invocation(
+        "tract_onnx_string_normalizer",
+        &[input, stopwords],
+        &[
+            ("case_change_action", string(op.case_change.as_str())),
+            ("is_case_sensitive", logical(op.case_sensitive)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let stopwords = invocation.named_arg_as(builder, "stopwords")?;
+    let case_change: String = invocation.named_arg_as(builder, "case_change_action")?;
+    let case_sensitive = invocation.named_arg_as(builder, "is_case_sensitive")?;
+    let op = StringNormalizer {
+        case_change: CaseChange::parse(&case_change)?,
+        case_sensitive,
+        stopwords,
+        len: builder.model.symbol_table.new_with_prefix("words"),
+    };
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Tensor {
+        tensor1(&s.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn remove_stopwords_and_upper() -> TractResult<()> {
+        let op = StringNormalizer {
+            case_change: CaseChange::Upper,
+            case_sensitive: false,
+            stopwords: strings(&["the", "a"]).into_arc_tensor(),
+            len: SymbolTable::default().sym("N"),
+        };
+        let output = op.eval(tvec!(strings(&["The", "quick", "fox", "A"]).into_tvalue()))?;
+        assert_eq!(*output[0], strings(&["QUICK", "FOX"]));
+        let output = op.eval(tvec!(strings(&["the"]).into_tvalue()))?;
+        assert_eq!(*output[0], strings(&[""]));
+        Ok(())
+    }
+}
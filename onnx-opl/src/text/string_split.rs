@@ -0,0 +1,160 @@
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_string_split",
+        &[
+            TypeName::String.tensor().named("input"),
+            TypeName::String.named("delimiter").default(""),
+            TypeName::Integer.named("maxsplit").default(-1),
+        ],
+        &[("output", TypeName::String.tensor()), ("lengths", TypeName::Integer.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#StringSplit
+///
+/// Splits like python str.split: on runs of whitespace when there is no delimiter. The
+/// substrings are padded with empty strings on a new last axis, which size only appears at
+/// runtime.
+#[derive(Clone, Debug, Hash)]
+pub struct StringSplit {
+    pub delimiter: Option<String>,
+    pub maxsplit: Option<usize>,
+    pub len: Symbol,
+}
+
+impl StringSplit {
+    fn split<'s>(&self, s: &'s str) -> Vec<&'s str> {
+        match &self.delimiter {
+            Some(delimiter) => match self.maxsplit {
+                Some(maxsplit) => s.splitn(maxsplit + 1, &**delimiter).collect(),
+                None => s.split(&**delimiter).collect(),
+            },
+            None => {
+                let mut parts = vec![];
+                let mut rest = s.trim_start();
+                while !rest.is_empty() {
+                    if self.maxsplit.is_some_and(|maxsplit| parts.len() == maxsplit) {
+                        parts.push(rest);
+                        break;
+                    }
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                    parts.push(&rest[..end]);
+                    rest = rest[end..].trim_start();
+                }
+                parts
+            }
+        }
+    }
+}
+
+impl Op for StringSplit {
+    fn name(&self) -> Cow<str> {
+        "StringSplit".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("delimiter: {:?}, maxsplit: {:?}", self.delimiter, self.maxsplit)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for StringSplit {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let parts: Vec<Vec<&str>> =
+            input.as_slice::<String>()?.iter().map(|s| self.split(s)).collect();
+        let width = parts.iter().map(|p| p.len()).max().unwrap_or(0);
+        let mut shape: TVec<usize> = input.shape().into();
+        let lengths: Vec<i64> = parts.iter().map(|p| p.len() as i64).collect();
+        let lengths = tract_ndarray::ArrayD::from_shape_vec(&*shape, lengths)?;
+        shape.push(width);
+        let mut output = tract_ndarray::ArrayD::<String>::default(&*shape);
+        let output_slice = output.as_slice_mut().unwrap();
+        for (ix, parts) in parts.iter().enumerate() {
+            for (jx, part) in parts.iter().enumerate() {
+                output_slice[ix * width + jx] = part.to_string();
+            }
+        }
+        Ok(tvec!(output.into_tvalue(), lengths.into_tvalue()))
+    }
+}
+
+impl TypedOp for StringSplit {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].datum_type == String::datum_type(), "StringSplit expects strings");
+        let mut shape = inputs[0].shape.to_tvec();
+        let lengths = i64::fact(&*shape);
+        shape.push(self.len.to_dim());
+        Ok(tvec!(String::fact(&*shape), lengths))
+    }
+
+    as_op!();
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &StringSplit) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_string_split",
+        &[input],
+        &[
+            ("delimiter", string(op.delimiter.as_deref().unwrap_or(""))),
+            ("maxsplit", numeric(op.maxsplit.map(|m| m as i64).unwrap_or(-1))),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let delimiter: String = invocation.named_arg_as(builder, "delimiter")?;
+    let maxsplit: i64 = invocation.named_arg_as(builder, "maxsplit")?;
+    let op = StringSplit {
+        delimiter: Some(delimiter).filter(|d| !d.is_empty()),
+        maxsplit: Some(maxsplit).filter(|m| *m >= 0).map(|m| m as usize),
+        len: builder.model.symbol_table.new_with_prefix("splits"),
+    };
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Tensor {
+        tensor1(&s.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn split_on_whitespace() -> TractResult<()> {
+        let op =
+            StringSplit { delimiter: None, maxsplit: None, len: SymbolTable::default().sym("S") };
+        let output = op.eval(tvec!(strings(&["  hello  world ", "", "tract"]).into_tvalue()))?;
+        let expected =
+            tensor2(&[["hello", "world"], ["", ""], ["tract", ""]].map(|r| r.map(String::from)));
+        assert_eq!(*output[0], expected);
+        assert_eq!(*output[1], tensor1(&[2i64, 0, 1]));
+        Ok(())
+    }
+
+    #[test]
+    fn split_on_delimiter_with_maxsplit() -> TractResult<()> {
+        let op = StringSplit {
+            delimiter: Some(",".into()),
+            maxsplit: Some(1),
+            len: SymbolTable::default().sym("S"),
+        };
+        let output = op.eval(tvec!(strings(&["a,b,c", ",", "d"]).into_tvalue()))?;
+        let expected = tensor2(&[["a", "b,c"], ["", ""], ["d", ""]].map(|r| r.map(String::from)));
+        assert_eq!(*output[0], expected);
+        assert_eq!(*output[1], tensor1(&[2i64, 2, 1]));
+        Ok(())
+    }
+}
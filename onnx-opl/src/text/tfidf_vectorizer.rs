@@ -0,0 +1,344 @@
+use std::hash::{Hash, Hasher};
+
+use tract_nnef::internal::*;
+use tract_nnef::ser::ints;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_tfidf_vectorizer",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("pool"),
+            TypeName::Integer.array().named("ngram_counts"),
+            TypeName::Integer.array().named("ngram_indexes"),
+            TypeName::Scalar.tensor().named("weights"),
+            TypeName::Integer.named("min_gram_length"),
+            TypeName::Integer.named("max_gram_length"),
+            TypeName::Integer.named("max_skip_count").default(0),
+            TypeName::String.named("mode"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TfIdfMode {
+    Tf,
+    Idf,
+    TfIdf,
+}
+
+impl TfIdfMode {
+    pub fn parse(s: &str) -> TractResult<TfIdfMode> {
+        Ok(match s {
+            "TF" => TfIdfMode::Tf,
+            "IDF" => TfIdfMode::Idf,
+            "TFIDF" => TfIdfMode::TfIdf,
+            _ => bail!("Unsupported TfIdfVectorizer mode {}", s),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TfIdfMode::Tf => "TF",
+            TfIdfMode::Idf => "IDF",
+            TfIdfMode::TfIdf => "TFIDF",
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#TfIdfVectorizer
+///
+/// Input tokens are mapped to ids (themselves for integers, their position in the pool for
+/// strings) so that n-grams can be looked up in a single table.
+#[derive(Clone, Debug)]
+pub struct TfIdfVectorizer {
+    pub min_gram_length: usize,
+    pub max_gram_length: usize,
+    pub max_skip_count: usize,
+    pub mode: TfIdfMode,
+    pub pool: Arc<Tensor>,
+    pub ngram_counts: TVec<usize>,
+    pub ngram_indexes: TVec<usize>,
+    pub weights: Option<Arc<Tensor>>,
+    vocabulary: HashMap<String, i64>,
+    ngrams: HashMap<TVec<i64>, usize>,
+    output_size: usize,
+}
+
+impl TfIdfVectorizer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        min_gram_length: usize,
+        max_gram_length: usize,
+        max_skip_count: usize,
+        mode: TfIdfMode,
+        pool: Arc<Tensor>,
+        ngram_counts: TVec<usize>,
+        ngram_indexes: TVec<usize>,
+        weights: Option<Arc<Tensor>>,
+    ) -> TractResult<TfIdfVectorizer> {
+        ensure!(
+            0 < min_gram_length && min_gram_length <= max_gram_length,
+            "Invalid n-gram lengths: {}..={}",
+            min_gram_length,
+            max_gram_length
+        );
+        let mut vocabulary = HashMap::<String, i64>::default();
+        let ids: Vec<i64> = if pool.datum_type() == String::datum_type() {
+            pool.as_slice::<String>()?
+                .iter()
+                .map(|s| {
+                    let next = vocabulary.len() as i64;
+                    *vocabulary.entry(s.clone()).or_insert(next)
+                })
+                .collect()
+        } else {
+            pool.cast_to::<i64>()?.as_slice::<i64>()?.to_vec()
+        };
+        let mut ngrams = HashMap::<TVec<i64>, usize>::default();
+        let mut indexes = ngram_indexes.iter();
+        for (ix, start) in ngram_counts.iter().enumerate() {
+            let n = ix + 1;
+            let end = ngram_counts.get(ix + 1).copied().unwrap_or(ids.len());
+            ensure!(
+                *start <= end && end <= ids.len() && (end - start) % n == 0,
+                "Inconsistent ngram_counts {:?} for a pool of {} tokens",
+                ngram_counts,
+                ids.len()
+            );
+            for gram in ids[*start..end].chunks(n) {
+                let output = *indexes.next().context("ngram_indexes is too short")?;
+                ngrams.insert(gram.into(), output);
+            }
+        }
+        let output_size = ngram_indexes.iter().max().map(|m| m + 1).unwrap_or(0);
+        if let Some(weights) = &weights {
+            ensure!(weights.len() >= output_size, "weights is too short");
+        }
+        Ok(TfIdfVectorizer {
+            min_gram_length,
+            max_gram_length,
+            max_skip_count,
+            mode,
+            pool,
+            ngram_counts,
+            ngram_indexes,
+            weights,
+            vocabulary,
+            ngrams,
+            output_size,
+        })
+    }
+
+    fn token_ids(&self, input: &Tensor) -> TractResult<Vec<Option<i64>>> {
+        if input.datum_type() == String::datum_type() {
+            Ok(input
+                .as_slice::<String>()?
+                .iter()
+                .map(|s| self.vocabulary.get(s).copied())
+                .collect())
+        } else {
+            Ok(input.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|i| Some(*i)).collect())
+        }
+    }
+
+    fn count(&self, tokens: &[Option<i64>], counts: &mut [f32]) {
+        for n in self.min_gram_length..=self.max_gram_length {
+            // unigrams are only counted once, skipping makes no sense for them
+            let max_skip = if n == 1 { 0 } else { self.max_skip_count };
+            for skip in 0..=max_skip {
+                let span = (n - 1) * (skip + 1);
+                if span >= tokens.len() {
+                    break;
+                }
+                for start in 0..tokens.len() - span {
+                    let gram: Option<TVec<i64>> =
+                        (0..n).map(|k| tokens[start + k * (skip + 1)]).collect();
+                    if let Some(output) = gram.and_then(|gram| self.ngrams.get(&gram)) {
+                        counts[*output] += 1.0;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Hash for TfIdfVectorizer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.min_gram_length.hash(state);
+        self.max_gram_length.hash(state);
+        self.max_skip_count.hash(state);
+        self.mode.hash(state);
+        self.pool.hash(state);
+        self.ngram_counts.hash(state);
+        self.ngram_indexes.hash(state);
+        self.weights.hash(state);
+    }
+}
+
+impl Op for TfIdfVectorizer {
+    fn name(&self) -> Cow<str> {
+        "TfIdfVectorizer".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!(
+                "mode: {:?}, grams: {}..={}, max_skip: {}",
+                self.mode, self.min_gram_length, self.max_gram_length, self.max_skip_count
+            ),
+            format!("{} n-grams, {} outputs", self.ngrams.len(), self.output_size),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TfIdfVectorizer {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        ensure!(input.rank() == 1 || input.rank() == 2, "TfIdfVectorizer expects [C] or [N, C]");
+        let ids = self.token_ids(&input)?;
+        let row_len = *input.shape().last().unwrap();
+        let rows = if input.rank() == 2 { input.shape()[0] } else { 1 };
+        let mut shape: TVec<usize> = input.shape().into();
+        *shape.last_mut().unwrap() = self.output_size;
+        let mut output = Tensor::zero::<f32>(&shape)?;
+        let output_slice = output.as_slice_mut::<f32>()?;
+        let weights = self.weights.as_ref().map(|w| w.as_slice::<f32>()).transpose()?;
+        for row in 0..rows {
+            let counts = &mut output_slice[row * self.output_size..][..self.output_size];
+            self.count(&ids[row * row_len..][..row_len], counts);
+            for (ix, count) in counts.iter_mut().enumerate() {
+                let weight = weights.map(|w| w[ix]).unwrap_or(1.0);
+                *count = match self.mode {
+                    TfIdfMode::Tf => *count,
+                    TfIdfMode::Idf => {
+                        if *count > 0.0 {
+                            weight
+                        } else {
+                            0.0
+                        }
+                    }
+                    TfIdfMode::TfIdf => *count * weight,
+                };
+            }
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for TfIdfVectorizer {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(
+            inputs[0].rank() == 1 || inputs[0].rank() == 2,
+            "TfIdfVectorizer expects [C] or [N, C]"
+        );
+        ensure!(
+            (inputs[0].datum_type == String::datum_type())
+                == (self.pool.datum_type() == String::datum_type()),
+            "TfIdfVectorizer input and pool should be both strings or both integers"
+        );
+        let mut shape = inputs[0].shape.to_tvec();
+        *shape.last_mut().unwrap() = self.output_size.to_dim();
+        Ok(tvec!(f32::fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &TfIdfVectorizer,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let pool = ast.konst_variable(format!("{}.pool", node.name), &op.pool)?;
+    let mut named_args = vec![
+        ("ngram_counts", ints(&op.ngram_counts)),
+        ("ngram_indexes", ints(&op.ngram_indexes)),
+        ("min_gram_length", numeric(op.min_gram_length)),
+        ("max_gram_length", numeric(op.max_gram_length)),
+        ("max_skip_count", numeric(op.max_skip_count)),
+        ("mode", string(op.mode.as_str())),
+    ];
+    if let Some(weights) = &op.weights {
+        let weights = ast.konst_variable(format!("{}.weights", node.name), weights)?;
+        named_args.push(("weights", (*weights).clone()));
+    }
+    Ok(Some(invocation("tract_onnx_tfidf_vectorizer", &[input, pool], &named_args)))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let pool = invocation.named_arg_as(builder, "pool")?;
+    let ngram_counts = invocation.named_arg_as(builder, "ngram_counts")?;
+    let ngram_indexes = invocation.named_arg_as(builder, "ngram_indexes")?;
+    let weights = invocation.named_arg_as(builder, "weights").ok();
+    let min_gram_length = invocation.named_arg_as(builder, "min_gram_length")?;
+    let max_gram_length = invocation.named_arg_as(builder, "max_gram_length")?;
+    let max_skip_count = invocation.named_arg_as(builder, "max_skip_count")?;
+    let mode: String = invocation.named_arg_as(builder, "mode")?;
+    let op = TfIdfVectorizer::new(
+        min_gram_length,
+        max_gram_length,
+        max_skip_count,
+        TfIdfMode::parse(&mode)?,
+        pool,
+        ngram_counts,
+        ngram_indexes,
+        weights,
+    )?;
+    builder.wire(op, &[input])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // onnx backend test_tfidfvectorizer_tf_batch_onlybigrams_skip5
+    #[test]
+    fn bigrams_with_skips() -> TractResult<()> {
+        let op = TfIdfVectorizer::new(
+            2,
+            2,
+            5,
+            TfIdfMode::Tf,
+            rctensor1(&[2i64, 3, 5, 4, 5, 6, 7, 8, 6, 7]),
+            tvec!(0, 4),
+            tvec!(0, 1, 2, 3, 4, 5, 6),
+            None,
+        )?;
+        let input = tensor2(&[[1i32, 1, 3, 3, 3, 7], [8, 6, 7, 5, 6, 8]]);
+        let output = op.eval(tvec!(input.into_tvalue()))?;
+        let expected = tensor2(&[[0f32, 0., 0., 0., 0., 0., 0.], [0., 0., 0., 0., 1., 1., 1.]]);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn string_unigrams_and_bigrams_tfidf() -> TractResult<()> {
+        let pool = ["a", "b", "a", "b"].map(String::from);
+        let op = TfIdfVectorizer::new(
+            1,
+            2,
+            0,
+            TfIdfMode::TfIdf,
+            rctensor1(&pool),
+            tvec!(0, 2),
+            tvec!(0, 1, 2),
+            Some(rctensor1(&[1f32, 0.5, 2.])),
+        )?;
+        let input = tensor1(&["a", "b", "c", "a", "b", "a"].map(String::from));
+        let output = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*output[0], tensor1(&[3f32, 1., 4.]));
+        Ok(())
+    }
+}
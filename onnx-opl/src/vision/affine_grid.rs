@@ -0,0 +1,138 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{s, Ix3, IxDyn};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_affine_grid",
+        &[
+            TypeName::Scalar.tensor().named("theta"),
+            TypeName::Integer.tensor().named("size"),
+            TypeName::Logical.named("align_corners").default(false),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#AffineGrid
+///
+/// Theta is [N, 2, 3] or [N, 3, 4], size is [N, C, H, W] or [N, C, D, H, W]. The output
+/// is a GridSample grid: [N, H, W, 2] or [N, D, H, W, 3], coordinates in x, y(, z) order.
+#[derive(Clone, Debug, Hash)]
+pub struct AffineGrid {
+    pub align_corners: bool,
+}
+
+impl AffineGrid {
+    fn base(&self, ix: usize, len: usize) -> f32 {
+        if self.align_corners {
+            if len > 1 {
+                -1.0 + 2.0 * ix as f32 / (len - 1) as f32
+            } else {
+                -1.0
+            }
+        } else {
+            -1.0 + (2 * ix + 1) as f32 / len as f32
+        }
+    }
+
+    fn eval_t<T: Datum + Float>(&self, theta: &Tensor, size: &[usize]) -> TractResult<Tensor> {
+        let theta = theta.to_array_view::<T>()?.into_dimensionality::<Ix3>()?;
+        let spatial = &size[2..];
+        let r = spatial.len();
+        let mut shape: TVec<usize> = tvec!(size[0]);
+        shape.extend(spatial.iter().copied());
+        shape.push(r);
+        let output = tract_ndarray::ArrayD::from_shape_fn(IxDyn(&shape), |coords| {
+            let theta = theta.slice(s![coords[0], coords[r + 1], ..]);
+            // base grid point is (x, y(, z), 1), spatial axes being in reverse order
+            let mut value = theta[r];
+            for axis in 0..r {
+                let base = self.base(coords[r - axis], spatial[r - 1 - axis]);
+                value = value + theta[axis] * T::from(base).unwrap();
+            }
+            value
+        });
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for AffineGrid {
+    fn name(&self) -> Cow<str> {
+        "AffineGrid".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("align_corners: {}", self.align_corners)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for AffineGrid {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (theta, size) = args_2!(inputs);
+        let size = size.cast_to::<i64>()?;
+        let size: TVec<usize> = size.as_slice::<i64>()?.iter().map(|d| *d as usize).collect();
+        ensure!(size.len() == 4 || size.len() == 5, "AffineGrid expects a 2D or 3D size");
+        let output = dispatch_floatlike!(Self::eval_t(theta.datum_type())(self, &theta, &size))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for AffineGrid {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 3, "AffineGrid expects a [N, r, r+1] theta");
+        let Some(size) = &inputs[1].konst else {
+            bail!("AffineGrid requires a constant size input")
+        };
+        let size = size.cast_to::<TDim>()?;
+        let size = size.as_slice::<TDim>()?;
+        ensure!(size.len() == 4 || size.len() == 5, "AffineGrid expects a 2D or 3D size");
+        let mut shape: TVec<TDim> = tvec!(inputs[0].shape[0].clone());
+        shape.extend(size[2..].iter().cloned());
+        shape.push((size.len() - 2).to_dim());
+        Ok(tvec!(inputs[0].datum_type.fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &AffineGrid) -> TractResult<Option<Arc<RValue>>> {
+    let theta = ast.mapping[&node.inputs[0]].clone();
+    let size = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_affine_grid",
+        &[theta, size],
+        &[("align_corners", logical(op.align_corners))],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let theta = invocation.named_arg_as(builder, "theta")?;
+    let size = invocation.named_arg_as(builder, "size")?;
+    let op = AffineGrid { align_corners: invocation.named_arg_as(builder, "align_corners")? };
+    builder.wire(op, &[theta, size])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identity_2d() -> TractResult<()> {
+        let op = AffineGrid { align_corners: false };
+        let theta = tensor3(&[[[1f32, 0., 0.], [0., 1., 0.]]]);
+        let size = tensor1(&[1i64, 1, 2, 2]);
+        let output = op.eval(tvec!(theta.into_tvalue(), size.into_tvalue()))?;
+        let expected = tensor4(&[[[[-0.5f32, -0.5], [0.5, -0.5]], [[-0.5, 0.5], [0.5, 0.5]]]]);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+}
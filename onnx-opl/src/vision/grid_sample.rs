@@ -0,0 +1,363 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{s, ArrayViewD, Axis, IxDyn};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_grid_sample",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("grid"),
+            TypeName::String.named("mode").default("linear"),
+            TypeName::String.named("padding_mode").default("zeros"),
+            TypeName::Logical.named("align_corners").default(false),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum InterpolationMode {
+    Nearest,
+    Linear,
+    Cubic,
+}
+
+impl InterpolationMode {
+    /// Accepts both opset 16 ("bilinear", "bicubic") and opset 20 ("linear", "cubic") names.
+    pub fn parse(s: &str) -> TractResult<InterpolationMode> {
+        Ok(match s {
+            "nearest" => InterpolationMode::Nearest,
+            "linear" | "bilinear" => InterpolationMode::Linear,
+            "cubic" | "bicubic" => InterpolationMode::Cubic,
+            _ => bail!("Unsupported interpolation mode {}", s),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterpolationMode::Nearest => "nearest",
+            InterpolationMode::Linear => "linear",
+            InterpolationMode::Cubic => "cubic",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PaddingMode {
+    Zeros,
+    Border,
+    Reflection,
+}
+
+impl PaddingMode {
+    pub fn parse(s: &str) -> TractResult<PaddingMode> {
+        Ok(match s {
+            "zeros" => PaddingMode::Zeros,
+            "border" => PaddingMode::Border,
+            "reflection" => PaddingMode::Reflection,
+            _ => bail!("Unsupported padding mode {}", s),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaddingMode::Zeros => "zeros",
+            PaddingMode::Border => "border",
+            PaddingMode::Reflection => "reflection",
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#GridSample
+///
+/// Input is [N, C, D1, ..., Dr], grid is [N, D1_out, ..., Dr_out, r] with the coordinates
+/// in reverse order (x first). Coordinates are normalized to [-1, 1].
+#[derive(Clone, Debug, Hash)]
+pub struct GridSample {
+    pub mode: InterpolationMode,
+    pub padding_mode: PaddingMode,
+    pub align_corners: bool,
+}
+
+/// Reflect x in [min, max] until it falls in the range.
+fn reflect(x: f32, min: f32, max: f32) -> f32 {
+    let range = max - min;
+    if x < min {
+        let dx = min - x;
+        let n = (dx / range) as usize;
+        let r = dx - n as f32 * range;
+        if n % 2 == 0 {
+            min + r
+        } else {
+            max - r
+        }
+    } else if x > max {
+        let dx = x - max;
+        let n = (dx / range) as usize;
+        let r = dx - n as f32 * range;
+        if n % 2 == 0 {
+            max - r
+        } else {
+            min + r
+        }
+    } else {
+        x
+    }
+}
+
+/// Round half to even, like numpy's rint.
+fn rint(x: f32) -> f32 {
+    let r = x.round();
+    if (r - x).abs() == 0.5 {
+        2.0 * (x / 2.0).round()
+    } else {
+        r
+    }
+}
+
+fn cubic_coeffs(t: f32) -> [f32; 4] {
+    const A: f32 = -0.75;
+    let x = t.abs();
+    [
+        ((A * (x + 1.0) - 5.0 * A) * (x + 1.0) + 8.0 * A) * (x + 1.0) - 4.0 * A,
+        ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0,
+        ((A + 2.0) * (1.0 - x) - (A + 3.0)) * (1.0 - x) * (1.0 - x) + 1.0,
+        ((A * (2.0 - x) - 5.0 * A) * (2.0 - x) + 8.0 * A) * (2.0 - x) - 4.0 * A,
+    ]
+}
+
+impl GridSample {
+    fn borders(&self, len: usize) -> (f32, f32) {
+        if self.align_corners {
+            (0.0, len as f32 - 1.0)
+        } else {
+            (-0.5, len as f32 - 0.5)
+        }
+    }
+
+    fn denormalize(&self, x: f32, len: usize) -> f32 {
+        if self.align_corners {
+            (x + 1.0) / 2.0 * (len as f32 - 1.0)
+        } else {
+            ((x + 1.0) * len as f32 - 1.0) / 2.0
+        }
+    }
+
+    /// Resolves a sampled index along one axis, None standing for a zero padded value.
+    fn index(&self, ix: i64, len: usize) -> Option<usize> {
+        match self.padding_mode {
+            PaddingMode::Zeros => (ix >= 0 && ix < len as i64).then_some(ix as usize),
+            PaddingMode::Border => Some(ix.clamp(0, len as i64 - 1) as usize),
+            PaddingMode::Reflection => {
+                let (min, max) = self.borders(len);
+                Some((reflect(ix as f32, min, max) as i64).clamp(0, len as i64 - 1) as usize)
+            }
+        }
+    }
+
+    /// Per-axis list of (index, weight) taps for a denormalized coordinate.
+    fn taps(&self, x: f32, len: usize) -> TVec<(Option<usize>, f32)> {
+        let (min, max) = self.borders(len);
+        let mut x = if self.mode == InterpolationMode::Nearest { rint(x) } else { x };
+        if x < min || x > max {
+            match self.padding_mode {
+                PaddingMode::Border => x = x.clamp(0.0, len as f32 - 1.0),
+                PaddingMode::Reflection => x = reflect(x, min, max),
+                PaddingMode::Zeros => (),
+            }
+        }
+        match self.mode {
+            InterpolationMode::Nearest => tvec!((self.index(x as i64, len), 1.0)),
+            InterpolationMode::Linear => {
+                let x0 = x.floor();
+                let t = x - x0;
+                tvec!((self.index(x0 as i64, len), 1.0 - t), (self.index(x0 as i64 + 1, len), t))
+            }
+            InterpolationMode::Cubic => {
+                let x0 = x.floor();
+                let coeffs = cubic_coeffs(x - x0);
+                (0..4).map(|i| (self.index(x0 as i64 - 1 + i, len), coeffs[i as usize])).collect()
+            }
+        }
+    }
+
+    fn sample<T: Datum + Float>(
+        &self,
+        data: &ArrayViewD<T>,
+        taps: &[TVec<(Option<usize>, f32)>],
+    ) -> T {
+        let mut value = T::zero();
+        let mut position: TVec<usize> = tvec![0; taps.len()];
+        let mut cursor = tvec![0; taps.len()];
+        'taps: loop {
+            let mut weight = 1.0;
+            let mut padded = false;
+            for (axis, tap) in cursor.iter().enumerate() {
+                let (ix, w) = taps[axis][*tap];
+                weight *= w;
+                match ix {
+                    Some(ix) => position[axis] = ix,
+                    None => padded = true,
+                }
+            }
+            if !padded && weight != 0.0 {
+                value = value + T::from(weight).unwrap() * data[&*position];
+            }
+            for axis in (0..cursor.len()).rev() {
+                cursor[axis] += 1;
+                if cursor[axis] < taps[axis].len() {
+                    continue 'taps;
+                }
+                cursor[axis] = 0;
+            }
+            break;
+        }
+        value
+    }
+
+    fn eval_t<T: Datum + Float>(&self, input: &Tensor, grid: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let grid = grid.cast_to::<f32>()?;
+        let grid = grid.to_array_view::<f32>()?;
+        let (n, c) = (input.shape()[0], input.shape()[1]);
+        let spatial = &input.shape()[2..];
+        let grid_spatial = grid.shape()[1..grid.ndim() - 1].to_vec();
+        let points = grid_spatial.iter().product::<usize>();
+        let grid = grid.into_shape((n, points, spatial.len()))?;
+        let mut output = tract_ndarray::Array3::<T>::zeros((n, c, points));
+        for b in 0..n {
+            let batch = input.index_axis(Axis(0), b);
+            for p in 0..points {
+                let taps: TVec<TVec<(Option<usize>, f32)>> = grid
+                    .slice(s![b, p, ..])
+                    .iter()
+                    .rev()
+                    .zip(spatial)
+                    .map(|(x, len)| self.taps(self.denormalize(*x, *len), *len))
+                    .collect();
+                for ch in 0..c {
+                    output[(b, ch, p)] = self.sample(&batch.index_axis(Axis(0), ch), &taps);
+                }
+            }
+        }
+        let mut shape: TVec<usize> = tvec!(n, c);
+        shape.extend(grid_spatial.iter().copied());
+        Ok(output.into_shape(IxDyn(&shape))?.into_tensor())
+    }
+}
+
+impl Op for GridSample {
+    fn name(&self) -> Cow<str> {
+        "GridSample".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "mode: {:?}, padding_mode: {:?}, align_corners: {}",
+            self.mode, self.padding_mode, self.align_corners
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for GridSample {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, grid) = args_2!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, &input, &grid))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for GridSample {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let rank = inputs[0].rank();
+        ensure!(rank >= 3, "GridSample expects a [N, C, D1, ..., Dr] input");
+        ensure!(inputs[1].rank() == rank, "GridSample expects a [N, D1, ..., Dr, r] grid");
+        ensure!(
+            inputs[1].shape[rank - 1] == (rank - 2).to_dim(),
+            "GridSample grid last axis must be {}",
+            rank - 2
+        );
+        let mut shape: TVec<TDim> = inputs[0].shape[0..2].into();
+        shape.extend(inputs[1].shape[1..rank - 1].iter().cloned());
+        Ok(tvec!(inputs[0].datum_type.fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &GridSample) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let grid = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_grid_sample",
+        &[input, grid],
+        &[
+            ("mode", string(op.mode.as_str())),
+            ("padding_mode", string(op.padding_mode.as_str())),
+            ("align_corners", logical(op.align_corners)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let grid = invocation.named_arg_as(builder, "grid")?;
+    let mode: String = invocation.named_arg_as(builder, "mode")?;
+    let padding_mode: String = invocation.named_arg_as(builder, "padding_mode")?;
+    let op = GridSample {
+        mode: InterpolationMode::parse(&mode)?,
+        padding_mode: PaddingMode::parse(&padding_mode)?,
+        align_corners: invocation.named_arg_as(builder, "align_corners")?,
+    };
+    builder.wire(op, &[input, grid])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample(op: GridSample, grid: Tensor) -> TractResult<Tensor> {
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let output = op.eval(tvec!(input.into_tvalue(), grid.into_tvalue()))?;
+        Ok(output[0].clone().into_tensor())
+    }
+
+    #[test]
+    fn bilinear_padding() -> TractResult<()> {
+        let grid = tensor4(&[[[[0f32, 0.], [-1., -1.]]]]);
+        let zeros = GridSample {
+            mode: InterpolationMode::Linear,
+            padding_mode: PaddingMode::Zeros,
+            align_corners: false,
+        };
+        assert_eq!(sample(zeros, grid.clone())?, tensor4(&[[[[2.5f32, 0.25]]]]));
+        let border = GridSample {
+            mode: InterpolationMode::Linear,
+            padding_mode: PaddingMode::Border,
+            align_corners: false,
+        };
+        assert_eq!(sample(border, grid)?, tensor4(&[[[[2.5f32, 1.0]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn nearest_align_corners() -> TractResult<()> {
+        let op = GridSample {
+            mode: InterpolationMode::Nearest,
+            padding_mode: PaddingMode::Zeros,
+            align_corners: true,
+        };
+        let grid = tensor4(&[[[[1f32, -1.], [-1., 1.]]]]);
+        assert_eq!(sample(op, grid)?, tensor4(&[[[[2f32, 3.]]]]));
+        Ok(())
+    }
+}
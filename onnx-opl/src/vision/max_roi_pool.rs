@@ -0,0 +1,166 @@
+use tract_nnef::internal::*;
+use tract_nnef::ser::ints;
+use tract_nnef::tract_ndarray::{s, Array4, Axis, Ix2, Ix4};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_max_roi_pool",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("rois"),
+            TypeName::Integer.array().named("pooled_shape"),
+            TypeName::Scalar.named("spatial_scale").default(1.0),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#MaxRoiPool
+///
+/// Input is [N, C, H, W], rois is [R, 5] as (batch_index, x1, y1, x2, y2). Output is
+/// [R, C, pooled_height, pooled_width]. Empty bins produce zeros.
+#[derive(Clone, Debug)]
+pub struct MaxRoiPool {
+    pub pooled_shape: (usize, usize),
+    pub spatial_scale: f32,
+}
+
+impl std::hash::Hash for MaxRoiPool {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.pooled_shape.hash(state);
+        self.spatial_scale.to_bits().hash(state);
+    }
+}
+
+impl MaxRoiPool {
+    fn eval_t<T: Datum + Float>(&self, input: &Tensor, rois: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let rois = rois.cast_to::<f32>()?;
+        let rois = rois.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let (channels, height, width) = (input.shape()[1], input.shape()[2], input.shape()[3]);
+        let (ph, pw) = self.pooled_shape;
+        let mut output = Array4::<T>::zeros((rois.shape()[0], channels, ph, pw));
+        for (roi_ix, roi) in rois.outer_iter().enumerate() {
+            let batch = roi[0] as usize;
+            ensure!(batch < input.shape()[0], "MaxRoiPool batch index {} out of bounds", batch);
+            let data = input.index_axis(Axis(0), batch);
+            let [start_w, start_h, end_w, end_h] =
+                [roi[1], roi[2], roi[3], roi[4]].map(|x| (x * self.spatial_scale).round() as i64);
+            let roi_height = (end_h - start_h + 1).max(1) as f32;
+            let roi_width = (end_w - start_w + 1).max(1) as f32;
+            let bin_h = roi_height / ph as f32;
+            let bin_w = roi_width / pw as f32;
+            let bin = |ix: usize, bin: f32, start: i64, len: usize| {
+                let from = (ix as f32 * bin).floor() as i64 + start;
+                let to = ((ix + 1) as f32 * bin).ceil() as i64 + start;
+                (from.clamp(0, len as i64) as usize, to.clamp(0, len as i64) as usize)
+            };
+            for y in 0..ph {
+                let (h_from, h_to) = bin(y, bin_h, start_h, height);
+                for x in 0..pw {
+                    let (w_from, w_to) = bin(x, bin_w, start_w, width);
+                    if h_to <= h_from || w_to <= w_from {
+                        continue;
+                    }
+                    for c in 0..channels {
+                        output[(roi_ix, c, y, x)] = data
+                            .slice(s![c, h_from..h_to, w_from..w_to])
+                            .iter()
+                            .copied()
+                            .reduce(T::max)
+                            .unwrap();
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for MaxRoiPool {
+    fn name(&self) -> Cow<str> {
+        "MaxRoiPool".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "pooled_shape: {:?}, spatial_scale: {}",
+            self.pooled_shape, self.spatial_scale
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for MaxRoiPool {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, rois) = args_2!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, &input, &rois))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for MaxRoiPool {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 4, "MaxRoiPool expects a [N, C, H, W] input");
+        ensure!(inputs[1].rank() == 2, "MaxRoiPool expects [R, 5] rois");
+        let shape = tvec!(
+            inputs[1].shape[0].clone(),
+            inputs[0].shape[1].clone(),
+            self.pooled_shape.0.to_dim(),
+            self.pooled_shape.1.to_dim()
+        );
+        Ok(tvec!(inputs[0].datum_type.fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &MaxRoiPool) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let rois = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_max_roi_pool",
+        &[input, rois],
+        &[
+            ("pooled_shape", ints(&[op.pooled_shape.0, op.pooled_shape.1])),
+            ("spatial_scale", numeric(op.spatial_scale)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let pooled_shape: TVec<usize> = invocation.named_arg_as(builder, "pooled_shape")?;
+    ensure!(pooled_shape.len() == 2, "MaxRoiPool expects a 2D pooled_shape");
+    let op = MaxRoiPool {
+        pooled_shape: (pooled_shape[0], pooled_shape[1]),
+        spatial_scale: invocation.named_arg_as(builder, "spatial_scale")?,
+    };
+    builder.wire(op, &[input, rois])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_itertools::Itertools;
+
+    #[test]
+    fn max_roi_pool_2x2() -> TractResult<()> {
+        let input = Tensor::from_shape(&[1, 1, 4, 4], &(0..16).map(|x| x as f32).collect_vec())?;
+        let rois = tensor2(&[[0f32, 0., 0., 3., 3.], [0., 1., 1., 2., 2.]]);
+        let op = MaxRoiPool { pooled_shape: (2, 2), spatial_scale: 1.0 };
+        let output = op.eval(tvec!(input.into_tvalue(), rois.into_tvalue()))?;
+        let expected = tensor4(&[[[[5f32, 7.], [13., 15.]]], [[[5., 6.], [9., 10.]]]]);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use tract_nnef::internal::*;
+
+pub mod affine_grid;
+pub mod grid_sample;
+pub mod max_roi_pool;
+pub mod roi_align;
+
+pub use affine_grid::AffineGrid;
+pub use grid_sample::{GridSample, InterpolationMode, PaddingMode};
+pub use max_roi_pool::MaxRoiPool;
+pub use roi_align::{RoiAlign, RoiAlignMode};
+
+pub fn register(registry: &mut Registry) {
+    affine_grid::register(registry);
+    grid_sample::register(registry);
+    max_roi_pool::register(registry);
+    roi_align::register(registry);
+}
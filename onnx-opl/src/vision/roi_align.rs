@@ -0,0 +1,292 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{s, Array4, Axis, Ix2, Ix4};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_roi_align",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("rois"),
+            TypeName::Integer.tensor().named("batch_indices"),
+            TypeName::String.named("mode").default("avg"),
+            TypeName::Integer.named("output_height").default(1),
+            TypeName::Integer.named("output_width").default(1),
+            TypeName::Integer.named("sampling_ratio").default(0),
+            TypeName::Scalar.named("spatial_scale").default(1.0),
+            TypeName::Logical.named("half_pixel").default(true),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum RoiAlignMode {
+    Avg,
+    Max,
+}
+
+impl RoiAlignMode {
+    pub fn parse(s: &str) -> TractResult<RoiAlignMode> {
+        Ok(match s {
+            "avg" => RoiAlignMode::Avg,
+            "max" => RoiAlignMode::Max,
+            _ => bail!("Unsupported RoiAlign mode {}", s),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoiAlignMode::Avg => "avg",
+            RoiAlignMode::Max => "max",
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#RoiAlign
+///
+/// Input is [N, C, H, W], rois is [R, 4] as (x1, y1, x2, y2), batch_indices is [R]. Output
+/// is [R, C, output_height, output_width]. half_pixel is false for the opset 10 behaviour
+/// ("output_half_pixel" coordinate transformation mode).
+#[derive(Clone, Debug)]
+pub struct RoiAlign {
+    pub mode: RoiAlignMode,
+    pub output_height: usize,
+    pub output_width: usize,
+    pub sampling_ratio: usize,
+    pub spatial_scale: f32,
+    pub half_pixel: bool,
+}
+
+impl std::hash::Hash for RoiAlign {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.mode.hash(state);
+        self.output_height.hash(state);
+        self.output_width.hash(state);
+        self.sampling_ratio.hash(state);
+        self.spatial_scale.to_bits().hash(state);
+        self.half_pixel.hash(state);
+    }
+}
+
+/// A bilinear sample: four positions in the feature map and their weights.
+struct Sample {
+    positions: [(usize, usize); 4],
+    weights: [f32; 4],
+}
+
+fn bilinear_sample(y: f32, x: f32, height: usize, width: usize) -> Sample {
+    if y < -1.0 || y > height as f32 || x < -1.0 || x > width as f32 {
+        return Sample { positions: [(0, 0); 4], weights: [0.0; 4] };
+    }
+    let (y, x) = (y.max(0.0), x.max(0.0));
+    let (y_low, y_high, y) = if y as usize >= height - 1 {
+        (height - 1, height - 1, (height - 1) as f32)
+    } else {
+        (y as usize, y as usize + 1, y)
+    };
+    let (x_low, x_high, x) = if x as usize >= width - 1 {
+        (width - 1, width - 1, (width - 1) as f32)
+    } else {
+        (x as usize, x as usize + 1, x)
+    };
+    let (ly, lx) = (y - y_low as f32, x - x_low as f32);
+    let (hy, hx) = (1.0 - ly, 1.0 - lx);
+    Sample {
+        positions: [(y_low, x_low), (y_low, x_high), (y_high, x_low), (y_high, x_high)],
+        weights: [hy * hx, hy * lx, ly * hx, ly * lx],
+    }
+}
+
+impl RoiAlign {
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        rois: &Tensor,
+        batch_indices: &Tensor,
+    ) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let rois = rois.cast_to::<f32>()?;
+        let rois = rois.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let batch_indices = batch_indices.cast_to::<i64>()?;
+        let batch_indices = batch_indices.as_slice::<i64>()?;
+        let (channels, height, width) = (input.shape()[1], input.shape()[2], input.shape()[3]);
+        let (oh, ow) = (self.output_height, self.output_width);
+        let mut output = Array4::<T>::zeros((rois.shape()[0], channels, oh, ow));
+        let offset = if self.half_pixel { 0.5 } else { 0.0 };
+        for (roi_ix, roi) in rois.outer_iter().enumerate() {
+            let roi: TVec<f32> = roi.iter().map(|x| x * self.spatial_scale - offset).collect();
+            let (start_w, start_h) = (roi[0], roi[1]);
+            let (mut roi_width, mut roi_height) = (roi[2] - roi[0], roi[3] - roi[1]);
+            if !self.half_pixel {
+                roi_width = roi_width.max(1.0);
+                roi_height = roi_height.max(1.0);
+            }
+            let bin_h = roi_height / oh as f32;
+            let bin_w = roi_width / ow as f32;
+            let grid_h =
+                if self.sampling_ratio > 0 { self.sampling_ratio } else { bin_h.ceil() as usize };
+            let grid_w =
+                if self.sampling_ratio > 0 { self.sampling_ratio } else { bin_w.ceil() as usize };
+            let count = (grid_h * grid_w).max(1) as f32;
+            let batch = batch_indices[roi_ix] as usize;
+            ensure!(batch < input.shape()[0], "RoiAlign batch index {} out of bounds", batch);
+            let data = input.index_axis(Axis(0), batch);
+            for ph in 0..oh {
+                for pw in 0..ow {
+                    let samples: Vec<Sample> = (0..grid_h)
+                        .flat_map(|iy| (0..grid_w).map(move |ix| (iy, ix)))
+                        .map(|(iy, ix)| {
+                            let y = start_h
+                                + ph as f32 * bin_h
+                                + (iy as f32 + 0.5) * bin_h / grid_h as f32;
+                            let x = start_w
+                                + pw as f32 * bin_w
+                                + (ix as f32 + 0.5) * bin_w / grid_w as f32;
+                            bilinear_sample(y, x, height, width)
+                        })
+                        .collect();
+                    for c in 0..channels {
+                        let plane = data.slice(s![c, .., ..]);
+                        let values = samples.iter().map(|sample| {
+                            let mut values = sample
+                                .positions
+                                .iter()
+                                .zip(sample.weights.iter())
+                                .map(|(p, w)| plane[*p] * T::from(*w).unwrap());
+                            match self.mode {
+                                RoiAlignMode::Avg => values.fold(T::zero(), |a, b| a + b),
+                                RoiAlignMode::Max => {
+                                    let first = values.next().unwrap();
+                                    values.fold(first, T::max)
+                                }
+                            }
+                        });
+                        output[(roi_ix, c, ph, pw)] = match self.mode {
+                            RoiAlignMode::Avg => {
+                                values.fold(T::zero(), |a, b| a + b) / T::from(count).unwrap()
+                            }
+                            RoiAlignMode::Max => values.reduce(T::max).unwrap_or(T::zero()),
+                        };
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for RoiAlign {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!(
+                "mode: {:?}, output: {}x{}, sampling_ratio: {}",
+                self.mode, self.output_height, self.output_width, self.sampling_ratio
+            ),
+            format!("spatial_scale: {}, half_pixel: {}", self.spatial_scale, self.half_pixel),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiAlign {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, rois, batch_indices) = args_3!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(
+            self,
+            &input,
+            &rois,
+            &batch_indices
+        ))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for RoiAlign {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 4, "RoiAlign expects a [N, C, H, W] input");
+        ensure!(inputs[1].rank() == 2, "RoiAlign expects [R, 4] rois");
+        let shape = tvec!(
+            inputs[1].shape[0].clone(),
+            inputs[0].shape[1].clone(),
+            self.output_height.to_dim(),
+            self.output_width.to_dim()
+        );
+        Ok(tvec!(inputs[0].datum_type.fact(&*shape)))
+    }
+
+    as_op!();
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &RoiAlign) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let rois = ast.mapping[&node.inputs[1]].clone();
+    let batch_indices = ast.mapping[&node.inputs[2]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_roi_align",
+        &[input, rois, batch_indices],
+        &[
+            ("mode", string(op.mode.as_str())),
+            ("output_height", numeric(op.output_height)),
+            ("output_width", numeric(op.output_width)),
+            ("sampling_ratio", numeric(op.sampling_ratio)),
+            ("spatial_scale", numeric(op.spatial_scale)),
+            ("half_pixel", logical(op.half_pixel)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let batch_indices = invocation.named_arg_as(builder, "batch_indices")?;
+    let mode: String = invocation.named_arg_as(builder, "mode")?;
+    let op = RoiAlign {
+        mode: RoiAlignMode::parse(&mode)?,
+        output_height: invocation.named_arg_as::<i64>(builder, "output_height")? as usize,
+        output_width: invocation.named_arg_as::<i64>(builder, "output_width")? as usize,
+        sampling_ratio: invocation.named_arg_as::<i64>(builder, "sampling_ratio")? as usize,
+        spatial_scale: invocation.named_arg_as(builder, "spatial_scale")?,
+        half_pixel: invocation.named_arg_as(builder, "half_pixel")?,
+    };
+    builder.wire(op, &[input, rois, batch_indices])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roi_align_modes() -> TractResult<()> {
+        let inputs = || {
+            tvec!(
+                tensor4(&[[[[1f32, 2.], [3., 4.]]]]).into_tvalue(),
+                tensor2(&[[0f32, 0., 1., 1.]]).into_tvalue(),
+                tensor1(&[0i64]).into_tvalue()
+            )
+        };
+        let mut op = RoiAlign {
+            mode: RoiAlignMode::Avg,
+            output_height: 1,
+            output_width: 1,
+            sampling_ratio: 1,
+            spatial_scale: 1.0,
+            half_pixel: false,
+        };
+        assert_eq!(*op.eval(inputs())?[0], tensor4(&[[[[2.5f32]]]]));
+        op.mode = RoiAlignMode::Max;
+        assert_eq!(*op.eval(inputs())?[0], tensor4(&[[[[1f32]]]]));
+        Ok(())
+    }
+}
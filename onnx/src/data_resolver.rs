@@ -1,7 +1,9 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tract_hir::internal::*;
+use tract_nnef::mmap::MappedFile;
 
 use tract_hir::internal::TractResult;
 
@@ -23,6 +25,21 @@ pub trait ModelDataResolver {
         offset: usize,
         length: Option<usize>,
     ) -> TractResult<()>;
+
+    /// Build a tensor from external data. The default reads the bytes and copies them in the
+    /// tensor, resolvers able to lend memory to the tensor override it.
+    fn tensor_from_path(
+        &self,
+        dt: DatumType,
+        shape: &[usize],
+        p: &Path,
+        offset: usize,
+        length: Option<usize>,
+    ) -> TractResult<Tensor> {
+        let mut buf = vec![];
+        self.read_bytes_from_path(&mut buf, p, offset, length)?;
+        crate::tensor::create_tensor(shape.to_vec(), dt, &buf)
+    }
 }
 
 pub struct FopenDataResolver;
@@ -73,3 +90,75 @@ impl ModelDataResolver for MmapDataResolver {
         Ok(())
     }
 }
+
+/// Maps each external data file once, and builds tensors borrowing their data from the
+/// mapping: constant weights are not copied until something (packing, typically) needs them.
+///
+/// External data files must not be truncated while the model is alive.
+#[derive(Default)]
+pub struct SharedMmapDataResolver {
+    mappings: Mutex<HashMap<PathBuf, MappedFile>>,
+}
+
+impl SharedMmapDataResolver {
+    fn region(&self, p: &Path, offset: usize, length: Option<usize>) -> TractResult<MappedFile> {
+        let mut mappings = self.mappings.lock().unwrap();
+        if !mappings.contains_key(p) {
+            mappings.insert(p.to_path_buf(), MappedFile::open(p)?);
+        }
+        let mapped = &mappings[p];
+        let end = length.map(|l| offset + l).unwrap_or(mapped.len());
+        mapped.slice(offset..end)
+    }
+}
+
+impl ModelDataResolver for SharedMmapDataResolver {
+    fn read_bytes_from_path(
+        &self,
+        buf: &mut Vec<u8>,
+        p: &Path,
+        offset: usize,
+        length: Option<usize>,
+    ) -> TractResult<()> {
+        buf.extend_from_slice(self.region(p, offset, length)?.as_bytes());
+        Ok(())
+    }
+
+    fn tensor_from_path(
+        &self,
+        dt: DatumType,
+        shape: &[usize],
+        p: &Path,
+        offset: usize,
+        length: Option<usize>,
+    ) -> TractResult<Tensor> {
+        let region = self.region(p, offset, length)?;
+        if dt == DatumType::Bool || !dt.is_copy() {
+            crate::tensor::create_tensor(shape.to_vec(), dt, region.as_bytes())
+        } else {
+            unsafe { region.tensor(dt, shape) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared_mmap_tensors() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let path = dir.path().join("weights.bin");
+        let data: Vec<u8> =
+            [1f32, 2., 3., 4., 5., 6.].iter().flat_map(|x| x.to_le_bytes()).collect();
+        std::fs::write(&path, &data)?;
+        let resolver = SharedMmapDataResolver::default();
+        let a = resolver.tensor_from_path(f32::datum_type(), &[2], &path, 0, Some(8))?;
+        let b = resolver.tensor_from_path(f32::datum_type(), &[2, 2], &path, 8, None)?;
+        let c = FopenDataResolver.tensor_from_path(f32::datum_type(), &[2, 2], &path, 8, None)?;
+        assert_eq!(a, tensor1(&[1f32, 2.]));
+        assert_eq!(b, c);
+        assert_eq!(resolver.mappings.lock().unwrap().len(), 1);
+        Ok(())
+    }
+}
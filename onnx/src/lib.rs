@@ -18,8 +18,9 @@ pub mod pb {
     include!("prost/onnx.rs");
 }
 
-pub mod pb_helpers;
 pub mod data_resolver;
+pub mod pb_helpers;
+pub mod ser;
 pub mod tensor;
 
 pub use model::Onnx;
@@ -47,5 +48,7 @@ pub fn for_reader<R: std::io::Read>(mut r: R) -> TractResult<InferenceModel> {
 pub fn onnx() -> Onnx {
     let mut ops = crate::model::OnnxOpRegister::default();
     ops::register_all_ops(&mut ops);
-    Onnx { op_register: ops, ..Onnx::default() }
+    let mut ser_register = ser::OnnxSerRegister::default();
+    ser::register_all_ops(&mut ser_register);
+    Onnx { op_register: ops, ser_register, ..Onnx::default() }
 }
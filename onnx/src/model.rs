@@ -9,7 +9,8 @@ use tract_hir::prelude::tract_itertools::Itertools;
 use crate::data_resolver::{self, ModelDataResolver};
 use crate::pb::type_proto::Value;
 use crate::pb::{self, TensorProto, TypeProto};
-use crate::tensor::{load_tensor, translate_inference_fact};
+use crate::ser::OnnxSerRegister;
+use crate::tensor::{load_tensor, translate_inference_fact, translate_type_proto};
 use prost::Message;
 
 pub fn optional_inputs(pb: &pb::NodeProto) -> impl Iterator<Item = Option<usize>> + '_ {
@@ -85,14 +86,9 @@ impl<'a> ParsingContext<'a> {
                 let id = model.add_const(input.name.to_owned(), init)?;
                 outlets_by_name.insert(input.name.to_owned(), id);
             } else {
-                let fact = input.r#type.as_ref().unwrap().value.as_ref().unwrap();
-                #[allow(irrefutable_let_patterns)]
-                let fact: InferenceFact = if let pb::type_proto::Value::TensorType(fact) = fact {
-                    translate_inference_fact(&ctx, fact, true)
-                        .with_context(|| format!("translating to fact: {:?}", fact))?
-                } else {
-                    bail!("Can not parse tensor type");
-                };
+                let fact = input.r#type.as_ref().context("Missing input type")?;
+                let fact: InferenceFact = translate_type_proto(&ctx, fact, true)
+                    .with_context(|| format!("translating to fact: {:?}", fact))?;
                 trace!("Input: {} is a source ({:?})", input.name, fact);
                 let id = model.add_source(&*input.name, fact)?;
                 outlets_by_name.insert(input.name.to_owned(), id);
@@ -171,9 +167,8 @@ impl<'a> ParsingContext<'a> {
         for output in graph.output.iter() {
             let mut fact = InferenceFact::default();
             if self.framework.use_output_shapes {
-                if let Some(f) = output.r#type.as_ref().and_then(|t| t.value.as_ref()) {
-                    let pb::type_proto::Value::TensorType(f) = f;
-                    fact = translate_inference_fact(&ctx, f, false)?
+                if let Some(f) = output.r#type.as_ref().filter(|t| t.value.is_some()) {
+                    fact = translate_type_proto(&ctx, f, false)?
                 };
             }
             if self.framework.ignore_output_types {
@@ -220,6 +215,9 @@ pub struct Onnx {
     pub use_output_shapes: bool,
     pub ignore_output_types: bool,
     pub provider: Arc<dyn ModelDataResolver + Send + Sync>,
+    pub ser_register: OnnxSerRegister,
+    pub export_opset: i64,
+    pub external_data_threshold: Option<usize>,
 }
 
 impl Default for Onnx {
@@ -229,6 +227,9 @@ impl Default for Onnx {
             use_output_shapes: Default::default(),
             ignore_output_types: Default::default(),
             provider: Arc::new(data_resolver::MmapDataResolver),
+            ser_register: Default::default(),
+            export_opset: crate::ser::DEFAULT_EXPORT_OPSET,
+            external_data_threshold: None,
         }
     }
 }
@@ -277,6 +278,13 @@ impl Onnx {
         Self { ignore_output_types: ignore, ..self }
     }
 
+    pub fn with_data_resolver(
+        self,
+        provider: impl ModelDataResolver + Send + Sync + 'static,
+    ) -> Onnx {
+        Self { provider: Arc::new(provider), ..self }
+    }
+
     pub fn determinize(model: &mut InferenceModel) -> TractResult<()> {
         use crate::ops::multinomial::Multinomial;
         for node in model.nodes_mut() {
@@ -0,0 +1,312 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::binary::wire_with_rank_broadcast;
+use tract_onnx_opl::ml::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LabelEncoder", label_encoder);
+    reg.insert("OneHotEncoder", one_hot_encoder);
+}
+
+fn typed_attr(node: &NodeProto, prefix: &str) -> TractResult<Option<Arc<Tensor>>> {
+    let strings = node.get_attr_opt_tvec::<String>(&format!("{prefix}_strings"))?;
+    let ints = node.get_attr_opt_slice::<i64>(&format!("{prefix}_int64s"))?;
+    let floats = node.get_attr_opt_slice::<f32>(&format!("{prefix}_floats"))?;
+    match (strings, ints, floats) {
+        (Some(strings), None, None) => Ok(Some(rctensor1(&strings))),
+        (None, Some(ints), None) => Ok(Some(rctensor1(ints))),
+        (None, None, Some(floats)) => Ok(Some(rctensor1(floats))),
+        (None, None, None) => Ok(None),
+        _ => bail!(
+            "only one of '{prefix}_strings', '{prefix}_int64s' and '{prefix}_floats' can be set"
+        ),
+    }
+}
+
+fn label_encoder(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let default_int64 = node.get_attr_opt("default_int64")?.unwrap_or(-1i64);
+    let default_string =
+        node.get_attr_opt::<String>("default_string")?.unwrap_or_else(|| "_Unused".to_string());
+    // version 1 maps classes to their index, or the other way around for integer inputs
+    if let Some(classes) = node.get_attr_opt_tvec::<String>("classes_strings")? {
+        let op = LabelEncoderV1 { classes: rctensor1(&classes), default_int64, default_string };
+        return Ok((expand(op), vec![]));
+    }
+    let keys = node.expect_ok_or_else(typed_attr(node, "keys")?, "keys attribute")?;
+    let values = node.expect_ok_or_else(typed_attr(node, "values")?, "values attribute")?;
+    node.expect(keys.len() == values.len(), "as many keys as values")?;
+    node.expect(keys.datum_type() != f32::datum_type(), "non-float keys")?;
+    let default = match values.datum_type() {
+        DatumType::String => rctensor0(default_string),
+        DatumType::I64 => rctensor0(default_int64),
+        _ => rctensor0(node.get_attr_opt("default_float")?.unwrap_or(-0f32)),
+    };
+    Ok((expand(LabelEncoder { keys, values, default }), vec![]))
+}
+
+fn one_hot_encoder(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let ints = node.get_attr_opt_slice::<i64>("cats_int64s")?;
+    let strings = node.get_attr_opt_tvec::<String>("cats_strings")?;
+    let categories = match (ints, strings) {
+        (Some(ints), None) => rctensor1(ints),
+        (None, Some(strings)) => rctensor1(&strings),
+        _ => bail!("OneHotEncoder requires exactly one of cats_int64s and cats_strings"),
+    };
+    let zeros = node.get_attr_opt("zeros")?.unwrap_or(true);
+    Ok((expand(OneHotEncoder { categories, zeros }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LabelEncoder {
+    keys: Arc<Tensor>,
+    values: Arc<Tensor>,
+    default: Arc<Tensor>,
+}
+
+impl Expansion for LabelEncoder {
+    fn name(&self) -> Cow<str> {
+        "LabelEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.equals(&inputs[0].datum_type, self.keys.datum_type())?;
+        s.equals(&outputs[0].datum_type, self.values.datum_type())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wire = model.wire_node(
+            format!("{prefix}.reverse"),
+            ReverseLookup::new(self.keys.clone(), -1)?,
+            inputs,
+        )?;
+        model.wire_node(
+            format!("{prefix}.direct"),
+            DirectLookup::new(self.values.clone(), self.default.clone())?,
+            &wire,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LabelEncoderV1 {
+    classes: Arc<Tensor>,
+    default_int64: i64,
+    default_string: String,
+}
+
+impl LabelEncoderV1 {
+    fn oriented(&self, input_dt: DatumType) -> LabelEncoder {
+        let indices: Vec<i64> = (0..self.classes.len() as i64).collect();
+        if input_dt == DatumType::String {
+            LabelEncoder {
+                keys: self.classes.clone(),
+                values: rctensor1(&indices),
+                default: rctensor0(self.default_int64),
+            }
+        } else {
+            LabelEncoder {
+                keys: rctensor1(&indices),
+                values: self.classes.clone(),
+                default: rctensor0(self.default_string.clone()),
+            }
+        }
+    }
+}
+
+impl Expansion for LabelEncoderV1 {
+    fn name(&self) -> Cow<str> {
+        "LabelEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.given(&inputs[0].datum_type, move |s, dt| {
+            let output_dt =
+                if dt == DatumType::String { DatumType::I64 } else { DatumType::String };
+            s.equals(&outputs[0].datum_type, output_dt)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input_dt = model.outlet_fact(inputs[0])?.datum_type;
+        self.oriented(input_dt).wire(prefix, model, inputs)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct OneHotEncoder {
+    categories: Arc<Tensor>,
+    /// encode unknown categories as zeros instead of failing
+    zeros: bool,
+}
+
+impl Expansion for OneHotEncoder {
+    fn name(&self) -> Cow<str> {
+        "OneHotEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(inputs[0].rank.bex() + 1, &outputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            for axis in 0..rank {
+                s.equals(&inputs[0].shape[axis], &outputs[0].shape[axis])?;
+            }
+            s.equals(&outputs[0].shape[rank], self.categories.len().to_dim())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut input = inputs[0];
+        let categories_dt = self.categories.datum_type();
+        if model.outlet_fact(input)?.datum_type != categories_dt {
+            input = model.wire_node(
+                format!("{prefix}.cast"),
+                tract_core::ops::cast::cast(categories_dt),
+                &[input],
+            )?[0];
+        }
+        let rank = model.outlet_fact(input)?.rank();
+        let lookup = if self.zeros {
+            ReverseLookup::new(self.categories.clone(), -1)?
+        } else {
+            ReverseLookup::strict(self.categories.clone())?
+        };
+        let index = model.wire_node(format!("{prefix}.reverse"), lookup, &[input])?;
+        let index = model.wire_node(format!("{prefix}.add_axis"), AxisOp::Add(rank), &index)?;
+        let range: Vec<i32> = (0..self.categories.len() as i32).collect();
+        let range = model.add_const(format!("{prefix}.range"), rctensor1(&range))?;
+        let one_hot = wire_with_rank_broadcast(
+            format!("{prefix}.one_hot"),
+            model,
+            tract_core::ops::logic::equals(),
+            &[index[0], range],
+        )?;
+        model.wire_node(
+            format!("{prefix}.as_f32"),
+            tract_core::ops::cast::cast(f32::datum_type()),
+            &one_hot,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+    use super::*;
+
+    fn strings(s: &[&str]) -> Tensor {
+        tensor1(&s.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn label_encoder_strings_to_ints() -> TractResult<()> {
+        let attributes = vec![
+            attr_strings("keys_strings", &["a", "b"]),
+            attr_ints("values_int64s", &[1, 2]),
+            attr_int("default_int64", -7),
+        ];
+        let outputs = run_node("LabelEncoder", attributes, tvec!(strings(&["b", "x", "a"])), 1)?;
+        assert_eq!(*outputs[0], tensor1(&[2i64, -7, 1]));
+        Ok(())
+    }
+
+    #[test]
+    fn label_encoder_ints_to_floats() -> TractResult<()> {
+        let attributes = vec![
+            attr_ints("keys_int64s", &[3, 4]),
+            attr_floats("values_floats", &[0.5, 1.5]),
+            attr_float("default_float", 9.),
+        ];
+        let outputs = run_node("LabelEncoder", attributes, tvec!(tensor1(&[4i64, 0, 3])), 1)?;
+        assert_eq!(*outputs[0], tensor1(&[1.5f32, 9., 0.5]));
+        Ok(())
+    }
+
+    #[test]
+    fn label_encoder_rejects_float_keys() {
+        let attributes = vec![attr_floats("keys_floats", &[0.5]), attr_ints("values_int64s", &[1])];
+        assert!(run_node("LabelEncoder", attributes, tvec!(tensor1(&[0.5f32])), 1).is_err());
+    }
+
+    #[test]
+    fn label_encoder_v1_both_ways() -> TractResult<()> {
+        let attributes = || vec![attr_strings("classes_strings", &["x", "y"])];
+        let outputs = run_node("LabelEncoder", attributes(), tvec!(strings(&["y", "z"])), 1)?;
+        assert_eq!(*outputs[0], tensor1(&[1i64, -1]));
+        let outputs = run_node("LabelEncoder", attributes(), tvec!(tensor1(&[0i64, 5])), 1)?;
+        assert_eq!(*outputs[0], strings(&["x", "_Unused"]));
+        Ok(())
+    }
+
+    #[test]
+    fn one_hot_encoder() -> TractResult<()> {
+        let attributes = vec![attr_ints("cats_int64s", &[3, 5, 7])];
+        let outputs = run_node("OneHotEncoder", attributes, tvec!(tensor1(&[5i64, 4])), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[0f32, 1., 0.], [0., 0., 0.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn one_hot_encoder_casts_input() -> TractResult<()> {
+        let attributes = vec![attr_ints("cats_int64s", &[3, 5, 7])];
+        let outputs = run_node("OneHotEncoder", attributes, tvec!(tensor1(&[7f32])), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[0f32, 0., 1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn one_hot_encoder_without_zeros() -> TractResult<()> {
+        let attributes = || vec![attr_strings("cats_strings", &["a", "b"]), attr_int("zeros", 0)];
+        let outputs = run_node("OneHotEncoder", attributes(), tvec!(strings(&["b"])), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[0f32, 1.]]));
+        assert!(run_node("OneHotEncoder", attributes(), tvec!(strings(&["c"])), 1).is_err());
+        Ok(())
+    }
+}
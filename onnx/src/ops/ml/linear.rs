@@ -0,0 +1,237 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::array::TypedConcat;
+use tract_hir::tract_core::ops::einsum::EinSum;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LinearClassifier", linear_classifier);
+    reg.insert("LinearRegressor", linear_regressor);
+}
+
+fn parse_coefficients(
+    node: &NodeProto,
+    n_rows: Option<usize>,
+) -> TractResult<(Arc<Tensor>, Option<Arc<Tensor>>)> {
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let intercepts: Option<Vec<f32>> = node.get_attr_opt_vec("intercepts")?;
+    let n_rows = intercepts.as_ref().map(|i| i.len()).or(n_rows).unwrap_or(1);
+    node.expect_attr("coefficients", n_rows > 0 && coefficients.len() % n_rows == 0, || {
+        format!("a multiple of {n_rows} values, got {}", coefficients.len())
+    })?;
+    let n_features = coefficients.len() / n_rows;
+    let coefficients = tensor1(&coefficients).into_shape(&[n_rows, n_features])?.into_arc_tensor();
+    Ok((coefficients, intercepts.map(|i| rctensor1(&i))))
+}
+
+fn linear_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node)?;
+    let (coefficients, intercepts) = parse_coefficients(node, Some(class_labels.len()))?;
+    let n_rows = coefficients.shape()[0];
+    node.expect(
+        n_rows == class_labels.len() || (n_rows == 1 && class_labels.len() == 2),
+        "one row of coefficients per class",
+    )?;
+    let post_transform = post_transform(node)?;
+    Ok((
+        expand(LinearClassifier { coefficients, intercepts, class_labels, post_transform }),
+        vec![],
+    ))
+}
+
+fn linear_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let targets = node.get_attr_opt("targets")?.unwrap_or(1);
+    let (coefficients, intercepts) = parse_coefficients(node, Some(targets))?;
+    node.expect(coefficients.shape()[0] == targets, "one row of coefficients per target")?;
+    let post_transform = post_transform(node)?;
+    Ok((expand(LinearRegressor { coefficients, intercepts, post_transform }), vec![]))
+}
+
+/// Computes x.coefficientsᵀ + intercepts, as [N, rows].
+fn wire_linear(
+    prefix: &str,
+    model: &mut TypedModel,
+    input: OutletId,
+    coefficients: &Arc<Tensor>,
+    intercepts: Option<&Arc<Tensor>>,
+) -> TractResult<OutletId> {
+    let input = wire_as_f32(prefix, model, input)?;
+    let coefficients = model.add_const(format!("{prefix}.coefficients"), coefficients.clone())?;
+    let mut wire = model.wire_node(
+        format!("{prefix}.matmul"),
+        EinSum::new("nf,cf->nc".parse()?, f32::datum_type()),
+        &[input, coefficients],
+    )?[0];
+    if let Some(intercepts) = intercepts {
+        wire = wire_add_vector(&format!("{prefix}.intercepts"), model, wire, intercepts)?;
+    }
+    Ok(wire)
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LinearClassifier {
+    coefficients: Arc<Tensor>,
+    intercepts: Option<Arc<Tensor>>,
+    class_labels: Arc<Tensor>,
+    post_transform: Option<PostTransform>,
+}
+
+impl Expansion for LinearClassifier {
+    fn name(&self) -> Cow<str> {
+        "LinearClassifier".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].datum_type, self.class_labels.datum_type())?;
+        s.equals(&outputs[1].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 1)?;
+        s.equals(&outputs[1].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[1], self.class_labels.len().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut scores =
+            wire_linear(prefix, model, inputs[0], &self.coefficients, self.intercepts.as_ref())?;
+        if self.coefficients.shape()[0] == 1 && self.class_labels.len() == 2 {
+            // binary classifier: a positive score is a vote for the second class
+            let negated = model.wire_node(
+                format!("{prefix}.negated"),
+                tract_core::ops::math::neg(),
+                &[scores],
+            )?[0];
+            scores = model.wire_node(
+                format!("{prefix}.binary"),
+                TypedConcat::new(1),
+                &[negated, scores],
+            )?[0];
+        }
+        let scores = wire_post_transform(prefix, model, tvec!(scores), self.post_transform)?;
+        let labels = wire_argmax_labels(prefix, model, scores[0], &self.class_labels)?;
+        Ok(tvec!(labels, scores[0]))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LinearRegressor {
+    coefficients: Arc<Tensor>,
+    intercepts: Option<Arc<Tensor>>,
+    post_transform: Option<PostTransform>,
+}
+
+impl Expansion for LinearRegressor {
+    fn name(&self) -> Cow<str> {
+        "LinearRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.coefficients.shape()[0].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let targets =
+            wire_linear(prefix, model, inputs[0], &self.coefficients, self.intercepts.as_ref())?;
+        wire_post_transform(prefix, model, tvec!(targets), self.post_transform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+    use super::*;
+
+    #[test]
+    fn regressor_with_intercepts() -> TractResult<()> {
+        let attributes = vec![
+            attr_int("targets", 2),
+            attr_floats("coefficients", &[1., 2., 3., 4.]),
+            attr_floats("intercepts", &[0.5, -1.]),
+        ];
+        let input = tensor2(&[[1f32, 1.], [2., 0.]]);
+        let outputs = run_node("LinearRegressor", attributes, tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[3.5f32, 6.], [2.5, 5.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn classifier_picks_best_score() -> TractResult<()> {
+        let attributes = vec![
+            attr_ints("classlabels_int64s", &[7, 8, 9]),
+            attr_floats("coefficients", &[1., 0., 0., 1., -1., -1.]),
+        ];
+        let input = tensor2(&[[2f32, 0.], [0., 2.], [-2., -2.]]);
+        let outputs = run_node("LinearClassifier", attributes, tvec!(input), 2)?;
+        assert_eq!(*outputs[0], tensor1(&[7i64, 8, 9]));
+        assert_eq!(*outputs[1], tensor2(&[[2f32, 0., -2.], [0., 2., -2.], [-2., -2., 4.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn binary_classifier_from_one_row() -> TractResult<()> {
+        let attributes = vec![
+            attr_strings("classlabels_strings", &["no", "yes"]),
+            attr_floats("coefficients", &[1., -1.]),
+            attr_floats("intercepts", &[0.]),
+        ];
+        let input = tensor2(&[[1f32, 0.], [0., 1.]]);
+        let outputs = run_node("LinearClassifier", attributes, tvec!(input), 2)?;
+        assert_eq!(*outputs[0], tensor1(&["yes".to_string(), "no".to_string()]));
+        assert_eq!(*outputs[1], tensor2(&[[-1f32, 1.], [1., -1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_ragged_coefficients() {
+        let attributes = vec![
+            attr_ints("classlabels_int64s", &[0, 1, 2]),
+            attr_floats("coefficients", &[1., 2., 3., 4.]),
+        ];
+        let input = tensor2(&[[1f32, 1.]]);
+        assert!(run_node("LinearClassifier", attributes, tvec!(input), 2).is_err());
+    }
+}
@@ -1,9 +1,214 @@
 mod category_mapper;
+mod encoders;
+mod linear;
+mod preprocessing;
+mod svm;
 mod tree_ensemble_classifier;
+mod zip_map;
 
 use crate::model::OnnxOpRegister;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::nn::{Reduce, Reducer};
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     category_mapper::register_all_ops(reg);
+    encoders::register_all_ops(reg);
+    linear::register_all_ops(reg);
+    preprocessing::register_all_ops(reg);
+    svm::register_all_ops(reg);
     tree_ensemble_classifier::register_all_ops(reg);
+    zip_map::register_all_ops(reg);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostTransform {
+    Softmax,
+    Logistic,
+    // SoftmaxZero,
+    // Probit, // probit, especially multinomial, is p.i.t.a. - so let's ignore it for now
+}
+
+pub fn parse_post_transform(s: &str) -> TractResult<Option<PostTransform>> {
+    match s {
+        "NONE" => Ok(None),
+        "SOFTMAX" => Ok(Some(PostTransform::Softmax)),
+        "LOGISTIC" => Ok(Some(PostTransform::Logistic)),
+        "PROBIT" | "SOFTMAX_ZERO" => bail!("PROBIT and SOFTMAX_ZERO unsupported"),
+        _ => bail!("Invalid post transform: {}", s),
+    }
+}
+
+fn post_transform(node: &NodeProto) -> TractResult<Option<PostTransform>> {
+    Ok(node.get_attr_opt("post_transform")?.map(parse_post_transform).transpose()?.flatten())
+}
+
+fn parse_class_labels(node: &NodeProto) -> TractResult<Arc<Tensor>> {
+    let ints = node.get_attr_opt_slice::<i64>("classlabels_int64s")?;
+    let strs = node.get_attr_opt_tvec::<&str>("classlabels_strings")?;
+    match (ints, strs) {
+        (Some(n), None) => Ok(rctensor1(n)),
+        (None, Some(n)) => Ok(rctensor1(&n.iter().map(|d| d.to_string()).collect::<Vec<_>>())),
+        (None, None) => {
+            bail!("cannot find neither 'classlabels_int64s' not 'classlabels_strings'")
+        }
+        (Some(_), Some(_)) => {
+            bail!("only one of 'classlabels_int64s' and 'classlabels_strings' can be set")
+        }
+    }
+}
+
+fn wire_as_f32(prefix: &str, model: &mut TypedModel, input: OutletId) -> TractResult<OutletId> {
+    if model.outlet_fact(input)?.datum_type == f32::datum_type() {
+        return Ok(input);
+    }
+    Ok(model.wire_node(
+        format!("{prefix}.as_f32"),
+        tract_core::ops::cast::cast(f32::datum_type()),
+        &[input],
+    )?[0])
+}
+
+fn wire_post_transform(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: TVec<OutletId>,
+    post_transform: Option<PostTransform>,
+) -> TractResult<TVec<OutletId>> {
+    match post_transform {
+        None => Ok(scores),
+        Some(PostTransform::Softmax) => tract_hir::ops::nn::LayerSoftmax::new(1, false).wire(
+            &format!("{prefix}.softmax"),
+            model,
+            &scores,
+        ),
+        Some(PostTransform::Logistic) => {
+            model.wire_node(format!("{prefix}.logistic"), tract_core::ops::nn::sigmoid(), &scores)
+        }
+    }
+}
+
+/// Looks up the labels of class indices (i32).
+fn wire_class_labels(
+    prefix: &str,
+    model: &mut TypedModel,
+    class_ix: OutletId,
+    class_labels: &Arc<Tensor>,
+) -> TractResult<OutletId> {
+    // indices are always in range: the fallback is never used, but strings have no zero
+    let fallback = if class_labels.datum_type() == String::datum_type() {
+        rctensor0(String::new())
+    } else {
+        Tensor::zero_dt(class_labels.datum_type(), &[])?.into_arc_tensor()
+    };
+    Ok(model.wire_node(
+        format!("{prefix}.labels"),
+        tract_onnx_opl::ml::DirectLookup::new(class_labels.clone(), fallback)?,
+        &[class_ix],
+    )?[0])
+}
+
+/// Looks up the labels of the best scoring classes of [N, C] scores.
+fn wire_argmax_labels(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: OutletId,
+    class_labels: &Arc<Tensor>,
+) -> TractResult<OutletId> {
+    let winners = model.wire_node(
+        format!("{prefix}.argmax"),
+        Reduce::new(tvec!(1), Reducer::ArgMax(false)),
+        &[scores],
+    )?;
+    let reduced = model.wire_node(
+        format!("{prefix}.rm_axis"),
+        tract_core::ops::change_axes::AxisOp::Rm(1),
+        &winners,
+    )?;
+    let casted = model.wire_node(
+        format!("{prefix}.casted"),
+        tract_core::ops::cast::cast(i32::datum_type()),
+        &reduced,
+    )?;
+    wire_class_labels(prefix, model, casted[0], class_labels)
+}
+
+/// Adds a [C] vector to [N, C] scores.
+fn wire_add_vector(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: OutletId,
+    vector: &Arc<Tensor>,
+) -> TractResult<OutletId> {
+    let vector = (**vector).clone().broadcast_into_rank(2)?.into_arc_tensor();
+    let vector = model.add_const(format!("{prefix}.const"), vector)?;
+    Ok(model.wire_node(prefix, tract_core::ops::math::add(), &[scores, vector])?[0])
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::model::ParsingContext;
+    use crate::pb::attribute_proto::AttributeType;
+    use crate::pb::{AttributeProto, ModelProto};
+    pub(crate) use crate::ser::{attr_float, attr_int, attr_ints, attr_string};
+
+    pub(crate) fn attr_floats(name: &str, floats: &[f32]) -> AttributeProto {
+        AttributeProto {
+            name: name.into(),
+            r#type: AttributeType::Floats as i32,
+            floats: floats.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn attr_strings(name: &str, strings: &[&str]) -> AttributeProto {
+        AttributeProto {
+            name: name.into(),
+            r#type: AttributeType::Strings as i32,
+            strings: strings.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads a single node and runs it, optimized, on the given inputs.
+    pub(crate) fn run_node(
+        op_type: &str,
+        attribute: Vec<AttributeProto>,
+        inputs: TVec<Tensor>,
+        outputs: usize,
+    ) -> TractResult<TVec<TValue>> {
+        let onnx = crate::onnx();
+        let proto = ModelProto::default();
+        let ctx = ParsingContext {
+            onnx_operator_set_version: 18,
+            framework: &onnx,
+            model: &proto,
+            parent_graphs: vec![],
+            model_dir: None,
+            symbol_table: SymbolTable::default(),
+        };
+        let node = NodeProto {
+            op_type: op_type.to_string(),
+            domain: "ai.onnx.ml".to_string(),
+            input: (0..inputs.len()).map(|ix| format!("input.{ix}")).collect(),
+            output: (0..outputs).map(|ix| format!("output.{ix}")).collect(),
+            attribute,
+            ..Default::default()
+        };
+        let (op, _) = (onnx.op_register.0[op_type])(&ctx, &node)?;
+        let mut model = InferenceModel::default();
+        let sources = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, t)| {
+                let fact = InferenceFact::from(TypedFact::shape_and_dt_of(t));
+                model.add_source(format!("input.{ix}"), fact)
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let wires = model.wire_node(op_type, op, &sources)?;
+        model.set_output_outlets(&wires)?;
+        let model = model.into_optimized()?.into_runnable()?;
+        model.run(inputs.into_iter().map(|t| t.into_tvalue()).collect())
+    }
 }
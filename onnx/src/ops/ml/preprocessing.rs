@@ -0,0 +1,321 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::binary::wire_with_rank_broadcast;
+use tract_hir::tract_core::ops::logic::Iff;
+use tract_hir::tract_core::ops::{logic, math};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Binarizer", binarizer);
+    reg.insert("Imputer", imputer);
+    reg.insert("Normalizer", normalizer);
+    reg.insert("Scaler", scaler);
+}
+
+fn binarizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let threshold = node.get_attr_opt("threshold")?.unwrap_or(0.0);
+    Ok((expand(Binarizer { threshold }), vec![]))
+}
+
+fn imputer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let floats: Option<Vec<f32>> = node.get_attr_opt_vec("imputed_value_floats")?;
+    let ints: Option<Vec<i64>> = node.get_attr_opt_vec("imputed_value_int64s")?;
+    let (imputed, replaced) = match (floats, ints) {
+        (Some(floats), None) => (
+            rctensor1(&floats),
+            rctensor0(node.get_attr_opt("replaced_value_float")?.unwrap_or(0f32)),
+        ),
+        (None, Some(ints)) => (
+            rctensor1(&ints),
+            rctensor0(node.get_attr_opt("replaced_value_int64")?.unwrap_or(0i64)),
+        ),
+        _ => bail!("Imputer requires exactly one of imputed_value_floats and imputed_value_int64s"),
+    };
+    node.expect(imputed.len() > 0, "at least one imputed value")?;
+    Ok((expand(Imputer { imputed, replaced }), vec![]))
+}
+
+fn normalizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let norm = match node.get_attr_opt("norm")?.unwrap_or("MAX") {
+        "MAX" => Norm::Max,
+        "L1" => Norm::L1,
+        "L2" => Norm::L2,
+        other => bail!("Invalid norm for Normalizer: {}", other),
+    };
+    Ok((expand(Normalizer { norm }), vec![]))
+}
+
+fn scaler(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let offset: Vec<f32> = node.get_attr_opt_vec("offset")?.unwrap_or_else(|| vec![0.0]);
+    let scale: Vec<f32> = node.get_attr_opt_vec("scale")?.unwrap_or_else(|| vec![1.0]);
+    Ok((expand(Scaler { offset: rctensor1(&offset), scale: rctensor1(&scale) }), vec![]))
+}
+
+#[derive(Debug, Clone)]
+struct Binarizer {
+    threshold: f32,
+}
+
+impl Expansion for Binarizer {
+    fn name(&self) -> Cow<str> {
+        "Binarizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let dt = model.outlet_fact(inputs[0])?.datum_type;
+        let threshold = tensor0(self.threshold).cast_to_dt(dt)?.into_owned();
+        let threshold = model.add_const(format!("{prefix}.threshold"), threshold)?;
+        let greater = wire_with_rank_broadcast(
+            format!("{prefix}.greater"),
+            model,
+            logic::greater(),
+            &[inputs[0], threshold],
+        )?;
+        model.wire_node(format!("{prefix}.cast"), tract_core::ops::cast::cast(dt), &greater)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Imputer {
+    imputed: Arc<Tensor>,
+    replaced: Arc<Tensor>,
+}
+
+impl Expansion for Imputer {
+    fn name(&self) -> Cow<str> {
+        "Imputer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let dt = model.outlet_fact(inputs[0])?.datum_type;
+        let missing = if self.replaced.to_scalar::<f32>().is_ok_and(|r| r.is_nan()) {
+            model.wire_node(
+                format!("{prefix}.is_nan"),
+                tract_onnx_opl::is_nan::is_nan(),
+                &[inputs[0]],
+            )?[0]
+        } else {
+            let replaced = self.replaced.cast_to_dt(dt)?.into_owned();
+            let replaced = model.add_const(format!("{prefix}.replaced"), replaced)?;
+            wire_with_rank_broadcast(
+                format!("{prefix}.is_missing"),
+                model,
+                logic::equals(),
+                &[inputs[0], replaced],
+            )?[0]
+        };
+        let imputed = self.imputed.cast_to_dt(dt)?.into_owned();
+        let imputed = model.add_const(format!("{prefix}.imputed"), imputed)?;
+        wire_with_rank_broadcast(prefix, model, Iff, &[missing, imputed, inputs[0]])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Norm {
+    Max,
+    L1,
+    L2,
+}
+
+#[derive(Debug, Clone)]
+struct Normalizer {
+    norm: Norm,
+}
+
+impl Expansion for Normalizer {
+    fn name(&self) -> Cow<str> {
+        "Normalizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input = wire_as_f32(prefix, model, inputs[0])?;
+        let axis = model.outlet_fact(input)?.rank() - 1;
+        let reduce = |reducer| Reduce::new(tvec!(axis), reducer);
+        let norm = match self.norm {
+            Norm::Max => {
+                model.wire_node(format!("{prefix}.max"), reduce(Reducer::Max), &[input])?[0]
+            }
+            Norm::L1 => {
+                let abs = model.wire_node(format!("{prefix}.abs"), math::abs(), &[input])?;
+                model.wire_node(format!("{prefix}.l1"), reduce(Reducer::Sum), &abs)?[0]
+            }
+            Norm::L2 => {
+                let square =
+                    model.wire_node(format!("{prefix}.square"), math::square(), &[input])?;
+                let sum =
+                    model.wire_node(format!("{prefix}.sum"), reduce(Reducer::Sum), &square)?;
+                model.wire_node(format!("{prefix}.l2"), math::sqrt(), &sum)?[0]
+            }
+        };
+        model.wire_node(prefix, math::div(), &[input, norm])
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Scaler {
+    offset: Arc<Tensor>,
+    scale: Arc<Tensor>,
+}
+
+impl Expansion for Scaler {
+    fn name(&self) -> Cow<str> {
+        "Scaler".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input = wire_as_f32(prefix, model, inputs[0])?;
+        let offset = model.add_const(format!("{prefix}.offset"), self.offset.clone())?;
+        let scale = model.add_const(format!("{prefix}.scale"), self.scale.clone())?;
+        let centered = wire_with_rank_broadcast(
+            format!("{prefix}.sub"),
+            model,
+            math::sub(),
+            &[input, offset],
+        )?;
+        wire_with_rank_broadcast(prefix, model, math::mul(), &[centered[0], scale])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+    use super::*;
+
+    #[test]
+    fn binarizer() -> TractResult<()> {
+        let attributes = vec![attr_float("threshold", 0.5)];
+        let input = tensor1(&[0.2f32, 0.7, 0.5]);
+        let outputs = run_node("Binarizer", attributes, tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor1(&[0f32, 1., 0.]));
+        Ok(())
+    }
+
+    #[test]
+    fn imputer_replaces_nans() -> TractResult<()> {
+        let attributes = vec![
+            attr_floats("imputed_value_floats", &[1., 2.]),
+            attr_float("replaced_value_float", f32::NAN),
+        ];
+        let input = tensor2(&[[f32::NAN, 3.], [4., f32::NAN]]);
+        let outputs = run_node("Imputer", attributes, tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[1f32, 3.], [4., 2.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn imputer_replaces_ints() -> TractResult<()> {
+        let attributes = vec![attr_ints("imputed_value_int64s", &[9])];
+        let input = tensor1(&[0i64, 1, 0]);
+        let outputs = run_node("Imputer", attributes, tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor1(&[9i64, 1, 9]));
+        Ok(())
+    }
+
+    #[test]
+    fn normalizer() -> TractResult<()> {
+        for (norm, input, expected) in [
+            ("MAX", [1f32, 2., 4.], [0.25f32, 0.5, 1.]),
+            ("L1", [1., -3., 0.], [0.25, -0.75, 0.]),
+            ("L2", [3., 0., 4.], [0.6, 0., 0.8]),
+        ] {
+            let attributes = vec![attr_string("norm", norm)];
+            let outputs = run_node("Normalizer", attributes, tvec!(tensor2(&[input])), 1)?;
+            outputs[0].close_enough(&tensor2(&[expected]), Approximation::Close)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn scaler() -> TractResult<()> {
+        let attributes = vec![attr_floats("offset", &[1., 2.]), attr_floats("scale", &[2., 0.5])];
+        let input = tensor2(&[[3i64, 6], [1, 2]]);
+        let outputs = run_node("Scaler", attributes, tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[4f32, 2.], [0., 0.]]));
+        Ok(())
+    }
+}
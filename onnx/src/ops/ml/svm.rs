@@ -0,0 +1,314 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::svm::{SvmKernel, SvmKernelType};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("SVMClassifier", svm_classifier);
+    reg.insert("SVMRegressor", svm_regressor);
+}
+
+fn parse_kernel(node: &NodeProto) -> TractResult<SvmKernel> {
+    let kernel_type = node.get_attr_opt("kernel_type")?.unwrap_or("LINEAR");
+    let params: Vec<f32> = node.get_attr_opt_vec("kernel_params")?.unwrap_or_default();
+    node.expect_attr(
+        "kernel_params",
+        params.is_empty() || params.len() == 3,
+        "gamma, coef0, degree",
+    )?;
+    let param = |ix: usize| params.get(ix).copied().unwrap_or(0.0);
+    Ok(SvmKernel {
+        kernel_type: SvmKernelType::parse(kernel_type)?,
+        gamma: param(0),
+        coef0: param(1),
+        degree: param(2),
+    })
+}
+
+fn svm_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node)?;
+    let n_classes = class_labels.len();
+    let kernel = parse_kernel(node)?;
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let support_vectors: Vec<f32> = node.get_attr_opt_vec("support_vectors")?.unwrap_or_default();
+    let vectors_per_class: TVec<usize> =
+        node.get_attr_opt_tvec("vectors_per_class")?.unwrap_or_default();
+    let rho: Option<Vec<f32>> = node.get_attr_opt_vec("rho")?;
+    let prob_a: Option<Vec<f32>> = node.get_attr_opt_vec("prob_a")?.filter(|p| !p.is_empty());
+    let prob_b: Option<Vec<f32>> = node.get_attr_opt_vec("prob_b")?.filter(|p| !p.is_empty());
+    let n_pairs = n_classes * n_classes.saturating_sub(1) / 2;
+    let op = if vectors_per_class.is_empty() || support_vectors.is_empty() {
+        node.expect_attr(
+            "coefficients",
+            n_classes > 0 && coefficients.len() % n_classes == 0,
+            "one row of weights per class",
+        )?;
+        let n_features = coefficients.len() / n_classes;
+        let rho = rho.unwrap_or_else(|| vec![0.0; n_classes]);
+        node.expect_attr("rho", rho.len() == n_classes, "one value per class")?;
+        tract_onnx_opl::ml::svm::SvmClassifier {
+            kernel,
+            n_classes,
+            support_vectors: Tensor::zero::<f32>(&[0, 0])?.into_arc_tensor(),
+            vectors_per_class: tvec!(),
+            coefficients: tensor1(&coefficients)
+                .into_shape(&[n_classes, n_features])?
+                .into_arc_tensor(),
+            rho: rctensor1(&rho),
+            prob_a: None,
+            prob_b: None,
+        }
+    } else {
+        node.expect_attr(
+            "vectors_per_class",
+            vectors_per_class.len() == n_classes,
+            "one value per class",
+        )?;
+        let n_vectors: usize = vectors_per_class.iter().sum();
+        node.expect_attr(
+            "support_vectors",
+            support_vectors.len() % n_vectors == 0,
+            "a whole number of vectors",
+        )?;
+        let n_features = support_vectors.len() / n_vectors;
+        node.expect_attr(
+            "coefficients",
+            coefficients.len() == (n_classes - 1) * n_vectors,
+            "(classes - 1) x vectors values",
+        )?;
+        let rho = node.expect_ok_or_else(rho, "attribute 'rho'")?;
+        node.expect_attr("rho", rho.len() == n_pairs, "one value per pair of classes")?;
+        for (name, prob) in [("prob_a", &prob_a), ("prob_b", &prob_b)] {
+            if let Some(prob) = prob {
+                node.expect_attr(name, prob.len() == n_pairs, "one value per pair of classes")?;
+            }
+        }
+        node.expect(prob_a.is_some() == prob_b.is_some(), "both or none of prob_a and prob_b")?;
+        tract_onnx_opl::ml::svm::SvmClassifier {
+            kernel,
+            n_classes,
+            support_vectors: tensor1(&support_vectors)
+                .into_shape(&[n_vectors, n_features])?
+                .into_arc_tensor(),
+            vectors_per_class,
+            coefficients: tensor1(&coefficients)
+                .into_shape(&[n_classes - 1, n_vectors])?
+                .into_arc_tensor(),
+            rho: rctensor1(&rho),
+            prob_a: prob_a.map(|p| rctensor1(&p)),
+            prob_b: prob_b.map(|p| rctensor1(&p)),
+        }
+    };
+    let post_transform = post_transform(node)?;
+    Ok((expand(SvmClassifier { op, class_labels, post_transform }), vec![]))
+}
+
+fn svm_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let kernel = parse_kernel(node)?;
+    let coefficients: Vec<f32> = node.get_attr_vec("coefficients")?;
+    let support_vectors: Vec<f32> = node.get_attr_opt_vec("support_vectors")?.unwrap_or_default();
+    let n_supports: usize = node.get_attr_opt("n_supports")?.unwrap_or(0);
+    let rho: Vec<f32> = node.get_attr_opt_vec("rho")?.unwrap_or_else(|| vec![0.0]);
+    node.expect_attr("rho", rho.len() == 1, "a single value")?;
+    let one_class = node.get_attr_opt("one_class")?.unwrap_or(false);
+    let support_vectors = if n_supports > 0 {
+        node.expect_attr(
+            "coefficients",
+            coefficients.len() == n_supports,
+            "one value per support vector",
+        )?;
+        node.expect_attr(
+            "support_vectors",
+            support_vectors.len() % n_supports == 0,
+            "a whole number of vectors",
+        )?;
+        let n_features = support_vectors.len() / n_supports;
+        tensor1(&support_vectors).into_shape(&[n_supports, n_features])?.into_arc_tensor()
+    } else {
+        Tensor::zero::<f32>(&[0, 0])?.into_arc_tensor()
+    };
+    let op = tract_onnx_opl::ml::svm::SvmRegressor {
+        kernel,
+        support_vectors,
+        coefficients: rctensor1(&coefficients),
+        rho: rho[0],
+        one_class,
+    };
+    let post_transform = post_transform(node)?;
+    Ok((expand(SvmRegressor { op, post_transform }), vec![]))
+}
+
+#[derive(Debug, Clone)]
+struct SvmClassifier {
+    op: tract_onnx_opl::ml::svm::SvmClassifier,
+    class_labels: Arc<Tensor>,
+    post_transform: Option<PostTransform>,
+}
+
+impl Expansion for SvmClassifier {
+    fn name(&self) -> Cow<str> {
+        "SVMClassifier".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].datum_type, self.class_labels.datum_type())?;
+        s.equals(&outputs[1].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 1)?;
+        s.equals(&outputs[1].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[1], self.op.n_scores().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wires = model.wire_node(format!("{prefix}.svm"), self.op.clone(), inputs)?;
+        let scores = wire_post_transform(prefix, model, tvec!(wires[1]), self.post_transform)?;
+        let labels = wire_class_labels(prefix, model, wires[0], &self.class_labels)?;
+        Ok(tvec!(labels, scores[0]))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SvmRegressor {
+    op: tract_onnx_opl::ml::svm::SvmRegressor,
+    post_transform: Option<PostTransform>,
+}
+
+impl Expansion for SvmRegressor {
+    fn name(&self) -> Cow<str> {
+        "SVMRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], 1.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wire = model.wire_node(format!("{prefix}.svm"), self.op.clone(), inputs)?;
+        wire_post_transform(prefix, model, wire, self.post_transform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+    use super::*;
+
+    #[test]
+    fn linear_classifier() -> TractResult<()> {
+        let attributes = vec![
+            attr_strings("classlabels_strings", &["a", "b", "c"]),
+            attr_floats("coefficients", &[1., 0., 0., 1., -1., -1.]),
+        ];
+        let input = tensor2(&[[2f32, 0.], [-2., -2.]]);
+        let outputs = run_node("SVMClassifier", attributes, tvec!(input), 2)?;
+        assert_eq!(*outputs[0], tensor1(&["a".to_string(), "c".to_string()]));
+        assert_eq!(*outputs[1], tensor2(&[[2f32, 0., -2.], [-2., -2., 4.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn one_vs_one_classifier() -> TractResult<()> {
+        let attributes = vec![
+            attr_ints("classlabels_int64s", &[5, 6, 7]),
+            attr_floats("support_vectors", &[1., 0., 0., 1., -1., -1.]),
+            attr_ints("vectors_per_class", &[1, 1, 1]),
+            attr_floats("coefficients", &[1., -1., -1., 1., 1., -1.]),
+            attr_floats("rho", &[0., 0., 0.]),
+        ];
+        let input = tensor2(&[[2f32, 0.], [0., 2.], [-2., -2.]]);
+        let outputs = run_node("SVMClassifier", attributes, tvec!(input), 2)?;
+        assert_eq!(*outputs[0], tensor1(&[5i64, 6, 7]));
+        assert_eq!(outputs[1].shape(), &[3, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn classifier_checks_vectors_per_class() {
+        let attributes = vec![
+            attr_ints("classlabels_int64s", &[5, 6, 7]),
+            attr_floats("support_vectors", &[1., 0., 0., 1.]),
+            attr_ints("vectors_per_class", &[1, 1]),
+            attr_floats("coefficients", &[1., -1., -1., 1.]),
+            attr_floats("rho", &[0., 0., 0.]),
+        ];
+        let input = tensor2(&[[2f32, 0.]]);
+        assert!(run_node("SVMClassifier", attributes, tvec!(input), 2).is_err());
+    }
+
+    #[test]
+    fn regressor() -> TractResult<()> {
+        let attributes = |one_class| {
+            vec![
+                attr_int("n_supports", 2),
+                attr_floats("support_vectors", &[1., 0., 0., 1.]),
+                attr_floats("coefficients", &[2., -1.]),
+                attr_floats("rho", &[0.5]),
+                attr_int("one_class", one_class),
+            ]
+        };
+        let input = tensor2(&[[1f32, 1.], [0., 2.]]);
+        let outputs = run_node("SVMRegressor", attributes(0), tvec!(input.clone()), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[1.5f32], [-1.5]]));
+        let outputs = run_node("SVMRegressor", attributes(1), tvec!(input), 1)?;
+        assert_eq!(*outputs[0], tensor2(&[[1f32], [-1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn rbf_kernel_parameters() -> TractResult<()> {
+        let attributes = vec![
+            attr_string("kernel_type", "RBF"),
+            attr_floats("kernel_params", &[0.5, 0., 0.]),
+            attr_int("n_supports", 1),
+            attr_floats("support_vectors", &[0., 0.]),
+            attr_floats("coefficients", &[1.]),
+        ];
+        let input = tensor2(&[[0f32, 0.], [1., 1.]]);
+        let outputs = run_node("SVMRegressor", attributes, tvec!(input), 1)?;
+        outputs[0].close_enough(&tensor2(&[[1f32], [(-1f32).exp()]]), Approximation::Close)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use super::*;
 use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::NodeProto;
 use crate::pb_helpers::*;
@@ -8,6 +9,7 @@ use tract_onnx_opl::ml::tree::*;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("TreeEnsembleClassifier", tree_classifier);
+    reg.insert("TreeEnsembleRegressor", tree_regressor);
 }
 
 fn tree_classifier(
@@ -15,11 +17,10 @@ fn tree_classifier(
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let ensemble = parse_nodes_data(node, true)?;
-    let class_labels = parse_class_data(node)?;
+    let class_labels = parse_class_labels(node)?;
     let base_class_score =
         get_vec_attr_opt::<f32>(node, "base_values", ensemble.n_classes())?.map(|t| rctensor1(&t));
-    let post_transform =
-        node.get_attr_opt("post_transform")?.map(parse_post_transform).transpose()?.unwrap_or(None);
+    let post_transform = post_transform(node)?;
 
     // even numbers in leaves are categories id target of leaf contrib
     let binary_result_layout = class_labels.len() < 3
@@ -43,22 +44,15 @@ fn tree_classifier(
     ))
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum PostTransform {
-    Softmax,
-    Logistic,
-    // SoftmaxZero,
-    // Probit, // probit, especially multinomial, is p.i.t.a. - so let's ignore it for now
-}
-
-pub fn parse_post_transform(s: &str) -> TractResult<Option<PostTransform>> {
-    match s {
-        "NONE" => Ok(None),
-        "SOFTMAX" => Ok(Some(PostTransform::Softmax)),
-        "LOGISTIC" => Ok(Some(PostTransform::Logistic)),
-        "PROBIT" | "SOFTMAX_ZERO" => bail!("PROBIT and SOFTMAX_ZERO unsupported"),
-        _ => bail!("Invalid post transform: {}", s),
-    }
+fn tree_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let ensemble = parse_nodes_data(node, false)?;
+    let base_values =
+        get_vec_attr_opt::<f32>(node, "base_values", ensemble.n_classes())?.map(|t| rctensor1(&t));
+    let post_transform = post_transform(node)?;
+    Ok((expand(TreeEnsembleRegressor { ensemble, base_values, post_transform }), vec![]))
 }
 
 fn parse_node_mode(s: &str) -> TractResult<Option<Cmp>> {
@@ -98,22 +92,6 @@ where
     }
 }
 
-fn parse_class_data(node: &NodeProto) -> TractResult<Arc<Tensor>> {
-    // parse n_classes from protobuf
-    let ints = node.get_attr_opt_slice::<i64>("classlabels_int64s")?;
-    let strs = node.get_attr_opt_tvec::<&str>("classlabels_strings")?;
-    match (ints, strs) {
-        (Some(n), None) => Ok(rctensor1(n)),
-        (None, Some(n)) => Ok(rctensor1(&n.iter().map(|d| d.to_string()).collect::<Vec<_>>())),
-        (None, None) => {
-            bail!("cannot find neither 'classlabels_int64s' not 'classlabels_strings'")
-        }
-        (Some(_), Some(_)) => {
-            bail!("only one of 'classlabels_int64s' and 'classlabels_strings' can be set")
-        }
-    }
-}
-
 fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEnsemble> {
     // parse n_classes from protobuf
     let n_classes = if is_classifier {
@@ -157,7 +135,7 @@ fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEn
     let aggregate_fn = parse_aggregate(if is_classifier {
         "SUM"
     } else {
-        node.get_attr_opt("aggregate")?.unwrap_or("SUM")
+        node.get_attr_opt("aggregate_function")?.unwrap_or("SUM")
     })?;
 
     // parse leaf data from protobuf
@@ -244,8 +222,6 @@ pub struct TreeEnsembleClassifier {
     pub binary_result_layout: bool,
 }
 
-
-
 impl Expansion for TreeEnsembleClassifier {
     fn name(&self) -> Cow<str> {
         "TreeEnsembleClassifier".into()
@@ -286,8 +262,6 @@ impl Expansion for TreeEnsembleClassifier {
         model: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        use tract_core::ops::nn::*;
-
         let mut scores = model.wire_node(
             format!("{prefix}.classifier"),
             tract_onnx_opl::ml::tree_ensemble_classifier::TreeEnsembleClassifier {
@@ -295,32 +269,15 @@ impl Expansion for TreeEnsembleClassifier {
             },
             inputs,
         )?;
-        if let Some(base_class_score) = self.base_class_score.as_deref() {
-            let base = base_class_score.clone().broadcast_into_rank(2)?.into_arc_tensor();
-            let base = model.add_const(prefix.to_string() + ".base", base)?;
-            scores = model.wire_node(
-                format!("{prefix}.base_class_score"),
-                tract_core::ops::math::add(),
-                &[scores[0], base],
+        if let Some(base_class_score) = &self.base_class_score {
+            scores[0] = wire_add_vector(
+                &format!("{prefix}.base_class_score"),
+                model,
+                scores[0],
+                base_class_score,
             )?;
         }
-        match self.post_transform {
-            None => (),
-            Some(PostTransform::Softmax) => {
-                scores = tract_hir::ops::nn::LayerSoftmax::new(1, false).wire(
-                    &format!("{prefix}.softmax"),
-                    model,
-                    &scores,
-                )?;
-            }
-            Some(PostTransform::Logistic) => {
-                scores = model.wire_node(
-                    &format!("{prefix}.logistic"),
-                    tract_core::ops::nn::sigmoid(),
-                    &scores,
-                )?;
-            }
-        }
+        scores = wire_post_transform(prefix, model, scores, self.post_transform)?;
         let processed_scores = scores.clone();
         if self.binary_result_layout {
             scores = model.wire_node(
@@ -340,29 +297,7 @@ impl Expansion for TreeEnsembleClassifier {
                 &[complement[0], scores[0]],
             )?;
         }
-        let winners = model.wire_node(
-            format!("{prefix}.argmax"),
-            Reduce::new(tvec!(1), Reducer::ArgMax(false)),
-            &processed_scores,
-        )?;
-        let reduced = model.wire_node(
-            format!("{prefix}.rm_axis"),
-            tract_core::ops::change_axes::AxisOp::Rm(1),
-            &winners,
-        )?;
-        let casted = model.wire_node(
-            format!("{prefix}.casted"),
-            tract_core::ops::cast::cast(i32::datum_type()),
-            &reduced,
-        )?;
-        let labels = model.wire_node(
-            format!("{prefix}.labels"),
-            tract_onnx_opl::ml::DirectLookup::new(
-                self.class_labels.clone(),
-                Tensor::zero_dt(self.class_labels.datum_type(), &[])?.into_arc_tensor(),
-            )?,
-            &casted,
-        )?[0];
+        let labels = wire_argmax_labels(prefix, model, processed_scores[0], &self.class_labels)?;
         Ok(tvec!(labels, scores[0]))
     }
 
@@ -370,3 +305,51 @@ impl Expansion for TreeEnsembleClassifier {
         Ok(2)
     }
 }
+
+#[derive(Debug, Clone, Hash)]
+pub struct TreeEnsembleRegressor {
+    pub ensemble: TreeEnsemble,
+    pub base_values: Option<Arc<Tensor>>,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl Expansion for TreeEnsembleRegressor {
+    fn name(&self) -> Cow<str> {
+        "TreeEnsembleRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.ensemble.n_classes().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut targets = model.wire_node(
+            format!("{prefix}.regressor"),
+            tract_onnx_opl::ml::tree_ensemble_regressor::TreeEnsembleRegressor {
+                ensemble: self.ensemble.clone(),
+            },
+            inputs,
+        )?;
+        if let Some(base_values) = &self.base_values {
+            targets[0] =
+                wire_add_vector(&format!("{prefix}.base_values"), model, targets[0], base_values)?;
+        }
+        wire_post_transform(prefix, model, targets, self.post_transform)
+    }
+}
@@ -0,0 +1,84 @@
+use super::*;
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("ZipMap", zip_map);
+}
+
+fn zip_map(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = parse_class_labels(node)?;
+    Ok((expand(ZipMap { class_labels }), vec![]))
+}
+
+/// The [N, C] probabilities become a sequence of N maps from class labels to probabilities.
+#[derive(Debug, Clone, Hash)]
+struct ZipMap {
+    class_labels: Arc<Tensor>,
+}
+
+impl Expansion for ZipMap {
+    fn name(&self) -> Cow<str> {
+        "ZipMap".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, DatumType::F32)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.class_labels.len().to_dim())?;
+        s.equals(&outputs[0].datum_type, Opaque::datum_type())?;
+        s.equals(&outputs[0].rank, 0)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let op = tract_onnx_opl::ml::ZipMap { class_labels: self.class_labels.clone() };
+        model.wire_node(prefix, op, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test::*;
+    use super::*;
+    use tract_onnx_opl::ml::TensorMap;
+    use tract_onnx_opl::sequence::TensorSequence;
+
+    #[test]
+    fn one_map_per_row() -> TractResult<()> {
+        let attributes = vec![attr_strings("classlabels_strings", &["cat", "dog"])];
+        let input = tensor2(&[[0.25f32, 0.75], [0.5, 0.5], [1., 0.]]);
+        let outputs = run_node("ZipMap", attributes, tvec!(input), 1)?;
+        let items = &TensorSequence::from_tensor(&outputs[0])?.items;
+        assert_eq!(items.len(), 3);
+        let map = TensorMap::from_tensor(&items[0])?;
+        assert_eq!(*map.keys, tensor1(&["cat".to_string(), "dog".to_string()]));
+        assert_eq!(*map.values, tensor1(&[0.25f32, 0.75]));
+        let map = TensorMap::from_tensor(&items[2])?;
+        assert_eq!(*map.values, tensor1(&[1f32, 0.]));
+        Ok(())
+    }
+
+    #[test]
+    fn checks_labels_count() {
+        let attributes = vec![attr_ints("classlabels_int64s", &[1, 2, 3])];
+        let input = tensor2(&[[0.25f32, 0.75]]);
+        assert!(run_node("ZipMap", attributes, tvec!(input), 1).is_err());
+    }
+}
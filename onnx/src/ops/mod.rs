@@ -19,6 +19,9 @@ mod random;
 pub mod rec;
 mod resize;
 mod s2d;
+mod sequence;
+mod text;
+mod vision;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Constant", konst);
@@ -42,6 +45,9 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     random::register_all_ops(reg);
     rec::register_all_ops(reg);
     s2d::register_all_ops(reg);
+    sequence::register_all_ops(reg);
+    text::register_all_ops(reg);
+    vision::register_all_ops(reg);
 }
 
 fn konst(
@@ -57,5 +63,5 @@ fn konst(
     } else {
         bail!("Could not extract value out of Constant node")
     };
-    Ok((Box::new(tract_hir::ops::konst::Const(value.into_arc_tensor())), vec![]))
+    Ok((Box::new(tract_hir::ops::konst::Const::new(value.into_arc_tensor())), vec![]))
 }
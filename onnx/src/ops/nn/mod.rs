@@ -71,6 +71,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Selu", selu);
     reg.insert("Sigmoid", |_, _| Ok((ops::nn::sigmoid().into_hir(), vec![])));
     reg.insert("HardSwish", |_, _| Ok((ops::nn::hard_swish().into_hir(), vec![])));
+    reg.insert("Gelu", gelu);
     reg.insert("Softmax", layer_soft_max);
     reg.insert("Softplus", |_, _| Ok((expand(ops::activations::Softplus), vec![])));
     reg.insert("Softsign", |_, _| Ok((expand(ops::activations::Softsign), vec![])));
@@ -243,6 +244,22 @@ pub fn global_lp_pool(
     Ok((expand(ops::nn::GlobalLpPool::new(p)), vec![]))
 }
 
+pub fn gelu(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let approximate = node.get_attr_opt("approximate")?.unwrap_or("none");
+    let op = node.check_value(
+        "approximate",
+        match approximate {
+            "none" => Ok(tract_core::ops::nn::gelu()),
+            "tanh" => Ok(tract_core::ops::nn::gelu_approximate()),
+            other => Err(other),
+        },
+    )?;
+    Ok((op.into_hir(), vec![]))
+}
+
 pub fn hard_sigmoid(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -2,7 +2,6 @@ use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::NodeProto;
 use tract_hir::internal::*;
 use tract_hir::ops::quant::*;
-use tract_ndarray::ArrayViewD;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("QuantizeLinear", quantize_linear);
@@ -14,7 +13,8 @@ fn quantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
+    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
@@ -22,10 +22,36 @@ fn dequantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
+    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
+/// Reshape a per-axis scale or zero point tensor so it broadcasts against an input of the
+/// given rank along `axis`, and add it as a constant.
+fn per_axis_const(
+    target: &mut TypedModel,
+    name: &str,
+    t: &Tensor,
+    dt: DatumType,
+    axis: usize,
+    rank: usize,
+) -> TractResult<OutletId> {
+    let mut shape = tvec!(1; rank);
+    if t.len() > 1 {
+        shape[axis] = t.len();
+    }
+    let t = t.cast_to_dt(dt)?.into_owned().into_shape(&shape)?;
+    target.add_const(name, t)
+}
+
+fn resolve_axis(target: &TypedModel, input: OutletId, axis: i64) -> TractResult<(usize, usize)> {
+    let rank = target.outlet_fact(input)?.rank();
+    let axis = if axis < 0 { axis + rank as i64 } else { axis };
+    ensure!(axis >= 0 && (axis as usize) < rank, "Invalid quantization axis {axis}");
+    Ok((axis as usize, rank))
+}
+
 fn dynamic_quantize_linear(
     _ctx: &ParsingContext,
     _node: &NodeProto,
@@ -37,6 +63,7 @@ fn dynamic_quantize_linear(
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct QuantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 
@@ -73,13 +100,8 @@ impl Expansion for QuantizeLinear {
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
         use tract_hir::ops::quant::*;
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0]
-            .recip();
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -90,6 +112,10 @@ impl Expansion for QuantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if scale.len() > 1 || zero_point.len() > 1 {
+            return self.wire_per_axis(prefix, target, inputs[0], &scale, &zero_point);
+        }
+        let scale = scale.as_slice::<f32>()?[0].recip();
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(quantize_linear_u8(scale, zero_point.as_slice::<u8>()?[0]))
         } else {
@@ -99,9 +125,52 @@ impl Expansion for QuantizeLinear {
     }
 }
 
+impl QuantizeLinear {
+    fn wire_per_axis(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        input: OutletId,
+        scale: &Tensor,
+        zero_point: &Tensor,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_core::ops::{cast::cast, math};
+        let (axis, rank) = resolve_axis(target, input, self.axis)?;
+        let dt = zero_point.datum_type();
+        let recip = scale.as_slice::<f32>()?.iter().map(|s| s.recip()).collect::<Vec<_>>();
+        let recip = per_axis_const(
+            target,
+            &format!("{prefix}.recip_scale"),
+            &tensor1(&recip),
+            f32::datum_type(),
+            axis,
+            rank,
+        )?;
+        let zp = per_axis_const(
+            target,
+            &format!("{prefix}.zero_point"),
+            zero_point,
+            f32::datum_type(),
+            axis,
+            rank,
+        )?;
+        let lo = dt.min_value().cast_to_dt(f32::datum_type())?.into_owned();
+        let lo = target.add_const(format!("{prefix}.min"), lo.broadcast_into_rank(rank)?)?;
+        let hi = dt.max_value().cast_to_dt(f32::datum_type())?.into_owned();
+        let hi = target.add_const(format!("{prefix}.max"), hi.broadcast_into_rank(rank)?)?;
+        let mut wire = target.wire_node(format!("{prefix}.scale"), math::mul(), &[input, recip])?;
+        wire = target.wire_node(format!("{prefix}.round"), math::round_half_to_even(), &wire)?;
+        wire = target.wire_node(format!("{prefix}.add_zp"), math::add(), &[wire[0], zp])?;
+        wire = target.wire_node(format!("{prefix}.clamp_min"), math::max(), &[wire[0], lo])?;
+        wire = target.wire_node(format!("{prefix}.clamp_max"), math::min(), &[wire[0], hi])?;
+        target.wire_node(prefix, cast(dt), &wire)
+    }
+}
+
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct DequantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 
@@ -136,12 +205,8 @@ impl Expansion for DequantizeLinear {
         target: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0];
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -152,6 +217,10 @@ impl Expansion for DequantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if scale.len() > 1 || zero_point.len() > 1 {
+            return self.wire_per_axis(prefix, target, inputs[0], &scale, &zero_point);
+        }
+        let scale = scale.as_slice::<f32>()?[0];
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(DequantizeLinearF32::new(scale, zero_point.as_slice::<u8>()?[0] as i32))
         } else if zero_point.datum_type() == i8::datum_type() {
@@ -163,6 +232,40 @@ impl Expansion for DequantizeLinear {
     }
 }
 
+impl DequantizeLinear {
+    fn wire_per_axis(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        input: OutletId,
+        scale: &Tensor,
+        zero_point: &Tensor,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_core::ops::{cast::cast, math};
+        let (axis, rank) = resolve_axis(target, input, self.axis)?;
+        let scale = per_axis_const(
+            target,
+            &format!("{prefix}.scale"),
+            scale,
+            f32::datum_type(),
+            axis,
+            rank,
+        )?;
+        let zp = per_axis_const(
+            target,
+            &format!("{prefix}.zero_point"),
+            zero_point,
+            f32::datum_type(),
+            axis,
+            rank,
+        )?;
+        let mut wire =
+            target.wire_node(format!("{prefix}.as_f32"), cast(f32::datum_type()), &[input])?;
+        wire = target.wire_node(format!("{prefix}.sub_zp"), math::sub(), &[wire[0], zp])?;
+        target.wire_node(prefix, math::mul(), &[wire[0], scale])
+    }
+}
+
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct DynamicQuantizeLinear {}
 
@@ -207,157 +310,35 @@ impl Expansion for DynamicQuantizeLinear {
     }
 }
 
-fn dynamic_quantize_linear_f32_u8(x: f32, scale: f32, zero_point: u8) -> u8 {
-    (((x / scale).round() as i32) + zero_point as i32)
-        .clamp(u8::MIN as i32, u8::MAX as i32) as u8
-}
-
-fn dynamic_quantize_linear_u8(scale: f32, zero_point: u8, xs: &[f32], ys: &mut [u8]) {
-    xs.iter()
-        .zip(ys.iter_mut())
-        .for_each(|(x, y)| *y = dynamic_quantize_linear_f32_u8(*x, scale, zero_point));
-}
-
-fn scale_and_zero_point(v: ArrayViewD<f32>) -> (f32, u8) {
-    // get the min and max of v and extend it to have zero included
-    // in the interval [min, max]
-    let (min, max) = v.fold((0., 0.), |(a_min, a_max), &v| {
-        if v < a_min {
-            (v, a_max)
-        } else if v > a_max {
-            (a_min, v)
-        } else {
-            (a_min, a_max)
-        }
-    });
-
-    // quantize range
-    let min_t = u8::MIN as f32;
-    let max_t = u8::MAX as f32;
-
-    let scale = (max - min) / max_t;
-
-    let zero_point = -min / scale;
-    let zero_point = zero_point.round();
-    // clipping to [0, 255]
-    let zero_point = zero_point.max(min_t);
-    let zero_point = zero_point.min(max_t);
-
-    let zero_point: u8 = zero_point as u8;
-
-    (scale, zero_point)
-}
-
-#[derive(Clone, Debug, new, Hash)]
-pub struct DynamicQuantizeLinearU8;
-
-impl Op for DynamicQuantizeLinearU8 {
-    fn name(&self) -> Cow<str> {
-        "DynamicQuantizeLinearU8".into()
-    }
-
-    fn info(&self) -> TractResult<Vec<String>> {
-        Ok(vec![])
-    }
-
-    fn validation(&self) -> Validation {
-        Validation::Accurate
-    }
-
-    op_as_typed_op!();
-}
-
-
-
-impl EvalOp for DynamicQuantizeLinearU8 {
-    fn is_stateless(&self) -> bool {
-        true
-    }
-    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
-        let input = &inputs[0];
-        let input = input.cast_to::<f32>()?;
-        let a_input = input.to_array_view::<f32>()?;
-        let (scale, zero_point) = scale_and_zero_point(a_input);
-
-        let mut dst = unsafe { Tensor::uninitialized_dt(u8::datum_type(), input.shape())? };
-        // We cannot use quantize_linear_u8 here because it does `x * scale.recip()`
-        // instead of `x / scale`. This change some number enough to be rounded to another integer.
-        dynamic_quantize_linear_u8(
-            scale,
-            zero_point,
-            input.as_slice::<f32>()?,
-            dst.as_slice_mut::<u8>()?,
-        );
-
-        let quantized_tensor = dst.into_tvalue();
-        let scale_tensor = tensor0(scale).into();
-        let zero_point_tensor = tensor0(zero_point).into();
-
-        Ok(tvec!(quantized_tensor, scale_tensor, zero_point_tensor))
-    }
-}
-
-impl TypedOp for DynamicQuantizeLinearU8 {
-    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
-        let mut quantized_fact = inputs[0].clone();
-        quantized_fact.datum_type = u8::datum_type();
-        let scale_fact = f32::fact([0; 0]);
-        let zero_fact = u8::fact([0; 0]);
-        Ok(tvec!(quantized_fact, scale_fact, zero_fact))
-    }
-
-    as_op!();
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tract_ndarray::arr1;
 
-    // Data for tests is from:
-    // https://github.com/onnx/onnx/blob/master/docs/Operators.md#DynamicQuantizeLinear
     #[test]
-    fn test_scale_and_zero_point() {
-        let data: [(&[f32], f32, u8); 3] = [
-            (&[0., 2., -3., -2.5, 1.34, 0.5], 0.019_607_844, 153),
-            (&[-1., -2.1, -1.3, -2.5, -3.34, -4.], 0.015_686_275, 255),
-            (&[1., 2.1, 1.3, 2.5, 3.34, 4., 1.5, 2.6, 3.9, 4., 3., 2.345], 0.015_686_275, 0),
-        ];
-
-        let epsilon = 0.00000001;
-        for (v, scale_ok, zero_point_ok) in &data {
-            let v = arr1(v).into_dyn();
-            let v = v.view();
-            let (scale, zero_point) = scale_and_zero_point(v);
-            assert!((scale - scale_ok).abs() < epsilon);
-            assert_eq!(zero_point, *zero_point_ok);
-        }
+    fn test_dequantize_linear_per_axis() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", i8::fact([2, 3]))?;
+        let scale = model.add_const("scale", tensor1(&[0.5f32, 2.0]))?;
+        let zp = model.add_const("zp", tensor1(&[0i8, 1]))?;
+        let y = DequantizeLinear::new(Some(2), 0).wire("dq", &mut model, &[x, scale, zp])?;
+        model.set_output_outlets(&y)?;
+        let x = tensor2(&[[2i8, 4, -2], [1, 2, 3]]);
+        let y = model.into_runnable()?.run(tvec!(x.into_tvalue()))?.remove(0);
+        y.close_enough(&tensor2(&[[1f32, 2., -1.], [0., 2., 4.]]), Approximation::Exact)?;
+        Ok(())
     }
 
     #[test]
-    fn test_dynamic_quantize_linear_u8() {
-        let data: [(&[f32], &[u8]); 3] = [
-            (&[0., 2., -3., -2.5, 1.34, 0.5], &[153, 255, 0, 26, 221, 179]),
-            (&[-1., -2.1, -1.3, -2.5, -3.34, -4.], &[191, 121, 172, 96, 42, 0]),
-            (
-                &[1., 2.1, 1.3, 2.5, 3.34, 4., 1.5, 2.6, 3.9, 4., 3., 2.345],
-                &[64, 134, 83, 159, 213, 255, 96, 166, 249, 255, 191, 149],
-            ),
-        ];
-
-        for (v, quantized_ok) in &data {
-            let v = arr1(v).into_dyn();
-            let (scale, zero_point) = scale_and_zero_point(v.view());
-
-            // same shape of v but with u8 type, values will be overwritten
-            let mut quantized = v.mapv(|_| 0_u8);
-            dynamic_quantize_linear_u8(
-                scale,
-                zero_point,
-                v.as_slice().unwrap(),
-                quantized.as_slice_mut().unwrap(),
-            );
-            assert_eq!(quantized.as_slice().unwrap(), *quantized_ok);
-        }
+    fn test_quantize_linear_per_axis() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 2]))?;
+        let scale = model.add_const("scale", tensor1(&[0.5f32, 0.01]))?;
+        let zp = model.add_const("zp", tensor1(&[10u8, 0]))?;
+        let y = QuantizeLinear::new(Some(2), -1).wire("q", &mut model, &[x, scale, zp])?;
+        model.set_output_outlets(&y)?;
+        let x = tensor2(&[[1f32, -10.], [1., 3.]]);
+        let y = model.into_runnable()?.run(tvec!(x.into_tvalue()))?.remove(0);
+        assert_eq!(*y, tensor2(&[[12u8, 0], [12, 255]]));
+        Ok(())
     }
 }
@@ -5,9 +5,11 @@ pub mod gru;
 pub mod lstm;
 pub mod rnn;
 pub mod scan;
+pub mod while_loop;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("GRU", gru::gru);
+    reg.insert("Loop", while_loop::while_loop);
     reg.insert("LSTM", lstm::lstm);
     reg.insert("RNN", rnn::rnn);
     reg.insert("Scan", scan::scan);
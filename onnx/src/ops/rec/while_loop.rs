@@ -0,0 +1,274 @@
+use crate::model::{ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+
+pub fn while_loop(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { model: body, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let mut options = crate::model::optional_inputs(node);
+    let optional_trip_count_input = options.next().unwrap();
+    let optional_cond_input = options.next().unwrap();
+    let outer_inputs = node.input.iter().filter(|s| !s.is_empty()).count();
+    let carried = outer_inputs
+        - optional_trip_count_input.is_some() as usize
+        - optional_cond_input.is_some() as usize;
+    ensure!(
+        body.input_outlets()?.len() == 2 + carried + unresolved_inputs.len(),
+        "Loop body expects iteration number, condition, {} loop-carried dependencies and {} closures, found {} inputs",
+        carried,
+        unresolved_inputs.len(),
+        body.input_outlets()?.len()
+    );
+    Ok((
+        Box::new(Loop {
+            body,
+            optional_trip_count_input,
+            optional_cond_input,
+            carried,
+            iterations: ctx.symbol_table.new_with_prefix("loop"),
+        }),
+        unresolved_inputs,
+    ))
+}
+
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub body: InferenceModel,
+    optional_trip_count_input: Option<usize>,
+    optional_cond_input: Option<usize>,
+    carried: usize,
+    iterations: Symbol,
+}
+
+impl Loop {
+    fn first_carried_input(&self) -> usize {
+        self.optional_trip_count_input.is_some() as usize
+            + self.optional_cond_input.is_some() as usize
+    }
+
+    fn unify_scan_output(
+        outer: &mut InferenceFact,
+        inner: &mut InferenceFact,
+        iterations: &Symbol,
+    ) -> TractResult<bool> {
+        let mut changed = outer.datum_type.unify_with_mut(&mut inner.datum_type)?;
+        let rank =
+            inner.shape.rank().concretize().map(|r| r as usize).or_else(|| {
+                outer.shape.rank().concretize().filter(|r| *r > 0).map(|r| r as usize - 1)
+            });
+        if let Some(rank) = rank {
+            if outer
+                .shape
+                .unify_with(&ShapeFactoid::closed(tvec!(GenericFactoid::Any; rank + 1)))?
+            {
+                changed = true;
+            }
+            if inner.shape.unify_with(&ShapeFactoid::closed(tvec!(GenericFactoid::Any; rank)))? {
+                changed = true;
+            }
+            if outer.shape.dim(0).unwrap().concretize().is_none()
+                && outer.shape.set_dim(0, iterations.to_dim())
+            {
+                changed = true;
+            }
+            for axis in 0..rank {
+                let value = inner
+                    .shape
+                    .dim(axis)
+                    .unwrap()
+                    .concretize()
+                    .or_else(|| outer.shape.dim(axis + 1).unwrap().concretize());
+                if let Some(value) = value {
+                    if outer.shape.set_dim(axis + 1, value.clone()) {
+                        changed = true
+                    }
+                    if inner.shape.set_dim(axis, value) {
+                        changed = true
+                    }
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let trip_count = self
+            .optional_trip_count_input
+            .map(|ix| inputs[ix].cast_to_scalar::<i64>())
+            .transpose()?
+            .unwrap_or(i64::MAX);
+        let mut cond = self
+            .optional_cond_input
+            .map(|ix| inputs[ix].cast_to_scalar::<bool>())
+            .transpose()?
+            .unwrap_or(true);
+        let first_carried = self.first_carried_input();
+        let mut carried: TVec<TValue> = inputs[first_carried..][..self.carried].into();
+        let closures = &inputs[first_carried + self.carried..];
+        let scan_outputs = self.body.outputs.len() - 1 - self.carried;
+        let mut scanned: Vec<Vec<Tensor>> = vec![vec![]; scan_outputs];
+        let plan = SimplePlan::new(&self.body)?;
+        let mut i = 0i64;
+        while i < trip_count && cond {
+            let mut iter_inputs: TVec<TValue> =
+                tvec!(tensor0(i).into_tvalue(), tensor0(cond).into_tvalue());
+            iter_inputs.extend(carried.drain(..));
+            iter_inputs.extend(closures.iter().cloned());
+            let mut iter_outputs = plan.run(iter_inputs)?;
+            for (ix, v) in iter_outputs.drain(1 + self.carried..).enumerate() {
+                let mut v = v.into_tensor();
+                v.insert_axis(0)?;
+                scanned[ix].push(v);
+            }
+            carried.extend(iter_outputs.drain(1..));
+            cond = iter_outputs[0].cast_to_scalar::<bool>()?;
+            i += 1;
+        }
+        let mut outputs = carried;
+        for (ix, values) in scanned.into_iter().enumerate() {
+            let output = if values.len() > 0 {
+                Tensor::stack_tensors(0, &values)?
+            } else {
+                let fact = self.body.output_fact(1 + self.carried + ix)?;
+                let dt = fact.datum_type.concretize().context("Loop scan output type unknown")?;
+                let mut shape = fact
+                    .shape
+                    .as_concrete_finite()?
+                    .context("Loop scan output shape unknown on empty loop")?;
+                shape.insert(0, 0);
+                Tensor::zero_dt(dt, &shape)?
+            };
+            outputs.push(output.into_tvalue());
+        }
+        Ok(outputs)
+    }
+}
+
+impl InferenceOp for Loop {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let first_carried = self.first_carried_input();
+        let scan_outputs = self.nboutputs()? - self.carried;
+        loop {
+            let mut changed = false;
+            if let Some(ix) = self.optional_trip_count_input {
+                changed = changed || inputs[ix].datum_type.unify_with(&i64::datum_type().into())?;
+            }
+            if let Some(ix) = self.optional_cond_input {
+                changed =
+                    changed || inputs[ix].datum_type.unify_with(&bool::datum_type().into())?;
+            }
+            changed = changed
+                || self.body.input_fact_mut(0)?.unify_with(&InferenceFact::dt_shape(
+                    i64::datum_type(),
+                    ShapeFactoid::closed(tvec!()),
+                ))?;
+            changed = changed
+                || self.body.input_fact_mut(1)?.unify_with(&InferenceFact::dt_shape(
+                    bool::datum_type(),
+                    ShapeFactoid::closed(tvec!()),
+                ))?;
+            changed = changed
+                || self
+                    .body
+                    .output_fact_mut(0)?
+                    .datum_type
+                    .unify_with(&bool::datum_type().into())?;
+            for ix in 0..self.carried {
+                let mut facts = self.body.outlets_fact_mut(&[
+                    self.body.input_outlets()?[2 + ix],
+                    self.body.output_outlets()?[1 + ix],
+                ])?;
+                facts.push(&mut inputs[first_carried + ix]);
+                facts.push(&mut outputs[ix]);
+                changed = changed
+                    || Factoid::unify_all(
+                        &mut facts.iter_mut().map(|f| &mut f.datum_type).collect::<TVec<_>>(),
+                    )?;
+                changed = changed
+                    || Factoid::unify_all(
+                        &mut facts.iter_mut().map(|f| &mut f.shape).collect::<TVec<_>>(),
+                    )?;
+            }
+            for ix in 2 + self.carried..self.body.input_outlets()?.len() {
+                changed = changed
+                    || self
+                        .body
+                        .input_fact_mut(ix)?
+                        .unify_with_mut(&mut inputs[first_carried + ix - 2])?;
+            }
+            for ix in 0..scan_outputs {
+                changed = changed
+                    || Self::unify_scan_output(
+                        &mut outputs[self.carried + ix],
+                        self.body.output_fact_mut(1 + self.carried + ix)?,
+                        &self.iterations,
+                    )?;
+            }
+            changed = changed || self.body.analyse(false)?;
+            if !changed {
+                return Ok((inputs, outputs, observed.into_iter().cloned().collect()));
+            }
+        }
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        let body_outputs = self.body.outputs.len();
+        ensure!(
+            body_outputs > self.carried,
+            "Loop body must output a condition and {} loop-carried dependencies, found {} outputs",
+            self.carried,
+            body_outputs
+        );
+        Ok(body_outputs - 1)
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let body = self.body.clone().into_typed()?;
+        let trip_count = if let Some(ix) = self.optional_trip_count_input {
+            mapping[&node.inputs[ix]]
+        } else {
+            target.add_const(format!("{}.trip_count", node.name), tensor0(i64::MAX))?
+        };
+        let cond = if let Some(ix) = self.optional_cond_input {
+            mapping[&node.inputs[ix]]
+        } else {
+            target.add_const(format!("{}.cond", node.name), tensor0(true))?
+        };
+        let mut inputs: TVec<OutletId> = tvec!(trip_count, cond);
+        inputs.extend(node.inputs[self.first_carried_input()..].iter().map(|o| mapping[o]));
+        let op = tract_core::ops::scan::Loop::new(body, self.carried, self.iterations.clone())?;
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    as_op!();
+}
@@ -0,0 +1,454 @@
+use crate::model::{OnnxOpRegister, ParseResult, ParsingContext};
+use crate::pb::*;
+use crate::tensor::translate_type_proto;
+use tract_hir::internal::*;
+use tract_onnx_opl::optional::*;
+use tract_onnx_opl::sequence::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("SequenceEmpty", sequence_empty);
+    reg.insert("SequenceConstruct", |ctx, _| {
+        let symbol = ctx.symbol_table.new_with_prefix("seq");
+        Ok((inference_wrap(SequenceConstruct { symbol }, 1, sequence_rules), vec![]))
+    });
+    reg.insert("SequenceInsert", |ctx, _| {
+        let symbol = ctx.symbol_table.new_with_prefix("seq");
+        Ok((inference_wrap(SequenceInsert { symbol }, 1, sequence_rules), vec![]))
+    });
+    reg.insert("SequenceAt", |_, _| Ok((inference_wrap(SequenceAt, 1, element_rules), vec![])));
+    reg.insert("SequenceErase", |_, _| {
+        Ok((inference_wrap(SequenceErase, 1, sequence_rules), vec![]))
+    });
+    reg.insert("SequenceLength", |_, _| {
+        Ok((inference_wrap(SequenceLength, 1, sequence_length_rules), vec![]))
+    });
+    reg.insert("SplitToSequence", split_to_sequence);
+    reg.insert("ConcatFromSequence", concat_from_sequence);
+    reg.insert("SequenceMap", sequence_map);
+    reg.insert("Optional", optional);
+    reg.insert("OptionalHasElement", |_, _| {
+        Ok((inference_wrap(OptionalHasElement, 1, optional_has_element_rules), vec![]))
+    });
+    reg.insert("OptionalGetElement", |_, _| {
+        Ok((inference_wrap(OptionalGetElement, 1, element_rules), vec![]))
+    });
+}
+
+fn sequence_empty(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let datum_type = node.get_attr_opt("dtype")?.unwrap_or(DatumType::F32);
+    Ok((inference_wrap(SequenceEmpty { datum_type }, 1, sequence_rules), vec![]))
+}
+
+fn split_to_sequence(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    let keepdims = node.get_attr_opt("keepdims")?.unwrap_or(true);
+    let symbol = ctx.symbol_table.new_with_prefix("seq");
+    Ok((inference_wrap(SplitToSequence { axis, keepdims, symbol }, 1, sequence_rules), vec![]))
+}
+
+fn concat_from_sequence(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr("axis")?;
+    let new_axis = node.get_attr_opt("new_axis")?.unwrap_or(false);
+    let symbol = ctx.symbol_table.new_with_prefix("seq");
+    Ok((inference_wrap(ConcatFromSequence { axis, new_axis, symbol }, 1, element_rules), vec![]))
+}
+
+fn optional(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let empty = node
+        .get_attr_opt::<&TypeProto>("type")?
+        .map(|tp| translate_type_proto(ctx, tp, true))
+        .transpose()?
+        .and_then(|fact| TypedFact::try_from(&fact).ok());
+    Ok((inference_wrap(OptionalConstruct { empty }, 1, sequence_rules), vec![]))
+}
+
+/// Sequences and optionals are opaque scalars.
+fn sequence_rules<'p>(
+    _op: &dyn Op,
+    s: &mut Solver,
+    _inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(outputs, 1)?;
+    s.equals(&outputs[0].datum_type, Opaque::datum_type())?;
+    s.equals(&outputs[0].rank, 0)?;
+    Ok(())
+}
+
+/// Element types only appear once the model is typed.
+fn element_rules<'p>(
+    _op: &dyn Op,
+    _s: &mut Solver,
+    _inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(outputs, 1)?;
+    Ok(())
+}
+
+fn sequence_length_rules<'p>(
+    _op: &dyn Op,
+    s: &mut Solver,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_input_arity(inputs, 1)?;
+    check_output_arity(outputs, 1)?;
+    s.equals(&outputs[0].datum_type, i64::datum_type())?;
+    s.equals(&outputs[0].rank, 0)?;
+    Ok(())
+}
+
+fn optional_has_element_rules<'p>(
+    _op: &dyn Op,
+    s: &mut Solver,
+    _inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(outputs, 1)?;
+    s.equals(&outputs[0].datum_type, bool::datum_type())?;
+    s.equals(&outputs[0].rank, 0)?;
+    Ok(())
+}
+
+fn sequence_map(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { model: body, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let outer_inputs = node.input.iter().filter(|s| !s.is_empty()).count();
+    ensure!(
+        body.input_outlets()?.len() == outer_inputs + unresolved_inputs.len(),
+        "SequenceMap body expects {} inputs and {} closures, found {} inputs",
+        outer_inputs,
+        unresolved_inputs.len(),
+        body.input_outlets()?.len()
+    );
+    Ok((Box::new(SequenceMapOp { body }), unresolved_inputs))
+}
+
+#[derive(Debug, Clone)]
+pub struct SequenceMapOp {
+    pub body: InferenceModel,
+}
+
+impl Op for SequenceMapOp {
+    fn name(&self) -> Cow<str> {
+        "SequenceMap".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for SequenceMapOp {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let sequences: TVec<Option<&TensorSequence>> =
+            inputs.iter().map(|input| TensorSequence::from_tensor(input).ok()).collect();
+        let len = sequences[0].context("SequenceMap first input must be a sequence")?.items.len();
+        let plan = SimplePlan::new(&self.body)?;
+        let mut outputs: TVec<TVec<Arc<Tensor>>> = tvec!(tvec!(); self.body.outputs.len());
+        for i in 0..len {
+            let iter_inputs = inputs
+                .iter()
+                .zip(sequences.iter())
+                .map(|(input, seq)| match seq {
+                    Some(seq) => seq.items[i].clone().into_tvalue(),
+                    None => input.clone(),
+                })
+                .collect::<TVec<TValue>>();
+            for (ix, output) in plan.run(iter_inputs)?.into_iter().enumerate() {
+                outputs[ix].push(output.into_arc_tensor());
+            }
+        }
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, items)| {
+                let datum_type = items
+                    .first()
+                    .map(|t| t.datum_type())
+                    .or_else(|| self.body.output_fact(ix).ok()?.datum_type.concretize())
+                    .context("SequenceMap output type unknown on empty sequence")?;
+                Ok(TensorSequence::new(datum_type, items).into_tvalue())
+            })
+            .collect()
+    }
+}
+
+impl InferenceOp for SequenceMapOp {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let sequence = InferenceFact::dt_shape(Opaque::datum_type(), ShapeFactoid::closed(tvec!()));
+        let outputs =
+            outputs.into_iter().map(|o| o.unify(&sequence)).collect::<TractResult<_>>()?;
+        Ok((
+            inputs.into_iter().cloned().collect(),
+            outputs,
+            observed.into_iter().cloned().collect(),
+        ))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.outputs.len())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs: TVec<OutletId> = node.inputs.iter().map(|o| mapping[o]).collect();
+        let mut body = self.body.clone();
+        let mut sequence_inputs = tvec!();
+        for (ix, input) in inputs.iter().enumerate() {
+            let fact = target.outlet_fact(*input)?;
+            let sequence = if fact.datum_type == Opaque::datum_type() {
+                SequenceFact::from_typed_fact(fact).ok()
+            } else {
+                None
+            };
+            let fact = if let Some(sequence) = &sequence {
+                sequence.element_fact()?
+            } else {
+                fact.without_value()
+            };
+            sequence_inputs.push(sequence.is_some());
+            body.set_input_fact(ix, InferenceFact::from(fact))?;
+        }
+        let op =
+            tract_onnx_opl::sequence::SequenceMap { body: body.into_typed()?, sequence_inputs };
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::attribute_proto::AttributeType;
+    use crate::pb::tensor_proto::DataType;
+    use crate::pb::tensor_shape_proto::{dimension, Dimension};
+    use crate::ser::attr_int;
+    use tract_hir::prelude::Framework;
+
+    fn tensor_type(dt: DataType, shape: &[i64]) -> TypeProto {
+        let dim = shape
+            .iter()
+            .map(|d| Dimension {
+                value: Some(dimension::Value::DimValue(*d)),
+                ..Dimension::default()
+            })
+            .collect();
+        let tensor =
+            type_proto::Tensor { elem_type: dt as i32, shape: Some(TensorShapeProto { dim }) };
+        TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..TypeProto::default() }
+    }
+
+    fn input(name: &str, dt: DataType, shape: &[i64]) -> ValueInfoProto {
+        let r#type = Some(tensor_type(dt, shape));
+        ValueInfoProto { name: name.into(), r#type, ..ValueInfoProto::default() }
+    }
+
+    fn output(name: &str) -> ValueInfoProto {
+        ValueInfoProto { name: name.into(), ..ValueInfoProto::default() }
+    }
+
+    fn node(
+        op_type: &str,
+        input: &[&str],
+        output: &[&str],
+        attribute: Vec<AttributeProto>,
+    ) -> NodeProto {
+        NodeProto {
+            op_type: op_type.into(),
+            input: input.iter().map(|s| s.to_string()).collect(),
+            output: output.iter().map(|s| s.to_string()).collect(),
+            attribute,
+            ..NodeProto::default()
+        }
+    }
+
+    fn graph(input: Vec<ValueInfoProto>, node: Vec<NodeProto>, output: &[&str]) -> GraphProto {
+        let output = output.iter().map(|name| self::output(name)).collect();
+        GraphProto { input, node, output, ..GraphProto::default() }
+    }
+
+    fn run(graph: GraphProto, inputs: TVec<Tensor>) -> TractResult<TVec<TValue>> {
+        let opset = OperatorSetIdProto { domain: String::new(), version: 18 };
+        let proto =
+            ModelProto { opset_import: vec![opset], graph: Some(graph), ..ModelProto::default() };
+        let model = crate::onnx().model_for_proto_model(&proto)?.into_optimized()?;
+        model.into_runnable()?.run(inputs.into_iter().map(|t| t.into_tvalue()).collect())
+    }
+
+    #[test]
+    fn construct_at_length() -> TractResult<()> {
+        let graph = graph(
+            vec![
+                input("x", DataType::Float, &[2]),
+                input("y", DataType::Float, &[3]),
+                input("pos", DataType::Int64, &[]),
+            ],
+            vec![
+                node("SequenceConstruct", &["x", "y"], &["seq"], vec![]),
+                node("SequenceAt", &["seq", "pos"], &["at"], vec![]),
+                node("SequenceLength", &["seq"], &["len"], vec![]),
+            ],
+            &["at", "len"],
+        );
+        let inputs = tvec!(tensor1(&[1f32, 2.]), tensor1(&[3f32, 4., 5.]), tensor0(-1i64));
+        let outputs = run(graph, inputs)?;
+        assert_eq!(*outputs[0], tensor1(&[3f32, 4., 5.]));
+        assert_eq!(*outputs[1], tensor0(2i64));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_insert_erase() -> TractResult<()> {
+        let graph = graph(
+            vec![
+                input("x", DataType::Int64, &[1]),
+                input("y", DataType::Int64, &[2]),
+                input("zero", DataType::Int64, &[]),
+            ],
+            vec![
+                node(
+                    "SequenceEmpty",
+                    &[],
+                    &["empty"],
+                    vec![attr_int("dtype", DataType::Int64 as i64)],
+                ),
+                node("SequenceInsert", &["empty", "x"], &["one"], vec![]),
+                node("SequenceInsert", &["one", "y", "zero"], &["two"], vec![]),
+                node("SequenceErase", &["two", "zero"], &["erased"], vec![]),
+                node("SequenceAt", &["erased", "zero"], &["first"], vec![]),
+                node("SequenceLength", &["two"], &["len"], vec![]),
+            ],
+            &["first", "len"],
+        );
+        let outputs = run(graph, tvec!(tensor1(&[1i64]), tensor1(&[2i64, 3]), tensor0(0i64)))?;
+        assert_eq!(*outputs[0], tensor1(&[1i64]));
+        assert_eq!(*outputs[1], tensor0(2i64));
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_concat() -> TractResult<()> {
+        let graph = graph(
+            vec![input("x", DataType::Float, &[2, 3])],
+            vec![
+                node("SplitToSequence", &["x"], &["cols"], vec![attr_int("axis", 1)]),
+                node("ConcatFromSequence", &["cols"], &["concat"], vec![attr_int("axis", 1)]),
+                node(
+                    "SplitToSequence",
+                    &["x"],
+                    &["squeezed"],
+                    vec![attr_int("axis", 1), attr_int("keepdims", 0)],
+                ),
+                node(
+                    "ConcatFromSequence",
+                    &["squeezed"],
+                    &["stacked"],
+                    vec![attr_int("axis", 0), attr_int("new_axis", 1)],
+                ),
+                node("SequenceLength", &["cols"], &["len"], vec![]),
+            ],
+            &["concat", "stacked", "len"],
+        );
+        let x = tensor2(&[[0f32, 1., 2.], [3., 4., 5.]]);
+        let outputs = run(graph, tvec!(x.clone()))?;
+        assert_eq!(*outputs[0], x);
+        assert_eq!(*outputs[1], tensor2(&[[0f32, 3.], [1., 4.], [2., 5.]]));
+        assert_eq!(*outputs[2], tensor0(3i64));
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_map_with_tensor_input() -> TractResult<()> {
+        let body = graph(
+            vec![input("e", DataType::Float, &[2]), input("t", DataType::Float, &[2])],
+            vec![node("Add", &["e", "t"], &["o"], vec![])],
+            &["o"],
+        );
+        let body = AttributeProto {
+            name: "body".into(),
+            r#type: AttributeType::Graph as i32,
+            g: Some(body),
+            ..AttributeProto::default()
+        };
+        let graph = graph(
+            vec![
+                input("x", DataType::Float, &[2]),
+                input("y", DataType::Float, &[2]),
+                input("t", DataType::Float, &[2]),
+            ],
+            vec![
+                node("SequenceConstruct", &["x", "y"], &["seq"], vec![]),
+                node("SequenceMap", &["seq", "t"], &["mapped"], vec![body]),
+                node(
+                    "ConcatFromSequence",
+                    &["mapped"],
+                    &["stacked"],
+                    vec![attr_int("axis", 0), attr_int("new_axis", 1)],
+                ),
+            ],
+            &["stacked"],
+        );
+        let inputs = tvec!(tensor1(&[1f32, 2.]), tensor1(&[3f32, 4.]), tensor1(&[10f32, 20.]));
+        let outputs = run(graph, inputs)?;
+        assert_eq!(*outputs[0], tensor2(&[[11f32, 22.], [13., 24.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn optional_wrap_and_empty() -> TractResult<()> {
+        let empty_type = AttributeProto {
+            name: "type".into(),
+            r#type: AttributeType::TypeProto as i32,
+            tp: Some(tensor_type(DataType::Float, &[2])),
+            ..AttributeProto::default()
+        };
+        let graph = graph(
+            vec![input("x", DataType::Float, &[2])],
+            vec![
+                node("Optional", &["x"], &["some"], vec![]),
+                node("OptionalHasElement", &["some"], &["has_some"], vec![]),
+                node("OptionalGetElement", &["some"], &["value"], vec![]),
+                node("Optional", &[], &["none"], vec![empty_type]),
+                node("OptionalHasElement", &["none"], &["has_none"], vec![]),
+                node("OptionalHasElement", &["x"], &["has_tensor"], vec![]),
+            ],
+            &["has_some", "value", "has_none", "has_tensor"],
+        );
+        let outputs = run(graph, tvec!(tensor1(&[1f32, 2.])))?;
+        assert_eq!(*outputs[0], tensor0(true));
+        assert_eq!(*outputs[1], tensor1(&[1f32, 2.]));
+        assert_eq!(*outputs[2], tensor0(false));
+        assert_eq!(*outputs[3], tensor0(true));
+        Ok(())
+    }
+}
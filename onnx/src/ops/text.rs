@@ -0,0 +1,262 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::ops::binary::wire_rank_broadcast;
+use tract_onnx_opl::text::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("RegexFullMatch", regex_full_match);
+    reg.insert("StringConcat", |_, _| Ok((expand(StringConcatExpansion), vec![])));
+    reg.insert("StringNormalizer", string_normalizer);
+    reg.insert("StringSplit", string_split);
+    reg.insert("TfIdfVectorizer", tfidf_vectorizer);
+}
+
+fn regex_full_match(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let pattern: String = node.get_attr("pattern")?;
+    Ok((expand(RegexFullMatchExpansion(RegexFullMatch::new(pattern)?)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct RegexFullMatchExpansion(RegexFullMatch);
+
+impl Expansion for RegexFullMatchExpansion {
+    fn name(&self) -> Cow<str> {
+        "RegexFullMatch".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, String::datum_type())?;
+        s.equals(&outputs[0].datum_type, bool::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct StringConcatExpansion;
+
+impl Expansion for StringConcatExpansion {
+    fn name(&self) -> Cow<str> {
+        "StringConcat".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        tract_hir::ops::binary::rules(s, inputs, outputs, |_, _| Ok(String::datum_type()))?;
+        s.equals(&inputs[0].datum_type, String::datum_type())?;
+        s.equals(&inputs[1].datum_type, String::datum_type())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wires = wire_rank_broadcast(prefix, model, inputs)?;
+        model.wire_node(prefix, StringConcat, &wires)
+    }
+}
+
+fn string_normalizer(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let case_change = node.get_attr_opt("case_change_action")?.unwrap_or("NONE");
+    let case_sensitive = node.get_attr_opt("is_case_sensitive")?.unwrap_or(false);
+    let stopwords: Vec<String> = node.get_attr_opt_vec("stopwords")?.unwrap_or_default();
+    if let Some(locale) = node.get_attr_opt::<&str>("locale")? {
+        log::debug!("StringNormalizer ignores locale {locale}");
+    }
+    let op = StringNormalizer {
+        case_change: CaseChange::parse(case_change)?,
+        case_sensitive,
+        stopwords: rctensor1(&stopwords),
+        len: ctx.symbol_table.new_with_prefix("words"),
+    };
+    Ok((expand(StringNormalizerExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct StringNormalizerExpansion(StringNormalizer);
+
+impl Expansion for StringNormalizerExpansion {
+    fn name(&self) -> Cow<str> {
+        "StringNormalizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, String::datum_type())?;
+        s.equals(&outputs[0].datum_type, String::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            if rank == 2 {
+                s.equals(&outputs[0].shape[0], 1usize.to_dim())?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+fn string_split(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let delimiter: Option<String> = node.get_attr_opt("delimiter")?;
+    let maxsplit: Option<usize> = node.get_attr_opt("maxsplit")?;
+    let op = StringSplit {
+        delimiter: delimiter.filter(|d| !d.is_empty()),
+        maxsplit,
+        len: ctx.symbol_table.new_with_prefix("splits"),
+    };
+    Ok((expand(StringSplitExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct StringSplitExpansion(StringSplit);
+
+impl Expansion for StringSplitExpansion {
+    fn name(&self) -> Cow<str> {
+        "StringSplit".into()
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+        s.equals(&inputs[0].datum_type, String::datum_type())?;
+        s.equals(&outputs[0].datum_type, String::datum_type())?;
+        s.equals(&outputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[1].shape)?;
+        s.equals(inputs[0].rank.bex() + 1, &outputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            for axis in 0..rank {
+                s.equals(&outputs[0].shape[axis], &inputs[0].shape[axis])?;
+            }
+            s.equals(&outputs[0].shape[rank], self.0.len.to_dim())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+fn tfidf_vectorizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let pool = if let Some(pool) = node.get_attr_opt_vec::<i64>("pool_int64s")? {
+        rctensor1(&pool)
+    } else {
+        rctensor1(&node.get_attr_vec::<String>("pool_strings")?)
+    };
+    let weights = node.get_attr_opt_vec::<f32>("weights")?.map(|w| rctensor1(&w));
+    let op = TfIdfVectorizer::new(
+        node.get_attr("min_gram_length")?,
+        node.get_attr("max_gram_length")?,
+        node.get_attr("max_skip_count")?,
+        TfIdfMode::parse(node.get_attr("mode")?)?,
+        pool,
+        node.get_attr_tvec("ngram_counts")?,
+        node.get_attr_tvec("ngram_indexes")?,
+        weights,
+    )?;
+    Ok((expand(TfIdfVectorizerExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct TfIdfVectorizerExpansion(TfIdfVectorizer);
+
+impl Expansion for TfIdfVectorizerExpansion {
+    fn name(&self) -> Cow<str> {
+        "TfIdfVectorizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            ensure!(rank == 1 || rank == 2, "TfIdfVectorizer expects [C] or [N, C]");
+            let output_size = self.0.ngram_indexes.iter().max().map(|m| m + 1).unwrap_or(0);
+            if rank == 2 {
+                s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+            }
+            s.equals(&outputs[0].shape[rank as usize - 1], output_size.to_dim())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
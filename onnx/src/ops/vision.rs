@@ -0,0 +1,235 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_onnx_opl::vision::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("AffineGrid", affine_grid);
+    reg.insert("GridSample", grid_sample);
+    reg.insert("MaxRoiPool", max_roi_pool);
+    reg.insert("RoiAlign", roi_align);
+}
+
+fn affine_grid(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let align_corners = node.get_attr_opt("align_corners")?.unwrap_or(false);
+    Ok((expand(AffineGridExpansion(AffineGrid { align_corners })), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct AffineGridExpansion(AffineGrid);
+
+impl Expansion for AffineGridExpansion {
+    fn name(&self) -> Cow<str> {
+        "AffineGrid".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.given(&inputs[1].shape[0], move |s, len| {
+            let len = len.to_usize()?;
+            ensure!(len == 4 || len == 5, "AffineGrid expects a 2D or 3D size");
+            s.equals(&outputs[0].rank, len as i64 - 1)?;
+            s.equals(&outputs[0].shape[len - 2], (len - 2).to_dim())
+        })?;
+        s.given(&inputs[1].value, move |s, size| {
+            let size = size.cast_to::<TDim>()?;
+            for (axis, dim) in size.as_slice::<TDim>()?.iter().enumerate().skip(2) {
+                s.equals(&outputs[0].shape[axis - 1], dim)?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+fn grid_sample(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let op = GridSample {
+        mode: InterpolationMode::parse(node.get_attr_opt("mode")?.unwrap_or("linear"))?,
+        padding_mode: PaddingMode::parse(node.get_attr_opt("padding_mode")?.unwrap_or("zeros"))?,
+        align_corners: node.get_attr_opt("align_corners")?.unwrap_or(false),
+    };
+    Ok((expand(GridSampleExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct GridSampleExpansion(GridSample);
+
+impl Expansion for GridSampleExpansion {
+    fn name(&self) -> Cow<str> {
+        "GridSample".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[1].rank, &outputs[0].rank)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            ensure!(rank >= 3, "GridSample expects a [N, C, D1, ..., Dr] input");
+            s.equals(&inputs[1].shape[rank - 1], (rank - 2).to_dim())?;
+            for axis in 2..rank {
+                s.equals(&outputs[0].shape[axis], &inputs[1].shape[axis - 1])?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+fn max_roi_pool(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let pooled_shape: TVec<usize> = node.get_attr_tvec("pooled_shape")?;
+    node.expect(pooled_shape.len() == 2, "a 2D pooled_shape")?;
+    let op = MaxRoiPool {
+        pooled_shape: (pooled_shape[0], pooled_shape[1]),
+        spatial_scale: node.get_attr_opt("spatial_scale")?.unwrap_or(1.0),
+    };
+    Ok((expand(MaxRoiPoolExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct MaxRoiPoolExpansion(MaxRoiPool);
+
+impl Expansion for MaxRoiPoolExpansion {
+    fn name(&self) -> Cow<str> {
+        "MaxRoiPool".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 5.to_dim())?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], self.0.pooled_shape.0.to_dim())?;
+        s.equals(&outputs[0].shape[3], self.0.pooled_shape.1.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
+
+fn roi_align(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let default_mode =
+        if ctx.onnx_operator_set_version >= 16 { "half_pixel" } else { "output_half_pixel" };
+    let half_pixel =
+        match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or(default_mode) {
+            "half_pixel" => true,
+            "output_half_pixel" => false,
+            s => bail!("coordinate_transformation_mode: {}", s),
+        };
+    let op = RoiAlign {
+        mode: RoiAlignMode::parse(node.get_attr_opt("mode")?.unwrap_or("avg"))?,
+        output_height: node.get_attr_opt("output_height")?.unwrap_or(1),
+        output_width: node.get_attr_opt("output_width")?.unwrap_or(1),
+        sampling_ratio: node.get_attr_opt("sampling_ratio")?.unwrap_or(0),
+        spatial_scale: node.get_attr_opt("spatial_scale")?.unwrap_or(1.0),
+        half_pixel,
+    };
+    Ok((expand(RoiAlignExpansion(op)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct RoiAlignExpansion(RoiAlign);
+
+impl Expansion for RoiAlignExpansion {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 4.to_dim())?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&inputs[2].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], self.0.output_height.to_dim())?;
+        s.equals(&outputs[0].shape[3], self.0.output_width.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
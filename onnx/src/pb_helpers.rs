@@ -87,6 +87,7 @@ impl Display for attribute_proto::AttributeType {
             AttributeType::Strings => "list of strings",
             AttributeType::Graph => "graph",
             AttributeType::Graphs => "graphs",
+            AttributeType::TypeProto => "type",
             _ => "<undefined>",
         })
     }
@@ -165,6 +166,13 @@ impl<'a> AttrScalarType<'a> for &'a GraphProto {
     }
 }
 
+impl<'a> AttrScalarType<'a> for &'a TypeProto {
+    fn get_attr_opt_scalar(node: &'a NodeProto, name: &str) -> TractResult<Option<Self>> {
+        node.get_attr_opt_with_type(name, AttributeType::TypeProto)?
+            .and_ok(|a| a.tp.as_ref().unwrap())
+    }
+}
+
 fn check_int<T>(node: &NodeProto, attr: &str, int: i64, is_list: bool) -> TractResult<T>
 where
     T: AsPrimitive<i64> + Bounded + Display,
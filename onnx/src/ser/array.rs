@@ -0,0 +1,315 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::array::{
+    Gather, GatherElements, GatherNd, MultiBroadcastTo, Pad, PadMode, Range, ScatterElements,
+    ScatterNd, Slice, Tile, Topk, Trilu, TypedConcat,
+};
+use tract_hir::tract_core::ops::identity::Identity;
+
+use super::{attr_int, attr_ints, attr_string, GraphBuilder, OnnxSerRegister};
+
+pub fn register_all_ops(reg: &mut OnnxSerRegister) {
+    reg.insert(axis_op);
+    reg.insert(concat);
+    reg.insert(gather);
+    reg.insert(gather_elements);
+    reg.insert(gather_nd);
+    reg.insert(identity);
+    reg.insert(multi_broadcast_to);
+    reg.insert(pad);
+    reg.insert(range);
+    reg.insert(scatter_elements);
+    reg.insert(scatter_nd);
+    reg.insert(slice);
+    reg.insert(tile);
+    reg.insert(topk);
+    reg.insert(trilu);
+}
+
+fn concrete(dims: &[TDim]) -> TractResult<Vec<i64>> {
+    dims.iter()
+        .map(|d| d.to_i64().with_context(|| format!("Symbolic dimension {d} is not supported")))
+        .collect()
+}
+
+fn axis_op(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &AxisOp,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let input_shape = &model.outlet_fact(node.inputs[0])?.shape;
+    match op {
+        AxisOp::Add(axis) => builder.node_with_axes(
+            "Unsqueeze",
+            13,
+            vec![input],
+            vec![output],
+            &[*axis as i64],
+            vec![],
+        )?,
+        AxisOp::Rm(axis) => builder.node_with_axes(
+            "Squeeze",
+            13,
+            vec![input],
+            vec![output],
+            &[*axis as i64],
+            vec![],
+        )?,
+        AxisOp::Move(from, to) => {
+            let mut perm: Vec<i64> = (0..input_shape.rank() as i64).collect();
+            let axis = perm.remove(*from);
+            perm.insert(*to, axis);
+            builder.node("Transpose", vec![input], vec![output], vec![attr_ints("perm", &perm)]);
+        }
+        AxisOp::Reshape(at, from, to) => {
+            // symbolic dimensions are copied from the input (0) when they stay in place, or
+            // inferred (-1) for at most one of them
+            let output_shape = &node.outputs[0].fact.shape;
+            let in_place = |ix: usize| ix < *at || (from.len() == to.len() && ix >= at + to.len());
+            let mut shape = vec![];
+            for (ix, dim) in output_shape.iter().enumerate() {
+                if let Ok(d) = dim.to_i64() {
+                    shape.push(d);
+                } else if in_place(ix) && input_shape[ix] == *dim {
+                    shape.push(0);
+                } else if !shape.contains(&-1) {
+                    shape.push(-1);
+                } else {
+                    bail!("Can not express reshape to {:?} in ONNX", output_shape)
+                }
+            }
+            let shape = builder.ints(format!("{}.shape", node.name), &shape)?;
+            builder.node("Reshape", vec![input, shape], vec![output], vec![]);
+        }
+    }
+    Ok(())
+}
+
+fn concat(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &TypedConcat,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Concat", inputs, vec![output], vec![attr_int("axis", op.axis as i64)]);
+    Ok(())
+}
+
+fn gather(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Gather,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Gather", inputs, vec![output], vec![attr_int("axis", op.axis as i64)]);
+    Ok(())
+}
+
+fn gather_elements(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &GatherElements,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("GatherElements", inputs, vec![output], vec![attr_int("axis", op.axis as i64)]);
+    Ok(())
+}
+
+fn gather_nd(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &GatherNd,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    let mut attributes = vec![];
+    if op.batch_dims > 0 {
+        builder.require_opset(12, "GatherND batch_dims")?;
+        attributes.push(attr_int("batch_dims", op.batch_dims as i64));
+    }
+    builder.node("GatherND", inputs, vec![output], attributes);
+    Ok(())
+}
+
+fn identity(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    _op: &Identity,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    builder.node("Identity", vec![input], vec![output], vec![]);
+    Ok(())
+}
+
+fn multi_broadcast_to(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &MultiBroadcastTo,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let input_shape = &model.outlet_fact(node.inputs[0])?.shape;
+    let offset = op.shape.rank() - input_shape.rank();
+    // Expand broadcasts both ways, so a 1 keeps symbolic input dimensions as they are
+    let shape = op
+        .shape
+        .iter()
+        .enumerate()
+        .map(|(ix, dim)| {
+            if let Ok(d) = dim.to_i64() {
+                Ok(d)
+            } else if ix >= offset && input_shape[ix - offset] == *dim {
+                Ok(1)
+            } else {
+                bail!("Can not broadcast to symbolic dimension {dim}")
+            }
+        })
+        .collect::<TractResult<Vec<i64>>>()?;
+    let shape = builder.ints(format!("{}.shape", node.name), &shape)?;
+    builder.node("Expand", vec![input, shape], vec![output], vec![]);
+    Ok(())
+}
+
+fn pad(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Pad,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let pads: Vec<i64> =
+        op.pads.iter().map(|p| p.0 as i64).chain(op.pads.iter().map(|p| p.1 as i64)).collect();
+    let mut inputs = vec![input, builder.ints(format!("{}.pads", node.name), &pads)?];
+    let mode = match &op.mode {
+        PadMode::Constant(value) => {
+            let dt = model.outlet_fact(node.inputs[0])?.datum_type;
+            let value = value.cast_to_dt(dt)?.into_owned();
+            inputs.push(builder.konst(format!("{}.value", node.name), &value)?);
+            "constant"
+        }
+        PadMode::Reflect => "reflect",
+        PadMode::Edge => "edge",
+    };
+    builder.node("Pad", inputs, vec![output], vec![attr_string("mode", mode)]);
+    Ok(())
+}
+
+fn range(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    _op: &Range,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Range", inputs, vec![output], vec![]);
+    Ok(())
+}
+
+fn scatter_elements(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &ScatterElements,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("ScatterElements", inputs, vec![output], vec![attr_int("axis", op.axis as i64)]);
+    Ok(())
+}
+
+fn scatter_nd(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    _op: &ScatterNd,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("ScatterND", inputs, vec![output], vec![]);
+    Ok(())
+}
+
+fn slice(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Slice,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let dim = &model.outlet_fact(node.inputs[0])?.shape[op.axis];
+    let start = concrete(&[op.start.clone()])?[0];
+    let end = if op.end == *dim { i64::MAX } else { concrete(&[op.end.clone()])?[0] };
+    let name = &node.name;
+    let inputs = vec![
+        input,
+        builder.ints(format!("{name}.starts"), &[start])?,
+        builder.ints(format!("{name}.ends"), &[end])?,
+        builder.ints(format!("{name}.axes"), &[op.axis as i64])?,
+    ];
+    builder.node("Slice", inputs, vec![output], vec![]);
+    Ok(())
+}
+
+fn tile(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Tile,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let repeats = builder.ints(format!("{}.repeats", node.name), &concrete(&op.multipliers)?)?;
+    builder.node("Tile", vec![input, repeats], vec![output], vec![]);
+    Ok(())
+}
+
+fn topk(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Topk,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let outputs = builder.outputs(model, node);
+    // ONNX expects k as a single element vector
+    let k = if let Some(k) = &model.outlet_fact(node.inputs[1])?.konst {
+        builder.ints(format!("{}.k", node.name), &[k.cast_to_scalar::<i64>()?])?
+    } else {
+        let k = builder.input(model, node, 1)?;
+        let shape = builder.ints(format!("{}.k_shape", node.name), &[1])?;
+        let reshaped = builder.unique_name(format!("{}.k", node.name));
+        builder.node("Reshape", vec![k, shape], vec![reshaped.clone()], vec![]);
+        reshaped
+    };
+    let attributes = vec![attr_int("axis", op.axis as i64), attr_int("largest", op.largest as i64)];
+    builder.node("TopK", vec![input, k], outputs, attributes);
+    Ok(())
+}
+
+fn trilu(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Trilu,
+) -> TractResult<()> {
+    builder.require_opset(14, "Trilu")?;
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Trilu", inputs, vec![output], vec![attr_int("upper", op.upper as i64)]);
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::binary::TypedBinOp;
+use tract_hir::tract_core::ops::cast::Cast;
+use tract_hir::tract_core::ops::einsum::EinSum;
+use tract_hir::tract_core::ops::element_wise::ElementWiseOp;
+use tract_hir::tract_core::ops::logic::{self, Iff};
+use tract_hir::tract_core::ops::{math, nn};
+
+use super::{attr_float, attr_int, attr_string, GraphBuilder, OnnxSerRegister};
+use crate::pb::tensor_proto::DataType;
+
+pub fn register_all_ops(reg: &mut OnnxSerRegister) {
+    reg.insert(bin);
+    reg.insert(cast);
+    reg.insert(einsum);
+    reg.insert(element_wise);
+    reg.insert(iff);
+}
+
+fn bin(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &TypedBinOp,
+) -> TractResult<()> {
+    ensure!(op.1.is_none(), "Quantized binary operators are not supported");
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    let is_bool = model.outlet_fact(node.inputs[0])?.datum_type == bool::datum_type();
+    macro_rules! direct {
+        ($($tract:ty => $onnx:expr),* $(,)?) => {
+            $(
+                if op.0.is::<$tract>() {
+                    builder.node($onnx, inputs, vec![output], vec![]);
+                    return Ok(());
+                }
+            )*
+        };
+    }
+    direct!(
+        math::Add => "Add",
+        math::Sub => "Sub",
+        math::Mul => "Mul",
+        math::Div => "Div",
+        math::Pow => "Pow",
+        math::Min => "Min",
+        math::Max => "Max",
+        logic::And => "And",
+        logic::Or => "Or",
+        logic::Xor => "Xor",
+        logic::Equals => "Equal",
+        logic::Less => "Less",
+        logic::Greater => "Greater",
+    );
+    if op.0.is::<math::Rem>() {
+        // tract remainder has the sign of the dividend, like C fmod
+        builder.node("Mod", inputs, vec![output], vec![attr_int("fmod", 1)]);
+    } else if op.0.is::<math::ShiftLeft>() || op.0.is::<math::ShiftRight>() {
+        let direction = if op.0.is::<math::ShiftLeft>() { "LEFT" } else { "RIGHT" };
+        builder.node("BitShift", inputs, vec![output], vec![attr_string("direction", direction)]);
+    } else if op.0.is::<logic::LessEqual>() || op.0.is::<logic::GreaterEqual>() {
+        builder.require_opset(12, "LessOrEqual and GreaterOrEqual")?;
+        let onnx = if op.0.is::<logic::LessEqual>() { "LessOrEqual" } else { "GreaterOrEqual" };
+        builder.node(onnx, inputs, vec![output], vec![]);
+    } else if op.0.is::<logic::NotEquals>() {
+        let equal = builder.unique_name(format!("{}.equal", node.name));
+        builder.node("Equal", inputs, vec![equal.clone()], vec![]);
+        builder.node("Not", vec![equal], vec![output], vec![]);
+    } else if op.0.is::<logic::BitAnd>() || op.0.is::<logic::BitOr>() || op.0.is::<logic::BitXor>()
+    {
+        let (logical, bitwise) = if op.0.is::<logic::BitAnd>() {
+            ("And", "BitwiseAnd")
+        } else if op.0.is::<logic::BitOr>() {
+            ("Or", "BitwiseOr")
+        } else {
+            ("Xor", "BitwiseXor")
+        };
+        if is_bool {
+            builder.node(logical, inputs, vec![output], vec![]);
+        } else {
+            builder.require_opset(18, bitwise)?;
+            builder.node(bitwise, inputs, vec![output], vec![]);
+        }
+    } else {
+        bail!("No ONNX counterpart for binary operator {}", op.0.name())
+    }
+    Ok(())
+}
+
+fn element_wise(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &ElementWiseOp,
+) -> TractResult<()> {
+    ensure!(op.1.is_none(), "Quantized element-wise operators are not supported");
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let dt = model.outlet_fact(node.inputs[0])?.datum_type;
+    macro_rules! direct {
+        ($($tract:ty => $onnx:expr),* $(,)?) => {
+            $(
+                if op.0.is::<$tract>() {
+                    builder.node($onnx, vec![input], vec![output], vec![]);
+                    return Ok(());
+                }
+            )*
+        };
+    }
+    direct!(
+        math::Abs => "Abs",
+        math::Exp => "Exp",
+        math::Ln => "Log",
+        math::Sqrt => "Sqrt",
+        math::Recip => "Reciprocal",
+        math::Ceil => "Ceil",
+        math::Floor => "Floor",
+        math::RoundHalfToEven => "Round",
+        math::Cos => "Cos",
+        math::Sin => "Sin",
+        math::Tan => "Tan",
+        math::Acos => "Acos",
+        math::Asin => "Asin",
+        math::Atan => "Atan",
+        math::Cosh => "Cosh",
+        math::Sinh => "Sinh",
+        math::Tanh => "Tanh",
+        math::Erf => "Erf",
+        math::Acosh => "Acosh",
+        math::Asinh => "Asinh",
+        math::Atanh => "Atanh",
+        math::Neg => "Neg",
+        math::Sign => "Sign",
+        logic::Not => "Not",
+        nn::Sigmoid => "Sigmoid",
+    );
+    let tmp = |builder: &mut GraphBuilder, suffix: &str| {
+        builder.unique_name(format!("{}.{suffix}", node.name))
+    };
+    if op.0.is::<math::Square>() {
+        builder.node("Mul", vec![input.clone(), input], vec![output], vec![]);
+    } else if op.0.is::<math::Rsqrt>() {
+        let sqrt = tmp(builder, "sqrt");
+        builder.node("Sqrt", vec![input], vec![sqrt.clone()], vec![]);
+        builder.node("Reciprocal", vec![sqrt], vec![output], vec![]);
+    } else if let Some(leaky) = op.0.downcast_ref::<nn::LeakyRelu>() {
+        builder.node(
+            "LeakyRelu",
+            vec![input],
+            vec![output],
+            vec![attr_float("alpha", leaky.alpha)],
+        );
+    } else if op.0.is::<nn::HardSwish>() {
+        if builder.opset >= 14 {
+            builder.node("HardSwish", vec![input], vec![output], vec![]);
+        } else {
+            let sigmoid = tmp(builder, "hard_sigmoid");
+            let attributes = vec![attr_float("alpha", 1. / 6.), attr_float("beta", 0.5)];
+            builder.node("HardSigmoid", vec![input.clone()], vec![sigmoid.clone()], attributes);
+            builder.node("Mul", vec![input, sigmoid], vec![output], vec![]);
+        }
+    } else if op.0.is::<nn::Silu>() {
+        let sigmoid = tmp(builder, "sigmoid");
+        builder.node("Sigmoid", vec![input.clone()], vec![sigmoid.clone()], vec![]);
+        builder.node("Mul", vec![input, sigmoid], vec![output], vec![]);
+    } else if op.0.is::<nn::Gelu>() {
+        if builder.opset >= 20 {
+            builder.node("Gelu", vec![input], vec![output], vec![]);
+        } else {
+            // 0.5 * x * (1 + erf(x / sqrt(2)))
+            let name = &node.name;
+            let rsqrt2 = builder.scalar(
+                format!("{name}.frac_1_sqrt_2"),
+                dt,
+                std::f32::consts::FRAC_1_SQRT_2,
+            )?;
+            let one = builder.scalar(format!("{name}.one"), dt, 1.0)?;
+            let half = builder.scalar(format!("{name}.half"), dt, 0.5)?;
+            let scaled = tmp(builder, "scaled");
+            let erf = tmp(builder, "erf");
+            let plus_one = tmp(builder, "plus_one");
+            let times_x = tmp(builder, "times_x");
+            builder.node("Mul", vec![input.clone(), rsqrt2], vec![scaled.clone()], vec![]);
+            builder.node("Erf", vec![scaled], vec![erf.clone()], vec![]);
+            builder.node("Add", vec![erf, one], vec![plus_one.clone()], vec![]);
+            builder.node("Mul", vec![input, plus_one], vec![times_x.clone()], vec![]);
+            builder.node("Mul", vec![times_x, half], vec![output], vec![]);
+        }
+    } else if op.0.is::<nn::GeluApproximate>() {
+        if builder.opset >= 20 {
+            let attributes = vec![attr_string("approximate", "tanh")];
+            builder.node("Gelu", vec![input], vec![output], attributes);
+        } else {
+            // 0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3)))
+            let name = &node.name;
+            let coef = builder.scalar(format!("{name}.coef"), dt, 0.044715)?;
+            let sqrt_2_over_pi = builder.scalar(
+                format!("{name}.sqrt_2_over_pi"),
+                dt,
+                (2.0 / std::f32::consts::PI).sqrt(),
+            )?;
+            let one = builder.scalar(format!("{name}.one"), dt, 1.0)?;
+            let half = builder.scalar(format!("{name}.half"), dt, 0.5)?;
+            let x2 = tmp(builder, "x2");
+            let x3 = tmp(builder, "x3");
+            let coef_x3 = tmp(builder, "coef_x3");
+            let inner = tmp(builder, "inner");
+            let scaled = tmp(builder, "scaled");
+            let tanh = tmp(builder, "tanh");
+            let plus_one = tmp(builder, "plus_one");
+            let times_x = tmp(builder, "times_x");
+            builder.node("Mul", vec![input.clone(), input.clone()], vec![x2.clone()], vec![]);
+            builder.node("Mul", vec![x2, input.clone()], vec![x3.clone()], vec![]);
+            builder.node("Mul", vec![x3, coef], vec![coef_x3.clone()], vec![]);
+            builder.node("Add", vec![input.clone(), coef_x3], vec![inner.clone()], vec![]);
+            builder.node("Mul", vec![inner, sqrt_2_over_pi], vec![scaled.clone()], vec![]);
+            builder.node("Tanh", vec![scaled], vec![tanh.clone()], vec![]);
+            builder.node("Add", vec![tanh, one], vec![plus_one.clone()], vec![]);
+            builder.node("Mul", vec![input, plus_one], vec![times_x.clone()], vec![]);
+            builder.node("Mul", vec![times_x, half], vec![output], vec![]);
+        }
+    } else if op.0.is::<logic::BitNot>() {
+        if dt == bool::datum_type() {
+            builder.node("Not", vec![input], vec![output], vec![]);
+        } else {
+            builder.require_opset(18, "BitwiseNot")?;
+            builder.node("BitwiseNot", vec![input], vec![output], vec![]);
+        }
+    } else {
+        bail!("No ONNX counterpart for element-wise operator {}", op.0.name())
+    }
+    Ok(())
+}
+
+fn cast(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Cast,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let to = DataType::try_from(op.to)? as i64;
+    builder.node("Cast", vec![input], vec![output], vec![attr_int("to", to)]);
+    Ok(())
+}
+
+fn iff(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    _op: &Iff,
+) -> TractResult<()> {
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Where", inputs, vec![output], vec![]);
+    Ok(())
+}
+
+fn einsum(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &EinSum,
+) -> TractResult<()> {
+    ensure!(op.q_params.is_none(), "Quantized EinSum is not supported");
+    builder.require_opset(12, "Einsum")?;
+    let mut inputs = builder.inputs(model, node)?;
+    for (ix, input) in inputs.iter_mut().enumerate() {
+        if model.outlet_fact(node.inputs[ix])?.datum_type != op.operating_dt {
+            let cast = builder.unique_name(format!("{}.cast_{ix}", node.name));
+            let to = DataType::try_from(op.operating_dt)? as i64;
+            builder.node("Cast", vec![input.clone()], vec![cast.clone()], vec![attr_int("to", to)]);
+            *input = cast;
+        }
+    }
+    let output = builder.output(model, node);
+    let (ins, outs) = op.axes.clone().relabel()?.to_strs();
+    let equation = format!("{}->{}", ins.join(","), outs.join(","));
+    builder.node("Einsum", inputs, vec![output], vec![attr_string("equation", &equation)]);
+    Ok(())
+}
@@ -0,0 +1,436 @@
+//! ONNX serialization of TypedModels.
+//!
+//! The model is first rewritten to fit ONNX conventions (NCHW data, OIHW kernels, vector
+//! biases), then each node is translated by the serializer registered for its op type.
+//! Constants become initializers when they are first used.
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use prost::Message;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::konst::Const;
+use tract_hir::tract_core::ops::source::TypedSource;
+
+use crate::model::Onnx;
+use crate::pb::attribute_proto::AttributeType;
+use crate::pb::tensor_proto::{DataLocation, DataType};
+use crate::pb::tensor_shape_proto::dimension::Value as DimValue;
+use crate::pb::*;
+
+mod array;
+mod math;
+mod nn;
+pub mod rewriter;
+
+pub const DEFAULT_EXPORT_OPSET: i64 = 17;
+pub const SUPPORTED_EXPORT_OPSETS: std::ops::RangeInclusive<i64> = 11..=21;
+const EXTERNAL_DATA_ALIGNMENT: usize = 4096;
+
+pub type OpSerializer =
+    Arc<dyn Fn(&mut GraphBuilder, &TypedModel, &TypedNode) -> TractResult<()> + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct OnnxSerRegister(pub HashMap<TypeId, OpSerializer>);
+
+impl OnnxSerRegister {
+    pub fn insert<T: Op>(
+        &mut self,
+        ser: fn(&mut GraphBuilder, &TypedModel, &TypedNode, &T) -> TractResult<()>,
+    ) {
+        self.0.insert(TypeId::of::<T>(), Arc::new(move |b, m, n| ser(b, m, n, n.op_as().unwrap())));
+    }
+}
+
+pub fn register_all_ops(reg: &mut OnnxSerRegister) {
+    array::register_all_ops(reg);
+    math::register_all_ops(reg);
+    nn::register_all_ops(reg);
+}
+
+pub struct GraphBuilder<'a> {
+    pub framework: &'a Onnx,
+    pub opset: i64,
+    pub graph: GraphProto,
+    names: HashMap<OutletId, String>,
+    used: HashSet<String>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    pub fn new(framework: &'a Onnx) -> GraphBuilder<'a> {
+        GraphBuilder {
+            framework,
+            opset: framework.export_opset,
+            graph: GraphProto { name: "main".into(), ..GraphProto::default() },
+            names: HashMap::default(),
+            used: HashSet::default(),
+        }
+    }
+
+    pub fn require_opset(&self, opset: i64, what: &str) -> TractResult<()> {
+        ensure!(
+            self.opset >= opset,
+            "{} requires ONNX opset {} or later, exporting to opset {}",
+            what,
+            opset,
+            self.opset
+        );
+        Ok(())
+    }
+
+    /// A tensor name derived from `name` and not used yet in the graph.
+    pub fn unique_name(&mut self, name: impl Into<String>) -> String {
+        let name = name.into();
+        let mut candidate = name.clone();
+        let mut ix = 0;
+        while self.used.contains(&candidate) {
+            ix += 1;
+            candidate = format!("{name}.{ix}");
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+
+    /// Name of the tensor for an outlet. Constants are written as initializers on first use.
+    pub fn outlet(&mut self, model: &TypedModel, outlet: OutletId) -> TractResult<String> {
+        if let Some(name) = self.names.get(&outlet) {
+            return Ok(name.clone());
+        }
+        let node = model.node(outlet.node);
+        let Some(konst) = node.op_as::<Const>() else {
+            bail!("{} is used before being serialized", node)
+        };
+        ensure!(
+            node.outputs[0].fact.per_axis_q.is_none(),
+            "Per-axis quantized constants are not supported"
+        );
+        let name = self.konst(&node.name, &konst.0)?;
+        self.names.insert(outlet, name.clone());
+        Ok(name)
+    }
+
+    pub fn input(
+        &mut self,
+        model: &TypedModel,
+        node: &TypedNode,
+        ix: usize,
+    ) -> TractResult<String> {
+        self.outlet(model, node.inputs[ix])
+    }
+
+    pub fn inputs(&mut self, model: &TypedModel, node: &TypedNode) -> TractResult<Vec<String>> {
+        node.inputs.iter().map(|i| self.outlet(model, *i)).collect()
+    }
+
+    /// Names of the tensors for the node outputs.
+    pub fn outputs(&mut self, model: &TypedModel, node: &TypedNode) -> Vec<String> {
+        (0..node.outputs.len())
+            .map(|slot| {
+                let outlet = OutletId::new(node.id, slot);
+                if let Some(name) = self.names.get(&outlet) {
+                    return name.clone();
+                }
+                let name = if let Some(label) = model.outlet_label(outlet) {
+                    label.to_string()
+                } else if slot == 0 {
+                    node.name.clone()
+                } else {
+                    format!("{}.{}", node.name, slot)
+                };
+                let name = self.unique_name(name);
+                self.names.insert(outlet, name.clone());
+                name
+            })
+            .collect()
+    }
+
+    pub fn output(&mut self, model: &TypedModel, node: &TypedNode) -> String {
+        self.outputs(model, node).remove(0)
+    }
+
+    /// Add a tensor as an initializer, returning its name.
+    pub fn konst(&mut self, name: impl Into<String>, tensor: &Tensor) -> TractResult<String> {
+        let name = self.unique_name(name);
+        let mut proto = TensorProto::try_from(tensor)?;
+        proto.name = name.clone();
+        self.graph.initializer.push(proto);
+        Ok(name)
+    }
+
+    pub fn ints(&mut self, name: impl Into<String>, values: &[i64]) -> TractResult<String> {
+        self.konst(name, &tensor1(values))
+    }
+
+    /// A scalar initializer of type `dt`.
+    pub fn scalar(
+        &mut self,
+        name: impl Into<String>,
+        dt: DatumType,
+        value: f32,
+    ) -> TractResult<String> {
+        self.konst(name, &tensor0(value).cast_to_dt(dt)?.into_owned())
+    }
+
+    /// Add a node, named after its first output.
+    pub fn node(
+        &mut self,
+        op_type: &str,
+        input: Vec<String>,
+        output: Vec<String>,
+        attribute: Vec<AttributeProto>,
+    ) {
+        self.graph.node.push(NodeProto {
+            name: output[0].clone(),
+            op_type: op_type.into(),
+            input,
+            output,
+            attribute,
+            ..NodeProto::default()
+        })
+    }
+
+    /// Add a node taking a list of axes: as an input from `opset` on, as an attribute
+    /// before.
+    pub fn node_with_axes(
+        &mut self,
+        op_type: &str,
+        opset: i64,
+        mut input: Vec<String>,
+        output: Vec<String>,
+        axes: &[i64],
+        mut attribute: Vec<AttributeProto>,
+    ) -> TractResult<()> {
+        if self.opset >= opset {
+            input.push(self.ints(format!("{}.axes", output[0]), axes)?);
+        } else {
+            attribute.push(attr_ints("axes", axes));
+        }
+        self.node(op_type, input, output, attribute);
+        Ok(())
+    }
+
+    pub fn write_graph(&mut self, model: &TypedModel) -> TractResult<()> {
+        let framework = self.framework;
+        for input in model.input_outlets()? {
+            let name = self.outputs(model, model.node(input.node))[input.slot].clone();
+            self.graph.input.push(value_info(&name, model.outlet_fact(*input)?)?);
+        }
+        for id in model.eval_order()? {
+            let node = model.node(id);
+            if node.op_is::<Const>() || node.op_is::<TypedSource>() {
+                continue;
+            }
+            let ser = framework
+                .ser_register
+                .0
+                .get(&(*(node.op)).type_id())
+                .with_context(|| format!("No ONNX serializer for op {}", node.op.name()))?;
+            ser(self, model, node).with_context(|| format!("Serializing {node}"))?;
+        }
+        for output in model.output_outlets()? {
+            let mut name = self.outlet(model, *output)?;
+            let producer = model.node(output.node);
+            if producer.op_is::<Const>()
+                || producer.op_is::<TypedSource>()
+                || self.graph.output.iter().any(|o| o.name == name)
+            {
+                let identity = self.unique_name(format!("{name}.output"));
+                self.node("Identity", vec![name], vec![identity.clone()], vec![]);
+                name = identity;
+            }
+            self.graph.output.push(value_info(&name, model.outlet_fact(*output)?)?);
+        }
+        Ok(())
+    }
+}
+
+pub fn attr_int(name: &str, i: i64) -> AttributeProto {
+    AttributeProto { name: name.into(), r#type: AttributeType::Int as i32, i, ..Default::default() }
+}
+
+pub fn attr_ints(name: &str, ints: &[i64]) -> AttributeProto {
+    AttributeProto {
+        name: name.into(),
+        r#type: AttributeType::Ints as i32,
+        ints: ints.to_vec(),
+        ..Default::default()
+    }
+}
+
+pub fn attr_float(name: &str, f: f32) -> AttributeProto {
+    AttributeProto {
+        name: name.into(),
+        r#type: AttributeType::Float as i32,
+        f,
+        ..Default::default()
+    }
+}
+
+pub fn attr_string(name: &str, s: &str) -> AttributeProto {
+    AttributeProto {
+        name: name.into(),
+        r#type: AttributeType::String as i32,
+        s: s.as_bytes().to_vec(),
+        ..Default::default()
+    }
+}
+
+fn value_info(name: &str, fact: &TypedFact) -> TractResult<ValueInfoProto> {
+    let dim = fact
+        .shape
+        .iter()
+        .map(|d| tensor_shape_proto::Dimension {
+            value: Some(match d.to_i64() {
+                Ok(v) => DimValue::DimValue(v),
+                Err(_) => DimValue::DimParam(d.to_string()),
+            }),
+            ..Default::default()
+        })
+        .collect();
+    let tensor = type_proto::Tensor {
+        elem_type: DataType::try_from(fact.datum_type)? as i32,
+        shape: Some(TensorShapeProto { dim }),
+    };
+    Ok(ValueInfoProto {
+        name: name.into(),
+        r#type: Some(TypeProto {
+            value: Some(type_proto::Value::TensorType(tensor)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+fn ir_version(opset: i64) -> i64 {
+    match opset {
+        ..=11 => 6,
+        12..=14 => 7,
+        15..=18 => 8,
+        19..=20 => 9,
+        _ => 10,
+    }
+}
+
+impl Onnx {
+    pub fn with_export_opset(self, opset: i64) -> Onnx {
+        Self { export_opset: opset, ..self }
+    }
+
+    /// Move initializers of `threshold` bytes or more to an external data file when writing
+    /// with `write_to_path`.
+    pub fn with_external_data_threshold(self, threshold: Option<usize>) -> Onnx {
+        Self { external_data_threshold: threshold, ..self }
+    }
+
+    pub fn model_proto_for_typed_model(&self, model: &TypedModel) -> TractResult<ModelProto> {
+        ensure!(
+            SUPPORTED_EXPORT_OPSETS.contains(&self.export_opset),
+            "Can not export to ONNX opset {} (supported: {:?})",
+            self.export_opset,
+            SUPPORTED_EXPORT_OPSETS
+        );
+        let mut model = model.clone();
+        rewriter::rewrite_for_onnx(&mut model).context("Rewriting model for ONNX")?;
+        let mut builder = GraphBuilder::new(self);
+        builder.write_graph(&model)?;
+        Ok(ModelProto {
+            ir_version: ir_version(self.export_opset),
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: self.export_opset,
+            }],
+            producer_name: "tract".into(),
+            producer_version: env!("CARGO_PKG_VERSION").into(),
+            graph: Some(builder.graph),
+            ..ModelProto::default()
+        })
+    }
+
+    /// Write the model as a single protobuf, all tensors inlined.
+    pub fn write(&self, model: &TypedModel, mut w: impl Write) -> TractResult<()> {
+        let proto = self.model_proto_for_typed_model(model)?;
+        w.write_all(&proto.encode_to_vec())?;
+        Ok(())
+    }
+
+    /// Write the model to a file. With an external data threshold, the large initializers
+    /// go to a "<file name>.data" file next to it.
+    pub fn write_to_path(&self, model: &TypedModel, path: impl AsRef<Path>) -> TractResult<()> {
+        let path = path.as_ref();
+        let mut proto = self.model_proto_for_typed_model(model)?;
+        if let Some(threshold) = self.external_data_threshold {
+            let file_name = path.file_name().with_context(|| format!("Invalid path {path:?}"))?;
+            let location = format!("{}.data", file_name.to_string_lossy());
+            let data_path = path.with_file_name(&location);
+            let mut data: Option<File> = None;
+            let mut offset = 0usize;
+            for tensor in &mut proto.graph.as_mut().unwrap().initializer {
+                if tensor.raw_data.len() < threshold || tensor.raw_data.is_empty() {
+                    continue;
+                }
+                if data.is_none() {
+                    data = Some(
+                        File::create(&data_path)
+                            .with_context(|| format!("Creating {data_path:?}"))?,
+                    );
+                }
+                let data = data.as_mut().unwrap();
+                let padding = offset.next_multiple_of(EXTERNAL_DATA_ALIGNMENT) - offset;
+                data.write_all(&vec![0u8; padding])?;
+                offset += padding;
+                let raw = std::mem::take(&mut tensor.raw_data);
+                data.write_all(&raw)?;
+                tensor.data_location = Some(DataLocation::External as i32);
+                tensor.external_data = [
+                    ("location", location.clone()),
+                    ("offset", offset.to_string()),
+                    ("length", raw.len().to_string()),
+                ]
+                .into_iter()
+                .map(|(key, value)| StringStringEntryProto { key: key.into(), value })
+                .collect();
+                offset += raw.len();
+            }
+        }
+        let mut file = File::create(path).with_context(|| format!("Creating {path:?}"))?;
+        file.write_all(&proto.encode_to_vec())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_hir::prelude::Framework;
+
+    #[test]
+    fn external_data_round_trip() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let dir = dir.path();
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 3]))?;
+        let w = model.add_const("w", Tensor::from_shape(&[2, 3], &[1f32, 2., 3., 4., 5., 6.])?)?;
+        let b = model.add_const("b", tensor0(1f32))?;
+        let y = model.wire_node("mul", tract_hir::tract_core::ops::math::mul(), &[x, w])?;
+        let b = model.wire_node("b.broadcast", AxisOp::Add(0), &[b])?;
+        let b = model.wire_node("b.broadcast2", AxisOp::Add(0), &b)?;
+        let y = model.wire_node("add", tract_hir::tract_core::ops::math::add(), &[y[0], b[0]])?;
+        model.set_output_outlets(&y)?;
+
+        let onnx = crate::onnx().with_external_data_threshold(Some(16));
+        let path = dir.join("model.onnx");
+        onnx.write_to_path(&model, &path)?;
+        assert!(dir.join("model.onnx.data").exists());
+        let proto = onnx.proto_model_for_path(&path)?;
+        let initializers = &proto.graph.as_ref().unwrap().initializer;
+        assert_eq!(initializers.iter().filter(|t| !t.external_data.is_empty()).count(), 1);
+
+        let reloaded = onnx.model_for_path(&path)?.into_optimized()?;
+        let x = Tensor::from_shape(&[2, 3], &[1f32; 6])?;
+        let y = reloaded.into_runnable()?.run(tvec!(x.into()))?;
+        assert_eq!(*y[0], Tensor::from_shape(&[2, 3], &[2f32, 3., 4., 5., 6., 7.])?);
+        Ok(())
+    }
+}
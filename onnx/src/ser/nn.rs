@@ -0,0 +1,213 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::cnn::{
+    Conv, Deconv, KernelFormat, MaxPool, PaddingSpec, PoolSpec, SumPool,
+};
+use tract_hir::tract_core::ops::nn::{DataFormat, Reduce, Reducer, Softmax};
+
+use super::{attr_int, attr_ints, attr_string, GraphBuilder, OnnxSerRegister};
+use crate::pb::AttributeProto;
+
+pub fn register_all_ops(reg: &mut OnnxSerRegister) {
+    reg.insert(conv);
+    reg.insert(deconv);
+    reg.insert(max_pool);
+    reg.insert(reduce);
+    reg.insert(softmax);
+    reg.insert(sum_pool);
+}
+
+fn ints(values: &[usize]) -> Vec<i64> {
+    values.iter().map(|v| *v as i64).collect()
+}
+
+fn pool_attributes(spec: &PoolSpec, with_dilations: bool) -> TractResult<Vec<AttributeProto>> {
+    ensure!(spec.data_format == DataFormat::NCHW, "Expects NCHW data, got {:?}", spec.data_format);
+    let mut attributes = vec![
+        attr_ints("kernel_shape", &ints(&spec.kernel_shape)),
+        attr_ints("strides", &ints(&spec.strides())),
+    ];
+    if with_dilations {
+        attributes.push(attr_ints("dilations", &ints(&spec.dilations())));
+    }
+    match &spec.padding {
+        PaddingSpec::Explicit(before, after) => {
+            attributes.push(attr_ints("pads", &ints(&[&before[..], &after[..]].concat())))
+        }
+        PaddingSpec::ExplicitOnnxPool(before, after, ceil_mode) => {
+            attributes.push(attr_ints("pads", &ints(&[&before[..], &after[..]].concat())));
+            attributes.push(attr_int("ceil_mode", *ceil_mode as i64));
+        }
+        PaddingSpec::Valid => attributes.push(attr_string("auto_pad", "VALID")),
+        PaddingSpec::SameUpper => attributes.push(attr_string("auto_pad", "SAME_UPPER")),
+        PaddingSpec::SameLower => attributes.push(attr_string("auto_pad", "SAME_LOWER")),
+    }
+    Ok(attributes)
+}
+
+fn conv(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Conv,
+) -> TractResult<()> {
+    ensure!(op.q_params.is_none(), "Quantized convolutions are not supported");
+    ensure!(op.kernel_fmt == KernelFormat::OIHW, "Expects OIHW kernel");
+    ensure!(
+        !matches!(op.pool_spec.padding, PaddingSpec::ExplicitOnnxPool(_, _, true)),
+        "Convolutions can not use ceil mode"
+    );
+    let mut attributes = pool_attributes(&op.pool_spec, true)?;
+    attributes.retain(|a| a.name != "ceil_mode");
+    attributes.push(attr_int("group", op.group as i64));
+    let inputs = builder.inputs(model, node)?;
+    let output = builder.output(model, node);
+    builder.node("Conv", inputs, vec![output], attributes);
+    Ok(())
+}
+
+fn deconv(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Deconv,
+) -> TractResult<()> {
+    ensure!(op.kernel_format == KernelFormat::OIHW, "Expects OIHW kernel");
+    let mut attributes = pool_attributes(&op.pool_spec, true)?;
+    attributes.push(attr_int("group", op.group as i64));
+    if op.adjustments.iter().any(|a| *a != 0) {
+        attributes.push(attr_ints("output_padding", &ints(&op.adjustments)));
+    }
+    let mut inputs = builder.inputs(model, node)?;
+    // ONNX transposed convolution kernels are laid out as (group * input channels, output
+    // channels per group, spatial...)
+    if let Some(kernel) = &model.outlet_fact(node.inputs[1])?.konst {
+        let kernel = kernel
+            .clone()
+            .into_tensor()
+            .split_axis(0, op.group)?
+            .move_axis(1, 2)?
+            .collapse_axis_with_next(0);
+        inputs[1] = builder.konst(format!("{}.kernel", node.name), &kernel)?;
+    } else {
+        ensure!(op.group == 1, "Grouped transposed convolutions need a constant kernel");
+        let mut perm: Vec<i64> = (0..op.pool_spec.rank() as i64 + 2).collect();
+        perm.swap(0, 1);
+        let kernel = builder.unique_name(format!("{}.kernel", node.name));
+        builder.node(
+            "Transpose",
+            vec![inputs[1].clone()],
+            vec![kernel.clone()],
+            vec![attr_ints("perm", &perm)],
+        );
+        inputs[1] = kernel;
+    }
+    let output = builder.output(model, node);
+    builder.node("ConvTranspose", inputs, vec![output], attributes);
+    Ok(())
+}
+
+fn max_pool(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &MaxPool,
+) -> TractResult<()> {
+    if let Some(dt) = op.with_index_outputs {
+        ensure!(dt == i64::datum_type(), "MaxPool indices must be i64");
+    }
+    let attributes = pool_attributes(&op.pool_spec, true)?;
+    let input = builder.input(model, node, 0)?;
+    let outputs = builder.outputs(model, node);
+    builder.node("MaxPool", vec![input], outputs, attributes);
+    Ok(())
+}
+
+fn sum_pool(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &SumPool,
+) -> TractResult<()> {
+    let dilated = op.pool_spec.dilations().iter().any(|d| *d != 1);
+    if dilated {
+        builder.require_opset(19, "AveragePool dilations")?;
+    }
+    let mut attributes = pool_attributes(&op.pool_spec, dilated)?;
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    if op.normalize {
+        attributes.push(attr_int("count_include_pad", op.count_include_pad as i64));
+        builder.node("AveragePool", vec![input], vec![output], attributes);
+    } else {
+        // padding counts as zeros, so the sum is the average over the whole kernel
+        attributes.push(attr_int("count_include_pad", 1));
+        let average = builder.unique_name(format!("{}.average", node.name));
+        builder.node("AveragePool", vec![input], vec![average.clone()], attributes);
+        let dt = node.outputs[0].fact.datum_type;
+        let volume = op.pool_spec.kernel_shape.iter().product::<usize>() as f32;
+        let volume = builder.scalar(format!("{}.volume", node.name), dt, volume)?;
+        builder.node("Mul", vec![average, volume], vec![output], vec![]);
+    }
+    Ok(())
+}
+
+fn reduce(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Reduce,
+) -> TractResult<()> {
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    let axes = ints(&op.axes);
+    let keepdims = attr_int("keepdims", 1);
+    match op.reducer {
+        Reducer::ArgMax(last) | Reducer::ArgMin(last) => {
+            ensure!(axes.len() == 1, "ArgMax and ArgMin work on a single axis");
+            let onnx = if matches!(op.reducer, Reducer::ArgMax(_)) { "ArgMax" } else { "ArgMin" };
+            let mut attributes = vec![attr_int("axis", axes[0]), keepdims];
+            if last {
+                builder.require_opset(12, "select_last_index")?;
+                attributes.push(attr_int("select_last_index", 1));
+            }
+            builder.node(onnx, vec![input], vec![output], attributes);
+        }
+        Reducer::Sum => builder.node_with_axes(
+            "ReduceSum",
+            13,
+            vec![input],
+            vec![output],
+            &axes,
+            vec![keepdims],
+        )?,
+        Reducer::Prod | Reducer::Min | Reducer::Max => {
+            let onnx = match op.reducer {
+                Reducer::Prod => "ReduceProd",
+                Reducer::Min => "ReduceMin",
+                _ => "ReduceMax",
+            };
+            builder.node_with_axes(onnx, 18, vec![input], vec![output], &axes, vec![keepdims])?
+        }
+        Reducer::MeanOfSquares => bail!("MeanOfSquares should have been expanded"),
+    }
+    Ok(())
+}
+
+fn softmax(
+    builder: &mut GraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Softmax,
+) -> TractResult<()> {
+    ensure!(op.quant_output_dt.is_none(), "Quantized softmax is not supported");
+    ensure!(op.axes.len() == 1, "ONNX Softmax works on a single axis");
+    let rank = model.outlet_fact(node.inputs[0])?.rank();
+    // before opset 13, Softmax flattens the input from the axis on
+    if builder.opset < 13 {
+        ensure!(op.axes[0] == rank - 1, "Softmax on an inner axis requires opset 13");
+    }
+    let input = builder.input(model, node, 0)?;
+    let output = builder.output(model, node);
+    builder.node("Softmax", vec![input], vec![output], vec![attr_int("axis", op.axes[0] as i64)]);
+    Ok(())
+}
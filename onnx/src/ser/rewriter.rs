@@ -0,0 +1,158 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::cnn::{
+    rewrite_conv_with_n_axis, rewrite_deconv_with_n_axis, wire_reshape_bias_as_vector, Conv,
+    Deconv, MaxPool, PoolSpec, SumPool,
+};
+use tract_hir::tract_core::ops::nn::{expand_mean_of_squares, DataFormat};
+use tract_nnef::ops::nnef::ser::{rewrite_kernel_conv_in_oihw, rewrite_kernel_deconv_in_oihw};
+
+pub fn rewrite_for_onnx(model: &mut TypedModel) -> TractResult<()> {
+    Rewriter::default()
+        .with_rule_for("rewrite_conv_with_n_axis", rewrite_conv_with_n_axis)
+        .with_rule_for("rewrite_deconv_with_n_axis", rewrite_deconv_with_n_axis)
+        .with_rule_for("rewrite_kernel_conv_in_oihw", rewrite_kernel_conv_in_oihw)
+        .with_rule_for("rewrite_kernel_deconv_in_oihw", rewrite_kernel_deconv_in_oihw)
+        .with_rule_for("conv-to-nchw", conv_to_nchw)
+        .with_rule_for("deconv-to-nchw", deconv_to_nchw)
+        .with_rule_for("maxpool-to-nchw", maxpool_to_nchw)
+        .with_rule_for("sumpool-to-nchw", sumpool_to_nchw)
+        .with_rule_for("conv_bias_as_vector", conv_bias_as_vector)
+        .with_rule_for("deconv_bias_as_vector", deconv_bias_as_vector)
+        .with_rule_for("expand-means-of-square", expand_mean_of_squares)
+        .rewrite(&(), model)
+}
+
+fn conv_to_nchw(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    conv: &Conv,
+) -> TractResult<Option<TypedModelPatch>> {
+    to_nchw(model, node, name, &conv.pool_spec, &|pool_spec| {
+        Box::new(Conv { pool_spec, ..conv.clone() })
+    })
+}
+
+fn deconv_to_nchw(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    to_nchw(model, node, name, &deconv.pool_spec, &|pool_spec| {
+        Box::new(Deconv { pool_spec, ..deconv.clone() })
+    })
+}
+
+fn maxpool_to_nchw(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    op: &MaxPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    // indices are computed on the flattened input, they would not survive the transposition
+    if op.with_index_outputs.is_some() {
+        return Ok(None);
+    }
+    to_nchw(model, node, name, &op.pool_spec, &|pool_spec| {
+        Box::new(MaxPool { pool_spec, ..op.clone() })
+    })
+}
+
+fn sumpool_to_nchw(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    op: &SumPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    to_nchw(model, node, name, &op.pool_spec, &|pool_spec| {
+        Box::new(SumPool { pool_spec, ..op.clone() })
+    })
+}
+
+fn to_nchw(
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    old: &PoolSpec,
+    op: &dyn Fn(PoolSpec) -> Box<dyn TypedOp>,
+) -> TractResult<Option<TypedModelPatch>> {
+    let format = old.data_format;
+    if format == DataFormat::NCHW {
+        return Ok(None);
+    }
+    let rank = old.rank() + 2;
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    if !format.has_n() {
+        wire[0] = patch.wire_node(format!("{name}.add_n"), AxisOp::Add(0), &[wire[0]])?[0];
+    }
+    if format.c_is_last() {
+        wire[0] =
+            patch.wire_node(format!("{name}.nchw"), AxisOp::Move(rank - 1, 1), &[wire[0]])?[0];
+    }
+    let new = PoolSpec { data_format: DataFormat::NCHW, ..old.clone() };
+    let outputs = patch.wire_node(name, op(new), &wire)?;
+    for (slot, mut output) in outputs.into_iter().enumerate() {
+        if format.c_is_last() {
+            output = patch.wire_node(
+                format!("{name}.nhwc.{slot}"),
+                AxisOp::Move(1, rank - 1),
+                &[output],
+            )?[0];
+        }
+        if !format.has_n() {
+            output = patch.wire_node(format!("{name}.rm_n.{slot}"), AxisOp::Rm(0), &[output])?[0];
+        }
+        patch.shunt_outside(model, OutletId::new(node.id, slot), output)?;
+    }
+    Ok(Some(patch))
+}
+
+fn conv_bias_as_vector(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    conv: &Conv,
+) -> TractResult<Option<TypedModelPatch>> {
+    bias_as_vector(model, node, name, conv.output_channels(), Box::new(conv.clone()))
+}
+
+fn deconv_bias_as_vector(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    bias_as_vector(model, node, name, deconv.pool_spec.output_channels, Box::new(deconv.clone()))
+}
+
+fn bias_as_vector(
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    co: usize,
+    op: Box<dyn TypedOp>,
+) -> TractResult<Option<TypedModelPatch>> {
+    let bias_fact = model.outlet_fact(node.inputs[2])?;
+    if *bias_fact.shape == [co.to_dim()] {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[2] = if bias_fact.shape.volume().is_one() {
+        wire_reshape_bias_as_vector(&mut patch, name, wire[2], co)?[0]
+    } else {
+        let reshape = AxisOp::Reshape(0, bias_fact.shape.to_tvec(), tvec!(co.to_dim()));
+        patch.wire_node(format!("{name}.bias"), reshape, &[wire[2]])?[0]
+    };
+    wire = patch.wire_node(name, op, &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
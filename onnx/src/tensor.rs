@@ -6,6 +6,8 @@ use prost::Message;
 use std::convert::{TryFrom, TryInto};
 use std::path::PathBuf;
 use tract_hir::internal::*;
+use tract_onnx_opl::optional::OptionalFact;
+use tract_onnx_opl::sequence::SequenceFact;
 
 impl TryFrom<DataType> for DatumType {
     type Error = TractError;
@@ -21,6 +23,7 @@ impl TryFrom<DataType> for DatumType {
             DataType::Int32 => Ok(DatumType::I32),
             DataType::Int64 => Ok(DatumType::I64),
             DataType::Float16 => Ok(DatumType::F16),
+            DataType::Bfloat16 => Ok(DatumType::BF16),
             DataType::Float => Ok(DatumType::F32),
             DataType::Double => Ok(DatumType::F64),
             DataType::String => Ok(DatumType::String),
@@ -29,6 +32,51 @@ impl TryFrom<DataType> for DatumType {
     }
 }
 
+impl TryFrom<DatumType> for DataType {
+    type Error = TractError;
+    fn try_from(t: DatumType) -> TractResult<DataType> {
+        match t {
+            DatumType::Bool => Ok(DataType::Bool),
+            DatumType::U8 => Ok(DataType::Uint8),
+            DatumType::U16 => Ok(DataType::Uint16),
+            DatumType::U32 => Ok(DataType::Uint32),
+            DatumType::U64 => Ok(DataType::Uint64),
+            DatumType::I8 => Ok(DataType::Int8),
+            DatumType::I16 => Ok(DataType::Int16),
+            DatumType::I32 => Ok(DataType::Int32),
+            DatumType::I64 | DatumType::TDim => Ok(DataType::Int64),
+            DatumType::F16 => Ok(DataType::Float16),
+            DatumType::BF16 => Ok(DataType::Bfloat16),
+            DatumType::F32 => Ok(DataType::Float),
+            DatumType::F64 => Ok(DataType::Double),
+            DatumType::String => Ok(DataType::String),
+            _ => bail!("No ONNX type for {:?}", t),
+        }
+    }
+}
+
+impl TryFrom<&Tensor> for TensorProto {
+    type Error = TractError;
+    fn try_from(t: &Tensor) -> TractResult<TensorProto> {
+        let mut proto = TensorProto {
+            dims: t.shape().iter().map(|d| *d as i64).collect(),
+            data_type: DataType::try_from(t.datum_type())? as i32,
+            ..TensorProto::default()
+        };
+        match t.datum_type() {
+            DatumType::String => {
+                proto.string_data =
+                    t.as_slice::<String>()?.iter().map(|s| s.as_bytes().to_vec()).collect()
+            }
+            DatumType::TDim => {
+                proto.raw_data = t.cast_to::<i64>()?.as_bytes().to_vec();
+            }
+            _ => proto.raw_data = t.as_bytes().to_vec(),
+        }
+        Ok(proto)
+    }
+}
+
 pub fn translate_inference_fact(
     ctx: &ParsingContext,
     t: &type_proto::Tensor,
@@ -63,12 +111,42 @@ pub fn translate_inference_fact(
     Ok(fact)
 }
 
-fn get_external_resources(
+/// Translates tensor types, and sequence and optional types to opaque scalars.
+pub fn translate_type_proto(
+    ctx: &ParsingContext,
+    t: &TypeProto,
+    include_unknown_symbols: bool,
+) -> TractResult<InferenceFact> {
+    let element = |elem_type: &Option<Box<TypeProto>>| {
+        let elem_type = elem_type.as_deref().context("Missing element type")?;
+        translate_type_proto(ctx, elem_type, include_unknown_symbols)
+    };
+    let opaque = InferenceFact::dt_shape(Opaque::datum_type(), ShapeFactoid::closed(tvec!()));
+    match t.value.as_ref().context("Missing type")? {
+        type_proto::Value::TensorType(t) => {
+            translate_inference_fact(ctx, t, include_unknown_symbols)
+        }
+        type_proto::Value::SequenceType(seq) => {
+            let element = element(&seq.elem_type)?;
+            let datum_type =
+                element.datum_type.concretize().context("Sequence element type must be known")?;
+            let shape = element.shape.concretize().map(ShapeFact::from_dims);
+            Ok(opaque.with_opaque_metadata(SequenceFact { datum_type, shape }))
+        }
+        type_proto::Value::OptionalType(opt) => {
+            let inner = TypedFact::try_from(&element(&opt.elem_type)?).ok();
+            Ok(opaque.with_opaque_metadata(OptionalFact { inner }))
+        }
+    }
+}
+
+fn load_external_tensor(
     provider: &dyn ModelDataResolver,
     t: &TensorProto,
     path: &str,
-) -> TractResult<Vec<u8>> {
-    let mut tensor_data: Vec<u8> = Vec::new();
+    dt: DatumType,
+    shape: &[usize],
+) -> TractResult<Tensor> {
     trace!("number of external file needed for this tensor: {}", t.external_data.len());
     let location = t
         .external_data
@@ -97,12 +175,12 @@ fn get_external_resources(
     let p = PathBuf::from(format!("{}/{}", path, location));
 
     trace!("external file detected: {:?}, offset {:?}, length: {:?}", p, offset, length);
-    provider.read_bytes_from_path(&mut tensor_data, &p, offset, length)?;
+    let tensor = provider.tensor_from_path(dt, shape, &p, offset, length)?;
     trace!("external file loaded");
-    Ok(tensor_data)
+    Ok(tensor)
 }
 
-fn create_tensor(shape: Vec<usize>, dt: DatumType, data: &[u8]) -> TractResult<Tensor> {
+pub(crate) fn create_tensor(shape: Vec<usize>, dt: DatumType, data: &[u8]) -> TractResult<Tensor> {
     unsafe {
         match dt {
             DatumType::U8 => Tensor::from_raw::<u8>(&shape, data),
@@ -114,6 +192,7 @@ fn create_tensor(shape: Vec<usize>, dt: DatumType, data: &[u8]) -> TractResult<T
             DatumType::I32 => Tensor::from_raw::<i32>(&shape, data),
             DatumType::I64 => Tensor::from_raw::<i64>(&shape, data),
             DatumType::F16 => Tensor::from_raw::<f16>(&shape, data),
+            DatumType::BF16 => Tensor::from_raw::<bf16>(&shape, data),
             DatumType::F32 => Tensor::from_raw::<f32>(&shape, data),
             DatumType::F64 => Tensor::from_raw::<f64>(&shape, data),
             DatumType::Bool => Ok(Tensor::from_raw::<u8>(&shape, data)?
@@ -140,8 +219,7 @@ pub fn load_tensor(
     } else if is_external {
         if let Some(model_path) = path {
             // external files will be loaded and fed to the tensor if necessary
-            let external_data = get_external_resources(provider, t, model_path)?;
-            create_tensor(shape, dt, &external_data)
+            load_external_tensor(provider, t, model_path, dt, &shape)
         } else {
             bail!("no model path was specified in the parsing context, yet external data was detected. aborting");
         }
@@ -177,6 +255,11 @@ pub fn load_tensor(
                 t.int32_data.iter().map(|&x| f16::from_bits(x as u16)).collect(),
             )?
             .into(),
+            DatumType::BF16 => Array::from_shape_vec(
+                &*shape,
+                t.int32_data.iter().map(|&x| bf16::from_bits(x as u16)).collect(),
+            )?
+            .into(),
             DatumType::F32 => Array::from_shape_vec(&*shape, t.float_data.to_vec())?.into(),
             DatumType::F64 => Array::from_shape_vec(&*shape, t.double_data.to_vec())?.into(),
             DatumType::String => {
@@ -0,0 +1,261 @@
+//! TensorFlow checkpoints (tensor bundles), as found in the `variables/` directory of a
+//! SavedModel.
+//!
+//! The `.index` file is a sorted string table mapping tensor names to a `BundleEntryProto`
+//! locating their bytes in the `.data-?????-of-?????` shards. Only uncompressed tables are
+//! supported, which is what TensorFlow writes for bundles.
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use prost::Message;
+use tract_hir::internal::*;
+
+use crate::tfpb::tensorflow::{BundleEntryProto, BundleHeaderProto, DataType, TensorShapeProto};
+
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const TABLE_FOOTER_LEN: usize = 48;
+
+pub struct Checkpoint {
+    prefix: PathBuf,
+    num_shards: usize,
+    entries: HashMap<String, BundleEntryProto>,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint stored under `prefix` (`variables/variables` in a SavedModel).
+    pub fn open(prefix: impl AsRef<Path>) -> TractResult<Checkpoint> {
+        let prefix = prefix.as_ref().to_path_buf();
+        let index_path = with_suffix(&prefix, ".index");
+        let index = fs::read(&index_path).with_context(|| format!("Reading {index_path:?}"))?;
+        let mut header = None;
+        let mut entries = HashMap::default();
+        for (key, value) in read_table(&index).with_context(|| format!("Parsing {index_path:?}"))? {
+            if key.is_empty() {
+                header = Some(BundleHeaderProto::decode(&*value)?);
+            } else {
+                entries.insert(String::from_utf8(key)?, BundleEntryProto::decode(&*value)?);
+            }
+        }
+        let header = header.context("Checkpoint index has no header")?;
+        ensure!(header.endianness == 0, "Big endian checkpoints are not supported");
+        Ok(Checkpoint { prefix, num_shards: header.num_shards as usize, entries })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|k| &**k)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn tensor(&self, key: &str) -> TractResult<Tensor> {
+        let entry =
+            self.entries.get(key).with_context(|| format!("No tensor {key} in checkpoint"))?;
+        ensure!(entry.slices.is_empty(), "Partitioned variable {} is not supported", key);
+        let shard = with_suffix(
+            &self.prefix,
+            &format!(".data-{:05}-of-{:05}", entry.shard_id, self.num_shards),
+        );
+        let mut file = fs::File::open(&shard).with_context(|| format!("Opening {shard:?}"))?;
+        file.seek(SeekFrom::Start(entry.offset as u64))?;
+        let mut bytes = vec![0u8; entry.size as usize];
+        file.read_exact(&mut bytes).with_context(|| format!("Reading {key} from {shard:?}"))?;
+        let shape: TVec<usize> = entry
+            .shape
+            .as_ref()
+            .map(|s: &TensorShapeProto| s.try_into())
+            .transpose()?
+            .unwrap_or_default();
+        let dtype = DataType::from_i32(entry.dtype).context("Unknown data type")?;
+        if dtype == DataType::DtString {
+            return decode_strings(&shape, &bytes).with_context(|| format!("Decoding {key}"));
+        }
+        let dt = DatumType::try_from(dtype)?;
+        ensure!(
+            bytes.len() == shape.iter().product::<usize>() * dt.size_of(),
+            "{} bytes can not hold a {:?} {:?} tensor for {}",
+            bytes.len(),
+            shape,
+            dt,
+            key
+        );
+        unsafe { Tensor::from_raw_dt(dt, &shape, &bytes) }
+    }
+}
+
+fn with_suffix(prefix: &Path, suffix: &str) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+// Strings are stored as all their lengths (varints), a checksum of the lengths, then all the
+// bytes.
+fn decode_strings(shape: &[usize], mut bytes: &[u8]) -> TractResult<Tensor> {
+    let lengths = (0..shape.iter().product::<usize>())
+        .map(|_| varint(&mut bytes).map(|l| l as usize))
+        .collect::<TractResult<Vec<usize>>>()?;
+    ensure!(bytes.len() == 4 + lengths.iter().sum::<usize>(), "Inconsistent string tensor");
+    bytes = &bytes[4..];
+    let mut blobs = vec![];
+    for len in lengths {
+        blobs.push(Blob::try_from(&bytes[..len])?);
+        bytes = &bytes[len..];
+    }
+    Ok(tract_ndarray::ArrayD::from_shape_vec(shape, blobs)?.into())
+}
+
+fn read_table(data: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    ensure!(data.len() >= TABLE_FOOTER_LEN, "Truncated table");
+    let footer = &data[data.len() - TABLE_FOOTER_LEN..];
+    ensure!(
+        u64::from_le_bytes(footer[40..].try_into()?) == TABLE_MAGIC,
+        "Not a table (wrong magic number)"
+    );
+    let mut handles = &footer[..40];
+    let _metaindex = block_handle(&mut handles)?;
+    let index = block_handle(&mut handles)?;
+    let mut entries = vec![];
+    for (_, handle) in read_block(data, index)? {
+        entries.extend(read_block(data, block_handle(&mut &*handle)?)?);
+    }
+    Ok(entries)
+}
+
+fn block_handle(data: &mut &[u8]) -> TractResult<Range<usize>> {
+    let offset = varint(data)? as usize;
+    let size = varint(data)? as usize;
+    Ok(offset..offset + size)
+}
+
+// Blocks are a sequence of prefix-compressed entries, followed by an array of restart points
+// (useless for a full scan) and a trailer with the compression type and a checksum.
+fn read_block(data: &[u8], range: Range<usize>) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    ensure!(range.end + 5 <= data.len(), "Truncated table block");
+    ensure!(data[range.end] == 0, "Compressed checkpoint tables are not supported");
+    let block = &data[range];
+    ensure!(block.len() >= 4, "Truncated table block");
+    let restarts = u32::from_le_bytes(block[block.len() - 4..].try_into()?) as usize;
+    let entries_len = block.len().checked_sub(4 * (restarts + 1)).context("Invalid table block")?;
+    let mut cursor = &block[..entries_len];
+    let mut key = vec![];
+    let mut entries = vec![];
+    while !cursor.is_empty() {
+        let shared = varint(&mut cursor)? as usize;
+        let non_shared = varint(&mut cursor)? as usize;
+        let value_len = varint(&mut cursor)? as usize;
+        ensure!(
+            shared <= key.len() && non_shared + value_len <= cursor.len(),
+            "Invalid table block entry"
+        );
+        key.truncate(shared);
+        key.extend_from_slice(&cursor[..non_shared]);
+        entries.push((key.clone(), cursor[non_shared..][..value_len].to_vec()));
+        cursor = &cursor[non_shared + value_len..];
+    }
+    Ok(entries)
+}
+
+fn varint(data: &mut &[u8]) -> TractResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data.split_first().context("Truncated varint")?;
+        *data = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Invalid varint")
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::tfpb::tensorflow::tensor_shape_proto::Dim;
+
+    fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    // one entry per restart point, no prefix compression
+    fn block(entries: &[(Vec<u8>, Vec<u8>)], table: &mut Vec<u8>) -> Vec<u8> {
+        let offset = table.len();
+        let mut restarts = vec![];
+        for (k, v) in entries {
+            restarts.push(table.len() - offset);
+            put_varint(table, 0);
+            put_varint(table, k.len() as u64);
+            put_varint(table, v.len() as u64);
+            table.extend_from_slice(k);
+            table.extend_from_slice(v);
+        }
+        for r in &restarts {
+            table.extend_from_slice(&(*r as u32).to_le_bytes());
+        }
+        table.extend_from_slice(&(restarts.len() as u32).to_le_bytes());
+        let mut handle = vec![];
+        put_varint(&mut handle, offset as u64);
+        put_varint(&mut handle, (table.len() - offset) as u64);
+        table.extend_from_slice(&[0; 5]);
+        handle
+    }
+
+    /// Write a single shard checkpoint under `prefix`.
+    pub(crate) fn write_checkpoint(prefix: &Path, tensors: &[(&str, Tensor)]) -> TractResult<()> {
+        let mut data = vec![];
+        let header = BundleHeaderProto { num_shards: 1, endianness: 0, version: None };
+        let mut entries = vec![(vec![], header.encode_to_vec())];
+        let mut tensors = tensors.to_vec();
+        tensors.sort_by_key(|(name, _)| name.to_string());
+        for (name, t) in tensors {
+            let entry = BundleEntryProto {
+                dtype: DataType::try_from(t.datum_type())? as i32,
+                shape: Some(TensorShapeProto {
+                    dim: t.shape().iter().map(|d| Dim { size: *d as _, name: "".into() }).collect(),
+                    unknown_rank: false,
+                }),
+                shard_id: 0,
+                offset: data.len() as i64,
+                size: t.as_bytes().len() as i64,
+                crc32c: 0,
+                slices: vec![],
+            };
+            data.extend_from_slice(t.as_bytes());
+            entries.push((name.as_bytes().to_vec(), entry.encode_to_vec()));
+        }
+        let mut table = vec![];
+        let data_handle = block(&entries, &mut table);
+        let metaindex_handle = block(&[], &mut table);
+        let index_handle = block(&[(b"~".to_vec(), data_handle)], &mut table);
+        let mut footer = [metaindex_handle, index_handle].concat();
+        footer.resize(40, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+        table.extend_from_slice(&footer);
+        fs::write(with_suffix(prefix, ".index"), table)?;
+        fs::write(with_suffix(prefix, ".data-00000-of-00001"), data)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_checkpoint() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let prefix = dir.path().join("variables");
+        let kernel = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let step = tensor0(12i64);
+        write_checkpoint(&prefix, &[("kernel", kernel.clone()), ("global_step", step.clone())])?;
+        let checkpoint = Checkpoint::open(&prefix)?;
+        assert_eq!(checkpoint.keys().count(), 2);
+        assert_eq!(checkpoint.tensor("kernel")?, kernel);
+        assert_eq!(checkpoint.tensor("global_step")?, step);
+        assert!(checkpoint.tensor("bias").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,395 @@
+//! TensorFlow 2 function libraries.
+//!
+//! Function calls (`PartitionedCall`, `StatefulPartitionedCall`, or nodes using a library
+//! function as their op) are inlined in the calling graph. Functional control flow (`While`,
+//! `If` and their stateless variants) keeps its functions, which are turned into standalone
+//! graphs and parsed as submodels.
+use crate::model::Tensorflow;
+use crate::tfpb;
+use crate::tfpb::tensorflow::{DataType, FunctionDef, FunctionDefLibrary, GraphDef, NodeDef};
+use tract_hir::internal::*;
+
+pub const CALL_OPS: &[&str] = &["PartitionedCall", "StatefulPartitionedCall"];
+pub const WHILE_OPS: &[&str] = &["While", "StatelessWhile"];
+pub const IF_OPS: &[&str] = &["If", "StatelessIf"];
+
+// Output arguments of the ops producing more than one of them. Function bodies refer to
+// outputs as "node:arg:index", while graphs use the flat output index.
+const OUTPUT_ARGS: &[(&str, &[&str])] = &[
+    ("BlockLSTM", &["i", "cs", "f", "o", "ci", "co", "h"]),
+    ("BlockLSTMV2", &["i", "cs", "f", "o", "ci", "co", "h"]),
+    (
+        "FusedBatchNorm",
+        &["y", "batch_mean", "batch_variance", "reserve_space_1", "reserve_space_2"],
+    ),
+    (
+        "FusedBatchNormV2",
+        &["y", "batch_mean", "batch_variance", "reserve_space_1", "reserve_space_2"],
+    ),
+    (
+        "FusedBatchNormV3",
+        &[
+            "y",
+            "batch_mean",
+            "batch_variance",
+            "reserve_space_1",
+            "reserve_space_2",
+            "reserve_space_3",
+        ],
+    ),
+    ("Merge", &["output", "value_index"]),
+    ("Switch", &["output_false", "output_true"]),
+    ("TopKV2", &["values", "indices"]),
+    ("Unique", &["y", "idx"]),
+];
+
+pub fn function<'a>(
+    library: Option<&'a FunctionDefLibrary>,
+    name: &str,
+) -> TractResult<&'a FunctionDef> {
+    library
+        .into_iter()
+        .flat_map(|l| l.function.iter())
+        .find(|f| f.signature.as_ref().is_some_and(|s| s.name == name))
+        .with_context(|| format!("Function {name} not found in library"))
+}
+
+fn is_function(library: Option<&FunctionDefLibrary>, name: &str) -> bool {
+    function(library, name).is_ok()
+}
+
+fn data_inputs(node: &NodeDef) -> Vec<String> {
+    node.input.iter().filter(|i| !i.starts_with('^')).cloned().collect()
+}
+
+/// Inline function calls and prepare While nodes: the initial evaluation of the loop
+/// condition is inlined and becomes the first input of the While node.
+pub fn lower(graph: &GraphDef) -> TractResult<GraphDef> {
+    let library = graph.library.as_ref();
+    let mut nodes = vec![];
+    let mut todo: Vec<NodeDef> = graph.node.iter().rev().cloned().collect();
+    while let Some(mut node) = todo.pop() {
+        let callee = if CALL_OPS.contains(&&*node.op) {
+            Some(node.get_attr_func("f")?.name.clone())
+        } else if is_function(library, &node.op) {
+            Some(node.op.clone())
+        } else {
+            None
+        };
+        if let Some(callee) = callee {
+            let mut body = vec![];
+            let outputs = inline(
+                library,
+                function(library, &callee)?,
+                &node.name,
+                &data_inputs(&node),
+                &mut body,
+            )
+            .with_context(|| format!("Inlining {callee} in {}", node.name))?;
+            todo.extend(body.into_iter().rev());
+            let mut identity = tfpb::node().name(&node.name).op("IdentityN");
+            identity.input = outputs;
+            identity.input.extend(node.input.iter().filter(|i| i.starts_with('^')).cloned());
+            nodes.push(identity);
+        } else if WHILE_OPS.contains(&&*node.op) {
+            let cond = function(library, &node.get_attr_func("cond")?.name)?;
+            let mut body = vec![];
+            let prefix = format!("{}/cond_init", node.name);
+            let outputs = inline(library, cond, &prefix, &data_inputs(&node), &mut body)
+                .with_context(|| format!("Inlining condition of {}", node.name))?;
+            ensure!(outputs.len() == 1, "While condition must have exactly one output");
+            todo.extend(body.into_iter().rev());
+            node.input.insert(0, outputs[0].clone());
+            nodes.push(node);
+        } else {
+            nodes.push(node);
+        }
+    }
+    Ok(GraphDef {
+        node: nodes,
+        library: graph.library.clone(),
+        versions: graph.versions.clone(),
+        ..GraphDef::default()
+    })
+}
+
+/// Append the nodes of `f` to `nodes`, prefixing their names, with its arguments bound to
+/// `inputs`. Returns the graph-style references to the function results.
+pub fn inline(
+    library: Option<&FunctionDefLibrary>,
+    f: &FunctionDef,
+    prefix: &str,
+    inputs: &[String],
+    nodes: &mut Vec<NodeDef>,
+) -> TractResult<Vec<String>> {
+    let signature = f.signature.as_ref().context("Function without signature")?;
+    ensure!(
+        signature.input_arg.len() == inputs.len(),
+        "Function {} expects {} inputs, got {}",
+        signature.name,
+        signature.input_arg.len(),
+        inputs.len()
+    );
+    for arg in signature.input_arg.iter().chain(signature.output_arg.iter()) {
+        ensure!(
+            arg.number_attr.is_empty() && arg.type_list_attr.is_empty(),
+            "Function {}: list argument {} is not supported",
+            signature.name,
+            arg.name
+        );
+    }
+    let args: HashMap<&str, &str> =
+        signature.input_arg.iter().map(|a| &*a.name).zip(inputs.iter().map(|s| &**s)).collect();
+    let ops: HashMap<&str, &str> = f.node_def.iter().map(|n| (&*n.name, &*n.op)).collect();
+    let translate = |input: &str| -> TractResult<Option<String>> {
+        if let Some(control) = input.strip_prefix('^') {
+            return Ok(if args.contains_key(control) {
+                None
+            } else {
+                Some(format!("^{prefix}/{control}"))
+            });
+        }
+        let parts: Vec<&str> = input.split(':').collect();
+        if let Some(arg) = args.get(parts[0]) {
+            ensure!(
+                parts.len() == 1 || parts[1..] == ["0"],
+                "Invalid argument reference {}",
+                input
+            );
+            return Ok(Some(arg.to_string()));
+        }
+        let op = ops.get(parts[0]).with_context(|| format!("Unknown node in {input}"))?;
+        let slot = match parts[..] {
+            [_] => 0,
+            [_, arg] => output_arg_offset(library, op, arg)?,
+            [_, arg, ix] => output_arg_offset(library, op, arg)? + ix.parse::<usize>()?,
+            _ => bail!("Invalid input reference {}", input),
+        };
+        Ok(Some(if slot == 0 {
+            format!("{prefix}/{}", parts[0])
+        } else {
+            format!("{prefix}/{}:{slot}", parts[0])
+        }))
+    };
+    for node in &f.node_def {
+        let mut node = node.clone();
+        node.name = format!("{prefix}/{}", node.name);
+        node.input = node
+            .input
+            .iter()
+            .filter_map(|i| translate(i).transpose())
+            .collect::<TractResult<_>>()?;
+        nodes.push(node);
+    }
+    signature
+        .output_arg
+        .iter()
+        .map(|arg| {
+            let ret = f
+                .ret
+                .get(&arg.name)
+                .with_context(|| format!("No value returned for {}", arg.name))?;
+            translate(ret)?.with_context(|| format!("Invalid return value {ret}"))
+        })
+        .collect()
+}
+
+fn output_arg_offset(
+    library: Option<&FunctionDefLibrary>,
+    op: &str,
+    arg: &str,
+) -> TractResult<usize> {
+    let args: Vec<&str> = if let Some((_, args)) = OUTPUT_ARGS.iter().find(|(name, _)| *name == op)
+    {
+        args.to_vec()
+    } else if let Ok(f) = function(library, op) {
+        f.signature.as_ref().unwrap().output_arg.iter().map(|a| &*a.name).collect()
+    } else {
+        return Ok(0);
+    };
+    args.iter()
+        .position(|a| *a == arg)
+        .with_context(|| format!("{op} has no output argument {arg}"))
+}
+
+/// A Placeholder node, typed when the type has a tract counterpart (resources do not).
+pub fn placeholder(name: &str, dtype: i32) -> NodeDef {
+    let node = tfpb::node().name(name).op("Placeholder");
+    match DataType::from_i32(dtype) {
+        Some(dt) if DatumType::try_from(dt).is_ok() => node.attr("dtype", dt),
+        _ => node,
+    }
+}
+
+/// Parse a graph built from library functions as a submodel with the given inputs and
+/// outputs.
+pub fn submodel(
+    tf: &Tensorflow,
+    library: Option<&FunctionDefLibrary>,
+    nodes: Vec<NodeDef>,
+    inputs: &[String],
+    outputs: &[String],
+    symbols: &SymbolTable,
+) -> TractResult<InferenceModel> {
+    let graph = GraphDef { node: nodes, library: library.cloned(), ..GraphDef::default() };
+    let mut model = tf.parse_graph_with_symbols(&graph, symbols)?.0;
+    model.set_input_names(inputs)?;
+    let outputs = outputs
+        .iter()
+        .map(|o| {
+            let (node, slot) = Tensorflow::parse_input(o)?;
+            Ok(OutletId::new(model.node_id_by_name(node)?, slot))
+        })
+        .collect::<TractResult<TVec<_>>>()?;
+    model.set_output_outlets(&outputs)?;
+    Ok(model)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::{NameAttrList, OpDef};
+
+    fn arg(name: &str) -> ArgDef {
+        ArgDef { name: name.into(), r#type: DataType::DtFloat as i32, ..ArgDef::default() }
+    }
+
+    fn def(name: &str, inputs: &[&str], nodes: Vec<NodeDef>, ret: &[(&str, &str)]) -> FunctionDef {
+        FunctionDef {
+            signature: Some(OpDef {
+                name: name.into(),
+                input_arg: inputs.iter().map(|i| arg(i)).collect(),
+                output_arg: ret.iter().map(|(o, _)| arg(o)).collect(),
+                ..OpDef::default()
+            }),
+            node_def: nodes,
+            ret: ret.iter().map(|(o, r)| (o.to_string(), r.to_string())).collect(),
+            ..FunctionDef::default()
+        }
+    }
+
+    // (x, k) -> (top_k(x, k).indices, x + values)
+    fn top() -> FunctionDef {
+        def(
+            "top",
+            &["x", "k"],
+            vec![
+                tfpb::node().name("topk").op("TopKV2").input("x").input("k").input("^x"),
+                tfpb::node().name("add").op("AddV2").input("x").input("topk:values:0"),
+                tfpb::node().name("sink").op("NoOp").input("^topk"),
+            ],
+            &[("indices", "topk:indices:0"), ("sum", "add:z:0")],
+        )
+    }
+
+    // x -> top(x, x).sum
+    fn outer() -> FunctionDef {
+        def(
+            "outer",
+            &["x"],
+            vec![tfpb::node().name("inner").op("top").input("x").input("x")],
+            &[("y", "inner:sum:0")],
+        )
+    }
+
+    fn graph(nodes: Vec<NodeDef>) -> GraphDef {
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("a").op("Placeholder"))
+            .node(tfpb::node().name("b").op("Placeholder"));
+        graph.node.extend(nodes);
+        graph.library = Some(FunctionDefLibrary {
+            function: vec![top(), outer()],
+            ..FunctionDefLibrary::default()
+        });
+        graph
+    }
+
+    fn summary(graph: &GraphDef) -> Vec<(&str, &str, Vec<&str>)> {
+        graph
+            .node
+            .iter()
+            .map(|n| (&*n.name, &*n.op, n.input.iter().map(|i| &**i).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn inline_partitioned_call() -> TractResult<()> {
+        let mut call = tfpb::node().name("call").op("PartitionedCall").input("a").input("b:1");
+        call.attr.insert(
+            "f".into(),
+            NameAttrList { name: "top".into(), ..NameAttrList::default() }.into(),
+        );
+        let lowered = lower(&graph(vec![call.input("^b")]))?;
+        assert_eq!(
+            summary(&lowered),
+            vec![
+                ("a", "Placeholder", vec![]),
+                ("b", "Placeholder", vec![]),
+                ("call", "IdentityN", vec!["call/topk:1", "call/add", "^b"]),
+                ("call/topk", "TopKV2", vec!["a", "b:1"]),
+                ("call/add", "AddV2", vec!["a", "call/topk"]),
+                ("call/sink", "NoOp", vec!["^call/topk"]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_nested_calls() -> TractResult<()> {
+        let call = tfpb::node().name("call").op("outer").input("a");
+        let lowered =
+            lower(&graph(vec![call, tfpb::node().name("y").op("Identity").input("call")]))?;
+        assert_eq!(
+            summary(&lowered),
+            vec![
+                ("a", "Placeholder", vec![]),
+                ("b", "Placeholder", vec![]),
+                ("call", "IdentityN", vec!["call/inner:1"]),
+                ("call/inner", "IdentityN", vec!["call/inner/topk:1", "call/inner/add"]),
+                ("call/inner/topk", "TopKV2", vec!["a", "a"]),
+                ("call/inner/add", "AddV2", vec!["a", "call/inner/topk"]),
+                ("call/inner/sink", "NoOp", vec!["^call/inner/topk"]),
+                ("y", "Identity", vec!["call"]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn while_gets_initial_condition() -> TractResult<()> {
+        let mut node = tfpb::node().name("while").op("While").input("a").input("b");
+        node.attr.insert(
+            "cond".into(),
+            NameAttrList { name: "top".into(), ..NameAttrList::default() }.into(),
+        );
+        let mut graph = graph(vec![node]);
+        graph.library.as_mut().unwrap().function[0].signature.as_mut().unwrap().output_arg.pop();
+        let lowered = lower(&graph)?;
+        assert_eq!(
+            summary(&lowered)[2..],
+            vec![
+                ("while", "While", vec!["while/cond_init/topk:1", "a", "b"]),
+                ("while/cond_init/topk", "TopKV2", vec!["a", "b"]),
+                ("while/cond_init/add", "AddV2", vec!["a", "while/cond_init/topk"]),
+                ("while/cond_init/sink", "NoOp", vec!["^while/cond_init/topk"]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_checks_arity() {
+        let call = tfpb::node().name("call").op("top").input("a");
+        assert!(lower(&graph(vec![call])).is_err());
+    }
+
+    #[test]
+    fn inline_checks_output_args() {
+        let mut f = top();
+        f.ret.insert("indices".into(), "topk:idx:0".into());
+        let mut nodes = vec![];
+        let inputs = ["a".to_string(), "b".to_string()];
+        assert!(inline(None, &f, "call", &inputs, &mut nodes).is_err());
+    }
+}
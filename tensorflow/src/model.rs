@@ -62,7 +62,7 @@ impl Tensorflow {
     // "src_output" indicating which output tensor to use from "node". If
     // "src_output" is 0 the ":0" suffix can be omitted. Regular inputs may
     // optionally be followed by control inputs that have the format "^node".
-    fn parse_input(i: &str) -> TractResult<(&str, usize)> {
+    pub(crate) fn parse_input(i: &str) -> TractResult<(&str, usize)> {
         let pair = if let Some(stripped) = i.strip_prefix('^') {
             (stripped, 0)
         } else {
@@ -130,7 +130,17 @@ impl Tensorflow {
         graph: &GraphDef,
         symbols: &SymbolTable,
     ) -> TractResult<TfModelAndExtensions> {
+        use crate::functions;
         use crate::ops::control_flow as cf;
+        use crate::ops::functional;
+
+        let lowered;
+        let graph = if graph.library.as_ref().is_some_and(|l| !l.function.is_empty()) {
+            lowered = functions::lower(graph)?;
+            &lowered
+        } else {
+            graph
+        };
 
         let mut model =
             InferenceModel { symbol_table: symbols.to_owned(), ..InferenceModel::default() };
@@ -159,14 +169,20 @@ impl Tensorflow {
                 continue;
             }
 
-            let op = match self.op_register.0.get(&pbnode.op) {
-                Some(builder) => (builder)(&context, pbnode)?,
-                None => tract_hir::ops::unimpl::UnimplementedOp::new(
+            let library = graph.library.as_ref();
+            let op = if functions::WHILE_OPS.contains(&&*pbnode.op) {
+                functional::while_loop(self, library, pbnode, symbols)?
+            } else if functions::IF_OPS.contains(&&*pbnode.op) {
+                functional::if_then_else(self, library, pbnode, symbols)?
+            } else if let Some(builder) = self.op_register.0.get(&pbnode.op) {
+                (builder)(&context, pbnode)?
+            } else {
+                tract_hir::ops::unimpl::UnimplementedOp::new(
                     context.node_output_arities.get(name).cloned().unwrap_or(1),
                     &pbnode.op,
                     format!("{pbnode:?}"),
                 )
-                .into(),
+                .into()
             };
 
             let noutputs =
@@ -175,8 +191,13 @@ impl Tensorflow {
 
             let node_id = model.add_node(name.clone(), op, facts)?;
             if pbnode.op == "Placeholder" {
-                let dt = pbnode.get_attr_datum_type("dtype")?;
-                let mut fact = InferenceFact::dt(dt);
+                // function arguments may be resources, without a tract type
+                let mut fact = pbnode
+                    .get_attr_opt_datum_type("dtype")
+                    .ok()
+                    .flatten()
+                    .map(InferenceFact::dt)
+                    .unwrap_or_default();
                 if let Some(shape) = pbnode.get_attr_opt_shape("shape")? {
                     let shape_factoid = ShapeFactoid::closed(
                         shape
@@ -249,8 +270,12 @@ impl Tensorflow {
 }
 
 impl Framework<GraphDef, InferenceModel> for Tensorflow {
-    /// This method will try to read as frozen model, then as a saved model.
+    /// This method will read a SavedModel directory, or try to read a file as frozen model,
+    /// then as a saved model.
     fn proto_model_for_path(&self, r: impl AsRef<path::Path>) -> TractResult<GraphDef> {
+        if r.as_ref().is_dir() {
+            return self.read_saved_model_dir(r, None);
+        }
         self.read_frozen_model(&mut fs::File::open(r.as_ref())?)
             .or_else(|_| self.read_saved_model(&mut fs::File::open(r.as_ref())?))
     }
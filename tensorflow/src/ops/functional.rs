@@ -0,0 +1,439 @@
+use tract_hir::internal::*;
+
+use crate::functions::{function, inline, placeholder, submodel};
+use crate::model::Tensorflow;
+use crate::tfpb::tensorflow::{DataType, FunctionDefLibrary, NodeDef};
+
+pub fn register_all_ops(reg: &mut crate::model::TfOpRegister) {
+    reg.insert("IdentityN", |_, node| {
+        Ok(Box::new(IdentityN(node.input.iter().filter(|i| !i.starts_with('^')).count())))
+    });
+    reg.insert("ReadVariableOp", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
+}
+
+// Placeholders for the function arguments, named arg_0, arg_1...
+fn arguments(
+    library: Option<&FunctionDefLibrary>,
+    name: &str,
+    nodes: &mut Vec<NodeDef>,
+) -> TractResult<Vec<String>> {
+    let f = function(library, name)?;
+    let mut args = vec![];
+    for (ix, arg) in f.signature.as_ref().unwrap().input_arg.iter().enumerate() {
+        let name = format!("arg_{ix}");
+        nodes.push(placeholder(&name, arg.r#type));
+        args.push(name);
+    }
+    Ok(args)
+}
+
+pub fn if_then_else(
+    tf: &Tensorflow,
+    library: Option<&FunctionDefLibrary>,
+    node: &NodeDef,
+    symbols: &SymbolTable,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let branch = |attr: &str| -> TractResult<InferenceModel> {
+        let name = &node.get_attr_func(attr)?.name;
+        let mut nodes = vec![];
+        let args = arguments(library, name, &mut nodes)?;
+        let outputs = inline(library, function(library, name)?, "branch", &args, &mut nodes)?;
+        submodel(tf, library, nodes, &args, &outputs, symbols)
+            .with_context(|| format!("Parsing {attr} of {}", node.name))
+    };
+    let then_body = branch("then_branch")?;
+    let else_body = branch("else_branch")?;
+    ensure!(
+        then_body.outputs.len() == else_body.outputs.len(),
+        "then_branch and else_branch of {} have different outputs",
+        node.name
+    );
+    Ok(Box::new(If { then_body, else_body }))
+}
+
+pub fn while_loop(
+    tf: &Tensorflow,
+    library: Option<&FunctionDefLibrary>,
+    node: &NodeDef,
+    symbols: &SymbolTable,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let body = &node.get_attr_func("body")?.name;
+    let cond = &node.get_attr_func("cond")?.name;
+    let mut nodes = vec![
+        placeholder("iteration", DataType::DtInt64 as i32),
+        placeholder("condition", DataType::DtBool as i32),
+    ];
+    let args = arguments(library, body, &mut nodes)?;
+    let outputs = inline(library, function(library, body)?, "body", &args, &mut nodes)?;
+    let next = inline(library, function(library, cond)?, "cond", &outputs, &mut nodes)?;
+    ensure!(next.len() == 1, "While condition must have exactly one output");
+    let inputs: Vec<String> =
+        ["iteration".to_string(), "condition".to_string()].into_iter().chain(args).collect();
+    let body_outputs: Vec<String> = next.into_iter().chain(outputs).collect();
+    let body = submodel(tf, library, nodes, &inputs, &body_outputs, symbols)
+        .with_context(|| format!("Parsing body of {}", node.name))?;
+    Ok(Box::new(While { body, iterations: symbols.new_with_prefix("while") }))
+}
+
+/// Functional If: input 0 is the condition, the other inputs are the branches arguments.
+#[derive(Debug, Clone)]
+pub struct If {
+    pub then_body: InferenceModel,
+    pub else_body: InferenceModel,
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for If {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let cond = inputs.remove(0).cast_to_scalar::<bool>()?;
+        let body = if cond { &self.then_body } else { &self.else_body };
+        body.clone().into_runnable()?.run(inputs)
+    }
+}
+
+impl InferenceOp for If {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        loop {
+            let mut changed = false;
+            for body in [&mut self.then_body, &mut self.else_body] {
+                for ix in 0..body.inputs.len() {
+                    changed |= body.input_fact_mut(ix)?.unify_with_mut(&mut inputs[1 + ix])?;
+                }
+                for ix in 0..body.outputs.len() {
+                    let fact = body.output_fact_mut(ix)?;
+                    changed |= fact.datum_type.unify_with_mut(&mut outputs[ix].datum_type)?;
+                    changed |= fact.shape.unify_with_mut(&mut outputs[ix].shape)?;
+                }
+                changed |= body.analyse(false)?;
+            }
+            if !changed {
+                return Ok((inputs, outputs, observed.into_iter().cloned().collect()));
+            }
+        }
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.outputs.len())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let mut inputs: TVec<OutletId> = node.inputs.iter().map(|o| mapping[o]).collect();
+        if target.outlet_fact(inputs[0])?.datum_type != bool::datum_type() {
+            inputs[0] = target.wire_node(
+                format!("{}.cond", node.name),
+                tract_core::ops::cast::cast(bool::datum_type()),
+                &[inputs[0]],
+            )?[0];
+        }
+        let mapping: Vec<usize> = (1..node.inputs.len()).collect();
+        let op = tract_core::ops::logic::IfThenElse {
+            then_body: self.then_body.clone().into_typed()?,
+            then_input_mapping: mapping.clone(),
+            else_body: self.else_body.clone().into_typed()?,
+            else_input_mapping: mapping,
+        };
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    as_op!();
+}
+
+/// Functional While, with the loop condition evaluated on the initial values as input 0,
+/// followed by the loop variables.
+///
+/// The body inputs are the iteration number, the current condition and the loop variables,
+/// its outputs are the next condition and the updated variables, as for core Loop.
+#[derive(Debug, Clone)]
+pub struct While {
+    pub body: InferenceModel,
+    iterations: Symbol,
+}
+
+impl Op for While {
+    fn name(&self) -> Cow<str> {
+        "While".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for While {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut cond = inputs.remove(0).cast_to_scalar::<bool>()?;
+        let mut vars = inputs;
+        let plan = SimplePlan::new(&self.body)?;
+        let mut i = 0i64;
+        while cond {
+            let mut iter_inputs: TVec<TValue> =
+                tvec!(tensor0(i).into_tvalue(), tensor0(cond).into_tvalue());
+            iter_inputs.extend(vars.drain(..));
+            let mut iter_outputs = plan.run(iter_inputs)?;
+            vars.extend(iter_outputs.drain(1..));
+            cond = iter_outputs[0].cast_to_scalar::<bool>()?;
+            i += 1;
+        }
+        Ok(vars)
+    }
+}
+
+impl InferenceOp for While {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        loop {
+            let mut changed = false;
+            changed |= inputs[0].datum_type.unify_with(&bool::datum_type().into())?;
+            changed |= self.body.input_fact_mut(0)?.unify_with(&InferenceFact::dt_shape(
+                i64::datum_type(),
+                ShapeFactoid::closed(tvec!()),
+            ))?;
+            changed |= self.body.input_fact_mut(1)?.unify_with(&InferenceFact::dt_shape(
+                bool::datum_type(),
+                ShapeFactoid::closed(tvec!()),
+            ))?;
+            changed |=
+                self.body.output_fact_mut(0)?.datum_type.unify_with(&bool::datum_type().into())?;
+            for ix in 0..outputs.len() {
+                let mut facts = self.body.outlets_fact_mut(&[
+                    self.body.input_outlets()?[2 + ix],
+                    self.body.output_outlets()?[1 + ix],
+                ])?;
+                facts.push(&mut inputs[1 + ix]);
+                facts.push(&mut outputs[ix]);
+                changed |= Factoid::unify_all(
+                    &mut facts.iter_mut().map(|f| &mut f.datum_type).collect::<TVec<_>>(),
+                )?;
+                changed |= Factoid::unify_all(
+                    &mut facts.iter_mut().map(|f| &mut f.shape).collect::<TVec<_>>(),
+                )?;
+            }
+            changed |= self.body.analyse(false)?;
+            if !changed {
+                return Ok((inputs, outputs, observed.into_iter().cloned().collect()));
+            }
+        }
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.outputs.len() - 1)
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let body = self.body.clone().into_typed()?;
+        let trip_count =
+            target.add_const(format!("{}.trip_count", node.name), tensor0(i64::MAX))?;
+        let mut inputs: TVec<OutletId> = tvec!(trip_count);
+        inputs.extend(node.inputs.iter().map(|o| mapping[o]));
+        let op =
+            tract_core::ops::scan::Loop::new(body, node.inputs.len() - 1, self.iterations.clone())?;
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    as_op!();
+}
+
+/// Pass-through of any number of tensors, left in place of inlined function calls.
+#[derive(Debug, Clone, Hash)]
+pub struct IdentityN(pub usize);
+
+impl Op for IdentityN {
+    fn name(&self) -> Cow<str> {
+        "IdentityN".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for IdentityN {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(inputs)
+    }
+}
+
+impl InferenceRulesOp for IdentityN {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, self.0)?;
+        check_output_arity(outputs, self.0)?;
+        for (i, o) in inputs.iter().zip(outputs.iter()) {
+            s.equals(&i.datum_type, &o.datum_type)?;
+            s.equals(&i.shape, &o.shape)?;
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.0)
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        _target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        Ok(node.inputs.iter().map(|o| mapping[o]).collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::{FunctionDef, GraphDef, NameAttrList, OpDef, TensorProto};
+    use tract_hir::prelude::Framework;
+
+    fn arg(name: &str, dt: DataType) -> ArgDef {
+        ArgDef { name: name.into(), r#type: dt as i32, ..ArgDef::default() }
+    }
+
+    fn konst(name: &str, t: Tensor) -> NodeDef {
+        tfpb::node()
+            .name(name)
+            .op("Const")
+            .attr("dtype", DataType::try_from(t.datum_type()).unwrap())
+            .attr("value", TensorProto::try_from(&t).unwrap())
+    }
+
+    // x -> x * factor
+    fn scale(name: &str, factor: f32) -> FunctionDef {
+        FunctionDef {
+            signature: Some(OpDef {
+                name: name.into(),
+                input_arg: vec![arg("x", DataType::DtFloat)],
+                output_arg: vec![arg("y", DataType::DtFloat)],
+                ..OpDef::default()
+            }),
+            node_def: vec![
+                konst("factor", tensor0(factor)),
+                tfpb::node().name("mul").op("Mul").input("x").input("factor:output:0"),
+            ],
+            ret: [("y".to_string(), "mul:z:0".to_string())].into_iter().collect(),
+            ..FunctionDef::default()
+        }
+    }
+
+    // x -> x < 100
+    fn small() -> FunctionDef {
+        FunctionDef {
+            signature: Some(OpDef {
+                name: "small".into(),
+                input_arg: vec![arg("x", DataType::DtFloat)],
+                output_arg: vec![arg("c", DataType::DtBool)],
+                ..OpDef::default()
+            }),
+            node_def: vec![
+                konst("limit", tensor0(100f32)),
+                tfpb::node().name("less").op("Less").input("x").input("limit:output:0"),
+            ],
+            ret: [("c".to_string(), "less:z:0".to_string())].into_iter().collect(),
+            ..FunctionDef::default()
+        }
+    }
+
+    fn func(name: &str) -> NameAttrList {
+        NameAttrList { name: name.into(), ..NameAttrList::default() }
+    }
+
+    fn graph(nodes: Vec<NodeDef>) -> GraphDef {
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("x").op("Placeholder").attr("dtype", DataType::DtFloat));
+        graph.node.extend(nodes);
+        graph.library = Some(FunctionDefLibrary {
+            function: vec![scale("double", 2.), scale("triple", 3.), small()],
+            ..FunctionDefLibrary::default()
+        });
+        graph
+    }
+
+    fn run(graph: &GraphDef, input: f32) -> TractResult<Tensor> {
+        let mut model = crate::tensorflow().model_for_proto_model(graph)?;
+        model.set_input_fact(0, f32::scalar_fact().into())?;
+        let model = model.into_optimized()?;
+        let output = model.into_runnable()?.run(tvec!(tensor0(input).into()))?.remove(0);
+        Ok(output.into_tensor())
+    }
+
+    #[test]
+    fn partitioned_call() -> TractResult<()> {
+        let mut call = tfpb::node().name("call").op("StatefulPartitionedCall").input("x");
+        call.attr.insert("f".into(), func("double").into());
+        let graph = graph(vec![call, tfpb::node().name("y").op("Identity").input("call")]);
+        assert_eq!(run(&graph, 3.)?, tensor0(6f32));
+        Ok(())
+    }
+
+    #[test]
+    fn stateless_while() -> TractResult<()> {
+        let mut node = tfpb::node().name("while").op("StatelessWhile").input("x");
+        node.attr.insert("body".into(), func("double").into());
+        node.attr.insert("cond".into(), func("small").into());
+        let graph = graph(vec![node, tfpb::node().name("y").op("Identity").input("while")]);
+        assert_eq!(run(&graph, 3.)?, tensor0(192f32));
+        assert_eq!(run(&graph, 300.)?, tensor0(300f32));
+        Ok(())
+    }
+
+    #[test]
+    fn stateless_if() -> TractResult<()> {
+        let cond = tfpb::node().name("cond").op("small").input("x");
+        let mut node = tfpb::node().name("if").op("StatelessIf").input("cond").input("x");
+        node.attr.insert("then_branch".into(), func("double").into());
+        node.attr.insert("else_branch".into(), func("triple").into());
+        let graph = graph(vec![cond, node, tfpb::node().name("y").op("Identity").input("if")]);
+        assert_eq!(run(&graph, 3.)?, tensor0(6f32));
+        assert_eq!(run(&graph, 300.)?, tensor0(900f32));
+        Ok(())
+    }
+}
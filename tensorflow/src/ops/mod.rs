@@ -6,23 +6,27 @@ use crate::tfpb::tensorflow::NodeDef;
 
 pub mod array;
 pub mod control_flow;
+pub mod functional;
 pub mod logic;
 pub mod math;
 pub mod nn;
 pub mod quant;
 pub mod random;
 pub mod rec;
+pub mod string;
 pub mod vars;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     array::register_all_ops(reg);
     control_flow::register_all_ops(reg);
+    functional::register_all_ops(reg);
     logic::register_all_ops(reg);
     math::register_all_ops(reg);
     nn::register_all_ops(reg);
     quant::register_all_ops(reg);
     random::register_all_ops(reg);
     rec::register_all_ops(reg);
+    string::register_all_ops(reg);
     vars::register_all_ops(reg);
     reg.insert("Cast", cast);
     reg.insert("Const", konst);
@@ -44,7 +48,7 @@ fn konst(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn Inference
         bail!("Const node {:?} doesn't have the expected {:?} type.", mat, dtype);
     }
 
-    Ok(Box::new(tract_hir::ops::konst::Const(mat.into())))
+    Ok(Box::new(tract_hir::ops::konst::Const::new(mat.into())))
 }
 
 #[derive(Clone, Debug, new, Hash)]
@@ -0,0 +1,424 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+use crate::model::TfOpRegister;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn register_all_ops(reg: &mut TfOpRegister) {
+    reg.insert("StringSplit", string_split);
+    reg.insert("StringToHashBucketFast", string_to_hash_bucket_fast);
+}
+
+// TensorFlow strings are loaded as blobs, but ops also accept tract strings.
+fn bytes(t: &Tensor) -> TractResult<Vec<&[u8]>> {
+    if t.datum_type() == DatumType::Blob {
+        Ok(t.as_slice::<Blob>()?.iter().map(|b| b.as_bytes()).collect())
+    } else {
+        Ok(t.as_slice::<String>()?.iter().map(|s| s.as_bytes()).collect())
+    }
+}
+
+fn from_bytes(dt: DatumType, items: &[&[u8]]) -> TractResult<Tensor> {
+    if dt == DatumType::Blob {
+        let blobs = items.iter().map(|b| Blob::from_bytes(b)).collect::<TractResult<Vec<_>>>()?;
+        Ok(tensor1(&blobs))
+    } else {
+        let strings = items
+            .iter()
+            .map(|b| Ok(std::str::from_utf8(b)?.to_string()))
+            .collect::<TractResult<Vec<_>>>()?;
+        Ok(tensor1(&strings))
+    }
+}
+
+fn string_split(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let skip_empty = node.get_attr_opt_bool("skip_empty")?.unwrap_or(true);
+    Ok(Box::new(StringSplit { skip_empty }))
+}
+
+fn eval_string_split(skip_empty: bool, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+    let (input, delimiter) = args_2!(inputs);
+    let delimiter = bytes(&delimiter)?[0];
+    let mut indices: Vec<i64> = vec![];
+    let mut values: Vec<&[u8]> = vec![];
+    let mut width = 0;
+    for (row, item) in bytes(&input)?.into_iter().enumerate() {
+        let tokens: Vec<&[u8]> = if delimiter.is_empty() {
+            item.chunks(1).collect()
+        } else {
+            item.split(|b| delimiter.contains(b))
+                .filter(|token| !skip_empty || !token.is_empty())
+                .collect()
+        };
+        for (col, token) in tokens.iter().enumerate() {
+            indices.extend([row as i64, col as i64]);
+            values.push(*token);
+        }
+        width = width.max(tokens.len());
+    }
+    let indices = tensor1(&indices).into_shape(&[values.len(), 2])?;
+    let values = from_bytes(input.datum_type(), &values)?;
+    let shape = tensor1(&[input.len() as i64, width as i64]);
+    Ok(tvec!(indices.into_tvalue(), values.into_tvalue(), shape.into_tvalue()))
+}
+
+/// https://www.tensorflow.org/api_docs/python/tf/raw_ops/StringSplit
+///
+/// Outputs the sparse representation (indices, values, dense shape) of the tokens. The
+/// delimiter is a set of single byte characters, and an empty delimiter splits all bytes apart.
+#[derive(Clone, Debug, Hash)]
+pub struct StringSplit {
+    skip_empty: bool,
+}
+
+impl Op for StringSplit {
+    fn name(&self) -> Cow<str> {
+        "StringSplit".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for StringSplit {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        eval_string_split(self.skip_empty, inputs)
+    }
+}
+
+impl InferenceRulesOp for StringSplit {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 3)?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&outputs[0].datum_type, i64::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], 2usize.to_dim())?;
+        s.equals(&outputs[1].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[1].rank, 1)?;
+        s.equals(&outputs[0].shape[0], &outputs[1].shape[0])?;
+        s.equals(&outputs[2].datum_type, i64::datum_type())?;
+        s.equals(&outputs[2].shape, shapefactoid!(2))?;
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(3)
+    }
+
+    as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = TypedStringSplit {
+            skip_empty: self.skip_empty,
+            tokens: target.symbol_table.new_with_prefix("tokens"),
+        };
+        let inputs: TVec<OutletId> = node.inputs.iter().map(|i| mapping[i]).collect();
+        target.wire_node(&*node.name, op, &inputs)
+    }
+}
+
+/// StringSplit, with the number of tokens as a symbol.
+#[derive(Clone, Debug, Hash)]
+pub struct TypedStringSplit {
+    skip_empty: bool,
+    tokens: Symbol,
+}
+
+impl Op for TypedStringSplit {
+    fn name(&self) -> Cow<str> {
+        "TypedStringSplit".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TypedStringSplit {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        eval_string_split(self.skip_empty, inputs)
+    }
+}
+
+impl TypedOp for TypedStringSplit {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(
+            i64::fact([self.tokens.to_dim(), 2usize.to_dim()]),
+            inputs[0].datum_type.fact([self.tokens.to_dim()]),
+            i64::fact([2usize]),
+        ))
+    }
+
+    as_op!();
+}
+
+fn string_to_hash_bucket_fast(
+    _ctx: &ParsingContext,
+    node: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let num_buckets: u64 = node.get_attr_int("num_buckets")?;
+    ensure!(num_buckets > 0, "StringToHashBucketFast expects a positive num_buckets");
+    Ok(Box::new(StringToHashBucketFast { num_buckets }))
+}
+
+/// https://www.tensorflow.org/api_docs/python/tf/raw_ops/StringToHashBucketFast
+#[derive(Clone, Debug, Hash)]
+pub struct StringToHashBucketFast {
+    num_buckets: u64,
+}
+
+impl Op for StringToHashBucketFast {
+    fn name(&self) -> Cow<str> {
+        "StringToHashBucketFast".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for StringToHashBucketFast {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let buckets: Vec<i64> = bytes(&input)?
+            .into_iter()
+            .map(|s| (fingerprint64(s) % self.num_buckets) as i64)
+            .collect();
+        Ok(tvec!(tensor1(&buckets).into_shape(input.shape())?.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for StringToHashBucketFast {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for StringToHashBucketFast {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(i64::fact(inputs[0].shape.iter())))
+    }
+
+    as_op!();
+}
+
+// Fingerprint64 from farmhash (farmhashna::Hash64), as used by TensorFlow.
+const K0: u64 = 0xc3a5c85c97cb3127;
+const K1: u64 = 0xb492b66fbe98f273;
+const K2: u64 = 0x9ae16a3b2f90404f;
+
+fn fetch64(s: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(s[at..at + 8].try_into().unwrap())
+}
+
+fn fetch32(s: &[u8], at: usize) -> u64 {
+    u32::from_le_bytes(s[at..at + 4].try_into().unwrap()) as u64
+}
+
+fn shift_mix(v: u64) -> u64 {
+    v ^ (v >> 47)
+}
+
+fn hash_len_16(u: u64, v: u64, mul: u64) -> u64 {
+    let a = shift_mix((u ^ v).wrapping_mul(mul));
+    let b = shift_mix((v ^ a).wrapping_mul(mul));
+    b.wrapping_mul(mul)
+}
+
+fn hash_len_0_to_16(s: &[u8]) -> u64 {
+    let len = s.len() as u64;
+    if s.len() >= 8 {
+        let mul = K2.wrapping_add(len * 2);
+        let a = fetch64(s, 0).wrapping_add(K2);
+        let b = fetch64(s, s.len() - 8);
+        let c = b.rotate_right(37).wrapping_mul(mul).wrapping_add(a);
+        let d = a.rotate_right(25).wrapping_add(b).wrapping_mul(mul);
+        hash_len_16(c, d, mul)
+    } else if s.len() >= 4 {
+        let mul = K2.wrapping_add(len * 2);
+        let a = fetch32(s, 0);
+        hash_len_16(len.wrapping_add(a << 3), fetch32(s, s.len() - 4), mul)
+    } else if !s.is_empty() {
+        let a = s[0] as u32;
+        let b = s[s.len() >> 1] as u32;
+        let c = s[s.len() - 1] as u32;
+        let y = a.wrapping_add(b << 8) as u64;
+        let z = (s.len() as u32).wrapping_add(c << 2) as u64;
+        shift_mix(y.wrapping_mul(K2) ^ z.wrapping_mul(K0)).wrapping_mul(K2)
+    } else {
+        K2
+    }
+}
+
+fn hash_len_17_to_32(s: &[u8]) -> u64 {
+    let len = s.len();
+    let mul = K2.wrapping_add(len as u64 * 2);
+    let a = fetch64(s, 0).wrapping_mul(K1);
+    let b = fetch64(s, 8);
+    let c = fetch64(s, len - 8).wrapping_mul(mul);
+    let d = fetch64(s, len - 16).wrapping_mul(K2);
+    hash_len_16(
+        a.wrapping_add(b).rotate_right(43).wrapping_add(c.rotate_right(30)).wrapping_add(d),
+        a.wrapping_add(b.wrapping_add(K2).rotate_right(18)).wrapping_add(c),
+        mul,
+    )
+}
+
+fn hash_len_33_to_64(s: &[u8]) -> u64 {
+    let len = s.len();
+    let mul = K2.wrapping_add(len as u64 * 2);
+    let a = fetch64(s, 0).wrapping_mul(K2);
+    let b = fetch64(s, 8);
+    let c = fetch64(s, len - 8).wrapping_mul(mul);
+    let d = fetch64(s, len - 16).wrapping_mul(K2);
+    let y = a.wrapping_add(b).rotate_right(43).wrapping_add(c.rotate_right(30)).wrapping_add(d);
+    let z =
+        hash_len_16(y, a.wrapping_add(b.wrapping_add(K2).rotate_right(18)).wrapping_add(c), mul);
+    let e = fetch64(s, 16).wrapping_mul(mul);
+    let f = fetch64(s, 24);
+    let g = y.wrapping_add(fetch64(s, len - 32)).wrapping_mul(mul);
+    let h = z.wrapping_add(fetch64(s, len - 24)).wrapping_mul(mul);
+    hash_len_16(
+        e.wrapping_add(f).rotate_right(43).wrapping_add(g.rotate_right(30)).wrapping_add(h),
+        e.wrapping_add(f.wrapping_add(a).rotate_right(18)).wrapping_add(g),
+        mul,
+    )
+}
+
+fn weak_hash_len_32_with_seeds(s: &[u8], at: usize, mut a: u64, mut b: u64) -> (u64, u64) {
+    let w = fetch64(s, at);
+    let x = fetch64(s, at + 8);
+    let y = fetch64(s, at + 16);
+    let z = fetch64(s, at + 24);
+    a = a.wrapping_add(w);
+    b = b.wrapping_add(a).wrapping_add(z).rotate_right(21);
+    let c = a;
+    a = a.wrapping_add(x).wrapping_add(y);
+    b = b.wrapping_add(a.rotate_right(44));
+    (a.wrapping_add(z), b.wrapping_add(c))
+}
+
+pub fn fingerprint64(s: &[u8]) -> u64 {
+    const SEED: u64 = 81;
+    let len = s.len();
+    if len <= 16 {
+        return hash_len_0_to_16(s);
+    } else if len <= 32 {
+        return hash_len_17_to_32(s);
+    } else if len <= 64 {
+        return hash_len_33_to_64(s);
+    }
+    let mut x = SEED;
+    let mut y = SEED.wrapping_mul(K1).wrapping_add(113);
+    let mut z = shift_mix(y.wrapping_mul(K2).wrapping_add(113)).wrapping_mul(K2);
+    let mut v = (0u64, 0u64);
+    let mut w = (0u64, 0u64);
+    x = x.wrapping_mul(K2).wrapping_add(fetch64(s, 0));
+    let end = ((len - 1) / 64) * 64;
+    let last64 = len - 64;
+    let mut at = 0;
+    while at != end {
+        x = x.wrapping_add(y).wrapping_add(v.0).wrapping_add(fetch64(s, at + 8)).rotate_right(37);
+        x = x.wrapping_mul(K1);
+        y = y.wrapping_add(v.1).wrapping_add(fetch64(s, at + 48)).rotate_right(42).wrapping_mul(K1);
+        x ^= w.1;
+        y = y.wrapping_add(v.0).wrapping_add(fetch64(s, at + 40));
+        z = z.wrapping_add(w.0).rotate_right(33).wrapping_mul(K1);
+        v = weak_hash_len_32_with_seeds(s, at, v.1.wrapping_mul(K1), x.wrapping_add(w.0));
+        w = weak_hash_len_32_with_seeds(
+            s,
+            at + 32,
+            z.wrapping_add(w.1),
+            y.wrapping_add(fetch64(s, at + 16)),
+        );
+        std::mem::swap(&mut z, &mut x);
+        at += 64;
+    }
+    let mul = K1.wrapping_add((z & 0xff) << 1);
+    at = last64;
+    w.0 = w.0.wrapping_add(((len - 1) & 63) as u64);
+    v.0 = v.0.wrapping_add(w.0);
+    w.0 = w.0.wrapping_add(v.0);
+    x = x.wrapping_add(y).wrapping_add(v.0).wrapping_add(fetch64(s, at + 8)).rotate_right(37);
+    x = x.wrapping_mul(mul);
+    y = y.wrapping_add(v.1).wrapping_add(fetch64(s, at + 48)).rotate_right(42).wrapping_mul(mul);
+    x ^= w.1.wrapping_mul(9);
+    y = y.wrapping_add(v.0.wrapping_mul(9)).wrapping_add(fetch64(s, at + 40));
+    z = z.wrapping_add(w.0).rotate_right(33).wrapping_mul(mul);
+    v = weak_hash_len_32_with_seeds(s, at, v.1.wrapping_mul(mul), x.wrapping_add(w.0));
+    w = weak_hash_len_32_with_seeds(
+        s,
+        at + 32,
+        z.wrapping_add(w.1),
+        y.wrapping_add(fetch64(s, at + 16)),
+    );
+    std::mem::swap(&mut z, &mut x);
+    hash_len_16(
+        hash_len_16(v.0, w.0, mul).wrapping_add(shift_mix(y).wrapping_mul(K0)).wrapping_add(z),
+        hash_len_16(v.1, w.1, mul).wrapping_add(x),
+        mul,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fingerprint_of_empty_string() {
+        assert_eq!(fingerprint64(b""), K2);
+    }
+
+    #[test]
+    fn hash_buckets() -> TractResult<()> {
+        let op = StringToHashBucketFast { num_buckets: 3 };
+        let input = tensor1(&["Hello", "TensorFlow", "2.x"].map(String::from));
+        let output = op.eval(tvec!(input.into_tvalue()))?;
+        assert_eq!(*output[0], tensor1(&[0i64, 2, 2]));
+        Ok(())
+    }
+
+    #[test]
+    fn split_on_any_delimiter_byte() -> TractResult<()> {
+        let op = StringSplit { skip_empty: true };
+        let input = tensor1(&["a b,c", "", "d,,e"].map(String::from));
+        let output =
+            op.eval(tvec!(input.into_tvalue(), tensor0(" ,".to_string()).into_tvalue()))?;
+        assert_eq!(*output[0], tensor2(&[[0i64, 0], [0, 1], [0, 2], [2, 0], [2, 1]]));
+        assert_eq!(*output[1], tensor1(&["a", "b", "c", "d", "e"].map(String::from)));
+        assert_eq!(*output[2], tensor1(&[3i64, 3]));
+        Ok(())
+    }
+}
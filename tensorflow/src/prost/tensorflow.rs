@@ -62,6 +62,47 @@ pub mod tensor_shape_proto {
         pub name: ::prost::alloc::string::String,
     }
 }
+/// Can only be interpreted if you know the corresponding TensorShape.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TensorSliceProto {
+    /// Extent of the slice in all tensor dimensions.
+    ///
+    /// Must have one entry for each of the dimension of the tensor that this
+    /// slice belongs to.  The order of sizes is the same as the order of
+    /// dimensions in the TensorShape.
+    #[prost(message, repeated, tag="1")]
+    pub extent: ::prost::alloc::vec::Vec<tensor_slice_proto::Extent>,
+}
+/// Nested message and enum types in `TensorSliceProto`.
+pub mod tensor_slice_proto {
+    /// Extent of the slice in one dimension.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Extent {
+        /// Start index of the slice, starting at 0.
+        #[prost(int64, tag="1")]
+        pub start: i64,
+        /// Length of the slice: if the length is missing or -1 we will
+        /// interpret this as "everything in this dimension".  We use
+        /// "oneof" to preserve information about whether the length is
+        /// present without changing the serialization format from the
+        /// prior proto2 version of this proto.
+        #[prost(oneof="extent::HasLength", tags="2")]
+        pub has_length: ::core::option::Option<extent::HasLength>,
+    }
+    /// Nested message and enum types in `Extent`.
+    pub mod extent {
+        /// Length of the slice: if the length is missing or -1 we will
+        /// interpret this as "everything in this dimension".  We use
+        /// "oneof" to preserve information about whether the length is
+        /// present without changing the serialization format from the
+        /// prior proto2 version of this proto.
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum HasLength {
+            #[prost(int64, tag="2")]
+            Length(i64),
+        }
+    }
+}
 /// LINT.IfChange
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -1317,6 +1358,67 @@ pub struct SavedResource {
     #[prost(string, tag="1")]
     pub device: ::prost::alloc::string::String,
 }
+/// Special header that is associated with a bundle.
+///
+/// TODO(zongheng,zhifengc): maybe in the future, we can add information about
+/// which binary produced this checkpoint, timestamp, etc. Sometime, these can be
+/// valuable debugging information. And if needed, these can be used as defensive
+/// information ensuring reader (binary version) of the checkpoint and the writer
+/// (binary version) must match within certain range, etc.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BundleHeaderProto {
+    /// Number of data files in the bundle.
+    #[prost(int32, tag="1")]
+    pub num_shards: i32,
+    #[prost(enumeration="bundle_header_proto::Endianness", tag="2")]
+    pub endianness: i32,
+    /// Versioning of the tensor bundle format.
+    #[prost(message, optional, tag="3")]
+    pub version: ::core::option::Option<VersionDef>,
+}
+/// Nested message and enum types in `BundleHeaderProto`.
+pub mod bundle_header_proto {
+    /// An enum indicating the endianness of the platform that produced this
+    /// bundle.  A bundle can only be read by a platform with matching endianness.
+    /// Defaults to LITTLE, as most modern platforms are little-endian.
+    ///
+    /// Affects the binary tensor data bytes only, not the metadata in protobufs.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Endianness {
+        Little = 0,
+        Big = 1,
+    }
+}
+/// Describes the metadata related to a checkpointed tensor.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BundleEntryProto {
+    /// The tensor dtype and shape.
+    #[prost(enumeration="DataType", tag="1")]
+    pub dtype: i32,
+    #[prost(message, optional, tag="2")]
+    pub shape: ::core::option::Option<TensorShapeProto>,
+    /// The binary content of the tensor lies in:
+    ///    File "shard_id": bytes [offset, offset + size).
+    #[prost(int32, tag="3")]
+    pub shard_id: i32,
+    #[prost(int64, tag="4")]
+    pub offset: i64,
+    #[prost(int64, tag="5")]
+    pub size: i64,
+    /// The CRC32C checksum of the tensor bytes.
+    #[prost(fixed32, tag="6")]
+    pub crc32c: u32,
+    /// Iff present, this entry represents a partitioned tensor.  The previous
+    /// fields are interpreted as follows:
+    ///
+    ///    "dtype", "shape": describe the full tensor.
+    ///    "shard_id", "offset", "size", "crc32c": all IGNORED.
+    ///       These information for each slice can be looked up in their own
+    ///       BundleEntryProto, keyed by each "slice_name".
+    #[prost(message, repeated, tag="7")]
+    pub slices: ::prost::alloc::vec::Vec<TensorSliceProto>,
+}
 /// Protocol buffer representing the configuration of a Saver.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SaverDef {
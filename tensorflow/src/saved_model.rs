@@ -0,0 +1,299 @@
+//! SavedModel directories: a `saved_model.pb` holding the graphs and their signatures, and a
+//! `variables/` checkpoint holding the variable values.
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use prost::Message;
+use tract_hir::internal::*;
+
+use crate::checkpoint::Checkpoint;
+use crate::functions::CALL_OPS;
+use crate::model::Tensorflow;
+use crate::tfpb;
+use crate::tfpb::tensorflow::saved_object::Kind;
+use crate::tfpb::tensorflow::tensor_info::Encoding;
+use crate::tfpb::tensorflow::{
+    DataType, GraphDef, MetaGraphDef, NodeDef, SignatureDef, TensorInfo, TensorProto,
+    TrackableObjectGraph,
+};
+
+const VARIABLE_OPS: &[&str] = &["VarHandleOp", "VariableV2", "Variable"];
+const OBJECT_GRAPH_KEY: &str = "_CHECKPOINTABLE_OBJECT_GRAPH";
+
+impl Tensorflow {
+    /// Load a SavedModel directory as a self-contained graph.
+    ///
+    /// The graph is the one of the meta graph tagged "serve" (or the first one), with its
+    /// variables turned into constants from the checkpoint, and pruned to the given signature
+    /// (by default "serving_default", or the only one). The signature inputs become the graph
+    /// placeholders, in the order of their keys, and its outputs are exposed as Identity
+    /// nodes named after their keys.
+    pub fn read_saved_model_dir(
+        &self,
+        dir: impl AsRef<Path>,
+        signature: Option<&str>,
+    ) -> TractResult<GraphDef> {
+        let dir = dir.as_ref();
+        let pb = dir.join("saved_model.pb");
+        let saved = self.open_saved_model(
+            &mut fs::File::open(&pb).with_context(|| format!("Opening {pb:?}"))?,
+        )?;
+        let meta = saved
+            .meta_graphs
+            .iter()
+            .find(|m| m.meta_info_def.as_ref().is_some_and(|i| i.tags.iter().any(|t| t == "serve")))
+            .or(saved.meta_graphs.first())
+            .context("SavedModel contains no meta graph")?;
+        let mut graph = meta.graph_def.clone().context("Meta graph without graph")?;
+        let variables = dir.join("variables").join("variables");
+        if dir.join("variables").join("variables.index").exists() {
+            let checkpoint = Checkpoint::open(&variables)?;
+            restore_variables(&mut graph, meta, &checkpoint)
+                .context("Restoring variables from checkpoint")?;
+        }
+        if let Some((name, signature)) = select_signature(meta, signature)? {
+            apply_signature(&mut graph, name, signature)
+                .with_context(|| format!("Applying signature {name}"))?;
+        }
+        Ok(graph)
+    }
+}
+
+fn restore_variables(
+    graph: &mut GraphDef,
+    meta: &MetaGraphDef,
+    checkpoint: &Checkpoint,
+) -> TractResult<()> {
+    let keys = checkpoint_keys(graph, meta, checkpoint)?;
+    for node in &mut graph.node {
+        if !VARIABLE_OPS.contains(&&*node.op) {
+            continue;
+        }
+        // variables missing from the checkpoint (saver state, optimizer slots...) are left
+        // alone, and pruned by the signature if they are not needed
+        let Some(key) = keys.get(&node.name) else { continue };
+        let value = checkpoint.tensor(key)?;
+        *node = tfpb::node()
+            .name(&node.name)
+            .op("Const")
+            .attr("dtype", DataType::try_from(value.datum_type())?)
+            .attr("value", TensorProto::try_from(&value)?);
+    }
+    Ok(())
+}
+
+// Checkpoint keys of the variable nodes.
+//
+// TF1 savers key variables by their names. TF2 checkpoints are keyed by object paths, and
+// contain an object graph whose nodes match the SavedModel object graph: SavedVariable
+// objects give their variable name, and concrete functions list the variables they capture,
+// which are the trailing inputs of their call nodes.
+fn checkpoint_keys(
+    graph: &GraphDef,
+    meta: &MetaGraphDef,
+    checkpoint: &Checkpoint,
+) -> TractResult<HashMap<String, String>> {
+    let mut keys = HashMap::default();
+    let variables = || graph.node.iter().filter(|n| VARIABLE_OPS.contains(&&*n.op));
+    let names = |node: &NodeDef| -> TractResult<Vec<String>> {
+        let shared = node.get_attr_opt_str("shared_name")?.filter(|s| !s.is_empty());
+        Ok(shared.into_iter().chain(std::iter::once(node.name.clone())).collect())
+    };
+    for node in variables() {
+        if let Some(name) = names(node)?.into_iter().find(|n| checkpoint.contains(n)) {
+            keys.insert(node.name.clone(), name);
+        }
+    }
+    let Some(objects) = &meta.object_graph_def else { return Ok(keys) };
+    if !checkpoint.contains(OBJECT_GRAPH_KEY) {
+        return Ok(keys);
+    }
+    let trackable = checkpoint.tensor(OBJECT_GRAPH_KEY)?;
+    let trackable = TrackableObjectGraph::decode(trackable.to_scalar::<Blob>()?.as_bytes())?;
+    let key_of = |id: usize| {
+        trackable.nodes.get(id).and_then(|n| {
+            n.attributes
+                .iter()
+                .find(|a| a.name == "VARIABLE_VALUE")
+                .map(|a| a.checkpoint_key.clone())
+        })
+    };
+    let mut by_name = HashMap::<String, String>::default();
+    for (id, object) in objects.nodes.iter().enumerate() {
+        if let (Some(Kind::Variable(v)), Some(key)) = (&object.kind, key_of(id)) {
+            by_name.insert(v.name.trim_end_matches(":0").to_string(), key);
+        }
+    }
+    for node in variables() {
+        if let Some(key) = names(node)?.iter().find_map(|n| by_name.get(n)) {
+            keys.insert(node.name.clone(), key.clone());
+        }
+    }
+    for node in graph.node.iter().filter(|n| CALL_OPS.contains(&&*n.op)) {
+        let Some(function) = objects.concrete_functions.get(&node.get_attr_func("f")?.name) else {
+            continue;
+        };
+        let inputs: Vec<&str> =
+            node.input.iter().filter(|i| !i.starts_with('^')).map(|i| &**i).collect();
+        let Some(first) = inputs.len().checked_sub(function.bound_inputs.len()) else {
+            continue;
+        };
+        for (input, id) in inputs[first..].iter().zip(function.bound_inputs.iter()) {
+            let (name, _) = Tensorflow::parse_input(input)?;
+            if let Some(key) = key_of(*id as usize) {
+                keys.insert(name.to_string(), key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+fn select_signature<'m>(
+    meta: &'m MetaGraphDef,
+    name: Option<&str>,
+) -> TractResult<Option<(&'m str, &'m SignatureDef)>> {
+    let signatures = &meta.signature_def;
+    let available = || signatures.keys().cloned().collect::<Vec<_>>().join(", ");
+    let found = if let Some(name) = name {
+        signatures
+            .get_key_value(name)
+            .with_context(|| format!("No signature {name} (available: {})", available()))?
+    } else if let Some(found) = signatures.get_key_value("serving_default") {
+        found
+    } else if signatures.len() == 1 {
+        signatures.iter().next().unwrap()
+    } else if signatures.is_empty() {
+        return Ok(None);
+    } else {
+        bail!("Several signatures, please pick one of: {}", available())
+    };
+    Ok(Some((&**found.0, found.1)))
+}
+
+fn tensor_name(info: &TensorInfo) -> TractResult<&str> {
+    match &info.encoding {
+        Some(Encoding::Name(name)) => Ok(name),
+        _ => bail!("Only dense tensors are supported in signatures"),
+    }
+}
+
+fn apply_signature(graph: &mut GraphDef, name: &str, signature: &SignatureDef) -> TractResult<()> {
+    let mut inputs: Vec<(&String, &TensorInfo)> = signature.inputs.iter().collect();
+    inputs.sort_by_key(|(key, _)| *key);
+    let mut outputs: Vec<(&String, &TensorInfo)> = signature.outputs.iter().collect();
+    outputs.sort_by_key(|(key, _)| *key);
+
+    let existing: HashSet<&str> = graph.node.iter().map(|n| &*n.name).collect();
+    let mut output_nodes = vec![];
+    for (key, info) in outputs {
+        let node_name =
+            if existing.contains(&**key) { format!("{name}/{key}") } else { key.to_string() };
+        output_nodes.push(tfpb::node().name(node_name).op("Identity").input(tensor_name(info)?));
+    }
+
+    let mut input_names = vec![];
+    for (_, info) in inputs {
+        let (node, _) = Tensorflow::parse_input(tensor_name(info)?)?;
+        input_names.push(node.to_string());
+    }
+
+    let by_name: HashMap<&str, &NodeDef> = graph.node.iter().map(|n| (&*n.name, n)).collect();
+    let mut keep: HashSet<&str> = input_names.iter().map(|s| &**s).collect();
+    let mut todo: Vec<&str> = vec![];
+    for node in &output_nodes {
+        for input in &node.input {
+            todo.push(Tensorflow::parse_input(input)?.0);
+        }
+    }
+    while let Some(name) = todo.pop() {
+        if keep.insert(name) {
+            let node = by_name.get(name).with_context(|| format!("Node {name} not found"))?;
+            for input in &node.input {
+                todo.push(Tensorflow::parse_input(input)?.0);
+            }
+        }
+    }
+    for input in &input_names {
+        ensure!(by_name.contains_key(&**input), "Signature input {} not found", input);
+    }
+
+    let mut nodes: Vec<NodeDef> = input_names.iter().map(|name| by_name[&**name].clone()).collect();
+    nodes.extend(
+        graph
+            .node
+            .iter()
+            .filter(|n| keep.contains(&*n.name) && !input_names.contains(&n.name))
+            .cloned(),
+    );
+    nodes.extend(output_nodes);
+    graph.node = nodes;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checkpoint::test::write_checkpoint;
+    use crate::tfpb::tensorflow::SavedModel;
+    use tract_hir::prelude::Framework;
+
+    fn info(name: &str) -> TensorInfo {
+        TensorInfo { encoding: Some(Encoding::Name(name.into())), ..TensorInfo::default() }
+    }
+
+    #[test]
+    fn load_saved_model_dir() -> TractResult<()> {
+        let dir = temp_dir::TempDir::new()?;
+        let dir = dir.path();
+        fs::create_dir_all(dir.join("variables"))?;
+        let graph = tfpb::graph()
+            .node(
+                tfpb::node()
+                    .name("serving_default_x")
+                    .op("Placeholder")
+                    .attr("dtype", DataType::DtFloat),
+            )
+            .node(
+                tfpb::node()
+                    .name("w")
+                    .op("VarHandleOp")
+                    .attr("shared_name", "w")
+                    .attr("dtype", DataType::DtFloat),
+            )
+            .node(tfpb::node().name("read").op("ReadVariableOp").input("w"))
+            .node(tfpb::node().name("mul").op("Mul").input("serving_default_x").input("read"))
+            .node(
+                tfpb::node()
+                    .name("saver_filename")
+                    .op("Placeholder")
+                    .attr("dtype", DataType::DtString),
+            );
+        let signature = SignatureDef {
+            inputs: [("x".to_string(), info("serving_default_x:0"))].into_iter().collect(),
+            outputs: [("y".to_string(), info("mul:0"))].into_iter().collect(),
+            method_name: String::new(),
+        };
+        let meta = MetaGraphDef {
+            graph_def: Some(graph),
+            signature_def: [("serving_default".to_string(), signature)].into_iter().collect(),
+            ..MetaGraphDef::default()
+        };
+        let saved = SavedModel { saved_model_schema_version: 1, meta_graphs: vec![meta] };
+        fs::write(dir.join("saved_model.pb"), saved.encode_to_vec())?;
+        write_checkpoint(&dir.join("variables").join("variables"), &[("w", tensor1(&[2f32, 3.]))])?;
+
+        let tf = crate::tensorflow();
+        let graph = tf.proto_model_for_path(dir)?;
+        assert_eq!(
+            graph.node.iter().map(|n| &*n.name).collect::<Vec<_>>(),
+            ["serving_default_x", "w", "read", "mul", "y"]
+        );
+        let mut model = tf.model_for_proto_model(&graph)?;
+        model.set_input_fact(0, f32::fact([2]).into())?;
+        let model = model.into_optimized()?;
+        let y = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        assert_eq!(*y[0], tensor1(&[2f32, 6.]));
+        Ok(())
+    }
+}
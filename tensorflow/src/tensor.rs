@@ -95,7 +95,7 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                     DataType::DtDouble => Self::from_raw::<f64>(&dims, content)?,
                     DataType::DtInt32 => Self::from_raw::<i32>(&dims, content)?,
                     DataType::DtInt64 => Self::from_raw::<i64>(&dims, content)?,
-                    _ => unimplemented!("missing type (for get_tensor_content) {:?}", dtype),
+                    _ => Self::from_raw_dt(DatumType::try_from(dtype)?, &dims, content)?,
                 }
             }
         } else {
@@ -166,6 +166,7 @@ impl<'a> TryFrom<&'a Tensor> for TensorProto {
             DatumType::I64 => {
                 tensor.int64_val = from.to_array_view::<i64>()?.iter().cloned().collect();
             }
+            dt if dt.is_copy() => tensor.tensor_content = from.as_bytes().to_vec(),
             _ => unimplemented!("missing type {:?}", from.datum_type()),
         }
         Ok(tensor)
@@ -16,7 +16,9 @@ pub mod tensorflow {
 
 use self::tensorflow::attr_value::ListValue;
 use self::tensorflow::attr_value::Value;
-use self::tensorflow::{AttrValue, DataType, GraphDef, NodeDef, TensorProto, TensorShapeProto};
+use self::tensorflow::{
+    AttrValue, DataType, GraphDef, NameAttrList, NodeDef, TensorProto, TensorShapeProto,
+};
 
 use std::convert::TryInto;
 
@@ -224,6 +226,21 @@ impl NodeDef {
         };
         Ok(None)
     }
+
+    pub fn get_attr_func(&self, name: &str) -> TractResult<&NameAttrList> {
+        self.get_attr_opt_func(name)?.with_context(|| {
+            format!("Node {} ({}) expected func attribute '{}'", self.name, self.op, name)
+        })
+    }
+
+    pub fn get_attr_opt_func(&self, name: &str) -> TractResult<Option<&NameAttrList>> {
+        if let Some(a) = self.attr.get(name) {
+            if let Value::Func(f) = a.value.as_ref().unwrap() {
+                return Ok(Some(f));
+            }
+        };
+        Ok(None)
+    }
 }
 
 impl From<DataType> for AttrValue {
@@ -290,3 +307,9 @@ impl From<bool> for AttrValue {
         AttrValue { value: Some(Value::B(t)) }
     }
 }
+
+impl From<NameAttrList> for AttrValue {
+    fn from(t: NameAttrList) -> AttrValue {
+        AttrValue { value: Some(Value::Func(t)) }
+    }
+}
@@ -2,9 +2,13 @@ use log::*;
 use prost::Message;
 use std::path::PathBuf;
 use tract_hir::internal::*;
-use tract_onnx::pb::TensorProto;
+use tract_onnx::pb::tensor_proto::DataType;
+use tract_onnx::pb::type_proto::Value;
+use tract_onnx::pb::{TensorProto, TypeProto};
 use tract_onnx::data_resolver::FopenDataResolver;
 use tract_onnx::tensor::load_tensor;
+use tract_onnx_opl::optional::OptionalValue;
+use tract_onnx_opl::sequence::TensorSequence;
 
 use infra::{Test, TestStatus, TestSuite};
 
@@ -52,7 +56,17 @@ impl Test for OnnxTestCase {
             onnx = onnx.with_ignore_output_types(true);
         }
 
-        trace!("Proto Model:\n{:#?}", onnx.proto_model_for_path(&model_file));
+        let proto = onnx.proto_model_for_path(&model_file)?;
+        trace!("Proto Model:\n{:#?}", proto);
+        let graph = proto.graph.as_ref().context("Missing graph")?;
+        let input_types: Vec<Option<&TypeProto>> = graph
+            .input
+            .iter()
+            .filter(|input| graph.initializer.iter().all(|init| init.name != input.name))
+            .map(|input| input.r#type.as_ref())
+            .collect();
+        let output_types: Vec<Option<&TypeProto>> =
+            graph.output.iter().map(|output| output.r#type.as_ref()).collect();
         for d in std::fs::read_dir(&self.path)? {
             let mut model = onnx.model_for_path(&model_file)?;
             let d = d?;
@@ -60,7 +74,7 @@ impl Test for OnnxTestCase {
                 && d.file_name().to_str().unwrap().starts_with("test_data_set_")
             {
                 let data_path = d.path();
-                let mut inputs = load_half_dataset("input", &data_path);
+                let mut inputs = load_half_dataset("input", &data_path, &input_types);
                 if let Some(input) = &self.input {
                     let mut actual_inputs = vec![];
                     let mut actual_input_values = tvec![];
@@ -86,7 +100,7 @@ impl Test for OnnxTestCase {
                 let model = model.into_typed()?.into_decluttered()?;
                 info!("Test model (mode: {}) {:#?}", runtime.name(), self.path);
                 let runnable = runtime.prepare(model)?;
-                run_model(&*runnable, inputs, &data_path, approx)?;
+                run_model(&*runnable, inputs, &data_path, &output_types, approx)?;
                 info!("Test model (mode: {}) {:#?} OK.", runtime.name(), self.path);
             }
         }
@@ -242,7 +256,103 @@ fn setup_test_logger() {
     let _ = env_logger::Builder::from_env("TRACT_LOG").try_init();
 }
 
-pub fn load_half_dataset(prefix: &str, path: &std::path::Path) -> TVec<Tensor> {
+/// Sequence values of test data sets, as in onnx-data.proto.
+#[derive(Clone, PartialEq, prost::Message)]
+struct SequenceProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "2")]
+    elem_type: i32,
+    #[prost(message, repeated, tag = "3")]
+    tensor_values: Vec<TensorProto>,
+}
+
+/// Optional values of test data sets, as in onnx-data.proto.
+#[derive(Clone, PartialEq, prost::Message)]
+struct OptionalProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "2")]
+    elem_type: i32,
+    #[prost(message, optional, tag = "3")]
+    tensor_value: Option<TensorProto>,
+    #[prost(message, optional, tag = "5")]
+    sequence_value: Option<SequenceProto>,
+}
+
+fn load_sequence(proto: &SequenceProto, elem_type: Option<&TypeProto>) -> TractResult<Tensor> {
+    let items = proto
+        .tensor_values
+        .iter()
+        .map(|t| Ok(load_tensor(&FopenDataResolver, t, None)?.into_arc_tensor()))
+        .collect::<TractResult<TVec<_>>>()?;
+    let datum_type = if let Some(first) = items.first() {
+        first.datum_type()
+    } else if let Some(Value::TensorType(t)) = elem_type.and_then(|t| t.value.as_ref()) {
+        DataType::from_i32(t.elem_type).context("Invalid element type")?.try_into()?
+    } else {
+        bail!("Unknown element type for empty sequence {}", proto.name)
+    };
+    Ok(TensorSequence::new(datum_type, items).into_tensor())
+}
+
+/// Decodes a tensor, sequence or optional according to its type in the model.
+fn load_value(bytes: bytes::Bytes, type_proto: Option<&TypeProto>) -> TractResult<Tensor> {
+    match type_proto.and_then(|t| t.value.as_ref()) {
+        Some(Value::SequenceType(seq)) => {
+            load_sequence(&SequenceProto::decode(bytes)?, seq.elem_type.as_deref())
+        }
+        Some(Value::OptionalType(opt)) => {
+            let proto = OptionalProto::decode(bytes)?;
+            let value = if let Some(tensor) = &proto.tensor_value {
+                Some(load_tensor(&FopenDataResolver, tensor, None)?)
+            } else if let Some(seq) = &proto.sequence_value {
+                let elem_type = match opt.elem_type.as_deref().and_then(|t| t.value.as_ref()) {
+                    Some(Value::SequenceType(seq)) => seq.elem_type.as_deref(),
+                    _ => None,
+                };
+                Some(load_sequence(seq, elem_type)?)
+            } else {
+                None
+            };
+            let value = OptionalValue(value.map(|t| t.into_arc_tensor()));
+            Ok(tensor0(Opaque(Arc::new(value))))
+        }
+        _ => load_tensor(&FopenDataResolver, &TensorProto::decode(bytes)?, None),
+    }
+}
+
+/// Compares sequences and optionals element-wise.
+fn compare_opaque(got: &Tensor, expected: &Tensor, approx: Approximation) -> TractResult<()> {
+    if expected.datum_type() != Opaque::datum_type() {
+        return got.close_enough(expected, approx);
+    }
+    if let Ok(expected) = TensorSequence::from_tensor(expected) {
+        let got = TensorSequence::from_tensor(got)?;
+        ensure!(
+            got.items.len() == expected.items.len(),
+            "Expected a sequence of {} items, got {}",
+            expected.items.len(),
+            got.items.len()
+        );
+        for (got, expected) in got.items.iter().zip(expected.items.iter()) {
+            compare_opaque(got, expected, approx)?;
+        }
+        return Ok(());
+    }
+    let got_value = OptionalValue::from_tensor(got).context("Expected an optional")?;
+    match (&got_value.0, &OptionalValue::from_tensor(expected).unwrap().0) {
+        (Some(got), Some(expected)) => compare_opaque(got, expected, approx),
+        (None, None) => Ok(()),
+        _ => bail!("Expected {:?}, got {:?}", expected, got),
+    }
+}
+
+pub fn load_half_dataset(
+    prefix: &str,
+    path: &std::path::Path,
+    types: &[Option<&TypeProto>],
+) -> TVec<Tensor> {
     let mut vec = tvec!();
     let len = std::fs::read_dir(path)
         .map_err(|e| format!("accessing {path:?}, {e:?}"))
@@ -252,9 +362,8 @@ pub fn load_half_dataset(prefix: &str, path: &std::path::Path) -> TVec<Tensor> {
     for i in 0..len {
         let filename = path.join(format!("{prefix}_{i}.pb"));
         let bytes = bytes::Bytes::from(std::fs::read(filename).unwrap());
-        let tensor = TensorProto::decode(bytes).unwrap();
-        let tensor = load_tensor(&FopenDataResolver, &tensor, None).unwrap();
-        vec.push(tensor)
+        let type_proto = types.get(i).copied().flatten();
+        vec.push(load_value(bytes, type_proto).unwrap())
     }
     debug!("{:?}: {:?}", path, vec);
     vec
@@ -264,9 +373,10 @@ fn run_model(
     model: &dyn Runnable,
     inputs: TVec<Tensor>,
     data_path: &std::path::Path,
+    output_types: &[Option<&TypeProto>],
     approx: Approximation,
 ) -> TractResult<()> {
-    let expected = load_half_dataset("output", data_path);
+    let expected = load_half_dataset("output", data_path, output_types);
     trace!("Loaded output asserts: {:?}", expected);
     let inputs = inputs.into_iter().map(|t| t.into_tvalue()).collect();
     let computed = model.run(inputs)?;
@@ -279,6 +389,12 @@ fn run_model(
         );
     }
     for (ix, (a, b)) in computed.iter().zip(expected.iter()).enumerate() {
+        if b.datum_type() == Opaque::datum_type() {
+            compare_opaque(a, b, approx).with_context(|| {
+                format!("For {data_path:?}, different ({approx:?}) result for output #{ix}")
+            })?;
+            continue;
+        }
         //                println!("computed: {:?}", computed[ix].dump(true));
         //                println!("expected: {:?}", expected[ix].dump(true));
         if let Err(e) = a.close_enough(b, approx) {
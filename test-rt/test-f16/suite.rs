@@ -84,6 +84,9 @@ test_unsqueeze
     .trim()
     .lines()
     .any(|s| t.last().unwrap() == s.trim())
+        || t.last().unwrap().starts_with("test_optional_")
+        || t.last().unwrap().starts_with("test_sequence_")
+        || t.last().unwrap().starts_with("test_split_to_sequence")
         || t.last().unwrap().starts_with("test_logsoftmax_large_number")
         || t.last().unwrap().starts_with("test_softmax_large_number")
         || t.last().unwrap().starts_with("test_resize")
@@ -45,6 +45,7 @@ test_dynamicquantizelinear_max_adjusted
 test_dynamicquantizelinear_min_adjusted
 test_gemm_broadcast
 test_gemm_nobroadcast
+test_loop11
 test_maxpool_2d_ceil
 test_maxpool_2d_same_lower
 test_maxpool_with_argmax_2d_precomputed_pads
@@ -75,6 +76,9 @@ test_unsqueeze
     .trim()
     .lines()
     .any(|s| t.last().unwrap() == s.trim())
+        || t.last().unwrap().starts_with("test_optional_")
+        || t.last().unwrap().starts_with("test_sequence_")
+        || t.last().unwrap().starts_with("test_split_to_sequence")
 }
 
 fn ignore_unit(t: &[String], tc: &dyn Test) -> bool {
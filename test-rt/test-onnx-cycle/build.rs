@@ -0,0 +1,6 @@
+#[path="suite.rs"]
+mod suite;
+
+fn main() {
+    suite::suite().test_runtime("onnx_cycle", "suite::suite()", "runtime()", "Approximation::Approximate");
+}
@@ -0,0 +1,43 @@
+#![cfg(test)]
+use log::*;
+use tract_core::internal::*;
+use tract_onnx::prelude::Framework;
+use tract_onnx::Onnx;
+
+#[path = "../suite.rs"]
+mod suite;
+
+mod onnx_cycle {
+    use super::*;
+
+    #[derive(Debug)]
+    struct OnnxCyclingRuntime;
+
+    impl Runtime for OnnxCyclingRuntime {
+        fn name(&self) -> Cow<str> {
+            "onnx_cycle".into()
+        }
+
+        fn prepare(&self, model: TypedModel) -> TractResult<Box<dyn Runnable>> {
+            info!("Store to ONNX");
+            let mut buffer = vec![];
+            onnx().write(&model, &mut buffer).context("Translating model to ONNX")?;
+            info!("Reload from ONNX");
+            let reloaded = onnx().model_for_read(&mut &*buffer).context("Reloading model")?;
+            Ok(Box::new(Arc::new(reloaded.into_optimized()?.into_runnable()?)))
+        }
+    }
+
+    fn onnx() -> &'static Onnx {
+        lazy_static::lazy_static! {
+            static ref ONNX: Onnx = tract_onnx::onnx();
+        };
+        &ONNX
+    }
+
+    fn runtime() -> &'static OnnxCyclingRuntime {
+        &OnnxCyclingRuntime
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/tests/onnx_cycle.rs"));
+}
@@ -0,0 +1,153 @@
+use infra::Test;
+use regex::Regex;
+
+pub fn suite() -> &'static infra::TestSuite {
+    lazy_static::lazy_static! {
+        static ref SUITE: infra::TestSuite  = mk_suite();
+    };
+    &SUITE
+}
+
+fn mk_suite() -> infra::TestSuite {
+    let mut onnx = suite_onnx::suite().clone();
+    onnx.ignore(&ignore_onnx);
+
+    let mut unit = suite_unit::suite().unwrap().clone();
+    unit.ignore_case(&ignore_unit);
+
+    infra::TestSuite::default().with("onnx", onnx).with("unit", unit)
+}
+
+fn patterns(s: &str) -> Vec<Regex> {
+    s.trim()
+        .lines()
+        .map(|s| s.split_once('#').map(|(left, _)| left).unwrap_or(s).trim())
+        .filter(|s| !s.is_empty())
+        .map(|pat| Regex::new(pat).unwrap())
+        .collect()
+}
+
+fn ignore_onnx(t: &[String]) -> bool {
+    let name = t.last().unwrap();
+    let included = patterns(
+        "
+        _conv_
+        Conv1d
+        Conv2d
+        Conv3d
+        test_convtranspose
+
+        test_averagepool
+        test_maxpool
+        test_globalaveragepool
+        test_globalmaxpool
+
+        squeeze
+        _transpose_
+        test_concat
+        test_expand
+        test_flatten
+        test_reshape
+        test_slice
+        test_split
+        test_gather
+        test_scatter_elements
+        test_scatternd
+        test_pad
+        test_edge_pad
+        test_reflect_pad
+        test_constant_pad
+        test_tile
+        test_top_k
+        test_tril
+        test_triu
+        test_range
+
+        test_where
+        test_less
+        test_greater
+        test_equal
+        test_not
+        test_cast_
+
+        test_add
+        test_mul
+        test_sub
+        test_div
+        test_pow
+        test_min
+        test_max
+        test_and
+        test_or
+        test_xor
+        test_mod
+        test_bitshift
+        test_einsum
+        test_matmul_
+
+        test_reduce_sum
+        test_reduce_prod
+        test_reduce_min
+        test_reduce_max
+        test_reduce_mean
+        test_reduce_l2
+        test_reduce_sum_square
+        test_softmax
+        test_argmax
+        test_argmin
+
+        test_abs
+        test_ceil
+        test_exp
+        test_floor
+        test_log
+        test_neg
+        test_reciprocal
+        test_round
+        test_sign
+        test_sqrt
+        test_erf
+
+        test_cos
+        test_sin
+        test_tan
+        test_acos
+        test_asin
+        test_atan
+
+        test_clip
+        test_batchnorm
+        test_gelu
+        test_hardsigmoid
+        test_hardswish
+        test_leakyrelu
+        test_prelu
+        test_relu
+        test_selu
+        test_sigmoid
+        test_tanh
+        test_thresholdrelu
+        ",
+    );
+    let excluded = patterns(
+        "
+        test_cast_STRING_to_FLOAT
+        test_cast_FLOAT_to_STRING
+        test_cast_.*FLOAT8
+        test_mod_mixed_sign_.*          # python modulo is not expressible with Mod fmod=1
+        test_mod_broadcast
+        test_mod_int64_fmod
+        test_mod_uint
+        test_reshape_allowzero_reordered
+        test_split_zero_size
+        test_split_to_sequence_.*
+        test_slice_start_out_of_bounds
+        test_maxpool_with_argmax_2d_precomputed_strides  # column-major indices
+        ",
+    );
+    !included.iter().any(|pat| pat.is_match(name)) || excluded.iter().any(|pat| pat.is_match(name))
+}
+
+fn ignore_unit(t: &[String], _case: &dyn Test) -> bool {
+    !["conv_f32", "deconv", "slice"].contains(&&*t[0])
+}
@@ -38,7 +38,7 @@ mod tflite_predump {
     include!(concat!(env!("OUT_DIR"), "/tests/tests.rs"));
 }
 
-mod tflite_cycle {
+pub(crate) mod tflite_cycle {
     use tract_tflite::internal::tract_core::ops::dummy::Dummy;
 
     use super::*;
@@ -55,36 +55,7 @@ mod tflite_cycle {
             let mut buffer = vec![];
             self.0.write(&model, &mut buffer).context("Translating model to tflite")?;
             info!("Reload from Tflite");
-            let mut reloaded =
-                self.0.model_for_read(&mut &*buffer).context("Reloading model from tflite")?;
-            for i in 0..model.inputs.len() {
-                if model.input_fact(i)? != reloaded.input_fact(i)?
-                    && model.input_fact(i)?.datum_type.unquantized()
-                        == reloaded.input_fact(i)?.datum_type.unquantized()
-                {
-                    let old_source_outlet = reloaded.inputs[i];
-                    let name = reloaded.node(old_source_outlet.node).name.clone();
-                    let new_source = reloaded.add_source(&name, model.input_fact(i)?.clone())?;
-                    let wire = reloaded.wire_node(
-                        format!("{}.qp", name),
-                        tract_core::ops::cast::cast(reloaded.input_fact(i)?.datum_type),
-                        &[new_source],
-                    )?[0];
-                    reloaded.inputs.pop();
-                    reloaded.inputs[i] = new_source;
-                    let succs = reloaded.node(old_source_outlet.node).outputs[0].successors.clone();
-                    for succ in succs {
-                        reloaded.add_edge(wire, succ)?;
-                    }
-                    for output in &mut reloaded.outputs {
-                        if *output == old_source_outlet {
-                            *output = new_source;
-                        }
-                    }
-                    reloaded.nodes[old_source_outlet.node].name.push_str(".old");
-                    reloaded.nodes[old_source_outlet.node].op = Box::new(Dummy);
-                }
-            }
+            let reloaded = reload(&self.0, &model, &buffer)?;
             Ok(Box::new(Arc::new(
                 reloaded
                     .into_optimized()
@@ -94,6 +65,46 @@ mod tflite_cycle {
         }
     }
 
+    /// Reload a written model, restoring the original input facts where tflite only kept
+    /// the quantized storage type.
+    pub(crate) fn reload(
+        tflite: &Tflite,
+        model: &TypedModel,
+        buffer: &[u8],
+    ) -> TractResult<TypedModel> {
+        let mut reloaded =
+            tflite.model_for_read(&mut &*buffer).context("Reloading model from tflite")?;
+        for i in 0..model.inputs.len() {
+            if model.input_fact(i)? != reloaded.input_fact(i)?
+                && model.input_fact(i)?.datum_type.unquantized()
+                    == reloaded.input_fact(i)?.datum_type.unquantized()
+            {
+                let old_source_outlet = reloaded.inputs[i];
+                let name = reloaded.node(old_source_outlet.node).name.clone();
+                let new_source = reloaded.add_source(&name, model.input_fact(i)?.clone())?;
+                let wire = reloaded.wire_node(
+                    format!("{}.qp", name),
+                    tract_core::ops::cast::cast(reloaded.input_fact(i)?.datum_type),
+                    &[new_source],
+                )?[0];
+                reloaded.inputs.pop();
+                reloaded.inputs[i] = new_source;
+                let succs = reloaded.node(old_source_outlet.node).outputs[0].successors.clone();
+                for succ in succs {
+                    reloaded.add_edge(wire, succ)?;
+                }
+                for output in &mut reloaded.outputs {
+                    if *output == old_source_outlet {
+                        *output = new_source;
+                    }
+                }
+                reloaded.nodes[old_source_outlet.node].name.push_str(".old");
+                reloaded.nodes[old_source_outlet.node].op = Box::new(Dummy);
+            }
+        }
+        Ok(reloaded)
+    }
+
     fn runtime() -> &'static TfliteCyclingRuntime {
         lazy_static::lazy_static! {
             static ref RT: TfliteCyclingRuntime = TfliteCyclingRuntime(Tflite::default());
@@ -103,3 +114,125 @@ mod tflite_cycle {
 
     include!(concat!(env!("OUT_DIR"), "/tests/tests.rs"));
 }
+
+mod tflite_roundtrip {
+    use super::tflite_cycle::reload;
+    use super::tflite_runtime::TfliteRunnable;
+    use super::*;
+
+    /// Runs the written model through both the TFLite interpreter and the tract reader, and
+    /// checks they agree before handing the interpreter outputs to the suite.
+    #[derive(Debug)]
+    struct TfliteRoundtripRuntime(Tflite);
+
+    impl Runtime for TfliteRoundtripRuntime {
+        fn name(&self) -> Cow<str> {
+            "tflite-roundtrip".into()
+        }
+
+        fn prepare(&self, model: TypedModel) -> TractResult<Box<dyn Runnable>> {
+            let mut buffer = vec![];
+            self.0.write(&model, &mut buffer).context("Translating model to tflite")?;
+            let reloaded = reload(&self.0, &model, &buffer)?
+                .into_optimized()
+                .context("Optimising reloaded model")?
+                .into_runnable()?;
+            Ok(Box::new(RoundtripRunnable(TfliteRunnable(buffer), Arc::new(reloaded))))
+        }
+    }
+
+    #[derive(Debug)]
+    struct RoundtripRunnable(TfliteRunnable, Arc<TypedRunnableModel<TypedModel>>);
+
+    impl Runnable for RoundtripRunnable {
+        fn spawn(&self) -> TractResult<Box<dyn State>> {
+            Ok(Box::new(RoundtripState(self.0.spawn()?, self.1.spawn()?)))
+        }
+    }
+
+    struct RoundtripState(Box<dyn State>, Box<dyn State>);
+
+    impl State for RoundtripState {
+        fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+            let interpreter = self.0.run(inputs.clone()).context("Running tflite interpreter")?;
+            let reader = self.1.run(inputs).context("Running reloaded model")?;
+            ensure!(interpreter.len() == reader.len());
+            for (ix, (i, r)) in interpreter.iter().zip(reader.iter()).enumerate() {
+                i.close_enough(r, Approximation::Approximate)
+                    .with_context(|| format!("Interpreter and reader disagree on output #{ix}"))?;
+            }
+            Ok(interpreter)
+        }
+    }
+
+    fn runtime() -> &'static TfliteRoundtripRuntime {
+        lazy_static::lazy_static! {
+            static ref RT: TfliteRoundtripRuntime = TfliteRoundtripRuntime(Tflite::default());
+        };
+        &RT
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/tests/tests.rs"));
+
+    // Writer paths the onnx suite does not reach.
+    mod cases {
+        use super::*;
+        use tract_core::ops::array::{Gather, TypedConcat};
+        use tract_core::ops::cast::cast;
+        use tract_core::ops::nn::{gelu, gelu_approximate};
+
+        fn check(model: TypedModel, input: Tensor) -> TractResult<()> {
+            let expected =
+                model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+            let found = runtime().prepare(model)?.run(tvec!(input.into_tvalue()))?;
+            expected[0].close_enough(&found[0], Approximation::Approximate)
+        }
+
+        fn unary(op: impl Into<Box<dyn TypedOp>>, input: Tensor) -> TractResult<()> {
+            let mut model = TypedModel::default();
+            let x = model.add_source("x", TypedFact::shape_and_dt_of(&input))?;
+            let y = model.wire_node("y", op, &[x])?;
+            model.set_output_outlets(&y)?;
+            check(model, input)
+        }
+
+        fn input() -> Tensor {
+            tensor2(&[[-2.5f32, -1., -0.25], [0., 0.7, 3.2]])
+        }
+
+        #[test]
+        fn cast_to_i64() -> TractResult<()> {
+            unary(cast(i64::datum_type()), input())
+        }
+
+        #[test]
+        fn gelu_exact() -> TractResult<()> {
+            unary(gelu(), input())
+        }
+
+        #[test]
+        fn gelu_tanh() -> TractResult<()> {
+            unary(gelu_approximate(), input())
+        }
+
+        #[test]
+        fn gather_constant_indices() -> TractResult<()> {
+            let mut model = TypedModel::default();
+            let x = model.add_source("x", f32::fact([2, 3]))?;
+            let indices = model.add_const("indices", tensor1(&[2i64, 0, 2]))?;
+            let y = model.wire_node("y", Gather { axis: 1 }, &[x, indices])?;
+            model.set_output_outlets(&y)?;
+            check(model, input())
+        }
+
+        #[test]
+        fn concat_with_constant() -> TractResult<()> {
+            let mut model = TypedModel::default();
+            let x = model.add_source("x", f32::fact([2, 3]))?;
+            let k = model.add_const("k", tensor2(&[[1f32, 2., 3.]]))?;
+            let y = model.wire_node("y", TypedConcat::new(0), &[x, k])?;
+            model.set_output_outlets(&y)?;
+            check(model, input())
+        }
+    }
+}
@@ -28,7 +28,7 @@ impl Runtime for TfliteRuntime {
 }
 
 #[derive(Clone)]
-struct TfliteRunnable(Vec<u8>);
+pub(crate) struct TfliteRunnable(pub(crate) Vec<u8>);
 
 impl Runnable for TfliteRunnable {
     fn spawn(&self) -> TractResult<Box<dyn State>> {
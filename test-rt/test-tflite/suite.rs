@@ -46,6 +46,7 @@ fn ignore_onnx(t: &[String]) -> bool {
         _conv_
         Conv1d
         Conv2d
+        test_convtranspose
 
         test_averagepool_2d
         test_maxpool_2d
@@ -53,10 +54,14 @@ fn ignore_onnx(t: &[String]) -> bool {
         squeeze
         _transpose_
         test_concat
+        test_cumsum
         test_flatten
         test_reshape
         test_slice
         test_split
+        test_gather_[0-9]
+        test_gather_2d_indices
+        test_top_k
 
         test_where
         test_less
@@ -73,6 +78,8 @@ fn ignore_onnx(t: &[String]) -> bool {
 
         test_reduce
         test_softmax
+        test_argmax
+        test_argmin
 
         test_abs
         test_ceil
@@ -107,8 +114,13 @@ fn ignore_onnx(t: &[String]) -> bool {
             test_Conv1d_depthwise_with_multiplier
             test_Conv2d_depthwise_with_multiplier
             test_Conv2d_groups_thnn
+            test_convtranspose_3d
+            test_convtranspose_dilations        # tflite TRANSPOSE_CONV has no dilations
+            test_convtranspose_group
             test_reshape_allowzero_reordered
             test_split_zero_size
+            test_top_k_smallest                 # tflite TOPK_V2 only finds the largest values
+            .*_select_last_index
             test_mul_uint8
             test_div_uint8
             test_reduce_log_sum_exp.*           # tflite does not support f64 reducers 🤷
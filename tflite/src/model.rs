@@ -95,10 +95,24 @@ impl Framework<TfliteProtoModel, TypedModel> for Tflite {
         }
         for op in main.operators().context("No operators in Tflite model")? {
             for input in op.inputs().context("No input in Tflite  operator")? {
+                if input < 0 {
+                    continue;
+                }
                 if let Entry::Vacant(slot) = mapping.entry(input) {
                     let (fact, name) = flat_tensor_to_tract_fact(&root, main, input)?;
+                    if fact.konst.is_none() && main.tensors().unwrap().get(input as _).is_variable()
+                    {
+                        let shape = fact.shape.as_concrete().context("Variable tensor shape")?;
+                        let zero = Tensor::zero_dt(fact.datum_type, shape)?;
+                        slot.insert(target.add_const(name, zero)?);
+                        continue;
+                    }
                     let value = fact.konst.with_context(|| format!("Error in TF file for operator {:?}. No prior computation nor constant for input {}", op, input))?;
                     let konst = target.add_const(name, value)?;
+                    if let Some(q) = fact.per_axis_q {
+                        let fact = target.outlet_fact(konst)?.clone().with_per_axis_q(q);
+                        target.set_outlet_fact(konst, fact)?;
+                    }
                     slot.insert(konst);
                 }
             }
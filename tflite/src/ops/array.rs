@@ -1,6 +1,9 @@
 use tract_core::internal::*;
-use tract_core::ops::array::{MultiBroadcastTo, Slice, TypedConcat};
-use tract_core::ops::binary::wire_cast;
+use tract_core::ops::array::{Gather, MultiBroadcastTo, Slice, Topk, TypedConcat};
+use tract_core::ops::binary::{wire_cast, TypedBinOp};
+use tract_core::ops::konst::Const;
+use tract_core::ops::scan::{self, ScanInfo};
+use tract_core::ops::source::TypedSource;
 use tract_core::ops::Downsample;
 use tract_core::prelude::tract_itertools::Itertools;
 use tract_ndarray::ArrayView2;
@@ -9,9 +12,11 @@ use crate::registry::{DeserOp, Registry};
 use crate::ser::{BuiltinOp, SubgraphBuilder};
 use crate::tflite::{
     ActivationFunctionType, BuiltinOperator, BuiltinOptions, ConcatenationOptions,
-    ConcatenationOptionsArgs, ExpandDimsOptions, ExpandDimsOptionsArgs, ReshapeOptions,
-    ReshapeOptionsArgs, SliceOptions, SliceOptionsArgs, SqueezeOptions, SqueezeOptionsArgs,
-    StridedSliceOptions, StridedSliceOptionsArgs, TransposeOptions, TransposeOptionsArgs,
+    ConcatenationOptionsArgs, CumsumOptions, CumsumOptionsArgs, ExpandDimsOptions,
+    ExpandDimsOptionsArgs, GatherOptions, GatherOptionsArgs, ReshapeOptions, ReshapeOptionsArgs,
+    SliceOptions, SliceOptionsArgs, SqueezeOptions, SqueezeOptionsArgs, StridedSliceOptions,
+    StridedSliceOptionsArgs, TopKV2Options, TopKV2OptionsArgs, TransposeOptions,
+    TransposeOptionsArgs,
 };
 
 use super::wire_fused_activation;
@@ -21,19 +26,38 @@ pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser_broadcast_to);
     reg.reg_to_tflite(ser_concat);
     reg.reg_to_tflite(ser_downsample);
+    reg.reg_to_tflite(ser_gather);
+    reg.reg_to_tflite(ser_scan);
     reg.reg_to_tflite(ser_slice);
+    reg.reg_to_tflite(ser_topk);
 
     reg.reg_to_tract(BuiltinOperator::BROADCAST_TO, de_broadcast_to);
     reg.reg_to_tract(BuiltinOperator::CONCATENATION, de_concat);
+    reg.reg_to_tract(BuiltinOperator::CUMSUM, de_cumsum);
     reg.reg_to_tract(BuiltinOperator::EXPAND_DIMS, de_expand_dims);
+    reg.reg_to_tract(BuiltinOperator::GATHER, de_gather);
+    reg.reg_to_tract(BuiltinOperator::PACK, de_pack);
     reg.reg_to_tract(BuiltinOperator::PAD, de_pad);
     reg.reg_to_tract(BuiltinOperator::PADV2, de_padv2);
     reg.reg_to_tract(BuiltinOperator::RESHAPE, de_reshape);
     reg.reg_to_tract(BuiltinOperator::SHAPE, de_shape);
     reg.reg_to_tract(BuiltinOperator::SLICE, de_slice);
+    reg.reg_to_tract(BuiltinOperator::SPACE_TO_DEPTH, de_space_to_depth);
+    reg.reg_to_tract(BuiltinOperator::SPLIT, de_split);
+    reg.reg_to_tract(BuiltinOperator::SPLIT_V, de_split_v);
     reg.reg_to_tract(BuiltinOperator::SQUEEZE, de_squeeze);
     reg.reg_to_tract(BuiltinOperator::STRIDED_SLICE, de_strided_slice);
+    reg.reg_to_tract(BuiltinOperator::TOPK_V2, de_topk_v2);
     reg.reg_to_tract(BuiltinOperator::TRANSPOSE, de_transpose);
+    reg.reg_to_tract(BuiltinOperator::UNPACK, de_unpack);
+}
+
+fn normalize_axis(axis: i64, rank: usize) -> usize {
+    if axis < 0 {
+        (axis + rank as i64) as usize
+    } else {
+        axis as usize
+    }
 }
 
 fn de_broadcast_to(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
@@ -54,6 +78,52 @@ fn de_concat(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
+fn de_cumsum(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_cumsum_options);
+    let (input, axis) = args_2!(op.facts()?);
+    let axis = axis.konst.as_ref().context("Dynamic CUMSUM axis is not supported")?;
+    let axis = normalize_axis(axis.cast_to_scalar::<i64>()?, input.rank());
+    let prefix = op.prefix;
+    let mut var_shape = input.shape.clone();
+    var_shape.set(axis, 1.to_dim());
+    let zero = op.ctx.target.add_const(
+        format!("{prefix}.zero"),
+        Tensor::zero_dt(input.datum_type, &[])?.into_arc_tensor(),
+    )?;
+    let init = op.ctx.target.wire_node(
+        format!("{prefix}.init"),
+        MultiBroadcastTo::new(var_shape.clone()),
+        &[zero],
+    )?[0];
+    let chunk = if options.reverse() { -1 } else { 1 };
+    let input_mapping =
+        vec![scan::InputMapping::Scan(ScanInfo { axis, chunk }), scan::InputMapping::State];
+    // body outputs are acc + x (inclusive) and acc (exclusive)
+    let output_mapping = vec![
+        scan::OutputMapping {
+            scan: Some((0, ScanInfo { axis, chunk })),
+            full_dim_hint: None,
+            last_value_slot: None,
+            state: true,
+        },
+        scan::OutputMapping {
+            scan: Some((1, ScanInfo { axis, chunk })),
+            full_dim_hint: None,
+            last_value_slot: None,
+            state: false,
+        },
+    ];
+    let mut body = TypedModel::default();
+    let var_fact = input.datum_type.fact(var_shape);
+    let x = body.add_source("scan_input", var_fact.clone())?;
+    let acc = body.add_source("acc_input", var_fact)?;
+    let sum = body.wire_node("add", tract_core::ops::math::add(), &[x, acc])?[0];
+    body.set_output_outlets(&[sum, acc])?;
+    let scan = scan::Scan::new(body, input_mapping, output_mapping, 0)?;
+    let wires = op.ctx.target.wire_node(prefix, scan, &[op.inputs[0], init])?;
+    Ok(tvec!(wires[options.exclusive() as usize]))
+}
+
 fn de_expand_dims(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let (input, axes) = args_2!(op.facts()?);
     let axes = axes.konst.clone().context("Dynamic EXPAND_DIMS is not supported")?;
@@ -67,6 +137,25 @@ fn de_expand_dims(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn de_gather(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_gather_options);
+    ensure!(options.batch_dims() == 0, "GATHER with batch_dims is not supported");
+    let axis = normalize_axis(options.axis() as i64, op.facts()?[0].rank());
+    let indices = wire_cast(op.prefix, op.ctx.target, &op.inputs[1..2], i64::datum_type())?;
+    op.ctx.target.wire_node(op.prefix, Gather { axis }, &[op.inputs[0], indices[0]])
+}
+
+fn de_pack(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_pack_options);
+    let axis = normalize_axis(options.axis() as i64, op.facts()?[0].rank() + 1);
+    let mut wires = tvec!();
+    for (ix, input) in op.inputs.iter().enumerate() {
+        let name = format!("{}.add_axis_{ix}", op.prefix);
+        wires.push(op.ctx.target.wire_node(name, AxisOp::Add(axis), &[*input])?[0]);
+    }
+    op.ctx.target.wire_node(op.prefix, TypedConcat::new(axis), &wires)
+}
+
 fn de_pad(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let (input, pads) = args_2!(op.facts()?);
     let pads = pads.konst.as_ref().context("Dynamic PAD is not supported")?;
@@ -133,6 +222,88 @@ fn de_slice(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn de_space_to_depth(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_space_to_depth_options);
+    let input = args_1!(op.facts()?);
+    ensure!(input.rank() == 4, "SPACE_TO_DEPTH expects NHWC input");
+    let block = options.block_size() as usize;
+    let (h, w, c) = (input.shape[1].clone(), input.shape[2].clone(), input.shape[3].clone());
+    // N,H,W,C -> N,H/b,b,W/b,b,C -> N,H/b,W/b,b,b,C -> N,H/b,W/b,b*b*C
+    let ops = [
+        AxisOp::Reshape(1, tvec!(h.clone()), tvec!(h / block, block.to_dim())),
+        AxisOp::Reshape(3, tvec!(w.clone()), tvec!(w / block, block.to_dim())),
+        AxisOp::Move(2, 3),
+        AxisOp::Reshape(
+            3,
+            tvec!(block.to_dim(), block.to_dim(), c.clone()),
+            tvec!(c * block * block),
+        ),
+    ];
+    let mut wire = tvec!(op.inputs[0]);
+    for (ix, axis_op) in ops.into_iter().enumerate() {
+        wire = op.ctx.target.wire_node(format!("{}.{ix}", op.prefix), axis_op, &wire)?;
+    }
+    Ok(wire)
+}
+
+fn wire_splits(
+    op: &mut DeserOp,
+    input: OutletId,
+    axis: usize,
+    sizes: &[usize],
+) -> TractResult<TVec<OutletId>> {
+    let mut outputs = tvec!();
+    let mut start = 0;
+    for (ix, size) in sizes.iter().enumerate() {
+        let slice = Slice { axis, start: start.to_dim(), end: (start + size).to_dim() };
+        outputs.push(op.ctx.target.wire_node(format!("{}.{ix}", op.prefix), slice, &[input])?[0]);
+        start += size;
+    }
+    Ok(outputs)
+}
+
+fn de_split(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let (axis, input) = args_2!(op.facts()?);
+    let axis = axis.konst.as_ref().context("Dynamic SPLIT axis is not supported")?;
+    let axis = normalize_axis(axis.cast_to_scalar::<i64>()?, input.rank());
+    let dim = input.shape[axis].to_usize()?;
+    let splits = op.output_facts.len();
+    ensure!(dim % splits == 0, "SPLIT of axis of size {dim} in {splits} parts");
+    let input = op.inputs[1];
+    wire_splits(op, input, axis, &vec![dim / splits; splits])
+}
+
+fn de_split_v(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let (input, sizes, axis) = args_3!(op.facts()?);
+    let axis = axis.konst.as_ref().context("Dynamic SPLIT_V axis is not supported")?;
+    let axis = normalize_axis(axis.cast_to_scalar::<i64>()?, input.rank());
+    let sizes = sizes.konst.as_ref().context("Dynamic SPLIT_V sizes are not supported")?;
+    let mut sizes = sizes.cast_to::<i64>()?.as_slice::<i64>()?.to_vec();
+    ensure!(
+        sizes.len() == op.output_facts.len(),
+        "SPLIT_V has {} sizes for {} outputs",
+        sizes.len(),
+        op.output_facts.len()
+    );
+    ensure!(
+        sizes.iter().all(|s| *s >= -1) && sizes.iter().filter(|s| **s == -1).count() <= 1,
+        "SPLIT_V sizes must be non-negative with at most one -1, got {sizes:?}"
+    );
+    let dim = input.shape[axis].to_i64()?;
+    let known: i64 = sizes.iter().filter(|s| **s >= 0).sum();
+    if let Some(inferred) = sizes.iter().position(|s| *s == -1) {
+        ensure!(known <= dim, "SPLIT_V sizes {sizes:?} exceed axis size {dim}");
+        sizes[inferred] = dim - known;
+    }
+    ensure!(
+        sizes.iter().sum::<i64>() == dim,
+        "SPLIT_V sizes {sizes:?} do not add up to axis size {dim}"
+    );
+    let sizes = sizes.into_iter().map(|s| s as usize).collect_vec();
+    let input = op.inputs[0];
+    wire_splits(op, input, axis, &sizes)
+}
+
 fn de_squeeze(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_squeeze_options);
     let mut wire = tvec!(op.inputs[0]);
@@ -158,6 +329,19 @@ fn de_strided_slice(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     op.ctx.target.wire_node(op.prefix, slice, op.inputs)
 }
 
+fn de_topk_v2(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let rank = op.facts()?[0].rank();
+    let topk = Topk { axis: rank - 1, largest: true, fallback_k: 0.to_dim() };
+    let wires = op.ctx.target.wire_node(op.prefix, topk, op.inputs)?;
+    let indices = wire_cast(
+        format!("{}.indices", op.prefix),
+        op.ctx.target,
+        &wires[1..2],
+        op.output_facts[1].datum_type,
+    )?;
+    Ok(tvec!(wires[0], indices[0]))
+}
+
 fn de_transpose(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let perm = op
         .ctx
@@ -175,6 +359,21 @@ fn de_transpose(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn de_unpack(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_unpack_options);
+    let input = args_1!(op.facts()?);
+    let axis = normalize_axis(options.axis() as i64, input.rank());
+    let mut outputs = tvec!();
+    let prefix = op.prefix;
+    for ix in 0..input.shape[axis].to_usize()? {
+        let slice = Slice { axis, start: ix.to_dim(), end: (ix + 1).to_dim() };
+        let wire = op.ctx.target.wire_node(format!("{prefix}.slice_{ix}"), slice, op.inputs)?;
+        let wire = op.ctx.target.wire_node(format!("{prefix}.{ix}"), AxisOp::Rm(axis), &wire)?;
+        outputs.push(wire[0]);
+    }
+    Ok(outputs)
+}
+
 fn ser_axisop(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -265,7 +464,7 @@ fn ser_broadcast_to(
 
 fn ser_concat(
     builder: &mut SubgraphBuilder,
-    _model: &TypedModel,
+    model: &TypedModel,
     node: &TypedNode,
     op: &TypedConcat,
 ) -> TractResult<()> {
@@ -276,7 +475,7 @@ fn ser_concat(
             fused_activation_function: ActivationFunctionType::NONE,
         },
     );
-    let inputs = node.inputs.iter().map(|outlet| builder.outlets_to_tensors[outlet]).collect_vec();
+    let inputs = builder.map_outlets(model, &node.inputs)?;
     let output = builder.outlets_to_tensors[&node.id.into()];
     builder.write_op_with_options(
         &inputs,
@@ -332,6 +531,100 @@ fn ser_downsample(
     )
 }
 
+fn ser_gather(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Gather,
+) -> TractResult<()> {
+    let inputs = builder.map_outlets(model, &node.inputs)?;
+    let output = builder.map_outlet(model, node.id.into())?;
+    let options = GatherOptions::create(
+        builder.fb(),
+        &GatherOptionsArgs { axis: op.axis as i32, batch_dims: 0 },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(36, 1, BuiltinOperator::GATHER, BuiltinOptions::GatherOptions),
+        options.as_union_value(),
+    )
+}
+
+// Only the cumulative sum shape of Scan (as built by de_cumsum or the ONNX CumSum loader) has a
+// TFLite counterpart. Recurrent layers, UNIDIRECTIONAL_SEQUENCE_LSTM included, are not written
+// back: decluttering reshapes their Scan body beyond recognition.
+fn ser_scan(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &scan::Scan,
+) -> TractResult<()> {
+    ensure!(op.skip == 0 && !op.reset_every_turn, "Only cumsum-shaped Scan can be serialized");
+    let (Some(x_ix), Some(acc_ix)) = (
+        op.input_mapping.iter().position(|m| m.is_scan()),
+        op.input_mapping.iter().position(|m| m.is_state()),
+    ) else {
+        bail!("Only cumsum-shaped Scan can be serialized")
+    };
+    let info = *op.input_mapping[x_ix].as_scan().unwrap();
+    ensure!(
+        op.input_mapping.len() == 2 && info.chunk.abs() == 1,
+        "Only cumsum-shaped Scan can be serialized, recurrent layers are not supported"
+    );
+    let body = &op.body;
+    let sources = body.input_outlets()?;
+    let adds = body
+        .nodes()
+        .iter()
+        .filter(|n| !n.op_is::<TypedSource>() && !n.op_is::<Const>())
+        .collect_vec();
+    ensure!(
+        adds.len() == 1
+            && adds[0]
+                .op_as::<TypedBinOp>()
+                .is_some_and(|bin| bin.0.is::<tract_core::ops::math::Add>())
+            && adds[0].inputs.iter().sorted().eq([sources[0], sources[1]].iter().sorted()),
+        "Only cumsum-shaped Scan can be serialized"
+    );
+    let sum = OutletId::new(adds[0].id, 0);
+    let init = model
+        .outlet_fact(node.inputs[acc_ix])?
+        .konst
+        .as_ref()
+        .and_then(|k| k.as_uniform())
+        .context("Cumsum Scan needs a constant initializer")?;
+    ensure!(init.is_zero()?, "Cumsum Scan needs a zero initializer");
+
+    let axis = builder.write_fact(format!("{}.axis", node.name), tensor0(info.axis as i32))?;
+    let input = builder.map_outlet(model, node.inputs[x_ix])?;
+    for (ix, mapping) in op.output_mapping.iter().enumerate() {
+        ensure!(mapping.last_value_slot.is_none() || mapping.state);
+        let Some((slot, output_info)) = mapping.scan else { continue };
+        ensure!(output_info == info);
+        let outlet = body.output_outlets()?[ix];
+        let exclusive = if outlet == sum {
+            false
+        } else if outlet == sources[acc_ix] {
+            true
+        } else {
+            bail!("Only cumsum-shaped Scan can be serialized")
+        };
+        let output = builder.map_outlet(model, OutletId::new(node.id, slot))?;
+        let options = CumsumOptions::create(
+            builder.fb(),
+            &CumsumOptionsArgs { exclusive, reverse: info.chunk < 0 },
+        );
+        builder.write_op_with_options(
+            &[input, axis],
+            &[output],
+            BuiltinOp::new(127, 1, BuiltinOperator::CUMSUM, BuiltinOptions::CumsumOptions),
+            options.as_union_value(),
+        )?;
+    }
+    Ok(())
+}
+
 fn ser_slice(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -365,3 +658,141 @@ fn ser_slice(
         options.as_union_value(),
     )
 }
+
+fn ser_topk(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Topk,
+) -> TractResult<()> {
+    let input_fact = model.outlet_fact(node.inputs[0])?;
+    ensure!(
+        op.largest && op.axis + 1 == input_fact.rank(),
+        "TFLite TOPK_V2 is largest-last-axis only"
+    );
+    let k =
+        model.outlet_fact(node.inputs[1])?.konst.as_ref().context("TOPK_V2 needs a constant k")?;
+    let k = builder.write_fact(format!("{}.k", node.name), tensor0(k.cast_to_scalar::<i32>()?))?;
+    let inputs = [builder.map_outlet(model, node.inputs[0])?, k];
+    let values = builder.map_outlet(model, node.id.into())?;
+    let indices = builder.map_outlet(model, OutletId::new(node.id, 1))?;
+    // TFLite produces i32 indices, tract i64
+    let indices_fact = i32::fact(node.outputs[1].fact.shape.clone());
+    let indices_i32 = builder.write_fact(format!("{}.indices_i32", node.name), indices_fact)?;
+    let options = TopKV2Options::create(builder.fb(), &TopKV2OptionsArgs {});
+    builder.write_op_with_options(
+        &inputs,
+        &[values, indices_i32],
+        BuiltinOp::new(48, 1, BuiltinOperator::TOPK_V2, BuiltinOptions::TopKV2Options),
+        options.as_union_value(),
+    )?;
+    builder.write_op(&[indices_i32], &[indices], 53, 1, BuiltinOperator::CAST)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::test::{run_builtin, Input};
+    use crate::tflite::{
+        PackOptions, PackOptionsArgs, SpaceToDepthOptions, SpaceToDepthOptionsArgs, SplitVOptions,
+        SplitVOptionsArgs, UnpackOptions, UnpackOptionsArgs,
+    };
+
+    #[test]
+    fn pack() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let b = tensor2(&[[7f32, 8., 9.], [10., 11., 12.]]);
+        let outputs = run_builtin(
+            BuiltinOperator::PACK,
+            BuiltinOptions::PackOptions,
+            |fb| {
+                PackOptions::create(fb, &PackOptionsArgs { values_count: 2, axis: -2 })
+                    .as_union_value()
+            },
+            vec![Input::Source(a), Input::Const(b)],
+            &[f32::fact([2, 2, 3])],
+        )?;
+        let expected = tensor3(&[[[1f32, 2., 3.], [7., 8., 9.]], [[4., 5., 6.], [10., 11., 12.]]]);
+        assert_eq!(outputs[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn unpack() -> TractResult<()> {
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let outputs = run_builtin(
+            BuiltinOperator::UNPACK,
+            BuiltinOptions::UnpackOptions,
+            |fb| UnpackOptions::create(fb, &UnpackOptionsArgs { num: 3, axis: 1 }).as_union_value(),
+            vec![Input::Source(input)],
+            &[f32::fact([2]), f32::fact([2]), f32::fact([2])],
+        )?;
+        assert_eq!(outputs[0], tensor1(&[1f32, 4.]));
+        assert_eq!(outputs[1], tensor1(&[2f32, 5.]));
+        assert_eq!(outputs[2], tensor1(&[3f32, 6.]));
+        Ok(())
+    }
+
+    fn split_v(sizes: &[i32]) -> TractResult<TVec<Tensor>> {
+        let input = tensor2(&[[0f32, 1., 2., 3., 4., 5.]]);
+        let outputs = sizes.iter().map(|s| f32::fact([1, (*s).max(0) as usize])).collect_vec();
+        run_builtin(
+            BuiltinOperator::SPLIT_V,
+            BuiltinOptions::SplitVOptions,
+            |fb| {
+                let num_splits = sizes.len() as i32;
+                SplitVOptions::create(fb, &SplitVOptionsArgs { num_splits }).as_union_value()
+            },
+            vec![Input::Source(input), Input::Const(tensor1(sizes)), Input::Const(tensor0(-1i32))],
+            &outputs,
+        )
+    }
+
+    #[test]
+    fn split_v_sizes() -> TractResult<()> {
+        let outputs = split_v(&[1, 3, 2])?;
+        assert_eq!(outputs[0], tensor2(&[[0f32]]));
+        assert_eq!(outputs[1], tensor2(&[[1f32, 2., 3.]]));
+        assert_eq!(outputs[2], tensor2(&[[4f32, 5.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn split_v_infers_one_size() -> TractResult<()> {
+        let outputs = split_v(&[2, -1, 1])?;
+        assert_eq!(outputs[0], tensor2(&[[0f32, 1.]]));
+        assert_eq!(outputs[1], tensor2(&[[2f32, 3., 4.]]));
+        assert_eq!(outputs[2], tensor2(&[[5f32]]));
+        Ok(())
+    }
+
+    #[test]
+    fn split_v_rejects_invalid_sizes() {
+        assert!(split_v(&[2, -1, -1]).is_err());
+        assert!(split_v(&[2, -2, 6]).is_err());
+        assert!(split_v(&[4, -1, 3]).is_err());
+        assert!(split_v(&[2, 3]).is_err());
+    }
+
+    #[test]
+    fn space_to_depth() -> TractResult<()> {
+        let input = tensor1(&(0..32).map(|x| x as f32).collect_vec()).into_shape(&[1, 4, 4, 2])?;
+        let outputs = run_builtin(
+            BuiltinOperator::SPACE_TO_DEPTH,
+            BuiltinOptions::SpaceToDepthOptions,
+            |fb| {
+                SpaceToDepthOptions::create(fb, &SpaceToDepthOptionsArgs { block_size: 2 })
+                    .as_union_value()
+            },
+            vec![Input::Source(input.clone())],
+            &[f32::fact([1, 2, 2, 8])],
+        )?;
+        let input = input.to_array_view::<f32>()?;
+        let expected = tract_ndarray::Array4::from_shape_fn((1, 2, 2, 8), |(n, h, w, c)| {
+            let (block, c) = (c / 2, c % 2);
+            input[[n, h * 2 + block / 2, w * 2 + block % 2, c]]
+        });
+        assert_eq!(outputs[0], expected.into_tensor());
+        Ok(())
+    }
+}
@@ -4,17 +4,17 @@ use crate::ser::{BuiltinOp, SubgraphBuilder};
 use crate::tflite::{
     ActivationFunctionType, BuiltinOperator, BuiltinOptions, Conv2DOptions, Conv2DOptionsArgs,
     DepthwiseConv2DOptions, DepthwiseConv2DOptionsArgs, PadOptions, PadOptionsArgs, Padding,
-    Pool2DOptions, Pool2DOptionsArgs,
+    Pool2DOptions, Pool2DOptionsArgs, TransposeConvOptions, TransposeConvOptionsArgs,
 };
 use flatbuffers::{FlatBufferBuilder, WIPOffset};
 use tract_core::internal::*;
 use tract_core::ops as core;
-use tract_core::ops::array::{Pad, PadMode};
+use tract_core::ops::array::{Gather, Pad, PadMode};
 use tract_core::ops::cast::cast;
-use tract_core::ops::cnn::{Conv, MaxPool, PaddingSpec, PoolSpec};
+use tract_core::ops::cnn::{Conv, Deconv, MaxPool, PaddingSpec, PoolSpec};
 use tract_core::ops::cnn::{KernelFormat, SumPool};
 use tract_core::ops::nn::DataFormat;
-use tract_core::prelude::tract_itertools::Itertools;
+use tract_core::prelude::tract_itertools::{izip, Itertools};
 
 pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser_max_pool);
@@ -25,6 +25,10 @@ pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser_conv);
     reg.reg_to_tract(BuiltinOperator::DEPTHWISE_CONV_2D, de_dw_conv2d);
     reg.reg_to_tflite(ser_pad);
+    reg.reg_to_tflite(ser_deconv);
+    reg.reg_to_tract(BuiltinOperator::TRANSPOSE_CONV, de_transpose_conv);
+    reg.reg_to_tract(BuiltinOperator::RESIZE_BILINEAR, |op| de_resize(op, true));
+    reg.reg_to_tract(BuiltinOperator::RESIZE_NEAREST_NEIGHBOR, |op| de_resize(op, false));
 }
 
 fn pool_2d_options<'fb>(
@@ -303,6 +307,206 @@ fn de_dw_conv2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
+fn de_transpose_conv(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_transpose_conv_options);
+    let facts = op.facts()?;
+    let (output_shape, kernel, input) = (&facts[0], &facts[1], &facts[2]);
+    ensure!(input.datum_type.is_float(), "Only float TRANSPOSE_CONV is supported");
+    let output_shape =
+        output_shape.konst.as_ref().context("Dynamic TRANSPOSE_CONV output shape")?;
+    let output_shape = output_shape.cast_to::<i64>()?;
+    let output_geo: TVec<usize> =
+        output_shape.as_slice::<i64>()?[1..3].iter().map(|d| *d as usize).collect();
+    let input_geo: TVec<usize> =
+        input.shape.as_concrete().context("Expect concrete input shape")?[1..3].into();
+    let kernel_full_shape = kernel.shape.as_concrete().context("Expect concrete kernel shape")?;
+    let kernel_shape: TVec<usize> = KernelFormat::OHWI.spatial_shape(kernel_full_shape).into();
+    let strides = tvec!(options.stride_h() as usize, options.stride_w() as usize);
+    let padding = match options.padding() {
+        Padding::VALID => PaddingSpec::Valid,
+        Padding::SAME => {
+            // TFLite crops (input - 1) * stride + kernel - output, smaller half first
+            let (before, after): (TVec<usize>, TVec<usize>) =
+                izip!(&input_geo, &kernel_shape, &strides, &output_geo)
+                    .map(|(x, k, s, y)| {
+                        let crop = ((x - 1) * s + k).saturating_sub(*y);
+                        (crop / 2, crop - crop / 2)
+                    })
+                    .unzip();
+            PaddingSpec::Explicit(before, after)
+        }
+        _ => bail!("Unsupported TRANSPOSE_CONV padding {:?}", options.padding()),
+    };
+    let input_channels = *KernelFormat::OHWI.i(kernel_full_shape);
+    let output_channels = *KernelFormat::OHWI.o(kernel_full_shape);
+    let pool_spec = PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape,
+        padding,
+        strides: Some(strides),
+        dilations: None,
+        input_channels,
+        output_channels,
+    };
+    let adjustments = core::cnn::deconv::adjustments(&pool_spec, &input_geo, &output_geo)?;
+    let bias = if let Some(bias) = op.inputs.get(3) {
+        *bias
+    } else {
+        let zeros = Tensor::zero_dt(input.datum_type, &[output_channels])?;
+        op.ctx.target.add_const(format!("{}.bias", op.prefix), zeros)?
+    };
+    let deconv = Deconv { pool_spec, kernel_format: KernelFormat::OHWI, adjustments, group: 1 };
+    let wires = op.ctx.target.wire_node(op.prefix, deconv, &[op.inputs[2], op.inputs[1], bias])?;
+    wire_fused_activation(op, &wires, &options.fused_activation_function())
+}
+
+fn ser_deconv(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    deconv: &Deconv,
+) -> TractResult<()> {
+    let facts = model.node_input_facts(node.id)?;
+    ensure!(deconv.pool_spec.data_format == DataFormat::NHWC);
+    ensure!(facts[0].rank() == 4);
+    ensure!(deconv.kernel_format == KernelFormat::OHWI);
+    ensure!(deconv.group == 1);
+    ensure!(deconv.pool_spec.dilations().iter().all(|d| *d == 1));
+    ensure!(facts[0].datum_type.is_float(), "Only float TRANSPOSE_CONV can be serialized");
+    let input_shape = facts[0].shape.as_concrete().context("Expect concrete input shape")?;
+    let output_shape = node.outputs[0].fact.shape.as_concrete().context("Expect concrete shape")?;
+    let computed = deconv.pool_spec.padding.compute_for_deconv(
+        &input_shape[1..3],
+        &deconv.pool_spec.kernel_shape,
+        &deconv.pool_spec.dilations(),
+        &deconv.pool_spec.strides(),
+        &deconv.adjustments,
+    )?;
+    // TFLite derives the cropping from the output shape: nothing for VALID, or the smaller half
+    // of the excess in front for SAME.
+    let padding = if computed.iter().all(|p| p.pad_before == 0) {
+        Padding::VALID
+    } else if izip!(
+        &computed,
+        &input_shape[1..3],
+        &deconv.pool_spec.kernel_shape,
+        &output_shape[1..3]
+    )
+    .zip(deconv.pool_spec.strides().iter())
+    .all(|((p, x, k, y), s)| p.pad_before == ((x - 1) * s + k).saturating_sub(*y) / 2)
+    {
+        Padding::SAME
+    } else {
+        bail!("Padding {:?} can not be expressed in tflite", deconv.pool_spec.padding)
+    };
+    let node_name = &node.name;
+    let output_shape = tensor1(&output_shape.iter().map(|d| *d as i32).collect_vec());
+    let mut inputs = tvec!(builder.write_fact(format!("{node_name}.output_shape"), output_shape)?);
+    inputs.push(builder.map_outlet(model, node.inputs[1])?);
+    inputs.push(builder.map_outlet(model, node.inputs[0])?);
+    inputs.push(builder.map_outlet(model, node.inputs[2])?);
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let options = TransposeConvOptions::create(
+        builder.fb(),
+        &TransposeConvOptionsArgs {
+            padding,
+            stride_h: deconv.pool_spec.stride(0) as _,
+            stride_w: deconv.pool_spec.stride(1) as _,
+            fused_activation_function: ActivationFunctionType::NONE,
+        },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(
+            67,
+            3,
+            BuiltinOperator::TRANSPOSE_CONV,
+            BuiltinOptions::TransposeConvOptions,
+        ),
+        options.as_union_value(),
+    )
+}
+
+fn de_resize(op: &mut DeserOp, bilinear: bool) -> TractResult<TVec<OutletId>> {
+    let (align_corners, half_pixel_centers) = if bilinear {
+        let options = builtin!(op, builtin_options_as_resize_bilinear_options);
+        (options.align_corners(), options.half_pixel_centers())
+    } else {
+        let options = builtin!(op, builtin_options_as_resize_nearest_neighbor_options);
+        (options.align_corners(), options.half_pixel_centers())
+    };
+    let (input, size) = args_2!(op.facts()?);
+    ensure!(input.rank() == 4, "Resize expects NHWC input");
+    ensure!(!bilinear || input.datum_type.is_float(), "Only float RESIZE_BILINEAR is supported");
+    let size = size.konst.context("Dynamic resize size is not supported")?;
+    let size = size.cast_to::<i64>()?;
+    let prefix = op.prefix;
+    let mut wire = op.inputs[0];
+    // Each spatial axis is resized independently by gathering precomputed source indices.
+    for (ix, axis) in [1, 2].into_iter().enumerate() {
+        let in_len = input.shape[axis].to_usize()?;
+        let out_len = size.as_slice::<i64>()?[ix] as usize;
+        let last = in_len as i64 - 1;
+        let scale = if align_corners && out_len > 1 {
+            (in_len - 1) as f32 / (out_len - 1) as f32
+        } else {
+            in_len as f32 / out_len as f32
+        };
+        let name = format!("{prefix}.axis_{axis}");
+        if bilinear {
+            let (mut lower, mut upper, mut frac) = (vec![], vec![], vec![]);
+            for dst in 0..out_len {
+                let src = if half_pixel_centers {
+                    (dst as f32 + 0.5) * scale - 0.5
+                } else {
+                    dst as f32 * scale
+                };
+                lower.push((src.floor() as i64).clamp(0, last));
+                upper.push((src.ceil() as i64).clamp(0, last));
+                frac.push(src - src.floor());
+            }
+            let mut frac_shape = [1; 4];
+            frac_shape[axis] = out_len;
+            let frac = tensor1(&frac).into_shape(&frac_shape)?;
+            let frac = frac.cast_to_dt(input.datum_type)?.into_owned();
+            let frac = op.ctx.target.add_const(format!("{name}.frac"), frac)?;
+            let a = wire_gather_const(op, format!("{name}.lower"), wire, axis, &lower)?;
+            let b = wire_gather_const(op, format!("{name}.upper"), wire, axis, &upper)?;
+            let target = &mut *op.ctx.target;
+            let diff = target.wire_node(format!("{name}.diff"), core::math::sub(), &[b, a])?;
+            let diff =
+                target.wire_node(format!("{name}.scaled"), core::math::mul(), &[diff[0], frac])?;
+            wire = target.wire_node(name, core::math::add(), &[a, diff[0]])?[0];
+        } else {
+            let indices = (0..out_len)
+                .map(|dst| {
+                    let src = if half_pixel_centers {
+                        (dst as f32 + 0.5) * scale
+                    } else {
+                        dst as f32 * scale
+                    };
+                    let src = if align_corners { src.round() } else { src.floor() };
+                    (src as i64).min(last)
+                })
+                .collect_vec();
+            wire = wire_gather_const(op, name, wire, axis, &indices)?;
+        }
+    }
+    Ok(tvec!(wire))
+}
+
+fn wire_gather_const(
+    op: &mut DeserOp,
+    name: String,
+    wire: OutletId,
+    axis: usize,
+    indices: &[i64],
+) -> TractResult<OutletId> {
+    let indices = op.ctx.target.add_const(format!("{name}.indices"), tensor1(indices))?;
+    Ok(op.ctx.target.wire_node(name, Gather { axis }, &[wire, indices])?[0])
+}
+
 fn ser_pad(
     builder: &mut SubgraphBuilder,
     _model: &TypedModel,
@@ -331,3 +535,74 @@ fn ser_pad(
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::test::{run_builtin, Input};
+    use crate::tflite::{
+        BuiltinOptions, ResizeBilinearOptions, ResizeBilinearOptionsArgs,
+        ResizeNearestNeighborOptions, ResizeNearestNeighborOptionsArgs,
+    };
+
+    fn resize(
+        bilinear: bool,
+        align_corners: bool,
+        half_pixel_centers: bool,
+        input: Tensor,
+        size: [usize; 2],
+    ) -> TractResult<Tensor> {
+        let shape = [1, size[0], size[1], input.shape()[3]];
+        let size = tensor1(&size.map(|d| d as i32));
+        let (code, options_type) = if bilinear {
+            (BuiltinOperator::RESIZE_BILINEAR, BuiltinOptions::ResizeBilinearOptions)
+        } else {
+            (BuiltinOperator::RESIZE_NEAREST_NEIGHBOR, BuiltinOptions::ResizeNearestNeighborOptions)
+        };
+        let mut outputs = run_builtin(
+            code,
+            options_type,
+            |fb| {
+                if bilinear {
+                    let args = ResizeBilinearOptionsArgs { align_corners, half_pixel_centers };
+                    ResizeBilinearOptions::create(fb, &args).as_union_value()
+                } else {
+                    let args =
+                        ResizeNearestNeighborOptionsArgs { align_corners, half_pixel_centers };
+                    ResizeNearestNeighborOptions::create(fb, &args).as_union_value()
+                }
+            },
+            vec![Input::Source(input), Input::Const(size)],
+            &[f32::fact(shape)],
+        )?;
+        Ok(outputs.remove(0))
+    }
+
+    fn row(values: &[f32]) -> Tensor {
+        tensor1(values).into_shape(&[1, 1, values.len(), 1]).unwrap()
+    }
+
+    #[test]
+    fn resize_nearest() -> TractResult<()> {
+        let input = tensor1(&[1f32, 2., 3., 4.]).into_shape(&[1, 2, 2, 1])?;
+        let output = resize(false, false, false, input, [4, 4])?;
+        let expected = tensor1(&[1f32, 1., 2., 2., 1., 1., 2., 2., 3., 3., 4., 4., 3., 3., 4., 4.])
+            .into_shape(&[1, 4, 4, 1])?;
+        assert_eq!(output, expected);
+        assert_eq!(resize(false, false, true, row(&[1., 2., 3.]), [1, 2])?, row(&[1., 3.]));
+        assert_eq!(resize(false, true, false, row(&[1., 2., 3.]), [1, 2])?, row(&[1., 3.]));
+        Ok(())
+    }
+
+    #[test]
+    fn resize_bilinear() -> TractResult<()> {
+        let input = row(&[0., 12.]);
+        let output = resize(true, false, false, input.clone(), [1, 4])?;
+        assert_eq!(output, row(&[0., 6., 12., 12.]));
+        let output = resize(true, false, true, input.clone(), [1, 4])?;
+        assert_eq!(output, row(&[0., 3., 9., 12.]));
+        let output = resize(true, true, false, input, [1, 4])?;
+        output.close_enough(&row(&[0., 4., 8., 12.]), Approximation::Close)?;
+        Ok(())
+    }
+}
@@ -2,23 +2,31 @@ use crate::registry::{DeserOp, Registry};
 use crate::ser::{BuiltinOp, SubgraphBuilder};
 use crate::tflite::{
     AbsOptions, AbsOptionsArgs, BuiltinOperator, BuiltinOptions, CosOptions, CosOptionsArgs,
-    ExpOptions, ExpOptionsArgs, HardSwishOptions, HardSwishOptionsArgs, LeakyReluOptions,
-    LeakyReluOptionsArgs, LogicalNotOptionsArgs, SquareOptions, SquareOptionsArgs, LogicalNotOptions,
+    ExpOptions, ExpOptionsArgs, GeluOptions, GeluOptionsArgs, HardSwishOptions,
+    HardSwishOptionsArgs, LeakyReluOptions, LeakyReluOptionsArgs, LogicalNotOptions,
+    LogicalNotOptionsArgs, SquareOptions, SquareOptionsArgs,
 };
 use tract_core::internal::*;
+use tract_core::ops::cast::Cast;
 use tract_core::ops::element_wise::ElementWiseOp;
-use tract_core::ops::logic::{ Not, not };
+use tract_core::ops::logic::{not, Not};
 use tract_core::ops::math::*;
-use tract_core::ops::nn::{hard_swish, leaky_relu, sigmoid, HardSwish, LeakyRelu, Sigmoid};
+use tract_core::ops::nn::{
+    gelu, gelu_approximate, hard_swish, leaky_relu, sigmoid, Gelu, GeluApproximate, HardSwish,
+    LeakyRelu, Sigmoid,
+};
 
 pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser);
+    reg.reg_to_tflite(ser_cast);
 
     reg.reg_to_tract(BuiltinOperator::ABS, |op| deser(op, abs()));
+    reg.reg_to_tract(BuiltinOperator::CAST, de_cast);
     reg.reg_to_tract(BuiltinOperator::CEIL, |op| deser(op, ceil()));
     reg.reg_to_tract(BuiltinOperator::COS, |op| deser(op, cos()));
     reg.reg_to_tract(BuiltinOperator::EXP, |op| deser(op, exp()));
     reg.reg_to_tract(BuiltinOperator::FLOOR, |op| deser(op, floor()));
+    reg.reg_to_tract(BuiltinOperator::GELU, de_gelu);
     reg.reg_to_tract(BuiltinOperator::HARD_SWISH, |op| deser(op, hard_swish()));
     reg.reg_to_tract(BuiltinOperator::LEAKY_RELU, de_leaky_relu);
     reg.reg_to_tract(BuiltinOperator::LOG, |op| deser(op, ln()));
@@ -35,11 +43,36 @@ fn deser(op: &mut DeserOp, ew: ElementWiseOp) -> TractResult<TVec<OutletId>> {
     op.ctx.target.wire_node(op.prefix, ew, op.inputs)
 }
 
+fn de_cast(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let to = op.output_facts[0].datum_type;
+    op.ctx.target.wire_node(op.prefix, Cast { to }, op.inputs)
+}
+
+fn de_gelu(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_gelu_options);
+    if options.approximate() {
+        deser(op, gelu_approximate())
+    } else {
+        deser(op, gelu())
+    }
+}
+
 fn de_leaky_relu(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_leaky_relu_options);
     op.ctx.target.wire_node(op.prefix, leaky_relu(options.alpha()), op.inputs)
 }
 
+fn ser_cast(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    _op: &Cast,
+) -> TractResult<()> {
+    let input = builder.map_outlet(model, node.inputs[0])?;
+    let output = builder.map_outlet(model, node.id.into())?;
+    builder.write_op(&[input], &[output], 53, 1, BuiltinOperator::CAST)
+}
+
 fn ser(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -72,6 +105,15 @@ fn ser(
             BuiltinOp::new(47, 1, BuiltinOperator::EXP, BuiltinOptions::ExpOptions),
             options.as_union_value(),
         )
+    } else if (*op.0).is::<Gelu>() || (*op.0).is::<GeluApproximate>() {
+        let approximate = (*op.0).is::<GeluApproximate>();
+        let options = GeluOptions::create(builder.fb(), &GeluOptionsArgs { approximate });
+        builder.write_op_with_options(
+            &[input],
+            &[output],
+            BuiltinOp::new(127, 1, BuiltinOperator::GELU, BuiltinOptions::GeluOptions),
+            options.as_union_value(),
+        )
     } else if (*op.0).is::<HardSwish>() {
         let options = HardSwishOptions::create(builder.fb(), &HardSwishOptionsArgs {});
         builder.write_op_with_options(
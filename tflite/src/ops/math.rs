@@ -120,7 +120,18 @@ fn ser_bin(
         );
     }
 
-    match op.0.as_linalg_binop().with_context(|| "Missing implementation for binary")? {
+    // Scale (as produced by MEAN) is a plain MUL as long as both sides share the float type.
+    let binop = if op.0.is::<tract_core::ops::quant::Scale>() {
+        let facts = model.node_input_facts(node.id)?;
+        ensure!(
+            facts[0].datum_type == facts[1].datum_type && facts[1].datum_type.is_float(),
+            "Scale can only be serialized on float operands of the same type"
+        );
+        BinOp::Mul
+    } else {
+        op.0.as_linalg_binop().with_context(|| "Missing implementation for binary")?
+    };
+    match binop {
         BinOp::Add => {
             let options = AddOptions::create(
                 builder.fb(),
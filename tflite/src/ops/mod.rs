@@ -26,6 +26,7 @@ mod cnn;
 mod element_wise;
 mod math;
 mod nn;
+mod rnn;
 
 pub fn register_all(reg: &mut Registry) {
     array::register_all(reg);
@@ -33,6 +34,7 @@ pub fn register_all(reg: &mut Registry) {
     element_wise::register_all(reg);
     math::register_all(reg);
     nn::register_all(reg);
+    rnn::register_all(reg);
     reg.reg_to_tflite(ser_iff);
     reg.reg_to_tract(BuiltinOperator::SELECT, de_iff);
     reg.reg_to_tract(BuiltinOperator::SELECT_V2, de_iff);
@@ -77,11 +79,7 @@ fn linearops_quantization_suport(
             rctensor0(k_qp.scale().unwrap().get(0))
         };
         let k_zp = k_qp.zero_point().unwrap().iter().map(|i| i as i32).collect_vec();
-        let k_zp = if k_zp.iter().all_equal() {
-            tensor0(k_zp[0])
-        } else {
-            tensor1(&k_zp)
-        };
+        let k_zp = if k_zp.iter().all_equal() { tensor0(k_zp[0]) } else { tensor1(&k_zp) };
         inputs.push(op.ctx.target.add_const(format!("{p}.i0"), rctensor0(iqp.zp_scale().0))?);
         inputs.push(op.ctx.target.add_const(format!("{p}.iscale"), rctensor0(iqp.zp_scale().1))?);
         inputs.push(op.ctx.target.add_const(format!("{p}.k0"), k_zp.into_arc_tensor())?);
@@ -108,3 +106,195 @@ fn ser_iff(
 fn de_iff(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     wire_with_rank_broadcast(op.prefix, op.ctx.target, Iff, op.inputs)
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::tflite::{
+        Buffer, BufferArgs, BuiltinOptions, CustomOptionsFormat, Model, ModelArgs, Operator,
+        OperatorArgs, OperatorCode, OperatorCodeArgs, SubGraph, SubGraphArgs, Tensor as FlatTensor,
+        TensorArgs,
+    };
+    use flatbuffers::{FlatBufferBuilder, UnionWIPOffset, WIPOffset};
+
+    pub enum Input {
+        /// A model input, fed with this value.
+        Source(Tensor),
+        Const(Tensor),
+        /// A zero-initialized variable tensor, like LSTM states.
+        Variable(TypedFact),
+        /// An omitted optional input.
+        Absent,
+    }
+
+    fn write_tensor(
+        fb: &mut FlatBufferBuilder<'static>,
+        buffers: &mut Vec<WIPOffset<Buffer<'static>>>,
+        tensors: &mut Vec<WIPOffset<FlatTensor<'static>>>,
+        name: &str,
+        fact: &TypedFact,
+        is_variable: bool,
+    ) -> TractResult<i32> {
+        let buffer = if let Some(k) = &fact.konst {
+            let data = fb.create_vector(k.as_bytes());
+            buffers.push(Buffer::create(fb, &BufferArgs { data: Some(data) }));
+            buffers.len() as u32 - 1
+        } else {
+            0
+        };
+        let shape = fact.shape.as_concrete().unwrap().iter().map(|d| *d as i32).collect_vec();
+        let shape = fb.create_vector(&shape);
+        let name = fb.create_string(name);
+        tensors.push(FlatTensor::create(
+            fb,
+            &TensorArgs {
+                name: Some(name),
+                buffer,
+                is_variable,
+                shape: Some(shape),
+                type_: fact.datum_type.try_into()?,
+                has_rank: true,
+                ..TensorArgs::default()
+            },
+        ));
+        Ok(tensors.len() as i32 - 1)
+    }
+
+    /// Build a model made of a single builtin operator and load it. Returns the model and the
+    /// values of its source inputs. This covers readers for builtins the writer never emits.
+    pub fn load_builtin(
+        code: BuiltinOperator,
+        options_type: BuiltinOptions,
+        options: impl FnOnce(&mut FlatBufferBuilder<'static>) -> WIPOffset<UnionWIPOffset>,
+        inputs: Vec<Input>,
+        outputs: &[TypedFact],
+    ) -> TractResult<(TypedModel, TVec<TValue>)> {
+        let mut fb = FlatBufferBuilder::new();
+        let mut buffers = vec![Buffer::create(&mut fb, &BufferArgs { data: None })];
+        let mut tensors = vec![];
+        let (mut op_inputs, mut graph_inputs, mut values) = (vec![], vec![], tvec![]);
+        for (ix, input) in inputs.into_iter().enumerate() {
+            let name = format!("input_{ix}");
+            let (fact, is_variable) = match input {
+                Input::Absent => {
+                    op_inputs.push(-1);
+                    continue;
+                }
+                Input::Source(t) => {
+                    graph_inputs.push(tensors.len() as i32);
+                    let fact = TypedFact::shape_and_dt_of(&t);
+                    values.push(t.into_tvalue());
+                    (fact, false)
+                }
+                Input::Const(t) => (TypedFact::from(t), false),
+                Input::Variable(fact) => (fact, true),
+            };
+            op_inputs.push(write_tensor(
+                &mut fb,
+                &mut buffers,
+                &mut tensors,
+                &name,
+                &fact,
+                is_variable,
+            )?);
+        }
+        let op_outputs = outputs
+            .iter()
+            .enumerate()
+            .map(|(ix, fact)| {
+                let name = format!("output_{ix}");
+                write_tensor(&mut fb, &mut buffers, &mut tensors, &name, fact, false)
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+
+        let builtin_options = options(&mut fb);
+        let inputs = fb.create_vector(&op_inputs);
+        let outputs = fb.create_vector(&op_outputs);
+        let operator = Operator::create(
+            &mut fb,
+            &OperatorArgs {
+                inputs: Some(inputs),
+                outputs: Some(outputs),
+                opcode_index: 0,
+                builtin_options: Some(builtin_options),
+                builtin_options_type: options_type,
+                custom_options: None,
+                custom_options_format: CustomOptionsFormat::FLEXBUFFERS,
+                mutating_variable_inputs: None,
+                intermediates: None,
+            },
+        );
+        let tensors = fb.create_vector(&tensors);
+        let inputs = fb.create_vector(&graph_inputs);
+        let outputs = fb.create_vector(&op_outputs);
+        let operators = fb.create_vector(&[operator]);
+        let subgraph = SubGraph::create(
+            &mut fb,
+            &SubGraphArgs {
+                name: None,
+                tensors: Some(tensors),
+                inputs: Some(inputs),
+                outputs: Some(outputs),
+                operators: Some(operators),
+            },
+        );
+        let operator_code = OperatorCode::create(
+            &mut fb,
+            &OperatorCodeArgs {
+                deprecated_builtin_code: code.0.min(127) as i8,
+                custom_code: None,
+                version: 1,
+                builtin_code: code,
+            },
+        );
+        let subgraphs = fb.create_vector(&[subgraph]);
+        let buffers = fb.create_vector(&buffers);
+        let operator_codes = fb.create_vector(&[operator_code]);
+        let model = Model::create(
+            &mut fb,
+            &ModelArgs {
+                version: 3,
+                operator_codes: Some(operator_codes),
+                subgraphs: Some(subgraphs),
+                description: None,
+                buffers: Some(buffers),
+                metadata_buffer: None,
+                metadata: None,
+                signature_defs: None,
+            },
+        );
+        fb.finish(model, Some("TFL3"));
+
+        let model = crate::tflite().model_for_read(&mut fb.finished_data())?;
+        Ok((model, values))
+    }
+
+    pub fn run(model: TypedModel, inputs: TVec<TValue>) -> TractResult<TVec<Tensor>> {
+        let outputs = model.into_optimized()?.into_runnable()?.run(inputs)?;
+        Ok(outputs.into_iter().map(|t| t.into_tensor()).collect())
+    }
+
+    /// Load and run a single builtin model, checking the model written back from what was
+    /// loaded computes the same outputs.
+    pub fn run_builtin(
+        code: BuiltinOperator,
+        options_type: BuiltinOptions,
+        options: impl FnOnce(&mut FlatBufferBuilder<'static>) -> WIPOffset<UnionWIPOffset>,
+        inputs: Vec<Input>,
+        outputs: &[TypedFact],
+    ) -> TractResult<TVec<Tensor>> {
+        let (model, inputs) = load_builtin(code, options_type, options, inputs, outputs)?;
+        let mut buffer = vec![];
+        crate::tflite().write(&model, &mut buffer).context("Writing loaded model")?;
+        let reloaded = crate::tflite().model_for_read(&mut &*buffer)?;
+        let outputs = run(model, inputs.clone())?;
+        let cycled = run(reloaded, inputs)?;
+        ensure!(outputs.len() == cycled.len());
+        for (ix, (output, cycled)) in outputs.iter().zip(cycled.iter()).enumerate() {
+            output
+                .close_enough(cycled, Approximation::Close)
+                .with_context(|| format!("Written model disagrees on output #{ix}"))?;
+        }
+        Ok(outputs)
+    }
+}
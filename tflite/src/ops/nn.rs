@@ -15,6 +15,8 @@ use crate::ser::BuiltinOp;
 use crate::ser::SubgraphBuilder;
 use crate::tflite::ArgMaxOptions;
 use crate::tflite::ArgMaxOptionsArgs;
+use crate::tflite::ArgMinOptions;
+use crate::tflite::ArgMinOptionsArgs;
 use crate::tflite::BatchMatMulOptions;
 use crate::tflite::BatchMatMulOptionsArgs;
 use crate::tflite::BuiltinOptions;
@@ -44,6 +46,8 @@ pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tract(BuiltinOperator::REDUCE_MIN, |op| de_reduce(op, Reducer::Min));
     reg.reg_to_tract(BuiltinOperator::SUM, |op| de_reduce(op, Reducer::Sum));
     reg.reg_to_tract(BuiltinOperator::REDUCE_PROD, |op| de_reduce(op, Reducer::Prod));
+    reg.reg_to_tract(BuiltinOperator::ARG_MAX, |op| de_arg_min_max(op, Reducer::ArgMax(false)));
+    reg.reg_to_tract(BuiltinOperator::ARG_MIN, |op| de_arg_min_max(op, Reducer::ArgMin(false)));
 }
 
 fn de_batch_matmul(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
@@ -130,6 +134,27 @@ fn de_reduce(op: &mut DeserOp, reducer: Reducer) -> TractResult<TVec<OutletId>>
     Ok(wire)
 }
 
+fn de_arg_min_max(op: &mut DeserOp, reducer: Reducer) -> TractResult<TVec<OutletId>> {
+    let (input, axis) = args_2!(op.facts()?);
+    let output_type = if reducer == Reducer::ArgMax(false) {
+        builtin!(op, builtin_options_as_arg_max_options).output_type()
+    } else {
+        builtin!(op, builtin_options_as_arg_min_options).output_type()
+    };
+    let axis = axis.konst.as_ref().context("Dynamic axis is not supported")?;
+    let axis = axis.cast_to_scalar::<i64>()?;
+    let axis = if axis < 0 { axis + input.rank() as i64 } else { axis } as usize;
+    let p = &op.prefix;
+    let wire = op.ctx.target.wire_node(
+        format!("{p}.reduce"),
+        core::nn::Reduce::new(tvec!(axis), reducer),
+        &[op.inputs[0]],
+    )?;
+    let wire = op.ctx.target.wire_node(format!("{p}.rm_axis"), AxisOp::Rm(axis), &wire)?;
+    let dt: DatumType = output_type.try_into()?;
+    op.ctx.target.wire_node(format!("{p}.cast"), Cast { to: dt }, &wire)
+}
+
 fn de_reduce_mean(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let (input, axes) = args_2!(op.facts()?);
     let axes: TVec<usize> = axes
@@ -238,23 +263,39 @@ fn ser_reduce(
     let inputs = [builder.map_outlet(model, node.inputs[0])?, axes];
     let output = builder.map_outlets(model, [OutletId::from(node.id)])?;
     if matches!(op.reducer, Reducer::ArgMin(_) | Reducer::ArgMax(_)) {
+        ensure!(op.axes.len() == 1, "TFLite ARG_MIN and ARG_MAX only support one axis");
         let mut intermediate_shape = model.outlet_fact(node.inputs[0])?.shape.to_vec();
-        for axis in op.axes.iter().sorted().rev() {
-            intermediate_shape.remove(*axis);
-        }
-        let intermediate_fact = i32::fact(intermediate_shape);
+        intermediate_shape.remove(op.axes[0]);
+        let intermediate_fact = i64::fact(intermediate_shape);
         let intermediate_tensor =
             builder.write_fact(format!("{}.removed_axes", node.name), intermediate_fact)?;
-        let options = ArgMaxOptions::create(
-            builder.fb(),
-            &ArgMaxOptionsArgs { output_type: TensorType::INT64 },
-        );
-        builder.write_op_with_options(
-            &inputs,
-            &[intermediate_tensor],
-            BuiltinOp::new(56, 1, BuiltinOperator::ARG_MAX, BuiltinOptions::ArgMaxOptions),
-            options.as_union_value(),
-        )?;
+        match op.reducer {
+            Reducer::ArgMax(false) => {
+                let options = ArgMaxOptions::create(
+                    builder.fb(),
+                    &ArgMaxOptionsArgs { output_type: TensorType::INT64 },
+                );
+                builder.write_op_with_options(
+                    &inputs,
+                    &[intermediate_tensor],
+                    BuiltinOp::new(56, 1, BuiltinOperator::ARG_MAX, BuiltinOptions::ArgMaxOptions),
+                    options.as_union_value(),
+                )?;
+            }
+            Reducer::ArgMin(false) => {
+                let options = ArgMinOptions::create(
+                    builder.fb(),
+                    &ArgMinOptionsArgs { output_type: TensorType::INT64 },
+                );
+                builder.write_op_with_options(
+                    &inputs,
+                    &[intermediate_tensor],
+                    BuiltinOp::new(79, 1, BuiltinOperator::ARG_MIN, BuiltinOptions::ArgMinOptions),
+                    options.as_union_value(),
+                )?;
+            }
+            _ => bail!("TFLite has no support for arg reducers selecting last index"),
+        }
         let expand_dim_options = ExpandDimsOptions::create(builder.fb(), &ExpandDimsOptionsArgs {});
         builder.write_op_with_options(
             &[intermediate_tensor, axes],
@@ -0,0 +1,233 @@
+use tract_core::internal::*;
+use tract_core::ops::einsum::EinSum;
+use tract_core::ops::math::{add, max, min, mul, sub, tanh};
+use tract_core::ops::nn::sigmoid;
+use tract_core::ops::scan::{self, ScanInfo};
+
+use crate::registry::{DeserOp, Registry};
+use crate::tflite::{ActivationFunctionType, BuiltinOperator};
+
+pub fn register_all(reg: &mut Registry) {
+    reg.reg_to_tract(
+        BuiltinOperator::UNIDIRECTIONAL_SEQUENCE_LSTM,
+        de_unidirectional_sequence_lstm,
+    );
+}
+
+// Inputs, by position:
+//  0 input, 1-4 input weights (i, f, c, o), 5-8 recurrent weights (i, f, c, o),
+//  9-11 peepholes, 12-15 biases (i, f, c, o), 16-17 projection, 18-19 output and cell states,
+//  20-23 layer normalization coefficients. Input gate tensors are absent with CIFG.
+fn de_unidirectional_sequence_lstm(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_unidirectional_sequence_lstmoptions);
+    ensure!(
+        options.fused_activation_function() == ActivationFunctionType::TANH,
+        "Unsupported LSTM activation {:?}",
+        options.fused_activation_function()
+    );
+    let inputs = op.optional_inputs();
+    ensure!(inputs.len() >= 20, "UNIDIRECTIONAL_SEQUENCE_LSTM expects at least 20 inputs");
+    ensure!(
+        [9, 10, 11, 16, 17].into_iter().chain(20..inputs.len()).all(|ix| inputs[ix].is_none()),
+        "LSTM with peepholes, projection or layer normalization is not supported"
+    );
+    let outlet = |ix: usize| inputs[ix].with_context(|| format!("Missing LSTM input #{ix}"));
+    let konst = |ix: usize| -> TractResult<Arc<Tensor>> {
+        op.ctx.target.outlet_fact(outlet(ix)?)?.konst.clone().context("Expect constant weights")
+    };
+    let x_fact = op.ctx.target.outlet_fact(outlet(0)?)?.clone();
+    ensure!(x_fact.datum_type.is_float(), "Only float LSTM is supported");
+    let cifg = inputs[1].is_none();
+    let gates = if cifg { &["f", "c", "o"][..] } else { &["i", "f", "c", "o"][..] };
+    let params = gates
+        .iter()
+        .map(|gate| {
+            let ix = "ifco".find(gate).unwrap();
+            Ok((*gate, konst(1 + ix)?, konst(5 + ix)?, konst(12 + ix)?))
+        })
+        .collect::<TractResult<Vec<_>>>()?;
+    ensure!(
+        params.iter().all(|(_, w, r, _)| w.datum_type() == x_fact.datum_type
+            && r.datum_type() == x_fact.datum_type),
+        "Hybrid LSTM is not supported"
+    );
+    let n_cell = params[0].1.shape()[0];
+    let n_output = params[0].2.shape()[1];
+
+    let dt = x_fact.datum_type;
+    let time_axis = if options.time_major() { 0 } else { 1 };
+    let state_fact = |n: usize| {
+        let mut shape: TVec<TDim> = tvec!(x_fact.shape[1 - time_axis].clone(), n.to_dim());
+        shape.insert(time_axis, 1.to_dim());
+        dt.fact(shape)
+    };
+
+    let mut body = TypedModel::default();
+    let mut x_source_fact = x_fact.without_value();
+    x_source_fact.shape.set(time_axis, 1.to_dim());
+    let x = body.add_source("x", x_source_fact)?;
+    let h_prev = body.add_source("h", state_fact(n_output))?;
+    let c_prev = body.add_source("c", state_fact(n_cell))?;
+    let x = body.wire_node("x.rm_time", AxisOp::Rm(time_axis), &[x])?[0];
+    let h_prev = body.wire_node("h.rm_time", AxisOp::Rm(time_axis), &[h_prev])?[0];
+    let c_prev = body.wire_node("c.rm_time", AxisOp::Rm(time_axis), &[c_prev])?[0];
+
+    let mut activations = HashMap::new();
+    for (gate, w, r, b) in params {
+        let wire = wire_lstm_gate(&mut body, gate, x, h_prev, w, r, b)?;
+        let activation = if gate == "c" { tanh() } else { sigmoid() };
+        activations.insert(gate, body.wire_node(format!("{gate}.act"), activation, &[wire])?[0]);
+    }
+    let one =
+        body.add_const("one", tensor0(1f32).cast_to_dt(dt)?.into_owned().broadcast_into_rank(2)?)?;
+    let i = if cifg {
+        body.wire_node("i", sub(), &[one, activations["f"]])?[0]
+    } else {
+        activations["i"]
+    };
+    let fc = body.wire_node("fc", mul(), &[activations["f"], c_prev])?[0];
+    let ig = body.wire_node("ig", mul(), &[i, activations["c"]])?[0];
+    let mut c = body.wire_node("c_new", add(), &[fc, ig])?[0];
+    if options.cell_clip() > 0.0 {
+        let clip = |v: f32| tensor0(v).cast_to_dt(dt)?.into_owned().broadcast_into_rank(2);
+        let low = body.add_const("cell_clip.low", clip(-options.cell_clip())?)?;
+        let high = body.add_const("cell_clip.high", clip(options.cell_clip())?)?;
+        c = body.wire_node("cell_clip.max", max(), &[c, low])?[0];
+        c = body.wire_node("cell_clip.min", min(), &[c, high])?[0];
+    }
+    let c_act = body.wire_node("c_new.act", tanh(), &[c])?[0];
+    let h = body.wire_node("h_new", mul(), &[activations["o"], c_act])?[0];
+    let h = body.wire_node("h_new.add_time", AxisOp::Add(time_axis), &[h])?[0];
+    let c = body.wire_node("c_new.add_time", AxisOp::Add(time_axis), &[c])?[0];
+    body.set_output_outlets(&[h, c])?;
+
+    let info = ScanInfo { axis: time_axis, chunk: 1 };
+    let input_mapping =
+        vec![scan::InputMapping::Scan(info), scan::InputMapping::State, scan::InputMapping::State];
+    let output_mapping = vec![
+        scan::OutputMapping {
+            state: true,
+            full_dim_hint: None,
+            last_value_slot: None,
+            scan: Some((0, info)),
+        },
+        scan::OutputMapping { state: true, full_dim_hint: None, last_value_slot: None, scan: None },
+    ];
+    let scan = scan::Scan::new(body, input_mapping, output_mapping, 0)?;
+
+    let prefix = op.prefix;
+    let (x, h0, c0) = (outlet(0)?, outlet(18)?, outlet(19)?);
+    let h0 = op.ctx.target.wire_node(format!("{prefix}.h0"), AxisOp::Add(time_axis), &[h0])?;
+    let c0 = op.ctx.target.wire_node(format!("{prefix}.c0"), AxisOp::Add(time_axis), &[c0])?;
+    let wires = op.ctx.target.wire_node(prefix, scan, &[x, h0[0], c0[0]])?;
+    Ok(tvec!(wires[0]))
+}
+
+fn wire_lstm_gate(
+    body: &mut TypedModel,
+    gate: &str,
+    x: OutletId,
+    h: OutletId,
+    w: Arc<Tensor>,
+    r: Arc<Tensor>,
+    b: Arc<Tensor>,
+) -> TractResult<OutletId> {
+    let dt = w.datum_type();
+    let matmul = || -> TractResult<EinSum> {
+        Ok(EinSum { axes: "bi,ni->bn".parse()?, q_params: None, operating_dt: dt })
+    };
+    let w = body.add_const(format!("{gate}.w"), w)?;
+    let r = body.add_const(format!("{gate}.r"), r)?;
+    let b = body.add_const(format!("{gate}.b"), b.into_tensor().broadcast_into_rank(2)?)?;
+    let wx = body.wire_node(format!("{gate}.wx"), matmul()?, &[x, w])?[0];
+    let rh = body.wire_node(format!("{gate}.rh"), matmul()?, &[h, r])?[0];
+    let sum = body.wire_node(format!("{gate}.sum"), add(), &[wx, rh])?[0];
+    Ok(body.wire_node(format!("{gate}.biased"), add(), &[sum, b])?[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::test::{load_builtin, run, Input};
+    use crate::tflite::{
+        BuiltinOptions, UnidirectionalSequenceLSTMOptions, UnidirectionalSequenceLSTMOptionsArgs,
+    };
+
+    fn sigmoid(x: f32) -> f32 {
+        1. / (1. + (-x).exp())
+    }
+
+    // Single cell LSTM over a batch of one. Weights and biases are given for the i, f, c and o
+    // gates, the input gate being coupled to the forget gate if absent (CIFG).
+    fn lstm(xs: &[f32], weights: [Option<(f32, f32, f32)>; 4]) -> TractResult<()> {
+        let mut inputs = vec![Input::Source(tensor1(xs).into_shape(&[1, xs.len(), 1])?)];
+        for pick in [|(w, _, _)| w, |(_, r, _)| r] {
+            for gate in &weights {
+                inputs.push(match gate {
+                    Some(gate) => Input::Const(tensor2(&[[pick(*gate)]])),
+                    None => Input::Absent,
+                });
+            }
+        }
+        inputs.extend((0..3).map(|_| Input::Absent));
+        for gate in &weights {
+            inputs.push(match gate {
+                Some((_, _, b)) => Input::Const(tensor1(&[*b])),
+                None => Input::Absent,
+            });
+        }
+        inputs.extend((0..2).map(|_| Input::Absent));
+        inputs.push(Input::Variable(f32::fact([1, 1])));
+        inputs.push(Input::Variable(f32::fact([1, 1])));
+        inputs.extend((0..4).map(|_| Input::Absent));
+        let (model, inputs) = load_builtin(
+            BuiltinOperator::UNIDIRECTIONAL_SEQUENCE_LSTM,
+            BuiltinOptions::UnidirectionalSequenceLSTMOptions,
+            |fb| {
+                let args = UnidirectionalSequenceLSTMOptionsArgs {
+                    fused_activation_function: ActivationFunctionType::TANH,
+                    ..UnidirectionalSequenceLSTMOptionsArgs::default()
+                };
+                UnidirectionalSequenceLSTMOptions::create(fb, &args).as_union_value()
+            },
+            inputs,
+            &[f32::fact([1, xs.len(), 1])],
+        )?;
+        let outputs = run(model, inputs)?;
+
+        let gate = |(w, r, b): (f32, f32, f32), x: f32, h: f32| w * x + r * h + b;
+        let (mut h, mut c, mut expected) = (0f32, 0f32, vec![]);
+        for x in xs {
+            let f = sigmoid(gate(weights[1].unwrap(), *x, h));
+            let i = weights[0].map(|i| sigmoid(gate(i, *x, h))).unwrap_or(1. - f);
+            let g = gate(weights[2].unwrap(), *x, h).tanh();
+            let o = sigmoid(gate(weights[3].unwrap(), *x, h));
+            c = f * c + i * g;
+            h = o * c.tanh();
+            expected.push(h);
+        }
+        let expected = tensor1(&expected).into_shape(&[1, xs.len(), 1])?;
+        outputs[0].close_enough(&expected, Approximation::Close)
+    }
+
+    #[test]
+    fn unidirectional_sequence_lstm() -> TractResult<()> {
+        lstm(
+            &[1., -0.5, 2.],
+            [
+                Some((0.5, -0.3, 0.1)),
+                Some((-0.4, 0.6, 0.2)),
+                Some((0.8, 0.2, -0.1)),
+                Some((0.3, 0.7, 0.)),
+            ],
+        )
+    }
+
+    #[test]
+    fn unidirectional_sequence_lstm_cifg() -> TractResult<()> {
+        lstm(
+            &[1., -0.5, 2.],
+            [None, Some((-0.4, 0.6, 0.2)), Some((0.8, 0.2, -0.1)), Some((0.3, 0.7, 0.))],
+        )
+    }
+}
@@ -41,6 +41,17 @@ impl<'op> DeserOp<'op> {
             .map(|o| self.ctx.target.outlet_fact(*o).cloned())
             .collect::<TractResult<TVec<_>>>()
     }
+
+    /// Operator inputs by flatbuffer position, with `None` for omitted optional inputs.
+    pub fn optional_inputs(&self) -> TVec<Option<OutletId>> {
+        let mut wires = self.inputs.iter();
+        self.flat
+            .inputs()
+            .unwrap()
+            .iter()
+            .map(|t| if t < 0 { None } else { wires.next().copied() })
+            .collect()
+    }
 }
 
 impl Registry {
@@ -67,7 +78,7 @@ impl Registry {
         mapping: &mut HashMap<i32, OutletId>,
     ) -> TractResult<()> {
         let inputs: TVec<OutletId> =
-            flat_op.inputs().unwrap().iter().map(|o| mapping[&o]).collect();
+            flat_op.inputs().unwrap().iter().filter(|o| *o >= 0).map(|o| mapping[&o]).collect();
         let tensors = subgraph.tensors().unwrap();
         let prefix = tensors.get(flat_op.outputs().unwrap().get(0) as usize).name().unwrap();
         let opcode_index = flat_op.opcode_index();
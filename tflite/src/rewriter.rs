@@ -2,7 +2,7 @@ use tract_core::internal::*;
 use tract_core::ops::array::{Pad, PadMode};
 use tract_core::ops::binary::wire_with_rank_broadcast;
 use tract_core::ops::cnn::{rewrite_conv_with_n_axis, KernelFormat, MaxPool, PoolSpec, SumPool};
-use tract_core::ops::cnn::{Conv, PaddingSpec};
+use tract_core::ops::cnn::{Conv, Deconv, PaddingSpec};
 use tract_core::ops::einsum::BasicMatMul;
 use tract_core::ops::element_wise::ElementWiseOp;
 use tract_core::ops::math::Recip;
@@ -19,6 +19,10 @@ pub fn rewrite_for_tflite(model: &mut TypedModel) -> TractResult<()> {
         .with_rule_for("make_1d_2d", make_1d_2d)
         .with_rule_for("rewrite_conv_with_n_axis", rewrite_conv_with_n_axis)
         .with_rule_for("conv-nchw-to-nhwc", conv_nchw_to_nhwc)
+        .with_rule_for("deconv_kernel_in_ohwi", deconv_kernel_in_ohwi)
+        .with_rule_for("deconv_bias_as_vector", deconv_bias_as_vector)
+        .with_rule_for("deconv_make_1d_2d", deconv_make_1d_2d)
+        .with_rule_for("deconv-nchw-to-nhwc", deconv_nchw_to_nhwc)
         .with_rule_for("maxpool-nchw-to-nhwc", maxpool_nchw_to_nhwc)
         .with_rule_for("sumpool-nchw-to-nhwc", sumpool_nchw_to_nhwc)
         .with_rule_for("padding", padding)
@@ -74,35 +78,52 @@ fn kernel_in_ohwi(
     }
     let mut patch = TypedModelPatch::default();
     let mut wire = patch.taps(model, &node.inputs)?;
+    wire[1] = wire_kernel_as_ohwi(
+        &mut patch,
+        name,
+        wire[1],
+        conv.kernel_fmt,
+        conv.group,
+        conv.input_channels(),
+        conv.pool_spec.kernel_shape.len(),
+    )?;
+    let new = Conv { kernel_fmt: KernelFormat::OHWI, ..conv.clone() };
+    wire = patch.wire_node(name, new, &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn wire_kernel_as_ohwi(
+    patch: &mut TypedModelPatch,
+    name: &str,
+    mut kernel: OutletId,
+    kernel_fmt: KernelFormat,
+    group: usize,
+    ci: usize,
+    geo_rank: usize,
+) -> TractResult<OutletId> {
     let prefix = format!("{name}.kernel_reorg");
-    for (ix, op) in conv
-        .kernel_fmt
-        .kernel_as_group_o_i_h_w_ops(&patch.outlet_fact(wire[1])?.shape, conv.group)
+    for (ix, op) in kernel_fmt
+        .kernel_as_group_o_i_h_w_ops(&patch.outlet_fact(kernel)?.shape, group)
         .into_iter()
         .enumerate()
     {
-        wire[1] = patch.wire_node(format!("{prefix}.{ix}"), op, &[wire[1]])?[0];
+        kernel = patch.wire_node(format!("{prefix}.{ix}"), op, &[kernel])?[0];
     }
-    let geo_rank = conv.pool_spec.kernel_shape.len();
     // group_o_i_h_w -> o_h_w_gi
-    let ci = conv.input_channels();
-    wire[1] =
-        patch.wire_node(format!("{prefix}.mv_g"), AxisOp::Move(0, geo_rank + 2), &[wire[1]])?[0];
-    wire[1] =
-        patch.wire_node(format!("{prefix}.mv_i"), AxisOp::Move(1, geo_rank + 2), &[wire[1]])?[0];
-    wire[1] = patch.wire_node(
+    kernel =
+        patch.wire_node(format!("{prefix}.mv_g"), AxisOp::Move(0, geo_rank + 2), &[kernel])?[0];
+    kernel =
+        patch.wire_node(format!("{prefix}.mv_i"), AxisOp::Move(1, geo_rank + 2), &[kernel])?[0];
+    Ok(patch.wire_node(
         format!("{prefix}.gi"),
         AxisOp::Reshape(
             geo_rank + 1,
-            tvec!(conv.group.to_dim(), (ci / conv.group).to_dim()),
+            tvec!(group.to_dim(), (ci / group).to_dim()),
             tvec!(ci.to_dim()),
         ),
-        &[wire[1]],
-    )?[0];
-    let new = Conv { kernel_fmt: KernelFormat::OHWI, ..conv.clone() };
-    wire = patch.wire_node(name, new, &wire)?;
-    patch.shunt_outside(model, node.id.into(), wire[0])?;
-    Ok(Some(patch))
+        &[kernel],
+    )?[0])
 }
 
 fn bias_as_vector(
@@ -253,6 +274,94 @@ fn nchw_to_nhwc(
     Ok(None)
 }
 
+fn deconv_kernel_in_ohwi(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    if deconv.kernel_format == KernelFormat::OHWI {
+        return Ok(None);
+    }
+    if deconv.group != 1 {
+        bail!("Grouped transposed convolution is not supported in tflite")
+    }
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[1] = wire_kernel_as_ohwi(
+        &mut patch,
+        name,
+        wire[1],
+        deconv.kernel_format,
+        1,
+        deconv.pool_spec.input_channels,
+        deconv.pool_spec.kernel_shape.len(),
+    )?;
+    let new = Deconv { kernel_format: KernelFormat::OHWI, ..deconv.clone() };
+    wire = patch.wire_node(name, new, &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn deconv_bias_as_vector(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    let bias_fact = model.outlet_fact(node.inputs[2])?;
+    let co = deconv.pool_spec.output_channels;
+    if *bias_fact.shape == [co.to_dim()] {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[2] = tract_core::ops::cnn::wire_reshape_bias_as_vector(&mut patch, name, wire[2], co)?[0];
+    wire = patch.wire_node(name, deconv.clone(), &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn deconv_make_1d_2d(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    if deconv.pool_spec.rank() == 1 {
+        let mut new = deconv.clone();
+        new.pool_spec = deconv.pool_spec.change_geo_axes(&AxisOp::Add(1))?;
+        new.adjustments.insert(1, 0);
+        let mut patch = TypedModelPatch::default();
+        let mut wire = patch.taps(model, &node.inputs)?;
+        let pos_data = deconv.pool_spec.data_format.h_axis() + 1;
+        wire[0] = patch.wire_node(format!("{name}.add_dim"), AxisOp::Add(pos_data), &[wire[0]])?[0];
+        let pos_kernel = deconv.kernel_format.h_axis() + 1;
+        wire[1] =
+            patch.wire_node(format!("{name}.add_dim_k"), AxisOp::Add(pos_kernel), &[wire[1]])?[0];
+        wire = patch.wire_node(name, new, &wire)?;
+        wire = patch.wire_node(format!("{name}.rm_dim"), AxisOp::Rm(pos_data), &wire)?;
+        patch.shunt_outside(model, node.id.into(), wire[0])?;
+        return Ok(Some(patch));
+    }
+    Ok(None)
+}
+
+fn deconv_nchw_to_nhwc(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    op: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    nchw_to_nhwc(_ctx, model, node, name, &op.pool_spec, &|pool_spec| {
+        Box::new(Deconv { pool_spec, ..op.clone() })
+    })
+}
+
 fn padding(
     _ctx: &(),
     model: &TypedModel,
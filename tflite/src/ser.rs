@@ -128,6 +128,10 @@ impl<'f, 'b, 'mb> SubgraphBuilder<'f, 'b, 'mb> {
         fact: impl Into<TypedFact>,
     ) -> TractResult<i32> {
         let fact = fact.into();
+        if let Some(q) = fact.per_axis_q.clone() {
+            let zp = q.zero_points.iter().map(|z| *z as i64).collect_vec();
+            return self.write_fact_with_per_axis_q(name, fact, &zp, &q.scales, q.axis);
+        }
         if fact.datum_type.unquantized() == i8::datum_type()
             || fact.datum_type.unquantized() == u8::datum_type()
             || fact.datum_type.qparams().is_some()
@@ -90,14 +90,17 @@ pub fn flat_tensor_uses_per_axis_q<'m>(graph: &'m SubGraph<'m>, id: i32) -> bool
     false
 }
 
-pub fn per_axis_q_params<'m>(
-    graph: &'m SubGraph<'m>,
-    id: i32,
-) -> TractResult<(Vec<i32>, Vec<f32>)> {
+pub fn per_axis_q_params<'m>(graph: &'m SubGraph<'m>, id: i32) -> TractResult<PerAxisQParams> {
     let flat = graph.tensors().unwrap().get(id as _);
     let Some(qp) = flat.quantization() else { bail!("Unquantized value") };
     let (Some(scale), Some(zp)) = (qp.scale(), qp.zero_point()) else { bail!("No ZP/scale found") };
-    Ok((zp.iter().map(|i| i as i32).collect_vec(), scale.iter().collect_vec()))
+    let mut zp = zp.iter().map(|i| i as i32).collect_vec();
+    let scale = scale.iter().collect_vec();
+    // tflite allows a single zero point for all channels
+    if zp.len() == 1 && scale.len() > 1 {
+        zp = vec![zp[0]; scale.len()];
+    }
+    PerAxisQParams::new(qp.quantized_dimension() as usize, zp, scale)
 }
 
 pub fn flat_tensor_to_tract_fact<'m>(
@@ -128,12 +131,8 @@ pub fn flat_tensor_to_tract_fact<'m>(
             fact = data.into();
         }
     }
+    if flat_tensor_uses_per_axis_q(graph, id) {
+        fact.per_axis_q = Some(Arc::new(per_axis_q_params(graph, id)?));
+    }
     Ok((fact, flat.name().unwrap()))
 }
-
-#[derive(Clone, Debug)]
-pub struct PerAxisQ {
-    axis: usize,
-    zp: Vec<i32>,
-    scale: Vec<f32>,
-}